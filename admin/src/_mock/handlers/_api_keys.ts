@@ -18,7 +18,20 @@ function requireAuth(headers: Headers): HttpResponse | null {
 const listApiKeys = http.get("/api/admin/api-keys", async ({ request }) => {
   const unauthorized = requireAuth(request.headers);
   if (unauthorized) return unauthorized;
-  return HttpResponse.json(STORE, { status: 200 });
+  const url = new URL(request.url);
+  // 游标与后端一致：base64url("page:per_page")
+  const cursor = url.searchParams.get("cursor");
+  const [page, perPage] = cursor
+    ? atob(cursor.replace(/-/g, "+").replace(/_/g, "/")).split(":").map(Number)
+    : [Number(url.searchParams.get("page") || 1), Number(url.searchParams.get("per_page") || 20)];
+  const items = STORE.slice((page - 1) * perPage, page * perPage);
+  const totalPages = Math.ceil(STORE.length / perPage);
+  const hasMore = page < totalPages;
+  const nextCursor = hasMore ? btoa(`${page + 1}:${perPage}`).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "") : null;
+  return HttpResponse.json(
+    { items, page, per_page: perPage, total: STORE.length, total_pages: totalPages, has_more: hasMore, next_cursor: nextCursor },
+    { status: 200 },
+  );
 });
 
 const createApiKey = http.post("/api/admin/api-keys", async ({ request }) => {
@@ -0,0 +1,17 @@
+import apiClient from "@/api/apiClient";
+import type { Page } from "#/api";
+
+// 后端单页上限（pagination.max_per_page 默认值）
+const PER_PAGE = 100;
+
+/** 沿 next_cursor 取完所有分页，供需要整表做前端搜索/分页的页面使用 */
+export async function fetchAllPages<T>(url: string, params: Record<string, unknown> = {}): Promise<T[]> {
+  const items: T[] = [];
+  let cursor: string | null | undefined;
+  do {
+    const page = await apiClient.get<Page<T>>({ url, params: cursor ? { ...params, cursor } : { ...params, per_page: PER_PAGE } });
+    items.push(...page.items);
+    cursor = page.has_more ? page.next_cursor : undefined;
+  } while (cursor);
+  return items;
+}
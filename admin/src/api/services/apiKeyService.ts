@@ -1,4 +1,5 @@
 import apiClient from "../apiClient";
+import { fetchAllPages } from "../pagination";
 
 export type ApiKeyRecord = {
   user: string;
@@ -16,7 +17,7 @@ export const ApiKeyApi = {
 };
 
 export function listApiKeys(): Promise<ApiKeyRecord[]> {
-  return apiClient.get<ApiKeyRecord[]>({ url: ApiKeyApi.List });
+  return fetchAllPages<ApiKeyRecord>(ApiKeyApi.List);
 }
 
 export function createApiKey(record: ApiKeyRecord): Promise<{ success: boolean } | any> {
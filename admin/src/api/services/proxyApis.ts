@@ -1,4 +1,5 @@
 import apiClient from "@/api/apiClient";
+import { fetchAllPages } from "@/api/pagination";
 
 export type ProxyApiModel = {
   id: string;
@@ -33,8 +34,7 @@ const base = "admin/proxy-apis";
 
 export default {
   async listProxyApis(tenant_id?: string): Promise<ProxyApiModel[]> {
-    const url = tenant_id ? `${base}?tenant_id=${tenant_id}` : base;
-    return apiClient.get({ url });
+    return fetchAllPages<ProxyApiModel>(base, tenant_id ? { tenant_id } : {});
   },
   async createProxyApi(payload: CreateProxyApiInput): Promise<ProxyApiModel> {
     return apiClient.post({ url: base, data: payload });
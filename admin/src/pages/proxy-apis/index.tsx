@@ -63,7 +63,7 @@ export default function ProxyApisPage() {
   });
 
   const form = useForm<CreateProxyApiInput>({
-    defaultValues: { tenant_id: "", endpoint_url: "/proxy/example", method: "GET", forward_target: "https://jsonplaceholder.typicode.com/posts", require_api_key: false },
+    defaultValues: { tenant_id: "", endpoint_url: "/proxy/example", method: "GET", forward_target: "http://127.0.0.1:9099/posts", require_api_key: false },
   });
 
   const editForm = useForm<{ endpoint_url: string; method: string; forward_target: string; require_api_key: boolean; enabled: boolean }>({
@@ -177,7 +177,7 @@ export default function ProxyApisPage() {
                 <FormField name="forward_target" control={form.control} rules={{ required: "必填" }} render={({ field }) => (
                   <FormItem>
                     <FormLabel>目标地址</FormLabel>
-                    <FormControl><Input placeholder="https://jsonplaceholder.typicode.com/posts" {...field} /></FormControl>
+                    <FormControl><Input placeholder="http://127.0.0.1:9099/posts" {...field} /></FormControl>
                     <FormMessage />
                   </FormItem>
                 )} />
@@ -301,7 +301,7 @@ export default function ProxyApisPage() {
             <FormField name="forward_target" control={editForm.control} rules={{ required: "必填" }} render={({ field }) => (
               <FormItem>
                 <FormLabel>目标地址</FormLabel>
-                <FormControl><Input placeholder="https://jsonplaceholder.typicode.com/posts" {...field} /></FormControl>
+                <FormControl><Input placeholder="http://127.0.0.1:9099/posts" {...field} /></FormControl>
                 <FormMessage />
               </FormItem>
             )} />
@@ -5,3 +5,14 @@ export interface Result<T = unknown> {
 	message: string;
 	data: T;
 }
+
+/** 后端分页列表（common::pagination::Page） */
+export interface Page<T> {
+	items: T[];
+	page: number;
+	per_page: number;
+	total: number;
+	total_pages: number;
+	has_more: boolean;
+	next_cursor?: string | null;
+}
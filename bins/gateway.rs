@@ -1,14 +1,25 @@
 use dotenvy::dotenv;
-use gateway::bootstrap;
+use gateway::bootstrap::{GatewayBuilder, DEFAULT_ADMIN_ADDR};
 use tracing::{ error, info};
-use common::utils::logging::init_logging_json;
+use gateway::config::ProxyConfig;
 use uuid::Uuid;
 
+// 堆剖析（/debug/pprof/heap）需要开启 profiling 的 jemalloc 作为全局分配器
+#[cfg(feature = "heap-profiling")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "heap-profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "_rjem_malloc_conf"]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
+
 fn init_logging() {
     // 加载 .env（允许使用 RUST_LOG 配置日志级别）
     dotenv().ok();
-    // 使用统一的JSON结构化日志初始化
-    init_logging_json();
+    // 按 config.json 的 logging 段初始化（默认 JSON 结构化日志）；无法加载时使用默认值
+    let logging = ProxyConfig::load_resolved("config.json").map(|r| r.config.logging).unwrap_or_else(|_| ProxyConfig::default().logging);
+    common::utils::logging::init_logging(&logging);
     info!(service = "gateway", event = "logger_init", "tracing subscriber initialized");
 }
 
@@ -53,8 +64,13 @@ fn main() {
         "delegating startup to gateway::bootstrap"
     );
 
-    // 仅作为启动入口，将执行流程委托给 crates/gateway
-    bootstrap::run();
+    // 仅作为启动入口：监听端口、后台服务与 admin server 均由 gateway::bootstrap 组装
+    // GATEWAY_ADMIN_ADDR 覆盖 admin 地址（同机运行多个实例，如 gateway::testing 启动的测试网关）
+    let admin_addr = std::env::var("GATEWAY_ADMIN_ADDR").unwrap_or_else(|_| DEFAULT_ADMIN_ADDR.to_string());
+    if let Err(e) = GatewayBuilder::new().config_path("config.json").admin_addr(admin_addr).run() {
+        error!(service = "gateway", event = "bootstrap_failed", %service_id, pid, error = %format!("{:#}", e), "gateway failed to start");
+        std::process::exit(1);
+    }
 
     // 服务停止事件（当 run 返回时记录；正常情况为永不返回）
     info!(
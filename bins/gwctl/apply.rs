@@ -0,0 +1,168 @@
+//! `gwctl proxy-api apply -f file.yaml`：按 (method, endpoint_url) 对比现有配置，创建缺失项、更新有差异的项。
+//!
+//! ```yaml
+//! tenant_id: 6f1c...   # 省略时使用 profile 的 tenant_id
+//! apis:
+//!   - endpoint_url: /api/orders
+//!     method: GET
+//!     forward_target: http://orders:8080
+//!     require_api_key: true
+//! ```
+
+use client::types::{CreateProxyApi, ProxyApi, UpdateProxyApi};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyFile {
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
+    pub apis: Vec<ApiSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiSpec {
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    #[serde(default)]
+    pub require_api_key: bool,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+#[derive(Debug)]
+pub enum Change {
+    Create(ApiSpec),
+    Update { id: Uuid, spec: ApiSpec, diff: UpdateProxyApi },
+    Unchanged(Uuid),
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Create(s) => format!("create     {} {} -> {}", s.method, s.endpoint_url, s.forward_target),
+            Change::Update { spec, .. } => format!("update     {} {} -> {}", spec.method, spec.endpoint_url, spec.forward_target),
+            Change::Unchanged(id) => format!("unchanged  {}", id),
+        }
+    }
+}
+
+pub fn parse(text: &str) -> anyhow::Result<ApplyFile> {
+    let mut file: ApplyFile = serde_yaml::from_str(text)?;
+    for api in &mut file.apis {
+        api.method = api.method.to_ascii_uppercase();
+        if !api.endpoint_url.starts_with('/') {
+            anyhow::bail!("endpoint_url must start with '/': {}", api.endpoint_url);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    for api in &file.apis {
+        if !seen.insert((api.method.as_str(), api.endpoint_url.as_str())) {
+            anyhow::bail!("duplicate entry {} {}", api.method, api.endpoint_url);
+        }
+    }
+    Ok(file)
+}
+
+/// 只比较文件中声明的字段；文件里没有的现有 API 不受影响
+pub fn plan(existing: &[ProxyApi], desired: &[ApiSpec]) -> Vec<Change> {
+    desired
+        .iter()
+        .map(|spec| {
+            let current = existing.iter().find(|a| a.method.eq_ignore_ascii_case(&spec.method) && a.endpoint_url == spec.endpoint_url);
+            let Some(cur) = current else { return Change::Create(spec.clone()) };
+            let diff = UpdateProxyApi {
+                forward_target: (cur.forward_target != spec.forward_target).then(|| spec.forward_target.clone()),
+                require_api_key: (cur.require_api_key != spec.require_api_key).then_some(spec.require_api_key),
+                enabled: (cur.enabled != spec.enabled).then_some(spec.enabled),
+                ..Default::default()
+            };
+            if diff.forward_target.is_none() && diff.require_api_key.is_none() && diff.enabled.is_none() {
+                Change::Unchanged(cur.id)
+            } else {
+                Change::Update { id: cur.id, spec: spec.clone(), diff }
+            }
+        })
+        .collect()
+}
+
+pub async fn execute(admin: &client::AdminClient, tenant_id: Uuid, changes: &[Change]) -> Result<(), client::ClientError> {
+    for change in changes {
+        match change {
+            Change::Create(spec) => {
+                let input = CreateProxyApi {
+                    tenant_id: Some(tenant_id.to_string()),
+                    endpoint_url: spec.endpoint_url.clone(),
+                    method: spec.method.clone(),
+                    forward_target: spec.forward_target.clone(),
+                    require_api_key: spec.require_api_key,
+                };
+                let created = admin.create_proxy_api(&input).await?;
+                // 创建接口不接受 enabled，默认启用
+                if !spec.enabled {
+                    admin.update_proxy_api(created.id, &UpdateProxyApi { enabled: Some(false), ..Default::default() }).await?;
+                }
+            }
+            Change::Update { id, diff, .. } => {
+                admin.update_proxy_api(*id, diff).await?;
+            }
+            Change::Unchanged(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing(method: &str, url: &str, target: &str) -> ProxyApi {
+        let now = chrono::Utc::now().fixed_offset();
+        ProxyApi {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            endpoint_url: url.into(),
+            method: method.into(),
+            forward_target: target.into(),
+            require_api_key: true,
+            enabled: true,
+            created_at: now,
+            updated_at: now,
+            mock_enabled: false,
+            mock_status: None,
+            mock_headers: None,
+            mock_body: None,
+            maintenance_start: None,
+            maintenance_end: None,
+            maintenance_message: None,
+            grpc_method: None,
+        }
+    }
+
+    #[test]
+    fn plans_creates_updates_and_noops() {
+        let file = parse(
+            "apis:\n  - { endpoint_url: /api/a, method: get, forward_target: http://a:80, require_api_key: true }\n  - { endpoint_url: /api/b, method: POST, forward_target: http://b2:80, require_api_key: true }\n  - { endpoint_url: /api/c, method: GET, forward_target: http://c:80 }\n",
+        )
+        .unwrap();
+        let current = vec![existing("GET", "/api/a", "http://a:80"), existing("POST", "/api/b", "http://b:80"), existing("GET", "/api/other", "http://o:80")];
+        let changes = plan(&current, &file.apis);
+        assert!(matches!(changes[0], Change::Unchanged(id) if id == current[0].id));
+        assert!(matches!(&changes[1], Change::Update { diff, .. } if diff.forward_target.as_deref() == Some("http://b2:80") && diff.enabled.is_none()));
+        assert!(matches!(&changes[2], Change::Create(s) if !s.require_api_key && s.enabled));
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(parse("apis:\n  - { endpoint_url: api/a, method: GET, forward_target: http://a }\n").is_err());
+        assert!(parse("apis:\n  - { endpoint_url: /a, method: GET, forward_target: http://a }\n  - { endpoint_url: /a, method: get, forward_target: http://b }\n").is_err());
+        assert!(parse("apis: []\nextra: 1\n").is_err());
+    }
+}
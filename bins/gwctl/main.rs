@@ -0,0 +1,339 @@
+//! gwctl：控制面命令行工具，通过 admin API 管理租户、proxy API、路由、API key，查看请求日志与统计。
+//! `gwctl login` 把令牌保存到当前 profile，之后的命令自动使用；`-o json` 输出原始 JSON。
+
+mod apply;
+mod output;
+mod profile;
+
+use std::io::BufRead;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Parser, Subcommand};
+use client::types::{ApiKeyRecord, ListRequestLogs, PageQuery, RequestLog};
+use client::AdminClient;
+use uuid::Uuid;
+
+use output::{opt, Format};
+use profile::{Profile, ProfileFile};
+
+#[derive(Parser)]
+#[command(name = "gwctl", version, about = "API proxy gateway admin CLI")]
+struct Cli {
+    /// 使用的 profile（默认为配置文件中的 current）
+    #[arg(long, global = true, env = "GWCTL_PROFILE")]
+    profile: Option<String>,
+    /// 覆盖 profile 中的控制面地址
+    #[arg(long, global = true, env = "GWCTL_SERVER")]
+    server: Option<String>,
+    /// 输出格式
+    #[arg(short, long, global = true, value_enum, default_value = "table")]
+    output: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 登录并把令牌保存到 profile
+    Login(LoginArgs),
+    /// 管理 profile
+    #[command(subcommand)]
+    Profile(ProfileCmd),
+    /// 租户列表与创建
+    #[command(subcommand)]
+    Tenant(TenantCmd),
+    /// Proxy API 列表与声明式 apply
+    #[command(subcommand, name = "proxy-api")]
+    ProxyApi(ProxyApiCmd),
+    /// 路由列表
+    #[command(subcommand)]
+    Route(RouteCmd),
+    /// 用户 API key
+    #[command(subcommand)]
+    Key(KeyCmd),
+    /// 请求日志
+    #[command(subcommand)]
+    Logs(LogsCmd),
+    /// 管理台概览（请求量、错误率、热门路由等）
+    Stats {
+        #[arg(long)]
+        top: Option<u32>,
+    },
+}
+
+#[derive(Args)]
+struct LoginArgs {
+    #[arg(long)]
+    tenant_id: Option<Uuid>,
+    #[arg(long)]
+    email: Option<String>,
+    /// 省略时从 GWCTL_PASSWORD 或标准输入读取
+    #[arg(long, env = "GWCTL_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ProfileCmd {
+    List,
+    /// 设为默认 profile
+    Use { name: String },
+}
+
+#[derive(Subcommand)]
+enum TenantCmd {
+    List,
+    Create { name: String },
+}
+
+#[derive(Subcommand)]
+enum ProxyApiCmd {
+    List {
+        #[arg(long)]
+        tenant_id: Option<Uuid>,
+    },
+    /// 按 YAML 文件创建或更新 proxy API
+    Apply {
+        #[arg(short, long)]
+        file: std::path::PathBuf,
+        /// 只打印变更计划
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RouteCmd {
+    List {
+        #[arg(long)]
+        tenant_id: Option<Uuid>,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCmd {
+    List,
+    /// 为用户设置 API key；省略 --key 时随机生成
+    Create {
+        #[arg(long)]
+        user: String,
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LogsCmd {
+    /// 最近的请求日志；--follow 持续拉取新记录
+    Tail {
+        #[arg(long)]
+        tenant_id: Option<Uuid>,
+        #[arg(long)]
+        route_id: Option<Uuid>,
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: u64,
+        #[arg(short, long)]
+        follow: bool,
+        /// --follow 时的轮询间隔（秒）
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+}
+
+struct Ctx {
+    format: Format,
+    profile_name: String,
+    profile: Profile,
+    admin: AdminClient,
+}
+
+impl Ctx {
+    fn tenant(&self, explicit: Option<Uuid>) -> anyhow::Result<Uuid> {
+        explicit.or(self.profile.tenant_id).ok_or_else(|| anyhow!("--tenant-id is required (or set tenant_id in profile '{}')", self.profile_name))
+    }
+}
+
+fn connect(cli: &Cli, file: &ProfileFile) -> anyhow::Result<Ctx> {
+    let profile_name = file.resolve_name(cli.profile.as_deref());
+    let mut profile = file.profiles.get(&profile_name).cloned().unwrap_or_default();
+    if let Some(server) = &cli.server {
+        profile.server = server.clone();
+    }
+    if profile.server.is_empty() {
+        bail!("no server configured for profile '{}'; run `gwctl --server <url> login`", profile_name);
+    }
+    let mut builder = AdminClient::builder(profile.server.clone()).with_user_agent(concat!("gwctl/", env!("CARGO_PKG_VERSION")));
+    if let Some(token) = &profile.token {
+        builder = builder.with_token(token.clone());
+    }
+    Ok(Ctx { format: cli.output, profile_name, admin: builder.build()?, profile })
+}
+
+fn read_password() -> anyhow::Result<String> {
+    eprint!("password: ");
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn random_key() -> String {
+    format!("gw_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn log_row(l: &RequestLog) -> Vec<String> {
+    vec![
+        l.id.to_string(),
+        l.timestamp.to_rfc3339(),
+        l.route_id.to_string(),
+        l.status_code.to_string(),
+        format!("{}ms", l.latency_ms),
+        opt(&l.client_ip),
+        opt(&l.error_message),
+    ]
+}
+
+const LOG_HEADERS: [&str; 7] = ["ID", "TIME", "ROUTE", "STATUS", "LATENCY", "CLIENT", "ERROR"];
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    let mut file = ProfileFile::load()?;
+    if let Command::Profile(cmd) = &cli.command {
+        return match cmd {
+            ProfileCmd::List => {
+                let current = file.resolve_name(None);
+                let rows: Vec<Vec<String>> = file
+                    .profiles
+                    .iter()
+                    .map(|(name, p)| vec![if *name == current { "*".into() } else { String::new() }, name.clone(), p.server.clone(), opt(&p.tenant_id), opt(&p.email)])
+                    .collect();
+                println!("{}", output::render_table(&["", "NAME", "SERVER", "TENANT", "EMAIL"], &rows));
+                Ok(())
+            }
+            ProfileCmd::Use { name } => {
+                if !file.profiles.contains_key(name) {
+                    bail!("unknown profile '{}'", name);
+                }
+                file.current = Some(name.clone());
+                file.save()
+            }
+        };
+    }
+
+    let ctx = connect(&cli, &file)?;
+    let admin = &ctx.admin;
+    match cli.command {
+        Command::Profile(_) => unreachable!("handled above"),
+        Command::Login(args) => {
+            let tenant_id = ctx.tenant(args.tenant_id)?;
+            let email = args.email.or(ctx.profile.email.clone()).ok_or_else(|| anyhow!("--email is required"))?;
+            let password = match args.password {
+                Some(p) => p,
+                None => read_password()?,
+            };
+            let out = admin.login(tenant_id, &email, &password).await?;
+            let profile = Profile { server: ctx.profile.server.clone(), tenant_id: Some(tenant_id), email: Some(email), token: Some(out.token) };
+            file.profiles.insert(ctx.profile_name.clone(), profile);
+            if file.current.is_none() {
+                file.current = Some(ctx.profile_name.clone());
+            }
+            file.save()?;
+            eprintln!("logged in as {} (profile '{}')", out.email, ctx.profile_name);
+        }
+        Command::Tenant(TenantCmd::List) => {
+            let page = admin.list_tenants(&PageQuery { per_page: Some(100), ..Default::default() }).await?;
+            output::print(ctx.format, &page.items, &["ID", "NAME", "SUSPENDED", "CREATED"], || {
+                page.items.iter().map(|t| vec![t.id.to_string(), t.name.clone(), t.suspended.to_string(), t.created_at.to_rfc3339()]).collect()
+            })?;
+        }
+        Command::Tenant(TenantCmd::Create { name }) => {
+            let t = admin.create_tenant(&name).await?;
+            output::print(ctx.format, &t, &["ID", "NAME"], || vec![vec![t.id.to_string(), t.name.clone()]])?;
+        }
+        Command::ProxyApi(ProxyApiCmd::List { tenant_id }) => {
+            let apis = admin.list_all_proxy_apis(tenant_id.or(ctx.profile.tenant_id)).await?;
+            output::print(ctx.format, &apis, &["ID", "METHOD", "ENDPOINT", "TARGET", "KEY", "ENABLED"], || {
+                apis.iter()
+                    .map(|a| vec![a.id.to_string(), a.method.clone(), a.endpoint_url.clone(), a.forward_target.clone(), a.require_api_key.to_string(), a.enabled.to_string()])
+                    .collect()
+            })?;
+        }
+        Command::ProxyApi(ProxyApiCmd::Apply { file: path, dry_run }) => {
+            let text = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+            let spec = apply::parse(&text).with_context(|| format!("parse {}", path.display()))?;
+            let tenant_id = ctx.tenant(spec.tenant_id)?;
+            let existing = admin.list_all_proxy_apis(Some(tenant_id)).await?;
+            let changes = apply::plan(&existing, &spec.apis);
+            for c in &changes {
+                println!("{}", c.describe());
+            }
+            if !dry_run {
+                apply::execute(admin, tenant_id, &changes).await?;
+            }
+        }
+        Command::Route(RouteCmd::List { tenant_id }) => {
+            let page = admin.list_routes(ctx.tenant(tenant_id)?, &PageQuery { per_page: Some(100), ..Default::default() }).await?;
+            output::print(ctx.format, &page.items, &["ID", "METHOD", "PATH", "UPSTREAM", "TIMEOUT", "COLOR"], || {
+                page.items
+                    .iter()
+                    .map(|r| vec![r.id.to_string(), r.method.clone(), r.path.clone(), r.upstream_id.to_string(), format!("{}ms", r.timeout_ms), opt(&r.active_color.map(|c| format!("{:?}", c).to_lowercase()))])
+                    .collect()
+            })?;
+        }
+        Command::Key(KeyCmd::List) => {
+            let keys = admin.list_api_keys().await?;
+            output::print(ctx.format, &keys, &["USER", "API_KEY"], || keys.iter().map(|k| vec![k.user.clone(), k.api_key.clone()]).collect())?;
+        }
+        Command::Key(KeyCmd::Create { user, key }) => {
+            let record = ApiKeyRecord { user, api_key: key.unwrap_or_else(random_key) };
+            admin.set_api_key(&record).await?;
+            output::print(ctx.format, &record, &["USER", "API_KEY"], || vec![vec![record.user.clone(), record.api_key.clone()]])?;
+        }
+        Command::Logs(LogsCmd::Tail { tenant_id, route_id, lines, follow, interval }) => {
+            let mut query = ListRequestLogs { tenant_id: tenant_id.or(ctx.profile.tenant_id), route_id, after_id: None, limit: Some(lines) };
+            let mut header = ctx.format == Format::Table;
+            loop {
+                let logs = admin.request_logs(&query).await?;
+                match ctx.format {
+                    // 每行一个 JSON 对象，便于管道处理
+                    Format::Json => logs.iter().try_for_each(|l| serde_json::to_string(l).map(|s| println!("{}", s)))?,
+                    Format::Table if !logs.is_empty() => {
+                        let table = output::render_table(&LOG_HEADERS, &logs.iter().map(log_row).collect::<Vec<_>>());
+                        let body = if header { table.as_str() } else { table.split_once('\n').map(|(_, rest)| rest).unwrap_or("") };
+                        println!("{}", body);
+                        header = false;
+                    }
+                    Format::Table => {}
+                }
+                if !follow {
+                    break;
+                }
+                query.after_id = logs.last().map(|l| l.id).or(query.after_id).or(Some(0));
+                query.limit = Some(1000);
+                tokio::time::sleep(Duration::from_secs(interval.max(1))).await;
+            }
+        }
+        Command::Stats { top } => {
+            let stats = admin.stats(top).await?;
+            // 统计结构较深，table 模式也输出缩进 JSON
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+    }
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    let cli = Cli::parse();
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("failed to build tokio runtime: {e}");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+    match rt.block_on(run(cli)) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! 输出格式：table（默认，对齐的纯文本列）或 json（原样序列化，便于 jq 处理）
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+/// 列宽按最长单元格对齐，列间两个空格
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(c, w)| format!("{:<width$}", c, width = w)).collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    let mut out = vec![line(headers.to_vec())];
+    out.extend(rows.iter().map(|r| line(r.iter().map(String::as_str).collect())));
+    out.join("\n")
+}
+
+/// json 格式打印 value；table 格式按 headers / rows 打印
+pub fn print<T: Serialize>(format: Format, value: &T, headers: &[&str], rows: impl FnOnce() -> Vec<Vec<String>>) -> anyhow::Result<()> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        Format::Table => println!("{}", render_table(headers, &rows())),
+    }
+    Ok(())
+}
+
+pub fn opt<T: ToString>(v: &Option<T>) -> String {
+    v.as_ref().map(ToString::to_string).unwrap_or_else(|| "-".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_columns() {
+        let rows = vec![vec!["GET".to_string(), "/api/orders".to_string()], vec!["DELETE".to_string(), "/a".to_string()]];
+        assert_eq!(render_table(&["METHOD", "PATH"], &rows), "METHOD  PATH\nGET     /api/orders\nDELETE  /a");
+    }
+}
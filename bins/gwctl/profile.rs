@@ -0,0 +1,86 @@
+//! 连接配置（profile）：`~/.config/gwctl/config.toml`，可用 `GWCTL_CONFIG` 指定路径。
+//! login 成功后把令牌写回当前 profile；文件权限设为 0600。
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub server: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProfileFile {
+    /// 未指定 --profile 时使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+pub fn config_path() -> anyhow::Result<PathBuf> {
+    if let Ok(p) = std::env::var("GWCTL_CONFIG") {
+        return Ok(PathBuf::from(p));
+    }
+    let home = std::env::var("HOME").map_err(|_| anyhow!("HOME is not set; use GWCTL_CONFIG to choose the config file"))?;
+    Ok(PathBuf::from(home).join(".config/gwctl/config.toml"))
+}
+
+impl ProfileFile {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = config_path()?;
+        match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).with_context(|| format!("parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("read {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = config_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+        }
+        std::fs::write(&path, toml::to_string_pretty(self)?).with_context(|| format!("write {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(())
+    }
+
+    /// --profile > GWCTL_PROFILE（clap 已合并） > current > default
+    pub fn resolve_name(&self, requested: Option<&str>) -> String {
+        requested.or(self.current.as_deref()).unwrap_or(DEFAULT_PROFILE).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_resolves_current_profile() {
+        let mut file = ProfileFile { current: Some("prod".into()), ..Default::default() };
+        file.profiles.insert("prod".into(), Profile { server: "https://admin.example.com".into(), token: Some("t".into()), ..Default::default() });
+        let text = toml::to_string_pretty(&file).unwrap();
+        let back: ProfileFile = toml::from_str(&text).unwrap();
+        assert_eq!(back.profiles["prod"].token.as_deref(), Some("t"));
+        assert_eq!(back.resolve_name(None), "prod");
+        assert_eq!(back.resolve_name(Some("staging")), "staging");
+        assert_eq!(ProfileFile::default().resolve_name(None), DEFAULT_PROFILE);
+    }
+}
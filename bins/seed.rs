@@ -0,0 +1,19 @@
+//! 开发环境演示数据：`cargo run --bin seed`（需 config.toml `[dev] enabled = true`）
+
+fn main() -> std::process::ExitCode {
+    common::utils::logging::init_logging_default();
+    let rt = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("failed to build tokio runtime: {e}");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+    match rt.block_on(server::seed::run_cli()) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("seed failed: {e}");
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
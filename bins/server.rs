@@ -2,11 +2,22 @@ use dotenvy::dotenv;
 use tracing::{error, info};
 use uuid::Uuid;
 
+// 堆剖析（/debug/pprof/heap）需要开启 profiling 的 jemalloc 作为全局分配器
+#[cfg(feature = "heap-profiling")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "heap-profiling")]
+#[allow(non_upper_case_globals)]
+#[export_name = "_rjem_malloc_conf"]
+pub static malloc_conf: &[u8] = b"prof:true,prof_active:true,lg_prof_sample:19\0";
+
 fn init_logging() {
     // 提前加载 .env，使得 RUST_LOG 等环境变量生效
     dotenv().ok();
-    // 复用公共日志初始化工具，统一日志格式与级别处理
-    common::utils::logging::init_logging_default();
+    // 按 config.toml [logging] 初始化；配置无法解析时使用默认值（解析错误稍后由 server::run 报告）
+    let logging = configs::resolve_default().map(|r| r.config.logging).unwrap_or_default();
+    common::utils::logging::init_logging(&logging);
     info!(service = "server", event = "logger_init", "tracing subscriber initialized");
 }
 
@@ -63,40 +74,17 @@ fn main() -> std::process::ExitCode {
         "server service starting"
     );
 
-    // 在独立任务中运行服务，并监听 Ctrl+C 优雅停机
+    // server::run 内部监听 SIGTERM / Ctrl+C 并优雅停机（排空连接、冲刷后台任务），返回即进程退出
     let exit_code = rt.block_on(async move {
-        let server_task = tokio::spawn(async move {
-            if let Err(e) = server::run().await {
-                error!(service = "server", event = "run_failed", error = %e, "server::run returned error");
-                Err(e)
-            } else {
-                Ok(())
-            }
-        });
-
-        tokio::select! {
-            res = server_task => {
-                match res {
-                    Ok(Ok(())) => {
-                        info!(service = "server", event = "stop", %service_id, pid, "server stopped normally");
-                        std::process::ExitCode::SUCCESS
-                    }
-                    Ok(Err(_)) => {
-                        // 错误已在上面记录
-                        std::process::ExitCode::FAILURE
-                    }
-                    Err(e) => {
-                        error!(service = "server", event = "task_join_error", error = %e, "server task join error");
-                        std::process::ExitCode::FAILURE
-                    }
-                }
-            }
-            _ = tokio::signal::ctrl_c() => {
-                info!(service = "server", event = "shutdown_signal", %service_id, pid, "received Ctrl+C, shutting down");
-                // 当前 server::run 不支持外部优雅停机信号，这里中止任务以尽快退出
-                // 如需更优雅的停机，应在服务内部支持 with_graceful_shutdown。
+        match server::run().await {
+            Ok(()) => {
+                info!(service = "server", event = "stop", %service_id, pid, "server stopped normally");
                 std::process::ExitCode::SUCCESS
             }
+            Err(e) => {
+                error!(service = "server", event = "run_failed", error = %e, "server::run returned error");
+                std::process::ExitCode::FAILURE
+            }
         }
     });
 
@@ -0,0 +1,127 @@
+//! 代理热路径微基准：路由匹配、限流检查、熔断器放行路径（含 1–8 线程争用）、上游请求头改写。
+//! `cargo bench -p bench --bench hot_path`；保存基线后对比：`-- --save-baseline main` / `-- --baseline main`
+
+use std::time::{Duration, Instant};
+
+use common::routing::Router;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use gateway::circuit_breaker::CircuitBreaker;
+use gateway::geoip::GeoInfo;
+use gateway::latency::route_label;
+use gateway::proxy::{rewrite_upstream_headers, RequestCtx};
+use gateway::rate_limiter::RateLimiter;
+use pingora_http::RequestHeader;
+
+/// 争用基准的线程数
+const THREADS: [u64; 4] = [1, 2, 4, 8];
+
+/// `iters` 次操作平均分给 `threads` 个线程并发执行，返回总耗时；`op` 的参数为线程序号
+fn contended(iters: u64, threads: u64, op: impl Fn(usize) + Sync) -> Duration {
+    let per_thread = (iters / threads).max(1);
+    let started = Instant::now();
+    std::thread::scope(|s| {
+        for t in 0..threads as usize {
+            let op = &op;
+            s.spawn(move || {
+                for _ in 0..per_thread {
+                    op(t);
+                }
+            });
+        }
+    });
+    started.elapsed()
+}
+
+fn bench_routing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("routing");
+    for n in [10usize, 100, 1_000] {
+        let prefixes: Vec<String> = (0..n).map(|i| format!("/svc{}/v1", i)).collect();
+        let path = format!("/svc{}/v1/items/42", n - 1);
+        group.bench_with_input(BenchmarkId::new("route_label", n), &path, |b, path| {
+            b.iter(|| route_label(black_box(&prefixes), black_box(path)).len())
+        });
+
+        let mut router = Router::new();
+        for (i, p) in prefixes.iter().enumerate() {
+            router.insert("GET", &format!("{}/items/{{id}}", p), i, true).expect("valid pattern");
+        }
+        group.bench_with_input(BenchmarkId::new("trie", n), &path, |b, path| {
+            b.iter(|| router.at(black_box("GET"), black_box(path)).map(|m| *m.value))
+        });
+    }
+    group.finish();
+}
+
+fn bench_rate_limiter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rate_limiter");
+    // 容量足够大，测量的是检查本身而非拒绝路径
+    let limiter = RateLimiter::new(u32::MAX as u64, u32::MAX as u64, true);
+    group.bench_function("check", |b| b.iter(|| limiter.check_rate_limit()));
+    let keyed = RateLimiter::new(u32::MAX as u64, u32::MAX as u64, true).with_spike_arrest(Duration::from_nanos(1));
+    group.bench_function("check_for_key", |b| b.iter(|| keyed.check_rate_limit_for(black_box("tenant-a"))));
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::new("check_contended", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(iters, threads, |_| {
+                black_box(limiter.check_rate_limit());
+            }))
+        });
+        // 每线程一个 key：分片存放后互不阻塞
+        let keys: Vec<String> = (0..threads).map(|i| format!("tenant-{}", i)).collect();
+        group.bench_with_input(BenchmarkId::new("check_for_key_contended", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(iters, threads, |t| {
+                black_box(keyed.check_rate_limit_for(&keys[t]));
+            }))
+        });
+    }
+    group.finish();
+}
+
+fn bench_circuit_breaker(c: &mut Criterion) {
+    let mut group = c.benchmark_group("circuit_breaker");
+    let breaker = CircuitBreaker::new(5, Duration::from_secs(30), 3, true);
+    // 每个请求的完整路径：放行检查 + 结果上报
+    let execute_and_record = |_| {
+        if breaker.can_execute() {
+            breaker.record_success();
+        }
+    };
+    group.bench_function("execute_and_record", |b| b.iter(|| execute_and_record(0)));
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::new("execute_and_record_contended", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(iters, threads, execute_and_record))
+        });
+    }
+    group.finish();
+}
+
+fn bench_header_rewrite(c: &mut Criterion) {
+    let mut group = c.benchmark_group("header_rewrite");
+    let plain = RequestCtx::default();
+    let enriched = RequestCtx {
+        waf_tags: vec!["sqli-probe".to_string(), "scanner".to_string()],
+        bot_score: Some(42),
+        geo: Some(GeoInfo { country: Some("DE".to_string()), asn: Some(3320), as_org: None }),
+        ..Default::default()
+    };
+    for (name, ctx) in [("host_and_request_id", &plain), ("with_waf_geo_bot", &enriched)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut req = RequestHeader::build("GET", b"/svc1/v1/items/42?expand=owner", None).expect("request header");
+                    req.insert_header("User-Agent", "bench/1.0").expect("header");
+                    req.insert_header("Accept", "application/json").expect("header");
+                    req
+                },
+                |mut req| {
+                    rewrite_upstream_headers(&mut req, black_box("127.0.0.1:8080"), ctx);
+                    req
+                },
+                criterion::BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_routing, bench_rate_limiter, bench_circuit_breaker, bench_header_rewrite);
+criterion_main!(benches);
@@ -0,0 +1,105 @@
+//! 网关压测：`cargo run --release -p bench --bin loadtest -- --spawn-echo 127.0.0.1:8080 --target http://127.0.0.1:6188/`
+//!
+//! 先以 `--spawn-echo` 在 config.json 的上游地址启动内置 echo，再启动网关，即可测量代理本身的开销；
+//! 设置了阈值且未达标时以退出码 1 结束，便于在 CI 中发现性能回归。
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use bench::{LoadOptions, LoadReport, Thresholds};
+
+const USAGE: &str = "usage: loadtest [--target URL] [--spawn-echo ADDR] [--concurrency N] [--duration-secs N] [--warmup-secs N]
+                [--timeout-ms N] [--min-rps F] [--max-p99-ms F] [--max-error-rate F] [--json]";
+
+struct Args {
+    opts: LoadOptions,
+    thresholds: Thresholds,
+    spawn_echo: Option<String>,
+    json: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut out = Args { opts: LoadOptions::default(), thresholds: Thresholds::default(), spawn_echo: None, json: false };
+    while let Some(flag) = args.next() {
+        if flag == "--json" {
+            out.json = true;
+            continue;
+        }
+        if flag == "-h" || flag == "--help" {
+            return Err(String::new());
+        }
+        let value = args.next().ok_or_else(|| format!("missing value for {}", flag))?;
+        let num = |v: &str| v.parse::<f64>().map_err(|_| format!("invalid number for {}: {}", flag, v));
+        match flag.as_str() {
+            "--target" => out.opts.target = value,
+            "--spawn-echo" => out.spawn_echo = Some(value),
+            "--concurrency" => out.opts.concurrency = num(&value)? as usize,
+            "--duration-secs" => out.opts.duration = Duration::from_secs_f64(num(&value)?),
+            "--warmup-secs" => out.opts.warmup = Duration::from_secs_f64(num(&value)?),
+            "--timeout-ms" => out.opts.request_timeout = Duration::from_millis(num(&value)? as u64),
+            "--min-rps" => out.thresholds.min_rps = Some(num(&value)?),
+            "--max-p99-ms" => out.thresholds.max_p99_ms = Some(num(&value)?),
+            "--max-error-rate" => out.thresholds.max_error_rate = Some(num(&value)?),
+            _ => return Err(format!("unknown flag: {}", flag)),
+        }
+    }
+    Ok(out)
+}
+
+fn print_human(r: &LoadReport) {
+    println!("target       {}", r.target);
+    println!("concurrency  {}", r.concurrency);
+    println!("duration     {:.2}s", r.duration_secs);
+    println!("requests     {} ({} errors, {:.2}%)", r.requests, r.errors, r.error_rate() * 100.0);
+    println!("throughput   {:.1} req/s", r.rps);
+    let l = &r.latency;
+    println!("latency      mean {:.2}ms  p50 {:.2}ms  p90 {:.2}ms  p99 {:.2}ms  max {:.2}ms", l.mean_ms, l.p50_ms, l.p90_ms, l.p99_ms, l.max_ms);
+    let statuses: Vec<String> = r.statuses.iter().map(|(s, n)| format!("{}={}", s, n)).collect();
+    println!("statuses     {}", statuses.join(" "));
+}
+
+async fn run(args: Args) -> anyhow::Result<bool> {
+    if let Some(addr) = &args.spawn_echo {
+        let local = common::echo::spawn(addr).await?;
+        eprintln!("echo upstream listening on {}", local);
+    }
+    let report = bench::run(&args.opts).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_human(&report);
+    }
+    let violations = args.thresholds.violations(&report);
+    for v in &violations {
+        eprintln!("threshold violated: {}", v);
+    }
+    Ok(violations.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(a) => a,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}", e);
+            }
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let rt = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("failed to build tokio runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match rt.block_on(run(args)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("loadtest failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! 代理热路径的性能回归工具。
+//!
+//! - `benches/hot_path.rs`：criterion 微基准（路由匹配、限流检查、熔断器加锁路径、上游请求头改写），
+//!   `cargo bench -p bench`；
+//! - `loadtest`：对本地网关施压（上游为内置 echo），输出 RPS 与延迟分位数，可设阈值作为回归门禁，
+//!   `cargo run --release -p bench --bin loadtest -- --help`。
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// 施压目标，通常为网关监听地址
+    pub target: String,
+    pub concurrency: usize,
+    pub duration: Duration,
+    /// 预热期间的请求不计入结果
+    pub warmup: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            target: "http://127.0.0.1:6188/".to_string(),
+            concurrency: 32,
+            duration: Duration::from_secs(10),
+            warmup: Duration::from_secs(2),
+            request_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct LatencyStats {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_unstable();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let at = |q: f64| ms(samples[((samples.len() - 1) as f64 * q).round() as usize]);
+        let total: Duration = samples.iter().sum();
+        Self {
+            mean_ms: ms(total) / samples.len() as f64,
+            p50_ms: at(0.50),
+            p90_ms: at(0.90),
+            p99_ms: at(0.99),
+            max_ms: ms(samples[samples.len() - 1]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadReport {
+    pub target: String,
+    pub concurrency: usize,
+    pub duration_secs: f64,
+    pub requests: u64,
+    /// 非 2xx/3xx 响应与连接/超时错误
+    pub errors: u64,
+    /// 状态码 -> 次数；0 表示未拿到响应
+    pub statuses: BTreeMap<u16, u64>,
+    pub rps: f64,
+    pub latency: LatencyStats,
+}
+
+impl LoadReport {
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 { 0.0 } else { self.errors as f64 / self.requests as f64 }
+    }
+}
+
+/// 回归门禁；未设置的项不检查
+#[derive(Debug, Clone, Default)]
+pub struct Thresholds {
+    pub min_rps: Option<f64>,
+    pub max_p99_ms: Option<f64>,
+    pub max_error_rate: Option<f64>,
+}
+
+impl Thresholds {
+    pub fn violations(&self, r: &LoadReport) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(min) = self.min_rps.filter(|min| r.rps < *min) {
+            out.push(format!("rps {:.1} < {:.1}", r.rps, min));
+        }
+        if let Some(max) = self.max_p99_ms.filter(|max| r.latency.p99_ms > *max) {
+            out.push(format!("p99 {:.2}ms > {:.2}ms", r.latency.p99_ms, max));
+        }
+        if let Some(max) = self.max_error_rate.filter(|max| r.error_rate() > *max) {
+            out.push(format!("error rate {:.4} > {:.4}", r.error_rate(), max));
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct WorkerResult {
+    samples: Vec<Duration>,
+    errors: u64,
+    statuses: BTreeMap<u16, u64>,
+}
+
+async fn worker(client: reqwest::Client, target: String, measure_from: Instant, deadline: Instant) -> WorkerResult {
+    let mut r = WorkerResult::default();
+    while Instant::now() < deadline {
+        let started = Instant::now();
+        let status = match client.get(&target).send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                // 读完响应体，计入完整往返
+                let ok = resp.bytes().await.is_ok() && (status.is_success() || status.is_redirection());
+                (status.as_u16(), ok)
+            }
+            Err(_) => (0, false),
+        };
+        if started < measure_from {
+            continue;
+        }
+        r.samples.push(started.elapsed());
+        *r.statuses.entry(status.0).or_default() += 1;
+        if !status.1 {
+            r.errors += 1;
+        }
+    }
+    r
+}
+
+/// 以 `concurrency` 个并发连接持续请求 `target`，预热后统计 `duration` 内的结果
+pub async fn run(opts: &LoadOptions) -> anyhow::Result<LoadReport> {
+    anyhow::ensure!(opts.concurrency > 0, "concurrency must be > 0");
+    anyhow::ensure!(!opts.duration.is_zero(), "duration must be > 0");
+    let client = reqwest::Client::builder()
+        .timeout(opts.request_timeout)
+        .pool_max_idle_per_host(opts.concurrency)
+        .build()?;
+    let measure_from = Instant::now() + opts.warmup;
+    let deadline = measure_from + opts.duration;
+    let handles: Vec<_> = (0..opts.concurrency)
+        .map(|_| tokio::spawn(worker(client.clone(), opts.target.clone(), measure_from, deadline)))
+        .collect();
+
+    let mut samples = Vec::new();
+    let mut errors = 0;
+    let mut statuses = BTreeMap::new();
+    for h in handles {
+        let r = h.await?;
+        samples.extend(r.samples);
+        errors += r.errors;
+        for (status, n) in r.statuses {
+            *statuses.entry(status).or_default() += n;
+        }
+    }
+    // 最后一批请求可能越过 deadline，以实际结束时间计算
+    let elapsed = Instant::now().saturating_duration_since(measure_from).as_secs_f64().max(f64::EPSILON);
+    let requests = samples.len() as u64;
+    Ok(LoadReport {
+        target: opts.target.clone(),
+        concurrency: opts.concurrency,
+        duration_secs: elapsed,
+        requests,
+        errors,
+        statuses,
+        rps: requests as f64 / elapsed,
+        latency: LatencyStats::from_samples(&mut samples),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_percentiles() {
+        let mut samples: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let s = LatencyStats::from_samples(&mut samples);
+        assert_eq!((s.p50_ms, s.p99_ms, s.max_ms), (51.0, 99.0, 100.0));
+        assert_eq!(s.mean_ms, 50.5);
+        assert_eq!(LatencyStats::from_samples(&mut []), LatencyStats::default());
+    }
+
+    #[test]
+    fn thresholds_report_violations() {
+        let report = LoadReport {
+            target: String::new(),
+            concurrency: 1,
+            duration_secs: 1.0,
+            requests: 100,
+            errors: 5,
+            statuses: BTreeMap::new(),
+            rps: 100.0,
+            latency: LatencyStats { p99_ms: 20.0, ..Default::default() },
+        };
+        let t = Thresholds { min_rps: Some(50.0), max_p99_ms: Some(10.0), max_error_rate: Some(0.01) };
+        assert_eq!(t.violations(&report).len(), 2);
+        assert!(Thresholds::default().violations(&report).is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn drives_echo_upstream() {
+        let addr = common::echo::spawn("127.0.0.1:0").await.unwrap();
+        let opts = LoadOptions {
+            target: format!("http://{}/bench?status=200", addr),
+            concurrency: 4,
+            duration: Duration::from_millis(300),
+            warmup: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let r = run(&opts).await.unwrap();
+        assert!(r.requests > 0);
+        assert_eq!(r.errors, 0);
+        assert_eq!(r.statuses.get(&200), Some(&r.requests));
+    }
+}
@@ -0,0 +1,73 @@
+//! 客户端错误：服务端返回的 JSON:API 错误体解析为 `Api`，其余为传输或解码错误。
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// 非 2xx 响应；title/detail 取自 `{"errors":[{...}]}`，纯文本错误体记入 detail
+    #[error("{} {}{}", status.as_u16(), title, detail.as_deref().map(|d| format!(": {}", d)).unwrap_or_default())]
+    Api { status: StatusCode, title: String, detail: Option<String> },
+    /// 连接、超时等传输层错误（重试用尽后返回）
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("decode error: {0}")]
+    Decode(String),
+    /// 调用需要登录的接口前未 login / with_token
+    #[error("not authenticated")]
+    NotAuthenticated,
+    #[error("invalid base url: {0}")]
+    InvalidUrl(String),
+}
+
+impl ClientError {
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Transport(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    errors: Vec<ErrorItem>,
+}
+
+#[derive(Deserialize)]
+struct ErrorItem {
+    title: String,
+    #[serde(default)]
+    detail: Option<String>,
+}
+
+/// 按响应体构造错误：JSON:API 错误体优先，否则整段文本作为 detail
+pub(crate) fn from_body(status: StatusCode, body: &str) -> ClientError {
+    let title = status.canonical_reason().unwrap_or("Error").to_string();
+    match serde_json::from_str::<ErrorBody>(body).ok().and_then(|b| b.errors.into_iter().next()) {
+        Some(item) => ClientError::Api { status, title: item.title, detail: item.detail },
+        None => ClientError::Api { status, title, detail: Some(body.trim().to_string()).filter(|d| !d.is_empty()) },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_api_and_plain_text_bodies() {
+        let e = from_body(StatusCode::CONFLICT, r#"{"errors":[{"status":409,"title":"Conflict","detail":"route already exists"}]}"#);
+        assert_eq!(e.to_string(), "409 Conflict: route already exists");
+        assert_eq!(e.status(), Some(StatusCode::CONFLICT));
+
+        let e = from_body(StatusCode::UNAUTHORIZED, "no auth");
+        assert!(matches!(&e, ClientError::Api { title, detail: Some(d), .. } if title == "Unauthorized" && d == "no auth"));
+        assert!(!e.is_not_found());
+    }
+}
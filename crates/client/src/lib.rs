@@ -0,0 +1,454 @@
+//! 控制面 admin / auth 接口的类型化客户端（按 server 的 `/api-docs/openapi.json` 手写）。
+//! - `login` 后自动携带 `Authorization: Bearer`（Bearer 认证不需要 CSRF token）；也可用 `with_token` 直接使用已签发的令牌；
+//! - 连接失败与 429/502/503/504 按 `RetryPolicy` 指数退避重试，429 优先使用 `Retry-After`；
+//!   非幂等的 POST 只在请求未送达（连接失败）或被限流拒绝（429）时重试。
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), client::ClientError> {
+//! let admin = client::AdminClient::builder("http://localhost:8080").build()?;
+//! admin.login(uuid::Uuid::nil(), "admin@example.com", "secret").await?;
+//! let page = admin.list_proxy_apis(&Default::default()).await?;
+//! println!("{} proxy apis", page.total);
+//! # Ok(()) }
+//! ```
+
+pub mod error;
+pub mod types;
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+use reqwest::{header, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+use uuid::Uuid;
+
+pub use error::ClientError;
+use types::*;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 首次请求之外的最大重试次数
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    /// 单次等待上限（含 Retry-After）
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, base_delay: Duration::from_millis(200), max_delay: Duration::from_secs(10) }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Default::default() }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempt)).min(self.max_delay)
+    }
+}
+
+pub struct AdminClientBuilder {
+    base_url: String,
+    token: Option<String>,
+    timeout: Duration,
+    retry: RetryPolicy,
+    user_agent: String,
+}
+
+impl AdminClientBuilder {
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn build(self) -> Result<AdminClient, ClientError> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+            return Err(ClientError::InvalidUrl(self.base_url));
+        }
+        let http = reqwest::Client::builder().timeout(self.timeout).user_agent(self.user_agent).build()?;
+        Ok(AdminClient { http, base_url, token: RwLock::new(self.token), retry: self.retry })
+    }
+}
+
+pub struct AdminClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: RwLock<Option<String>>,
+    retry: RetryPolicy,
+}
+
+/// 可重试的响应状态：限流与网关/上游暂时不可用
+fn retryable_status(status: StatusCode) -> bool {
+    matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT)
+}
+
+fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    resp.headers().get(header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok().map(Duration::from_secs)
+}
+
+impl AdminClient {
+    pub fn builder(base_url: impl Into<String>) -> AdminClientBuilder {
+        AdminClientBuilder {
+            base_url: base_url.into(),
+            token: None,
+            timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+            user_agent: concat!("api-proxy-client/", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+
+    /// 当前使用的 Bearer 令牌（login 后可保存下来供下次 with_token）
+    pub fn token(&self) -> Option<String> {
+        self.token.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn set_token(&self, token: Option<String>) {
+        *self.token.write().unwrap_or_else(|e| e.into_inner()) = token;
+    }
+
+    /// 发送请求：body 先序列化为 JSON，重试时原样重发
+    async fn send(&self, method: Method, path: &str, query: Option<&Value>, body: Option<&Value>) -> Result<reqwest::Response, ClientError> {
+        let idempotent = method != Method::POST;
+        let url = format!("{}{}", self.base_url, path);
+        let mut attempt = 0;
+        loop {
+            let mut req = self.http.request(method.clone(), &url);
+            if let Some(token) = self.token() {
+                req = req.bearer_auth(token);
+            }
+            if let Some(q) = query {
+                req = req.query(q);
+            }
+            if let Some(b) = body {
+                req = req.json(b);
+            }
+            let can_retry = attempt < self.retry.max_retries;
+            match req.send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => {
+                    let status = resp.status();
+                    if can_retry && retryable_status(status) && (idempotent || status == StatusCode::TOO_MANY_REQUESTS) {
+                        let wait = retry_after(&resp).unwrap_or_else(|| self.retry.backoff(attempt)).min(self.retry.max_delay);
+                        debug!(%method, path, status = status.as_u16(), attempt, wait_ms = wait.as_millis() as u64, "retrying admin request");
+                        tokio::time::sleep(wait).await;
+                    } else {
+                        let text = resp.text().await.unwrap_or_default();
+                        return Err(error::from_body(status, &text));
+                    }
+                }
+                Err(e) if can_retry && (e.is_connect() || (idempotent && e.is_timeout())) => {
+                    let wait = self.retry.backoff(attempt);
+                    debug!(%method, path, err = %e, attempt, wait_ms = wait.as_millis() as u64, "retrying admin request");
+                    tokio::time::sleep(wait).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+            attempt += 1;
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: Method, path: &str, query: Option<&Value>, body: Option<&Value>) -> Result<T, ClientError> {
+        let resp = self.send(method, path, query, body).await?;
+        let bytes = resp.bytes().await?;
+        serde_json::from_slice(&bytes).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    async fn authed<T: DeserializeOwned>(&self, method: Method, path: &str, query: Option<&Value>, body: Option<&Value>) -> Result<T, ClientError> {
+        if self.token().is_none() {
+            return Err(ClientError::NotAuthenticated);
+        }
+        self.call(method, path, query, body).await
+    }
+
+    async fn authed_empty(&self, method: Method, path: &str) -> Result<(), ClientError> {
+        if self.token().is_none() {
+            return Err(ClientError::NotAuthenticated);
+        }
+        self.send(method, path, None, None).await.map(drop)
+    }
+
+    fn json<B: Serialize>(body: &B) -> Result<Value, ClientError> {
+        serde_json::to_value(body).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    // ---- auth ----
+
+    /// 登录并保存返回的令牌，后续请求自动携带
+    pub async fn login(&self, tenant_id: Uuid, email: &str, password: &str) -> Result<LoginOutput, ClientError> {
+        let body = Self::json(&LoginRequest { tenant_id, email: email.to_string(), password: password.to_string() })?;
+        let out: LoginOutput = self.call(Method::POST, "/auth/login", None, Some(&body)).await?;
+        self.set_token(Some(out.token.clone()));
+        Ok(out)
+    }
+
+    /// 清除本地令牌；服务端 JWT 无状态，不调用 /auth/logout（它只清 Cookie）
+    pub fn logout(&self) {
+        self.set_token(None);
+    }
+
+    pub async fn me(&self) -> Result<Me, ClientError> {
+        self.authed(Method::GET, "/auth/me", None, None).await
+    }
+
+    // ---- proxy APIs ----
+
+    pub async fn list_proxy_apis(&self, query: &ListProxyApis) -> Result<Page<ProxyApi>, ClientError> {
+        self.authed(Method::GET, "/admin/proxy-apis", Some(&Self::json(query)?), None).await
+    }
+
+    /// 按 next_cursor 翻页取完全部结果
+    pub async fn list_all_proxy_apis(&self, tenant_id: Option<Uuid>) -> Result<Vec<ProxyApi>, ClientError> {
+        let mut query = ListProxyApis { tenant_id, per_page: Some(100), ..Default::default() };
+        let mut out = Vec::new();
+        loop {
+            let page = self.list_proxy_apis(&query).await?;
+            out.extend(page.items);
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => return Ok(out),
+            }
+        }
+    }
+
+    pub async fn get_proxy_api(&self, id: Uuid) -> Result<ProxyApi, ClientError> {
+        self.authed(Method::GET, &format!("/admin/proxy-apis/{}", id), None, None).await
+    }
+
+    pub async fn create_proxy_api(&self, input: &CreateProxyApi) -> Result<ProxyApi, ClientError> {
+        self.authed(Method::POST, "/admin/proxy-apis", None, Some(&Self::json(input)?)).await
+    }
+
+    pub async fn update_proxy_api(&self, id: Uuid, input: &UpdateProxyApi) -> Result<ProxyApi, ClientError> {
+        self.authed(Method::PUT, &format!("/admin/proxy-apis/{}", id), None, Some(&Self::json(input)?)).await
+    }
+
+    pub async fn delete_proxy_api(&self, id: Uuid) -> Result<(), ClientError> {
+        self.authed_empty(Method::DELETE, &format!("/admin/proxy-apis/{}", id)).await
+    }
+
+    pub async fn set_proxy_api_mock(&self, id: Uuid, input: &SetMock) -> Result<ProxyApi, ClientError> {
+        self.authed(Method::PUT, &format!("/admin/proxy-apis/{}/mock", id), None, Some(&Self::json(input)?)).await
+    }
+
+    pub async fn set_proxy_api_maintenance(&self, id: Uuid, input: &SetMaintenance) -> Result<ProxyApi, ClientError> {
+        self.authed(Method::PUT, &format!("/admin/proxy-apis/{}/maintenance", id), None, Some(&Self::json(input)?)).await
+    }
+
+    /// 租户数据面的 OpenAPI 文档（原样 JSON）
+    pub async fn proxy_api_openapi(&self, tenant_id: Uuid) -> Result<Value, ClientError> {
+        self.authed(Method::GET, "/admin/proxy-apis/openapi.json", Some(&serde_json::json!({ "tenant_id": tenant_id })), None).await
+    }
+
+    // ---- tenants ----
+
+    pub async fn list_tenants(&self, query: &PageQuery) -> Result<Page<Tenant>, ClientError> {
+        self.authed(Method::GET, "/admin/tenants", Some(&Self::json(query)?), None).await
+    }
+
+    pub async fn create_tenant(&self, name: &str) -> Result<Tenant, ClientError> {
+        self.authed(Method::POST, "/admin/tenants", None, Some(&serde_json::json!({ "name": name }))).await
+    }
+
+    pub async fn set_tenant_suspension(&self, id: Uuid, suspended: bool, reason: Option<&str>) -> Result<Tenant, ClientError> {
+        let body = serde_json::json!({ "suspended": suspended, "reason": reason });
+        self.authed(Method::PUT, &format!("/admin/tenants/{}/suspension", id), None, Some(&body)).await
+    }
+
+    /// 提交导出任务；用 `wait_for_job` 取快照
+    pub async fn export_tenant(&self, id: Uuid) -> Result<Job, ClientError> {
+        self.authed(Method::POST, &format!("/admin/tenants/{}/export", id), None, None).await
+    }
+
+    // ---- routes ----
+
+    pub async fn list_routes(&self, tenant_id: Uuid, query: &PageQuery) -> Result<Page<Route>, ClientError> {
+        let mut q = Self::json(query)?;
+        q["tenant_id"] = serde_json::json!(tenant_id);
+        self.authed(Method::GET, "/admin/routes", Some(&q), None).await
+    }
+
+    pub async fn set_route_blue_green(&self, id: Uuid, blue_upstream_id: Uuid, green_upstream_id: Uuid) -> Result<Route, ClientError> {
+        let body = serde_json::json!({ "blue_upstream_id": blue_upstream_id, "green_upstream_id": green_upstream_id });
+        self.authed(Method::PUT, &format!("/admin/routes/{}/blue-green", id), None, Some(&body)).await
+    }
+
+    /// target 为 None 时切到当前未生效的一侧
+    pub async fn cutover_route(&self, id: Uuid, target: Option<Color>) -> Result<RouteCutover, ClientError> {
+        let body = serde_json::json!({ "target": target });
+        self.authed(Method::POST, &format!("/admin/routes/{}/cutover", id), None, Some(&body)).await
+    }
+
+    pub async fn rollback_route(&self, id: Uuid) -> Result<RouteCutover, ClientError> {
+        self.authed(Method::POST, &format!("/admin/routes/{}/rollback", id), None, None).await
+    }
+
+    // ---- jobs ----
+
+    pub async fn get_job(&self, id: Uuid) -> Result<Job, ClientError> {
+        self.authed(Method::GET, &format!("/admin/jobs/{}", id), None, None).await
+    }
+
+    /// 轮询直到任务结束（succeeded / failed / cancelled）
+    pub async fn wait_for_job(&self, id: Uuid, poll_interval: Duration) -> Result<Job, ClientError> {
+        loop {
+            let job = self.get_job(id).await?;
+            if job.status.is_finished() {
+                return Ok(job);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    // ---- API keys / stats ----
+
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKeyRecord>, ClientError> {
+        self.authed(Method::GET, "/admin/api-keys", None, None).await
+    }
+
+    /// 为用户设置（覆盖）API key
+    pub async fn set_api_key(&self, record: &ApiKeyRecord) -> Result<(), ClientError> {
+        let _: Value = self.authed(Method::POST, "/admin/api-keys", None, Some(&Self::json(record)?)).await?;
+        Ok(())
+    }
+
+    /// 管理台概览（`GET /admin/stats`），字段随版本增加，原样返回 JSON
+    pub async fn stats(&self, top: Option<u32>) -> Result<Value, ClientError> {
+        let query = top.map(|t| serde_json::json!({ "top": t }));
+        self.authed(Method::GET, "/admin/stats", query.as_ref(), None).await
+    }
+
+    // ---- logs ----
+
+    pub async fn request_logs(&self, query: &ListRequestLogs) -> Result<Vec<RequestLog>, ClientError> {
+        self.authed(Method::GET, "/admin/request-logs", Some(&Self::json(query)?), None).await
+    }
+
+    pub async fn log_level(&self) -> Result<LogFilterState, ClientError> {
+        self.authed(Method::GET, "/admin/log-level", None, None).await
+    }
+
+    /// filter 为 None 时恢复启动时的规则
+    pub async fn set_log_level(&self, filter: Option<&str>) -> Result<LogFilterState, ClientError> {
+        self.authed(Method::PUT, "/admin/log-level", None, Some(&serde_json::json!({ "filter": filter }))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, routing::{get, post}, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy { max_retries: 2, base_delay: Duration::from_millis(5), max_delay: Duration::from_millis(20) }
+    }
+
+    #[tokio::test]
+    async fn login_token_is_sent_as_bearer() {
+        let app = Router::new()
+            .route(
+                "/auth/login",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(body["email"], "ops@example.com");
+                    Json(serde_json::json!({ "user_id": Uuid::nil(), "email": "ops@example.com", "name": "ops", "token": "t1", "csrf_token": "c" }))
+                }),
+            )
+            .route(
+                "/admin/log-level",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers.get("authorization").unwrap(), "Bearer t1");
+                    Json(serde_json::json!({ "filter": "debug", "initial": "info" }))
+                }),
+            );
+        let c = AdminClient::builder(serve(app).await).build().unwrap();
+        assert!(matches!(c.log_level().await, Err(ClientError::NotAuthenticated)));
+
+        c.login(Uuid::nil(), "ops@example.com", "pw").await.unwrap();
+        assert_eq!(c.token().as_deref(), Some("t1"));
+        assert_eq!(c.log_level().await.unwrap().filter, "debug");
+    }
+
+    #[tokio::test]
+    async fn retries_transient_failures_only_when_safe() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (get_calls, post_calls) = (Arc::clone(&calls), Arc::clone(&calls));
+        let unavailable = || (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "errors": [{ "status": 503, "title": "Unavailable" }] })));
+        let app = Router::new()
+            .route(
+                "/admin/jobs/:id",
+                get(move || {
+                    let n = get_calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        if n == 0 {
+                            return Err(unavailable());
+                        }
+                        Ok(Json(serde_json::json!({
+                            "id": Uuid::nil(), "kind": "tenant_export", "status": "succeeded", "progress": { "done": 1, "total": 1 },
+                            "cancel_requested": false, "created_at": "2024-01-01T00:00:00Z", "started_at": null, "finished_at": null
+                        })))
+                    }
+                }),
+            )
+            .route(
+                "/admin/routes/:id/rollback",
+                post(move || {
+                    post_calls.fetch_add(1, Ordering::SeqCst);
+                    async move { unavailable() }
+                }),
+            );
+        let c = AdminClient::builder(serve(app).await).with_token("t").with_retry(fast_retry()).build().unwrap();
+
+        let job = c.get_job(Uuid::nil()).await.unwrap();
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert!(job.result.is_none());
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+
+        // POST 收到 503 时服务端可能已执行，不重试
+        let err = c.rollback_route(Uuid::nil()).await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_are_retried_then_surface_the_error() {
+        let app = Router::new().route(
+            "/admin/routes/:id/cutover",
+            post(|| async { (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")], Json(serde_json::json!({ "errors": [{ "status": 429, "title": "Too Many Requests", "detail": "retry after 0s" }] }))) }),
+        );
+        let c = AdminClient::builder(serve(app).await).with_token("t").with_retry(fast_retry()).build().unwrap();
+        let err = c.cutover_route(Uuid::nil(), Some(Color::Green)).await.unwrap_err();
+        assert_eq!(err.to_string(), "429 Too Many Requests: retry after 0s");
+
+        assert!(matches!(AdminClient::builder("localhost:8080").build(), Err(ClientError::InvalidUrl(_))));
+    }
+}
@@ -0,0 +1,270 @@
+//! 请求与响应体，字段与 server 的 `/api-docs/openapi.json` 中同名 schema 一致。
+//! 不依赖 models / service，自动化脚本只需引入本 crate。
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub type Timestamp = DateTime<FixedOffset>;
+
+/// 分页列表；`next_cursor` 传给下一次请求的 `cursor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+// ---- auth ----
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginRequest {
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginOutput {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub token: String,
+    pub csrf_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Me {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+}
+
+// ---- proxy APIs ----
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyApi {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub require_api_key: bool,
+    pub enabled: bool,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    pub mock_enabled: bool,
+    pub mock_status: Option<i32>,
+    pub mock_headers: Option<String>,
+    pub mock_body: Option<String>,
+    pub maintenance_start: Option<Timestamp>,
+    pub maintenance_end: Option<Timestamp>,
+    pub maintenance_message: Option<String>,
+    pub grpc_method: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListProxyApis {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateProxyApi {
+    /// 省略时使用 default 租户
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub require_api_key: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpdateProxyApi {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_api_key: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetMock {
+    pub enabled: bool,
+    /// 省略时沿用已保存的 mock 响应
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<MockResponse>,
+}
+
+/// start/end 均为 None 表示清除维护窗口
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SetMaintenance {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub message: Option<String>,
+}
+
+// ---- tenants ----
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: Timestamp,
+    pub suspended: bool,
+    pub suspended_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PageQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+// ---- routes ----
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub upstream_id: Uuid,
+    pub timeout_ms: i32,
+    pub retry_max_attempts: i32,
+    pub circuit_breaker_threshold: i32,
+    pub rate_limit_id: Option<Uuid>,
+    pub created_at: Timestamp,
+    pub blue_upstream_id: Option<Uuid>,
+    pub green_upstream_id: Option<Uuid>,
+    pub active_color: Option<Color>,
+    pub previous_upstream_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Blue,
+    Green,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteCutover {
+    pub route: Route,
+    pub from_upstream_id: Uuid,
+    pub to_upstream_id: Uuid,
+}
+
+// ---- jobs ----
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub done: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub kind: String,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: Timestamp,
+    pub started_at: Option<Timestamp>,
+    pub finished_at: Option<Timestamp>,
+}
+
+// ---- API keys ----
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub user: String,
+    pub api_key: String,
+}
+
+// ---- logs ----
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ListRequestLogs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route_id: Option<Uuid>,
+    /// 只取 id 更大的记录；省略时返回最近的 limit 条
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestLog {
+    pub id: i64,
+    pub route_id: Uuid,
+    pub api_key_id: Option<Uuid>,
+    pub status_code: i32,
+    pub latency_ms: i32,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub client_ip: Option<String>,
+    pub timestamp: Timestamp,
+    pub config_revision: Option<i64>,
+    pub country: Option<String>,
+    pub asn: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFilterState {
+    /// 当前生效的 EnvFilter 规则
+    pub filter: String,
+    /// 启动时的规则
+    pub initial: String,
+}
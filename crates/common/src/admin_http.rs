@@ -1,32 +1,141 @@
 //! Lightweight admin HTTP server spawner
 //!
-//! Exposes `/healthz` and `/metrics` endpoints, with metrics provided by caller.
+//! Exposes `/livez` (`/healthz`) and `/metrics` endpoints, with metrics provided by caller;
+//! callers add `/readyz` and other routes via [`spawn_admin_server_with`].
 
 use std::thread;
-use axum::{routing::get, Router};
-use axum::http::StatusCode;
+use std::time::Duration;
+use axum::{extract::Query, routing::get, Json, Router};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
 use tokio::net::TcpListener;
 use tokio::runtime::Builder;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::profiling::{self, Profile, ProfileParams, ProfilingConfig, ProfilingError};
+use crate::utils::logging::{self, LogFilterError, LogFilterState};
+
+/// 升级期间旧进程仍占用 admin 端口，新进程等待其退出后再绑定
+const BIND_RETRY_TIMEOUT: Duration = Duration::from_secs(120);
+const BIND_RETRY_INTERVAL: Duration = Duration::from_millis(500);
 
 async fn healthz() -> &'static str { "OK" }
 
-async fn metrics_handler(f: fn() -> (StatusCode, String)) -> (StatusCode, String) {
-    f()
+
+async fn bind_with_retry(addr: &str) -> std::io::Result<TcpListener> {
+    let deadline = tokio::time::Instant::now() + BIND_RETRY_TIMEOUT;
+    let mut warned = false;
+    loop {
+        match TcpListener::bind(addr).await {
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && tokio::time::Instant::now() < deadline => {
+                if !warned {
+                    warn!(%addr, "admin address in use (upgrade in progress?), retrying");
+                    warned = true;
+                }
+                tokio::time::sleep(BIND_RETRY_INTERVAL).await;
+            }
+            r => return r,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LogLevelInput {
+    /// EnvFilter 规则；为 null 时恢复启动时的规则
+    filter: Option<String>,
+}
+
+fn log_level_response(r: Result<LogFilterState, LogFilterError>) -> Result<Json<LogFilterState>, (StatusCode, String)> {
+    r.map(Json).map_err(|e| match e {
+        LogFilterError::Invalid(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+        LogFilterError::NotInstalled => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
+    })
 }
 
-/// Spawn an admin HTTP server exposing healthz and metrics endpoints.
+/// `GET/PUT /log-level`：查看与调整运行时日志过滤规则
+pub fn log_level_routes() -> Router {
+    Router::new().route(
+        "/log-level",
+        get(|| async { log_level_response(logging::current_filter()) }).put(|Json(input): Json<LogLevelInput>| async move {
+            let r = logging::set_filter(input.filter.as_deref());
+            if let Ok(state) = &r {
+                info!(filter = %state.filter, "log filter changed");
+            }
+            log_level_response(r)
+        }),
+    )
+}
+
+fn profile_response(r: Result<Profile, ProfilingError>) -> Response {
+    match r {
+        Ok(p) => ([(header::CONTENT_TYPE, p.content_type)], p.body).into_response(),
+        Err(e) => {
+            let status = match e {
+                ProfilingError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+                ProfilingError::Busy => StatusCode::CONFLICT,
+                ProfilingError::InvalidParams(_) => StatusCode::BAD_REQUEST,
+                ProfilingError::Failed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, e.to_string()).into_response()
+        }
+    }
+}
+
+/// `GET /debug/pprof/profile`、`GET /debug/pprof/heap`：需 `Authorization: Bearer <profiling.token>`
+pub fn profiling_routes(cfg: ProfilingConfig) -> Router {
+    let authorized = move |headers: &HeaderMap| cfg.authorize(headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()));
+    let heap_authorized = authorized.clone();
+    Router::new()
+        .route(
+            "/debug/pprof/profile",
+            get(move |headers: HeaderMap, Query(params): Query<ProfileParams>| async move {
+                if !authorized(&headers) {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                info!(seconds = ?params.seconds, format = ?params.format, "cpu profile started");
+                profile_response(profiling::cpu_profile(&params).await)
+            }),
+        )
+        .route(
+            "/debug/pprof/heap",
+            get(move |headers: HeaderMap| async move {
+                if !heap_authorized(&headers) {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                match tokio::task::spawn_blocking(profiling::heap_profile).await {
+                    Ok(r) => profile_response(r),
+                    Err(e) => profile_response(Err(ProfilingError::Failed(e.to_string()))),
+                }
+            }),
+        )
+}
+
+/// Spawn an admin HTTP server exposing livez/healthz and metrics endpoints.
 /// The metrics are provided by the caller via a function.
 pub fn spawn_admin_server(addr: &str, metrics_fn: fn() -> (StatusCode, String)) {
+    spawn_admin_server_with(addr, metrics_fn, Router::new());
+}
+
+/// Same as [`spawn_admin_server`], with additional caller routes merged in.
+/// `metrics_fn` may capture state, e.g. an injected metrics registry.
+pub fn spawn_admin_server_with<F>(addr: &str, metrics_fn: F, extra: Router)
+where
+    F: Fn() -> (StatusCode, String) + Clone + Send + Sync + 'static,
+{
     let addr = addr.to_string();
     thread::spawn(move || {
         let rt = Builder::new_multi_thread().enable_all().build().expect("build admin runtime");
         rt.block_on(async move {
-            let mf = metrics_fn;
             let router = Router::new()
+                .route("/livez", get(healthz))
                 .route("/healthz", get(healthz))
-                .route("/metrics", get(move || metrics_handler(mf)));
-            let listener = TcpListener::bind(&addr).await.expect("bind admin");
+                .route("/metrics", get(move || {
+                    let metrics_fn = metrics_fn.clone();
+                    async move { metrics_fn() }
+                }))
+                .merge(extra);
+            let listener = bind_with_retry(&addr).await.expect("bind admin");
             info!(%addr, "admin server listening");
             axum::serve(listener, router).await.expect("serve admin");
         });
@@ -0,0 +1,159 @@
+//! CORS 策略与 CorsLayer 构造，控制面（config.toml `[cors]`）与网关 admin 端口（config.json `cors`）共用。
+//! 未配置来源时按环境取默认：开发模式放行任意来源（回显请求的 Origin），否则只允许同源访问。
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use axum::http::{request::Parts, HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+/// CORS 策略：控制面的 `[cors]` 与网关 admin 端口的 `cors` 共用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// 允许的来源：完整 origin（`https://admin.example.com`）、子域通配（`https://*.example.com`）或 `*`。
+    /// 为空时按环境取默认：`[dev] enabled` 时放行任意来源，否则只允许同源访问
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    /// 允许携带 Cookie；不能与 `*` 来源同时使用
+    pub allow_credentials: bool,
+    /// 预检结果缓存时间
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE"].map(String::from).to_vec(),
+            allowed_headers: ["content-type", "authorization", "x-api-key", "x-csrf-token", "x-request-id"].map(String::from).to_vec(),
+            allow_credentials: true,
+            max_age_secs: 600,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// origin 是否被允许（大小写不敏感；通配只匹配至少一级子域）
+    pub fn origin_allowed(&self, origin: &str) -> bool {
+        let origin = origin.to_ascii_lowercase();
+        self.allowed_origins.iter().any(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            match pattern.split_once("://*.") {
+                Some((scheme, suffix)) => origin
+                    .strip_prefix(scheme)
+                    .and_then(|rest| rest.strip_prefix("://"))
+                    .and_then(|host| host.strip_suffix(suffix))
+                    .and_then(|sub| sub.strip_suffix('.'))
+                    .is_some_and(|sub| !sub.is_empty() && !sub.contains('/')),
+                None => pattern == "*" || pattern == origin,
+            }
+        })
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for origin in &self.allowed_origins {
+            if origin == "*" {
+                if self.allow_credentials {
+                    return Err(anyhow!("cors.allowed_origins 为 * 时不能开启 allow_credentials（浏览器会拒绝）"));
+                }
+                continue;
+            }
+            let Some((scheme, host)) = origin.split_once("://") else {
+                return Err(anyhow!("cors.allowed_origins 中的 {} 缺少 http:// 或 https:// 前缀", origin));
+            };
+            let host = host.strip_prefix("*.").unwrap_or(host);
+            if !matches!(scheme, "http" | "https") || host.is_empty() || host.contains(['/', '*']) {
+                return Err(anyhow!("cors.allowed_origins 中的 {} 不是合法 origin（形如 https://a.example.com 或 https://*.example.com，不带路径）", origin));
+            }
+        }
+        if let Some(m) = self.allowed_methods.iter().find(|m| m.is_empty() || !m.bytes().all(|b| b.is_ascii_uppercase())) {
+            return Err(anyhow!("cors.allowed_methods 中的 {:?} 不是合法的 HTTP 方法（需大写）", m));
+        }
+        if let Some(h) = self.allowed_headers.iter().find(|h| h.is_empty() || !h.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')) {
+            return Err(anyhow!("cors.allowed_headers 中的 {:?} 不是合法的请求头名", h));
+        }
+        Ok(())
+    }
+}
+
+/// 按配置构造 CorsLayer；`dev` 为开发模式（未配置来源时放行任意来源）
+pub fn layer(cfg: &CorsConfig, dev: bool) -> CorsLayer {
+    let origin = if cfg.allowed_origins.is_empty() {
+        if dev { AllowOrigin::mirror_request() } else { AllowOrigin::list(Vec::<HeaderValue>::new()) }
+    } else if cfg.allowed_origins.iter().any(|o| o == "*") && !cfg.allow_credentials {
+        AllowOrigin::any()
+    } else {
+        let cfg = cfg.clone();
+        AllowOrigin::predicate(move |origin: &HeaderValue, _: &Parts| origin.to_str().is_ok_and(|o| cfg.origin_allowed(o)))
+    };
+    let methods: Vec<Method> = cfg
+        .allowed_methods
+        .iter()
+        .filter_map(|m| Method::from_bytes(m.as_bytes()).map_err(|_| warn!(method = %m, "cors.allowed_methods entry ignored")).ok())
+        .collect();
+    let headers: Vec<HeaderName> = cfg
+        .allowed_headers
+        .iter()
+        .filter_map(|h| HeaderName::from_bytes(h.as_bytes()).map_err(|_| warn!(header = %h, "cors.allowed_headers entry ignored")).ok())
+        .collect();
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(cfg.allow_credentials)
+        .max_age(Duration::from_secs(cfg.max_age_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::Service;
+
+    /// 预检请求的 Access-Control-Allow-Origin（未放行时为 None）
+    async fn preflight(cfg: &CorsConfig, dev: bool, origin: &str) -> Option<String> {
+        let mut app = Router::new().route("/admin/stats", get(|| async { "ok" })).layer(layer(cfg, dev));
+        let req = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/admin/stats")
+            .header("origin", origin)
+            .header("access-control-request-method", "POST")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.call(req).await.unwrap();
+        resp.headers().get("access-control-allow-origin").map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn origins_follow_config_and_environment() {
+        let cfg = CorsConfig { allowed_origins: vec!["https://admin.example.com".into(), "https://*.corp.example".into()], ..Default::default() };
+        assert_eq!(preflight(&cfg, false, "https://admin.example.com").await.as_deref(), Some("https://admin.example.com"));
+        assert!(preflight(&cfg, false, "https://ops.eu.corp.example").await.is_some());
+        assert!(preflight(&cfg, false, "https://corp.example").await.is_none());
+        assert!(preflight(&cfg, false, "https://evil.example").await.is_none());
+        // 未配置来源：开发环境放行，生产环境只允许同源
+        let default = CorsConfig::default();
+        assert_eq!(preflight(&default, true, "http://localhost:5173").await.as_deref(), Some("http://localhost:5173"));
+        assert!(preflight(&default, false, "http://localhost:5173").await.is_none());
+    }
+
+    #[test]
+    fn validation_rejects_unsafe_or_malformed_entries() {
+        let with = |origins: &[&str], credentials: bool| CorsConfig {
+            allowed_origins: origins.iter().map(|o| o.to_string()).collect(),
+            allow_credentials: credentials,
+            ..Default::default()
+        };
+        assert!(with(&["https://*.example.com", "http://localhost:3000"], true).validate().is_ok());
+        assert!(with(&["*"], false).validate().is_ok());
+        assert!(with(&["*"], true).validate().is_err());
+        assert!(with(&["admin.example.com"], true).validate().is_err());
+        assert!(with(&["https://example.com/admin"], true).validate().is_err());
+        assert!(with(&["https://a.*.example.com"], true).validate().is_err());
+        assert!(CorsConfig { allowed_methods: vec!["get".into()], ..Default::default() }.validate().is_err());
+    }
+}
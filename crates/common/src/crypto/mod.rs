@@ -0,0 +1,231 @@
+//! 敏感字段的应用层加密（AES-256-GCM）。
+//! - 密文格式 `v1:{key_id}:{base64(nonce || ciphertext || tag)}`，每个值使用随机 nonce
+//! - `MasterKeys` 持有当前主密钥与历史主密钥：新值用当前主密钥加密，旧值按 `key_id` 解密，
+//!   轮换主密钥后由重加密任务把旧值改写为当前主密钥
+//! - 进程启动时 `install` 一次，models 中的加密列类型透明地加解密
+//! - 加密上线前签名密钥使用的旧格式 `v1:{base64}`（无 key_id）可用 [`MasterKeys::with_legacy`] 的密钥读取
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use thiserror::Error;
+
+const PREFIX: &str = "v1:";
+
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+    #[error("unknown key id {0}")]
+    UnknownKey(String),
+    #[error("malformed sealed value")]
+    Malformed,
+    #[error("decryption failed (wrong key or corrupted value)")]
+    Decrypt,
+    #[error("encryption failed")]
+    Encrypt,
+    #[error("no master key installed")]
+    NoMasterKey,
+}
+
+/// 单个 AES-256-GCM 密钥
+pub struct SecretBox {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretBox {
+    pub fn new(key: [u8; 32]) -> Self {
+        let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).expect("32-byte AES-256 key"));
+        Self { key, rng: SystemRandom::new() }
+    }
+
+    /// base64 编码的 32 字节密钥
+    pub fn from_base64(encoded: &str) -> Result<Self, CryptoError> {
+        let bytes = STANDARD.decode(encoded.trim()).map_err(|e| CryptoError::InvalidKey(format!("not base64: {}", e)))?;
+        let key: [u8; 32] = bytes.try_into().map_err(|_| CryptoError::InvalidKey("must be 32 bytes".into()))?;
+        Ok(Self::new(key))
+    }
+
+    /// 口令的 SHA-256；仅用于未配置独立主密钥的部署
+    pub fn derive_from(passphrase: &str) -> Self {
+        let mut key = [0u8; 32];
+        key.copy_from_slice(digest(&SHA256, passphrase.as_bytes()).as_ref());
+        Self::new(key)
+    }
+
+    /// nonce || ciphertext || tag
+    fn seal_raw(&self, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng.fill(&mut nonce).map_err(|_| CryptoError::Encrypt)?;
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+            .map_err(|_| CryptoError::Encrypt)?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&in_out);
+        Ok(out)
+    }
+
+    fn open_raw(&self, bytes: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if bytes.len() < NONCE_LEN {
+            return Err(CryptoError::Malformed);
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| CryptoError::Malformed)?;
+        let mut in_out = ciphertext.to_vec();
+        let plain = self.key.open_in_place(nonce, Aad::empty(), &mut in_out).map_err(|_| CryptoError::Decrypt)?;
+        Ok(plain.to_vec())
+    }
+}
+
+/// 当前主密钥 + 仍需解密的历史主密钥
+pub struct MasterKeys {
+    primary: String,
+    keys: HashMap<String, SecretBox>,
+    legacy: Option<SecretBox>,
+}
+
+impl MasterKeys {
+    pub fn new(key_id: &str, key: SecretBox) -> Self {
+        Self { primary: key_id.to_string(), keys: HashMap::from([(key_id.to_string(), key)]), legacy: None }
+    }
+
+    /// 解密旧格式 `v1:{base64}` 的密钥（升级前的 `auth.key_encryption_key`）
+    pub fn with_legacy(mut self, key: SecretBox) -> Self {
+        self.legacy = Some(key);
+        self
+    }
+
+    /// 轮换前的主密钥，只用于解密
+    pub fn with_previous(mut self, key_id: &str, key: SecretBox) -> Self {
+        self.keys.entry(key_id.to_string()).or_insert(key);
+        self
+    }
+
+    pub fn primary_id(&self) -> &str {
+        &self.primary
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<String, CryptoError> {
+        let raw = self.keys[&self.primary].seal_raw(plaintext)?;
+        Ok(format!("{}{}:{}", PREFIX, self.primary, STANDARD.encode(raw)))
+    }
+
+    pub fn open(&self, sealed: &str) -> Result<Vec<u8>, CryptoError> {
+        let (key_id, raw) = parse(sealed).ok_or(CryptoError::Malformed)?;
+        let key = self.keys.get(key_id).ok_or_else(|| CryptoError::UnknownKey(key_id.to_string()))?;
+        key.open_raw(&raw)
+    }
+
+    /// 旧格式 `v1:{base64}`，见 [`is_legacy_sealed`]
+    pub fn open_legacy(&self, sealed: &str) -> Result<Vec<u8>, CryptoError> {
+        let raw = parse_legacy(sealed).ok_or(CryptoError::Malformed)?;
+        self.legacy.as_ref().ok_or_else(|| CryptoError::UnknownKey("<legacy>".into()))?.open_raw(&raw)
+    }
+
+    /// 已用当前主密钥加密（重加密任务据此跳过）
+    pub fn is_current(&self, value: &str) -> bool {
+        parse(value).is_some_and(|(id, _)| id == self.primary)
+    }
+}
+
+/// 是否为加密值；不是时按明文处理（加密上线前写入的数据）。
+/// 要求完整的密文结构（合法 key_id、base64、至少 nonce + tag 长度），以 `v1:x:` 开头的普通字符串不算
+pub fn is_sealed(value: &str) -> bool {
+    parse(value).is_some()
+}
+
+/// 加密上线前签名密钥的格式：`v1:{base64(nonce || ciphertext || tag)}`，没有 key_id
+pub fn is_legacy_sealed(value: &str) -> bool {
+    parse_legacy(value).is_some()
+}
+
+fn is_key_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// nonce || ciphertext || tag 的最短长度（空明文）
+fn is_sealed_raw(raw: &[u8]) -> bool {
+    raw.len() >= NONCE_LEN + AES_256_GCM.tag_len()
+}
+
+fn parse(value: &str) -> Option<(&str, Vec<u8>)> {
+    let (id, encoded) = value.strip_prefix(PREFIX)?.split_once(':')?;
+    let raw = STANDARD.decode(encoded).ok().filter(|raw| is_key_id(id) && is_sealed_raw(raw))?;
+    Some((id, raw))
+}
+
+fn parse_legacy(value: &str) -> Option<Vec<u8>> {
+    let encoded = value.strip_prefix(PREFIX).filter(|rest| !rest.contains(':'))?;
+    STANDARD.decode(encoded).ok().filter(|raw| is_sealed_raw(raw))
+}
+
+/// 常量时间比较（令牌、签名等），避免按字节提前返回泄露匹配长度
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+static INSTALLED: RwLock<Option<Arc<MasterKeys>>> = RwLock::new(None);
+
+/// 进程级主密钥（启动时设置）
+pub fn install(keys: MasterKeys) {
+    *INSTALLED.write().expect("master keys lock") = Some(Arc::new(keys));
+}
+
+/// 未安装时返回 None：加密列拒绝写入（[`CryptoError::NoMasterKey`]），只能读取明文旧数据
+pub fn installed() -> Option<Arc<MasterKeys>> {
+    INSTALLED.read().expect("master keys lock").clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_round_trips_and_tracks_key_id() {
+        let keys = MasterKeys::new("k1", SecretBox::derive_from("passphrase"));
+        let sealed = keys.seal(b"top secret").unwrap();
+        assert!(sealed.starts_with("v1:k1:") && !sealed.contains("top secret"));
+        assert_ne!(sealed, keys.seal(b"top secret").unwrap(), "nonce must differ per value");
+        assert_eq!(keys.open(&sealed).unwrap(), b"top secret");
+        assert!(keys.is_current(&sealed) && is_sealed(&sealed));
+        assert!(!is_sealed("plain-webhook-secret"));
+        // 形似密文的明文
+        for plain in ["v1:x:", "v1:x:abc", "v1:k1:not base64!", "v1:a b:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"] {
+            assert!(!is_sealed(plain), "{}", plain);
+            assert!(matches!(keys.open(plain), Err(CryptoError::Malformed)));
+        }
+
+        // 轮换：新主密钥加密，旧值仍可按 key_id 解密
+        let rotated = MasterKeys::new("k2", SecretBox::derive_from("next")).with_previous("k1", SecretBox::derive_from("passphrase"));
+        assert_eq!(rotated.open(&sealed).unwrap(), b"top secret");
+        assert!(!rotated.is_current(&sealed));
+        assert!(matches!(MasterKeys::new("k1", SecretBox::derive_from("other")).open(&sealed), Err(CryptoError::Decrypt)));
+        assert!(matches!(MasterKeys::new("k3", SecretBox::derive_from("x")).open(&sealed), Err(CryptoError::UnknownKey(_))));
+    }
+
+    #[test]
+    fn legacy_values_open_with_legacy_key() {
+        let old = SecretBox::derive_from("jwt-secret");
+        let legacy = format!("{}{}", PREFIX, STANDARD.encode(old.seal_raw(b"material").unwrap()));
+        assert!(is_legacy_sealed(&legacy) && !is_sealed(&legacy));
+
+        let keys = MasterKeys::new("k1", SecretBox::derive_from("passphrase"));
+        assert!(matches!(keys.open_legacy(&legacy), Err(CryptoError::UnknownKey(_))));
+        let keys = keys.with_legacy(SecretBox::derive_from("jwt-secret"));
+        assert_eq!(keys.open_legacy(&legacy).unwrap(), b"material");
+        assert!(!is_legacy_sealed(&keys.seal(b"material").unwrap()));
+    }
+
+    #[test]
+    fn keys_from_base64_must_be_32_bytes() {
+        assert!(SecretBox::from_base64(&STANDARD.encode([7u8; 32])).is_ok());
+        assert!(SecretBox::from_base64(&STANDARD.encode([7u8; 16])).is_err());
+        assert!(SecretBox::from_base64("not base64!").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! 自检：`/livez` 只表示进程存活；`/readyz` 汇总各项依赖检查，任一项 `fail` 即返回 503。
+//!
+//! `warn` 表示值得关注但不影响接流量（如服务端尚未配置任何上游）。
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use serde::Serialize;
+
+/// 单项检查超时
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+/// 配置后 `/readyz` 检查 Redis 连通性
+pub const REDIS_URL_ENV: &str = "REDIS_URL";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+    /// 未配置
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl Check {
+    pub fn new(name: &str, status: CheckStatus, detail: impl Into<Option<String>>) -> Self {
+        Self { name: name.to_string(), status, detail: detail.into(), duration_ms: 0 }
+    }
+
+    pub fn ok(name: &str, detail: impl Into<Option<String>>) -> Self {
+        Self::new(name, CheckStatus::Ok, detail)
+    }
+
+    pub fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, Some(detail.into()))
+    }
+}
+
+/// 带超时（[`CHECK_TIMEOUT`]）与耗时统计地执行一项检查
+pub async fn timed<F>(name: &str, check: F) -> Check
+where
+    F: Future<Output = Check>,
+{
+    timed_within(name, CHECK_TIMEOUT, check).await
+}
+
+async fn timed_within<F>(name: &str, limit: Duration, check: F) -> Check
+where
+    F: Future<Output = Check>,
+{
+    let started = Instant::now();
+    let mut c = match tokio::time::timeout(limit, check).await {
+        Ok(c) => c,
+        Err(_) => Check::fail(name, format!("timed out after {}ms", limit.as_millis())),
+    };
+    c.duration_ms = started.elapsed().as_millis() as u64;
+    c
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    /// ready / not_ready
+    pub status: &'static str,
+    pub checks: Vec<Check>,
+}
+
+impl Readiness {
+    pub fn new(checks: Vec<Check>) -> Self {
+        let ready = checks.iter().all(|c| c.status != CheckStatus::Fail);
+        Self { status: if ready { "ready" } else { "not_ready" }, checks }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status == "ready"
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        if self.is_ready() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE }
+    }
+}
+
+/// `redis://[user:pass@]host[:port][/db]` 的 host:port
+fn redis_addr(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("redis://").or_else(|| url.strip_prefix("rediss://"))?;
+    let authority = rest.split('/').next()?;
+    let host_port = authority.rsplit('@').next()?;
+    if host_port.is_empty() {
+        return None;
+    }
+    Some(if host_port.contains(':') { host_port.to_string() } else { format!("{}:6379", host_port) })
+}
+
+/// 设置了 `REDIS_URL` 时检查 TCP 连通性，否则跳过
+pub async fn redis_check() -> Check {
+    let Ok(url) = std::env::var(REDIS_URL_ENV) else {
+        return Check::new("redis", CheckStatus::Skipped, Some("REDIS_URL not set".to_string()));
+    };
+    let Some(addr) = redis_addr(&url) else {
+        return Check::fail("redis", "REDIS_URL is not a redis:// URL");
+    };
+    timed("redis", async {
+        match tokio::net::TcpStream::connect(&addr).await {
+            Ok(_) => Check::ok("redis", Some(addr.clone())),
+            Err(e) => Check::fail("redis", format!("{}: {}", addr, e)),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_failure_makes_not_ready() {
+        let r = Readiness::new(vec![Check::ok("db", None), Check::new("upstreams", CheckStatus::Warn, Some("0 active".to_string()))]);
+        assert!(r.is_ready());
+        let r = Readiness::new(vec![Check::ok("db", None), Check::fail("migrations", "1 pending")]);
+        assert_eq!((r.status, r.status_code()), ("not_ready", StatusCode::SERVICE_UNAVAILABLE));
+    }
+
+    #[test]
+    fn parses_redis_addr() {
+        assert_eq!(redis_addr("redis://cache:6380/0").as_deref(), Some("cache:6380"));
+        assert_eq!(redis_addr("rediss://user:pw@cache").as_deref(), Some("cache:6379"));
+        assert_eq!(redis_addr("http://cache"), None);
+    }
+
+    #[tokio::test]
+    async fn slow_checks_time_out() {
+        let c = timed_within("slow", Duration::from_millis(20), async {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Check::ok("slow", None)
+        })
+        .await;
+        assert_eq!(c.status, CheckStatus::Fail);
+    }
+}
@@ -0,0 +1,127 @@
+//! Embedded echo upstream for local development and tests (feature `echo`).
+//!
+//! Every request is answered with a JSON description of itself. Query parameters
+//! simulate upstream behaviour:
+//! - `latency_ms=N`   sleep N ms before responding
+//! - `status=CODE`    respond with CODE instead of 200
+//! - `error_rate=F`   with probability F (0.0..=1.0) respond with `error_status` (default 500)
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::{
+    body::Bytes,
+    extract::Query,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    Json, Router,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// 单次模拟延迟上限
+const MAX_LATENCY_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EchoResponse {
+    pub method: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+    pub body_len: usize,
+    pub simulated_latency_ms: u64,
+}
+
+pub fn router() -> Router {
+    Router::new().fallback(echo)
+}
+
+async fn echo(method: Method, uri: Uri, headers: HeaderMap, Query(query): Query<HashMap<String, String>>, body: Bytes) -> (StatusCode, Json<EchoResponse>) {
+    let latency_ms = query.get("latency_ms").and_then(|v| v.parse::<u64>().ok()).unwrap_or(0).min(MAX_LATENCY_MS);
+    if latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+    }
+    let status = simulated_status(&query, rand::thread_rng().gen::<f64>());
+    let resp = EchoResponse {
+        method: method.to_string(),
+        path: uri.path().to_string(),
+        query: query.into_iter().collect(),
+        headers: headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned()))
+            .collect(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+        body_len: body.len(),
+        simulated_latency_ms: latency_ms,
+    };
+    (status, Json(resp))
+}
+
+/// `roll` 为 [0, 1) 随机数，命中 error_rate 时返回 error_status
+fn simulated_status(query: &HashMap<String, String>, roll: f64) -> StatusCode {
+    let parse_status = |key: &str, default: StatusCode| {
+        query
+            .get(key)
+            .and_then(|v| v.parse::<u16>().ok())
+            .and_then(|c| StatusCode::from_u16(c).ok())
+            .unwrap_or(default)
+    };
+    let error_rate = query.get("error_rate").and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0).clamp(0.0, 1.0);
+    if roll < error_rate {
+        return parse_status("error_status", StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    parse_status("status", StatusCode::OK)
+}
+
+/// 绑定 `addr` 并在后台任务中运行 echo 服务，返回实际监听地址（端口 0 时由系统分配）
+pub async fn spawn(addr: &str) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await?;
+    let local = listener.local_addr()?;
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router()).await {
+            warn!(error = %e, "echo upstream stopped");
+        }
+    });
+    info!(addr = %local, "echo upstream listening");
+    Ok(local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_simulated_status() {
+        assert_eq!(simulated_status(&q(&[]), 0.0), StatusCode::OK);
+        assert_eq!(simulated_status(&q(&[("status", "201")]), 0.5), StatusCode::CREATED);
+        assert_eq!(simulated_status(&q(&[("error_rate", "0.3")]), 0.2), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(simulated_status(&q(&[("error_rate", "0.3")]), 0.4), StatusCode::OK);
+        assert_eq!(simulated_status(&q(&[("error_rate", "1"), ("error_status", "503")]), 0.99), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_echo_reflects_request() {
+        let addr = spawn("127.0.0.1:0").await.unwrap();
+        let resp = reqwest::Client::new()
+            .post(format!("http://{}/orders/1?status=202&foo=bar", addr))
+            .header("x-demo", "1")
+            .body("hello")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 202);
+        let json: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(json["method"], "POST");
+        assert_eq!(json["path"], "/orders/1");
+        assert_eq!(json["query"]["foo"], "bar");
+        assert_eq!(json["headers"]["x-demo"], "1");
+        assert_eq!(json["body"], "hello");
+    }
+}
@@ -0,0 +1,306 @@
+//! 出站访问控制（防 SSRF）：
+//! - 全局 allowlist（主机名 / CIDR）：创建/修改 proxy API 时校验 forward_target，发送请求前与跟随重定向时再次校验；
+//!   两个列表都为空时不限制主机；
+//! - 私有/环回/链路本地（含云元数据 169.254.169.254）等保留网段默认拒绝，`allowed_cidrs` 可显式放行；
+//!   主机名在保存时解析检查，连接时由 [`EgressResolver`] 对每次 DNS 解析结果再次检查，DNS 应答变化（如 rebinding）后同样生效；
+//! - 经出站代理（`[http_client].proxy`，HTTP CONNECT 或 `socks5://`）转发时由代理解析目标，只检查主机名与 IP 字面量。
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+
+use ipnet::IpNet;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressConfig {
+    /// 允许的主机名，`*.example.com` 匹配任意子域名
+    pub allowed_hosts: Vec<String>,
+    /// 允许的目标网段，如 `10.20.0.0/16`；单个 IP 视为 /32（/128）。命中的私有网段不受 block_private_ranges 限制
+    pub allowed_cidrs: Vec<String>,
+    /// 拒绝私有/环回/链路本地等网段；未设置时生产环境开启、开发模式关闭
+    pub block_private_ranges: Option<bool>,
+}
+
+impl EgressConfig {
+    /// 按环境补齐未设置的项
+    pub fn resolve_for(mut self, dev: bool) -> Self {
+        self.block_private_ranges = Some(self.block_private_ranges.unwrap_or(!dev));
+        self
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EgressError {
+    #[error("invalid CIDR {0}")]
+    InvalidCidr(String),
+    #[error("invalid upstream URL: {0}")]
+    InvalidUrl(String),
+    #[error("destination {0} is not in the egress allowlist")]
+    NotAllowed(String),
+    #[error("destination {0} resolves to a private or reserved address")]
+    Blocked(String),
+    #[error("cannot resolve {0}")]
+    Resolve(String),
+}
+
+/// 默认拒绝的网段：环回、私有、CGNAT、链路本地（云元数据）、组播与保留地址
+const RESERVED_RANGES: &[&str] = &[
+    "0.0.0.0/8", "10.0.0.0/8", "100.64.0.0/10", "127.0.0.0/8", "169.254.0.0/16", "172.16.0.0/12", "192.0.0.0/24",
+    "192.168.0.0/16", "198.18.0.0/15", "224.0.0.0/4", "240.0.0.0/4", "::/128", "::1/128", "fc00::/7", "fe80::/10", "ff00::/8",
+];
+
+fn reserved_ranges() -> &'static [IpNet] {
+    static RANGES: OnceLock<Vec<IpNet>> = OnceLock::new();
+    RANGES.get_or_init(|| RESERVED_RANGES.iter().map(|r| r.parse().expect("valid reserved range")).collect())
+}
+
+/// IPv4-mapped IPv6（`::ffff:a.b.c.d`）按 IPv4 判断
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    }
+}
+
+pub fn is_reserved(ip: IpAddr) -> bool {
+    let ip = canonical(ip);
+    reserved_ranges().iter().any(|net| net.contains(&ip))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EgressPolicy {
+    hosts: Vec<String>,
+    cidrs: Vec<IpNet>,
+    block_private: bool,
+}
+
+impl EgressPolicy {
+    /// 不限制任何目标
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// 无 allowlist，只拒绝私有/保留网段；未 [`install`] 时的默认策略
+    pub fn deny_private() -> Self {
+        Self { block_private: true, ..Self::default() }
+    }
+
+    /// block_private_ranges 未设置时视为开启（调用方应先 [`EgressConfig::resolve_for`]）
+    pub fn from_config(cfg: &EgressConfig) -> Result<Self, EgressError> {
+        let cidrs = cfg
+            .allowed_cidrs
+            .iter()
+            .map(|c| {
+                let c = c.trim();
+                c.parse::<IpNet>().or_else(|_| c.parse::<IpAddr>().map(IpNet::from)).map_err(|_| EgressError::InvalidCidr(c.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        let hosts = cfg.allowed_hosts.iter().map(|h| h.trim().trim_end_matches('.').to_ascii_lowercase()).filter(|h| !h.is_empty()).collect();
+        Ok(Self { hosts, cidrs, block_private: cfg.block_private_ranges.unwrap_or(true) })
+    }
+
+    fn has_allowlist(&self) -> bool {
+        !self.hosts.is_empty() || !self.cidrs.is_empty()
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.has_allowlist() || self.block_private
+    }
+
+    fn in_allowed_cidrs(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.cidrs.iter().any(|net| net.contains(&ip))
+    }
+
+    /// DNS 解析出的地址：allowed_cidrs 放行，其余只拒绝保留网段
+    pub fn check_resolved(&self, host: &str, ip: IpAddr) -> Result<(), EgressError> {
+        if self.block_private && is_reserved(ip) && !self.in_allowed_cidrs(ip) {
+            return Err(EgressError::Blocked(format!("{} ({})", host, ip)));
+        }
+        Ok(())
+    }
+
+    /// IP 字面量目标：需命中 allowed_cidrs（配置了 allowlist 时），且不在被拒绝的保留网段
+    pub fn check_ip(&self, ip: IpAddr) -> Result<(), EgressError> {
+        if self.in_allowed_cidrs(ip) {
+            return Ok(());
+        }
+        self.check_resolved(&ip.to_string(), ip)?;
+        if self.has_allowlist() {
+            return Err(EgressError::NotAllowed(ip.to_string()));
+        }
+        Ok(())
+    }
+
+    /// 主机名按 allowed_hosts 判断（不解析）；IP 字面量按 [`Self::check_ip`]
+    pub fn check_host(&self, host: &str) -> Result<(), EgressError> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self.check_ip(ip);
+        }
+        if !self.has_allowlist() {
+            return Ok(());
+        }
+        let name = host.trim_end_matches('.').to_ascii_lowercase();
+        let allowed = self.hosts.iter().any(|pattern| match pattern.strip_prefix("*.") {
+            Some(suffix) => name.len() > suffix.len() && name.ends_with(suffix) && name[..name.len() - suffix.len()].ends_with('.'),
+            None => *pattern == name,
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err(EgressError::NotAllowed(host.to_string()))
+        }
+    }
+
+    /// 校验完整 URL 的目标主机（不解析 DNS）
+    pub fn check_url(&self, url: &str) -> Result<(), EgressError> {
+        if !self.is_restricted() {
+            return Ok(());
+        }
+        self.check_host(&url_host(url)?)
+    }
+
+    /// [`Self::check_url`] 之外再解析主机名并检查每个地址；保存 forward_target 时使用
+    pub async fn check_url_resolved(&self, url: &str) -> Result<(), EgressError> {
+        self.check_url(url)?;
+        if !self.block_private {
+            return Ok(());
+        }
+        let host = url_host(url)?;
+        if host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok() {
+            return Ok(());
+        }
+        for addr in lookup(&host).await? {
+            self.check_resolved(&host, addr.ip())?;
+        }
+        Ok(())
+    }
+}
+
+fn url_host(url: &str) -> Result<String, EgressError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| EgressError::InvalidUrl(e.to_string()))?;
+    parsed.host_str().map(str::to_string).ok_or_else(|| EgressError::InvalidUrl(format!("{} has no host", url)))
+}
+
+async fn lookup(host: &str) -> Result<Vec<SocketAddr>, EgressError> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await.map_err(|e| EgressError::Resolve(format!("{}: {}", host, e)))?.collect();
+    if addrs.is_empty() {
+        return Err(EgressError::Resolve(host.to_string()));
+    }
+    Ok(addrs)
+}
+
+/// reqwest DNS 解析器：每次建立连接前解析并检查地址，任一地址命中被拒绝的网段即失败（防 DNS rebinding）
+#[derive(Debug, Clone)]
+pub struct EgressResolver {
+    policy: Arc<EgressPolicy>,
+}
+
+impl EgressResolver {
+    pub fn new(policy: Arc<EgressPolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Resolve for EgressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = Arc::clone(&self.policy);
+        Box::pin(async move {
+            let host = name.as_str();
+            let addrs = lookup(host).await?;
+            for addr in &addrs {
+                policy.check_resolved(host, addr.ip())?;
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+static GLOBAL: OnceLock<Arc<EgressPolicy>> = OnceLock::new();
+
+/// 设置进程级出站策略；只有第一次调用生效
+pub fn install(policy: EgressPolicy) {
+    let _ = GLOBAL.set(Arc::new(policy));
+}
+
+/// 进程级出站策略；未 [`install`]（如配置加载失败）时按 [`EgressPolicy::deny_private`] 拒绝私有网段，而不是放行所有目标
+pub fn global() -> Arc<EgressPolicy> {
+    GLOBAL.get_or_init(|| Arc::new(EgressPolicy::deny_private())).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(hosts: &[&str], cidrs: &[&str]) -> EgressPolicy {
+        let cfg = EgressConfig {
+            allowed_hosts: hosts.iter().map(|s| s.to_string()).collect(),
+            allowed_cidrs: cidrs.iter().map(|s| s.to_string()).collect(),
+            block_private_ranges: None,
+        };
+        EgressPolicy::from_config(&cfg).unwrap()
+    }
+
+    #[test]
+    fn matches_hosts_wildcards_and_cidrs() {
+        let p = policy(&["api.partner.com", "*.internal.example"], &["10.20.0.0/16", "2001:db8::/32", "203.0.113.7"]);
+        assert!(p.check_url("https://api.partner.com/v1").is_ok());
+        assert!(p.check_url("https://API.partner.com./v1").is_ok());
+        assert!(p.check_url("http://orders.internal.example:8080/x").is_ok());
+        assert!(p.check_url("http://internal.example/x").is_err());
+        assert!(p.check_url("http://evilinternal.example/x").is_err());
+        // 显式放行的私有网段
+        assert!(p.check_url("http://10.20.3.4/x").is_ok());
+        assert!(p.check_url("http://203.0.113.7/x").is_ok());
+        assert!(p.check_url("http://[2001:db8::1]:9000/").is_ok());
+        assert_eq!(p.check_url("http://198.51.100.1/x"), Err(EgressError::NotAllowed("198.51.100.1".into())));
+        assert!(matches!(p.check_url("http://10.21.0.1/x"), Err(EgressError::Blocked(_))));
+    }
+
+    #[test]
+    fn blocks_reserved_ranges_unless_allowed() {
+        let p = policy(&[], &[]);
+        assert!(p.is_restricted());
+        for url in ["http://169.254.169.254/latest/meta-data", "http://127.0.0.1:6379", "http://[::1]/", "http://[::ffff:10.0.0.1]/", "http://[fd00:ec2::254]/", "http://0.0.0.0/"] {
+            assert!(matches!(p.check_url(url), Err(EgressError::Blocked(_))), "{}", url);
+        }
+        assert!(p.check_url("http://93.184.216.34/").is_ok());
+        assert!(p.check_url("https://example.com/").is_ok());
+
+        let opted_out = EgressPolicy::from_config(&EgressConfig::default().resolve_for(true)).unwrap();
+        assert!(!opted_out.is_restricted());
+        assert!(opted_out.check_url("http://127.0.0.1/x").is_ok());
+    }
+
+    #[test]
+    fn default_policy_denies_private_ranges() {
+        let p = EgressPolicy::deny_private();
+        assert!(matches!(p.check_url("http://169.254.169.254/latest/meta-data"), Err(EgressError::Blocked(_))));
+        assert!(p.check_url("https://example.com/").is_ok());
+    }
+
+    #[tokio::test]
+    async fn resolved_addresses_are_checked() {
+        let p = policy(&[], &[]);
+        assert!(matches!(p.check_url_resolved("http://localhost:8080/").await, Err(EgressError::Blocked(_))));
+        let allowed = policy(&["localhost"], &["127.0.0.0/8", "::1/128"]);
+        assert!(allowed.check_url_resolved("http://localhost:8080/").await.is_ok());
+
+        let resolver = EgressResolver::new(Arc::new(p));
+        let err = resolver.resolve("localhost".parse().unwrap()).await.err().unwrap();
+        assert!(err.to_string().contains("private or reserved"));
+    }
+
+    #[test]
+    fn empty_policy_allows_everything_and_bad_cidr_is_rejected() {
+        let p = EgressPolicy::allow_all();
+        assert!(!p.is_restricted());
+        assert!(p.check_url("http://127.0.0.1/x").is_ok());
+        let bad = EgressConfig { allowed_cidrs: vec!["10.0.0.0/33".into()], ..Default::default() };
+        assert!(matches!(EgressPolicy::from_config(&bad), Err(EgressError::InvalidCidr(_))));
+    }
+}
@@ -0,0 +1,154 @@
+//! 按租户灰度新功能的特性开关。网关（config.json `feature_flags`）与控制面（`feature_flag` 表）共用同一套判定：
+//! 1. 租户覆盖（强制开/关）
+//! 2. 百分比灰度：`bucket(flag, tenant) < rollout_percent`，同一租户在同一开关上的分桶稳定
+//! 3. 默认值
+//!
+//! 未定义的开关返回调用方给出的兜底值，旧配置不受影响。
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// 网关：按租户启用 bandit 上游选择（需 `[bandit] enabled`）
+pub const GATEWAY_BANDIT: &str = "gateway.bandit_selection";
+/// 控制面：数据面转发时经读缓存加载租户
+pub const SERVER_READ_CACHE: &str = "server.read_cache";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagRule {
+    pub name: String,
+    #[serde(default)]
+    pub default_enabled: bool,
+    /// 0-100；超过 100 按 100 处理
+    #[serde(default)]
+    pub rollout_percent: u8,
+    /// 租户 ID -> 强制开/关
+    #[serde(default)]
+    pub overrides: HashMap<String, bool>,
+}
+
+/// 判定来源，便于排查“为什么这个租户开着”
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    Override,
+    Rollout,
+    Default,
+    /// 开关未定义
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub enabled: bool,
+    pub reason: Reason,
+}
+
+impl FlagRule {
+    pub fn evaluate(&self, tenant: Option<&str>) -> Evaluation {
+        if let Some(&enabled) = tenant.and_then(|t| self.overrides.get(t)) {
+            return Evaluation { enabled, reason: Reason::Override };
+        }
+        if let Some(t) = tenant {
+            if self.rollout_percent > 0 && bucket(&self.name, t) < self.rollout_percent.min(100) {
+                return Evaluation { enabled: true, reason: Reason::Rollout };
+            }
+        }
+        Evaluation { enabled: self.default_enabled, reason: Reason::Default }
+    }
+}
+
+/// 开关集合；序列化为 `FlagRule` 列表
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<FlagRule>", into = "Vec<FlagRule>")]
+pub struct FlagSet {
+    rules: HashMap<String, FlagRule>,
+}
+
+impl From<Vec<FlagRule>> for FlagSet {
+    fn from(rules: Vec<FlagRule>) -> Self {
+        Self { rules: rules.into_iter().map(|r| (r.name.clone(), r)).collect() }
+    }
+}
+
+impl From<FlagSet> for Vec<FlagRule> {
+    fn from(set: FlagSet) -> Self {
+        let mut rules: Vec<FlagRule> = set.rules.into_values().collect();
+        rules.sort_by(|a, b| a.name.cmp(&b.name));
+        rules
+    }
+}
+
+impl FlagSet {
+    pub fn get(&self, name: &str) -> Option<&FlagRule> {
+        self.rules.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn evaluate(&self, name: &str, tenant: Option<&str>) -> Evaluation {
+        match self.rules.get(name) {
+            Some(rule) => rule.evaluate(tenant),
+            None => Evaluation { enabled: false, reason: Reason::Unknown },
+        }
+    }
+
+    /// 开关未定义时返回 `fallback`
+    pub fn enabled_or(&self, name: &str, tenant: Option<&str>, fallback: bool) -> bool {
+        self.rules.get(name).map(|r| r.evaluate(tenant).enabled).unwrap_or(fallback)
+    }
+}
+
+/// 租户在某个开关上的分桶（0-99），FNV-1a(`flag:tenant`)，跨进程与版本稳定
+pub fn bucket(flag: &str, tenant: &str) -> u8 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in flag.as_bytes().iter().chain(b":").chain(tenant.as_bytes()) {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(default_enabled: bool, rollout_percent: u8) -> FlagRule {
+        FlagRule { name: "new_limiter".into(), default_enabled, rollout_percent, overrides: HashMap::new() }
+    }
+
+    #[test]
+    fn override_beats_rollout_beats_default() {
+        let mut r = rule(false, 100);
+        r.overrides.insert("t-off".into(), false);
+        assert_eq!(r.evaluate(Some("t-off")), Evaluation { enabled: false, reason: Reason::Override });
+        assert_eq!(r.evaluate(Some("t-any")), Evaluation { enabled: true, reason: Reason::Rollout });
+        // 无租户的请求不参与灰度
+        assert_eq!(r.evaluate(None), Evaluation { enabled: false, reason: Reason::Default });
+        assert_eq!(rule(true, 0).evaluate(Some("t-any")), Evaluation { enabled: true, reason: Reason::Default });
+    }
+
+    #[test]
+    fn rollout_is_stable_and_roughly_proportional() {
+        let r = rule(false, 30);
+        let tenants: Vec<String> = (0..2000).map(|i| format!("tenant-{}", i)).collect();
+        let on = tenants.iter().filter(|t| r.evaluate(Some(t)).enabled).count();
+        assert!((500..700).contains(&on), "{} of 2000 enabled", on);
+        assert!(tenants.iter().all(|t| r.evaluate(Some(t)) == r.evaluate(Some(t))));
+        // 扩大灰度只会新增租户，不会把已开启的租户关掉
+        let wider = rule(false, 60);
+        assert!(tenants.iter().filter(|t| r.evaluate(Some(t)).enabled).all(|t| wider.evaluate(Some(t)).enabled));
+    }
+
+    #[test]
+    fn set_falls_back_for_unknown_flags() {
+        let set: FlagSet = serde_json::from_str(r#"[{"name":"a","default_enabled":true}]"#).unwrap();
+        assert!(set.enabled_or("a", Some("t"), false));
+        assert!(!set.enabled_or("missing", Some("t"), false));
+        assert!(set.enabled_or("missing", Some("t"), true));
+        assert_eq!(set.evaluate("missing", None).reason, Reason::Unknown);
+        assert_eq!(serde_json::to_value(&set).unwrap()[0]["name"], "a");
+    }
+}
@@ -0,0 +1,319 @@
+//! 上游 HTTP 客户端：[`HttpClientFactory`] 按统一配置（连接池、超时、出站代理）构建 reqwest 客户端，
+//! [`UpstreamClient`] 在其上提供类型化的请求/响应与重试钩子。
+//!
+//! reqwest::Client 内部是共享连接池，构建一次后 clone 使用，不要按请求新建。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::http::{HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::egress::{EgressError, EgressPolicy, EgressResolver};
+
+/// 与 reqwest 默认重定向上限一致
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    pub connect_timeout_ms: u64,
+    /// 整个请求（含读取响应体）的超时
+    pub timeout_ms: u64,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    /// 出站代理，如 `http://proxy.internal:3128`（HTTP CONNECT）或 `socks5h://proxy.internal:1080`；未设置时遵循 `HTTPS_PROXY` 等环境变量
+    pub proxy: Option<String>,
+    /// 不走代理的主机，逗号分隔（同 `NO_PROXY`）
+    pub no_proxy: Option<String>,
+    pub user_agent: String,
+    /// 幂等请求失败（连接错误或 `retry_statuses`）后的重试次数，0 表示不重试
+    pub retries: u32,
+    pub retry_backoff_ms: u64,
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 2_000,
+            timeout_ms: 10_000,
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_secs: 90,
+            proxy: None,
+            no_proxy: None,
+            user_agent: concat!("api-proxy/", env!("CARGO_PKG_VERSION")).to_string(),
+            retries: 0,
+            retry_backoff_ms: 100,
+            retry_statuses: vec![502, 503, 504],
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HttpClientError {
+    #[error("invalid proxy: {0}")]
+    InvalidProxy(String),
+    #[error("build http client: {0}")]
+    Build(String),
+    #[error("upstream request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Egress(#[from] EgressError),
+}
+
+/// 按 [`HttpClientConfig`] 构建客户端；需要额外设置（如 HTTP/2 prior knowledge）时用 [`HttpClientFactory::builder`]
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientFactory {
+    config: HttpClientConfig,
+    egress: Arc<EgressPolicy>,
+}
+
+impl HttpClientFactory {
+    pub fn new(config: HttpClientConfig) -> Self {
+        Self { config, egress: Arc::default() }
+    }
+
+    /// 出站策略：发送前与跟随重定向时校验目标，连接时检查 DNS 解析结果
+    pub fn with_egress(mut self, egress: Arc<EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    pub fn config(&self) -> &HttpClientConfig {
+        &self.config
+    }
+
+    /// 已应用连接池、超时与代理设置的 builder
+    pub fn builder(&self) -> Result<reqwest::ClientBuilder, HttpClientError> {
+        let c = &self.config;
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(c.connect_timeout_ms))
+            .timeout(Duration::from_millis(c.timeout_ms))
+            .pool_max_idle_per_host(c.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(c.pool_idle_timeout_secs))
+            .user_agent(c.user_agent.clone());
+        if let Some(url) = c.proxy.as_deref().filter(|u| !u.is_empty()) {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| HttpClientError::InvalidProxy(e.to_string()))?
+                .no_proxy(c.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string));
+            builder = builder.proxy(proxy);
+        }
+        if self.egress.is_restricted() {
+            builder = builder.dns_resolver(Arc::new(EgressResolver::new(Arc::clone(&self.egress))));
+            let egress = Arc::clone(&self.egress);
+            builder = builder.redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if let Err(e) = egress.check_url(attempt.url().as_str()) {
+                    attempt.error(e)
+                } else {
+                    attempt.follow()
+                }
+            }));
+        }
+        Ok(builder)
+    }
+
+    pub fn build(&self) -> Result<reqwest::Client, HttpClientError> {
+        self.builder()?.build().map_err(|e| HttpClientError::Build(e.to_string()))
+    }
+
+    /// 带默认重试策略的类型化客户端
+    pub fn upstream(&self) -> Result<UpstreamClient, HttpClientError> {
+        Ok(UpstreamClient::new(self.build()?).with_retry(Arc::new(RetryPolicy::from_config(&self.config))).with_egress(Arc::clone(&self.egress)))
+    }
+}
+
+/// 一次尝试的结果：拿到响应时为状态码，否则为传输错误
+pub enum Attempt<'a> {
+    Status(StatusCode),
+    Error(&'a reqwest::Error),
+}
+
+/// 重试钩子：返回 `Some(delay)` 表示等待后重试；`attempt` 从 1 开始
+pub trait RetryHook: Send + Sync {
+    fn retry_after(&self, method: &Method, attempt: u32, outcome: Attempt<'_>) -> Option<Duration>;
+}
+
+/// 只重试幂等方法；连接/超时错误与 `statuses` 中的状态码可重试，退避按次数线性增长
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub backoff: Duration,
+    pub statuses: Vec<u16>,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self { retries: 0, backoff: Duration::ZERO, statuses: Vec::new() }
+    }
+
+    pub fn from_config(c: &HttpClientConfig) -> Self {
+        Self { retries: c.retries, backoff: Duration::from_millis(c.retry_backoff_ms), statuses: c.retry_statuses.clone() }
+    }
+}
+
+impl RetryHook for RetryPolicy {
+    fn retry_after(&self, method: &Method, attempt: u32, outcome: Attempt<'_>) -> Option<Duration> {
+        let idempotent = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE);
+        let retryable = match outcome {
+            Attempt::Status(s) => self.statuses.contains(&s.as_u16()),
+            Attempt::Error(e) => e.is_connect() || e.is_timeout(),
+        };
+        (idempotent && retryable && attempt <= self.retries).then(|| self.backoff * attempt)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpstreamRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl UpstreamRequest {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self { method, url: url.into(), headers: HeaderMap::new(), body: Bytes::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpstreamResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl UpstreamResponse {
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get(axum::http::header::CONTENT_TYPE).and_then(|v| v.to_str().ok())
+    }
+}
+
+/// 共享连接池上的类型化上游客户端；clone 代价很低
+#[derive(Clone)]
+pub struct UpstreamClient {
+    client: reqwest::Client,
+    retry: Arc<dyn RetryHook>,
+    egress: Arc<EgressPolicy>,
+}
+
+impl UpstreamClient {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client, retry: Arc::new(RetryPolicy::none()), egress: Arc::default() }
+    }
+
+    pub fn with_egress(mut self, egress: Arc<EgressPolicy>) -> Self {
+        self.egress = egress;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Arc<dyn RetryHook>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// 发送请求并读完响应体；按重试钩子决定是否重试
+    pub async fn send(&self, req: UpstreamRequest) -> Result<UpstreamResponse, HttpClientError> {
+        self.egress.check_url(&req.url)?;
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .client
+                .request(req.method.clone(), &req.url)
+                .headers(req.headers.clone())
+                .body(req.body.clone())
+                .send()
+                .await;
+            let delay = match &result {
+                Ok(resp) => self.retry.retry_after(&req.method, attempt, Attempt::Status(resp.status())),
+                Err(e) => self.retry.retry_after(&req.method, attempt, Attempt::Error(e)),
+            };
+            match (result, delay) {
+                (Ok(resp), None) => {
+                    let status = resp.status();
+                    let headers = resp.headers().clone();
+                    let body = resp.bytes().await?;
+                    return Ok(UpstreamResponse { status, headers, body });
+                }
+                (Err(e), None) => return Err(e.into()),
+                (_, Some(delay)) => {
+                    debug!(method = %req.method, attempt, delay_ms = delay.as_millis() as u64, "retrying upstream request");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_only_idempotent_methods_within_budget() {
+        let policy = RetryPolicy { retries: 2, backoff: Duration::from_millis(10), statuses: vec![503] };
+        let unavailable = || Attempt::Status(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(policy.retry_after(&Method::GET, 1, unavailable()), Some(Duration::from_millis(10)));
+        assert_eq!(policy.retry_after(&Method::GET, 2, unavailable()), Some(Duration::from_millis(20)));
+        assert_eq!(policy.retry_after(&Method::GET, 3, unavailable()), None);
+        assert_eq!(policy.retry_after(&Method::POST, 1, unavailable()), None);
+        assert_eq!(policy.retry_after(&Method::GET, 1, Attempt::Status(StatusCode::INTERNAL_SERVER_ERROR)), None);
+    }
+
+    #[test]
+    fn rejects_invalid_proxy() {
+        let factory = HttpClientFactory::new(HttpClientConfig { proxy: Some("::not a url::".into()), ..Default::default() });
+        assert!(matches!(factory.build(), Err(HttpClientError::InvalidProxy(_))));
+        assert!(HttpClientFactory::default().build().is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_destinations_and_redirects_outside_egress_allowlist() {
+        use crate::egress::EgressConfig;
+
+        let app = axum::Router::new()
+            .route("/ok", axum::routing::get(|| async { "ok" }))
+            .route("/redirect", axum::routing::get(|| async { axum::response::Redirect::temporary("http://blocked.example/x") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let egress = EgressPolicy::from_config(&EgressConfig { allowed_cidrs: vec!["127.0.0.0/8".into()], ..Default::default() }).unwrap();
+        let client = HttpClientFactory::default().with_egress(Arc::new(egress)).upstream().unwrap();
+        let ok = client.send(UpstreamRequest::new(Method::GET, format!("http://{}/ok", addr))).await.unwrap();
+        assert_eq!(ok.status, StatusCode::OK);
+        let denied = client.send(UpstreamRequest::new(Method::GET, "http://198.51.100.1/x")).await;
+        assert!(matches!(denied, Err(HttpClientError::Egress(EgressError::NotAllowed(_)))));
+        let redirected = client.send(UpstreamRequest::new(Method::GET, format!("http://{}/redirect", addr))).await;
+        assert!(matches!(redirected, Err(HttpClientError::Request(e)) if e.is_redirect()));
+    }
+
+    #[tokio::test]
+    async fn retries_retryable_status_then_returns_last_response() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        static HITS: AtomicU32 = AtomicU32::new(0);
+        let app = axum::Router::new().fallback(|| async {
+            if HITS.fetch_add(1, Ordering::SeqCst) == 0 { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let factory = HttpClientFactory::new(HttpClientConfig { retries: 1, retry_backoff_ms: 1, ..Default::default() });
+        let client = factory.upstream().unwrap();
+        let resp = client.send(UpstreamRequest::new(Method::GET, format!("http://{}/x", addr))).await.unwrap();
+        assert_eq!((resp.status, HITS.load(Ordering::SeqCst)), (StatusCode::OK, 2));
+    }
+}
@@ -6,6 +6,21 @@ pub mod utils;
 pub mod pagination;
 pub mod env;
 pub mod admin_http;
+pub mod time;
+pub mod routing;
+pub mod redaction;
+pub mod diagnostics;
+pub mod profiling;
+pub mod http_client;
+pub mod egress;
+pub mod lock;
+pub mod pubsub;
+pub mod feature_flags;
+pub mod rate_limit;
+pub mod cors;
+pub mod siem;
+#[cfg(feature = "echo")]
+pub mod echo;
 
 #[derive(Debug, Error)]
 pub enum CoreError {
@@ -15,34 +30,6 @@ pub enum CoreError {
     Parse(String),
 }
 
-pub mod posts {
-    use super::*;
-
-    pub async fn fetch_posts() -> Result<serde_json::Value, CoreError> {
-        let url = "https://jsonplaceholder.typicode.com/posts";
-        let resp = reqwest::get(url)
-            .await
-            .map_err(|e| CoreError::Network(e.to_string()))?;
-        let json = resp
-            .json::<serde_json::Value>()
-            .await
-            .map_err(|e| CoreError::Parse(e.to_string()))?;
-        Ok(json)
-    }
-
-    pub async fn fetch_post(id: u32) -> Result<serde_json::Value, CoreError> {
-        let url = format!("https://jsonplaceholder.typicode.com/posts/{id}");
-        let resp = reqwest::get(&url)
-            .await
-            .map_err(|e| CoreError::Network(e.to_string()))?;
-        let json = resp
-            .json::<serde_json::Value>()
-            .await
-            .map_err(|e| CoreError::Parse(e.to_string()))?;
-        Ok(json)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,187 @@
+//! 多副本协调用的分布式锁：定时任务、缓存预热等在整个集群只跑一份。
+//! - `LocalLock`：进程内互斥，单副本或测试使用
+//! - `PgAdvisoryLock`（feature `postgres`）：Postgres 事务级 advisory lock，持有者断连即释放
+//! - `RedisLock`：`SET key token NX PX ttl`，按 token 比较后删除，TTL 到期自动释放
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod redis;
+
+#[cfg(feature = "postgres")]
+pub use postgres::PgAdvisoryLock;
+pub use redis::RedisLock;
+
+#[derive(Debug, Error)]
+pub enum LockError {
+    #[error("lock backend unavailable: {0}")]
+    Backend(String),
+    #[error("lock protocol error: {0}")]
+    Protocol(String),
+}
+
+/// 已获得的锁由具体后端释放
+#[async_trait]
+pub trait LockLease: Send {
+    async fn release(self: Box<Self>) -> Result<(), LockError>;
+}
+
+/// 非阻塞的命名锁；拿不到返回 `Ok(None)`，由调用方决定跳过还是稍后重试
+#[async_trait]
+pub trait DistributedLock: Send + Sync {
+    /// `ttl` 为持有上限：Redis 到期自动释放；Postgres 在连接或事务结束时释放，忽略 `ttl`
+    async fn try_acquire(&self, name: &str, ttl: Duration) -> Result<Option<LockGuard>, LockError>;
+
+    /// 后端名称（日志与 /readyz 展示）
+    fn backend(&self) -> &'static str;
+}
+
+/// 持有中的锁。优先显式 `release().await`；直接 drop 时在当前 runtime 上异步释放
+pub struct LockGuard {
+    name: String,
+    lease: Option<Box<dyn LockLease>>,
+}
+
+impl LockGuard {
+    pub fn new(name: impl Into<String>, lease: Box<dyn LockLease>) -> Self {
+        Self { name: name.into(), lease: Some(lease) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub async fn release(mut self) -> Result<(), LockError> {
+        match self.lease.take() {
+            Some(lease) => lease.release().await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let Some(lease) = self.lease.take() else { return };
+        let name = std::mem::take(&mut self.name);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(e) = lease.release().await {
+                        warn!(lock = %name, err = %e, "release dropped lock failed");
+                    }
+                });
+            }
+            // 没有 runtime 时只能等后端自行过期（Redis TTL / Postgres 断连）
+            Err(_) => warn!(lock = %name, "lock dropped outside a runtime; relying on backend expiry"),
+        }
+    }
+}
+
+/// 拿到锁才执行 `task`，结束后释放。未拿到或锁后端出错时跳过并返回 None（后端错误记录告警）；
+/// `lock` 为 None 表示单副本部署，直接执行
+pub async fn run_exclusive<Fut: Future>(lock: Option<&dyn DistributedLock>, name: &str, ttl: Duration, task: Fut) -> Option<Fut::Output> {
+    let Some(lock) = lock else { return Some(task.await) };
+    let guard = match lock.try_acquire(name, ttl).await {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            debug!(lock = name, backend = lock.backend(), "lock held elsewhere; skipping");
+            return None;
+        }
+        Err(e) => {
+            warn!(lock = name, backend = lock.backend(), err = %e, "acquire lock failed; skipping");
+            return None;
+        }
+    };
+    let out = task.await;
+    if let Err(e) = guard.release().await {
+        warn!(lock = name, backend = lock.backend(), err = %e, "release lock failed");
+    }
+    Some(out)
+}
+
+/// 进程内锁，语义与分布式实现一致（忽略 ttl）
+#[derive(Default, Clone)]
+pub struct LocalLock {
+    held: Arc<Mutex<HashSet<String>>>,
+}
+
+struct LocalLease {
+    held: Arc<Mutex<HashSet<String>>>,
+    name: String,
+}
+
+#[async_trait]
+impl LockLease for LocalLease {
+    async fn release(self: Box<Self>) -> Result<(), LockError> {
+        self.held.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.name);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DistributedLock for LocalLock {
+    async fn try_acquire(&self, name: &str, _ttl: Duration) -> Result<Option<LockGuard>, LockError> {
+        if !self.held.lock().unwrap_or_else(|e| e.into_inner()).insert(name.to_string()) {
+            return Ok(None);
+        }
+        let lease = LocalLease { held: Arc::clone(&self.held), name: name.to_string() };
+        Ok(Some(LockGuard::new(name, Box::new(lease))))
+    }
+
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+}
+
+/// 锁名 -> 64 位键（FNV-1a），跨版本与跨进程稳定
+pub fn lock_key(name: &str) -> i64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in name.as_bytes() {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_lock_is_exclusive_until_released() {
+        let lock = LocalLock::default();
+        let ttl = Duration::from_secs(1);
+        let g = lock.try_acquire("job", ttl).await.unwrap().expect("first acquire");
+        assert!(lock.try_acquire("job", ttl).await.unwrap().is_none());
+        assert!(lock.try_acquire("other", ttl).await.unwrap().is_some());
+        g.release().await.unwrap();
+        assert!(lock.try_acquire("job", ttl).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn run_exclusive_skips_when_held() {
+        let lock = LocalLock::default();
+        let ttl = Duration::from_secs(1);
+        let _held = lock.try_acquire("job", ttl).await.unwrap().unwrap();
+        assert_eq!(run_exclusive(Some(&lock), "job", ttl, async { 1 }).await, None);
+        assert_eq!(run_exclusive(Some(&lock), "free", ttl, async { 2 }).await, Some(2));
+        // released after the task, so it can run again
+        assert_eq!(run_exclusive(Some(&lock), "free", ttl, async { 3 }).await, Some(3));
+        assert_eq!(run_exclusive(None, "job", ttl, async { 4 }).await, Some(4));
+    }
+
+    #[test]
+    fn lock_key_is_stable() {
+        assert_eq!(lock_key(""), 0xcbf2_9ce4_8422_2325_u64 as i64);
+        assert_eq!(lock_key("a"), 0xaf63_dc4c_8601_ec8c_u64 as i64);
+        assert_ne!(lock_key("metrics_rollup"), lock_key("config_backup"));
+    }
+}
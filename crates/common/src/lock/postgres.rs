@@ -0,0 +1,50 @@
+//! Postgres advisory lock：`pg_try_advisory_xact_lock` 在独立事务里获取，提交或回滚即释放。
+//! 持有期间占用连接池中的一个连接；进程崩溃时连接断开，锁随之释放。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, Statement, TransactionTrait};
+
+use super::{lock_key, DistributedLock, LockError, LockGuard, LockLease};
+
+pub struct PgAdvisoryLock {
+    db: DatabaseConnection,
+}
+
+impl PgAdvisoryLock {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+struct PgLease {
+    txn: DatabaseTransaction,
+}
+
+#[async_trait]
+impl LockLease for PgLease {
+    async fn release(self: Box<Self>) -> Result<(), LockError> {
+        self.txn.commit().await.map_err(|e| LockError::Backend(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl DistributedLock for PgAdvisoryLock {
+    async fn try_acquire(&self, name: &str, _ttl: Duration) -> Result<Option<LockGuard>, LockError> {
+        let backend = |e: sea_orm::DbErr| LockError::Backend(e.to_string());
+        let txn = self.db.begin().await.map_err(backend)?;
+        let stmt = Statement::from_sql_and_values(DbBackend::Postgres, "SELECT pg_try_advisory_xact_lock($1) AS locked", [lock_key(name).into()]);
+        let row = txn.query_one(stmt).await.map_err(backend)?.ok_or_else(|| LockError::Protocol("empty advisory lock result".into()))?;
+        let locked: bool = row.try_get("", "locked").map_err(backend)?;
+        if !locked {
+            txn.rollback().await.map_err(backend)?;
+            return Ok(None);
+        }
+        Ok(Some(LockGuard::new(name, Box::new(PgLease { txn }))))
+    }
+
+    fn backend(&self) -> &'static str {
+        "postgres"
+    }
+}
@@ -0,0 +1,266 @@
+//! Redis 锁：`SET key token NX PX ttl` 获取，Lua 脚本比较 token 后删除，避免误删他人续上的锁。
+//! 直接使用 RESP 协议，每次操作一个短连接；只支持明文 `redis://`，不支持 TLS 与集群。
+//! 连接与 RESP 编解码也供 [`crate::pubsub`] 使用。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use super::{DistributedLock, LockError, LockGuard, LockLease};
+
+const RELEASE_SCRIPT: &str = "if redis.call('get', KEYS[1]) == ARGV[1] then return redis.call('del', KEYS[1]) else return 0 end";
+
+static TOKEN_SEQ: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub(crate) struct Endpoint {
+    addr: String,
+    user: Option<String>,
+    password: Option<String>,
+    db: Option<u32>,
+}
+
+pub struct RedisLock {
+    endpoint: Endpoint,
+    /// 键前缀，多个环境共用一个 Redis 时区分
+    prefix: String,
+}
+
+impl RedisLock {
+    /// `redis://[user:pass@]host[:port][/db]`；不是 redis:// 时返回错误
+    pub fn from_url(url: &str, prefix: impl Into<String>) -> Result<Self, LockError> {
+        let endpoint = parse_url(url).ok_or_else(|| LockError::Backend(format!("unsupported redis url (expect redis://host[:port][/db]): {}", url)))?;
+        Ok(Self { endpoint, prefix: prefix.into() })
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+
+    async fn connect(&self) -> Result<Connection, LockError> {
+        self.endpoint.connect().await
+    }
+}
+
+impl Endpoint {
+    /// 建立连接并完成 AUTH / SELECT
+    pub(crate) async fn connect(&self) -> Result<Connection, LockError> {
+        let stream = TcpStream::connect(&self.addr).await.map_err(|e| LockError::Backend(format!("{}: {}", self.addr, e)))?;
+        let mut conn = Connection { stream: BufReader::new(stream) };
+        if let Some(password) = &self.password {
+            let reply = match &self.user {
+                Some(user) => conn.call(&["AUTH", user, password]).await?,
+                None => conn.call(&["AUTH", password]).await?,
+            };
+            reply.expect_ok()?;
+        }
+        if let Some(db) = self.db {
+            conn.call(&["SELECT", &db.to_string()]).await?.expect_ok()?;
+        }
+        Ok(conn)
+    }
+}
+
+struct RedisLease {
+    lock: RedisLock,
+    key: String,
+    token: String,
+}
+
+#[async_trait]
+impl LockLease for RedisLease {
+    async fn release(self: Box<Self>) -> Result<(), LockError> {
+        let mut conn = self.lock.connect().await?;
+        match conn.call(&["EVAL", RELEASE_SCRIPT, "1", &self.key, &self.token]).await? {
+            Reply::Integer(_) => Ok(()),
+            other => Err(other.unexpected()),
+        }
+    }
+}
+
+#[async_trait]
+impl DistributedLock for RedisLock {
+    async fn try_acquire(&self, name: &str, ttl: Duration) -> Result<Option<LockGuard>, LockError> {
+        let key = self.key(name);
+        let token = new_token();
+        let ttl_ms = ttl.as_millis().max(1).to_string();
+        let mut conn = self.connect().await?;
+        match conn.call(&["SET", &key, &token, "NX", "PX", &ttl_ms]).await? {
+            Reply::Simple(s) if s == "OK" => {
+                let lease = RedisLease { lock: RedisLock { endpoint: self.endpoint.clone(), prefix: self.prefix.clone() }, key, token };
+                Ok(Some(LockGuard::new(name, Box::new(lease))))
+            }
+            Reply::Nil => Ok(None),
+            other => Err(other.unexpected()),
+        }
+    }
+
+    fn backend(&self) -> &'static str {
+        "redis"
+    }
+}
+
+/// 本进程内唯一的持有者标识
+fn new_token() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    format!("{}-{}-{}", std::process::id(), nanos, TOKEN_SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+pub(crate) fn parse_url(url: &str) -> Option<Endpoint> {
+    let rest = url.strip_prefix("redis://")?;
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (credentials, host_port) = match authority.rsplit_once('@') {
+        Some((c, h)) => (Some(c), h),
+        None => (None, authority),
+    };
+    if host_port.is_empty() {
+        return None;
+    }
+    let (user, password) = match credentials.map(|c| c.split_once(':').unwrap_or(("", c))) {
+        Some((u, p)) => ((!u.is_empty()).then(|| u.to_string()), (!p.is_empty()).then(|| p.to_string())),
+        None => (None, None),
+    };
+    Some(Endpoint {
+        addr: if host_port.contains(':') { host_port.to_string() } else { format!("{}:6379", host_port) },
+        user,
+        password,
+        db: path.parse().ok(),
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Reply {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    Nil,
+    /// pub/sub 的订阅确认与消息
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    pub(crate) fn expect_ok(self) -> Result<(), LockError> {
+        match self {
+            Reply::Simple(_) => Ok(()),
+            other => Err(other.unexpected()),
+        }
+    }
+
+    pub(crate) fn unexpected(self) -> LockError {
+        match self {
+            Reply::Error(e) => LockError::Backend(e),
+            other => LockError::Protocol(format!("unexpected reply {:?}", other)),
+        }
+    }
+}
+
+pub(crate) struct Connection {
+    stream: BufReader<TcpStream>,
+}
+
+impl Connection {
+    pub(crate) async fn call(&mut self, args: &[&str]) -> Result<Reply, LockError> {
+        self.send(args).await?;
+        self.read().await
+    }
+
+    pub(crate) async fn send(&mut self, args: &[&str]) -> Result<(), LockError> {
+        self.stream.get_mut().write_all(&encode(args)).await.map_err(|e| LockError::Backend(e.to_string()))
+    }
+
+    pub(crate) async fn read(&mut self) -> Result<Reply, LockError> {
+        read_reply(&mut self.stream).await
+    }
+}
+
+fn encode(args: &[&str]) -> Vec<u8> {
+    let mut out = format!("*{}\r\n", args.len()).into_bytes();
+    for a in args {
+        out.extend_from_slice(format!("${}\r\n", a.len()).as_bytes());
+        out.extend_from_slice(a.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+async fn read_reply<R: AsyncBufRead + Unpin>(r: &mut R) -> Result<Reply, LockError> {
+    let line = read_line(r).await?;
+    match line.strip_prefix('*') {
+        // 数组只出现在 pub/sub 回复中，元素均为非数组
+        Some(len) => match parse_int(len)? {
+            n if n < 0 => Ok(Reply::Nil),
+            n => {
+                let mut items = Vec::with_capacity(n as usize);
+                for _ in 0..n {
+                    let line = read_line(r).await?;
+                    items.push(read_scalar(r, &line).await?);
+                }
+                Ok(Reply::Array(items))
+            }
+        },
+        None => read_scalar(r, &line).await,
+    }
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(r: &mut R) -> Result<String, LockError> {
+    let mut line = String::new();
+    if r.read_line(&mut line).await.map_err(|e| LockError::Backend(e.to_string()))? == 0 {
+        return Err(LockError::Protocol("connection closed".into()));
+    }
+    Ok(line.trim_end_matches("\r\n").to_string())
+}
+
+fn parse_int(s: &str) -> Result<i64, LockError> {
+    s.parse::<i64>().map_err(|_| LockError::Protocol(format!("bad integer reply: {}", s)))
+}
+
+async fn read_scalar<R: AsyncBufRead + Unpin>(r: &mut R, line: &str) -> Result<Reply, LockError> {
+    let (kind, body) = line.split_at(line.len().min(1));
+    match kind {
+        "+" => Ok(Reply::Simple(body.to_string())),
+        "-" => Ok(Reply::Error(body.to_string())),
+        ":" => Ok(Reply::Integer(parse_int(body)?)),
+        "$" => match parse_int(body)? {
+            n if n < 0 => Ok(Reply::Nil),
+            n => {
+                let mut buf = vec![0; n as usize + 2];
+                r.read_exact(&mut buf).await.map_err(|e| LockError::Backend(e.to_string()))?;
+                buf.truncate(n as usize);
+                Ok(Reply::Bulk(buf))
+            }
+        },
+        _ => Err(LockError::Protocol(format!("unsupported reply: {}", line))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_redis_urls() {
+        let e = parse_url("redis://:secret@cache.internal/2").unwrap();
+        assert_eq!((e.addr.as_str(), e.user, e.password.as_deref(), e.db), ("cache.internal:6379", None, Some("secret"), Some(2)));
+        let e = parse_url("redis://app:pw@10.0.0.1:6380").unwrap();
+        assert_eq!((e.user.as_deref(), e.addr.as_str(), e.db), (Some("app"), "10.0.0.1:6380", None));
+        assert!(parse_url("rediss://host").is_none());
+        assert!(parse_url("redis://").is_none());
+    }
+
+    #[tokio::test]
+    async fn encodes_commands_and_reads_replies() {
+        assert_eq!(encode(&["SET", "k", "v"]), b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n".to_vec());
+        let mut input: &[u8] = b"+OK\r\n$-1\r\n:1\r\n$3\r\nabc\r\n-ERR nope\r\n*3\r\n$7\r\nmessage\r\n$2\r\nch\r\n$2\r\nhi\r\n";
+        assert_eq!(read_reply(&mut input).await.unwrap(), Reply::Simple("OK".into()));
+        assert_eq!(read_reply(&mut input).await.unwrap(), Reply::Nil);
+        assert_eq!(read_reply(&mut input).await.unwrap(), Reply::Integer(1));
+        assert_eq!(read_reply(&mut input).await.unwrap(), Reply::Bulk(b"abc".to_vec()));
+        assert!(matches!(read_reply(&mut input).await.unwrap().unexpected(), LockError::Backend(e) if e == "ERR nope"));
+        let message = Reply::Array(vec![Reply::Bulk(b"message".to_vec()), Reply::Bulk(b"ch".to_vec()), Reply::Bulk(b"hi".to_vec())]);
+        assert_eq!(read_reply(&mut input).await.unwrap(), message);
+    }
+}
@@ -1,43 +1,208 @@
 //! Pagination utilities shared across crates
 //!
-//! Provides a simple `Pagination` struct and helpers to normalize inputs.
+//! Provides a simple `Pagination` struct, strict validation with typed errors,
+//! opaque cursors and a `Page<T>` envelope carrying totals.
+//!
+//! The `per_page` upper bound is configuration (`pagination.max_per_page`) and is
+//! passed in by the caller. Lists ordered newest first resume from a keyset
+//! [`Anchor`] carried in the cursor, so rows inserted while a client pages
+//! through do not shift the following pages.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Default upper bound for `per_page`
+pub const DEFAULT_MAX_PER_PAGE: u32 = 100;
+
+/// Typed pagination errors
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PaginationError {
+    #[error("page must be >= 1")]
+    InvalidPage,
+    #[error("per_page must be between 1 and {max}, got {per_page}")]
+    InvalidPerPage { per_page: u32, max: u32 },
+    #[error("page {page} out of range (total pages: {total_pages})")]
+    PageOutOfRange { page: u32, total_pages: u64 },
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
+}
+
+/// Keyset position: sort timestamp (µs since epoch) and id of the last row already returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    pub at_micros: i64,
+    pub id: u128,
+}
 
 /// Pagination parameters
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Pagination {
     /// 1-based page index
     pub page: u32,
     /// items per page
     pub per_page: u32,
+    /// resume after this row instead of skipping `(page - 1) * per_page` rows
+    pub after: Option<Anchor>,
 }
 
 impl Pagination {
+    pub fn new(page: u32, per_page: u32) -> Self {
+        Self { page, per_page, after: None }
+    }
+
     /// Clamp to sane defaults and convert to `(page_index, per_page)` as `u64`
-    pub fn normalize(self) -> (u64, u64) {
+    pub fn normalize(self, max: u32) -> (u64, u64) {
         let page = if self.page == 0 { 1 } else { self.page };
-        let per_page = self.per_page.clamp(1, 100);
+        let per_page = self.per_page.clamp(1, max.max(1));
         ((page - 1) as u64, per_page as u64)
     }
+
+    /// Strict variant of `normalize`: reject instead of clamping
+    pub fn validate(self, max: u32) -> Result<(u64, u64), PaginationError> {
+        if self.page == 0 {
+            return Err(PaginationError::InvalidPage);
+        }
+        if self.per_page == 0 || self.per_page > max {
+            return Err(PaginationError::InvalidPerPage { per_page: self.per_page, max });
+        }
+        Ok(((self.page - 1) as u64, self.per_page as u64))
+    }
+
+    /// Build from request parameters: a cursor takes precedence over `page`/`per_page`
+    pub fn resolve(page: Option<u32>, per_page: Option<u32>, cursor: Option<&str>, max: u32) -> Result<Self, PaginationError> {
+        if let Some(c) = cursor {
+            return Self::from_cursor(c, max);
+        }
+        let d = Self::default();
+        let p = Self::new(page.unwrap_or(d.page), per_page.unwrap_or(d.per_page));
+        p.validate(max)?;
+        Ok(p)
+    }
+
+    /// Encode as an opaque cursor: `page:per_page`, plus the keyset anchor when present
+    pub fn to_cursor(self) -> String {
+        let raw = match self.after {
+            Some(a) => format!("{}:{}:{}:{:032x}", self.page, self.per_page, a.at_micros, a.id),
+            None => format!("{}:{}", self.page, self.per_page),
+        };
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode a cursor produced by `to_cursor`
+    pub fn from_cursor(cursor: &str, max: u32) -> Result<Self, PaginationError> {
+        let invalid = || PaginationError::InvalidCursor(cursor.to_string());
+        let raw = URL_SAFE_NO_PAD.decode(cursor.trim()).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let parts: Vec<&str> = raw.split(':').collect();
+        let after = match parts.as_slice() {
+            [_, _] => None,
+            [_, _, at, id] => Some(Anchor {
+                at_micros: at.parse().map_err(|_| invalid())?,
+                id: u128::from_str_radix(id, 16).map_err(|_| invalid())?,
+            }),
+            _ => return Err(invalid()),
+        };
+        let p = Self {
+            page: parts[0].parse().map_err(|_| invalid())?,
+            per_page: parts[1].parse().map_err(|_| invalid())?,
+            after,
+        };
+        p.validate(max)?;
+        Ok(p)
+    }
 }
 
 impl Default for Pagination {
-    fn default() -> Self { Self { page: 1, per_page: 20 } }
+    fn default() -> Self { Self::new(1, 20) }
+}
+
+/// One page of results plus totals
+#[derive(Clone, Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, opts: Pagination, total: u64) -> Self {
+        let total_pages = total_pages(total, opts.per_page);
+        let has_more = (opts.page as u64) < total_pages;
+        let next_cursor = has_more.then(|| Pagination::new(opts.page + 1, opts.per_page).to_cursor());
+        Self { items, page: opts.page, per_page: opts.per_page, total, total_pages, has_more, next_cursor }
+    }
+
+    /// Keyset variant of `new`: `items` holds up to `per_page + 1` rows, the extra one
+    /// only signalling that more follow; the next cursor resumes after the last row kept.
+    pub fn keyset(mut items: Vec<T>, opts: Pagination, total: u64, anchor: impl Fn(&T) -> Anchor) -> Self {
+        let has_more = items.len() > opts.per_page as usize;
+        items.truncate(opts.per_page as usize);
+        let next_cursor = if has_more {
+            items.last().map(|last| Pagination { page: opts.page + 1, per_page: opts.per_page, after: Some(anchor(last)) }.to_cursor())
+        } else {
+            None
+        };
+        let total_pages = total_pages(total, opts.per_page);
+        Self { items, page: opts.page, per_page: opts.per_page, total, total_pages, has_more, next_cursor }
+    }
+
+    /// Slice a list the store already holds entirely in memory (file-backed stores);
+    /// database-backed lists page in the query instead.
+    pub fn from_vec(all: Vec<T>, opts: Pagination) -> Result<Self, PaginationError> {
+        opts.validate(u32::MAX)?;
+        let total = all.len() as u64;
+        ensure_in_range(opts, total)?;
+        let offset = (opts.page as usize - 1).saturating_mul(opts.per_page as usize);
+        let items = all.into_iter().skip(offset).take(opts.per_page as usize).collect();
+        Ok(Self::new(items, opts, total))
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            page: self.page,
+            per_page: self.per_page,
+            total: self.total,
+            total_pages: self.total_pages,
+            has_more: self.has_more,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Number of pages for `total` items
+pub fn total_pages(total: u64, per_page: u32) -> u64 {
+    if per_page == 0 { 0 } else { total.div_ceil(per_page as u64) }
+}
+
+/// Reject pages past the end; page 1 of an empty result is valid ("no data")
+pub fn ensure_in_range(opts: Pagination, total: u64) -> Result<(), PaginationError> {
+    let pages = total_pages(total, opts.per_page);
+    if opts.page > 1 && opts.page as u64 > pages {
+        return Err(PaginationError::PageOutOfRange { page: opts.page, total_pages: pages });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Pagination;
+    use super::*;
 
     #[test]
     fn normalize_clamps_zero_to_defaults() {
-        let (idx, per) = Pagination { page: 0, per_page: 0 }.normalize();
+        let (idx, per) = Pagination::new(0, 0).normalize(100);
         assert_eq!(idx, 0);
         assert_eq!(per, 1);
     }
 
     #[test]
     fn normalize_clamps_upper_bound() {
-        let (idx, per) = Pagination { page: 5, per_page: 1000 }.normalize();
+        let (idx, per) = Pagination::new(5, 1000).normalize(100);
         assert_eq!(idx, 4);
         assert_eq!(per, 100);
     }
@@ -48,4 +213,81 @@ mod tests {
         assert_eq!(d.page, 1);
         assert_eq!(d.per_page, 20);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn validate_rejects_instead_of_clamping() {
+        assert_eq!(Pagination::new(0, 10).validate(100), Err(PaginationError::InvalidPage));
+        assert_eq!(
+            Pagination::new(1, 500).validate(100),
+            Err(PaginationError::InvalidPerPage { per_page: 500, max: 100 })
+        );
+        assert_eq!(Pagination::new(3, 10).validate(100), Ok((2, 10)));
+    }
+
+    #[test]
+    fn cursor_roundtrip_and_invalid() {
+        let p = Pagination::new(4, 25);
+        assert_eq!(Pagination::from_cursor(&p.to_cursor(), 100), Ok(p));
+        assert!(matches!(Pagination::from_cursor("not-a-cursor!", 100), Err(PaginationError::InvalidCursor(_))));
+        assert!(matches!(Pagination::from_cursor(&URL_SAFE_NO_PAD.encode("x:y"), 100), Err(PaginationError::InvalidCursor(_))));
+    }
+
+    #[test]
+    fn from_vec_slices_and_rejects_past_end() {
+        let opts = Pagination::new(2, 2);
+        let page = Page::from_vec(vec![1, 2, 3, 4, 5], opts).unwrap();
+        assert_eq!((page.items, page.total, page.total_pages, page.has_more), (vec![3, 4], 5, 3, true));
+        assert!(Page::<u8>::from_vec(Vec::new(), Pagination::default()).unwrap().items.is_empty());
+        assert!(matches!(Page::from_vec(vec![1], opts), Err(PaginationError::PageOutOfRange { .. })));
+    }
+
+    #[test]
+    fn resolve_prefers_cursor() {
+        let c = Pagination::new(2, 5).to_cursor();
+        assert_eq!(Pagination::resolve(Some(9), Some(9), Some(&c), 100), Ok(Pagination::new(2, 5)));
+        assert_eq!(Pagination::resolve(None, None, None, 100), Ok(Pagination::default()));
+        assert_eq!(Pagination::resolve(Some(0), None, None, 100), Err(PaginationError::InvalidPage));
+    }
+
+    #[test]
+    fn resolve_and_cursor_use_the_configured_max() {
+        assert_eq!(
+            Pagination::resolve(None, Some(50), None, 10),
+            Err(PaginationError::InvalidPerPage { per_page: 50, max: 10 })
+        );
+        assert_eq!(Pagination::resolve(None, Some(50), None, 50), Ok(Pagination::new(1, 50)));
+        let c = Pagination::new(2, 50).to_cursor();
+        assert!(matches!(Pagination::from_cursor(&c, 10), Err(PaginationError::InvalidPerPage { .. })));
+    }
+
+    #[test]
+    fn keyset_page_resumes_after_last_item() {
+        let anchor = |n: &i64| Anchor { at_micros: *n, id: *n as u128 };
+        let page = Page::keyset(vec![30, 20, 10], Pagination::new(1, 2), 3, anchor);
+        assert_eq!((page.items.as_slice(), page.has_more), (&[30, 20][..], true));
+        let next = Pagination::from_cursor(page.next_cursor.as_deref().unwrap(), 100).unwrap();
+        assert_eq!(next, Pagination { page: 2, per_page: 2, after: Some(Anchor { at_micros: 20, id: 20 }) });
+
+        let last = Page::keyset(vec![10], next, 3, anchor);
+        assert!(!last.has_more);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn page_totals_and_range() {
+        let page = Page::new(vec![1, 2], Pagination::new(1, 2), 5);
+        assert_eq!(page.total_pages, 3);
+        assert!(page.has_more);
+        assert_eq!(Pagination::from_cursor(page.next_cursor.as_deref().unwrap(), 100), Ok(Pagination::new(2, 2)));
+
+        let last = Page::new(vec![5], Pagination::new(3, 2), 5);
+        assert!(!last.has_more);
+        assert!(last.next_cursor.is_none());
+
+        assert!(ensure_in_range(Pagination::new(1, 10), 0).is_ok());
+        assert_eq!(
+            ensure_in_range(Pagination::new(4, 2), 5),
+            Err(PaginationError::PageOutOfRange { page: 4, total_pages: 3 })
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! CPU / 堆剖析，供服务端 `/admin/debug/pprof/*` 与网关 admin 端口 `/debug/pprof/*` 使用。
+//!
+//! - CPU：`pprof` feature，采样 `seconds` 秒后返回火焰图（SVG）或 pprof protobuf（`go tool pprof` 可读）；
+//! - 堆：`heap-profiling` feature，二进制以 jemalloc（`prof:true`）为全局分配器时 dump 当前堆剖析（`jeprof` 可读）。
+//!
+//! 未编译对应 feature 时返回 [`ProfilingError::Unsupported`]；同一时间只允许一个 CPU 剖析。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const MAX_PROFILE_SECS: u64 = 60;
+pub const DEFAULT_PROFILE_SECS: u64 = 10;
+pub const DEFAULT_FREQUENCY: i32 = 99;
+
+/// 剖析端点开关；默认关闭，仅在需要排查时于预发环境打开
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfilingConfig {
+    pub enabled: bool,
+    /// 网关 admin 端口无登录态，要求 `Authorization: Bearer <token>`；未设置时网关端点一律 401。
+    /// 服务端端点走 admin 鉴权，不使用该字段
+    pub token: Option<String>,
+}
+
+impl ProfilingConfig {
+    /// 校验网关 admin 端口请求携带的 Bearer token
+    pub fn authorize(&self, authorization: Option<&str>) -> bool {
+        match (self.token.as_deref(), authorization.and_then(|h| h.strip_prefix("Bearer "))) {
+            (Some(expected), Some(given)) => !expected.is_empty() && crate::crypto::constant_time_eq(expected.as_bytes(), given.as_bytes()),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ProfilingError {
+    #[error("built without the `{0}` feature")]
+    Unsupported(&'static str),
+    #[error("another CPU profile is in progress")]
+    Busy,
+    #[error("invalid parameters: {0}")]
+    InvalidParams(String),
+    #[error("profiling failed: {0}")]
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFormat {
+    /// SVG 火焰图
+    #[default]
+    Flamegraph,
+    /// pprof protobuf
+    Proto,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ProfileParams {
+    pub seconds: Option<u64>,
+    pub frequency: Option<i32>,
+    #[serde(default)]
+    pub format: ProfileFormat,
+}
+
+impl ProfileParams {
+    /// (seconds, frequency)
+    pub fn resolve(&self) -> Result<(u64, i32), ProfilingError> {
+        let seconds = self.seconds.unwrap_or(DEFAULT_PROFILE_SECS);
+        if seconds == 0 || seconds > MAX_PROFILE_SECS {
+            return Err(ProfilingError::InvalidParams(format!("seconds must be 1..={}", MAX_PROFILE_SECS)));
+        }
+        let frequency = self.frequency.unwrap_or(DEFAULT_FREQUENCY);
+        if !(1..=1000).contains(&frequency) {
+            return Err(ProfilingError::InvalidParams("frequency must be 1..=1000".into()));
+        }
+        Ok((seconds, frequency))
+    }
+}
+
+pub struct Profile {
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+static CPU_BUSY: AtomicBool = AtomicBool::new(false);
+
+struct BusyGuard(&'static AtomicBool);
+
+impl BusyGuard {
+    fn acquire(flag: &'static AtomicBool) -> Result<Self, ProfilingError> {
+        flag.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).map(|_| BusyGuard(flag)).map_err(|_| ProfilingError::Busy)
+    }
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// 采样整个进程的 CPU；在阻塞线程上等待 `seconds` 秒
+pub async fn cpu_profile(params: &ProfileParams) -> Result<Profile, ProfilingError> {
+    let (seconds, frequency) = params.resolve()?;
+    let format = params.format;
+    let guard = BusyGuard::acquire(&CPU_BUSY)?;
+    let result = tokio::task::spawn_blocking(move || sample_cpu(seconds, frequency, format))
+        .await
+        .map_err(|e| ProfilingError::Failed(e.to_string()))?;
+    drop(guard);
+    result
+}
+
+#[cfg(feature = "pprof")]
+fn sample_cpu(seconds: u64, frequency: i32, format: ProfileFormat) -> Result<Profile, ProfilingError> {
+    use pprof::protos::Message;
+
+    let failed = |e: pprof::Error| ProfilingError::Failed(e.to_string());
+    let profiler = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(failed)?;
+    std::thread::sleep(std::time::Duration::from_secs(seconds));
+    let report = profiler.report().build().map_err(failed)?;
+    if report.data.is_empty() {
+        return Err(ProfilingError::Failed("no samples collected (process idle?)".into()));
+    }
+    let mut body = Vec::new();
+    match format {
+        ProfileFormat::Flamegraph => {
+            report.flamegraph(&mut body).map_err(failed)?;
+            Ok(Profile { content_type: "image/svg+xml", body })
+        }
+        ProfileFormat::Proto => {
+            let profile = report.pprof().map_err(failed)?;
+            profile.encode(&mut body).map_err(|e| ProfilingError::Failed(e.to_string()))?;
+            Ok(Profile { content_type: "application/octet-stream", body })
+        }
+    }
+}
+
+#[cfg(not(feature = "pprof"))]
+fn sample_cpu(_seconds: u64, _frequency: i32, _format: ProfileFormat) -> Result<Profile, ProfilingError> {
+    Err(ProfilingError::Unsupported("pprof"))
+}
+
+/// 通过 jemalloc `prof.dump` 导出当前堆剖析
+#[cfg(feature = "heap-profiling")]
+pub fn heap_profile() -> Result<Profile, ProfilingError> {
+    let failed = |e: &dyn std::fmt::Display| ProfilingError::Failed(e.to_string());
+    let active: bool = unsafe { tikv_jemalloc_ctl::raw::read(b"opt.prof\0") }.map_err(|e| failed(&e))?;
+    if !active {
+        return Err(ProfilingError::Failed("jemalloc profiling is not active (malloc_conf prof:true)".into()));
+    }
+    let path = std::env::temp_dir().join(format!("heap-{}-{}.prof", std::process::id(), chrono::Utc::now().timestamp_millis()));
+    let c_path = std::ffi::CString::new(path.to_string_lossy().into_owned()).map_err(|e| failed(&e))?;
+    unsafe { tikv_jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr()) }.map_err(|e| failed(&e))?;
+    let body = std::fs::read(&path).map_err(|e| failed(&e));
+    let _ = std::fs::remove_file(&path);
+    Ok(Profile { content_type: "application/octet-stream", body: body? })
+}
+
+#[cfg(not(feature = "heap-profiling"))]
+pub fn heap_profile() -> Result<Profile, ProfilingError> {
+    Err(ProfilingError::Unsupported("heap-profiling"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_params() {
+        assert_eq!(ProfileParams::default().resolve().unwrap(), (DEFAULT_PROFILE_SECS, DEFAULT_FREQUENCY));
+        let p = ProfileParams { seconds: Some(MAX_PROFILE_SECS + 1), ..Default::default() };
+        assert!(matches!(p.resolve(), Err(ProfilingError::InvalidParams(_))));
+        let p = ProfileParams { frequency: Some(0), ..Default::default() };
+        assert!(p.resolve().is_err());
+    }
+
+    #[test]
+    fn gateway_token_required() {
+        let cfg = ProfilingConfig { enabled: true, token: Some("s3cret".into()) };
+        assert!(cfg.authorize(Some("Bearer s3cret")));
+        assert!(!cfg.authorize(Some("Bearer nope")));
+        assert!(!cfg.authorize(None));
+        assert!(!ProfilingConfig { enabled: true, token: None }.authorize(Some("Bearer ")));
+    }
+
+    #[test]
+    fn only_one_cpu_profile_at_a_time() {
+        static FLAG: AtomicBool = AtomicBool::new(false);
+        let first = BusyGuard::acquire(&FLAG).unwrap();
+        assert!(matches!(BusyGuard::acquire(&FLAG), Err(ProfilingError::Busy)));
+        drop(first);
+        assert!(BusyGuard::acquire(&FLAG).is_ok());
+    }
+
+    #[cfg(feature = "pprof")]
+    #[tokio::test]
+    async fn cpu_profile_renders_flamegraph() {
+        let spin = std::thread::spawn(|| {
+            let started = std::time::Instant::now();
+            let mut x = 0u64;
+            while started.elapsed() < std::time::Duration::from_millis(1500) {
+                x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(7));
+            }
+        });
+        let params = ProfileParams { seconds: Some(1), ..Default::default() };
+        let p = cpu_profile(&params).await.unwrap();
+        spin.join().unwrap();
+        assert_eq!(p.content_type, "image/svg+xml");
+        assert!(String::from_utf8_lossy(&p.body).contains("<svg"));
+    }
+}
@@ -0,0 +1,89 @@
+//! Redis pub/sub：多实例之间广播小消息（网关集群模式同步限流、熔断与健康状态）。
+//! 与 [`crate::lock::RedisLock`] 共用 RESP 实现，只支持明文 `redis://`；消息不持久化，
+//! 订阅断开期间发布的消息会丢失，调用方需能容忍。
+
+use crate::lock::redis::{parse_url, Connection, Endpoint, Reply};
+use crate::lock::LockError;
+
+#[derive(Debug, Clone)]
+pub struct RedisPubSub {
+    endpoint: Endpoint,
+}
+
+impl RedisPubSub {
+    /// `redis://[user:pass@]host[:port][/db]`
+    pub fn from_url(url: &str) -> Result<Self, LockError> {
+        let endpoint = parse_url(url).ok_or_else(|| LockError::Backend(format!("unsupported redis url (expect redis://host[:port][/db]): {}", url)))?;
+        Ok(Self { endpoint })
+    }
+
+    /// 发布用的长连接
+    pub async fn publisher(&self) -> Result<Publisher, LockError> {
+        Ok(Publisher { conn: self.endpoint.connect().await? })
+    }
+
+    /// 订阅 `channel`；返回时订阅已被确认
+    pub async fn subscribe(&self, channel: &str) -> Result<Subscription, LockError> {
+        let mut conn = self.endpoint.connect().await?;
+        match conn.call(&["SUBSCRIBE", channel]).await? {
+            Reply::Array(items) if items.first() == Some(&Reply::Bulk(b"subscribe".to_vec())) => Ok(Subscription { conn }),
+            other => Err(other.unexpected()),
+        }
+    }
+}
+
+pub struct Publisher {
+    conn: Connection,
+}
+
+impl Publisher {
+    /// 返回收到消息的订阅者数
+    pub async fn publish(&mut self, channel: &str, payload: &str) -> Result<i64, LockError> {
+        match self.conn.call(&["PUBLISH", channel, payload]).await? {
+            Reply::Integer(n) => Ok(n),
+            other => Err(other.unexpected()),
+        }
+    }
+}
+
+pub struct Subscription {
+    conn: Connection,
+}
+
+impl Subscription {
+    /// 等待下一条消息，返回消息体；不可在 `select!` 中取消（会丢失读到一半的回复）
+    pub async fn next(&mut self) -> Result<Vec<u8>, LockError> {
+        loop {
+            match self.conn.read().await? {
+                Reply::Array(items) => {
+                    if let Some(payload) = message_payload(items) {
+                        return Ok(payload);
+                    }
+                }
+                other => return Err(other.unexpected()),
+            }
+        }
+    }
+}
+
+/// `["message", channel, payload]` 的 payload；其他推送（订阅确认等）为 None
+fn message_payload(items: Vec<Reply>) -> Option<Vec<u8>> {
+    let mut items = items.into_iter();
+    match (items.next(), items.next(), items.next()) {
+        (Some(Reply::Bulk(kind)), Some(_), Some(Reply::Bulk(payload))) if kind == b"message" => Some(payload),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_message_payloads() {
+        let bulk = |s: &str| Reply::Bulk(s.as_bytes().to_vec());
+        assert_eq!(message_payload(vec![bulk("message"), bulk("ch"), bulk("{}")]), Some(b"{}".to_vec()));
+        assert_eq!(message_payload(vec![bulk("subscribe"), bulk("ch"), Reply::Integer(1)]), None);
+        assert!(RedisPubSub::from_url("rediss://cache").is_err());
+    }
+}
@@ -0,0 +1,705 @@
+//! 令牌桶限流：网关数据面（全局、WAF、机器人、地域限流）与 server 管理接口限流共用。
+//! - `TokenBucket`：单个桶，无锁
+//! - `SlidingWindowLog` / `FixedWindow`：严格按窗口计数，不允许突发（限流记录可选的算法）
+//! - `Limiter`：按 [`Algorithm`] 选择上述之一
+//! - `KeyedLimiter`：按 key（用户、API Key、客户端 IP）各自一个桶
+
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// 无锁令牌桶（GCRA）：容量 `capacity`，每 `period` 补充 `refill` 个令牌。
+///
+/// 时间取单调时钟 `Instant` 相对 `origin` 的 tick（约 1ns），一个令牌恰好 `ticks_per_token` 个 tick；
+/// 纳秒与 tick 按 `refill × ticks_per_token / period` 的整数比例从 `origin` 起整体换算，
+/// 舍入误差不超过 1 tick 且不随时间累积，速率除不尽（如 3 个/秒）时长期放行数也不会漂移。
+///
+/// 只维护一个“理论到达时间” TAT（tick），每次放行用一次 CAS 把 TAT 推后 `n × ticks_per_token`；
+/// TAT 超前当前时间不超过 `capacity × ticks_per_token` 即有令牌。并发请求取到的 `now` 先后不一时，
+/// TAT 取两者较大值，较早的时刻不会让桶多放行。
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: u64,
+    /// 每个周期补充的令牌数；0 表示不再补充
+    refill: u64,
+    /// 补充周期（纳秒）
+    period_nanos: u64,
+    ticks_per_token: u64,
+    origin: Instant,
+    tat: AtomicU64,
+}
+
+impl TokenBucket {
+    /// 每秒补充 `refill_rate` 个令牌
+    pub fn new(capacity: u64, refill_rate: u64) -> Self {
+        Self::with_rate(capacity, refill_rate, Duration::from_secs(1))
+    }
+
+    /// 每 `interval` 补充一个令牌，用于低于 1 个/秒或非整数的速率（如每分钟 5 次登录、每 400ms 一次）
+    pub fn with_interval(capacity: u64, interval: Duration) -> Self {
+        Self::with_rate(capacity, 1, interval)
+    }
+
+    /// 每 `period` 补充 `refill` 个令牌；`period` 精确到纳秒，最小 1ns
+    pub fn with_rate(capacity: u64, refill: u64, period: Duration) -> Self {
+        let period_nanos = period.as_nanos().clamp(1, u64::MAX as u128) as u64;
+        Self {
+            capacity,
+            refill,
+            period_nanos,
+            ticks_per_token: (period_nanos / refill.max(1)).max(1),
+            origin: Instant::now(),
+            tat: AtomicU64::new(0),
+        }
+    }
+
+    /// `now` 相对 `origin` 的 tick 数；早于 `origin` 的时刻按 0 计
+    fn ticks_at(&self, now: Instant) -> u64 {
+        let nanos = now.saturating_duration_since(self.origin).as_nanos();
+        let ticks = nanos * (self.refill as u128 * self.ticks_per_token as u128) / self.period_nanos as u128;
+        ticks.min(u64::MAX as u128) as u64
+    }
+
+    /// `ticks` 个 tick 对应的时长（向上取整）；不补充令牌时为最大值
+    fn duration_of(&self, ticks: u64) -> Duration {
+        let per_period = self.refill as u128 * self.ticks_per_token as u128;
+        if ticks == 0 {
+            return Duration::ZERO;
+        }
+        if per_period == 0 {
+            return Duration::from_nanos(u64::MAX);
+        }
+        let nanos = (ticks as u128 * self.period_nanos as u128).div_ceil(per_period);
+        Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+    }
+
+    pub fn try_acquire(&self, tokens: u64) -> bool {
+        self.try_acquire_at(tokens, Instant::now())
+    }
+
+    fn try_acquire_at(&self, tokens: u64, now: Instant) -> bool {
+        let now = self.ticks_at(now);
+        let cost = tokens.saturating_mul(self.ticks_per_token);
+        let limit = self.capacity.saturating_mul(self.ticks_per_token);
+        let mut tat = self.tat.load(Ordering::Acquire);
+        loop {
+            let next = tat.max(now).saturating_add(cost);
+            if next - now > limit {
+                warn!("Rate limit exceeded, tokens: {}, requested: {}", self.available_at(tat, now), tokens);
+                return false;
+            }
+            match self.tat.compare_exchange_weak(tat, next, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {
+                    debug!("Token acquired, remaining: {}", self.available_at(next, now));
+                    return true;
+                }
+                Err(current) => tat = current,
+            }
+        }
+    }
+
+    /// 扣除其他地方已放行的 `tokens` 个令牌（集群模式下同步其他实例的用量）；不足时扣到 0 为止
+    pub fn consume(&self, tokens: u64) {
+        self.consume_at(tokens, Instant::now())
+    }
+
+    fn consume_at(&self, tokens: u64, now: Instant) {
+        let now = self.ticks_at(now);
+        let cost = tokens.saturating_mul(self.ticks_per_token);
+        let limit = now.saturating_add(self.capacity.saturating_mul(self.ticks_per_token));
+        let _ = self.tat.fetch_update(Ordering::AcqRel, Ordering::Acquire, |tat| Some(tat.max(now).saturating_add(cost).min(limit.max(tat))));
+    }
+
+    /// 再等多久才有 `tokens` 个令牌（已足够时为 0），用于 `Retry-After`
+    pub fn wait_time(&self, tokens: u64) -> Duration {
+        self.wait_time_at(tokens, Instant::now())
+    }
+
+    fn wait_time_at(&self, tokens: u64, now: Instant) -> Duration {
+        let now = self.ticks_at(now);
+        let next = self.tat.load(Ordering::Acquire).max(now).saturating_add(tokens.saturating_mul(self.ticks_per_token));
+        self.duration_of((next - now).saturating_sub(self.capacity.saturating_mul(self.ticks_per_token)))
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// 当前可用令牌数
+    pub fn available(&self) -> u64 {
+        self.available_at(self.tat.load(Ordering::Acquire), self.ticks_at(Instant::now()))
+    }
+
+    /// 多久后桶重新补满（已满时为 0），用于 `X-RateLimit-Reset`
+    pub fn time_to_full(&self) -> Duration {
+        self.time_to_full_at(Instant::now())
+    }
+
+    fn time_to_full_at(&self, now: Instant) -> Duration {
+        self.duration_of(self.tat.load(Ordering::Acquire).saturating_sub(self.ticks_at(now)))
+    }
+
+    /// 桶已补满，与新建的桶等价
+    fn is_full_at(&self, now: Instant) -> bool {
+        self.tat.load(Ordering::Acquire) <= self.ticks_at(now)
+    }
+
+    fn available_at(&self, tat: u64, now: u64) -> u64 {
+        let used = tat.saturating_sub(now).div_ceil(self.ticks_per_token);
+        self.capacity.saturating_sub(used)
+    }
+}
+
+/// 限流算法，每条限流记录（`rate_limit.algorithm`）各自选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    /// 令牌桶：平均速率 + 突发容量 `burst`
+    #[default]
+    TokenBucket,
+    /// 滑动窗口日志：任意一个窗口内都不超过上限
+    SlidingWindow,
+    /// 固定窗口计数：按自然窗口计数，窗口边界前后可能各用满一次
+    FixedWindow,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::TokenBucket => "token_bucket",
+            Algorithm::SlidingWindow => "sliding_window",
+            Algorithm::FixedWindow => "fixed_window",
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "token_bucket" => Ok(Algorithm::TokenBucket),
+            "sliding_window" => Ok(Algorithm::SlidingWindow),
+            "fixed_window" => Ok(Algorithm::FixedWindow),
+            other => Err(format!("unknown rate limit algorithm '{}' (expect token_bucket, sliding_window or fixed_window)", other)),
+        }
+    }
+}
+
+/// 滑动窗口日志：记录最近一个 `window` 内每次放行的时刻，任意长度为 `window` 的区间内最多放行 `limit` 次。
+/// 精确但内存与 `limit` 成正比，适合每分钟几千次以内的上限
+#[derive(Debug)]
+pub struct SlidingWindowLog {
+    limit: u64,
+    window: Duration,
+    log: Mutex<VecDeque<Instant>>,
+}
+
+impl SlidingWindowLog {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self { limit, window, log: Mutex::new(VecDeque::new()) }
+    }
+
+    /// 放行返回 Ok；被限流时返回最早一次放行移出窗口前的等待时间
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        // 并发请求取到的时刻可能略早于上一次记录，按较晚者记录，保持日志有序
+        let now = log.back().map_or(now, |last| now.max(*last));
+        while log.front().is_some_and(|oldest| now.duration_since(*oldest) >= self.window) {
+            log.pop_front();
+        }
+        if (log.len() as u64) < self.limit {
+            log.push_back(now);
+            return Ok(());
+        }
+        Err(log.front().map_or(self.window, |oldest| self.window - now.duration_since(*oldest)))
+    }
+
+    /// 窗口内没有放行记录
+    fn is_empty_at(&self, now: Instant) -> bool {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.back().is_none_or(|last| now.saturating_duration_since(*last) >= self.window)
+    }
+}
+
+/// 固定窗口计数：从创建时刻起每 `window` 为一个窗口，窗口内最多放行 `limit` 次
+#[derive(Debug)]
+pub struct FixedWindow {
+    limit: u64,
+    window_nanos: u128,
+    origin: Instant,
+    /// (窗口序号, 本窗口已放行次数)
+    state: Mutex<(u64, u64)>,
+}
+
+impl FixedWindow {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self { limit, window_nanos: window.as_nanos().max(1), origin: Instant::now(), state: Mutex::new((0, 0)) }
+    }
+
+    /// 放行返回 Ok；被限流时返回距下一个窗口的时间
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.origin).as_nanos();
+        let index = (elapsed / self.window_nanos) as u64;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if index > state.0 {
+            *state = (index, 0);
+        }
+        if state.1 < self.limit {
+            state.1 += 1;
+            return Ok(());
+        }
+        let next_window = (state.0 as u128 + 1) * self.window_nanos;
+        Err(Duration::from_nanos(next_window.saturating_sub(elapsed).min(u64::MAX as u128) as u64))
+    }
+
+    /// 当前窗口还没有放行记录
+    fn is_empty_at(&self, now: Instant) -> bool {
+        let index = (now.saturating_duration_since(self.origin).as_nanos() / self.window_nanos) as u64;
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        index > state.0 || state.1 == 0
+    }
+}
+
+/// 按 [`Algorithm`] 选择的限流器：每 `period` 最多 `limit` 次；令牌桶另以 `burst` 为容量，窗口算法不允许突发
+#[derive(Debug)]
+pub enum Limiter {
+    TokenBucket(TokenBucket),
+    SlidingWindow(SlidingWindowLog),
+    FixedWindow(FixedWindow),
+}
+
+impl Limiter {
+    pub fn new(algorithm: Algorithm, limit: u64, burst: u64, period: Duration) -> Self {
+        match algorithm {
+            Algorithm::TokenBucket => Limiter::TokenBucket(TokenBucket::with_rate(burst, limit, period)),
+            Algorithm::SlidingWindow => Limiter::SlidingWindow(SlidingWindowLog::new(limit, period)),
+            Algorithm::FixedWindow => Limiter::FixedWindow(FixedWindow::new(limit, period)),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Limiter::TokenBucket(_) => Algorithm::TokenBucket,
+            Limiter::SlidingWindow(_) => Algorithm::SlidingWindow,
+            Limiter::FixedWindow(_) => Algorithm::FixedWindow,
+        }
+    }
+
+    /// 放行返回 Ok；被限流时返回需要等待的时间
+    pub fn check(&self) -> Result<(), Duration> {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&self, now: Instant) -> Result<(), Duration> {
+        match self {
+            Limiter::TokenBucket(bucket) => {
+                if bucket.try_acquire_at(1, now) {
+                    Ok(())
+                } else {
+                    Err(bucket.wait_time_at(1, now))
+                }
+            }
+            Limiter::SlidingWindow(log) => log.try_acquire_at(now),
+            Limiter::FixedWindow(window) => window.try_acquire_at(now),
+        }
+    }
+
+    /// 已恢复到初始状态：丢弃后重建不会放宽限流
+    fn is_idle_at(&self, now: Instant) -> bool {
+        match self {
+            Limiter::TokenBucket(bucket) => bucket.is_full_at(now),
+            Limiter::SlidingWindow(log) => log.is_empty_at(now),
+            Limiter::FixedWindow(window) => window.is_empty_at(now),
+        }
+    }
+}
+
+/// 默认最多保留的 key 数
+const KEYED_MAX_KEYS: usize = 10_000;
+
+#[derive(Debug)]
+struct KeyedEntry {
+    limiter: Limiter,
+    last_seen: Instant,
+}
+
+/// 按 key 隔离的限流器：默认令牌桶（容量 `burst`，每 `period` 补充 `refill` 个令牌），
+/// 也可用 [`Self::with_algorithm`] 改为窗口算法（每 `period` 最多 `refill` 次）。
+///
+/// key 数达到上限时先丢弃已恢复到初始状态的限流器；仍在限流中的 key 全部保留，
+/// 只有全部都在使用时才按最近使用时间淘汰最久未见的 1/10
+#[derive(Debug)]
+pub struct KeyedLimiter {
+    burst: u64,
+    refill: u64,
+    period: Duration,
+    algorithm: Algorithm,
+    max_keys: usize,
+    buckets: Mutex<HashMap<String, KeyedEntry>>,
+}
+
+impl KeyedLimiter {
+    /// 每秒 `per_second` 个
+    pub fn per_second(per_second: u64, burst: u64) -> Self {
+        Self::with_rate(per_second.max(1), Duration::from_secs(1), burst)
+    }
+
+    /// 每分钟 `per_minute` 个
+    pub fn per_minute(per_minute: u64, burst: u64) -> Self {
+        Self::with_rate(per_minute.max(1), Duration::from_secs(60), burst)
+    }
+
+    /// 每 `interval` 一个
+    pub fn with_interval(interval: Duration, burst: u64) -> Self {
+        Self::with_rate(1, interval, burst)
+    }
+
+    pub fn with_rate(refill: u64, period: Duration, burst: u64) -> Self {
+        Self {
+            burst: burst.max(1),
+            refill,
+            period,
+            algorithm: Algorithm::TokenBucket,
+            max_keys: KEYED_MAX_KEYS,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 最多保留的 key 数，默认 10000
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys.max(1);
+        self
+    }
+
+    /// 放行返回 Ok；被限流时返回需要等待的时间
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= self.max_keys && !buckets.contains_key(key) {
+            self.evict(&mut buckets, now);
+        }
+        let entry = buckets
+            .entry(key.to_string())
+            .or_insert_with(|| KeyedEntry { limiter: Limiter::new(self.algorithm, self.refill, self.burst, self.period), last_seen: now });
+        entry.last_seen = entry.last_seen.max(now);
+        entry.limiter.check_at(now)
+    }
+
+    fn evict(&self, buckets: &mut HashMap<String, KeyedEntry>, now: Instant) {
+        buckets.retain(|_, e| !e.limiter.is_idle_at(now));
+        if buckets.len() < self.max_keys {
+            return;
+        }
+        // 全部在使用：淘汰最久未见的 key，淘汰后留出 1/10 的空位，避免每个新 key 都全表扫描
+        let evict = buckets.len() - self.max_keys * 9 / 10;
+        let mut seen: Vec<Instant> = buckets.values().map(|e| e.last_seen).collect();
+        let (_, cutoff, _) = seen.select_nth_unstable(evict - 1);
+        let cutoff = *cutoff;
+        buckets.retain(|_, e| e.last_seen > cutoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::time::sleep;
+
+    #[tokio::test]
+    async fn test_token_bucket_basic() {
+        let bucket = TokenBucket::new(10, 5);
+        
+        // Should be able to acquire tokens initially
+        assert!(bucket.try_acquire(5));
+        assert!(bucket.try_acquire(5));
+        
+        // Should fail when bucket is empty
+        assert!(!bucket.try_acquire(1));
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_refill() {
+        let bucket = TokenBucket::new(10, 10); // 10 tokens per second
+        
+        // Drain the bucket
+        assert!(bucket.try_acquire(10));
+        assert!(!bucket.try_acquire(1));
+        
+        // Wait for refill
+        sleep(Duration::from_millis(1100)).await;
+        
+        // Should have refilled
+        assert!(bucket.try_acquire(10));
+    }
+
+    #[test]
+    fn test_token_bucket_concurrent_acquire_is_exact() {
+        // 不补充令牌时，并发抢占的成功次数恰好等于容量
+        let bucket = Arc::new(TokenBucket::new(1000, 0));
+        let granted: usize = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8).map(|_| s.spawn(|| (0..500).filter(|_| bucket.try_acquire(1)).count())).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+        assert_eq!(granted, 1000);
+    }
+
+    #[test]
+    fn test_token_bucket_refills_at_rate() {
+        let bucket = TokenBucket::new(2, 10); // 100ms per token
+        let t0 = bucket.origin;
+        assert!(bucket.try_acquire_at(2, t0));
+        assert!(!bucket.try_acquire_at(1, t0 + Duration::from_millis(50)));
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_millis(100)));
+        // 长时间空闲后最多恢复到容量
+        assert!(bucket.try_acquire_at(2, t0 + Duration::from_secs(10)));
+        assert!(!bucket.try_acquire_at(1, t0 + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_wait_time_until_next_token() {
+        let bucket = TokenBucket::with_interval(2, Duration::from_secs(12));
+        let t0 = bucket.origin;
+        assert_eq!(bucket.wait_time_at(1, t0), Duration::ZERO);
+        assert!(bucket.try_acquire_at(2, t0));
+        assert!(!bucket.try_acquire_at(1, t0 + Duration::from_secs(2)));
+        assert_eq!(bucket.wait_time_at(1, t0 + Duration::from_secs(2)), Duration::from_secs(10));
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn test_time_to_full_after_draining() {
+        let bucket = TokenBucket::with_interval(3, Duration::from_secs(10));
+        let t0 = bucket.origin;
+        assert_eq!(bucket.time_to_full_at(t0), Duration::ZERO);
+        assert!(bucket.try_acquire_at(2, t0));
+        assert_eq!(bucket.time_to_full_at(t0 + Duration::from_secs(5)), Duration::from_secs(15));
+        assert_eq!(bucket.time_to_full_at(t0 + Duration::from_secs(30)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_consume_drains_without_overdrawing() {
+        let bucket = TokenBucket::with_interval(4, Duration::from_secs(1));
+        let t0 = bucket.origin;
+        bucket.consume_at(3, t0);
+        assert!(bucket.try_acquire_at(1, t0));
+        assert!(!bucket.try_acquire_at(1, t0));
+        // 超出容量的用量不会让桶欠账：补充一个间隔后即有令牌
+        bucket.consume_at(100, t0);
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_secs(1)));
+    }
+
+    /// 可复现的伪随机数（xorshift64*），用于下面的性质测试；失败信息中带 seed 以便复现
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn range(&mut self, lo: u64, hi: u64) -> u64 {
+            lo + self.next() % (hi - lo + 1)
+        }
+
+        /// 随机容量、速率与周期（1ms ~ 5s），覆盖除不尽的速率
+        fn bucket(&mut self, min_capacity: u64) -> TokenBucket {
+            TokenBucket::with_rate(self.range(min_capacity, 50), self.range(1, 1000), Duration::from_millis(self.range(1, 5000)))
+        }
+    }
+
+    const CASES: u64 = 100;
+
+    #[test]
+    fn prop_long_run_grants_match_rate_exactly() {
+        for seed in 1..=CASES {
+            let mut rng = Rng(seed);
+            let bucket = rng.bucket(2);
+            let t0 = bucket.origin;
+            // 每一步都把桶取空，且步长内补充的令牌不超过取空后的空位（桶不会满溢而浪费令牌）
+            let max_step = ((bucket.capacity - 1) as u128 * bucket.period_nanos as u128 / bucket.refill as u128).max(1) as u64;
+            let step = rng.range((max_step / 4).max(1), max_step);
+            let steps = rng.range(1, 500);
+            let mut granted = 0u64;
+            for i in 0..=steps {
+                let now = t0 + Duration::from_nanos(step * i);
+                while bucket.try_acquire_at(1, now) {
+                    granted += 1;
+                }
+            }
+            let elapsed = step as u128 * steps as u128;
+            let expected = bucket.capacity + (elapsed * bucket.refill as u128 / bucket.period_nanos as u128) as u64;
+            assert_eq!(granted, expected, "seed {}: {:?}", seed, bucket);
+        }
+    }
+
+    #[test]
+    fn prop_out_of_order_timestamps_never_over_grant() {
+        for seed in 1..=CASES {
+            let mut rng = Rng(seed);
+            let bucket = rng.bucket(1);
+            let t0 = bucket.origin;
+            let horizon = bucket.period_nanos.saturating_mul(rng.range(1, 20));
+            let mut granted = 0u64;
+            // 并发请求取到的时刻先后不一，相当于时钟在 CAS 之间“回退”
+            for _ in 0..rng.range(1, 2000) {
+                let now = t0 + Duration::from_nanos(rng.range(0, horizon));
+                let tokens = rng.range(1, 3);
+                if bucket.try_acquire_at(tokens, now) {
+                    granted += tokens;
+                }
+                assert!(bucket.available_at(bucket.tat.load(Ordering::Acquire), bucket.ticks_at(now)) <= bucket.capacity);
+            }
+            let ceiling = bucket.capacity + (horizon as u128 * bucket.refill as u128 / bucket.period_nanos as u128) as u64;
+            assert!(granted <= ceiling, "seed {}: granted {} > {}", seed, granted, ceiling);
+        }
+    }
+
+    #[test]
+    fn prop_wait_time_is_sufficient() {
+        for seed in 1..=CASES {
+            let mut rng = Rng(seed);
+            let bucket = rng.bucket(1);
+            let now = bucket.origin + Duration::from_nanos(rng.range(0, bucket.period_nanos));
+            while bucket.try_acquire_at(1, now) {}
+            let tokens = rng.range(1, bucket.capacity);
+            let wait = bucket.wait_time_at(tokens, now);
+            assert!(wait > Duration::ZERO, "seed {}", seed);
+            assert!(bucket.try_acquire_at(tokens, now + wait), "seed {}: {:?} after {:?}", seed, bucket, wait);
+        }
+    }
+
+    #[test]
+    fn test_millisecond_refill_interval() {
+        // 每 7ms 补充 3 个：1000 个周期后恰好补充 3000 个，不因 7ms / 3 除不尽而漂移
+        let bucket = TokenBucket::with_rate(3, 3, Duration::from_millis(7));
+        let t0 = bucket.origin;
+        assert!(bucket.try_acquire_at(3, t0));
+        assert!(!bucket.try_acquire_at(1, t0 + Duration::from_millis(2)));
+        assert!(bucket.try_acquire_at(1, t0 + Duration::from_micros(2334)));
+        let mut granted = 1;
+        for ms in 1..=7000 {
+            while bucket.try_acquire_at(1, t0 + Duration::from_millis(ms)) {
+                granted += 1;
+            }
+        }
+        assert_eq!(granted, 3000);
+        assert_eq!(bucket.wait_time_at(3, t0 + Duration::from_millis(7000)), Duration::from_millis(7));
+    }
+
+    #[test]
+    fn test_sliding_window_is_strict_across_boundaries() {
+        let log = SlidingWindowLog::new(3, Duration::from_secs(60));
+        let t0 = Instant::now();
+        for s in [0, 10, 59] {
+            assert!(log.try_acquire_at(t0 + Duration::from_secs(s)).is_ok());
+        }
+        // 任意 60s 内不超过 3 次：第一次放行移出窗口前一直拒绝
+        assert_eq!(log.try_acquire_at(t0 + Duration::from_secs(59)), Err(Duration::from_secs(1)));
+        assert!(log.try_acquire_at(t0 + Duration::from_secs(60)).is_ok());
+        assert_eq!(log.try_acquire_at(t0 + Duration::from_secs(61)), Err(Duration::from_secs(9)));
+        assert!(SlidingWindowLog::new(0, Duration::from_secs(1)).try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_fixed_window_resets_each_window() {
+        let window = FixedWindow::new(2, Duration::from_secs(60));
+        let t0 = window.origin;
+        assert!(window.try_acquire_at(t0 + Duration::from_secs(50)).is_ok());
+        assert!(window.try_acquire_at(t0 + Duration::from_secs(55)).is_ok());
+        assert_eq!(window.try_acquire_at(t0 + Duration::from_secs(58)), Err(Duration::from_secs(2)));
+        // 新窗口立即恢复（边界前后共放行 4 次，这是固定窗口与滑动窗口的区别）
+        assert!(window.try_acquire_at(t0 + Duration::from_secs(60)).is_ok());
+        assert!(window.try_acquire_at(t0 + Duration::from_secs(61)).is_ok());
+        assert!(window.try_acquire_at(t0 + Duration::from_secs(62)).is_err());
+    }
+
+    #[test]
+    fn test_limiter_selects_algorithm() {
+        assert_eq!("sliding_window".parse::<Algorithm>(), Ok(Algorithm::SlidingWindow));
+        assert!("leaky_bucket".parse::<Algorithm>().is_err());
+        for algorithm in [Algorithm::TokenBucket, Algorithm::SlidingWindow, Algorithm::FixedWindow] {
+            assert_eq!(algorithm.as_str().parse::<Algorithm>(), Ok(algorithm));
+            assert_eq!(Limiter::new(algorithm, 60, 10, Duration::from_secs(60)).algorithm(), algorithm);
+        }
+        // 令牌桶允许突发到 burst，窗口算法严格按 limit
+        let burst = |algorithm| {
+            let limiter = Limiter::new(algorithm, 5, 10, Duration::from_secs(60));
+            let now = Instant::now();
+            (0..20).filter(|_| limiter.check_at(now).is_ok()).count()
+        };
+        assert_eq!(burst(Algorithm::TokenBucket), 10);
+        assert_eq!(burst(Algorithm::SlidingWindow), 5);
+        assert_eq!(burst(Algorithm::FixedWindow), 5);
+    }
+
+    #[test]
+    fn test_keyed_limiter_with_window_algorithm() {
+        let limiter = KeyedLimiter::per_minute(2, 10).with_algorithm(Algorithm::SlidingWindow);
+        assert!(limiter.check("alice").is_ok() && limiter.check("alice").is_ok());
+        let wait = limiter.check("alice").unwrap_err();
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60), "{:?}", wait);
+        assert!(limiter.check("bob").is_ok());
+    }
+
+    #[test]
+    fn test_keyed_limiter_keeps_limited_keys_when_full() {
+        let limiter = KeyedLimiter::with_interval(Duration::from_secs(60), 1).with_max_keys(3);
+        let t0 = Instant::now();
+        for key in ["alice", "bob", "carol"] {
+            assert!(limiter.check_at(key, t0).is_ok());
+        }
+        // bob、carol 已补满；alice 仍在限流
+        let t1 = t0 + Duration::from_secs(61);
+        assert!(limiter.check_at("alice", t1).is_ok());
+        assert!(limiter.check_at("dave", t1).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 2);
+        assert!(limiter.check_at("alice", t1).is_err(), "limited key must survive eviction");
+    }
+
+    #[test]
+    fn test_keyed_limiter_evicts_least_recently_seen_when_all_active() {
+        let limiter = KeyedLimiter::with_interval(Duration::from_secs(60), 1).with_max_keys(3);
+        let t0 = Instant::now();
+        for (i, key) in ["alice", "bob", "carol", "dave"].into_iter().enumerate() {
+            assert!(limiter.check_at(key, t0 + Duration::from_secs(i as u64)).is_ok());
+        }
+        let t = t0 + Duration::from_secs(4);
+        assert!(limiter.check_at("bob", t).is_err());
+        assert!(limiter.check_at("carol", t).is_err());
+        assert!(limiter.check_at("alice", t).is_ok(), "oldest key was evicted");
+    }
+
+    #[test]
+    fn test_keyed_limiter_isolates_keys() {
+        let limiter = KeyedLimiter::per_minute(1, 2);
+        assert!(limiter.check("alice").is_ok());
+        assert!(limiter.check("alice").is_ok());
+        let wait = limiter.check("alice").unwrap_err();
+        assert!(wait > Duration::from_secs(50) && wait <= Duration::from_secs(60), "{:?}", wait);
+        assert!(limiter.check("bob").is_ok());
+    }
+}
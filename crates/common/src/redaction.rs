@@ -0,0 +1,228 @@
+//! 敏感数据脱敏：写日志、落库（request_log、config_revision 等）之前统一处理。
+//!
+//! - `headers`：请求/响应头名（大小写不敏感）；
+//! - `query_keys`：查询参数名，URI 与自由文本（错误信息）中的 `key=value` 同样处理；
+//! - `json_paths`：JSON body 字段路径。不含 `.` 的路径匹配任意深度的同名字段；
+//!   含 `.` 的路径从根开始匹配，`*` 匹配任意字段名，数组透明展开（`cards.number` 匹配 `cards[*].number`）。
+//!
+//! 服务端在启动时 [`install`] 配置，之后各处通过 [`global`] 取用；网关随 config.json 热更新。
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 替换后的值
+pub const MASK: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default = "default_headers")]
+    pub headers: Vec<String>,
+    #[serde(default = "default_query_keys")]
+    pub query_keys: Vec<String>,
+    #[serde(default = "default_json_paths")]
+    pub json_paths: Vec<String>,
+}
+
+fn default_headers() -> Vec<String> {
+    ["authorization", "proxy-authorization", "cookie", "set-cookie", "x-api-key"].map(String::from).to_vec()
+}
+fn default_query_keys() -> Vec<String> {
+    ["api_key", "apikey", "access_token", "token", "password", "secret", "signature"].map(String::from).to_vec()
+}
+fn default_json_paths() -> Vec<String> {
+    ["password", "token", "access_token", "refresh_token", "api_key", "secret"].map(String::from).to_vec()
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self { headers: default_headers(), query_keys: default_query_keys(), json_paths: default_json_paths() }
+    }
+}
+
+static GLOBAL: OnceLock<RedactionConfig> = OnceLock::new();
+
+/// 设置进程级脱敏配置；只有第一次调用生效
+pub fn install(cfg: RedactionConfig) {
+    let _ = GLOBAL.set(cfg);
+}
+
+/// 进程级脱敏配置，未 [`install`] 时为默认值
+pub fn global() -> &'static RedactionConfig {
+    GLOBAL.get_or_init(RedactionConfig::default)
+}
+
+impl RedactionConfig {
+    pub fn is_sensitive_header(&self, name: &str) -> bool {
+        self.headers.iter().any(|h| h.eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_sensitive_query_key(&self, key: &str) -> bool {
+        self.query_keys.iter().any(|k| k.eq_ignore_ascii_case(key))
+    }
+
+    pub fn header_value<'a>(&self, name: &str, value: &'a str) -> &'a str {
+        if self.is_sensitive_header(name) { MASK } else { value }
+    }
+
+    /// 替换查询串中敏感参数的值，参数名与顺序保持不变
+    pub fn redact_uri<'a>(&self, uri: &'a str) -> Cow<'a, str> {
+        let Some((path, query)) = uri.split_once('?') else { return Cow::Borrowed(uri) };
+        let pairs: Vec<Cow<str>> = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((k, _)) if self.is_sensitive_query_key(k) => Cow::Owned(format!("{}={}", k, MASK)),
+                _ => Cow::Borrowed(pair),
+            })
+            .collect();
+        if pairs.iter().all(|p| matches!(p, Cow::Borrowed(_))) {
+            return Cow::Borrowed(uri);
+        }
+        Cow::Owned(format!("{}?{}", path, pairs.join("&")))
+    }
+
+    pub fn redact_json(&self, value: &mut Value) {
+        for path in &self.json_paths {
+            let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+            match segments.as_slice() {
+                [] => {}
+                [key] => mask_key_anywhere(value, key),
+                _ => mask_path(value, &segments),
+            }
+        }
+    }
+
+    /// 请求/响应体：JSON 按字段路径处理，表单按查询参数处理，其他内容原样返回
+    pub fn redact_body(&self, body: &str) -> String {
+        if let Ok(mut json) = serde_json::from_str::<Value>(body) {
+            if json.is_object() || json.is_array() {
+                self.redact_json(&mut json);
+                return json.to_string();
+            }
+        }
+        if !body.contains(char::is_whitespace) && body.contains('=') {
+            return self.redact_uri(&format!("?{}", body))[1..].to_string();
+        }
+        body.to_string()
+    }
+
+    /// 自由文本（错误信息等）：JSON 按字段路径处理；否则替换 `key=value` 中的敏感值与 `Bearer` 凭据
+    pub fn redact_text(&self, text: &str) -> String {
+        if let Ok(mut json) = serde_json::from_str::<Value>(text) {
+            if json.is_object() || json.is_array() {
+                self.redact_json(&mut json);
+                return json.to_string();
+            }
+        }
+        let mut out = mask_after(text, "bearer ", |_| true);
+        for key in &self.query_keys {
+            out = mask_after(&out, &format!("{}=", key), |before| before.is_none_or(|c| !c.is_ascii_alphanumeric() && c != '_'));
+        }
+        out
+    }
+}
+
+fn mask_key_anywhere(value: &mut Value, key: &str) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if k.eq_ignore_ascii_case(key) {
+                    *v = Value::String(MASK.into());
+                } else {
+                    mask_key_anywhere(v, key);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| mask_key_anywhere(v, key)),
+        _ => {}
+    }
+}
+
+fn mask_path(value: &mut Value, segments: &[&str]) {
+    let Some((first, rest)) = segments.split_first() else { return };
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                if *first == "*" || k.eq_ignore_ascii_case(first) {
+                    if rest.is_empty() {
+                        *v = Value::String(MASK.into());
+                    } else {
+                        mask_path(v, rest);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| mask_path(v, segments)),
+        _ => {}
+    }
+}
+
+/// 大小写不敏感地查找 `marker`，把其后直到分隔符的内容替换为 [`MASK`]；
+/// `boundary` 检查 marker 之前的字符，避免 `mytoken=` 命中 `token=`
+fn mask_after(text: &str, marker: &str, boundary: impl Fn(Option<char>) -> bool) -> String {
+    let lower = text.to_ascii_lowercase();
+    let marker = marker.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find(&marker) {
+        let start = pos + found;
+        let value_start = start + marker.len();
+        out.push_str(&text[pos..value_start]);
+        if !boundary(text[..start].chars().next_back()) {
+            pos = value_start;
+            continue;
+        }
+        let len = text[value_start..].find(|c: char| c.is_whitespace() || "&,;\"'".contains(c)).unwrap_or(text.len() - value_start);
+        if len > 0 {
+            out.push_str(MASK);
+        }
+        pos = value_start + len;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn headers_and_query_strings() {
+        let r = RedactionConfig::default();
+        assert_eq!(r.header_value("Authorization", "Bearer abc"), MASK);
+        assert_eq!(r.header_value("accept", "*/*"), "*/*");
+        assert_eq!(r.redact_uri("/v1/users?page=2&API_KEY=k1&token=t"), "/v1/users?page=2&API_KEY=[REDACTED]&token=[REDACTED]");
+        assert!(matches!(r.redact_uri("/v1/users?page=2"), Cow::Borrowed(_)));
+        assert_eq!(r.redact_body("user=ada&password=hunter2"), "user=ada&password=[REDACTED]");
+    }
+
+    #[test]
+    fn json_paths_nested_and_anywhere() {
+        let r = RedactionConfig { json_paths: vec!["password".into(), "cards.number".into(), "meta.*.ssn".into()], ..Default::default() };
+        let mut v = json!({
+            "user": { "name": "ada", "Password": "p1" },
+            "cards": [{ "number": "4111", "brand": "visa" }],
+            "meta": { "a": { "ssn": "123" } },
+            "number": "keep"
+        });
+        r.redact_json(&mut v);
+        assert_eq!(v["user"]["Password"], MASK);
+        assert_eq!(v["cards"][0]["number"], MASK);
+        assert_eq!(v["cards"][0]["brand"], "visa");
+        assert_eq!(v["meta"]["a"]["ssn"], MASK);
+        assert_eq!(v["number"], "keep");
+    }
+
+    #[test]
+    fn free_text() {
+        let r = RedactionConfig::default();
+        assert_eq!(
+            r.redact_text("upstream GET http://x/y?token=abc&mytoken=ok failed: Authorization: Bearer eyJ.x"),
+            "upstream GET http://x/y?token=[REDACTED]&mytoken=ok failed: Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(r.redact_text(r#"{"password":"x"}"#), r#"{"password":"[REDACTED]"}"#);
+    }
+}
@@ -0,0 +1,295 @@
+//! 路由匹配：按路径段组织的前缀树，带方法分发、`{name}` 路径参数与末尾 `*` 通配。
+//!
+//! - 匹配优先级：静态段 > 参数段 > 通配，走不通时回溯；
+//! - 前缀路由（`prefix = true`）在没有更深的匹配时命中，剩余路径通过 [`Match::rest`] 返回；
+//! - 方法为 `*` 的路由匹配任意方法，同一节点上具体方法优先。
+//!
+//! 查找开销只与路径段数有关，与路由总数无关；路由表变化时整体重建后替换（见 `ArcSwap` 用法）。
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// 匹配任意方法
+pub const ANY_METHOD: &str = "*";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// `*` 只能出现在最后一段
+    WildcardNotLast(String),
+    EmptyParam(String),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::WildcardNotLast(p) => write!(f, "wildcard must be the last segment: {}", p),
+            RouteError::EmptyParam(p) => write!(f, "empty path parameter name: {}", p),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+struct Endpoint<T> {
+    value: T,
+    /// 参数名（按出现顺序），通配段记为 `*`
+    params: Vec<Box<str>>,
+    prefix: bool,
+    /// 模式以 `/` 结尾：请求路径恰好多一个 `/` 时不算剩余路径
+    trailing_slash: bool,
+}
+
+struct Node<T> {
+    statics: HashMap<Box<str>, Node<T>>,
+    param: Option<Box<Node<T>>>,
+    wildcard: Vec<(Box<str>, Endpoint<T>)>,
+    endpoints: Vec<(Box<str>, Endpoint<T>)>,
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self { statics: HashMap::new(), param: None, wildcard: Vec::new(), endpoints: Vec::new() }
+    }
+}
+
+fn for_method<'a, T>(endpoints: &'a [(Box<str>, Endpoint<T>)], method: &str) -> Option<&'a Endpoint<T>> {
+    endpoints
+        .iter()
+        .find(|(m, _)| m.eq_ignore_ascii_case(method))
+        .or_else(|| endpoints.iter().find(|(m, _)| &**m == ANY_METHOD))
+        .map(|(_, e)| e)
+}
+
+/// 返回是否为新路由
+fn upsert<T>(endpoints: &mut Vec<(Box<str>, Endpoint<T>)>, method: Box<str>, endpoint: Endpoint<T>) -> bool {
+    match endpoints.iter_mut().find(|(m, _)| *m == method) {
+        Some(slot) => {
+            slot.1 = endpoint;
+            false
+        }
+        None => {
+            endpoints.push((method, endpoint));
+            true
+        }
+    }
+}
+
+/// 校验模式并返回参数名（按出现顺序，通配段为 `*`）
+pub fn pattern_params(pattern: &str) -> Result<Vec<String>, RouteError> {
+    let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let mut params = Vec::new();
+    for (i, seg) in segments.iter().enumerate() {
+        if *seg == "*" {
+            if i + 1 != segments.len() {
+                return Err(RouteError::WildcardNotLast(pattern.to_string()));
+            }
+            params.push("*".to_string());
+        } else if let Some(name) = seg.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            if name.is_empty() {
+                return Err(RouteError::EmptyParam(pattern.to_string()));
+            }
+            params.push(name.to_string());
+        }
+    }
+    Ok(params)
+}
+
+/// 一次成功匹配
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<'r, 'p, T> {
+    pub value: &'r T,
+    /// (参数名, 值)；通配段名为 `*`，值为不带前导 `/` 的剩余路径
+    pub params: Vec<(&'r str, &'p str)>,
+    /// 前缀路由未消费的路径（以 `/` 开头或为空）
+    pub rest: &'p str,
+}
+
+impl<T> Match<'_, '_, T> {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+}
+
+pub struct Router<T> {
+    root: Node<T>,
+    len: usize,
+}
+
+impl<T> Default for Router<T> {
+    fn default() -> Self {
+        Self { root: Node::default(), len: 0 }
+    }
+}
+
+impl<T> Router<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 注册路由；相同方法与模式重复注册时后者覆盖前者
+    pub fn insert(&mut self, method: &str, pattern: &str, value: T, prefix: bool) -> Result<(), RouteError> {
+        let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+        let mut node = &mut self.root;
+        let mut params = Vec::new();
+        for (i, seg) in segments.iter().enumerate() {
+            if *seg == "*" {
+                if i + 1 != segments.len() {
+                    return Err(RouteError::WildcardNotLast(pattern.to_string()));
+                }
+                params.push("*".into());
+                let endpoint = Endpoint { value, params, prefix: false, trailing_slash: false };
+                self.len += upsert(&mut node.wildcard, method.to_ascii_uppercase().into(), endpoint) as usize;
+                return Ok(());
+            }
+            node = match seg.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some("") => return Err(RouteError::EmptyParam(pattern.to_string())),
+                Some(name) => {
+                    params.push(name.into());
+                    node.param.get_or_insert_with(Default::default)
+                }
+                None => node.statics.entry((*seg).into()).or_default(),
+            };
+        }
+        let endpoint = Endpoint { value, params, prefix, trailing_slash: pattern.ends_with('/') };
+        self.len += upsert(&mut node.endpoints, method.to_ascii_uppercase().into(), endpoint) as usize;
+        Ok(())
+    }
+
+    pub fn at<'r, 'p>(&'r self, method: &str, path: &'p str) -> Option<Match<'r, 'p, T>> {
+        let segments = segment_spans(path);
+        let mut captures = Vec::new();
+        let (endpoint, consumed) = walk(&self.root, method, path, &segments, 0, &mut captures)?;
+        let end = if consumed == 0 { 0 } else { segments[consumed - 1].1 };
+        let mut rest = if endpoint.params.last().is_some_and(|p| &**p == "*") { "" } else { &path[end..] };
+        if rest == "/" && consumed == segments.len() && (endpoint.trailing_slash || !endpoint.prefix) {
+            rest = "";
+        }
+        let params = endpoint.params.iter().map(|n| &**n).zip(captures).collect();
+        Some(Match { value: &endpoint.value, params, rest })
+    }
+}
+
+/// 非空路径段的 (起, 止) 字节位置
+fn segment_spans(path: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (i, b) in path.bytes().enumerate() {
+        if b == b'/' {
+            if i > start {
+                spans.push((start, i));
+            }
+            start = i + 1;
+        }
+    }
+    if path.len() > start {
+        spans.push((start, path.len()));
+    }
+    spans
+}
+
+fn walk<'r, 'p, T>(
+    node: &'r Node<T>,
+    method: &str,
+    path: &'p str,
+    segments: &[(usize, usize)],
+    i: usize,
+    captures: &mut Vec<&'p str>,
+) -> Option<(&'r Endpoint<T>, usize)> {
+    let Some(&(start, end)) = segments.get(i) else {
+        if let Some(e) = for_method(&node.endpoints, method) {
+            return Some((e, i));
+        }
+        // `/files/*` 也匹配 `/files`
+        return for_method(&node.wildcard, method).map(|e| {
+            captures.push("");
+            (e, i)
+        });
+    };
+    let seg = &path[start..end];
+    if let Some(child) = node.statics.get(seg) {
+        if let Some(found) = walk(child, method, path, segments, i + 1, captures) {
+            return Some(found);
+        }
+    }
+    if let Some(child) = &node.param {
+        captures.push(seg);
+        if let Some(found) = walk(child, method, path, segments, i + 1, captures) {
+            return Some(found);
+        }
+        captures.pop();
+    }
+    if let Some(e) = for_method(&node.wildcard, method) {
+        captures.push(&path[start..]);
+        return Some((e, segments.len()));
+    }
+    for_method(&node.endpoints, method).filter(|e| e.prefix).map(|e| (e, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> Router<&'static str> {
+        let mut r = Router::new();
+        r.insert("GET", "/api/posts", "posts", true).unwrap();
+        r.insert("GET", "/api/posts/special", "special", true).unwrap();
+        r.insert("GET", "/users/{id}", "user", false).unwrap();
+        r.insert("GET", "/users/me", "me", false).unwrap();
+        r.insert("*", "/users/{id}/orders/{order}", "order", false).unwrap();
+        r.insert("GET", "/files/*", "files", false).unwrap();
+        r
+    }
+
+    #[test]
+    fn static_prefix_and_longest_match() {
+        let r = router();
+        let m = r.at("get", "/api/posts/1").unwrap();
+        assert_eq!((*m.value, m.rest), ("posts", "/1"));
+        assert_eq!(r.at("GET", "/api/posts").unwrap().rest, "");
+        assert_eq!(r.at("GET", "/api/posts/").unwrap().rest, "/");
+        let m = r.at("GET", "/api/posts/special/x").unwrap();
+        assert_eq!((*m.value, m.rest), ("special", "/x"));
+        assert!(r.at("GET", "/api/postsx").is_none());
+        assert!(r.at("POST", "/api/posts").is_none());
+    }
+
+    #[test]
+    fn params_wildcards_and_methods() {
+        let r = router();
+        assert_eq!(*r.at("GET", "/users/me").unwrap().value, "me");
+        let m = r.at("GET", "/users/42").unwrap();
+        assert_eq!((*m.value, m.param("id")), ("user", Some("42")));
+        assert!(r.at("GET", "/users/42/x").is_none());
+
+        // 任意方法；静态 `me` 走不通时回溯到参数段
+        let m = r.at("DELETE", "/users/me/orders/7").unwrap();
+        assert_eq!(m.params, vec![("id", "me"), ("order", "7")]);
+
+        let m = r.at("GET", "/files/a/b.txt").unwrap();
+        assert_eq!((*m.value, m.param("*"), m.rest), ("files", Some("a/b.txt"), ""));
+        assert_eq!(r.at("GET", "/files").unwrap().param("*"), Some(""));
+    }
+
+    #[test]
+    fn rejects_bad_patterns_and_overwrites_duplicates() {
+        let mut r = Router::new();
+        assert_eq!(r.insert("GET", "/a/*/b", 1, false), Err(RouteError::WildcardNotLast("/a/*/b".into())));
+        assert!(r.insert("GET", "/a/{}", 1, false).is_err());
+        assert_eq!(pattern_params("/orders/{id}/items/*").unwrap(), vec!["id", "*"]);
+        assert!(pattern_params("/a/*/b").is_err());
+        r.insert("GET", "/", 1, true).unwrap();
+        r.insert("GET", "/", 2, true).unwrap();
+        assert_eq!(r.len(), 1);
+        let m = r.at("GET", "/anything").unwrap();
+        assert_eq!((*m.value, m.rest), (2, "/anything"));
+        assert_eq!(r.at("GET", "/").unwrap().rest, "");
+    }
+}
@@ -0,0 +1,502 @@
+//! SIEM 导出：把 `audit`（管理操作、配置变更）与 `security`（认证失败、WAF/地域/机器人拦截）target 的
+//! tracing 事件以 RFC 5424 syslog（消息体为 JSON）或 CEF 格式发送到 SIEM。
+//!
+//! - 传输：`udp://host:514`（每条一个报文）、`tcp://host:601` 与 `tls://host:6514`（RFC 5425 octet counting 分帧）
+//! - 事件先进入有界队列，由独立线程发送，不阻塞请求处理；队列满时丢弃，连接失败时退避重连并重发当前事件
+//! - 发送结果见 `api_proxy_siem_events_total{result="sent|dropped|failed"}`
+//! - 独立于 `logging.level`：日志级别调高后审计事件仍会导出；字段按 `[redaction]` 规则脱敏
+
+use std::fmt;
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use prometheus::IntCounterVec;
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{Filtered, Targets};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+const CEF_VENDOR: &str = "dexcodehub";
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SiemFormat {
+    /// `CEF:0|dexcodehub|{app_name}|...`，外层为 RFC 5424 头
+    #[default]
+    Cef,
+    /// RFC 5424，消息体为 JSON 对象
+    Syslog,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiemConfig {
+    pub enabled: bool,
+    /// `udp://host:514`、`tcp://host:601` 或 `tls://host:6514`
+    pub endpoint: String,
+    pub format: SiemFormat,
+    /// 导出的 tracing target
+    pub targets: Vec<String>,
+    /// syslog facility（0-23），默认 13（log audit）
+    pub facility: u8,
+    /// RFC 5424 APP-NAME，同时作为 CEF Device Product
+    pub app_name: String,
+    /// 缺省取环境变量 HOSTNAME
+    pub hostname: Option<String>,
+    /// tls：额外信任的 CA（PEM）
+    pub ca_path: Option<String>,
+    /// tls：客户端证书与 PKCS#8 私钥（PEM），SIEM 要求双向认证时配置
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+    /// 队列容量
+    pub buffer: usize,
+    pub connect_timeout_secs: u64,
+}
+
+impl Default for SiemConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: String::new(),
+            format: SiemFormat::Cef,
+            targets: vec!["audit".to_string(), "security".to_string()],
+            facility: 13,
+            app_name: "api-proxy".to_string(),
+            hostname: None,
+            ca_path: None,
+            client_cert_path: None,
+            client_key_path: None,
+            buffer: 10_000,
+            connect_timeout_secs: 5,
+        }
+    }
+}
+
+impl SiemConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let endpoint = Endpoint::parse(&self.endpoint)?;
+        if self.facility > 23 {
+            return Err("facility must be 0..=23".into());
+        }
+        if self.targets.is_empty() || self.buffer == 0 {
+            return Err("targets must not be empty and buffer must be >= 1".into());
+        }
+        if self.client_cert_path.is_some() != self.client_key_path.is_some() {
+            return Err("client_cert_path and client_key_path must be set together".into());
+        }
+        if endpoint.transport != Transport::Tls && (self.ca_path.is_some() || self.client_cert_path.is_some()) {
+            return Err("ca_path / client_cert_path require a tls:// endpoint".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Udp,
+    Tcp,
+    Tls,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub transport: Transport,
+    pub host: String,
+    pub port: u16,
+}
+
+impl Endpoint {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (scheme, rest) = s.split_once("://").ok_or_else(|| format!("endpoint {:?} must look like tls://host:6514", s))?;
+        let transport = match scheme {
+            "udp" => Transport::Udp,
+            "tcp" => Transport::Tcp,
+            "tls" => Transport::Tls,
+            other => return Err(format!("unsupported endpoint scheme {}; expected udp, tcp or tls", other)),
+        };
+        let (host, port) = rest.rsplit_once(':').ok_or_else(|| format!("endpoint {:?} has no port", s))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port: u16 = port.trim_end_matches('/').parse().map_err(|_| format!("endpoint {:?} has an invalid port", s))?;
+        if host.is_empty() || port == 0 {
+            return Err(format!("endpoint {:?} needs a host and a port", s));
+        }
+        Ok(Self { transport, host: host.to_string(), port })
+    }
+}
+
+fn events_total() -> &'static IntCounterVec {
+    static COUNTER: OnceLock<IntCounterVec> = OnceLock::new();
+    COUNTER.get_or_init(|| {
+        prometheus::register_int_counter_vec!("api_proxy_siem_events_total", "Audit/security events exported to the SIEM by result (sent/dropped/failed)", &["result"])
+            .expect("register siem_events_total")
+    })
+}
+
+/// 一条待导出的事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiemEvent {
+    pub timestamp: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// 按记录顺序的字段（不含 message）
+    pub fields: Vec<(String, String)>,
+}
+
+impl SiemEvent {
+    fn field(&self, names: &[&str]) -> Option<&str> {
+        names.iter().find_map(|n| self.fields.iter().find(|(k, _)| k == n).map(|(_, v)| v.as_str()))
+    }
+
+    /// `action`（控制面）或 `event`（网关）字段，缺省为 target
+    pub fn action(&self) -> &str {
+        self.field(&["action", "event"]).unwrap_or(&self.target)
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
+
+/// 收集事件并交给发送线程
+pub struct SiemLayer {
+    tx: SyncSender<SiemEvent>,
+}
+
+impl<S: Subscriber> Layer<S> for SiemLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let meta = event.metadata();
+        // 与落库日志相同的脱敏规则
+        let redaction = crate::redaction::global();
+        let fields = visitor.fields.into_iter().map(|(k, v)| (k, redaction.redact_text(&v))).collect();
+        let ev = SiemEvent { timestamp: Utc::now(), level: *meta.level(), target: meta.target().to_string(), message: redaction.redact_text(&visitor.message), fields };
+        if self.tx.try_send(ev).is_err() {
+            events_total().with_label_values(&["dropped"]).inc();
+        }
+    }
+}
+
+/// 按配置创建导出层并启动发送线程；未启用或配置无效时返回 `None`（无效时在 stderr 提示）
+pub fn layer<S>(cfg: &SiemConfig) -> Option<Filtered<SiemLayer, Targets, S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if !cfg.enabled {
+        return None;
+    }
+    let sender = match cfg.validate().and_then(|_| Sender::new(cfg)) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("logging.siem disabled: {}", e);
+            return None;
+        }
+    };
+    let (tx, rx) = mpsc::sync_channel(cfg.buffer);
+    let spawned = std::thread::Builder::new().name("siem-export".into()).spawn(move || sender.run(rx));
+    if let Err(e) = spawned {
+        eprintln!("logging.siem disabled: spawn exporter thread: {}", e);
+        return None;
+    }
+    let targets = cfg.targets.iter().fold(Targets::new(), |t, name| t.with_target(name.clone(), Level::TRACE));
+    Some(SiemLayer { tx }.with_filter(targets))
+}
+
+fn syslog_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 5,
+        _ => 7,
+    }
+}
+
+fn cef_severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 8,
+        Level::WARN => 6,
+        Level::INFO => 3,
+        _ => 1,
+    }
+}
+
+/// RFC 5424 头字段：可打印 ASCII、无空格，空值用 `-`
+fn header_token(s: &str, max: usize) -> String {
+    let t: String = s.chars().filter(|c| c.is_ascii_graphic()).take(max).collect();
+    if t.is_empty() { "-".into() } else { t }
+}
+
+fn cef_header(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn cef_value(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('=', "\\=").replace('\r', "\\r").replace('\n', "\\n")
+}
+
+/// CEF 扩展键只允许字母数字
+fn cef_key(s: &str) -> String {
+    s.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+pub fn format_cef(app_name: &str, ev: &SiemEvent) -> String {
+    let action = ev.action();
+    let name = if ev.message.is_empty() { action } else { ev.message.as_str() };
+    let mut ext = vec![format!("rt={}", ev.timestamp.timestamp_millis()), format!("cat={}", cef_value(&ev.target))];
+    for (k, v) in &ev.fields {
+        let key = match k.as_str() {
+            "action" | "event" => "act".to_string(),
+            "client" | "client_ip" => "src".to_string(),
+            "email" | "user" | "principal" | "subject" => "suser".to_string(),
+            "path" | "uri" => "request".to_string(),
+            "method" => "requestMethod".to_string(),
+            other => cef_key(other),
+        };
+        if !key.is_empty() {
+            ext.push(format!("{}={}", key, cef_value(v)));
+        }
+    }
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+        CEF_VENDOR,
+        cef_header(app_name),
+        env!("CARGO_PKG_VERSION"),
+        cef_header(&format!("{}:{}", ev.target, action)),
+        cef_header(name),
+        cef_severity(&ev.level),
+        ext.join(" ")
+    )
+}
+
+pub fn format_json(ev: &SiemEvent) -> String {
+    let mut obj = serde_json::Map::new();
+    obj.insert("target".into(), ev.target.clone().into());
+    obj.insert("level".into(), ev.level.as_str().into());
+    obj.insert("message".into(), ev.message.clone().into());
+    for (k, v) in &ev.fields {
+        obj.insert(k.clone(), v.clone().into());
+    }
+    serde_json::Value::Object(obj).to_string()
+}
+
+/// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID - MSG`
+pub fn format_syslog(facility: u8, hostname: &str, app_name: &str, msg: &str, ev: &SiemEvent) -> String {
+    format!(
+        "<{}>1 {} {} {} {} {} - {}",
+        facility as u16 * 8 + syslog_severity(&ev.level) as u16,
+        ev.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+        header_token(hostname, 255),
+        header_token(app_name, 48),
+        std::process::id(),
+        header_token(ev.action(), 32),
+        msg
+    )
+}
+
+/// RFC 5425 / 6587 octet counting
+pub fn frame(msg: &str) -> Vec<u8> {
+    let mut out = format!("{} ", msg.len()).into_bytes();
+    out.extend_from_slice(msg.as_bytes());
+    out
+}
+
+enum Conn {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Tls(Box<native_tls::TlsStream<TcpStream>>),
+}
+
+impl Conn {
+    fn send(&mut self, msg: &str) -> io::Result<()> {
+        match self {
+            Conn::Udp(s) => s.send(msg.as_bytes()).map(|_| ()),
+            Conn::Tcp(s) => s.write_all(&frame(msg)),
+            Conn::Tls(s) => s.write_all(&frame(msg)),
+        }
+    }
+}
+
+struct Sender {
+    endpoint: Endpoint,
+    tls: Option<native_tls::TlsConnector>,
+    format: SiemFormat,
+    facility: u8,
+    hostname: String,
+    app_name: String,
+    connect_timeout: Duration,
+}
+
+impl Sender {
+    fn new(cfg: &SiemConfig) -> Result<Self, String> {
+        let endpoint = Endpoint::parse(&cfg.endpoint)?;
+        let tls = match endpoint.transport {
+            Transport::Tls => {
+                let mut b = native_tls::TlsConnector::builder();
+                if let Some(path) = &cfg.ca_path {
+                    let pem = std::fs::read(path).map_err(|e| format!("read ca_path {}: {}", path, e))?;
+                    b.add_root_certificate(native_tls::Certificate::from_pem(&pem).map_err(|e| format!("ca_path {}: {}", path, e))?);
+                }
+                if let (Some(cert), Some(key)) = (&cfg.client_cert_path, &cfg.client_key_path) {
+                    let read = |p: &String| std::fs::read(p).map_err(|e| format!("read {}: {}", p, e));
+                    b.identity(native_tls::Identity::from_pkcs8(&read(cert)?, &read(key)?).map_err(|e| format!("client certificate: {}", e))?);
+                }
+                Some(b.build().map_err(|e| format!("tls: {}", e))?)
+            }
+            Transport::Udp | Transport::Tcp => None,
+        };
+        let hostname = cfg.hostname.clone().or_else(|| std::env::var("HOSTNAME").ok()).unwrap_or_default();
+        Ok(Self {
+            endpoint,
+            tls,
+            format: cfg.format,
+            facility: cfg.facility,
+            hostname,
+            app_name: cfg.app_name.clone(),
+            connect_timeout: Duration::from_secs(cfg.connect_timeout_secs.max(1)),
+        })
+    }
+
+    fn render(&self, ev: &SiemEvent) -> String {
+        let msg = match self.format {
+            SiemFormat::Cef => format_cef(&self.app_name, ev),
+            SiemFormat::Syslog => format_json(ev),
+        };
+        format_syslog(self.facility, &self.hostname, &self.app_name, &msg, ev)
+    }
+
+    fn connect(&self) -> io::Result<Conn> {
+        let addr = (self.endpoint.host.as_str(), self.endpoint.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "endpoint did not resolve"))?;
+        match self.endpoint.transport {
+            Transport::Udp => {
+                let bind = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(bind)?;
+                socket.connect(addr)?;
+                Ok(Conn::Udp(socket))
+            }
+            Transport::Tcp => Ok(Conn::Tcp(TcpStream::connect_timeout(&addr, self.connect_timeout)?)),
+            Transport::Tls => {
+                let stream = TcpStream::connect_timeout(&addr, self.connect_timeout)?;
+                let connector = self.tls.as_ref().ok_or_else(|| io::Error::other("tls connector missing"))?;
+                let tls = connector.connect(&self.endpoint.host, stream).map_err(|e| io::Error::other(e.to_string()))?;
+                Ok(Conn::Tls(Box::new(tls)))
+            }
+        }
+    }
+
+    /// 队列关闭（进程退出）前持续发送；失败时断开重连，当前事件重发直到成功
+    fn run(self, rx: Receiver<SiemEvent>) {
+        let mut conn: Option<Conn> = None;
+        let mut backoff = Duration::from_millis(500);
+        while let Ok(ev) = rx.recv() {
+            let msg = self.render(&ev);
+            loop {
+                let result = match conn.take() {
+                    Some(mut c) => c.send(&msg).map(|_| c),
+                    None => self.connect().and_then(|mut c| c.send(&msg).map(|_| c)),
+                };
+                match result {
+                    Ok(c) => {
+                        conn = Some(c);
+                        events_total().with_label_values(&["sent"]).inc();
+                        backoff = Duration::from_millis(500);
+                        break;
+                    }
+                    Err(e) => {
+                        events_total().with_label_values(&["failed"]).inc();
+                        eprintln!("siem export to {}:{} failed: {}; retrying in {:?}", self.endpoint.host, self.endpoint.port, e, backoff);
+                        std::thread::sleep(backoff);
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(fields: &[(&str, &str)]) -> SiemEvent {
+        SiemEvent {
+            timestamp: DateTime::parse_from_rfc3339("2026-10-16T08:00:00.123Z").unwrap().with_timezone(&Utc),
+            level: Level::WARN,
+            target: "security".into(),
+            message: "Request rejected by WAF rule".into(),
+            fields: fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_endpoints_and_validates() {
+        assert_eq!(Endpoint::parse("tls://siem.example.com:6514").unwrap(), Endpoint { transport: Transport::Tls, host: "siem.example.com".into(), port: 6514 });
+        assert_eq!(Endpoint::parse("udp://[::1]:514").unwrap().host, "::1");
+        for bad in ["siem:514", "http://siem:514", "tcp://siem", "tcp://:514", "tcp://siem:0"] {
+            assert!(Endpoint::parse(bad).is_err(), "{}", bad);
+        }
+        let cfg = SiemConfig { enabled: true, endpoint: "tcp://siem:601".into(), ca_path: Some("ca.pem".into()), ..Default::default() };
+        assert!(cfg.validate().unwrap_err().contains("tls://"));
+        assert!(SiemConfig { enabled: true, endpoint: "udp://siem:514".into(), ..Default::default() }.validate().is_ok());
+        assert!(SiemConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn formats_cef_inside_syslog() {
+        let ev = event(&[("event", "waf_rejected"), ("rule", "sqli|union"), ("client", "203.0.113.9"), ("note", "a=b\nc")]);
+        let cef = format_cef("api-proxy", &ev);
+        assert_eq!(
+            cef,
+            format!(
+                "CEF:0|dexcodehub|api-proxy|{}|security:waf_rejected|Request rejected by WAF rule|6|rt=1792137600123 cat=security act=waf_rejected rule=sqli|union src=203.0.113.9 note=a\\=b\\nc",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        let line = format_syslog(13, "gw-1", "api-proxy", &cef, &ev);
+        // facility 13 * 8 + warning (4)
+        assert!(line.starts_with(&format!("<108>1 2026-10-16T08:00:00.123Z gw-1 api-proxy {} waf_rejected - CEF:0|", std::process::id())));
+        assert_eq!(frame("abc"), b"3 abc".to_vec());
+    }
+
+    #[test]
+    fn formats_json_and_header_fallbacks() {
+        let ev = SiemEvent { target: "audit".into(), level: Level::INFO, message: String::new(), ..event(&[("action", "route_cutover"), ("route_id", "r1")]) };
+        let v: serde_json::Value = serde_json::from_str(&format_json(&ev)).unwrap();
+        assert_eq!((v["target"].as_str(), v["action"].as_str(), v["route_id"].as_str()), (Some("audit"), Some("route_cutover"), Some("r1")));
+        assert!(format_cef("a|b", &ev).starts_with("CEF:0|dexcodehub|a\\|b|"));
+        assert!(format_syslog(13, "", "app", "{}", &ev).contains(" - app "));
+    }
+}
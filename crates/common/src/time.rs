@@ -0,0 +1,92 @@
+//! Time source abstraction
+//!
+//! All persisted timestamps are `timestamptz` in UTC. Code that decides something
+//! by comparing against "now" (expiry, TTLs, throttles, reporting windows, schedules)
+//! takes a `SharedClock` so it can be tested with `MockClock` instead of the wall clock.
+//! Plain record stamps (`created_at` / `updated_at`, log timestamps, last save times)
+//! written by CRUD helpers and repositories come from `Utc::now()`; nothing branches on them.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+
+/// Source of the current UTC time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current time as the `DateTimeWithTimeZone` stored by SeaORM entities (offset +00:00).
+    fn now_fixed(&self) -> DateTime<FixedOffset> {
+        self.now().fixed_offset()
+    }
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Wall clock used in production.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Default clock handle (system time).
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// Manually driven clock for tests.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: Arc::new(Mutex::new(start)) }
+    }
+
+    pub fn set(&self, t: DateTime<Utc>) {
+        *self.now.lock().expect("mock clock lock") = t;
+    }
+
+    pub fn advance(&self, d: Duration) {
+        let mut now = self.now.lock().expect("mock clock lock");
+        *now += d;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("mock clock lock")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn mock_clock_is_controllable() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::hours(12));
+        assert_eq!(clock.now(), start + Duration::hours(12));
+        assert_eq!(clock.now_fixed().offset().local_minus_utc(), 0);
+    }
+
+    #[test]
+    fn serializes_as_rfc3339_utc() {
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 3, 4, 5, 6, 7).unwrap());
+        let json = serde_json::to_string(&clock.now()).unwrap();
+        assert_eq!(json, "\"2024-03-04T05:06:07Z\"");
+        let json = serde_json::to_string(&clock.now_fixed()).unwrap();
+        assert_eq!(json, "\"2024-03-04T05:06:07Z\"");
+        let back: DateTime<FixedOffset> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, clock.now_fixed());
+    }
+}
@@ -1,19 +1,178 @@
-use std::io;
-use tracing_subscriber::{fmt, EnvFilter};
+//! 日志初始化：格式（json/pretty/compact）、输出（stdout/文件）、文件轮转（按天/按大小）与按模块的级别。
+//!
+//! 设置了 `RUST_LOG` 时以其为准，否则由 `level` + `modules` 组成过滤规则；运行中可通过
+//! [`set_filter`] 替换（管理接口 `PUT /admin/log-level`），无需重启。
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 每行一个 JSON 对象，便于 ELK/Datadog 采集
+    Json,
+    Pretty,
+    #[default]
+    Compact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogOutput {
+    #[default]
+    Stdout,
+    File,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    /// `{prefix}.YYYY-MM-DD.log`，UTC 日期切换时换文件
+    #[default]
+    Daily,
+    /// `{prefix}.log` 超过 `max_size_mb` 时依次改名为 `{prefix}.log.1`、`.2` ...
+    Size,
+    Never,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    pub output: LogOutput,
+    /// output = "file" 时的日志目录
+    pub dir: String,
+    pub file_prefix: String,
+    pub rotation: LogRotation,
+    pub max_size_mb: u64,
+    /// 保留的历史文件数
+    pub max_files: usize,
+    /// 默认级别
+    pub level: String,
+    /// 模块 -> 级别，如 `"gateway::proxy" = "debug"`
+    pub modules: BTreeMap<String, String>,
+    /// 审计与安全事件导出到 SIEM（syslog / CEF），不受 level 影响
+    pub siem: crate::siem::SiemConfig,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Compact,
+            output: LogOutput::Stdout,
+            dir: "logs".to_string(),
+            file_prefix: "app".to_string(),
+            rotation: LogRotation::Daily,
+            max_size_mb: 100,
+            max_files: 7,
+            level: "info".to_string(),
+            modules: BTreeMap::new(),
+            siem: crate::siem::SiemConfig::default(),
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// `level,module=level,...`
+    pub fn filter_directives(&self) -> String {
+        std::iter::once(self.level.clone())
+            .chain(self.modules.iter().map(|(m, l)| format!("{}={}", m, l)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// 按配置初始化全局 subscriber；重复调用无效。文件无法打开或过滤规则非法时退回 stdout / `info` 并在 stderr 提示。
+/// 过滤规则只作用于日志输出，SIEM 导出层按 `siem.targets` 单独过滤
+pub fn init_logging(cfg: &LoggingConfig) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::try_new(cfg.filter_directives()).unwrap_or_else(|e| {
+            eprintln!("invalid logging level/modules ({}): {}; using info", cfg.filter_directives(), e);
+            EnvFilter::new("info")
+        })
+    });
+    let (writer, ansi) = match cfg.output {
+        LogOutput::Stdout => (BoxMakeWriter::new(io::stdout), true),
+        LogOutput::File => match RollingFile::new(cfg) {
+            Ok(file) => (BoxMakeWriter::new(move || file.clone()), false),
+            Err(e) => {
+                eprintln!("open log file in {} failed: {}; logging to stdout", cfg.dir, e);
+                (BoxMakeWriter::new(io::stdout), true)
+            }
+        },
+    };
+    let initial = filter.to_string();
+    let (filter, handle) = reload::Layer::new(filter);
+    let layer = fmt::layer().with_target(false).with_ansi(ansi).with_writer(writer);
+    let layer = match cfg.format {
+        LogFormat::Json => layer.json().boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Compact => layer.compact().boxed(),
+    };
+    let siem = crate::siem::layer(&cfg.siem);
+    if tracing_subscriber::registry().with(layer.with_filter(filter)).with(siem).try_init().is_ok() {
+        let _ = FILTER.set(LogFilter { handle, initial });
+    }
+}
+
+struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// 启动时的过滤规则
+    initial: String,
+}
+
+static FILTER: OnceLock<LogFilter> = OnceLock::new();
+
+#[derive(Debug, Error)]
+pub enum LogFilterError {
+    #[error("logging was not initialized by init_logging")]
+    NotInstalled,
+    #[error("invalid filter directives: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogFilterState {
+    /// 当前生效的过滤规则
+    pub filter: String,
+    /// 启动时的过滤规则
+    pub initial: String,
+}
+
+/// 当前日志过滤规则
+pub fn current_filter() -> Result<LogFilterState, LogFilterError> {
+    let f = FILTER.get().ok_or(LogFilterError::NotInstalled)?;
+    let filter = f.handle.with_current(|c| c.to_string()).map_err(|_| LogFilterError::NotInstalled)?;
+    Ok(LogFilterState { filter, initial: f.initial.clone() })
+}
+
+/// 运行时替换过滤规则（EnvFilter 语法，如 `info,gateway::proxy=trace`）；`None` 恢复启动时的规则
+pub fn set_filter(directives: Option<&str>) -> Result<LogFilterState, LogFilterError> {
+    let f = FILTER.get().ok_or(LogFilterError::NotInstalled)?;
+    let directives = directives.unwrap_or(&f.initial);
+    let filter = EnvFilter::try_new(directives).map_err(|e| LogFilterError::Invalid(e.to_string()))?;
+    f.handle.reload(filter).map_err(|_| LogFilterError::NotInstalled)?;
+    current_filter()
+}
 
 /// Initialize tracing subscriber with sensible defaults and stdout writer.
 /// - Respects `RUST_LOG` if set
 /// - Falls back to `info,tower_http=info,axum=info`
 /// - Writes to stdout to improve visibility in environments that hide stderr
 pub fn init_logging_default() {
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,tower_http=info,axum=info"));
-    let _ = fmt()
-        .with_env_filter(env_filter)
-        .with_target(false)
-        .compact()
-        .with_writer(|| io::stdout())
-        .try_init();
+    let modules = [("tower_http", "info"), ("axum", "info")].map(|(m, l)| (m.to_string(), l.to_string())).into();
+    init_logging(&LoggingConfig { modules, ..Default::default() });
 }
 
 /// Initialize tracing subscriber with JSON structured output.
@@ -23,12 +182,218 @@ pub fn init_logging_default() {
 pub fn init_logging_json() {
     // 默认启用 info，并对 gateway::proxy 下的详细请求处理使用 debug 以便可见
     // 可通过 RUST_LOG 覆盖，例如 RUST_LOG=info,gateway::proxy=trace
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("info,gateway::proxy=debug"));
-    let _ = fmt()
-        .with_env_filter(env_filter)
-        .with_target(false)
-        .json()
-        .with_writer(|| io::stdout())
-        .try_init();
-}
\ No newline at end of file
+    let modules = [("gateway::proxy".to_string(), "debug".to_string())].into();
+    init_logging(&LoggingConfig { format: LogFormat::Json, modules, ..Default::default() });
+}
+
+struct RollingState {
+    dir: PathBuf,
+    prefix: String,
+    rotation: LogRotation,
+    max_bytes: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+    day: NaiveDate,
+}
+
+/// 轮转日志文件；clone 共享同一文件句柄，可直接作为 `MakeWriter`
+#[derive(Clone)]
+pub struct RollingFile {
+    state: Arc<Mutex<RollingState>>,
+}
+
+impl RollingFile {
+    pub fn new(cfg: &LoggingConfig) -> io::Result<Self> {
+        fs::create_dir_all(&cfg.dir)?;
+        let mut state = RollingState {
+            dir: PathBuf::from(&cfg.dir),
+            prefix: cfg.file_prefix.clone(),
+            rotation: cfg.rotation,
+            max_bytes: cfg.max_size_mb.max(1) * 1024 * 1024,
+            max_files: cfg.max_files,
+            file: None,
+            size: 0,
+            day: Utc::now().date_naive(),
+        };
+        state.open()?;
+        Ok(Self { state: Arc::new(Mutex::new(state)) })
+    }
+
+    fn write_at(&self, buf: &[u8], now: DateTime<Utc>) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.rotate_if_needed(now, buf.len() as u64)?;
+        let n = match state.file.as_mut() {
+            Some(f) => f.write(buf)?,
+            None => return Err(io::Error::other("log file is not open")),
+        };
+        state.size += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, Utc::now())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.file.as_mut().map_or(Ok(()), |f| f.flush())
+    }
+}
+
+impl RollingState {
+    fn current_path(&self) -> PathBuf {
+        match self.rotation {
+            LogRotation::Daily => self.dir.join(format!("{}.{}.log", self.prefix, self.day.format("%Y-%m-%d"))),
+            LogRotation::Size | LogRotation::Never => self.dir.join(format!("{}.log", self.prefix)),
+        }
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        let path = self.current_path();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate_if_needed(&mut self, now: DateTime<Utc>, incoming: u64) -> io::Result<()> {
+        match self.rotation {
+            LogRotation::Daily if now.date_naive() != self.day => {
+                self.day = now.date_naive();
+                self.open()?;
+                self.prune_daily();
+            }
+            LogRotation::Size if self.size > 0 && self.size + incoming > self.max_bytes => {
+                self.file = None;
+                self.shift_numbered()?;
+                self.open()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn numbered(&self, n: usize) -> PathBuf {
+        self.dir.join(format!("{}.log.{}", self.prefix, n))
+    }
+
+    /// `.log.{n-1}` -> `.log.{n}`，超出 max_files 的删除
+    fn shift_numbered(&self) -> io::Result<()> {
+        let current = self.current_path();
+        if self.max_files == 0 {
+            return fs::remove_file(current);
+        }
+        let _ = fs::remove_file(self.numbered(self.max_files));
+        for n in (1..self.max_files).rev() {
+            let from = self.numbered(n);
+            if from.exists() {
+                fs::rename(from, self.numbered(n + 1))?;
+            }
+        }
+        fs::rename(current, self.numbered(1))
+    }
+
+    /// 保留当前文件与最近 max_files 个按天的历史文件
+    fn prune_daily(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else { return };
+        let mut dated: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| is_dated_log(p, &self.prefix))
+            .collect();
+        dated.sort();
+        let keep = self.max_files + 1;
+        if dated.len() > keep {
+            for p in &dated[..dated.len() - keep] {
+                let _ = fs::remove_file(p);
+            }
+        }
+    }
+}
+
+fn is_dated_log(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .and_then(|n| n.strip_prefix(prefix)?.strip_prefix('.')?.strip_suffix(".log"))
+        .is_some_and(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("logging_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn filter_from_level_and_modules() {
+        let mut cfg = LoggingConfig { level: "warn".into(), ..Default::default() };
+        cfg.modules.insert("gateway::proxy".into(), "debug".into());
+        cfg.modules.insert("axum".into(), "info".into());
+        assert_eq!(cfg.filter_directives(), "warn,axum=info,gateway::proxy=debug");
+    }
+
+    #[test]
+    fn filter_can_be_replaced_and_restored() {
+        init_logging(&LoggingConfig::default());
+        let initial = current_filter().unwrap().initial;
+        let state = set_filter(Some("warn,gateway::proxy=trace")).unwrap();
+        assert!(state.filter.contains("gateway::proxy=trace"));
+        assert!(matches!(set_filter(Some("info,=[")), Err(LogFilterError::Invalid(_))));
+        assert_eq!(set_filter(None).unwrap().filter, initial);
+    }
+
+    #[test]
+    fn parses_config_section() {
+        let cfg: LoggingConfig = serde_json::from_str(r#"{"format":"json","output":"file","rotation":"size","max_size_mb":5}"#).unwrap();
+        assert_eq!((cfg.format, cfg.output, cfg.rotation, cfg.max_size_mb), (LogFormat::Json, LogOutput::File, LogRotation::Size, 5));
+        assert_eq!(cfg.level, "info");
+    }
+
+    #[test]
+    fn size_rotation_keeps_max_files() {
+        let dir = temp_dir("size");
+        let cfg = LoggingConfig { output: LogOutput::File, dir: dir.clone(), rotation: LogRotation::Size, max_files: 2, ..Default::default() };
+        let file = RollingFile::new(&cfg).unwrap();
+        file.state.lock().unwrap().max_bytes = 10;
+        let now = Utc::now();
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            file.write_at(line.as_bytes(), now).unwrap();
+        }
+        let read = |name: &str| fs::read_to_string(Path::new(&dir).join(name)).unwrap();
+        assert_eq!(read("app.log"), "dddddddd\n");
+        assert_eq!(read("app.log.1"), "cccccccc\n");
+        assert_eq!(read("app.log.2"), "bbbbbbbb\n");
+        assert!(!Path::new(&dir).join("app.log.3").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn daily_rotation_switches_file_and_prunes() {
+        let dir = temp_dir("daily");
+        let cfg = LoggingConfig { output: LogOutput::File, dir: dir.clone(), max_files: 1, ..Default::default() };
+        let file = RollingFile::new(&cfg).unwrap();
+        {
+            let mut state = file.state.lock().unwrap();
+            fs::remove_file(state.current_path()).unwrap();
+            state.day = at("2026-01-01T00:00:00Z").date_naive();
+            state.open().unwrap();
+        }
+        for day in ["2026-01-01", "2026-01-02", "2026-01-03"] {
+            file.write_at(b"x\n", at(&format!("{}T12:00:00Z", day))).unwrap();
+        }
+        let exists = |day: &str| Path::new(&dir).join(format!("app.{}.log", day)).exists();
+        assert!(exists("2026-01-03") && exists("2026-01-02"));
+        assert!(!exists("2026-01-01"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
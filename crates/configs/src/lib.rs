@@ -1,30 +1,988 @@
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use anyhow::anyhow;
 
-#[derive(Debug, Clone, Deserialize, Default)]
+pub mod resolver;
+
+use resolver::{Resolved, Resolver};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppConfig {
     #[serde(default)]
     pub server: ServerConfig,
     #[serde(default)]
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub pagination: PaginationConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub dev: DevConfig,
+    #[serde(default)]
+    pub slo: SloConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    /// 日志与落库前的敏感数据脱敏
+    #[serde(default)]
+    pub redaction: common::redaction::RedactionConfig,
+    #[serde(default)]
+    pub anomaly: AnomalyConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub expiry: ExpiryConfig,
+    #[serde(default)]
+    pub metrics_rollup: MetricsRollupConfig,
+    /// 按日/周生成租户流量报表并经 webhook 投递
+    #[serde(default)]
+    pub scheduled_reports: ScheduledReportsConfig,
+    /// 按路由抽样保存请求/响应体（供重放与排查）
+    #[serde(default)]
+    pub capture: CaptureConfig,
+    /// 请求日志存储后端（Postgres / ClickHouse）
+    #[serde(default)]
+    pub request_log: RequestLogConfig,
+    /// 请求日志导出到 Kafka / S3 / HTTP
+    #[serde(default)]
+    pub log_export: LogExportConfig,
+    /// 日志格式、输出与轮转
+    #[serde(default)]
+    pub logging: common::utils::logging::LoggingConfig,
+    /// /admin/debug/pprof/* 剖析端点
+    #[serde(default)]
+    pub profiling: common::profiling::ProfilingConfig,
+    /// 控制面访问上游的 HTTP 客户端：连接池、超时、出站代理与重试
+    #[serde(default)]
+    pub http_client: common::http_client::HttpClientConfig,
+    /// admin API Key 与 /admin/apis 的存储后端
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// 控制面热点实体读缓存的 TTL 与容量
+    #[serde(default)]
+    pub cache: CacheConfig,
+    /// 多副本下定时任务的分布式锁
+    #[serde(default)]
+    pub lock: LockConfig,
+    /// 启动编排：数据库重试、启动时迁移、缓存预热
+    #[serde(default)]
+    pub startup: StartupConfig,
+    /// /admin 与 /auth 的按调用方限流
+    #[serde(default)]
+    pub admin_rate_limit: AdminRateLimitConfig,
+    /// 浏览器跨域访问控制面
+    #[serde(default)]
+    pub cors: common::cors::CorsConfig,
+    /// 登录令牌与认证 Cookie
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// 自助开通租户（POST /signup）
+    #[serde(default)]
+    pub signup: SignupConfig,
+    /// 敏感字段加密的主密钥
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// 上游目标 allowlist（forward_target 校验与转发时检查）
+    #[serde(default)]
+    pub egress: common::egress::EgressConfig,
+}
+
+/// 管理接口限流：按 JWT subject / API Key / 客户端 IP 各自一个令牌桶；登录与注册单独使用更严格的桶
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminRateLimitConfig {
+    pub enabled: bool,
+    /// /admin 与 /auth 每个调用方每秒请求数
+    pub requests_per_second: u64,
+    pub burst: u64,
+    /// /auth/login、/auth/register 等公开入口（含 /signup）每个客户端 IP 每分钟次数
+    pub login_per_minute: u64,
+    pub login_burst: u64,
+}
+
+impl Default for AdminRateLimitConfig {
+    fn default() -> Self {
+        Self { enabled: true, requests_per_second: 20, burst: 40, login_per_minute: 10, login_burst: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSitePolicy {
+    Strict,
+    #[default]
+    Lax,
+    /// 跨站也携带 Cookie，要求 Secure
+    None,
+}
+
+/// 用户活跃会话达到上限后再次登录的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionLimitPolicy {
+    /// 拒绝新登录（409）
+    Reject,
+    /// 撤销最早的会话
+    #[default]
+    RevokeOldest,
+}
+
+/// 登录令牌（HS256 JWT）与 auth_token Cookie
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// 签名密钥；敏感信息，建议通过环境变量 JWT_SECRET 提供。非开发模式下必填
+    pub jwt_secret: String,
+    /// 令牌有效期，同时作为 Cookie 的 Max-Age
+    pub access_token_ttl_secs: u64,
+    /// 设置后签发 `iss` / `aud`，中间件校验时要求一致
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    /// Cookie 的 Domain；不设置时仅当前主机
+    pub cookie_domain: Option<String>,
+    /// Cookie 的 Secure 属性；不设置时生产环境开启，开发模式（`[dev] enabled`）关闭以便 http://localhost 调试
+    pub cookie_secure: Option<bool>,
+    pub cookie_same_site: SameSitePolicy,
+    /// 轮换后旧密钥继续用于校验的时长；不设置时等于 access_token_ttl_secs
+    pub key_rotation_grace_secs: Option<u64>,
+    /// 每个用户同时有效的会话（登录令牌）数上限；0 表示不限制
+    pub max_sessions_per_user: u32,
+    pub session_limit_policy: SessionLimitPolicy,
+    /// 邮箱验证令牌有效期（租户开启 email_verification 时注册发送）
+    pub email_verification_ttl_secs: u64,
+    /// 验证链接前缀，如 `https://portal.example.com/verify`；设置后 webhook 中附带 `verify_url`（前缀 + `?token=...`）
+    pub email_verification_url: Option<String>,
+    /// 邀请令牌有效期（POST /admin/invitations）
+    pub invitation_ttl_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: String::new(),
+            access_token_ttl_secs: 12 * 3600,
+            issuer: None,
+            audience: None,
+            cookie_domain: None,
+            cookie_secure: None,
+            cookie_same_site: SameSitePolicy::Lax,
+            key_rotation_grace_secs: None,
+            max_sessions_per_user: 0,
+            session_limit_policy: SessionLimitPolicy::RevokeOldest,
+            email_verification_ttl_secs: 24 * 3600,
+            email_verification_url: None,
+            invitation_ttl_secs: 7 * 24 * 3600,
+        }
+    }
+}
+
+/// 开发模式未配置密钥时使用，仅限本地调试
+pub const DEV_JWT_SECRET: &str = "dev-secret-change-me";
+
+impl AuthConfig {
+    /// 按环境补齐未设置的项：开发模式使用固定密钥并关闭 Secure
+    pub fn resolve_for(mut self, dev: bool) -> Self {
+        if self.jwt_secret.is_empty() && dev {
+            self.jwt_secret = DEV_JWT_SECRET.to_string();
+        }
+        self.cookie_secure = Some(self.cookie_secure.unwrap_or(!dev));
+        self
+    }
+
+    pub fn validate(&self, dev: bool) -> Result<()> {
+        if self.jwt_secret.is_empty() && !dev {
+            return Err(anyhow!("auth.jwt_secret 为空；请通过环境变量 JWT_SECRET 提供（仅 [dev] enabled 时可省略）"));
+        }
+        if !self.jwt_secret.is_empty() && self.jwt_secret.len() < 16 {
+            return Err(anyhow!("auth.jwt_secret 至少 16 个字符"));
+        }
+        if self.access_token_ttl_secs < 60 {
+            return Err(anyhow!("auth.access_token_ttl_secs 必须 >= 60"));
+        }
+        if self.cookie_same_site == SameSitePolicy::None && self.cookie_secure == Some(false) {
+            return Err(anyhow!("auth.cookie_same_site = \"none\" 要求 cookie_secure = true"));
+        }
+        if [&self.issuer, &self.audience, &self.cookie_domain].into_iter().any(|v| v.as_deref().is_some_and(|v| v.trim().is_empty())) {
+            return Err(anyhow!("auth.issuer / audience / cookie_domain 设置时不能为空字符串"));
+        }
+        Ok(())
+    }
+}
+
+/// 自助开通：一次事务内创建租户、首个管理员、默认限流与初始 API key。
+/// 默认关闭（不注册路由）。开启时必须设置 `token`，请求须携带相同的 `X-Signup-Token`：
+/// /admin/* 尚未按租户隔离，开通的管理员能访问全部租户的配置，因此只向受信任的运营方开放
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SignupConfig {
+    pub enabled: bool,
+    pub token: Option<String>,
+    /// 新租户默认限流
+    pub requests_per_minute: i32,
+    pub burst: i32,
+}
+
+impl Default for SignupConfig {
+    fn default() -> Self {
+        Self { enabled: false, token: None, requests_per_minute: 600, burst: 50 }
+    }
+}
+
+impl SignupConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err(anyhow!("signup.token 设置时不能为空字符串"));
+        }
+        if self.enabled && self.token.is_none() {
+            return Err(anyhow!("signup.enabled = true 时必须设置 signup.token（/admin/* 尚未按租户隔离，不能开放匿名开通）"));
+        }
+        if self.requests_per_minute <= 0 || self.burst <= 0 {
+            return Err(anyhow!("signup.requests_per_minute 与 burst 必须 > 0"));
+        }
+        Ok(())
+    }
+}
+
+/// 敏感字段（webhook 密钥、签名密钥等）的加密主密钥。
+/// 主密钥为 32 字节 base64，取自环境变量 ENCRYPTION_MASTER_KEY 或挂载的密钥文件（如 Kubernetes Secret）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// 当前主密钥的 ID，写入每个密文，轮换时换一个新 ID
+    pub key_id: String,
+    pub master_key: String,
+    /// 优先于 master_key
+    pub master_key_file: Option<String>,
+    /// 轮换前的主密钥，只用于解密；重加密任务完成后可移除
+    pub previous_keys: Vec<PreviousMasterKey>,
+    /// 升级前 `auth.key_encryption_key`（JWT_KEY_ENCRYPTION_KEY）的值，用于读取旧格式 `v1:{base64}` 的签名密钥；
+    /// 为空时与旧版一致由 jwt_secret 派生。重加密任务完成后不再需要
+    pub legacy_key: String,
+    /// 从未配置主密钥、由 jwt_secret 派生列加密密钥的旧版本升级时，填旧版的 key_id 以继续解密，
+    /// 重加密任务完成后移除（之后更换 JWT_SECRET 不再影响已加密字段）
+    pub jwt_derived_key_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviousMasterKey {
+    pub key_id: String,
+    pub key: String,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            key_id: "k1".into(),
+            master_key: String::new(),
+            master_key_file: None,
+            previous_keys: Vec::new(),
+            legacy_key: String::new(),
+            jwt_derived_key_id: None,
+        }
+    }
+}
+
+/// 开发模式未配置主密钥时使用，仅限本地调试；网关需以 GATEWAY__ENCRYPTION__MASTER_KEY 设置相同的值
+pub const DEV_MASTER_KEY: &str = "ZGV2LW1hc3Rlci1rZXktY2hhbmdlLW1lLTMyYnl0ZXM=";
+
+impl EncryptionConfig {
+    /// 按环境补齐未设置的项：开发模式使用固定主密钥
+    pub fn resolve_for(mut self, dev: bool) -> Self {
+        if dev && self.master_key_file.is_none() && self.master_key.trim().is_empty() {
+            self.master_key = DEV_MASTER_KEY.to_string();
+        }
+        self
+    }
+
+    /// 当前主密钥（base64）；文件优先，两者都没有时为 None
+    pub fn resolve_master_key(&self) -> Result<Option<String>> {
+        if let Some(path) = &self.master_key_file {
+            let key = std::fs::read_to_string(path).map_err(|e| anyhow!("读取 encryption.master_key_file {} 失败：{}", path, e))?;
+            return Ok(Some(key.trim().to_string()));
+        }
+        Ok((!self.master_key.trim().is_empty()).then(|| self.master_key.trim().to_string()))
+    }
+
+    /// 当前主密钥与 previous_keys；未配置主密钥时为 None（网关只在配置中出现密文时才需要）
+    pub fn master_keys(&self) -> Result<Option<common::crypto::MasterKeys>> {
+        use common::crypto::{MasterKeys, SecretBox};
+        let Some(primary) = self.resolve_master_key()? else {
+            return Ok(None);
+        };
+        let primary = SecretBox::from_base64(&primary).map_err(|e| anyhow!("encryption.master_key：{}", e))?;
+        let mut keys = MasterKeys::new(&self.key_id, primary);
+        for prev in &self.previous_keys {
+            let key = SecretBox::from_base64(&prev.key).map_err(|e| anyhow!("encryption.previous_keys {}：{}", prev.key_id, e))?;
+            keys = keys.with_previous(&prev.key_id, key);
+        }
+        Ok(Some(keys))
+    }
+
+    /// 控制面安装的主密钥：未配置主密钥时报错（不从 jwt_secret 派生，否则网关无法解密、更换 JWT_SECRET 后密文无法读取）。
+    /// jwt_secret 只用于读取旧格式签名密钥与 `jwt_derived_key_id` 下升级前的密文
+    pub fn server_keys(&self, jwt_secret: &str) -> Result<common::crypto::MasterKeys> {
+        use common::crypto::SecretBox;
+        let mut keys = self
+            .master_keys()?
+            .ok_or_else(|| anyhow!("encryption.master_key 未配置；请通过环境变量 ENCRYPTION_MASTER_KEY 或 encryption.master_key_file 提供（仅 [dev] enabled 时可省略）"))?;
+        if let Some(id) = &self.jwt_derived_key_id {
+            keys = keys.with_previous(id, SecretBox::derive_from(jwt_secret));
+        }
+        let legacy = match self.legacy_key.trim() {
+            "" => SecretBox::derive_from(jwt_secret),
+            key => SecretBox::from_base64(key).map_err(|e| anyhow!("encryption.legacy_key：{}", e))?,
+        };
+        Ok(keys.with_legacy(legacy))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let ids = std::iter::once(&self.key_id).chain(self.previous_keys.iter().map(|k| &k.key_id)).chain(self.jwt_derived_key_id.iter());
+        for id in ids {
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                return Err(anyhow!("encryption 的 key_id {:?} 只能包含字母、数字、- 与 _", id));
+            }
+        }
+        if self.previous_keys.iter().any(|k| k.key_id == self.key_id) || self.jwt_derived_key_id.as_ref() == Some(&self.key_id) {
+            return Err(anyhow!("encryption.previous_keys / jwt_derived_key_id 不能包含当前 key_id {}", self.key_id));
+        }
+        Ok(())
+    }
+}
+
+/// server 启动顺序：校验配置 → 注册指标 → 连接数据库（退避重试）→ 迁移（可选）→ 预热缓存 → 监听端口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    /// 连接数据库的最大尝试次数
+    pub db_connect_attempts: u32,
+    /// 首次重试等待，之后逐次翻倍，不超过 db_backoff_max_ms
+    pub db_backoff_initial_ms: u64,
+    pub db_backoff_max_ms: u64,
+    /// 启动时执行待处理的迁移（多副本通过 [lock] 串行）
+    pub run_migrations: bool,
+    /// 监听端口前预加载 proxy API 路由表与上游列表
+    pub warm_caches: bool,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self { db_connect_attempts: 10, db_backoff_initial_ms: 500, db_backoff_max_ms: 10_000, run_migrations: false, warm_caches: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockBackend {
+    /// 设置了 redis_url / REDIS_URL 时用 redis，否则 postgres
+    #[default]
+    Auto,
+    /// 不加锁，每个副本都执行（单副本部署）
+    None,
+    Postgres,
+    Redis,
+}
+
+/// 定时任务（快照、指标汇总、到期扫描、异常检测）每轮只在一个副本上执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockConfig {
+    pub backend: LockBackend,
+    /// 未设置时读取 REDIS_URL
+    pub redis_url: Option<String>,
+    /// Redis 键前缀
+    pub key_prefix: String,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        Self { backend: LockBackend::Auto, redis_url: None, key_prefix: "api_proxy:lock:".into() }
+    }
+}
+
+/// 读缓存：变更事件触发失效，TTL 兜底其他副本或直接改库造成的不一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// 0 表示不按时间过期，仅靠事件失效
+    pub ttl_secs: u64,
+    /// 每类缓存的最大条目数，0 表示不限
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 300, max_entries: 10_000 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// 配置了数据库时使用 database，否则 file
+    #[default]
+    Auto,
+    /// 本地 JSON 文件，仅适用于单副本
+    File,
+    /// admin_api_key / managed_api 表，多副本共享
+    Database,
+}
+
+impl StorageBackend {
+    /// 解析 `auto`；结果只会是 File 或 Database
+    pub fn resolve(self, database_configured: bool) -> Self {
+        match self {
+            StorageBackend::Auto if database_configured => StorageBackend::Database,
+            StorageBackend::Auto => StorageBackend::File,
+            other => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// 文件后端的路径；数据库后端首次启动时从这里导入
+    pub api_keys_path: String,
+    pub apis_path: String,
+    /// 数据库后端且表为空时，导入上述 JSON 文件中的已有数据
+    pub import_from_files: bool,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::Auto,
+            api_keys_path: "data/api_keys.json".into(),
+            apis_path: "data/apis.json".into(),
+            import_from_files: true,
+        }
+    }
+}
+
+/// 指标汇总：后台按分钟把 request_log 汇总进 metrics_rollup，供 /admin/metrics/query 画图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsRollupConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// 每次重新汇总最近多少分钟（覆盖迟到写入的日志）
+    pub lookback_minutes: u64,
+    /// 汇总数据保留天数
+    pub retention_days: u64,
+}
+
+impl Default for MetricsRollupConfig {
+    fn default() -> Self {
+        Self { enabled: true, interval_secs: 60, lookback_minutes: 5, retention_days: 30 }
+    }
+}
+
+/// 定期报表：按日/周汇总各租户流量（基于 metrics_rollup），以 `report.generated` 事件投递给订阅的租户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduledReportsConfig {
+    pub enabled: bool,
+    /// 检查是否有到期报表的间隔
+    pub interval_secs: u64,
+    pub daily: bool,
+    /// 周报统计上周一至本周一（UTC）
+    pub weekly: bool,
+    /// 周期结束后在该 UTC 小时（0-23）之后发送，留出汇总迟到数据的时间
+    pub send_hour_utc: u32,
+    /// 报表中请求最多的路由数量
+    pub top_endpoints: u32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Default for ScheduledReportsConfig {
+    fn default() -> Self {
+        Self { enabled: false, interval_secs: 300, daily: true, weekly: true, send_hour_utc: 1, top_endpoints: 10 }
+    }
+}
+
+impl ScheduledReportsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.send_hour_utc > 23 {
+            return Err(anyhow!("scheduled_reports.send_hour_utc 必须在 0..=23 范围内"));
+        }
+        if self.top_endpoints == 0 || self.top_endpoints > 100 {
+            return Err(anyhow!("scheduled_reports.top_endpoints 必须在 1..=100 范围内"));
+        }
+        if self.enabled && !self.daily && !self.weekly {
+            return Err(anyhow!("scheduled_reports.enabled 为 true 时 daily 与 weekly 至少开启一个"));
+        }
+        Ok(())
+    }
+}
+
+/// 请求体捕获总开关；开启后各路由按 `route_capture` 中的抽样比例与大小上限保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub enabled: bool,
+    /// 路由可设置的 max_bytes 上限
+    pub max_bytes_limit: u32,
+    /// 路由未指定 max_bytes 时的默认值
+    pub default_max_bytes: u32,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self { enabled: false, max_bytes_limit: 1024 * 1024, default_max_bytes: 16 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestLogBackend {
+    /// request_log 表
+    #[default]
+    Postgres,
+    /// ClickHouse MergeTree 表（异步写入，按 TTL 过期），适合高流量部署
+    Clickhouse,
+}
+
+/// 请求日志存储：写入与 /admin/request-logs、/admin/api-keys/{id}/usage 等查询按 backend 自动切换
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestLogConfig {
+    pub backend: RequestLogBackend,
+    pub clickhouse: ClickHouseConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickHouseConfig {
+    /// HTTP 接口地址
+    pub url: String,
+    pub database: String,
+    /// 表名，启动时不存在则创建
+    pub table: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// 日志保留天数（表 TTL）
+    pub ttl_days: u32,
+    /// 服务端合并小批量写入
+    pub async_insert: bool,
+    /// 等待异步写入落盘后再返回，写入失败时调用方能感知
+    pub wait_for_async_insert: bool,
+    pub timeout_secs: u64,
+}
+
+impl Default for ClickHouseConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8123".into(),
+            database: "default".into(),
+            table: "request_log".into(),
+            user: None,
+            password: None,
+            ttl_days: 30,
+            async_insert: true,
+            wait_for_async_insert: true,
+            timeout_secs: 10,
+        }
+    }
+}
+
+impl RequestLogConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.backend != RequestLogBackend::Clickhouse {
+            return Ok(());
+        }
+        let ch = &self.clickhouse;
+        if !(ch.url.starts_with("http://") || ch.url.starts_with("https://")) {
+            return Err(anyhow!("request_log.clickhouse.url 必须以 http:// 或 https:// 开头"));
+        }
+        let ident = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !ident(&ch.table) || !ident(&ch.database) {
+            return Err(anyhow!("request_log.clickhouse.database 与 table 只能包含字母、数字与下划线"));
+        }
+        if ch.ttl_days == 0 {
+            return Err(anyhow!("request_log.clickhouse.ttl_days 必须 >= 1"));
+        }
+        Ok(())
+    }
+}
+
+/// 请求日志导出：实时请求日志按批写入外部 sink，分析侧不必查询 Postgres
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogExportConfig {
+    pub enabled: bool,
+    /// 每批最多条数；未满一批时每 `flush_interval_secs` 秒发送一次
+    pub batch_size: usize,
+    pub flush_interval_secs: u64,
+    /// 实时日志通道容量，导出跟不上时超出部分丢弃
+    pub buffer: usize,
+    /// 每个 sink 排队等待发送的批次上限，超出后丢弃新批次
+    pub max_pending_batches: usize,
+    /// 单批失败后的重试次数（退避从 1 秒起翻倍）
+    pub max_retries: u32,
+    pub timeout_secs: u64,
+    pub sinks: Vec<LogSinkConfig>,
+}
+
+impl Default for LogExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            batch_size: 500,
+            flush_interval_secs: 5,
+            buffer: 8192,
+            max_pending_batches: 16,
+            max_retries: 3,
+            timeout_secs: 10,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl LogExportConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.sinks.is_empty() {
+            return Err(anyhow!("log_export.enabled 为 true 时至少需要配置一个 [[log_export.sinks]]"));
+        }
+        if self.batch_size == 0 || self.flush_interval_secs == 0 {
+            return Err(anyhow!("log_export.batch_size 与 flush_interval_secs 必须 >= 1"));
+        }
+        for sink in &self.sinks {
+            let url = match sink {
+                LogSinkConfig::Http { url, .. } => url,
+                LogSinkConfig::Kafka { rest_url, .. } => rest_url,
+                LogSinkConfig::S3 { endpoint, .. } => endpoint,
+            };
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(anyhow!("log_export sink {} 的地址必须以 http:// 或 https:// 开头", sink.name()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 导出目标，`type` 区分；`name` 用作指标标签，缺省为类型名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogSinkConfig {
+    /// 通用 HTTP 批量接口：每批一次 POST
+    Http {
+        name: Option<String>,
+        url: String,
+        /// ndjson | json（整批一个 JSON 数组）
+        #[serde(default = "default_log_sink_format")]
+        format: String,
+        /// 附加请求头，如 Authorization
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
+    },
+    /// 经 Kafka REST Proxy 写入 topic，以租户 ID 为 key
+    Kafka {
+        name: Option<String>,
+        rest_url: String,
+        topic: String,
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
+    },
+    /// S3 / MinIO：每批一个 JSON Lines 对象，按 `dt=/hour=` 分区
+    S3 {
+        name: Option<String>,
+        endpoint: String,
+        bucket: String,
+        #[serde(default)]
+        prefix: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        /// 为空时读取 AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY
+        #[serde(default)]
+        access_key_id: String,
+        #[serde(default)]
+        secret_access_key: String,
+        /// MinIO 等需要 `{endpoint}/{bucket}/{key}` 路径风格
+        #[serde(default = "default_true")]
+        path_style: bool,
+    },
+}
+
+impl LogSinkConfig {
+    pub fn name(&self) -> String {
+        let (name, kind) = match self {
+            LogSinkConfig::Http { name, .. } => (name, "http"),
+            LogSinkConfig::Kafka { name, .. } => (name, "kafka"),
+            LogSinkConfig::S3 { name, .. } => (name, "s3"),
+        };
+        name.clone().unwrap_or_else(|| kind.to_string())
+    }
+}
+
+/// 到期提醒：定期扫描 API Key、JWT 签名密钥与 TLS 证书，临近到期时经 webhook 通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpiryConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// 默认提前多少天提醒
+    pub lead_days: u32,
+    /// 租户 ID -> 提前天数
+    pub tenant_lead_days: std::collections::HashMap<String, u32>,
+    /// 需要监控的 PEM 证书文件（如网关监听器的 cert_path）
+    pub certificates: Vec<String>,
+    /// JWT 签名密钥的到期时间
+    pub jwt_signing_keys: Vec<JwtKeyExpiry>,
+    /// 证书与签名密钥的提醒投递给该租户的 webhook；未配置时只写日志
+    pub operator_tenant_id: Option<String>,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 3600,
+            lead_days: 14,
+            tenant_lead_days: Default::default(),
+            certificates: Vec::new(),
+            jwt_signing_keys: Vec::new(),
+            operator_tenant_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKeyExpiry {
+    pub kid: String,
+    /// RFC 3339，如 2027-01-01T00:00:00Z
+    pub expires_at: String,
+}
+
+/// 租户 webhook 投递：后台 worker 轮询待投递记录，失败后指数退避重试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    /// 单次轮询最多投递的记录数
+    pub batch_size: u64,
+    /// 超过该次数仍失败则标记为 failed
+    pub max_attempts: u32,
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 5,
+            batch_size: 100,
+            max_attempts: 8,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 3600,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// 流量异常检测：定期分析最近的 request_log，与之前若干个窗口的基线比较后发送告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// 当前窗口长度
+    pub window_secs: u64,
+    /// 基线取之前多少个窗口
+    pub baseline_windows: u32,
+    /// 同一异常（类型 + 租户 + 路由）在该时间内只告警一次
+    pub cooldown_secs: u64,
+    /// 告警 webhook；未配置时只写日志
+    pub webhook_url: Option<String>,
+    /// json（通用结构）或 slack（`{"text": ...}`，兼容 Slack/飞书等 incoming webhook）
+    pub webhook_format: String,
+    pub thresholds: AnomalyThresholdsConfig,
+    /// 租户 ID -> 覆盖的阈值（未设置的字段沿用 thresholds）
+    pub tenants: std::collections::HashMap<String, AnomalyThresholdsConfig>,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 60,
+            window_secs: 300,
+            baseline_windows: 12,
+            cooldown_secs: 1800,
+            webhook_url: None,
+            webhook_format: "json".into(),
+            thresholds: AnomalyThresholdsConfig::default(),
+            tenants: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AnomalyThresholdsConfig {
+    /// 窗口内请求数低于该值时不判断
+    #[serde(default)]
+    pub min_requests: Option<u64>,
+    /// 错误率较基线上升的绝对值，如 0.1 表示上升 10 个百分点
+    #[serde(default)]
+    pub error_rate_increase: Option<f64>,
+    /// p95 延迟为基线的倍数
+    #[serde(default)]
+    pub latency_ratio: Option<f64>,
+    /// 租户请求量为基线窗口均值的倍数
+    #[serde(default)]
+    pub traffic_ratio: Option<f64>,
+}
+
+/// gRPC-JSON 转码：`protoc --include_imports --descriptor_set_out=...` 生成的描述符文件
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GrpcConfig {
+    #[serde(default)]
+    pub descriptor_sets: Vec<String>,
+}
+
+/// 后台任务（批量导入、租户导出、批量吊销）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobsConfig {
+    #[serde(default = "default_jobs_max_concurrent")]
+    pub max_concurrent: usize,
+    /// 未完成任务上限，超过后拒绝新任务
+    #[serde(default = "default_jobs_max_pending")]
+    pub max_pending: usize,
+    /// 保留最近 N 个已完成任务
+    #[serde(default = "default_jobs_retention")]
+    pub retention: usize,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: default_jobs_max_concurrent(),
+            max_pending: default_jobs_max_pending(),
+            retention: default_jobs_retention(),
+        }
+    }
+}
+
+/// SLO 目标：用于生成告警规则与 Grafana 面板（GET /admin/observability/bundle）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloConfig {
+    #[serde(default = "default_slo_availability")]
+    pub availability: f64,
+    #[serde(default = "default_slo_latency_p99_ms")]
+    pub latency_p99_ms: u64,
+    /// 告警规则的 `for` 持续时间
+    #[serde(default = "default_slo_alert_for")]
+    pub alert_for: String,
+    /// 被限流请求占比告警阈值
+    #[serde(default = "default_slo_max_rate_limited_ratio")]
+    pub max_rate_limited_ratio: f64,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            availability: default_slo_availability(),
+            latency_p99_ms: default_slo_latency_p99_ms(),
+            alert_for: default_slo_alert_for(),
+            max_rate_limited_ratio: default_slo_max_rate_limited_ratio(),
+        }
+    }
+}
+
+/// 开发模式：开启后才允许写入演示数据（POST /admin/dev/seed、`seed` 命令）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 演示上游指向的 echo 服务地址
+    #[serde(default = "default_echo_base_url")]
+    pub echo_base_url: String,
+    /// 开发模式下随 server 启动内置 echo 上游（监听 echo_base_url 的 host:port）
+    #[serde(default = "default_true")]
+    pub embedded_echo: bool,
+}
+
+impl DevConfig {
+    /// echo_base_url 中的 host:port
+    pub fn echo_listen_addr(&self) -> Option<String> {
+        let rest = self.echo_base_url.split_once("://").map(|(_, r)| r)?;
+        let authority = rest.split('/').next().filter(|a| !a.is_empty())?;
+        Some(if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) })
+    }
+}
+
+impl Default for DevConfig {
+    fn default() -> Self {
+        Self { enabled: false, echo_base_url: default_echo_base_url(), embedded_echo: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 定时快照间隔
+    #[serde(default = "default_backup_interval")]
+    pub interval_secs: u64,
+    /// 每种来源（scheduled / manual / pre_apply）各保留最近 N 份快照
+    #[serde(default = "default_backup_retention")]
+    pub retention: usize,
+    /// 同一对象变更前快照的最小间隔，避免连续修改时频繁快照
+    #[serde(default = "default_pre_apply_min_interval")]
+    pub pre_apply_min_interval_secs: u64,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: default_backup_interval(),
+            retention: default_backup_retention(),
+            pre_apply_min_interval_secs: default_pre_apply_min_interval(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationConfig {
+    /// 所有列表接口允许的最大 per_page
+    #[serde(default = "default_max_per_page")]
+    pub max_per_page: u32,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self { max_per_page: default_max_per_page() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     #[serde(default)]
     pub worker_threads: Option<usize>,
+    /// 优雅停机时等待进行中请求完成的上限，超时后强制关闭剩余连接
+    #[serde(default = "default_drain_timeout")]
+    pub drain_timeout_secs: u64,
+    /// Prometheus `/metrics` 的内部监听地址（另含 `/livez`），与公开端口分开；为空字符串时不提供指标
+    #[serde(default = "default_metrics_addr")]
+    pub metrics_addr: String,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
-        Self { host: "127.0.0.1".into(), port: 8080, worker_threads: Some(4) }
+        Self { host: "127.0.0.1".into(), port: 8080, worker_threads: Some(4), drain_timeout_secs: default_drain_timeout(), metrics_addr: default_metrics_addr() }
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
     #[serde(default = "default_max_connections")] 
@@ -43,22 +1001,87 @@ pub struct DatabaseConfig {
     pub sqlx_logging: bool,
 }
 
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: default_max_connections(),
+            min_connections: default_min_connections(),
+            connect_timeout_secs: default_connect_timeout(),
+            idle_timeout_secs: default_idle_timeout(),
+            max_lifetime_secs: default_max_lifetime(),
+            acquire_timeout_secs: default_acquire_timeout(),
+            sqlx_logging: false,
+        }
+    }
+}
+
 fn default_max_connections() -> u32 { 10 }
 fn default_min_connections() -> u32 { 2 }
 fn default_connect_timeout() -> u64 { 30 }
 fn default_idle_timeout() -> u64 { 600 }
 fn default_max_lifetime() -> u64 { 3600 }
 fn default_acquire_timeout() -> u64 { 30 }
+fn default_max_per_page() -> u32 { 100 }
+fn default_drain_timeout() -> u64 { 30 }
+fn default_metrics_addr() -> String { "127.0.0.1:9189".into() }
+fn default_true() -> bool { true }
+fn default_backup_interval() -> u64 { 3600 }
+fn default_backup_retention() -> usize { 48 }
+fn default_pre_apply_min_interval() -> u64 { 60 }
+fn default_echo_base_url() -> String { "http://127.0.0.1:9099".into() }
+fn default_slo_availability() -> f64 { 0.999 }
+fn default_slo_latency_p99_ms() -> u64 { 500 }
+fn default_slo_alert_for() -> String { "5m".into() }
+fn default_slo_max_rate_limited_ratio() -> f64 { 0.05 }
+fn default_jobs_max_concurrent() -> usize { 2 }
+fn default_jobs_max_pending() -> usize { 16 }
+fn default_jobs_retention() -> usize { 100 }
+fn default_log_sink_format() -> String { "ndjson".into() }
+fn default_s3_region() -> String { "us-east-1".into() }
+
+/// 环境变量覆盖的前缀：`API_PROXY__DATABASE__URL`
+pub const ENV_PREFIX: &str = "API_PROXY";
+
+/// 历史环境变量名（前缀变量优先）
+pub const ENV_ALIASES: &[(&str, &[&str])] = &[
+    ("server.host", &["SERVER_HOST"]),
+    ("server.port", &["SERVER_PORT"]),
+    ("server.worker_threads", &["TOKIO_WORKER_THREADS"]),
+    ("database.url", &["DATABASE_URL"]),
+    ("auth.jwt_secret", &["JWT_SECRET"]),
+    ("encryption.master_key", &["ENCRYPTION_MASTER_KEY"]),
+    ("encryption.legacy_key", &["JWT_KEY_ENCRYPTION_KEY"]),
+    ("database.max_connections", &["DATABASE_MAX_CONNECTIONS", "DB_MAX_CONNECTIONS"]),
+    ("database.min_connections", &["DATABASE_MIN_CONNECTIONS", "DB_MIN_CONNECTIONS"]),
+    ("database.connect_timeout_secs", &["DATABASE_CONNECT_TIMEOUT", "DB_CONNECT_TIMEOUT_SECS"]),
+    ("database.acquire_timeout_secs", &["DATABASE_ACQUIRE_TIMEOUT", "DB_ACQUIRE_TIMEOUT_SECS"]),
+    ("database.idle_timeout_secs", &["DATABASE_IDLE_TIMEOUT"]),
+    ("database.sqlx_logging", &["DB_SQLX_LOGGING", "SQLX_LOGGING"]),
+];
 
 pub fn load_default() -> Result<AppConfig> {
-    let path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
-    load_from_file(&path)
+    resolve_default().map(|r| r.config)
+}
+
+/// 默认来源解析并保留 provenance：CONFIG_PATH（缺省 config.toml，不存在时跳过）+ .env + 环境变量
+pub fn resolve_default() -> Result<Resolved<AppConfig>> {
+    let explicit = std::env::var("CONFIG_PATH").ok();
+    let path = explicit.clone().unwrap_or_else(|| "config.toml".to_string());
+    let file = Resolver::read_file(&path)?;
+    if file.is_none() && explicit.is_some() {
+        return Err(anyhow!("CONFIG_PATH={} 不存在", path));
+    }
+    resolve_from(file.map(|f| (path.as_str(), f)))
 }
 
 pub fn load_from_file(path: &str) -> Result<AppConfig> {
-    let content = std::fs::read_to_string(path)?;
-    let cfg: AppConfig = toml::from_str(&content)?;
-    Ok(cfg)
+    let file = Resolver::read_file(path)?.ok_or_else(|| anyhow!("配置文件 {} 不存在", path))?;
+    resolve_from(Some((path, file))).map(|r| r.config)
+}
+
+fn resolve_from(file: Option<(&str, serde_json::Value)>) -> Result<Resolved<AppConfig>> {
+    Resolver::new(ENV_PREFIX).with_aliases(ENV_ALIASES).resolve(&AppConfig::default(), file)
 }
 
 impl AppConfig {
@@ -74,6 +1097,24 @@ impl AppConfig {
         // 归一化 database（支持从环境变量填充 URL）
         self.database.normalize_from_env();
         self.database.validate()?;
+        if self.pagination.max_per_page == 0 {
+            return Err(anyhow!("pagination.max_per_page 必须 >= 1"));
+        }
+        if self.backup.enabled && (self.backup.interval_secs == 0 || self.backup.retention == 0) {
+            return Err(anyhow!("backup.interval_secs 与 backup.retention 必须 >= 1"));
+        }
+        let rl = &self.admin_rate_limit;
+        if rl.enabled && (rl.requests_per_second == 0 || rl.burst == 0 || rl.login_per_minute == 0 || rl.login_burst == 0) {
+            return Err(anyhow!("admin_rate_limit 的速率与突发量必须 >= 1（关闭请设置 enabled = false）"));
+        }
+        self.cors.validate()?;
+        self.auth.validate(self.dev.enabled)?;
+        self.signup.validate()?;
+        self.encryption.validate()?;
+        self.request_log.validate()?;
+        self.log_export.validate()?;
+        self.scheduled_reports.validate()?;
+        self.logging.siem.validate().map_err(|e| anyhow!("logging.siem: {}", e))?;
         Ok(())
     }
 }
@@ -83,7 +1124,7 @@ impl ServerConfig {
         if self.host.trim().is_empty() {
             self.host = "127.0.0.1".to_string();
         }
-        if self.port == 0 || self.port > 65535 {
+        if self.port == 0 {
             return Err(anyhow!("server.port 必须在 1..=65535 范围内"));
         }
         if let Some(w) = self.worker_threads {
@@ -0,0 +1,303 @@
+//! 统一配置解析：按固定优先级合并各来源，并记录每个生效值的来源（provenance）。
+//!
+//! 优先级（低 → 高）：内置默认值 < 配置文件（config.toml / config.json）< `.env` < 进程环境变量。
+//! 环境变量命名：`{PREFIX}__{SECTION}__{KEY}`（如 `API_PROXY__DATABASE__URL`），
+//! 另支持少量历史变量名（如 `DATABASE_URL`、`SERVER_PORT`），前缀变量优先于历史变量名。
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+/// 配置值的来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Source {
+    Default,
+    File { path: String },
+    DotEnv { var: String },
+    Env { var: String },
+}
+
+/// 单个生效值及其来源；`overridden` 为被覆盖的低优先级来源（由低到高）
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    pub value: Value,
+    pub source: Source,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overridden: Vec<Source>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Resolved<T> {
+    pub config: T,
+    /// 以 `section.key` 为键
+    pub provenance: BTreeMap<String, Provenance>,
+}
+
+impl<T> Resolved<T> {
+    /// 脱敏后的 provenance（密码、密钥等不直接输出）
+    pub fn redacted_provenance(&self) -> BTreeMap<String, Provenance> {
+        self.provenance
+            .iter()
+            .map(|(k, p)| (k.clone(), Provenance { value: redact(k, &p.value), ..p.clone() }))
+            .collect()
+    }
+}
+
+impl<T: Serialize> Resolved<T> {
+    /// 脱敏后的生效配置
+    pub fn redacted_config(&self) -> Value {
+        let mut tree = serde_json::to_value(&self.config).unwrap_or(Value::Null);
+        let mut leaves = Vec::new();
+        flatten("", &tree, &mut leaves);
+        for (path, v) in leaves {
+            let r = redact(&path, &v);
+            if r != v {
+                set_path(&mut tree, &path, r);
+            }
+        }
+        tree
+    }
+}
+
+/// 解析器：描述一种配置（环境变量前缀、历史变量名、.env 位置）
+pub struct Resolver<'a> {
+    env_prefix: &'a str,
+    aliases: &'a [(&'a str, &'a [&'a str])],
+    dotenv_path: &'a str,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(env_prefix: &'a str) -> Self {
+        Self { env_prefix, aliases: &[], dotenv_path: ".env" }
+    }
+
+    /// `(section.key, [历史变量名...])`
+    pub fn with_aliases(mut self, aliases: &'a [(&'a str, &'a [&'a str])]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    pub fn with_dotenv(mut self, path: &'a str) -> Self {
+        self.dotenv_path = path;
+        self
+    }
+
+    /// 读取配置文件（按扩展名解析 TOML / JSON）；文件不存在时返回 None
+    pub fn read_file(path: &str) -> Result<Option<Value>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let value = if path.ends_with(".json") {
+            serde_json::from_str(&content)?
+        } else {
+            serde_json::to_value(toml::from_str::<toml::Value>(&content)?)?
+        };
+        Ok(Some(value))
+    }
+
+    /// 使用进程环境变量解析
+    pub fn resolve<T: Serialize + DeserializeOwned>(&self, defaults: &T, file: Option<(&str, Value)>) -> Result<Resolved<T>> {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        self.resolve_with_env(defaults, file, &env)
+    }
+
+    pub fn resolve_with_env<T: Serialize + DeserializeOwned>(
+        &self,
+        defaults: &T,
+        file: Option<(&str, Value)>,
+        env: &HashMap<String, String>,
+    ) -> Result<Resolved<T>> {
+        let mut tree = serde_json::to_value(defaults)?;
+        let mut default_leaves = Vec::new();
+        flatten("", &tree, &mut default_leaves);
+
+        let mut provenance: BTreeMap<String, Provenance> = default_leaves
+            .iter()
+            .map(|(k, v)| (k.clone(), Provenance { value: v.clone(), source: Source::Default, overridden: Vec::new() }))
+            .collect();
+        let mut apply = |tree: &mut Value, path: &str, value: Value, source: Source| {
+            set_path(tree, path, value.clone());
+            match provenance.get_mut(path) {
+                Some(p) => {
+                    let prev = std::mem::replace(&mut p.source, source);
+                    p.overridden.push(prev);
+                    p.value = value;
+                }
+                None => {
+                    provenance.insert(path.to_string(), Provenance { value, source, overridden: Vec::new() });
+                }
+            }
+        };
+
+        if let Some((path, file_tree)) = file {
+            let mut leaves = Vec::new();
+            flatten("", &file_tree, &mut leaves);
+            for (k, v) in leaves {
+                apply(&mut tree, &k, v, Source::File { path: path.to_string() });
+            }
+        }
+
+        let dotenv = read_dotenv(self.dotenv_path);
+        for (path, default) in &default_leaves {
+            let Some((var, raw, from_dotenv)) = self.lookup(path, env, &dotenv) else { continue };
+            let value = parse_env_value(&raw, default);
+            let source = if from_dotenv { Source::DotEnv { var } } else { Source::Env { var } };
+            apply(&mut tree, path, value, source);
+        }
+
+        let config: T = serde_json::from_value(tree).map_err(|e| anyhow!("invalid configuration: {}", e))?;
+        Ok(Resolved { config, provenance })
+    }
+
+    /// 返回 (变量名, 值, 是否来自 .env)；进程环境变量优先于 .env
+    fn lookup(&self, path: &str, env: &HashMap<String, String>, dotenv: &HashMap<String, String>) -> Option<(String, String, bool)> {
+        let mut names = vec![format!("{}__{}", self.env_prefix, path.replace('.', "__").to_uppercase())];
+        if let Some((_, alias)) = self.aliases.iter().find(|(p, _)| *p == path) {
+            names.extend(alias.iter().map(|a| a.to_string()));
+        }
+        names.into_iter().find_map(|name| match (env.get(&name), dotenv.get(&name)) {
+            // dotenvy 不覆盖已存在的变量：值相同说明来自 .env
+            (Some(v), Some(d)) => Some((name, v.clone(), v == d)),
+            (Some(v), None) => Some((name, v.clone(), false)),
+            (None, Some(d)) => Some((name, d.clone(), true)),
+            (None, None) => None,
+        })
+    }
+}
+
+fn read_dotenv(path: &str) -> HashMap<String, String> {
+    match dotenvy::from_path_iter(path) {
+        Ok(iter) => iter.filter_map(|r| r.ok()).collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// 按默认值的类型解析环境变量；字符串字段保持原样
+fn parse_env_value(raw: &str, default: &Value) -> Value {
+    match default {
+        Value::String(_) => Value::String(raw.to_string()),
+        Value::Bool(_) => Value::Bool(matches!(raw.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes" | "on")),
+        _ => serde_json::from_str(raw.trim()).unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+/// 展开为 (a.b.c, 叶子值)；数组与空对象视为叶子
+fn flatten(prefix: &str, v: &Value, out: &mut Vec<(String, Value)>) {
+    match v {
+        Value::Object(m) if !m.is_empty() => {
+            for (k, child) in m {
+                let path = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+                flatten(&path, child, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), v.clone())),
+    }
+}
+
+fn set_path(tree: &mut Value, path: &str, value: Value) {
+    let mut cur = tree;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if !cur.is_object() {
+            *cur = Value::Object(Map::new());
+        }
+        let map = cur.as_object_mut().expect("object");
+        if parts.peek().is_none() {
+            map.insert(part.to_string(), value);
+            return;
+        }
+        cur = map.entry(part.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+/// 密钥类字段整体隐藏；URL 中的密码部分隐藏
+pub fn redact(path: &str, value: &Value) -> Value {
+    let key = path.rsplit('.').next().unwrap_or(path).to_ascii_lowercase();
+    let sensitive = ["secret", "password", "token", "key"].iter().any(|s| key == *s || key.ends_with(&format!("_{}", s)));
+    if sensitive && !value.is_null() {
+        return Value::String("***".into());
+    }
+    if let Value::String(s) = value {
+        if let Some((scheme, rest)) = s.split_once("://") {
+            if let Some((userinfo, host)) = rest.split_once('@') {
+                if let Some((user, _)) = userinfo.split_once(':') {
+                    return Value::String(format!("{}://{}:***@{}", scheme, user, host));
+                }
+            }
+        }
+    }
+    value.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Cfg {
+        server: Server,
+        db: Db,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        debug: bool,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Db {
+        url: String,
+    }
+
+    fn defaults() -> Cfg {
+        Cfg { server: Server { host: "127.0.0.1".into(), port: 8080, debug: false }, db: Db { url: String::new() } }
+    }
+
+    #[test]
+    fn precedence_and_provenance() {
+        let file: Value = serde_json::json!({ "server": { "port": 9000 }, "db": { "url": "postgres://u:p@h/db" } });
+        let env = HashMap::from([
+            ("APP__SERVER__PORT".to_string(), "9100".to_string()),
+            ("LEGACY_DEBUG".to_string(), "true".to_string()),
+        ]);
+        let aliases: &[(&str, &[&str])] = &[("server.debug", &["LEGACY_DEBUG"])];
+        let r = Resolver::new("APP").with_aliases(aliases).with_dotenv("/nonexistent/.env")
+            .resolve_with_env(&defaults(), Some(("config.toml", file)), &env)
+            .unwrap();
+
+        assert_eq!(r.config.server.port, 9100);
+        assert!(r.config.server.debug);
+        assert_eq!(r.config.server.host, "127.0.0.1");
+
+        let port = &r.provenance["server.port"];
+        assert_eq!(port.source, Source::Env { var: "APP__SERVER__PORT".into() });
+        assert_eq!(port.overridden, vec![Source::Default, Source::File { path: "config.toml".into() }]);
+        assert_eq!(r.provenance["server.host"].source, Source::Default);
+        assert_eq!(r.provenance["server.debug"].source, Source::Env { var: "LEGACY_DEBUG".into() });
+
+        assert_eq!(r.redacted_provenance()["db.url"].value, "postgres://u:***@h/db");
+        assert_eq!(r.redacted_config()["db"]["url"], "postgres://u:***@h/db");
+    }
+
+    #[test]
+    fn dotenv_source_is_reported() {
+        let dir = std::env::temp_dir().join(format!("resolver_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".env");
+        std::fs::write(&path, "APP__SERVER__HOST=0.0.0.0\n").unwrap();
+        let r = Resolver::new("APP").with_dotenv(path.to_str().unwrap())
+            .resolve_with_env(&defaults(), None, &HashMap::new())
+            .unwrap();
+        assert_eq!(r.config.server.host, "0.0.0.0");
+        assert_eq!(r.provenance["server.host"].source, Source::DotEnv { var: "APP__SERVER__HOST".into() });
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
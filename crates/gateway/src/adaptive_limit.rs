@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::bulkhead::BulkheadError;
+use crate::observability::Metrics;
+
+/// 每隔多少个样本重置一次 min_rtt，使基线能跟随上游的长期变化
+const MIN_RTT_RESET_SAMPLES: u64 = 1000;
+
+/// 单个上游的自适应并发状态
+#[derive(Debug)]
+struct LimitState {
+    limit: f64,
+    in_flight: usize,
+    min_rtt: Option<Duration>,
+    samples: u64,
+}
+
+#[derive(Debug)]
+struct AdaptiveLimiterInner {
+    upstreams: HashMap<String, LimitState>,
+    initial_limit: f64,
+    min_limit: f64,
+    max_limit: f64,
+    latency_tolerance: f64,
+    backoff_ratio: f64,
+    metrics: Arc<Metrics>,
+}
+
+impl AdaptiveLimiterInner {
+    fn state(&mut self, upstream: &str) -> &mut LimitState {
+        let initial = self.initial_limit;
+        self.upstreams.entry(upstream.to_string()).or_insert_with(|| LimitState {
+            limit: initial,
+            in_flight: 0,
+            min_rtt: None,
+            samples: 0,
+        })
+    }
+
+    /// AIMD：延迟超过 min_rtt * tolerance 或请求失败时乘性减小，否则加性增大
+    fn on_sample(&mut self, upstream: &str, rtt: Duration, ok: bool, in_flight_at_start: usize) {
+        let (min_limit, max_limit) = (self.min_limit, self.max_limit);
+        let (tolerance, backoff) = (self.latency_tolerance, self.backoff_ratio);
+        let metrics = Arc::clone(&self.metrics);
+        let state = self.state(upstream);
+
+        state.samples += 1;
+        if state.samples.is_multiple_of(MIN_RTT_RESET_SAMPLES) {
+            state.min_rtt = None;
+        }
+        let min_rtt = *state.min_rtt.get_or_insert(rtt);
+        if rtt < min_rtt {
+            state.min_rtt = Some(rtt);
+        }
+
+        let congested = !ok || rtt.as_secs_f64() > min_rtt.as_secs_f64() * tolerance;
+        if congested {
+            state.limit = (state.limit * backoff).max(min_limit);
+        } else if in_flight_at_start as f64 * 2.0 >= state.limit {
+            // 仅在名额被充分使用时增长，避免低负载下 limit 无限膨胀
+            state.limit = (state.limit + 1.0 / state.limit).min(max_limit);
+        }
+
+        metrics.adaptive_concurrency_limit.with_label_values(&[upstream]).set(state.limit as i64);
+        if let Some(m) = state.min_rtt {
+            metrics.adaptive_min_rtt_seconds.with_label_values(&[upstream]).set(m.as_secs_f64());
+        }
+    }
+}
+
+/// 基于延迟的自适应并发限制（AIMD），作为静态 bulkhead 上限的替代方案：
+/// 每个上游的并发上限随观测到的延迟动态调整，饱和时直接拒绝（不排队）。
+#[derive(Clone)]
+pub struct AdaptiveLimiter {
+    inner: Arc<Mutex<AdaptiveLimiterInner>>,
+    enabled: bool,
+}
+
+impl AdaptiveLimiter {
+    pub fn new(
+        initial_limit: usize,
+        min_limit: usize,
+        max_limit: usize,
+        latency_tolerance: f64,
+        backoff_ratio: f64,
+        enabled: bool,
+    ) -> Self {
+        let min_limit = min_limit.max(1) as f64;
+        let max_limit = (max_limit as f64).max(min_limit);
+        Self {
+            inner: Arc::new(Mutex::new(AdaptiveLimiterInner {
+                upstreams: HashMap::new(),
+                initial_limit: (initial_limit as f64).clamp(min_limit, max_limit),
+                min_limit,
+                max_limit,
+                latency_tolerance: latency_tolerance.max(1.0),
+                backoff_ratio: backoff_ratio.clamp(0.1, 0.99),
+                metrics: Metrics::detached(),
+            })),
+            enabled,
+        }
+    }
+
+    /// 并发上限、min_rtt 与在途计数记录到注入的实例
+    pub fn with_metrics(self, metrics: Arc<Metrics>) -> Self {
+        self.inner.lock().expect("adaptive limiter lock").metrics = metrics;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 尝试占用一个名额；未启用时返回 None
+    pub fn try_acquire(&self, upstream: &str) -> Result<Option<AdaptivePermit>, BulkheadError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let mut inner = self.inner.lock().expect("adaptive limiter lock");
+        let metrics = Arc::clone(&inner.metrics);
+        let state = inner.state(upstream);
+        if state.in_flight as f64 >= state.limit.floor() {
+            debug!(upstream = %upstream, limit = state.limit, "adaptive concurrency limit reached");
+            metrics.bulkhead_rejected_total.with_label_values(&[upstream, "adaptive_limit"]).inc();
+            return Err(BulkheadError::LimitExceeded);
+        }
+        state.in_flight += 1;
+        let in_flight = state.in_flight;
+        metrics.bulkhead_in_flight.with_label_values(&[upstream]).inc();
+        Ok(Some(AdaptivePermit {
+            limiter: self.inner.clone(),
+            metrics,
+            upstream: upstream.to_string(),
+            start: Instant::now(),
+            in_flight,
+            outcome: None,
+        }))
+    }
+
+    /// 当前上限（用于测试与诊断）
+    pub fn limit(&self, upstream: &str) -> usize {
+        let mut inner = self.inner.lock().expect("adaptive limiter lock");
+        inner.state(upstream).limit as usize
+    }
+}
+
+/// 自适应名额；记录的请求结果在 Drop 时作为样本反馈给限流器
+#[derive(Debug)]
+pub struct AdaptivePermit {
+    limiter: Arc<Mutex<AdaptiveLimiterInner>>,
+    metrics: Arc<Metrics>,
+    upstream: String,
+    start: Instant,
+    in_flight: usize,
+    outcome: Option<(Duration, bool)>,
+}
+
+impl AdaptivePermit {
+    /// 记录本次请求的延迟（从获取名额开始计算）与是否成功；仅首次调用生效
+    pub fn record(&mut self, ok: bool) {
+        if self.outcome.is_none() {
+            self.outcome = Some((self.start.elapsed(), ok));
+        }
+    }
+
+    #[cfg(test)]
+    fn record_with_latency(&mut self, rtt: Duration, ok: bool) {
+        self.outcome = Some((rtt, ok));
+    }
+}
+
+impl Drop for AdaptivePermit {
+    fn drop(&mut self) {
+        self.metrics.bulkhead_in_flight.with_label_values(&[&self.upstream]).dec();
+        let mut inner = match self.limiter.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let state = inner.state(&self.upstream);
+        state.in_flight = state.in_flight.saturating_sub(1);
+        if let Some((rtt, ok)) = self.outcome {
+            inner.on_sample(&self.upstream, rtt, ok, self.in_flight);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(initial: usize) -> AdaptiveLimiter {
+        AdaptiveLimiter::new(initial, 1, 100, 2.0, 0.5, true)
+    }
+
+    #[test]
+    fn test_rejects_when_limit_reached() {
+        let l = limiter(2);
+        let _a = l.try_acquire("u").unwrap();
+        let b = l.try_acquire("u").unwrap();
+        assert_eq!(l.try_acquire("u").unwrap_err(), BulkheadError::LimitExceeded);
+        drop(b);
+        assert!(l.try_acquire("u").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_additive_increase_when_saturated_and_fast() {
+        let l = limiter(2);
+        let mut a = l.try_acquire("u").unwrap().unwrap();
+        let mut b = l.try_acquire("u").unwrap().unwrap();
+        a.record_with_latency(Duration::from_millis(10), true);
+        b.record_with_latency(Duration::from_millis(10), true);
+        drop(a);
+        drop(b);
+        // 2 + 1/2 + 1/2.5 ≈ 2.9
+        assert_eq!(l.limit("u"), 2);
+        let mut c = l.try_acquire("u").unwrap().unwrap();
+        let mut d = l.try_acquire("u").unwrap().unwrap();
+        c.record_with_latency(Duration::from_millis(10), true);
+        d.record_with_latency(Duration::from_millis(10), true);
+        drop(c);
+        drop(d);
+        assert_eq!(l.limit("u"), 3);
+    }
+
+    #[test]
+    fn test_multiplicative_decrease_on_latency_or_failure() {
+        let l = limiter(40);
+        let mut p = l.try_acquire("u").unwrap().unwrap();
+        p.record_with_latency(Duration::from_millis(10), true);
+        drop(p);
+        // 延迟超过 min_rtt * 2
+        let mut p = l.try_acquire("u").unwrap().unwrap();
+        p.record_with_latency(Duration::from_millis(50), true);
+        drop(p);
+        assert_eq!(l.limit("u"), 20);
+        // 失败同样触发减小，且不低于 min_limit
+        for _ in 0..10 {
+            let mut p = l.try_acquire("u").unwrap().unwrap();
+            p.record_with_latency(Duration::from_millis(1), false);
+        }
+        assert_eq!(l.limit("u"), 1);
+    }
+
+    #[test]
+    fn test_disabled_and_unrecorded_permits() {
+        let off = AdaptiveLimiter::new(1, 1, 1, 2.0, 0.5, false);
+        for _ in 0..3 {
+            assert!(off.try_acquire("u").unwrap().is_none());
+        }
+        // 未记录结果的名额只释放并发，不影响 limit
+        let l = limiter(5);
+        drop(l.try_acquire("u").unwrap());
+        assert_eq!(l.limit("u"), 5);
+    }
+}
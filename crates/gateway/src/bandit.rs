@@ -0,0 +1,208 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::config::{BanditConfig, BanditStrategy};
+use crate::observability::Metrics;
+
+/// 单个上游的观测统计
+#[derive(Debug, Default)]
+struct Arm {
+    /// 奖励的指数滑动平均（0-1）
+    reward: f64,
+    samples: u64,
+    /// 近期流量占比的指数滑动平均
+    share: f64,
+}
+
+#[derive(Debug)]
+struct BanditInner {
+    arms: HashMap<String, Arm>,
+    total_samples: u64,
+}
+
+/// 实验性的多臂老虎机选择器：按观测到的延迟/成功率把流量逐步偏向表现最好的上游，
+/// 同时受 min_share / max_share 约束，避免饿死或压垮单个上游。
+#[derive(Clone)]
+pub struct BanditSelector {
+    inner: Arc<Mutex<BanditInner>>,
+    config: BanditConfig,
+    metrics: Arc<Metrics>,
+}
+
+impl BanditSelector {
+    pub fn new(config: BanditConfig) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(BanditInner { arms: HashMap::new(), total_samples: 0 })),
+            config,
+            metrics: Metrics::detached(),
+        }
+    }
+
+    /// 奖励、占比与选择计数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 成功请求的奖励随延迟衰减：latency_target 处为 0.5；失败为 0
+    fn reward(&self, rtt: Duration, ok: bool) -> f64 {
+        if !ok {
+            return 0.0;
+        }
+        let target = self.config.latency_target_ms.max(1) as f64;
+        1.0 / (1.0 + rtt.as_secs_f64() * 1000.0 / target)
+    }
+
+    /// 从候选上游中选择一个，返回下标；候选为空或未启用时返回 None
+    pub fn select(&self, candidates: &[String]) -> Option<usize> {
+        if !self.config.enabled || candidates.is_empty() {
+            return None;
+        }
+        let mut rng = rand::thread_rng();
+        let explore = rng.gen_bool(self.config.epsilon.clamp(0.0, 1.0));
+        let mut inner = self.inner.lock().expect("bandit lock");
+        let (choice, reason) = self.choose(&inner, candidates, explore, |n| rng.gen_range(0..n));
+
+        // 更新占比：选中者趋向 1，其余趋向 0
+        let alpha = self.config.share_alpha.clamp(0.001, 1.0);
+        for (i, addr) in candidates.iter().enumerate() {
+            let arm = inner.arms.entry(addr.clone()).or_default();
+            let hit = if i == choice { 1.0 } else { 0.0 };
+            arm.share += alpha * (hit - arm.share);
+            self.metrics.bandit_share.with_label_values(&[addr]).set(arm.share);
+        }
+        self.metrics.bandit_selections_total.with_label_values(&[&candidates[choice], reason]).inc();
+        debug!(upstream = %candidates[choice], reason, "bandit upstream selected");
+        Some(choice)
+    }
+
+    fn choose(&self, inner: &BanditInner, candidates: &[String], explore: bool, mut random: impl FnMut(usize) -> usize) -> (usize, &'static str) {
+        let arm = |inner: &BanditInner, i: usize| inner.arms.get(&candidates[i]).map(|a| (a.reward, a.samples, a.share)).unwrap_or((0.0, 0, 0.0));
+
+        // 1. 保底：占比低于 min_share 的上游优先（取最缺流量的一个）
+        let starved = (0..candidates.len())
+            .filter(|&i| arm(inner, i).2 < self.config.min_share)
+            .min_by(|&a, &b| arm(inner, a).2.total_cmp(&arm(inner, b).2));
+        // 候选过多时 min_share 之和会超过 1，无法同时满足，此时不做保底
+        let min_share_feasible = self.config.min_share * candidates.len() as f64 <= 1.0;
+        if let Some(i) = starved.filter(|_| min_share_feasible) {
+            return (i, "min_share");
+        }
+
+        // 2. 上限：占比超过 max_share 的上游暂不参与（全部超限时忽略约束）
+        let mut eligible: Vec<usize> = (0..candidates.len()).filter(|&i| arm(inner, i).2 <= self.config.max_share).collect();
+        if eligible.is_empty() {
+            eligible = (0..candidates.len()).collect();
+        }
+
+        match self.config.strategy {
+            BanditStrategy::EpsilonGreedy => {
+                if explore {
+                    return (eligible[random(eligible.len())], "explore");
+                }
+                // 未有样本的上游先试一次
+                if let Some(&i) = eligible.iter().find(|&&i| arm(inner, i).1 == 0) {
+                    return (i, "explore");
+                }
+                let best = eligible.iter().copied().max_by(|&a, &b| arm(inner, a).0.total_cmp(&arm(inner, b).0)).unwrap_or(eligible[0]);
+                (best, "exploit")
+            }
+            BanditStrategy::Ucb => {
+                if let Some(&i) = eligible.iter().find(|&&i| arm(inner, i).1 == 0) {
+                    return (i, "explore");
+                }
+                let ln_n = (inner.total_samples.max(1) as f64).ln();
+                let c = self.config.ucb_exploration;
+                let score = |i: usize| {
+                    let (reward, n, _) = arm(inner, i);
+                    reward + c * (2.0 * ln_n / n as f64).sqrt()
+                };
+                let best = eligible.iter().copied().max_by(|&a, &b| score(a).total_cmp(&score(b))).unwrap_or(eligible[0]);
+                (best, "ucb")
+            }
+        }
+    }
+
+    /// 记录一次请求结果（延迟从请求开始计算）
+    pub fn record(&self, upstream: &str, rtt: Duration, ok: bool) {
+        if !self.config.enabled {
+            return;
+        }
+        let reward = self.reward(rtt, ok);
+        let alpha = self.config.reward_alpha.clamp(0.001, 1.0);
+        let mut inner = self.inner.lock().expect("bandit lock");
+        inner.total_samples += 1;
+        let arm = inner.arms.entry(upstream.to_string()).or_default();
+        arm.reward = if arm.samples == 0 { reward } else { arm.reward + alpha * (reward - arm.reward) };
+        arm.samples += 1;
+        self.metrics.bandit_reward.with_label_values(&[upstream]).set(arm.reward);
+    }
+
+    /// 当前奖励估计（用于测试与诊断）
+    pub fn reward_of(&self, upstream: &str) -> Option<f64> {
+        self.inner.lock().expect("bandit lock").arms.get(upstream).map(|a| a.reward)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(strategy: BanditStrategy, epsilon: f64) -> BanditSelector {
+        BanditSelector::new(BanditConfig { enabled: true, strategy, epsilon, min_share: 0.05, max_share: 0.9, ..BanditConfig::default() })
+    }
+
+    fn peers() -> Vec<String> {
+        vec!["10.0.0.1:80".to_string(), "10.0.0.2:80".to_string()]
+    }
+
+    fn run(b: &BanditSelector, rounds: usize) -> usize {
+        let peers = peers();
+        let mut fast = 0;
+        for _ in 0..rounds {
+            let i = b.select(&peers).unwrap();
+            let rtt = if i == 0 { Duration::from_millis(20) } else { Duration::from_millis(400) };
+            b.record(&peers[i], rtt, true);
+            fast += usize::from(i == 0);
+        }
+        fast
+    }
+
+    #[test]
+    fn test_shifts_traffic_to_faster_peer() {
+        let b = selector(BanditStrategy::EpsilonGreedy, 0.1);
+        let fast = run(&b, 1000);
+        assert!(fast > 700, "fast peer got {fast}");
+        assert!(b.reward_of("10.0.0.1:80").unwrap() > b.reward_of("10.0.0.2:80").unwrap());
+
+        let ucb = selector(BanditStrategy::Ucb, 0.0);
+        assert!(run(&ucb, 1000) > 600);
+    }
+
+    #[test]
+    fn test_share_bounds() {
+        let b = selector(BanditStrategy::EpsilonGreedy, 0.0);
+        let fast = run(&b, 2000);
+        // max_share 0.9 + min_share 0.05：慢上游仍能拿到一部分流量
+        assert!(fast < 1960, "fast peer got {fast}");
+        assert!(fast > 1700, "fast peer got {fast}");
+    }
+
+    #[test]
+    fn test_failures_have_zero_reward_and_disabled_selects_nothing() {
+        let b = selector(BanditStrategy::EpsilonGreedy, 0.0);
+        b.record("u", Duration::from_millis(1), false);
+        assert_eq!(b.reward_of("u"), Some(0.0));
+
+        let off = BanditSelector::new(BanditConfig::default());
+        assert_eq!(off.select(&peers()), None);
+        assert_eq!(selector(BanditStrategy::Ucb, 0.0).select(&[]), None);
+    }
+}
@@ -2,42 +2,211 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use arc_swap::ArcSwap;
+use pingora_core::server::configuration::Opt;
 use pingora_core::server::Server;
 use pingora_core::services::background::background_service;
+use pingora_core::services::Service;
 use pingora_load_balancing::health_check;
 use pingora_load_balancing::selection::RoundRobin;
-use pingora_load_balancing::LoadBalancer;
+use pingora_load_balancing::{Backend, Backends, LoadBalancer};
 use tracing::{info, warn};
-use common::utils::logging::init_logging_json;
+use common::diagnostics::{redis_check, Check, CheckStatus, Readiness};
+use common::time::{system_clock, SharedClock};
+use common::utils::logging::init_logging;
 use service::admin_http;
 
-use crate::config::ProxyConfig;
-use crate::observability;
+use crate::config::{ListenerConfig, ProxyConfig};
+use crate::observability::Metrics;
 use crate::proxy::LB;
 use crate::rate_limiter::RateLimiter;
 use crate::retry::RetryPolicy;
 use crate::circuit_breaker::CircuitBreaker;
+use crate::outlier::OutlierDetector;
+use crate::bulkhead::Bulkhead;
+use crate::adaptive_limit::AdaptiveLimiter;
+use crate::bandit::BanditSelector;
+use crate::locality::{self, LocalitySelector};
+use crate::load_shed::LoadShedder;
+use crate::upgrade;
+use crate::discovery::KubernetesDiscovery;
+use crate::listeners::{self, Listeners};
+use crate::waf::Waf;
+use crate::bot::BotDetector;
+use crate::geoip::GeoIp;
+use crate::latency::LatencyTracker;
+use crate::error_pages::{self, ErrorPages};
+use crate::faults::{self, FaultInjector};
+use crate::failover::{self, Failover, UpstreamGroup};
+use crate::cluster::ClusterSync;
+use crate::key_activity::KeyActivity;
+use crate::rate_limit_records::RateLimitRecords;
+use crate::recording::Recording;
+use crate::reload::{self, ConfigReloader};
+use crate::secrets::UpstreamSecrets;
 
 // admin server spawner moved to service::admin_http
 
-fn init_tracing() { init_logging_json(); }
+/// `/readyz`：配置有效、至少一个健康上游、Redis（如配置）
+async fn readiness(upstreams: &LoadBalancer<RoundRobin>, config: Check) -> Readiness {
+    let backends = upstreams.backends().get_backend();
+    let healthy = backends.iter().filter(|b| upstreams.backends().ready(b)).count();
+    let detail = format!("{}/{} healthy", healthy, backends.len());
+    let pool = if healthy > 0 { Check::ok("upstreams", detail) } else { Check::fail("upstreams", detail) };
+    Readiness::new(vec![config, pool, redis_check().await])
+}
 
-pub fn run() {
-    init_tracing();
-
-    // Load configuration
-    let config = ProxyConfig::load_from_file("config.json").unwrap_or_else(|e| {
-        warn!("Failed to load config file: {}, using defaults", e);
-        ProxyConfig::default()
+/// 在独立线程的 runtime 上运行内置 echo 上游，返回实际监听地址
+fn spawn_echo(addr: &str) -> anyhow::Result<std::net::SocketAddr> {
+    let addr = addr.to_string();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => return drop(tx.send(Err(e.into()))),
+        };
+        rt.block_on(async move {
+            let bound = common::echo::spawn(&addr).await;
+            let ok = bound.is_ok();
+            let _ = tx.send(bound);
+            if ok {
+                std::future::pending::<()>().await;
+            }
+        });
     });
-    info!("Loaded configuration: {:?}", config);
+    rx.recv()?
+}
+
+/// 默认的 admin 监听地址（livez/readyz/metrics 等）
+pub const DEFAULT_ADMIN_ADDR: &str = "127.0.0.1:9188";
+
+/// 组装网关进程：配置、监听端口、附加后台服务与 admin 路由。
+/// 二进制入口只做进程级工作（日志、panic 钩子），其余都经由这里，修复只需改一处。
+pub struct GatewayBuilder {
+    config_path: String,
+    admin_addr: String,
+    admin_routes: axum::Router,
+    listeners: Vec<ListenerConfig>,
+    services: Vec<Box<dyn Service>>,
+    clock: SharedClock,
+}
+
+impl Default for GatewayBuilder {
+    fn default() -> Self {
+        Self {
+            config_path: "config.json".to_string(),
+            admin_addr: DEFAULT_ADMIN_ADDR.to_string(),
+            admin_routes: axum::Router::new(),
+            listeners: Vec::new(),
+            services: Vec::new(),
+            clock: system_clock(),
+        }
+    }
+}
+
+impl GatewayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config_path(mut self, path: impl Into<String>) -> Self {
+        self.config_path = path.into();
+        self
+    }
+
+    pub fn admin_addr(mut self, addr: impl Into<String>) -> Self {
+        self.admin_addr = addr.into();
+        self
+    }
+
+    /// 合并到 admin server 的额外路由
+    pub fn admin_routes(mut self, routes: axum::Router) -> Self {
+        self.admin_routes = self.admin_routes.merge(routes);
+        self
+    }
+
+    /// 在配置文件 `listeners` 之外追加监听端口；name 不可与已有的重复
+    pub fn listener(mut self, listener: ListenerConfig) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
+    /// 与代理服务一同托管的 Pingora 服务（后台任务等）
+    pub fn service(mut self, service: impl Service + 'static) -> Self {
+        self.services.push(Box::new(service));
+        self
+    }
 
-    // Spawn admin server for healthz/metrics
-    admin_http::spawn_admin_server("127.0.0.1:9188", observability::encode_metrics);
+    /// 请求处理中与 now 比较的时钟（调试请求时间戳、转发签名）；默认系统时钟
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
 
-    // Create Pingora server process
-    let mut server = Server::new(None).expect("init server");
+    /// 构建并运行网关；正常情况下不返回，配置无效时返回错误
+    pub fn run(self) -> anyhow::Result<()> {
+        launch(self)
+    }
+}
+
+/// 以默认设置（config.json、admin 127.0.0.1:9188）运行网关
+pub fn run() -> anyhow::Result<()> {
+    GatewayBuilder::new().run()
+}
+
+fn launch(builder: GatewayBuilder) -> anyhow::Result<()> {
+    let GatewayBuilder { config_path, admin_addr, admin_routes: extra_admin_routes, listeners: extra_listeners, services, clock } = builder;
+    // Load configuration; logging is initialized from its `logging` section before anything is reported
+    let resolved = ProxyConfig::load_resolved(&config_path);
+    init_logging(&resolved.as_ref().map(|r| r.config.logging.clone()).unwrap_or_else(|_| ProxyConfig::default().logging));
+    let mut config_check = Check::ok("config", None);
+    let mut config = match resolved {
+        Ok(resolved) => {
+            for (key, p) in resolved.redacted_provenance() {
+                if p.source != configs::resolver::Source::Default {
+                    info!(key = %key, value = %p.value, source = ?p.source, "config value");
+                }
+            }
+            resolved.config
+        }
+        Err(e) => {
+            warn!("Failed to load config file: {}, using defaults", e);
+            // 没有配置文件时以默认值运行；文件存在但无法解析则不就绪
+            config_check = if std::path::Path::new(&config_path).exists() {
+                Check::fail("config", e.to_string())
+            } else {
+                Check::new("config", CheckStatus::Warn, Some(format!("{} not found; using defaults", config_path)))
+            };
+            ProxyConfig::default()
+        }
+    };
+    info!("Loaded configuration: {:?}", config);
+    // 网关指标实例（独立 registry），注入 LB、各组件与 admin server
+    let metrics = Arc::new(Metrics::new(config.latency.buckets.clone()).map_err(|e| anyhow::anyhow!("register gateway metrics: {}", e))?);
+    let latency = Arc::new(LatencyTracker::new(&config.latency, metrics.route_request_duration.clone()));
+
+    // Create Pingora server process; `--upgrade` takes over listening sockets from the old process
+    let opt = Opt::parse_args();
+    let (upgrading, daemon) = (opt.upgrade, opt.daemon);
+    let mut server = Server::new_with_opt_and_conf(opt, upgrade::server_conf(&config.upgrade));
     server.bootstrap();
+    if !daemon {
+        if let Err(e) = upgrade::write_pid_file(&config.upgrade.pid_file) {
+            warn!(pid_file = %config.upgrade.pid_file, err = %e, "write pid file failed");
+        }
+    }
+    info!(
+        event = "process_start",
+        pid = std::process::id(),
+        upgrade = upgrading,
+        upgrade_sock = %config.upgrade.upgrade_sock,
+        "gateway process bootstrapped"
+    );
+
+    // Built-in echo upstream starts before the load balancer so the first health check already sees it
+    if config.echo.enabled {
+        let addr = spawn_echo(&config.echo.listen).expect("start echo upstream");
+        info!(event = "echo_upstream", addr = %addr, "built-in echo upstream listening");
+    }
 
     // Build upstream list for load balancing from config
     let peers: Vec<std::net::SocketAddr> = config
@@ -47,7 +216,23 @@ pub fn run() {
         .collect();
 
     // Create LoadBalancer with RoundRobin selection and health checks
-    let mut load_balancer = LoadBalancer::<RoundRobin>::try_from_iter(peers).expect("create lb");
+    let mut load_balancer = if config.kubernetes.enabled {
+        // Static upstreams only serve until the first Kubernetes sync completes
+        let fallback = peers.iter().map(|p| Backend::new(&p.to_string()).expect("upstream backend")).collect();
+        let discovery = KubernetesDiscovery::new(fallback).with_metrics(Arc::clone(&metrics));
+        #[cfg(feature = "k8s")]
+        server.add_service(background_service(
+            "k8s discovery",
+            crate::discovery::KubernetesWatcher::new(discovery.clone(), config.kubernetes.clone()),
+        ));
+        #[cfg(not(feature = "k8s"))]
+        warn!("kubernetes discovery is enabled but the gateway was built without the `k8s` feature; using static upstreams");
+        let mut lb = LoadBalancer::<RoundRobin>::from_backends(Backends::new(Box::new(discovery)));
+        lb.update_frequency = Some(Duration::from_millis(config.kubernetes.update_interval_ms.max(100)));
+        lb
+    } else {
+        LoadBalancer::<RoundRobin>::try_from_iter(peers).expect("create lb")
+    };
     let tcp_hc = health_check::TcpHealthCheck::new();
     load_balancer.set_health_check(tcp_hc);
     load_balancer.health_check_frequency = Some(Duration::from_secs(1));
@@ -57,12 +242,57 @@ pub fn run() {
     let upstreams = background.task();
     server.add_service(background);
 
+    // Fault injection rules are validated at startup; the admin API replaces them at runtime (only when enabled)
+    let fault_injector = Arc::new(FaultInjector::from_config(&config.fault_injection).expect("invalid fault_injection rules"));
+    if fault_injector.is_enabled() {
+        warn!(event = "fault_injection_enabled", rules = config.fault_injection.rules.len(), "fault injection is enabled; do not use in production");
+    }
+
+    // Tenant error pages; the admin API manages them at runtime (only when enabled)
+    let error_pages = Arc::new(ErrorPages::from_config(&config.error_pages).expect("invalid error_pages"));
+
+    // Spawn admin server for livez/readyz/metrics, the per-route latency summary, runtime log level and optional profiling
+    let tracker = Arc::clone(&latency);
+    let pool = Arc::clone(&upstreams);
+    let admin_routes = axum::Router::new()
+        .route("/readyz", axum::routing::get(move || {
+            let (pool, config_check) = (Arc::clone(&pool), config_check.clone());
+            async move {
+                let r = readiness(&pool, config_check).await;
+                (r.status_code(), axum::Json(r))
+            }
+        }))
+        .route("/latency", axum::routing::get(move || async move { axum::Json(tracker.summary()) }))
+        .merge(admin_http::log_level_routes())
+        .merge(extra_admin_routes);
+    let admin_routes = if config.profiling.enabled {
+        if config.profiling.token.as_deref().unwrap_or_default().is_empty() {
+            warn!("profiling is enabled but profiling.token is empty; /debug/pprof/* will reject every request");
+        }
+        admin_routes.merge(admin_http::profiling_routes(config.profiling.clone()))
+    } else {
+        admin_routes
+    };
+    let admin_routes = if fault_injector.is_enabled() {
+        admin_routes.merge(faults::admin_routes(Arc::clone(&fault_injector)))
+    } else {
+        admin_routes
+    };
+    let admin_routes = if error_pages.is_enabled() {
+        admin_routes.merge(error_pages::admin_routes(Arc::clone(&error_pages)))
+    } else {
+        admin_routes
+    };
     // Create rate limiter
     let rate_limiter = RateLimiter::new(
         config.rate_limit.requests_per_second,
         config.rate_limit.burst_size,
         config.rate_limit.enabled,
-    );
+    )
+    .with_metrics(Arc::clone(&metrics))
+    .with_refill_interval(config.rate_limit.refill_interval_ms.map(Duration::from_millis))
+    .with_spike_arrest(Duration::from_millis(config.rate_limit.spike_arrest_interval_ms.unwrap_or(0)));
+    rate_limiter.spawn_top_keys_exporter(config.rate_limit.top_rejected_keys, Duration::from_secs(config.rate_limit.top_rejected_keys_interval_secs.max(1)));
 
     // Create circuit breaker
     let circuit_breaker = CircuitBreaker::new(
@@ -70,7 +300,36 @@ pub fn run() {
         config.recovery_timeout(),
         config.circuit_breaker.half_open_max_calls,
         config.circuit_breaker.enabled,
-    );
+    )
+    .with_metrics(Arc::clone(&metrics))
+    .with_half_open_queue(config.circuit_breaker.half_open_queue_size, config.half_open_queue_timeout());
+
+    // Failover groups get their own health checks and breakers; the default group is `upstreams` with the gateway breaker
+    let failover = if config.failover.enabled {
+        failover::validate(&config.failover).expect("invalid failover config");
+        let mut groups = vec![UpstreamGroup::new(failover::DEFAULT_GROUP, Arc::clone(&upstreams), circuit_breaker.clone())];
+        for (name, addrs) in &config.failover.groups {
+            let mut lb = LoadBalancer::<RoundRobin>::try_from_iter(addrs.iter().map(String::as_str)).expect("create failover group lb");
+            lb.set_health_check(health_check::TcpHealthCheck::new());
+            lb.health_check_frequency = Some(Duration::from_secs(1));
+            let background = background_service(&format!("health check {}", name), lb);
+            let breaker = CircuitBreaker::for_upstream(
+                name,
+                config.circuit_breaker.failure_threshold,
+                config.recovery_timeout(),
+                config.circuit_breaker.half_open_max_calls,
+                config.circuit_breaker.enabled,
+            )
+            .with_metrics(Arc::clone(&metrics))
+            .with_half_open_queue(config.circuit_breaker.half_open_queue_size, config.half_open_queue_timeout());
+            groups.push(UpstreamGroup::new(name.clone(), background.task(), breaker));
+            server.add_service(background);
+        }
+        info!(event = "failover_enabled", groups = groups.len(), routes = config.failover.routes.len(), "upstream failover is enabled");
+        Failover::new(&config.failover, groups).expect("invalid failover config")
+    } else {
+        Failover::disabled()
+    };
 
     // Create retry policy
     let retry_policy = RetryPolicy::new(
@@ -80,25 +339,155 @@ pub fn run() {
         config.retry.enabled,
     );
 
-    // Create shared config for hot reloading
+    // Create outlier detector (passive health checking)
+    let outlier_detector = OutlierDetector::new(
+        config.outlier_detection.consecutive_failures,
+        config.base_ejection(),
+        config.max_ejection(),
+        config.outlier_detection.enabled,
+    )
+    .with_metrics(Arc::clone(&metrics));
+
+    // Create per-upstream bulkhead (concurrency isolation)
+    let bulkhead = Bulkhead::new(
+        config.bulkhead.max_in_flight,
+        config.bulkhead.max_queue,
+        config.queue_timeout(),
+        config.bulkhead.per_upstream.clone(),
+        config.bulkhead.enabled,
+    )
+    .with_metrics(Arc::clone(&metrics));
+
+    // Create adaptive concurrency limiter (alternative to static bulkhead limits)
+    let adaptive_limiter = AdaptiveLimiter::new(
+        config.adaptive_concurrency.initial_limit,
+        config.adaptive_concurrency.min_limit,
+        config.adaptive_concurrency.max_limit,
+        config.adaptive_concurrency.latency_tolerance,
+        config.adaptive_concurrency.backoff_ratio,
+        config.adaptive_concurrency.enabled,
+    )
+    .with_metrics(Arc::clone(&metrics));
+
+    // Create priority-aware load shedder (CPU sampled on a background thread)
+    let load_shedder = LoadShedder::new(
+        config.load_shedding.max_in_flight,
+        config.load_shedding.thresholds.clone(),
+        config.load_shedding.enabled,
+    )
+    .with_metrics(Arc::clone(&metrics));
+    load_shedder.spawn_cpu_sampler(Duration::from_millis(config.load_shedding.cpu_sample_interval_ms.max(100)));
+
+    // Experimental bandit upstream selection (falls back to round robin when disabled)
+    let bandit = BanditSelector::new(config.bandit.clone()).with_metrics(Arc::clone(&metrics));
+
+    // Locality-aware selection prefers same-zone/region upstreams of `upstreams`
+    locality::validate(&config.locality).expect("invalid locality config");
+    let locality = LocalitySelector::new(&config.locality);
+    if locality.is_enabled() {
+        info!(event = "locality_enabled", region = %config.locality.region, zone = ?config.locality.zone, labeled = config.locality.upstreams.len(), "locality-aware upstream selection is enabled");
+    }
+
+    // Listeners are fixed at startup; scope rules are read per request
+    config.listeners.extend(extra_listeners);
+    let listeners = Listeners::from_config(&config.listeners).expect("invalid listeners");
+    listeners::validate_scopes(&config.listener_scopes, &listeners).expect("invalid listener_scopes");
+    let listener_configs = config.listeners.clone();
+
+    // WAF rules are compiled once; invalid regexes fail startup
+    let waf = Waf::new(&config.waf).expect("invalid waf rules").with_metrics(Arc::clone(&metrics));
+    let bot_detector = BotDetector::new(config.bot_detection.clone()).with_metrics(Arc::clone(&metrics));
+    let geoip = Arc::new(GeoIp::with_metrics(config.geoip.clone(), Arc::clone(&metrics)));
+    geoip.spawn_reloader();
+
+    // Upstream credentials and the signing secret are decrypted with the master key; a missing or wrong key fails startup
+    let secrets = Arc::new(UpstreamSecrets::from_config(&config)?);
+
+    // Recording fixtures are created (record) or loaded (playback) once; a missing or invalid fixture fails startup
+    let recording = Recording::from_config(&config.recording).expect("invalid recording config");
+
+    // Cluster mode shares rate limit usage, breaker trips and ejections with the other instances
+    if config.cluster.enabled {
+        let breakers = std::iter::once((failover::DEFAULT_GROUP.to_string(), circuit_breaker.clone()))
+            .chain(failover.groups().filter(|g| g.name != failover::DEFAULT_GROUP).map(|g| (g.name.clone(), g.breaker.clone())))
+            .collect();
+        let sync = ClusterSync::new(config.cluster.clone(), rate_limiter.clone(), breakers, outlier_detector.clone(), Arc::clone(&metrics)).expect("invalid cluster config");
+        info!(event = "cluster_enabled", node = %sync.node(), channel = %config.cluster.channel, "cluster mode is enabled");
+        server.add_service(background_service("cluster sync", sync));
+    }
+
+    // Per-tenant spike arrest intervals come from the control plane's rate_limit records
+    if config.rate_limit.records.enabled {
+        let records = RateLimitRecords::new(&config.rate_limit.records, rate_limiter.clone()).expect("invalid rate_limit.records config");
+        info!(event = "rate_limit_records_enabled", "tenant spike arrest intervals are synced from rate_limit records");
+        server.add_service(background_service("rate limit records", records));
+    }
+
+    // API key last_used_at is written back by a background service; requests only record in memory
+    let key_activity = if config.key_activity.enabled {
+        let activity = KeyActivity::new(&config.key_activity).expect("invalid key_activity config");
+        let recorder = activity.recorder();
+        info!(event = "key_activity_enabled", header = %recorder.header(), "api key activity tracking is enabled");
+        server.add_service(background_service("key activity", activity));
+        Some(recorder)
+    } else {
+        None
+    };
+
+    // 浏览器跨域访问 admin 路由按 `cors` 放行；网关没有开发模式，未配置来源时只允许同源
+    config.cors.validate().expect("invalid cors config");
+    let cors = common::cors::layer(&config.cors, false);
+
+    // Create shared config for hot reloading; POST /config/reload replaces it and rebuilds the upstream secrets
     let shared_config = Arc::new(ArcSwap::from_pointee(config));
+    let listeners = Arc::new(listeners);
+    let reloader = ConfigReloader::new(config_path, Arc::clone(&shared_config), Arc::clone(&secrets), Arc::clone(&listeners));
+    let admin_routes = admin_routes.merge(reload::admin_routes(Arc::new(reloader))).layer(cors);
+    let exported = Arc::clone(&metrics);
+    admin_http::spawn_admin_server_with(&admin_addr, move || exported.encode(), admin_routes);
 
     // Create LB instance with all components
     let lb_service = LB {
         load_balancer: upstreams,
         rate_limiter,
         circuit_breaker,
+        failover: Arc::new(failover),
         retry_policy,
+        outlier_detector,
+        bulkhead,
+        adaptive_limiter,
+        load_shedder,
+        bandit,
+        locality,
+        listeners,
+        waf,
+        bot_detector,
+        geoip,
+        latency,
+        secrets,
+        recording: Arc::new(recording),
+        faults: fault_injector,
+        error_pages,
+        key_activity,
+        metrics,
+        clock,
         config: shared_config,
     };
 
     // Create HTTP proxy service that uses our LB policy
     let mut proxy_service = pingora_proxy::http_proxy_service(&server.configuration, lb_service);
-    let listen_addr = "0.0.0.0:6188";
-    proxy_service.add_tcp(listen_addr);
-    info!(event = "listen", addr = listen_addr, "gateway listening");
+    for listener in &listener_configs {
+        match &listener.tls {
+            Some(tls) => proxy_service
+                .add_tls(&listener.addr, &tls.cert_path, &tls.key_path)
+                .expect("add tls listener"),
+            None => proxy_service.add_tcp(&listener.addr),
+        }
+        info!(event = "listen", listener = %listener.name, addr = %listener.addr, tls = listener.tls.is_some(), "gateway listening");
+    }
 
-    // Host proxy service
+    // Host proxy service alongside services registered on the builder
+    server.add_services(services);
     server.add_service(proxy_service);
     server.run_forever();
-}
\ No newline at end of file
+}
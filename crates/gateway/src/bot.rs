@@ -0,0 +1,331 @@
+//! 机器人识别与按客户端指纹限流。
+//! - 打分：UA 缺失或为自动化工具、自称浏览器却缺少浏览器必带的请求头、请求头过少、同一指纹请求过密；
+//! - 指纹：方法 + UA + 请求头名顺序的哈希，与客户端 IP 一起作为限流 key；
+//! - 疑似机器人按租户策略放行、限流、challenge（回传签名 cookie）或拦截；allowlist 中的良性爬虫直接放行。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::config::{BotAction, BotDetectionConfig, BotPolicy};
+use crate::observability::Metrics;
+use crate::rate_limiter::TokenBucket;
+
+/// 通过 challenge 后客户端回传的 cookie
+pub const CHALLENGE_COOKIE: &str = "gw_bot_pass";
+/// 转发给上游的机器人分数
+pub const BOT_SCORE_HEADER: &str = "X-Bot-Score";
+
+const AUTOMATION_AGENTS: &[&str] = &[
+    "curl", "wget", "python", "go-http-client", "java/", "libwww", "httpclient", "scrapy", "okhttp", "axios",
+    "node-fetch", "aiohttp", "phantomjs", "headlesschrome", "selenium", "puppeteer", "playwright", "bot", "crawler",
+    "spider",
+];
+/// 超过该数量时清理过期的窗口计数 / 整体清空限流桶
+const PRUNE_THRESHOLD: usize = 10_000;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotClass {
+    Human,
+    Bot,
+    AllowedBot,
+    /// 携带有效 challenge cookie
+    Verified,
+}
+
+impl BotClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BotClass::Human => "human",
+            BotClass::Bot => "bot",
+            BotClass::AllowedBot => "allowed_bot",
+            BotClass::Verified => "verified",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Throttled,
+    /// 返回 403 并下发该 cookie 值
+    Challenge(String),
+    Blocked,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Pass => "pass",
+            Outcome::Throttled => "throttled",
+            Outcome::Challenge(_) => "challenge",
+            Outcome::Blocked => "blocked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BotVerdict {
+    pub class: BotClass,
+    pub score: u32,
+    pub reasons: Vec<&'static str>,
+    pub fingerprint: String,
+    pub outcome: Outcome,
+}
+
+/// 一次请求中用于识别的部分
+pub struct ClientRequest<'a> {
+    pub client_ip: &'a str,
+    pub method: &'a str,
+    /// 请求头名，按收到的顺序
+    pub header_names: &'a [&'a str],
+}
+
+#[derive(Debug)]
+pub struct BotDetector {
+    cfg: BotDetectionConfig,
+    secret: Vec<u8>,
+    windows: Mutex<HashMap<String, (Instant, u32)>>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    metrics: Arc<Metrics>,
+}
+
+/// 方法 + UA + 请求头名顺序；cookie 不参与，回传 challenge cookie 后指纹不变
+pub fn fingerprint(method: &str, user_agent: Option<&str>, header_names: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(user_agent.unwrap_or_default().as_bytes());
+    for name in header_names.iter().filter(|n| !n.eq_ignore_ascii_case("cookie")) {
+        hasher.update(b"\n");
+        hasher.update(name.to_ascii_lowercase().as_bytes());
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+impl BotDetector {
+    pub fn new(cfg: BotDetectionConfig) -> Self {
+        let secret = match cfg.challenge_secret.as_deref().filter(|s| !s.is_empty()) {
+            Some(s) => s.as_bytes().to_vec(),
+            None => {
+                let mut buf = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut buf);
+                buf
+            }
+        };
+        Self {
+            cfg,
+            secret,
+            windows: Mutex::new(HashMap::new()),
+            buckets: Mutex::new(HashMap::new()),
+            metrics: Metrics::detached(),
+        }
+    }
+
+    /// 分类与处置计数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cfg.enabled
+    }
+
+    pub fn tenant_header(&self) -> &str {
+        &self.cfg.tenant_header
+    }
+
+    fn policy(&self, tenant: Option<&str>) -> &BotPolicy {
+        tenant.and_then(|t| self.cfg.tenant_policies.get(t)).unwrap_or(&self.cfg.default_policy)
+    }
+
+    /// challenge cookie 值：hex(HMAC-SHA256(secret, "{ip}|{fingerprint}"))
+    pub fn challenge_token(&self, client_ip: &str, fingerprint: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("hmac accepts any key length");
+        mac.update(format!("{}|{}", client_ip, fingerprint).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn verify_token(&self, client_ip: &str, fingerprint: &str, token: &str) -> bool {
+        let Ok(sig) = hex::decode(token.trim()) else { return false };
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("hmac accepts any key length");
+        mac.update(format!("{}|{}", client_ip, fingerprint).as_bytes());
+        mac.verify_slice(&sig).is_ok()
+    }
+
+    /// 窗口内计数 +1，返回是否超过上限
+    fn record_hit(&self, key: &str, now: Instant) -> bool {
+        let window = Duration::from_secs(self.cfg.window_secs.max(1));
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() >= PRUNE_THRESHOLD {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < window);
+        }
+        let entry = windows.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= window {
+            *entry = (now, 0);
+        }
+        entry.1 += 1;
+        entry.1 > self.cfg.window_max_requests
+    }
+
+    fn score<'h>(&self, req: &ClientRequest<'_>, key: &str, header: &impl Fn(&str) -> Option<&'h str>) -> (u32, Vec<&'static str>) {
+        let mut score = 0;
+        let mut reasons = Vec::new();
+        let mut add = |points: u32, reason: &'static str| {
+            score += points;
+            reasons.push(reason);
+        };
+        match header("user-agent").map(str::to_ascii_lowercase) {
+            None => add(50, "no_user_agent"),
+            Some(ua) if AUTOMATION_AGENTS.iter().any(|a| ua.contains(a)) => add(40, "automation_user_agent"),
+            Some(ua) if ua.starts_with("mozilla/") => {
+                if header("accept-language").is_none() {
+                    add(25, "missing_accept_language");
+                }
+                if header("accept").is_none() {
+                    add(20, "missing_accept");
+                }
+            }
+            Some(_) => {}
+        }
+        if req.header_names.iter().filter(|n| !n.eq_ignore_ascii_case("cookie")).count() < 3 {
+            add(15, "few_headers");
+        }
+        if self.record_hit(key, Instant::now()) {
+            add(30, "high_request_rate");
+        }
+        (score.min(100), reasons)
+    }
+
+    fn throttle(&self, key: &str, policy: &BotPolicy) -> bool {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_THRESHOLD && !buckets.contains_key(key) {
+            buckets.clear();
+        }
+        let rps = policy.requests_per_second.max(1);
+        !buckets.entry(key.to_string()).or_insert_with(|| TokenBucket::new(policy.burst.max(1), rps)).try_acquire(1)
+    }
+
+    pub fn inspect<'h>(&self, req: &ClientRequest<'_>, header: impl Fn(&str) -> Option<&'h str>) -> BotVerdict {
+        let user_agent = header("user-agent");
+        let fp = fingerprint(req.method, user_agent, req.header_names);
+        let key = format!("{}|{}", req.client_ip, fp);
+        let verdict = |class, score, reasons, outcome| BotVerdict { class, score, reasons, fingerprint: fp.clone(), outcome };
+
+        let allowed = user_agent
+            .map(str::to_ascii_lowercase)
+            .is_some_and(|ua| self.cfg.allowlist.iter().any(|a| ua.contains(&a.to_ascii_lowercase())));
+        let result = if allowed {
+            verdict(BotClass::AllowedBot, 0, Vec::new(), Outcome::Pass)
+        } else {
+            let (score, reasons) = self.score(req, &key, &header);
+            let passed = cookie(header("cookie"), CHALLENGE_COOKIE).is_some_and(|t| self.verify_token(req.client_ip, &fp, t));
+            if score < self.cfg.score_threshold {
+                verdict(BotClass::Human, score, reasons, Outcome::Pass)
+            } else if passed {
+                verdict(BotClass::Verified, score, reasons, Outcome::Pass)
+            } else {
+                let policy = self.policy(header(&self.cfg.tenant_header));
+                let outcome = match policy.action {
+                    BotAction::Allow => Outcome::Pass,
+                    BotAction::Throttle if self.throttle(&key, policy) => Outcome::Throttled,
+                    BotAction::Throttle => Outcome::Pass,
+                    BotAction::Challenge => Outcome::Challenge(self.challenge_token(req.client_ip, &fp)),
+                    BotAction::Block => Outcome::Blocked,
+                };
+                verdict(BotClass::Bot, score, reasons, outcome)
+            }
+        };
+        self.metrics.bot_requests_total.with_label_values(&[result.class.as_str(), result.outcome.as_str()]).inc();
+        result
+    }
+}
+
+fn cookie<'a>(header: Option<&'a str>, name: &str) -> Option<&'a str> {
+    header?.split(';').filter_map(|kv| kv.trim().split_once('=')).find(|(k, _)| *k == name).map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROWSER: &[(&str, &str)] = &[
+        ("host", "api.example.com"),
+        ("user-agent", "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0"),
+        ("accept", "*/*"),
+        ("accept-language", "en-US"),
+    ];
+
+    fn inspect(d: &BotDetector, headers: &[(&str, &str)]) -> BotVerdict {
+        let names: Vec<&str> = headers.iter().map(|(k, _)| *k).collect();
+        let req = ClientRequest { client_ip: "10.0.0.1", method: "GET", header_names: &names };
+        d.inspect(&req, |name| headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| *v))
+    }
+
+    fn detector(action: BotAction) -> BotDetector {
+        BotDetector::new(BotDetectionConfig {
+            enabled: true,
+            default_policy: BotPolicy { action, requests_per_second: 1, burst: 1 },
+            challenge_secret: Some("s3cret".into()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn classifies_browsers_tools_and_allowlisted_agents() {
+        let d = detector(BotAction::Allow);
+        let human = inspect(&d, BROWSER);
+        assert_eq!((human.class, human.score), (BotClass::Human, 0));
+
+        let tool = inspect(&d, &[("host", "x"), ("user-agent", "python-requests/2.31")]);
+        assert_eq!(tool.class, BotClass::Bot);
+        assert_eq!(tool.reasons, vec!["automation_user_agent", "few_headers"]);
+
+        let fake_browser = inspect(&d, &[("host", "x"), ("user-agent", "Mozilla/5.0"), ("connection", "close")]);
+        assert_eq!(fake_browser.score, 45);
+        assert_eq!(inspect(&d, &[("user-agent", "Mozilla/5.0 (compatible; Googlebot/2.1)")]).class, BotClass::AllowedBot);
+
+        // 请求头顺序不同，指纹不同
+        let reordered: Vec<(&str, &str)> = BROWSER.iter().rev().cloned().collect();
+        assert_ne!(inspect(&d, &reordered).fingerprint, human.fingerprint);
+    }
+
+    #[test]
+    fn policies_throttle_challenge_and_block() {
+        let bot = &[("host", "x"), ("user-agent", "curl/8.0")];
+        let d = detector(BotAction::Throttle);
+        assert_eq!(inspect(&d, bot).outcome, Outcome::Pass);
+        assert_eq!(inspect(&d, bot).outcome, Outcome::Throttled);
+        assert_eq!(inspect(&d, BROWSER).outcome, Outcome::Pass);
+
+        let d = detector(BotAction::Challenge);
+        let Outcome::Challenge(token) = inspect(&d, bot).outcome else { panic!("expected challenge") };
+        let cookie = format!("a=b; {}={}", CHALLENGE_COOKIE, token);
+        let verified = inspect(&d, &[bot[0], bot[1], ("cookie", cookie.as_str())]);
+        assert_eq!((verified.class, verified.outcome), (BotClass::Verified, Outcome::Pass));
+        let forged = format!("{}=00ff", CHALLENGE_COOKIE);
+        assert!(matches!(inspect(&d, &[bot[0], bot[1], ("cookie", forged.as_str())]).outcome, Outcome::Challenge(_)));
+
+        let mut cfg = BotDetectionConfig { enabled: true, ..Default::default() };
+        cfg.tenant_policies.insert("acme".into(), BotPolicy { action: BotAction::Block, ..Default::default() });
+        let d = BotDetector::new(cfg);
+        assert_eq!(inspect(&d, &[("x-tenant-id", "acme"), ("user-agent", "curl/8.0")]).outcome, Outcome::Blocked);
+        assert_eq!(inspect(&d, &[("x-tenant-id", "other"), ("user-agent", "curl/8.0")]).outcome, Outcome::Pass);
+    }
+
+    #[test]
+    fn dense_requests_raise_the_score() {
+        let d = BotDetector::new(BotDetectionConfig { enabled: true, window_max_requests: 2, ..Default::default() });
+        assert_eq!(inspect(&d, BROWSER).score, 0);
+        assert_eq!(inspect(&d, BROWSER).score, 0);
+        let third = inspect(&d, BROWSER);
+        assert_eq!((third.score, third.reasons), (30, vec!["high_request_rate"]));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use crate::observability::Metrics;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkheadError {
+    /// 并发已满且等待队列已满
+    QueueFull,
+    /// 排队超时
+    Timeout,
+    /// 超出自适应并发上限
+    LimitExceeded,
+}
+
+impl std::fmt::Display for BulkheadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BulkheadError::QueueFull => write!(f, "bulkhead queue full"),
+            BulkheadError::Timeout => write!(f, "bulkhead queue timeout"),
+            BulkheadError::LimitExceeded => write!(f, "adaptive concurrency limit reached"),
+        }
+    }
+}
+
+impl std::error::Error for BulkheadError {}
+
+/// 单个上游的并发隔离舱
+#[derive(Debug)]
+struct Partition {
+    semaphore: Arc<Semaphore>,
+    waiting: AtomicUsize,
+}
+
+/// 持有期间占用一个并发名额，Drop 时释放
+#[derive(Debug)]
+pub struct BulkheadPermit {
+    upstream: String,
+    metrics: Arc<Metrics>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for BulkheadPermit {
+    fn drop(&mut self) {
+        self.metrics.bulkhead_in_flight.with_label_values(&[&self.upstream]).dec();
+    }
+}
+
+/// 按上游划分的并发限制（bulkhead）：
+/// 每个上游最多 max_in_flight 个在途请求，超出后最多 max_queue 个请求排队等待 queue_timeout。
+#[derive(Clone)]
+pub struct Bulkhead {
+    partitions: Arc<Mutex<HashMap<String, Arc<Partition>>>>,
+    max_in_flight: usize,
+    overrides: HashMap<String, usize>,
+    max_queue: usize,
+    queue_timeout: Duration,
+    enabled: bool,
+    metrics: Arc<Metrics>,
+}
+
+impl Bulkhead {
+    pub fn new(
+        max_in_flight: usize,
+        max_queue: usize,
+        queue_timeout: Duration,
+        overrides: HashMap<String, usize>,
+        enabled: bool,
+    ) -> Self {
+        Self {
+            partitions: Arc::new(Mutex::new(HashMap::new())),
+            max_in_flight: max_in_flight.max(1),
+            overrides,
+            max_queue,
+            queue_timeout,
+            enabled,
+            metrics: Metrics::detached(),
+        }
+    }
+
+    /// 在途与拒绝计数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    fn partition(&self, upstream: &str) -> Arc<Partition> {
+        let mut map = self.partitions.lock().expect("bulkhead lock");
+        map.entry(upstream.to_string())
+            .or_insert_with(|| {
+                let limit = self.overrides.get(upstream).copied().unwrap_or(self.max_in_flight).max(1);
+                Arc::new(Partition { semaphore: Arc::new(Semaphore::new(limit)), waiting: AtomicUsize::new(0) })
+            })
+            .clone()
+    }
+
+    /// 获取上游的并发名额；未启用时返回 None
+    pub async fn acquire(&self, upstream: &str) -> Result<Option<BulkheadPermit>, BulkheadError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let partition = self.partition(upstream);
+        let permit = match partition.semaphore.clone().try_acquire_owned() {
+            Ok(p) => p,
+            Err(_) => {
+                if partition.waiting.fetch_add(1, Ordering::AcqRel) >= self.max_queue {
+                    partition.waiting.fetch_sub(1, Ordering::AcqRel);
+                    self.metrics.bulkhead_rejected_total.with_label_values(&[upstream, "queue_full"]).inc();
+                    return Err(BulkheadError::QueueFull);
+                }
+                debug!(upstream = %upstream, "bulkhead saturated, queueing request");
+                let res = tokio::time::timeout(self.queue_timeout, partition.semaphore.clone().acquire_owned()).await;
+                partition.waiting.fetch_sub(1, Ordering::AcqRel);
+                match res {
+                    Ok(Ok(p)) => p,
+                    _ => {
+                        self.metrics.bulkhead_rejected_total.with_label_values(&[upstream, "timeout"]).inc();
+                        return Err(BulkheadError::Timeout);
+                    }
+                }
+            }
+        };
+        self.metrics.bulkhead_in_flight.with_label_values(&[upstream]).inc();
+        Ok(Some(BulkheadPermit { upstream: upstream.to_string(), metrics: Arc::clone(&self.metrics), _permit: permit }))
+    }
+
+    /// 当前可用名额（用于测试与诊断）
+    pub fn available(&self, upstream: &str) -> usize {
+        self.partition(upstream).semaphore.available_permits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bulkhead_limits_in_flight() {
+        let bh = Bulkhead::new(2, 0, Duration::from_millis(10), HashMap::new(), true);
+
+        let p1 = bh.acquire("a").await.unwrap();
+        let _p2 = bh.acquire("a").await.unwrap();
+        assert_eq!(bh.acquire("a").await.unwrap_err(), BulkheadError::QueueFull);
+        // 其他上游不受影响
+        assert!(bh.acquire("b").await.is_ok());
+
+        drop(p1);
+        assert!(bh.acquire("a").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_queue_timeout() {
+        let bh = Bulkhead::new(1, 1, Duration::from_millis(20), HashMap::new(), true);
+        let _p1 = bh.acquire("a").await.unwrap();
+        assert_eq!(bh.acquire("a").await.unwrap_err(), BulkheadError::Timeout);
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_queued_request_gets_released_permit() {
+        let bh = Bulkhead::new(1, 1, Duration::from_millis(500), HashMap::new(), true);
+        let p1 = bh.acquire("a").await.unwrap();
+        let bh2 = bh.clone();
+        let waiter = tokio::spawn(async move { bh2.acquire("a").await.map(|p| p.is_some()) });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(p1);
+        assert_eq!(waiter.await.unwrap(), Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_override_and_disabled() {
+        let overrides = HashMap::from([("slow".to_string(), 1)]);
+        let bh = Bulkhead::new(10, 0, Duration::from_millis(10), overrides, true);
+        let _p = bh.acquire("slow").await.unwrap();
+        assert!(bh.acquire("slow").await.is_err());
+        assert_eq!(bh.available("fast"), 10);
+
+        let off = Bulkhead::new(1, 0, Duration::from_millis(10), HashMap::new(), false);
+        for _ in 0..5 {
+            assert!(off.acquire("a").await.unwrap().is_none());
+        }
+    }
+}
@@ -1,8 +1,14 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, info, warn};
 
+use crate::observability::Metrics;
+
+/// 未指定上游时指标中的 upstream 标签（网关对整个上游池共用一个熔断器）
+pub const DEFAULT_UPSTREAM: &str = "default";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CircuitState {
     Closed,   // Normal operation
@@ -10,114 +16,318 @@ pub enum CircuitState {
     HalfOpen, // Testing if service has recovered
 }
 
+const CLOSED: u64 = 0;
+const OPEN: u64 = 1;
+const HALF_OPEN: u64 = 2;
+const STATE_BITS: u64 = 2;
+const STATE_MASK: u64 = (1 << STATE_BITS) - 1;
+
+fn state_name(state: u64) -> &'static str {
+    match state {
+        CLOSED => "closed",
+        OPEN => "open",
+        _ => "half_open",
+    }
+}
+
+/// 状态与进入该状态的时刻（相对 `origin` 的毫秒）打包在一个 u64 中，状态迁移用 CAS 完成，
+/// 并发请求中只有一个赢得迁移并负责重置计数与记录日志。
+fn pack(state: u64, at_ms: u64) -> u64 {
+    (at_ms << STATE_BITS) | state
+}
+
+fn unpack(word: u64) -> (u64, u64) {
+    (word & STATE_MASK, word >> STATE_BITS)
+}
+
+/// 无锁熔断器状态；计数均为原子量，热路径上只有 load 与少量 fetch_add
 #[derive(Debug)]
 pub struct CircuitBreakerInner {
-    state: CircuitState,
-    failure_count: u64,
-    success_count: u64,
-    last_failure_time: Option<Instant>,
+    state: AtomicU64,
+    /// Closed 下的连续失败数
+    failure_count: AtomicU64,
+    /// HalfOpen 下的成功数
+    success_count: AtomicU64,
     failure_threshold: u64,
     recovery_timeout: Duration,
     half_open_max_calls: u64,
+    origin: Instant,
+    /// 指标标签
+    upstream: String,
+    metrics: Arc<Metrics>,
 }
 
 impl CircuitBreakerInner {
     pub fn new(failure_threshold: u64, recovery_timeout: Duration, half_open_max_calls: u64) -> Self {
+        Self::for_upstream(DEFAULT_UPSTREAM, failure_threshold, recovery_timeout, half_open_max_calls)
+    }
+
+    pub fn for_upstream(upstream: impl Into<String>, failure_threshold: u64, recovery_timeout: Duration, half_open_max_calls: u64) -> Self {
         Self {
-            state: CircuitState::Closed,
-            failure_count: 0,
-            success_count: 0,
-            last_failure_time: None,
+            state: AtomicU64::new(pack(CLOSED, 0)),
+            failure_count: AtomicU64::new(0),
+            success_count: AtomicU64::new(0),
             failure_threshold,
             recovery_timeout,
             half_open_max_calls,
+            origin: Instant::now(),
+            upstream: upstream.into(),
+            metrics: Metrics::detached(),
         }
     }
 
-    pub fn can_execute(&mut self) -> bool {
-        match self.state {
-            CircuitState::Closed => true,
-            CircuitState::Open => {
-                if let Some(last_failure) = self.last_failure_time {
-                    if last_failure.elapsed() >= self.recovery_timeout {
-                        info!("Circuit breaker transitioning to half-open state");
-                        self.state = CircuitState::HalfOpen;
-                        self.success_count = 0;
-                        true
-                    } else {
+    fn now_ms(&self) -> u64 {
+        self.origin.elapsed().as_millis() as u64
+    }
+
+    /// 从 `from` 迁移到 `to`；返回是否由本调用完成迁移（只有完成迁移的调用更新指标）
+    fn transition(&self, from: u64, to: u64) -> bool {
+        let won = self.state.compare_exchange(from, pack(to, self.now_ms()), Ordering::AcqRel, Ordering::Acquire).is_ok();
+        let from = unpack(from).0;
+        // Open 下的失败会刷新打开时刻（Open -> Open），不算状态变化
+        if won && from != to {
+            self.metrics.circuit_breaker_state.with_label_values(&[&self.upstream]).set(to as i64);
+            self.metrics.circuit_breaker_transitions_total.with_label_values(&[&self.upstream, state_name(from), state_name(to)]).inc();
+        }
+        won
+    }
+
+    fn reject(&self) -> bool {
+        self.metrics.circuit_breaker_rejected_total.with_label_values(&[&self.upstream]).inc();
+        false
+    }
+
+    pub fn can_execute(&self) -> bool {
+        loop {
+            let word = self.state.load(Ordering::Acquire);
+            match unpack(word) {
+                (CLOSED, _) => return true,
+                (OPEN, opened_at) => {
+                    if self.now_ms().saturating_sub(opened_at) < self.recovery_timeout.as_millis() as u64 {
                         debug!("Circuit breaker is open, rejecting request");
-                        false
+                        return self.reject();
+                    }
+                    if self.transition(word, HALF_OPEN) {
+                        info!("Circuit breaker transitioning to half-open state");
+                        self.success_count.store(0, Ordering::Release);
+                        return true;
                     }
-                } else {
-                    false
+                    // 其他请求已完成迁移，按新状态重新判断
+                }
+                _ => {
+                    if self.half_open_has_capacity() {
+                        return true;
+                    }
+                    return self.reject();
                 }
-            }
-            CircuitState::HalfOpen => {
-                self.success_count < self.half_open_max_calls
             }
         }
     }
 
-    pub fn record_success(&mut self) {
-        match self.state {
-            CircuitState::Closed => {
-                self.failure_count = 0;
+    /// half-open 下是否还有试探名额（只读，供预览）
+    fn half_open_has_capacity(&self) -> bool {
+        self.success_count.load(Ordering::Acquire) < self.half_open_max_calls
+    }
+
+    /// Open 时距离允许试探（half-open）的剩余时间；其他状态为 0
+    pub fn retry_after(&self) -> Duration {
+        match unpack(self.state.load(Ordering::Acquire)) {
+            (OPEN, opened_at) => self.recovery_timeout.saturating_sub(Duration::from_millis(self.now_ms().saturating_sub(opened_at))),
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn record_success(&self) {
+        let word = self.state.load(Ordering::Acquire);
+        match unpack(word).0 {
+            CLOSED => {
+                // 避免每个成功请求都写共享缓存行
+                if self.failure_count.load(Ordering::Relaxed) != 0 {
+                    self.failure_count.store(0, Ordering::Release);
+                }
             }
-            CircuitState::HalfOpen => {
-                self.success_count += 1;
-                if self.success_count >= self.half_open_max_calls {
+            HALF_OPEN => {
+                let successes = self.success_count.fetch_add(1, Ordering::AcqRel) + 1;
+                if successes >= self.half_open_max_calls && self.transition(word, CLOSED) {
                     info!("Circuit breaker closing after successful recovery");
-                    self.state = CircuitState::Closed;
-                    self.failure_count = 0;
-                    self.success_count = 0;
-                    self.last_failure_time = None;
+                    self.reset_counts();
                 }
             }
-            CircuitState::Open => {
+            _ => {
                 // Should not happen, but reset if it does
-                self.state = CircuitState::Closed;
-                self.failure_count = 0;
-                self.success_count = 0;
-                self.last_failure_time = None;
+                if self.transition(word, CLOSED) {
+                    self.reset_counts();
+                }
             }
         }
-        debug!("Circuit breaker recorded success, state: {:?}", self.state);
+        debug!("Circuit breaker recorded success, state: {:?}", self.get_state());
     }
 
-    pub fn record_failure(&mut self) {
-        match self.state {
-            CircuitState::Closed => {
-                self.failure_count += 1;
-                if self.failure_count >= self.failure_threshold {
-                    warn!("Circuit breaker opening due to {} failures", self.failure_count);
-                    self.state = CircuitState::Open;
-                    self.last_failure_time = Some(Instant::now());
+    pub fn record_failure(&self) {
+        let failures = self.failure_count.fetch_add(1, Ordering::AcqRel) + 1;
+        loop {
+            let word = self.state.load(Ordering::Acquire);
+            match unpack(word).0 {
+                CLOSED => {
+                    if failures >= self.failure_threshold && self.transition(word, OPEN) {
+                        warn!("Circuit breaker opening due to {} failures", failures);
+                    }
+                }
+                HALF_OPEN => {
+                    if !self.transition(word, OPEN) {
+                        continue;
+                    }
+                    warn!("Circuit breaker opening again after failure in half-open state");
+                    self.success_count.store(0, Ordering::Release);
+                }
+                _ => {
+                    // 刷新打开时刻，重新计算恢复等待
+                    if !self.transition(word, OPEN) {
+                        continue;
+                    }
                 }
             }
-            CircuitState::HalfOpen => {
-                warn!("Circuit breaker opening again after failure in half-open state");
-                self.state = CircuitState::Open;
-                self.failure_count += 1;
-                self.last_failure_time = Some(Instant::now());
-                self.success_count = 0;
+            break;
+        }
+        debug!("Circuit breaker recorded failure, state: {:?}, count: {}", self.get_state(), failures);
+    }
+
+    /// 其他实例报告该上游熔断：Closed / HalfOpen 直接打开；已打开时不刷新打开时刻
+    pub fn trip(&self) -> bool {
+        loop {
+            let word = self.state.load(Ordering::Acquire);
+            if unpack(word).0 == OPEN {
+                return false;
             }
-            CircuitState::Open => {
-                self.failure_count += 1;
-                self.last_failure_time = Some(Instant::now());
+            if self.transition(word, OPEN) {
+                warn!(upstream = %self.upstream, "Circuit breaker opened by a cluster peer");
+                self.success_count.store(0, Ordering::Release);
+                return true;
             }
         }
-        debug!("Circuit breaker recorded failure, state: {:?}, count: {}", self.state, self.failure_count);
+    }
+
+    fn reset_counts(&self) {
+        self.failure_count.store(0, Ordering::Release);
+        self.success_count.store(0, Ordering::Release);
     }
 
     pub fn get_state(&self) -> CircuitState {
-        self.state.clone()
+        match unpack(self.state.load(Ordering::Acquire)).0 {
+            CLOSED => CircuitState::Closed,
+            OPEN => CircuitState::Open,
+            _ => CircuitState::HalfOpen,
+        }
+    }
+}
+
+/// 排队请求检查熔断器状态的间隔
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// [`CircuitBreaker::admit`] 拒绝的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdmitError {
+    /// 熔断器打开
+    Open,
+    /// 试探名额已满且等待队列已满
+    QueueFull,
+    /// 排队超时
+    Timeout,
+    /// 排队期间试探失败，熔断器重新打开
+    Reopened,
+}
+
+impl AdmitError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdmitError::Open => "open",
+            AdmitError::QueueFull => "queue_full",
+            AdmitError::Timeout => "timeout",
+            AdmitError::Reopened => "reopened",
+        }
+    }
+}
+
+/// half-open 试探名额，请求结束（ctx 释放）时归还
+#[derive(Debug)]
+pub struct ProbePermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// half-open 期间的排队：最多 `half_open_max_calls` 个试探请求同时在途，
+/// 其余请求最多 `capacity` 个等待 `timeout`，等到试探名额或熔断器关闭后放行。
+#[derive(Debug)]
+struct HalfOpenQueue {
+    probes: Arc<Semaphore>,
+    capacity: usize,
+    depth: AtomicUsize,
+    timeout: Duration,
+}
+
+impl HalfOpenQueue {
+    async fn admit(&self, breaker: &CircuitBreakerInner) -> Result<Option<ProbePermit>, AdmitError> {
+        if let Ok(permit) = self.probes.clone().try_acquire_owned() {
+            return self.record(breaker, Ok(Some(ProbePermit { _permit: permit })));
+        }
+        if self.depth.fetch_add(1, Ordering::AcqRel) >= self.capacity {
+            self.depth.fetch_sub(1, Ordering::AcqRel);
+            return self.record(breaker, Err(AdmitError::QueueFull));
+        }
+        let depth = breaker.metrics.circuit_breaker_queue_depth.with_label_values(&[&breaker.upstream]);
+        depth.inc();
+        debug!(upstream = %breaker.upstream, "Circuit breaker half-open, queueing request");
+        let result = self.wait(breaker).await;
+        depth.dec();
+        self.depth.fetch_sub(1, Ordering::AcqRel);
+        self.record(breaker, result)
+    }
+
+    /// 只读判定：有空闲试探名额或队列未满时会被接纳
+    fn preview(&self) -> Result<(), AdmitError> {
+        if self.probes.available_permits() > 0 || self.depth.load(Ordering::Acquire) < self.capacity {
+            Ok(())
+        } else {
+            Err(AdmitError::QueueFull)
+        }
+    }
+
+    async fn wait(&self, breaker: &CircuitBreakerInner) -> Result<Option<ProbePermit>, AdmitError> {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(AdmitError::Timeout);
+            }
+            let permit = tokio::time::timeout(remaining.min(QUEUE_POLL_INTERVAL), self.probes.clone().acquire_owned()).await;
+            // 等待期间试探可能已让熔断器关闭或重新打开
+            match (breaker.get_state(), permit) {
+                (CircuitState::Closed, _) => return Ok(None),
+                (CircuitState::Open, _) => return Err(AdmitError::Reopened),
+                (CircuitState::HalfOpen, Ok(Ok(permit))) => return Ok(Some(ProbePermit { _permit: permit })),
+                (CircuitState::HalfOpen, _) => {}
+            }
+        }
+    }
+
+    fn record(&self, breaker: &CircuitBreakerInner, result: Result<Option<ProbePermit>, AdmitError>) -> Result<Option<ProbePermit>, AdmitError> {
+        let label = match &result {
+            Ok(Some(_)) => "probe",
+            Ok(None) => "closed",
+            Err(err) => {
+                breaker.metrics.circuit_breaker_rejected_total.with_label_values(&[&breaker.upstream]).inc();
+                err.as_str()
+            }
+        };
+        breaker.metrics.circuit_breaker_queued_total.with_label_values(&[&breaker.upstream, label]).inc();
+        result
     }
 }
 
 #[derive(Clone)]
 pub struct CircuitBreaker {
-    inner: Arc<Mutex<CircuitBreakerInner>>,
+    inner: Arc<CircuitBreakerInner>,
     enabled: bool,
+    queue: Option<Arc<HalfOpenQueue>>,
 }
 
 impl CircuitBreaker {
@@ -126,51 +336,122 @@ impl CircuitBreaker {
         recovery_timeout: Duration,
         half_open_max_calls: u64,
         enabled: bool,
+    ) -> Self {
+        Self::for_upstream(DEFAULT_UPSTREAM, failure_threshold, recovery_timeout, half_open_max_calls, enabled)
+    }
+
+    /// 指标按 `upstream` 标签导出；启用时状态 gauge 立即置为 closed(0)
+    pub fn for_upstream(
+        upstream: &str,
+        failure_threshold: u64,
+        recovery_timeout: Duration,
+        half_open_max_calls: u64,
+        enabled: bool,
     ) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(CircuitBreakerInner::new(
+            inner: Arc::new(CircuitBreakerInner::for_upstream(
+                upstream,
                 failure_threshold,
                 recovery_timeout,
                 half_open_max_calls,
-            ))),
+            )),
             enabled,
+            queue: None,
         }
+        .with_metrics(Metrics::detached())
     }
 
-    pub async fn can_execute(&self) -> bool {
+    /// 状态、迁移与拒绝计数记录到注入的实例；须在熔断器被 clone 共享之前调用
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        if self.enabled {
+            metrics.circuit_breaker_state.with_label_values(&[&self.inner.upstream]).set(CLOSED as i64);
+        }
+        Arc::get_mut(&mut self.inner).expect("with_metrics is called before the breaker is shared").metrics = metrics;
+        self
+    }
+
+    /// half-open 时限制试探并发并让最多 `capacity` 个请求排队等待 `timeout`，而不是全部放行或拒绝；
+    /// `capacity` 为 0 时不排队（默认行为）
+    pub fn with_half_open_queue(mut self, capacity: usize, timeout: Duration) -> Self {
+        self.queue = (capacity > 0).then(|| {
+            Arc::new(HalfOpenQueue {
+                probes: Arc::new(Semaphore::new(self.inner.half_open_max_calls.max(1) as usize)),
+                capacity,
+                depth: AtomicUsize::new(0),
+                timeout,
+            })
+        });
+        self
+    }
+
+    /// 请求准入：未配置 half-open 队列时等同 [`Self::can_execute`]；配置后 half-open 期间
+    /// 试探请求取得 [`ProbePermit`]，其余请求排队等待，队列满或超时即拒绝
+    pub async fn admit(&self) -> Result<Option<ProbePermit>, AdmitError> {
+        let allowed = self.can_execute();
+        match &self.queue {
+            Some(queue) if self.enabled && self.inner.get_state() == CircuitState::HalfOpen => queue.admit(&self.inner).await,
+            _ if allowed => Ok(None),
+            _ => Err(AdmitError::Open),
+        }
+    }
+
+    /// 同 [`Self::admit`] 的判定，但不迁移状态、不占用试探名额或队列位置、不计入指标（供 dry-run 预览）；
+    /// 恢复等待已过的 Open 视为可放行，因为下一个请求会把熔断器迁移到 half-open 并成为试探
+    pub fn preview(&self) -> Result<(), AdmitError> {
         if !self.enabled {
-            return true;
+            return Ok(());
+        }
+        match (self.inner.get_state(), &self.queue) {
+            (CircuitState::Closed, _) => Ok(()),
+            (CircuitState::Open, _) if self.inner.retry_after().is_zero() => Ok(()),
+            (CircuitState::Open, _) => Err(AdmitError::Open),
+            (CircuitState::HalfOpen, Some(queue)) => queue.preview(),
+            (CircuitState::HalfOpen, None) if self.inner.half_open_has_capacity() => Ok(()),
+            (CircuitState::HalfOpen, None) => Err(AdmitError::Open),
         }
+    }
 
-        let mut inner = self.inner.lock().await;
-        inner.can_execute()
+    pub fn can_execute(&self) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.inner.can_execute()
     }
 
-    pub async fn record_success(&self) {
+    pub fn record_success(&self) {
         if !self.enabled {
             return;
         }
-
-        let mut inner = self.inner.lock().await;
-        inner.record_success();
+        self.inner.record_success();
     }
 
-    pub async fn record_failure(&self) {
+    pub fn record_failure(&self) {
         if !self.enabled {
             return;
         }
+        self.inner.record_failure();
+    }
 
-        let mut inner = self.inner.lock().await;
-        inner.record_failure();
+    /// 见 [`CircuitBreakerInner::trip`]；返回是否由本调用打开
+    pub fn trip(&self) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        self.inner.trip()
     }
 
-    pub async fn get_state(&self) -> CircuitState {
+    pub fn get_state(&self) -> CircuitState {
         if !self.enabled {
             return CircuitState::Closed;
         }
+        self.inner.get_state()
+    }
 
-        let inner = self.inner.lock().await;
-        inner.get_state()
+    pub fn retry_after(&self) -> Duration {
+        if !self.enabled {
+            return Duration::ZERO;
+        }
+        self.inner.retry_after()
     }
 }
 
@@ -184,17 +465,18 @@ mod tests {
         let cb = CircuitBreaker::new(3, Duration::from_millis(100), 2, true);
         
         // Should be closed initially
-        assert!(cb.can_execute().await);
-        assert_eq!(cb.get_state().await, CircuitState::Closed);
+        assert!(cb.can_execute());
+        assert_eq!(cb.get_state(), CircuitState::Closed);
         
         // Record failures
-        cb.record_failure().await;
-        cb.record_failure().await;
-        assert!(cb.can_execute().await); // Still closed
+        cb.record_failure();
+        cb.record_failure();
+        assert!(cb.can_execute()); // Still closed
         
-        cb.record_failure().await;
-        assert_eq!(cb.get_state().await, CircuitState::Open);
-        assert!(!cb.can_execute().await); // Now open
+        cb.record_failure();
+        assert_eq!(cb.get_state(), CircuitState::Open);
+        assert!(!cb.can_execute()); // Now open
+        assert!(cb.retry_after() > Duration::ZERO && cb.retry_after() <= Duration::from_millis(100));
     }
 
     #[tokio::test]
@@ -202,20 +484,20 @@ mod tests {
         let cb = CircuitBreaker::new(2, Duration::from_millis(50), 1, true);
         
         // Open the circuit
-        cb.record_failure().await;
-        cb.record_failure().await;
-        assert_eq!(cb.get_state().await, CircuitState::Open);
+        cb.record_failure();
+        cb.record_failure();
+        assert_eq!(cb.get_state(), CircuitState::Open);
         
         // Wait for recovery timeout
         sleep(Duration::from_millis(60)).await;
         
         // Should transition to half-open
-        assert!(cb.can_execute().await);
-        assert_eq!(cb.get_state().await, CircuitState::HalfOpen);
+        assert!(cb.can_execute());
+        assert_eq!(cb.get_state(), CircuitState::HalfOpen);
         
         // Record success to close
-        cb.record_success().await;
-        assert_eq!(cb.get_state().await, CircuitState::Closed);
+        cb.record_success();
+        assert_eq!(cb.get_state(), CircuitState::Closed);
     }
 
     #[tokio::test]
@@ -224,10 +506,155 @@ mod tests {
         
         // Should always allow when disabled
         for _ in 0..10 {
-            cb.record_failure().await;
-            assert!(cb.can_execute().await);
+            cb.record_failure();
+            assert!(cb.can_execute());
         }
         
-        assert_eq!(cb.get_state().await, CircuitState::Closed);
+        assert_eq!(cb.get_state(), CircuitState::Closed);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_circuit_breaker_concurrent_failures_open() {
+        let cb = CircuitBreaker::new(100, Duration::from_secs(60), 1, true);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        if cb.can_execute() {
+                            cb.record_failure();
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(cb.get_state(), CircuitState::Open);
+        assert!(!cb.can_execute());
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_metrics_per_upstream() {
+        let upstream = "metrics-test:1";
+        let metrics = Metrics::detached();
+        let state = || metrics.circuit_breaker_state.with_label_values(&[upstream]).get();
+        let transitions = |from, to| metrics.circuit_breaker_transitions_total.with_label_values(&[upstream, from, to]).get();
+        let rejected = || metrics.circuit_breaker_rejected_total.with_label_values(&[upstream]).get();
+
+        let cb = CircuitBreaker::for_upstream(upstream, 1, Duration::from_millis(20), 1, true).with_metrics(Arc::clone(&metrics));
+        assert_eq!(state(), 0);
+        cb.record_failure();
+        assert_eq!((state(), transitions("closed", "open")), (1, 1));
+        assert!(!cb.can_execute());
+        assert_eq!(rejected(), 1);
+
+        sleep(Duration::from_millis(30)).await;
+        assert!(cb.can_execute());
+        assert_eq!((state(), transitions("open", "half_open")), (2, 1));
+        cb.record_success();
+        assert_eq!((state(), transitions("half_open", "closed")), (0, 1));
+        assert_eq!(rejected(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_half_open_failure_reopens() {
+        let cb = CircuitBreaker::new(1, Duration::from_millis(20), 2, true);
+        cb.record_failure();
+        sleep(Duration::from_millis(30)).await;
+        assert!(cb.can_execute());
+        cb.record_success();
+        assert_eq!(cb.get_state(), CircuitState::HalfOpen);
+        cb.record_failure();
+        assert_eq!(cb.get_state(), CircuitState::Open);
+        assert!(!cb.can_execute());
+    }
+
+    #[test]
+    fn test_circuit_breaker_trip_from_peer() {
+        let cb = CircuitBreaker::for_upstream("peer-trip", 5, Duration::from_secs(30), 1, true);
+        assert!(cb.trip());
+        assert_eq!(cb.get_state(), CircuitState::Open);
+        assert!(!cb.can_execute());
+        // 已打开时不重复计入
+        assert!(!cb.trip());
+        assert!(!CircuitBreaker::new(5, Duration::from_secs(30), 1, false).trip());
+    }
+
+    #[tokio::test]
+    async fn test_half_open_queue_trickles_probes() {
+        let upstream = "half-open-queue";
+        let metrics = Metrics::detached();
+        let depth = || metrics.circuit_breaker_queue_depth.with_label_values(&[upstream]).get();
+        let queued_total = |label| metrics.circuit_breaker_queued_total.with_label_values(&[upstream, label]).get();
+        let cb = CircuitBreaker::for_upstream(upstream, 1, Duration::from_millis(20), 1, true)
+            .with_metrics(Arc::clone(&metrics))
+            .with_half_open_queue(1, Duration::from_millis(200));
+        cb.record_failure();
+        assert_eq!(cb.admit().await.unwrap_err(), AdmitError::Open);
+        sleep(Duration::from_millis(30)).await;
+
+        // 第一个请求成为试探，第二个排队，第三个因队列已满被拒绝
+        let probe = cb.admit().await.unwrap();
+        assert!(probe.is_some());
+        let queued = tokio::spawn({
+            let cb = cb.clone();
+            async move { cb.admit().await.map(|p| p.is_some()) }
+        });
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(depth(), 1);
+        assert_eq!(cb.admit().await.unwrap_err(), AdmitError::QueueFull);
+
+        // 试探成功关闭熔断器，排队请求直接放行
+        cb.record_success();
+        drop(probe);
+        assert_eq!(queued.await.unwrap(), Ok(false));
+        assert_eq!(depth(), 0);
+        assert_eq!((queued_total("probe"), queued_total("closed"), queued_total("queue_full")), (1, 1, 1));
+        assert!(cb.admit().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_half_open_queue_timeout_and_reopen() {
+        let cb = CircuitBreaker::new(1, Duration::from_millis(20), 1, true).with_half_open_queue(4, Duration::from_millis(30));
+        cb.record_failure();
+        sleep(Duration::from_millis(30)).await;
+        let probe = cb.admit().await.unwrap();
+        assert!(probe.is_some());
+        assert_eq!(cb.admit().await.unwrap_err(), AdmitError::Timeout);
+
+        let queued = tokio::spawn({
+            let cb = cb.clone();
+            async move { cb.admit().await.map(|p| p.is_some()) }
+        });
+        sleep(Duration::from_millis(5)).await;
+        cb.record_failure();
+        assert_eq!(queued.await.unwrap(), Err(AdmitError::Reopened));
+    }
+
+    #[tokio::test]
+    async fn test_admit_without_queue_matches_can_execute() {
+        let cb = CircuitBreaker::new(1, Duration::from_millis(20), 1, true).with_half_open_queue(0, Duration::from_millis(30));
+        assert!(cb.admit().await.unwrap().is_none());
+        cb.record_failure();
+        assert_eq!(cb.admit().await.unwrap_err(), AdmitError::Open);
+        sleep(Duration::from_millis(30)).await;
+        assert!(cb.admit().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_preview_does_not_change_state_or_metrics() {
+        let metrics = Metrics::detached();
+        let rejected = || metrics.circuit_breaker_rejected_total.with_label_values(&["preview"]).get();
+        let cb = CircuitBreaker::for_upstream("preview", 1, Duration::from_millis(20), 1, true).with_metrics(Arc::clone(&metrics));
+        assert_eq!(cb.preview(), Ok(()));
+        cb.record_failure();
+        assert_eq!(cb.preview(), Err(AdmitError::Open));
+        assert_eq!(rejected(), 0);
+
+        // 恢复等待已过：预览放行，但熔断器仍停留在 Open，直到真实请求迁移它
+        sleep(Duration::from_millis(30)).await;
+        assert_eq!(cb.preview(), Ok(()));
+        assert_eq!(cb.get_state(), CircuitState::Open);
+        assert!(cb.can_execute());
+        assert_eq!(cb.get_state(), CircuitState::HalfOpen);
+        assert_eq!(cb.preview(), Ok(()));
+    }
+}
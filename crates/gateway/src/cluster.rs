@@ -0,0 +1,313 @@
+//! 集群模式：同一 L4 负载均衡后的多个网关实例经 Redis pub/sub（`cluster.channel`）共享状态，
+//! 让限流与熔断决策在实例之间保持一致。
+//! - 限流：每个 `sync_interval_ms` 广播本实例全局桶放行的令牌数，其他实例从自己的桶中扣除，
+//!   整个集群合计约等于 `rate_limit` 配置的额度（有一个同步间隔的滞后）；该消息同时作为心跳；
+//! - 熔断：本地熔断器打开时广播，其他实例直接打开同名（upstream 标签）熔断器，之后各自 half-open 试探；
+//! - 健康：outlier detection 摘除的上游广播给其他实例，按各自的摘除时长摘除；
+//! - 只广播本地新出现的状态，收到的状态不再转发；Redis 不可用时各实例退化为独立决策并退避重连。
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use common::diagnostics::REDIS_URL_ENV;
+use common::lock::LockError;
+use common::pubsub::{Publisher, RedisPubSub};
+use pingora_core::server::ShutdownWatch;
+use pingora_core::services::background::BackgroundService;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+use crate::config::ClusterConfig;
+use crate::observability::Metrics;
+use crate::outlier::OutlierDetector;
+use crate::rate_limiter::RateLimiter;
+
+/// 重连退避上限
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClusterEvent {
+    /// 上次同步以来全局桶放行的令牌数；同时作为心跳
+    Usage { admitted: u64 },
+    CircuitOpen { upstream: String },
+    Ejected { upstream: String },
+}
+
+impl ClusterEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClusterEvent::Usage { .. } => "usage",
+            ClusterEvent::CircuitOpen { .. } => "circuit_open",
+            ClusterEvent::Ejected { .. } => "ejected",
+        }
+    }
+}
+
+/// 频道上的消息：`{"node":"gw-1","kind":"usage","admitted":12}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub node: String,
+    #[serde(flatten)]
+    pub event: ClusterEvent,
+}
+
+pub fn default_node_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "gateway".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+/// `current` 中 `known` 没有的项（需要广播），并以 `current` 替换 `known`
+fn fresh(known: &mut HashSet<String>, current: HashSet<String>) -> Vec<String> {
+    let mut new: Vec<String> = current.difference(known).cloned().collect();
+    new.sort();
+    *known = current;
+    new
+}
+
+/// 已广播或由其他实例同步来的熔断/摘除状态
+#[derive(Debug, Default)]
+struct Known {
+    open: HashSet<String>,
+    ejected: HashSet<String>,
+}
+
+pub struct ClusterSync {
+    cfg: ClusterConfig,
+    node: String,
+    pubsub: RedisPubSub,
+    rate_limiter: RateLimiter,
+    /// (upstream 标签, 熔断器)：`default` 与故障转移分组
+    breakers: Vec<(String, CircuitBreaker)>,
+    outlier: OutlierDetector,
+    metrics: Arc<Metrics>,
+    known: Mutex<Known>,
+    peers: Mutex<HashMap<String, Instant>>,
+}
+
+impl ClusterSync {
+    pub fn new(cfg: ClusterConfig, rate_limiter: RateLimiter, breakers: Vec<(String, CircuitBreaker)>, outlier: OutlierDetector, metrics: Arc<Metrics>) -> Result<Self, LockError> {
+        let url = cfg.redis_url.clone().or_else(|| std::env::var(REDIS_URL_ENV).ok()).ok_or_else(|| LockError::Backend("cluster.redis_url or REDIS_URL is required".to_string()))?;
+        let pubsub = RedisPubSub::from_url(&url)?;
+        let node = cfg.node_id.clone().unwrap_or_else(default_node_id);
+        Ok(Self { cfg, node, pubsub, rate_limiter, breakers, outlier, metrics, known: Mutex::new(Known::default()), peers: Mutex::new(HashMap::new()) })
+    }
+
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+
+    /// 本次同步要广播的事件
+    fn collect(&self) -> Vec<ClusterEvent> {
+        let admitted = if self.cfg.share_rate_limit { self.rate_limiter.take_admitted() } else { 0 };
+        let mut events = vec![ClusterEvent::Usage { admitted }];
+        let mut known = self.known.lock().expect("cluster state lock");
+        if self.cfg.share_circuit_breaker {
+            let open = self.breakers.iter().filter(|(_, b)| b.get_state() == CircuitState::Open).map(|(name, _)| name.clone()).collect();
+            events.extend(fresh(&mut known.open, open).into_iter().map(|upstream| ClusterEvent::CircuitOpen { upstream }));
+        }
+        if self.cfg.share_health {
+            let ejected = self.outlier.ejected_hosts().into_iter().collect();
+            events.extend(fresh(&mut known.ejected, ejected).into_iter().map(|upstream| ClusterEvent::Ejected { upstream }));
+        }
+        events
+    }
+
+    /// 处理一条频道消息；本实例发出的消息忽略
+    fn receive(&self, payload: &[u8]) {
+        let envelope: Envelope = match serde_json::from_slice(payload) {
+            Ok(e) => e,
+            Err(e) => {
+                debug!(event = "cluster_message_invalid", error = %e, "ignoring malformed cluster message");
+                return;
+            }
+        };
+        if envelope.node == self.node {
+            return;
+        }
+        self.metrics.cluster_messages_total.with_label_values(&["received", envelope.event.kind()]).inc();
+        self.peers.lock().expect("cluster peers lock").insert(envelope.node.clone(), Instant::now());
+        self.apply(&envelope.node, envelope.event);
+    }
+
+    fn apply(&self, node: &str, event: ClusterEvent) {
+        match event {
+            ClusterEvent::Usage { admitted } if self.cfg.share_rate_limit => self.rate_limiter.consume_shared(admitted),
+            ClusterEvent::CircuitOpen { upstream } if self.cfg.share_circuit_breaker => {
+                self.known.lock().expect("cluster state lock").open.insert(upstream.clone());
+                if self.breakers.iter().find(|(name, _)| *name == upstream).is_some_and(|(_, b)| b.trip()) {
+                    info!(event = "cluster_circuit_open", peer = %node, upstream = %upstream, "circuit breaker opened by cluster peer");
+                }
+            }
+            ClusterEvent::Ejected { upstream } if self.cfg.share_health => {
+                self.known.lock().expect("cluster state lock").ejected.insert(upstream.clone());
+                if self.outlier.eject(&upstream) {
+                    info!(event = "cluster_upstream_ejected", peer = %node, upstream = %upstream, "upstream ejected by cluster peer");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 最近 `peer_timeout_secs` 内有消息的其他实例数
+    fn live_peers(&self) -> usize {
+        let timeout = Duration::from_secs(self.cfg.peer_timeout_secs.max(1));
+        let mut peers = self.peers.lock().expect("cluster peers lock");
+        peers.retain(|_, seen| seen.elapsed() < timeout);
+        peers.len()
+    }
+
+    async fn flush(&self, publisher: &mut Publisher) -> Result<(), LockError> {
+        for event in self.collect() {
+            let kind = event.kind();
+            let payload = serde_json::to_string(&Envelope { node: self.node.clone(), event }).map_err(|e| LockError::Protocol(e.to_string()))?;
+            publisher.publish(&self.cfg.channel, &payload).await?;
+            self.metrics.cluster_messages_total.with_label_values(&["sent", kind]).inc();
+        }
+        self.metrics.cluster_peers.set(self.live_peers() as i64);
+        Ok(())
+    }
+
+    /// 订阅并周期广播，直到连接出错
+    async fn session(&self) -> Result<(), LockError> {
+        let mut subscription = self.pubsub.subscribe(&self.cfg.channel).await?;
+        let mut publisher = self.pubsub.publisher().await?;
+        info!(event = "cluster_joined", node = %self.node, channel = %self.cfg.channel, "joined gateway cluster");
+        // 读取放在独立任务中：Subscription::next 不可取消
+        let (tx, mut rx) = mpsc::channel(1024);
+        let reader = tokio::spawn(async move {
+            loop {
+                let message = subscription.next().await;
+                let failed = message.is_err();
+                if tx.send(message).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        let mut tick = tokio::time::interval(Duration::from_millis(self.cfg.sync_interval_ms.max(50)));
+        let result = loop {
+            tokio::select! {
+                message = rx.recv() => match message {
+                    Some(Ok(payload)) => self.receive(&payload),
+                    Some(Err(e)) => break Err(e),
+                    None => break Err(LockError::Protocol("subscription closed".to_string())),
+                },
+                _ = tick.tick() => {
+                    if let Err(e) = self.flush(&mut publisher).await {
+                        break Err(e);
+                    }
+                }
+            }
+        };
+        reader.abort();
+        result
+    }
+
+    async fn run(&self) {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            let started = Instant::now();
+            if let Err(e) = self.session().await {
+                warn!(event = "cluster_sync_failed", node = %self.node, error = %e, retry_in_ms = backoff.as_millis() as u64, "cluster sync disconnected; deciding locally until reconnected");
+            }
+            self.metrics.cluster_peers.set(0);
+            if started.elapsed() > MAX_BACKOFF {
+                backoff = Duration::from_secs(1);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundService for ClusterSync {
+    async fn start(&self, mut shutdown: ShutdownWatch) {
+        tokio::select! {
+            _ = self.run() => {}
+            _ = shutdown.changed() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sync(cfg: ClusterConfig) -> (ClusterSync, CircuitBreaker) {
+        let breaker = CircuitBreaker::for_upstream("cluster-test", 1, Duration::from_secs(30), 1, true);
+        let sync = ClusterSync::new(
+            ClusterConfig { redis_url: Some("redis://127.0.0.1:1".into()), node_id: Some("gw-1".into()), ..cfg },
+            RateLimiter::new(1, 10, true),
+            vec![("cluster-test".to_string(), breaker.clone())],
+            OutlierDetector::new(1, Duration::from_secs(10), Duration::from_secs(60), true),
+            Arc::new(Metrics::new(vec![0.1]).unwrap()),
+        )
+        .unwrap();
+        (sync, breaker)
+    }
+
+    #[test]
+    fn envelopes_are_flat_json() {
+        let e = Envelope { node: "gw-1".into(), event: ClusterEvent::Usage { admitted: 12 } };
+        let json = serde_json::to_string(&e).unwrap();
+        assert_eq!(json, r#"{"node":"gw-1","kind":"usage","admitted":12}"#);
+        assert_eq!(serde_json::from_str::<Envelope>(&json).unwrap(), e);
+    }
+
+    #[test]
+    fn fresh_reports_only_new_entries() {
+        let mut known = HashSet::from(["a".to_string()]);
+        assert_eq!(fresh(&mut known, HashSet::from(["a".to_string(), "b".to_string()])), vec!["b".to_string()]);
+        assert!(fresh(&mut known, HashSet::from(["b".to_string()])).is_empty());
+        assert_eq!(fresh(&mut known, HashSet::from(["a".to_string(), "b".to_string()])), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn broadcasts_local_state_once() {
+        let (sync, breaker) = sync(ClusterConfig::default());
+        assert!(sync.rate_limiter.check_rate_limit());
+        breaker.record_failure();
+        sync.outlier.record_failure("10.0.0.1:80");
+        let events = sync.collect();
+        assert_eq!(
+            events,
+            vec![
+                ClusterEvent::Usage { admitted: 1 },
+                ClusterEvent::CircuitOpen { upstream: "cluster-test".into() },
+                ClusterEvent::Ejected { upstream: "10.0.0.1:80".into() },
+            ]
+        );
+        assert_eq!(sync.collect(), vec![ClusterEvent::Usage { admitted: 0 }]);
+    }
+
+    #[test]
+    fn applies_peer_state_without_echoing_it() {
+        let (sync, breaker) = sync(ClusterConfig::default());
+        let message = |event| serde_json::to_vec(&Envelope { node: "gw-2".into(), event }).unwrap();
+        sync.receive(&message(ClusterEvent::CircuitOpen { upstream: "cluster-test".into() }));
+        sync.receive(&message(ClusterEvent::Ejected { upstream: "10.0.0.9:80".into() }));
+        sync.receive(&message(ClusterEvent::Usage { admitted: 10 }));
+        assert_eq!(breaker.get_state(), CircuitState::Open);
+        assert!(sync.outlier.is_ejected("10.0.0.9:80"));
+        assert!(!sync.rate_limiter.check_rate_limit());
+        assert_eq!(sync.collect(), vec![ClusterEvent::Usage { admitted: 0 }]);
+        assert_eq!(sync.live_peers(), 1);
+
+        // 自己发出的消息不处理
+        let own = serde_json::to_vec(&Envelope { node: "gw-1".into(), event: ClusterEvent::Ejected { upstream: "10.0.0.7:80".into() } }).unwrap();
+        sync.receive(&own);
+        assert!(!sync.outlier.is_ejected("10.0.0.7:80"));
+    }
+
+    #[test]
+    fn shared_kinds_can_be_disabled() {
+        let (sync, breaker) = sync(ClusterConfig { share_circuit_breaker: false, ..ClusterConfig::default() });
+        sync.apply("gw-2", ClusterEvent::CircuitOpen { upstream: "cluster-test".into() });
+        assert_eq!(breaker.get_state(), CircuitState::Closed);
+    }
+}
@@ -1,13 +1,111 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
+use common::feature_flags::FlagSet;
+use common::redaction::RedactionConfig;
+use common::cors::CorsConfig;
+use common::profiling::ProfilingConfig;
+use common::utils::logging::{LogFormat, LoggingConfig};
+use configs::resolver::{Resolved, Resolver};
+use configs::EncryptionConfig;
+
+use crate::load_shed::Priority;
+
+/// 网关配置的环境变量前缀
+pub const ENV_PREFIX: &str = "GATEWAY";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyConfig {
     pub rate_limit: RateLimitConfig,
     pub circuit_breaker: CircuitBreakerConfig,
     pub retry: RetryConfig,
     pub timeout: TimeoutConfig,
+    #[serde(default)]
+    pub outlier_detection: OutlierDetectionConfig,
+    #[serde(default)]
+    pub bulkhead: BulkheadConfig,
+    #[serde(default)]
+    pub adaptive_concurrency: AdaptiveConcurrencyConfig,
+    #[serde(default)]
+    pub debug: DebugConfig,
+    #[serde(default)]
+    pub load_shedding: LoadSheddingConfig,
+    #[serde(default)]
+    pub bandit: BanditConfig,
+    /// 按地域/可用区优先选择同区域上游，本地健康容量不足时才跨区域
+    #[serde(default)]
+    pub locality: LocalityConfig,
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+    #[serde(default)]
+    pub kubernetes: KubernetesDiscoveryConfig,
+    #[serde(default = "default_listeners")]
+    pub listeners: Vec<ListenerConfig>,
+    #[serde(default)]
+    pub listener_scopes: ListenerScopeConfig,
+    /// 访问日志中的敏感查询参数等
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    #[serde(default)]
+    pub waf: WafConfig,
+    #[serde(default)]
+    pub bot_detection: BotDetectionConfig,
+    #[serde(default)]
+    pub geoip: GeoIpConfig,
+    #[serde(default)]
+    pub latency: LatencyConfig,
+    /// 按路由限制 multipart 上传（part 数、单个 part 大小、文件类型）
+    #[serde(default)]
+    pub uploads: UploadConfig,
+    /// 按路由声明接受的请求 Content-Type 与预期的响应 Content-Type
+    #[serde(default)]
+    pub content_types: ContentTypeConfig,
+    /// 按路由录制上游请求/响应到 fixture 文件，或用 fixture 代替上游回放（离线测试）
+    #[serde(default)]
+    pub recording: RecordingConfig,
+    /// 内置 echo 上游（调试/测试用），启用后 upstreams 可指向 `echo.listen`
+    #[serde(default)]
+    pub echo: EchoConfig,
+    /// 按路由注入错误响应、延迟与连接重置（混沌测试），默认关闭
+    #[serde(default)]
+    pub fault_injection: FaultInjectionConfig,
+    /// 租户自定义 429/502/503/504 错误页，替代默认 JSON 错误 body
+    #[serde(default)]
+    pub error_pages: ErrorPagesConfig,
+    #[serde(default = "default_logging")]
+    pub logging: LoggingConfig,
+    /// admin 端口 /debug/pprof/*；需 Bearer token
+    #[serde(default)]
+    pub profiling: ProfilingConfig,
+    /// admin 端口的 CORS 策略（与控制面 `[cors]` 格式相同）；未配置来源时只允许同源
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// 按租户灰度的特性开关（与控制面 GET /admin/feature-flag-rules 格式相同）；租户取自 `listener_scopes.tenant_header`
+    #[serde(default)]
+    pub feature_flags: FlagSet,
+    /// 上游地址（与 upstreams 写法一致）-> 转发时注入的认证凭据
+    #[serde(default)]
+    pub upstream_auth: HashMap<String, UpstreamCredential>,
+    /// 为转发请求签名，上游据此确认流量来自网关
+    #[serde(default)]
+    pub request_signing: RequestSigningConfig,
+    /// 解密 upstream_auth / request_signing 中 `v1:` 密文的主密钥（与控制面 `[encryption]` 相同）
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
     pub upstreams: Vec<String>,
+    /// 具名上游分组与按路由故障转移：主分组无健康节点或熔断打开时切到备用分组
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    /// 集群模式：多个网关实例经 Redis pub/sub 共享限流用量、熔断与摘除状态
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    /// 按请求头中的 API key ID 异步回写 `api_key.last_used_at`
+    #[serde(default)]
+    pub key_activity: KeyActivityConfig,
+    /// 生成本配置的控制面版本号（`config_revision`），写入请求日志用于关联变更
+    #[serde(default)]
+    pub revision: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +113,68 @@ pub struct RateLimitConfig {
     pub enabled: bool,
     pub requests_per_second: u64,
     pub burst_size: u64,
+    /// 全局令牌桶每隔多少毫秒补充一个令牌，配置后替代 `requests_per_second` 的补充速率
+    /// （用于低于 1 个/秒或非整数的速率，如 400 表示 2.5 个/秒）；未配置时按 `requests_per_second` 每秒补充
+    #[serde(default)]
+    pub refill_interval_ms: Option<u64>,
+    /// Spike arrest：同一 key 两次放行的最小间隔（毫秒），未配置则关闭
+    #[serde(default)]
+    pub spike_arrest_interval_ms: Option<u64>,
+    /// spike arrest 的 key 取自该请求头（前置 key 校验写入的 API key ID）；
+    /// 只采用 `trusted_sources` 发来的值，其他来源或缺失时使用客户端 IP
+    #[serde(default = "default_spike_arrest_key_header")]
+    pub spike_arrest_key_header: String,
+    /// 租户 ID 请求头，用于查找租户的 spike arrest 间隔；同样只采用 `trusted_sources` 发来的值
+    #[serde(default = "default_rate_limit_tenant_header")]
+    pub tenant_header: String,
+    /// 允许设置上面两个请求头的来源（IP 或 CIDR）；默认为空，即一律按客户端 IP 限流
+    #[serde(default)]
+    pub trusted_sources: Vec<String>,
+    /// 从控制面 rate_limit 表同步每个租户的 spike arrest 间隔
+    #[serde(default)]
+    pub records: RateLimitRecordsConfig,
+    /// 在响应中返回 X-RateLimit-Limit/Remaining/Reset（放行与 429 均返回）
+    #[serde(default = "default_true")]
+    pub headers: bool,
+    /// 被拒绝次数最多的 N 个 key 导出为 `api_proxy_rate_limit_top_rejected_keys`；0 关闭
+    #[serde(default = "default_top_rejected_keys")]
+    pub top_rejected_keys: usize,
+    /// 上述统计的窗口（秒），每个窗口结束时刷新指标并清零
+    #[serde(default = "default_top_rejected_keys_interval_secs")]
+    pub top_rejected_keys_interval_secs: u64,
+}
+
+fn default_spike_arrest_key_header() -> String {
+    "x-api-key-id".to_string()
+}
+
+fn default_rate_limit_tenant_header() -> String {
+    "x-tenant-id".to_string()
+}
+
+/// 租户记录中的 `spike_arrest_interval_ms` 覆盖全局 `spike_arrest_interval_ms`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitRecordsConfig {
+    pub enabled: bool,
+    /// 未配置时使用 `DATABASE_URL`
+    pub database_url: Option<String>,
+    /// 同步间隔（秒）
+    pub refresh_secs: u64,
+}
+
+impl Default for RateLimitRecordsConfig {
+    fn default() -> Self {
+        Self { enabled: false, database_url: None, refresh_secs: 30 }
+    }
+}
+
+fn default_top_rejected_keys() -> usize {
+    10
+}
+
+fn default_top_rejected_keys_interval_secs() -> u64 {
+    60
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +183,16 @@ pub struct CircuitBreakerConfig {
     pub failure_threshold: u64,
     pub recovery_timeout_secs: u64,
     pub half_open_max_calls: u64,
+    /// half-open 期间最多排队等待试探名额的请求数；0 表示不排队
+    #[serde(default)]
+    pub half_open_queue_size: usize,
+    /// half-open 排队的最长等待时间
+    #[serde(default = "default_half_open_queue_timeout_ms")]
+    pub half_open_queue_timeout_ms: u64,
+}
+
+fn default_half_open_queue_timeout_ms() -> u64 {
+    500
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +209,810 @@ pub struct TimeoutConfig {
     pub request_timeout_secs: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierDetectionConfig {
+    pub enabled: bool,
+    pub consecutive_failures: u32,
+    pub base_ejection_secs: u64,
+    pub max_ejection_secs: u64,
+}
+
+impl Default for OutlierDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            consecutive_failures: 5,
+            base_ejection_secs: 30,
+            max_ejection_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkheadConfig {
+    pub enabled: bool,
+    /// 每个上游的最大在途请求数
+    pub max_in_flight: usize,
+    /// 名额用尽后允许排队的请求数
+    pub max_queue: usize,
+    pub queue_timeout_ms: u64,
+    /// 饱和时 503 响应中的 Retry-After
+    pub retry_after_secs: u64,
+    /// 按上游地址覆盖 max_in_flight
+    #[serde(default)]
+    pub per_upstream: HashMap<String, usize>,
+}
+
+impl Default for BulkheadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_in_flight: 256,
+            max_queue: 64,
+            queue_timeout_ms: 1000,
+            retry_after_secs: 1,
+            per_upstream: HashMap::new(),
+        }
+    }
+}
+
+/// 启用后替代 bulkhead 的静态 max_in_flight（Retry-After 仍取自 bulkhead 配置）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveConcurrencyConfig {
+    pub enabled: bool,
+    pub initial_limit: usize,
+    pub min_limit: usize,
+    pub max_limit: usize,
+    /// 延迟超过 min_rtt * latency_tolerance 视为拥塞
+    pub latency_tolerance: f64,
+    /// 拥塞时 limit 乘以该系数
+    pub backoff_ratio: f64,
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 1000,
+            latency_tolerance: 2.0,
+            backoff_ratio: 0.9,
+        }
+    }
+}
+
+/// 调试模式：在响应头中返回网关决策信息，并支持 dry-run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// X-Gateway-Debug 签名密钥；未配置时只能通过 paths 开启
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// 命中这些路径前缀的请求始终开启调试
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// X-Gateway-Debug-Timestamp 与网关时间允许的最大偏差（秒）
+    #[serde(default = "default_debug_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+fn default_debug_max_skew_secs() -> u64 {
+    300
+}
+
+impl Default for DebugConfig {
+    fn default() -> Self {
+        Self { enabled: false, secret: None, paths: Vec::new(), max_skew_secs: default_debug_max_skew_secs() }
+    }
+}
+
+/// 过载时按优先级丢弃请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadSheddingConfig {
+    pub enabled: bool,
+    /// 在途请求数达到该值视为队列饱和（负载 1.0）
+    pub max_in_flight: usize,
+    pub cpu_sample_interval_ms: u64,
+    /// 各优先级的负载阈值（0-1），critical 永不丢弃
+    pub thresholds: HashMap<Priority, f64>,
+    /// 显式优先级请求头（low/normal/high/critical）
+    pub priority_header: String,
+    /// 租户等级请求头，按 tiers 映射为优先级
+    pub tier_header: String,
+    /// 允许设置上面两个请求头的来源（IP 或 CIDR，通常是完成认证的前置层）；
+    /// 其他来源的这两个头在入口处移除并按 default_priority 处理。默认为空，即一律不信任
+    #[serde(default)]
+    pub trusted_sources: Vec<String>,
+    #[serde(default)]
+    pub tiers: HashMap<String, Priority>,
+    pub default_priority: Priority,
+    pub retry_after_secs: u64,
+}
+
+impl Default for LoadSheddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_in_flight: 4096,
+            cpu_sample_interval_ms: 1000,
+            thresholds: HashMap::from([(Priority::Low, 0.7), (Priority::Normal, 0.85), (Priority::High, 0.95)]),
+            priority_header: "x-priority".to_string(),
+            tier_header: "x-tenant-tier".to_string(),
+            trusted_sources: Vec::new(),
+            tiers: HashMap::new(),
+            default_priority: Priority::Normal,
+            retry_after_secs: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BanditStrategy {
+    EpsilonGreedy,
+    Ucb,
+}
+
+/// 就近选择上游（见 `locality` 模块）：优先同可用区，其次同地域，最后跨地域
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalityConfig {
+    pub enabled: bool,
+    /// 本网关实例所在地域，可用环境变量 `GATEWAY__LOCALITY__REGION` 按实例设置
+    pub region: String,
+    pub zone: Option<String>,
+    /// 上游地址（与 upstreams 写法一致）-> 地域/可用区；未标注的上游视为与网关同地域、无可用区
+    pub upstreams: HashMap<String, UpstreamLocality>,
+    /// 某一级可用（健康且未被摘除）上游占比低于该百分比时溢出到下一级
+    pub min_healthy_percent: u8,
+}
+
+impl Default for LocalityConfig {
+    fn default() -> Self {
+        Self { enabled: false, region: String::new(), zone: None, upstreams: HashMap::new(), min_healthy_percent: 50 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpstreamLocality {
+    pub region: String,
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+/// 实验性：按观测延迟/成功率动态分配上游流量（替代轮询）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanditConfig {
+    pub enabled: bool,
+    pub strategy: BanditStrategy,
+    /// epsilon-greedy 的随机探索概率
+    pub epsilon: f64,
+    /// UCB 探索系数
+    pub ucb_exploration: f64,
+    /// 成功请求延迟等于该值时奖励为 0.5
+    pub latency_target_ms: u64,
+    /// 奖励滑动平均系数，越大越偏重近期样本
+    pub reward_alpha: f64,
+    /// 流量占比滑动平均系数
+    pub share_alpha: f64,
+    /// 每个上游的最小/最大流量占比
+    pub min_share: f64,
+    pub max_share: f64,
+}
+
+impl Default for BanditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: BanditStrategy::EpsilonGreedy,
+            epsilon: 0.1,
+            ucb_exploration: 0.5,
+            latency_target_ms: 200,
+            reward_alpha: 0.1,
+            share_alpha: 0.01,
+            min_share: 0.05,
+            max_share: 0.8,
+        }
+    }
+}
+
+/// 零停机升级（Pingora graceful upgrade）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpgradeConfig {
+    /// 新旧进程交接监听 socket 的 Unix socket 路径，两个进程必须一致
+    pub upgrade_sock: String,
+    /// 当前进程 PID，升级时向其发送 SIGQUIT
+    pub pid_file: String,
+    /// 旧进程交出 socket 后继续处理存量请求的时间（秒）
+    pub grace_period_secs: u64,
+    /// grace period 之后等待后台服务退出的最长时间（秒）
+    pub graceful_shutdown_timeout_secs: u64,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            upgrade_sock: "/tmp/api_proxy_gateway_upgrade.sock".to_string(),
+            pid_file: "/tmp/api_proxy_gateway.pid".to_string(),
+            grace_period_secs: 30,
+            graceful_shutdown_timeout_secs: 10,
+        }
+    }
+}
+
+/// 监听端口；`listener_scopes` 中按 name 引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerConfig {
+    pub name: String,
+    pub addr: String,
+    #[serde(default)]
+    pub tls: Option<ListenerTlsConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerTlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+fn default_listeners() -> Vec<ListenerConfig> {
+    vec![ListenerConfig { name: "public".to_string(), addr: "0.0.0.0:6188".to_string(), tls: None }]
+}
+
+/// 网关默认输出 JSON 日志，并对 gateway::proxy 的请求处理细节使用 debug
+fn default_logging() -> LoggingConfig {
+    LoggingConfig {
+        format: LogFormat::Json,
+        file_prefix: "gateway".to_string(),
+        modules: [("gateway::proxy".to_string(), "debug".to_string())].into(),
+        ..Default::default()
+    }
+}
+
+/// WAF 规则：条件全部命中时执行 action，`default_rules` 启用内置的注入/扫描器规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WafConfig {
+    pub enabled: bool,
+    pub default_rules: bool,
+    pub rules: Vec<WafRuleConfig>,
+}
+
+impl Default for WafConfig {
+    fn default() -> Self {
+        Self { enabled: false, default_rules: true, rules: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WafAction {
+    Block,
+    RateLimit,
+    Tag,
+}
+
+/// 未配置的条件不参与匹配；至少配置一个条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WafRuleConfig {
+    pub id: String,
+    pub action: WafAction,
+    /// 为空时匹配任意方法
+    #[serde(default)]
+    pub methods: Vec<String>,
+    /// 正则，匹配请求目标（path + query，保持原始编码）
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 请求头名 -> 正则；请求头缺失视为不匹配
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Content-Length 超过该值时命中
+    #[serde(default)]
+    pub max_body_bytes: Option<u64>,
+    /// block 的响应码，默认 403
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// rate_limit：每个客户端 IP 每秒请求数与突发量
+    #[serde(default)]
+    pub requests_per_second: Option<u64>,
+    #[serde(default)]
+    pub burst: Option<u64>,
+}
+
+/// 机器人识别：按 UA、请求头指纹与请求频率打分，超过阈值的客户端按租户策略处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotDetectionConfig {
+    pub enabled: bool,
+    /// 0-100，达到该分数视为疑似机器人
+    pub score_threshold: u32,
+    /// 已知良性爬虫 UA 片段（大小写不敏感），命中后不打分也不限制
+    pub allowlist: Vec<String>,
+    pub tenant_header: String,
+    pub default_policy: BotPolicy,
+    /// 租户 ID -> 策略
+    pub tenant_policies: HashMap<String, BotPolicy>,
+    /// 同一客户端指纹在窗口内的请求数超过上限时加分
+    pub window_secs: u64,
+    pub window_max_requests: u32,
+    /// challenge cookie 签名密钥；未配置时启动时随机生成（重启后需重新通过 challenge）
+    pub challenge_secret: Option<String>,
+}
+
+impl Default for BotDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            score_threshold: 50,
+            allowlist: ["Googlebot", "bingbot", "DuckDuckBot", "Baiduspider", "YandexBot", "Slackbot", "UptimeRobot"]
+                .map(String::from)
+                .to_vec(),
+            tenant_header: "x-tenant-id".to_string(),
+            default_policy: BotPolicy::default(),
+            tenant_policies: HashMap::new(),
+            window_secs: 10,
+            window_max_requests: 100,
+            challenge_secret: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotAction {
+    Allow,
+    /// 按客户端指纹限流
+    Throttle,
+    /// 要求回传签名 cookie，不保存 cookie 的脚本无法通过
+    Challenge,
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotPolicy {
+    pub action: BotAction,
+    pub requests_per_second: u64,
+    pub burst: u64,
+}
+
+impl Default for BotPolicy {
+    fn default() -> Self {
+        Self { action: BotAction::Throttle, requests_per_second: 2, burst: 5 }
+    }
+}
+
+/// MaxMind 格式（.mmdb）的 GeoIP 库：标注请求国家/ASN，并按路径前缀配置国家维度的放行/拒绝/限流
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeoIpConfig {
+    pub enabled: bool,
+    /// GeoLite2-Country / GeoIP2-City 等含 country 字段的库
+    pub country_db: Option<String>,
+    /// GeoLite2-ASN
+    pub asn_db: Option<String>,
+    /// 检查库文件修改时间的间隔，文件变化时热加载
+    pub reload_interval_secs: u64,
+    pub rules: Vec<GeoRule>,
+}
+
+impl Default for GeoIpConfig {
+    fn default() -> Self {
+        Self { enabled: false, country_db: None, asn_db: None, reload_interval_secs: 60, rules: Vec::new() }
+    }
+}
+
+/// API key 最近使用时间（见 `key_activity` 模块）；`database_url` 未配置时读取 `DATABASE_URL`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyActivityConfig {
+    pub enabled: bool,
+    pub database_url: Option<String>,
+    /// 携带 API key ID（`api_key.id`）的请求头，由前置的 key 校验写入；不是合法 UUID 的值忽略
+    pub key_id_header: String,
+    /// 回写间隔（秒）；同一 key 每个间隔最多写一次
+    pub flush_interval_secs: u64,
+}
+
+impl Default for KeyActivityConfig {
+    fn default() -> Self {
+        Self { enabled: false, database_url: None, key_id_header: "x-api-key-id".to_string(), flush_interval_secs: 30 }
+    }
+}
+
+/// 按最长 path_prefix 命中一条规则；国家代码为 ISO 3166-1 alpha-2，查不到国家时记为 `unknown`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoRule {
+    pub path_prefix: String,
+    /// 非空时只放行这些国家
+    #[serde(default)]
+    pub allow_countries: Vec<String>,
+    #[serde(default)]
+    pub deny_countries: Vec<String>,
+    #[serde(default)]
+    pub rate_limit: Option<GeoRateLimit>,
+}
+
+/// 来自 `countries`（为空表示全部）的请求按客户端 IP 限流
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoRateLimit {
+    #[serde(default)]
+    pub countries: Vec<String>,
+    pub requests_per_second: u64,
+    pub burst: u64,
+}
+
+/// 按路由/上游的延迟直方图（`api_proxy_route_request_duration_seconds`）与滑动窗口分位数（admin `/latency`）；
+/// 路由按最长 `routes` 前缀归类，未命中记为 `other`，避免标签基数随路径增长
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyConfig {
+    pub enabled: bool,
+    /// 直方图桶（秒），启动时生效
+    pub buckets: Vec<f64>,
+    /// 路由路径前缀
+    pub routes: Vec<String>,
+    /// 分位数统计的滑动窗口
+    pub window_secs: u64,
+    /// 每个路由窗口内最多保留的样本数，超出时丢弃最旧的
+    pub max_samples_per_route: usize,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            buckets: vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+            routes: Vec::new(),
+            window_secs: 300,
+            max_samples_per_route: 10_000,
+        }
+    }
+}
+
+/// multipart/form-data 上传检查：在转发 body 的同时流式解析，不把整个上传读入内存
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    pub enabled: bool,
+    pub rules: Vec<UploadRule>,
+}
+
+/// 按最长 path_prefix 命中一条规则；未命中的请求不检查
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadRule {
+    pub path_prefix: String,
+    /// 单个 part 的最大字节数（普通表单字段同样计入，省略 filename 无法绕过），超出返回 413
+    #[serde(default)]
+    pub max_file_bytes: Option<u64>,
+    /// 最多 part 数，超出返回 413
+    #[serde(default)]
+    pub max_parts: Option<usize>,
+    /// 非空时文件 part 的 Content-Type 须在列表中（支持 `image/*`），否则返回 415
+    #[serde(default)]
+    pub allowed_content_types: Vec<String>,
+}
+
+/// 路由的 Content-Type 约定（见 `content_type` 模块）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentTypeConfig {
+    pub enabled: bool,
+    pub rules: Vec<ContentTypeRule>,
+}
+
+/// 按最长 path_prefix 命中一条规则；类型支持 `image/*`、`*/*`，忽略 charset 等参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentTypeRule {
+    pub path_prefix: String,
+    /// 非空时带 body 的请求须为其中之一，否则返回 415
+    #[serde(default)]
+    pub request: Vec<String>,
+    /// 非空时上游响应不在其中记入 `api_proxy_unexpected_content_type_total`
+    #[serde(default)]
+    pub response: Vec<String>,
+}
+
+/// 流量录制与回放（见 `recording` 模块）；fixture 在启动时加载/创建，修改规则需重启
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingConfig {
+    pub enabled: bool,
+    pub rules: Vec<RecordingRule>,
+    /// 请求体或响应体超过该大小的交互不录制
+    pub max_body_bytes: usize,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        Self { enabled: false, rules: Vec::new(), max_body_bytes: 1024 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingMode {
+    /// 正常转发，并把每个请求/响应追加到 fixture（启动时清空）
+    Record,
+    /// 不连接上游，按请求匹配 fixture 中的响应
+    Playback,
+}
+
+/// 按最长 path_prefix 命中一条规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingRule {
+    pub path_prefix: String,
+    pub mode: RecordingMode,
+    /// fixture 文件路径（JSON）
+    pub fixture: String,
+    /// 参与匹配的请求头；默认只按方法、路径、查询参数与请求体匹配
+    #[serde(default)]
+    pub match_headers: Vec<String>,
+    #[serde(default = "default_true")]
+    pub match_body: bool,
+    /// 回放未命中时转发到真实上游；默认返回 404
+    #[serde(default)]
+    pub passthrough_on_miss: bool,
+    /// 按 `redaction` 脱敏后再写入 fixture（匹配时请求同样先脱敏）
+    #[serde(default = "default_true")]
+    pub redact: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 内置 echo 上游：把方法、路径、查询参数、请求头与 body 以 JSON 返回（见 `common::echo`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EchoConfig {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+impl Default for EchoConfig {
+    fn default() -> Self {
+        Self { enabled: false, listen: "127.0.0.1:9099".to_string() }
+    }
+}
+
+/// 故障注入（见 `faults` 模块）；`enabled` 为 false 时规则不生效，admin 端口也不提供 `/faults`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultInjectionConfig {
+    pub enabled: bool,
+    pub rules: Vec<FaultRule>,
+}
+
+/// 按最长 path_prefix 命中一条规则；百分比取值 0-100，`reset_percent + abort_percent` 不超过 100
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub path_prefix: String,
+    /// 不转发、直接返回 `abort_status` 的请求比例
+    #[serde(default)]
+    pub abort_percent: f64,
+    #[serde(default = "default_abort_status")]
+    pub abort_status: u16,
+    /// 不返回响应、直接关闭连接的请求比例
+    #[serde(default)]
+    pub reset_percent: f64,
+    /// 处理前等待 `delay_ms` 加 0..=`delay_jitter_ms` 随机毫秒的请求比例
+    #[serde(default)]
+    pub delay_percent: f64,
+    #[serde(default)]
+    pub delay_ms: u64,
+    #[serde(default)]
+    pub delay_jitter_ms: u64,
+    /// 注入的错误/重置同时计为熔断器失败，用于验证网关自身的熔断行为
+    #[serde(default)]
+    pub count_as_upstream_failure: bool,
+}
+
+fn default_abort_status() -> u16 {
+    503
+}
+
+/// 上游故障转移（见 `failover` 模块）；`enabled` 为 false 时所有请求只转发到 `upstreams`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailoverConfig {
+    pub enabled: bool,
+    /// 分组名 -> 上游地址（与 upstreams 写法一致）；`default` 保留给 `upstreams`，每个分组独立健康检查与熔断
+    pub groups: HashMap<String, Vec<String>>,
+    pub routes: Vec<FailoverRoute>,
+}
+
+/// 集群模式（见 `cluster` 模块）；`redis_url` 未配置时读取 `REDIS_URL`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    pub enabled: bool,
+    pub redis_url: Option<String>,
+    /// 同一集群的实例使用同一频道；多个环境共用 Redis 时区分
+    pub channel: String,
+    /// 实例标识，默认 `HOSTNAME-pid`
+    pub node_id: Option<String>,
+    /// 广播本实例限流用量与状态的间隔（毫秒）
+    pub sync_interval_ms: u64,
+    /// 超过该时长未收到消息的实例不再计入 `api_proxy_cluster_peers`
+    pub peer_timeout_secs: u64,
+    pub share_rate_limit: bool,
+    pub share_circuit_breaker: bool,
+    pub share_health: bool,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redis_url: None,
+            channel: "api-proxy:gateway:cluster".to_string(),
+            node_id: None,
+            sync_interval_ms: 1000,
+            peer_timeout_secs: 10,
+            share_rate_limit: true,
+            share_circuit_breaker: true,
+            share_health: true,
+        }
+    }
+}
+
+/// 按最长 path_prefix 命中一条规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailoverRoute {
+    pub path_prefix: String,
+    /// 主分组，默认为 `upstreams`（`default`）
+    #[serde(default = "default_failover_primary")]
+    pub primary: String,
+    /// 备用分组，例如静态维护页服务或另一个地域
+    pub fallback: String,
+}
+
+fn default_failover_primary() -> String {
+    "default".to_string()
+}
+
+/// 租户错误页（见 `error_pages` 模块）；`enabled` 为 true 时 admin 端口提供 `/error-pages`，可在运行时增删租户模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ErrorPagesConfig {
+    pub enabled: bool,
+    /// 租户 ID 请求头
+    pub tenant_header: String,
+    /// 单个模板 body 上限（字节）
+    pub max_body_bytes: usize,
+    /// 每个租户的模板数上限
+    pub max_pages_per_tenant: usize,
+    /// 租户 -> 模板
+    pub tenants: HashMap<String, Vec<ErrorPage>>,
+}
+
+impl Default for ErrorPagesConfig {
+    fn default() -> Self {
+        Self { enabled: false, tenant_header: "x-tenant-id".to_string(), max_body_bytes: 16 * 1024, max_pages_per_tenant: 8, tenants: HashMap::new() }
+    }
+}
+
+/// 某个状态码的一种表示；同一状态码可配置多个 `content_type`，按请求 Accept 协商
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorPage {
+    /// 429 / 502 / 503 / 504
+    pub status: u16,
+    #[serde(default = "default_error_page_content_type")]
+    pub content_type: String,
+    /// 模板，占位符 `{{status}}` `{{error}}` `{{message}}` `{{request_id}}` `{{retry_after}}`
+    pub body: String,
+}
+
+fn default_error_page_content_type() -> String {
+    "text/html; charset=utf-8".to_string()
+}
+
+/// 路由/租户限定监听端口：命中规则的请求只能从规则列出的 listener 访问，其他端口返回 404
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListenerScopeConfig {
+    /// 租户 ID 请求头
+    pub tenant_header: String,
+    pub rules: Vec<ListenerScopeRule>,
+}
+
+impl Default for ListenerScopeConfig {
+    fn default() -> Self {
+        Self { tenant_header: "x-tenant-id".to_string(), rules: Vec::new() }
+    }
+}
+
+/// path_prefix 与 tenant 至少配置一个；同时配置时需同时命中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerScopeRule {
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    pub listeners: Vec<String>,
+}
+
+/// 上游认证方式；密钥字段可写明文，或控制面主密钥加密后的 `v1:<key_id>:...`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpstreamCredential {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    Basic { username: String, password: String },
+    /// 任意请求头，如 `X-Api-Key`
+    ApiKey { header: String, value: String },
+    /// OAuth2 client credentials：按需向 token_url 换取 access token 并缓存到过期前
+    Oauth2ClientCredentials {
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        #[serde(default)]
+        scope: Option<String>,
+        #[serde(default)]
+        audience: Option<String>,
+        /// 提前刷新的秒数
+        #[serde(default = "default_token_refresh_skew_secs")]
+        refresh_skew_secs: u64,
+    },
+}
+
+fn default_token_refresh_skew_secs() -> u64 {
+    30
+}
+
+/// 转发请求 HMAC-SHA256 签名（见 `signing` 模块）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestSigningConfig {
+    pub enabled: bool,
+    /// 随签名发送，上游轮换密钥时据此选择 secret
+    pub key_id: String,
+    /// 至少 16 字节；可为控制面加密后的 `v1:` 值
+    pub secret: String,
+    /// 超过该大小（或 chunked）的 body 不参与签名，记为 UNSIGNED-PAYLOAD；上限为 pingora 重试缓冲区 64 KiB
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestSigningConfig {
+    fn default() -> Self {
+        Self { enabled: false, key_id: "default".to_string(), secret: String::new(), max_body_bytes: 64 * 1024 }
+    }
+}
+
+/// Kubernetes 服务发现（watch 需以 `--features k8s` 编译）；启用后 upstreams 仅作为首次同步前的兜底
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KubernetesDiscoveryConfig {
+    pub enabled: bool,
+    /// 只 watch 该 namespace；为空时 watch 全部 namespace
+    pub namespace: Option<String>,
+    /// 值为 "true" 的 Service 纳入上游池
+    pub annotation: String,
+    /// 指定端口名或端口号；未设置时使用 EndpointSlice 的第一个端口
+    pub port_annotation: String,
+    /// 负载均衡器刷新上游列表的间隔
+    pub update_interval_ms: u64,
+}
+
+impl Default for KubernetesDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            namespace: None,
+            annotation: "api-proxy.dexcode.io/upstream".to_string(),
+            port_annotation: "api-proxy.dexcode.io/port".to_string(),
+            update_interval_ms: 1000,
+        }
+    }
+}
+
 impl Default for ProxyConfig {
     fn default() -> Self {
         Self {
@@ -46,12 +1020,23 @@ impl Default for ProxyConfig {
                 enabled: true,
                 requests_per_second: 1000,
                 burst_size: 100,
+                refill_interval_ms: None,
+                spike_arrest_interval_ms: None,
+                spike_arrest_key_header: default_spike_arrest_key_header(),
+                tenant_header: default_rate_limit_tenant_header(),
+                trusted_sources: Vec::new(),
+                records: RateLimitRecordsConfig::default(),
+                headers: true,
+                top_rejected_keys: default_top_rejected_keys(),
+                top_rejected_keys_interval_secs: default_top_rejected_keys_interval_secs(),
             },
             circuit_breaker: CircuitBreakerConfig {
                 enabled: true,
                 failure_threshold: 5,
                 recovery_timeout_secs: 30,
                 half_open_max_calls: 3,
+                half_open_queue_size: 0,
+                half_open_queue_timeout_ms: default_half_open_queue_timeout_ms(),
             },
             retry: RetryConfig {
                 enabled: true,
@@ -63,16 +1048,53 @@ impl Default for ProxyConfig {
                 connect_timeout_secs: 5,
                 request_timeout_secs: 30,
             },
+            outlier_detection: OutlierDetectionConfig::default(),
+            bulkhead: BulkheadConfig::default(),
+            adaptive_concurrency: AdaptiveConcurrencyConfig::default(),
+            debug: DebugConfig::default(),
+            load_shedding: LoadSheddingConfig::default(),
+            bandit: BanditConfig::default(),
+            locality: LocalityConfig::default(),
+            upgrade: UpgradeConfig::default(),
+            kubernetes: KubernetesDiscoveryConfig::default(),
+            listeners: default_listeners(),
+            listener_scopes: ListenerScopeConfig::default(),
+            redaction: RedactionConfig::default(),
+            waf: WafConfig::default(),
+            bot_detection: BotDetectionConfig::default(),
+            geoip: GeoIpConfig::default(),
+            latency: LatencyConfig::default(),
+            uploads: UploadConfig::default(),
+            content_types: ContentTypeConfig::default(),
+            recording: RecordingConfig::default(),
+            echo: EchoConfig::default(),
+            fault_injection: FaultInjectionConfig::default(),
+            error_pages: ErrorPagesConfig::default(),
+            logging: default_logging(),
+            profiling: ProfilingConfig::default(),
+            cors: CorsConfig::default(),
+            feature_flags: FlagSet::default(),
+            upstream_auth: HashMap::new(),
+            request_signing: RequestSigningConfig::default(),
+            encryption: EncryptionConfig::default(),
             upstreams: vec!["127.0.0.1:8080".to_string()],
+            failover: FailoverConfig::default(),
+            cluster: ClusterConfig::default(),
+            key_activity: KeyActivityConfig::default(),
+            revision: None,
         }
     }
 }
 
 impl ProxyConfig {
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string(path)?;
-        let config: ProxyConfig = serde_json::from_str(&content)?;
-        Ok(config)
+        Ok(Self::load_resolved(path)?.config)
+    }
+
+    /// 默认值 < config.json < .env < 环境变量（`GATEWAY__RATE_LIMIT__REQUESTS_PER_SECOND`），并记录每个值的来源
+    pub fn load_resolved(path: &str) -> Result<Resolved<Self>, Box<dyn std::error::Error>> {
+        let file = Resolver::read_file(path)?.ok_or_else(|| format!("{} not found", path))?;
+        Ok(Resolver::new(ENV_PREFIX).resolve(&ProxyConfig::default(), Some((path, file)))?)
     }
 
     pub fn connect_timeout(&self) -> Duration {
@@ -87,6 +1109,10 @@ impl ProxyConfig {
         Duration::from_secs(self.circuit_breaker.recovery_timeout_secs)
     }
 
+    pub fn half_open_queue_timeout(&self) -> Duration {
+        Duration::from_millis(self.circuit_breaker.half_open_queue_timeout_ms)
+    }
+
     pub fn backoff_base(&self) -> Duration {
         Duration::from_millis(self.retry.backoff_base_ms)
     }
@@ -94,4 +1120,16 @@ impl ProxyConfig {
     pub fn backoff_max(&self) -> Duration {
         Duration::from_millis(self.retry.backoff_max_ms)
     }
+
+    pub fn queue_timeout(&self) -> Duration {
+        Duration::from_millis(self.bulkhead.queue_timeout_ms)
+    }
+
+    pub fn base_ejection(&self) -> Duration {
+        Duration::from_secs(self.outlier_detection.base_ejection_secs)
+    }
+
+    pub fn max_ejection(&self) -> Duration {
+        Duration::from_secs(self.outlier_detection.max_ejection_secs)
+    }
 }
\ No newline at end of file
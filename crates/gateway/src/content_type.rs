@@ -0,0 +1,97 @@
+//! 按路由声明请求/响应的 Content-Type：
+//! - 带 body 的请求类型不在 `request` 列表中返回 415；
+//! - 上游响应类型不在 `response` 列表中只记指标与日志，仍原样返回，用于尽早发现配置错误的上游。
+
+use crate::config::{ContentTypeConfig, ContentTypeRule};
+
+/// 按最长前缀选择规则
+pub fn rule_for<'a>(cfg: &'a ContentTypeConfig, path: &str) -> Option<&'a ContentTypeRule> {
+    if !cfg.enabled {
+        return None;
+    }
+    cfg.rules.iter().filter(|r| path.starts_with(r.path_prefix.as_str())).max_by_key(|r| r.path_prefix.len())
+}
+
+/// 去掉参数并转为小写：`Application/JSON; charset=utf-8` -> `application/json`
+pub fn media_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+/// `allowed` 支持 `image/*` 与 `*/*` 通配；`actual` 须已经过 [`media_type`]
+pub fn media_type_matches(allowed: &str, actual: &str) -> bool {
+    let allowed = allowed.trim();
+    if allowed == "*/*" {
+        return true;
+    }
+    match allowed.strip_suffix("/*") {
+        Some(major) => actual.split('/').next().is_some_and(|m| m.eq_ignore_ascii_case(major)),
+        None => allowed.eq_ignore_ascii_case(actual),
+    }
+}
+
+/// 列表为空时不限制；缺少 Content-Type 视为不匹配
+fn accepts(allowed: &[String], content_type: Option<&str>) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    let Some(actual) = content_type.map(media_type) else {
+        return false;
+    };
+    allowed.iter().any(|a| media_type_matches(a, &actual))
+}
+
+/// 请求是否带 body：chunked 或 Content-Length 大于 0
+pub fn has_body(content_length: Option<&str>, transfer_encoding: Option<&str>) -> bool {
+    transfer_encoding.is_some() || content_length.and_then(|v| v.trim().parse::<u64>().ok()).is_some_and(|n| n > 0)
+}
+
+impl ContentTypeRule {
+    /// 无 body 的请求（GET 等）不检查
+    pub fn accepts_request(&self, content_type: Option<&str>, has_body: bool) -> bool {
+        !has_body || accepts(&self.request, content_type)
+    }
+
+    /// 204/304 等无 body 的响应由调用方跳过
+    pub fn accepts_response(&self, content_type: Option<&str>) -> bool {
+        accepts(&self.response, content_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(prefix: &str, request: &[&str], response: &[&str]) -> ContentTypeRule {
+        ContentTypeRule {
+            path_prefix: prefix.into(),
+            request: request.iter().map(|s| s.to_string()).collect(),
+            response: response.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn request_types_are_enforced_only_with_body() {
+        let r = rule("/api", &["application/json", "text/*"], &[]);
+        assert!(r.accepts_request(Some("Application/JSON; charset=utf-8"), true));
+        assert!(r.accepts_request(Some("text/csv"), true));
+        assert!(!r.accepts_request(Some("application/xml"), true));
+        assert!(!r.accepts_request(None, true));
+        assert!(r.accepts_request(None, false));
+        assert!(r.accepts_response(Some("application/octet-stream")));
+
+        assert!(has_body(Some("12"), None));
+        assert!(has_body(None, Some("chunked")));
+        assert!(!has_body(Some("0"), None));
+        assert!(!has_body(None, None));
+    }
+
+    #[test]
+    fn picks_longest_prefix_when_enabled() {
+        let mut cfg = ContentTypeConfig { enabled: true, rules: vec![rule("/api", &[], &["application/json"]), rule("/api/files", &[], &["*/*"])] };
+        assert!(rule_for(&cfg, "/api/files/1").unwrap().accepts_response(Some("image/png")));
+        assert!(!rule_for(&cfg, "/api/users").unwrap().accepts_response(Some("text/html")));
+        assert!(rule_for(&cfg, "/health").is_none());
+        cfg.enabled = false;
+        assert!(rule_for(&cfg, "/api/users").is_none());
+    }
+}
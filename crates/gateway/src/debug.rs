@@ -0,0 +1,157 @@
+use hmac::{Hmac, Mac};
+use pingora_core::Result;
+use pingora_http::ResponseHeader;
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::config::DebugConfig;
+use crate::latency::OTHER_ROUTE;
+
+/// 携带签名以开启调试模式：hex(HMAC-SHA256(secret, "{METHOD} {path} {timestamp}"))
+pub const DEBUG_HEADER: &str = "x-gateway-debug";
+/// 签名时间（Unix 秒），与网关时间相差超过 `debug.max_skew_secs` 的签名无效，避免截获的请求头被长期重放
+pub const DEBUG_TIMESTAMP_HEADER: &str = "x-gateway-debug-timestamp";
+/// 调试模式下设置为 1/true 时只评估处理流程，不转发到上游
+pub const DRY_RUN_HEADER: &str = "x-gateway-dry-run";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn mac(secret: &str, method: &str, path: &str, timestamp: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(format!("{} {} {}", method, path, timestamp).as_bytes());
+    mac
+}
+
+/// 计算调试签名（供客户端/运维工具生成请求头）
+pub fn sign(secret: &str, method: &str, path: &str, timestamp: i64) -> String {
+    hex::encode(mac(secret, method, path, timestamp).finalize().into_bytes())
+}
+
+/// 校验签名（常量时间比较）与时间偏差
+pub fn verify(secret: &str, method: &str, path: &str, timestamp: i64, signature: &str, now: i64, max_skew_secs: u64) -> bool {
+    if now.abs_diff(timestamp) > max_skew_secs {
+        return false;
+    }
+    let Ok(sig) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    mac(secret, method, path, timestamp).verify_slice(&sig).is_ok()
+}
+
+/// 判断本次请求是否开启调试：路径命中 `debug.paths` 前缀，或携带有效签名头与签名时间
+pub fn is_debug_request(cfg: &DebugConfig, method: &str, path: &str, signature: Option<&str>, timestamp: Option<&str>, now: i64) -> bool {
+    if !cfg.enabled {
+        return false;
+    }
+    if cfg.paths.iter().any(|p| path.starts_with(p.as_str())) {
+        return true;
+    }
+    let timestamp = timestamp.and_then(|t| t.trim().parse::<i64>().ok());
+    match (cfg.secret.as_deref(), signature, timestamp) {
+        (Some(secret), Some(sig), Some(ts)) if !secret.is_empty() => verify(secret, method, path, ts, sig, now, cfg.max_skew_secs),
+        _ => false,
+    }
+}
+
+pub fn is_dry_run(value: Option<&str>) -> bool {
+    matches!(value.map(|v| v.trim().to_ascii_lowercase()).as_deref(), Some("1" | "true"))
+}
+
+/// 网关对单个请求的决策信息
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Decision {
+    /// 按 `latency.routes` 最长前缀归类的路由，与路由级指标一致
+    pub route: String,
+    pub upstream: Option<String>,
+    /// allowed / waf / geo / bot / load_shed / rate_limited / circuit_open / bulkhead_rejected / no_upstream
+    pub limiter: &'static str,
+    /// 回放路由命中录制为 HIT、未命中为 MISS；其他请求不经缓存，为 BYPASS
+    pub cache: &'static str,
+    pub retries: u32,
+}
+
+impl Default for Decision {
+    fn default() -> Self {
+        Self {
+            route: OTHER_ROUTE.to_string(),
+            upstream: None,
+            limiter: "allowed",
+            cache: "BYPASS",
+            retries: 0,
+        }
+    }
+}
+
+impl Decision {
+    /// 写入 X-Gateway-* 响应头
+    pub fn apply_headers(&self, resp: &mut ResponseHeader) -> Result<()> {
+        resp.insert_header("X-Gateway-Route", self.route.clone())?;
+        resp.insert_header("X-Gateway-Upstream", self.upstream.clone().unwrap_or_default())?;
+        resp.insert_header("X-Gateway-Limiter", self.limiter)?;
+        resp.insert_header("X-Gateway-Cache", self.cache)?;
+        resp.insert_header("X-Gateway-Retries", self.retries.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> DebugConfig {
+        DebugConfig { enabled: true, secret: Some("s3cret".into()), paths: vec!["/internal/".into()], max_skew_secs: 300 }
+    }
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let sig = sign("s3cret", "GET", "/api/users", NOW);
+        assert!(verify("s3cret", "GET", "/api/users", NOW, &sig, NOW, 300));
+        assert!(!verify("s3cret", "POST", "/api/users", NOW, &sig, NOW, 300));
+        assert!(!verify("other", "GET", "/api/users", NOW, &sig, NOW, 300));
+        assert!(!verify("s3cret", "GET", "/api/users", NOW + 1, &sig, NOW, 300));
+        assert!(!verify("s3cret", "GET", "/api/users", NOW, "zz-not-hex", NOW, 300));
+    }
+
+    #[test]
+    fn test_signature_outside_skew_is_rejected() {
+        let sig = sign("s3cret", "GET", "/a", NOW);
+        assert!(verify("s3cret", "GET", "/a", NOW, &sig, NOW + 300, 300));
+        assert!(verify("s3cret", "GET", "/a", NOW, &sig, NOW - 300, 300));
+        assert!(!verify("s3cret", "GET", "/a", NOW, &sig, NOW + 301, 300));
+        assert!(!verify("s3cret", "GET", "/a", NOW, &sig, NOW - 301, 300));
+    }
+
+    #[test]
+    fn test_debug_request_by_header_or_path() {
+        let c = cfg();
+        let sig = sign("s3cret", "GET", "/a", NOW);
+        let ts = NOW.to_string();
+        assert!(is_debug_request(&c, "GET", "/a", Some(&sig), Some(&ts), NOW));
+        assert!(!is_debug_request(&c, "GET", "/a", Some(&sig), None, NOW));
+        assert!(!is_debug_request(&c, "GET", "/a", Some(&sig), Some("yesterday"), NOW));
+        assert!(!is_debug_request(&c, "GET", "/a", Some(&sig), Some(&ts), NOW + 3600));
+        assert!(!is_debug_request(&c, "GET", "/a", Some("deadbeef"), Some(&ts), NOW));
+        assert!(!is_debug_request(&c, "GET", "/a", None, Some(&ts), NOW));
+        assert!(is_debug_request(&c, "GET", "/internal/x", None, None, NOW));
+
+        let disabled = DebugConfig { enabled: false, ..cfg() };
+        assert!(!is_debug_request(&disabled, "GET", "/internal/x", Some(&sig), Some(&ts), NOW));
+    }
+
+    #[test]
+    fn test_decision_headers_and_dry_run_flag() {
+        let d = Decision { upstream: Some("127.0.0.1:8080".into()), retries: 2, ..Decision::default() };
+        let mut resp = ResponseHeader::build(200, None).unwrap();
+        d.apply_headers(&mut resp).unwrap();
+        assert_eq!(resp.headers.get("X-Gateway-Upstream").unwrap(), "127.0.0.1:8080");
+        assert_eq!(resp.headers.get("X-Gateway-Retries").unwrap(), "2");
+        assert_eq!(resp.headers.get("X-Gateway-Cache").unwrap(), "BYPASS");
+
+        assert!(is_dry_run(Some("1")));
+        assert!(is_dry_run(Some("TRUE")));
+        assert!(!is_dry_run(Some("no")));
+        assert!(!is_dry_run(None));
+    }
+}
@@ -0,0 +1,311 @@
+//! Kubernetes 服务发现：watch 带注解的 Service 及其 EndpointSlice，上游池随 Deployment 扩缩容自动更新。
+//! - Service 注解 `api-proxy.dexcode.io/upstream: "true"` 纳入上游池，`api-proxy.dexcode.io/port`
+//!   指定端口名或端口号（默认 EndpointSlice 的第一个端口）；
+//! - 只使用 ready 且非 terminating 的 endpoint；
+//! - 首次同步完成前沿用 config.json 中的静态 upstreams。
+//!
+//! watch 部分需要 `k8s` feature；`KubernetesDiscovery` 本身只保存最近一次同步结果，
+//! 由 Pingora 的 `LoadBalancer` 按 `update_frequency` 拉取。
+
+use std::collections::{BTreeSet, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use arc_swap::ArcSwapOption;
+use async_trait::async_trait;
+use pingora_load_balancing::discovery::ServiceDiscovery;
+use pingora_load_balancing::Backend;
+use tracing::info;
+
+use crate::observability::Metrics;
+
+#[cfg(feature = "k8s")]
+pub use watch::KubernetesWatcher;
+
+/// 带上游注解的 Service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedService {
+    pub namespace: String,
+    pub name: String,
+    /// 端口名或端口号
+    pub port: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlicePort {
+    pub name: Option<String>,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SliceEndpoint {
+    pub addresses: Vec<String>,
+    pub ready: bool,
+}
+
+/// EndpointSlice 中与上游相关的部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slice {
+    pub namespace: String,
+    /// `kubernetes.io/service-name` 标签
+    pub service: String,
+    pub ports: Vec<SlicePort>,
+    pub endpoints: Vec<SliceEndpoint>,
+}
+
+impl Slice {
+    fn port_for(&self, wanted: Option<&str>) -> Option<u16> {
+        match wanted {
+            None => self.ports.first().map(|p| p.port),
+            Some(w) => self
+                .ports
+                .iter()
+                .find(|p| p.name.as_deref() == Some(w) || p.port.to_string() == w)
+                .map(|p| p.port),
+        }
+    }
+}
+
+/// 注解 Service 的 ready endpoint 地址集合
+pub fn compute_backends(services: &[AnnotatedService], slices: &[Slice]) -> BTreeSet<Backend> {
+    let wanted: HashMap<(&str, &str), Option<&str>> =
+        services.iter().map(|s| ((s.namespace.as_str(), s.name.as_str()), s.port.as_deref())).collect();
+    let mut out = BTreeSet::new();
+    for slice in slices {
+        let Some(port_sel) = wanted.get(&(slice.namespace.as_str(), slice.service.as_str())) else { continue };
+        let Some(port) = slice.port_for(*port_sel) else { continue };
+        for ep in slice.endpoints.iter().filter(|e| e.ready) {
+            for ip in ep.addresses.iter().filter_map(|a| a.parse::<IpAddr>().ok()) {
+                if let Ok(b) = Backend::new(&SocketAddr::new(ip, port).to_string()) {
+                    out.insert(b);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 供 `Backends::new` 使用的服务发现；watch 任务通过 `publish` 写入最新结果
+#[derive(Clone)]
+pub struct KubernetesDiscovery {
+    discovered: Arc<ArcSwapOption<BTreeSet<Backend>>>,
+    fallback: Arc<BTreeSet<Backend>>,
+    metrics: Arc<Metrics>,
+}
+
+impl KubernetesDiscovery {
+    pub fn new(fallback: BTreeSet<Backend>) -> Self {
+        Self { discovered: Arc::new(ArcSwapOption::empty()), fallback: Arc::new(fallback), metrics: Metrics::detached() }
+    }
+
+    /// 发现的后端数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn publish(&self, backends: BTreeSet<Backend>) {
+        let changed = self.discovered.load().as_deref() != Some(&backends);
+        if changed {
+            let addrs: Vec<String> = backends.iter().map(|b| b.addr.to_string()).collect();
+            info!(event = "k8s_backends_updated", count = addrs.len(), backends = ?addrs, "kubernetes upstreams updated");
+        }
+        self.metrics.k8s_discovered_backends.set(backends.len() as i64);
+        self.discovered.store(Some(Arc::new(backends)));
+    }
+
+    /// 是否已完成首次同步
+    pub fn is_synced(&self) -> bool {
+        self.discovered.load().is_some()
+    }
+
+    pub fn current(&self) -> BTreeSet<Backend> {
+        match self.discovered.load_full() {
+            Some(b) => (*b).clone(),
+            None => (*self.fallback).clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ServiceDiscovery for KubernetesDiscovery {
+    async fn discover(&self) -> pingora_core::Result<(BTreeSet<Backend>, HashMap<u64, bool>)> {
+        Ok((self.current(), HashMap::new()))
+    }
+}
+
+#[cfg(feature = "k8s")]
+mod watch {
+    use futures::{stream, StreamExt};
+    use k8s_openapi::api::core::v1::Service;
+    use k8s_openapi::api::discovery::v1::EndpointSlice;
+    use kube::runtime::{reflector, watcher, WatchStreamExt};
+    use kube::{Api, Client, ResourceExt};
+    use pingora_core::server::ShutdownWatch;
+    use pingora_core::services::background::BackgroundService;
+    use tracing::warn;
+
+    use super::*;
+    use crate::config::KubernetesDiscoveryConfig;
+
+    const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+    /// 后台 watch 任务（Pingora background service）
+    pub struct KubernetesWatcher {
+        discovery: KubernetesDiscovery,
+        cfg: KubernetesDiscoveryConfig,
+    }
+
+    impl KubernetesWatcher {
+        pub fn new(discovery: KubernetesDiscovery, cfg: KubernetesDiscoveryConfig) -> Self {
+            Self { discovery, cfg }
+        }
+
+        async fn run(&self) {
+            let client = match Client::try_default().await {
+                Ok(c) => c,
+                Err(e) => {
+                    warn!(err = %e, "kubernetes client unavailable; keeping static upstreams");
+                    return;
+                }
+            };
+            let (services, slices): (Api<Service>, Api<EndpointSlice>) = match &self.cfg.namespace {
+                Some(ns) => (Api::namespaced(client.clone(), ns), Api::namespaced(client, ns)),
+                None => (Api::all(client.clone()), Api::all(client)),
+            };
+            let (svc_store, svc_writer) = reflector::store();
+            let (slice_store, slice_writer) = reflector::store();
+            // (来源, 是否为一次完整 list 的结束)
+            let svc_events = reflector(svc_writer, watcher(services, watcher::Config::default()))
+                .default_backoff()
+                .map(|e| ("services", e.map(|ev| matches!(ev, watcher::Event::InitDone)).map_err(|e| e.to_string())))
+                .boxed();
+            let slice_events = reflector(slice_writer, watcher(slices, watcher::Config::default().labels(SERVICE_NAME_LABEL)))
+                .default_backoff()
+                .map(|e| ("endpointslices", e.map(|ev| matches!(ev, watcher::Event::InitDone)).map_err(|e| e.to_string())))
+                .boxed();
+            let mut events = stream::select(svc_events, slice_events);
+            let (mut services_synced, mut slices_synced) = (false, false);
+            while let Some((source, event)) = events.next().await {
+                match event {
+                    Ok(init_done) => {
+                        if init_done {
+                            match source {
+                                "services" => services_synced = true,
+                                _ => slices_synced = true,
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!(source, err = %e, "kubernetes watch error");
+                        continue;
+                    }
+                }
+                if !(services_synced && slices_synced) {
+                    continue;
+                }
+                let annotated: Vec<AnnotatedService> =
+                    svc_store.state().iter().filter_map(|s| self.annotated(s)).collect();
+                let slices: Vec<Slice> = slice_store.state().iter().filter_map(|s| to_slice(s)).collect();
+                self.discovery.publish(compute_backends(&annotated, &slices));
+            }
+        }
+
+        fn annotated(&self, svc: &Service) -> Option<AnnotatedService> {
+            let annotations = svc.annotations();
+            if annotations.get(&self.cfg.annotation).map(String::as_str) != Some("true") {
+                return None;
+            }
+            Some(AnnotatedService {
+                namespace: svc.namespace().unwrap_or_default(),
+                name: svc.name_any(),
+                port: annotations.get(&self.cfg.port_annotation).cloned(),
+            })
+        }
+    }
+
+    fn to_slice(s: &EndpointSlice) -> Option<Slice> {
+        Some(Slice {
+            namespace: s.namespace().unwrap_or_default(),
+            service: s.labels().get(SERVICE_NAME_LABEL)?.clone(),
+            ports: s
+                .ports
+                .iter()
+                .flatten()
+                .filter_map(|p| Some(SlicePort { name: p.name.clone(), port: u16::try_from(p.port?).ok()? }))
+                .collect(),
+            endpoints: s
+                .endpoints
+                .iter()
+                .map(|e| SliceEndpoint {
+                    addresses: e.addresses.clone(),
+                    // 未声明 ready 视为 ready（与 kube-proxy 一致）
+                    ready: e.conditions.as_ref().is_none_or(|c| c.ready.unwrap_or(true) && !c.terminating.unwrap_or(false)),
+                })
+                .collect(),
+        })
+    }
+
+    #[async_trait]
+    impl BackgroundService for KubernetesWatcher {
+        async fn start(&self, mut shutdown: ShutdownWatch) {
+            info!(namespace = ?self.cfg.namespace, annotation = %self.cfg.annotation, "kubernetes discovery started");
+            tokio::select! {
+                _ = self.run() => {}
+                _ = shutdown.changed() => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn svc(name: &str, port: Option<&str>) -> AnnotatedService {
+        AnnotatedService { namespace: "default".into(), name: name.into(), port: port.map(str::to_string) }
+    }
+
+    fn slice(service: &str, ports: &[(Option<&str>, u16)], endpoints: &[(&str, bool)]) -> Slice {
+        Slice {
+            namespace: "default".into(),
+            service: service.into(),
+            ports: ports.iter().map(|(n, p)| SlicePort { name: n.map(str::to_string), port: *p }).collect(),
+            endpoints: endpoints.iter().map(|(a, r)| SliceEndpoint { addresses: vec![a.to_string()], ready: *r }).collect(),
+        }
+    }
+
+    fn addrs(backends: &BTreeSet<Backend>) -> Vec<String> {
+        backends.iter().map(|b| b.addr.to_string()).collect()
+    }
+
+    #[test]
+    fn only_ready_endpoints_of_annotated_services() {
+        let slices = vec![
+            slice("api", &[(Some("metrics"), 9090), (Some("http"), 8080)], &[("10.0.0.1", true), ("10.0.0.2", false)]),
+            slice("api", &[(Some("http"), 8080)], &[("10.0.0.3", true)]),
+            slice("other", &[(None, 80)], &[("10.0.1.1", true)]),
+        ];
+        let backends = compute_backends(&[svc("api", Some("http"))], &slices);
+        assert_eq!(addrs(&backends), vec!["10.0.0.1:8080", "10.0.0.3:8080"]);
+
+        // 未指定端口时取第一个；IPv6 地址正确拼接
+        let v6 = vec![slice("api", &[(None, 9090)], &[("fd00::1", true)])];
+        assert_eq!(addrs(&compute_backends(&[svc("api", None)], &v6)), vec!["[fd00::1]:9090"]);
+        assert!(compute_backends(&[svc("api", Some("grpc"))], &slices).is_empty());
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_static_until_synced() {
+        let fallback = BTreeSet::from([Backend::new("127.0.0.1:8080").unwrap()]);
+        let d = KubernetesDiscovery::new(fallback.clone());
+        assert!(!d.is_synced());
+        assert_eq!(d.discover().await.unwrap().0, fallback);
+
+        d.publish(BTreeSet::new());
+        assert!(d.is_synced());
+        assert!(d.discover().await.unwrap().0.is_empty());
+        d.publish(BTreeSet::from([Backend::new("10.0.0.1:8080").unwrap()]));
+        assert_eq!(addrs(&d.discover().await.unwrap().0), vec!["10.0.0.1:8080"]);
+    }
+}
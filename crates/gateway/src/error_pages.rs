@@ -0,0 +1,292 @@
+//! 租户自定义错误页：网关返回 429/502/503/504 时，按租户请求头与 Accept 协商选用租户配置的模板，替代默认的 JSON 错误 body。
+//! - 默认关闭；`error_pages.enabled` 为 true 时生效，admin 端口提供 `GET /error-pages` 与 `GET/PUT/DELETE /error-pages/:tenant`；
+//! - 运行时修改不写回 config.json，重启后恢复配置文件中的模板；
+//! - 模板大小与数量受 `max_body_bytes` / `max_pages_per_tenant` 限制；占位符只替换为网关生成的值（错误码、请求 ID 等），不包含请求内容。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::config::{ErrorPage, ErrorPagesConfig};
+
+/// 可配置模板的状态码
+pub const PAGE_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+/// 默认错误 body 的类型；客户端更偏好它时不使用租户模板
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorPageError {
+    pub tenant: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ErrorPageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid error pages for tenant {}: {}", self.tenant, self.reason)
+    }
+}
+
+impl std::error::Error for ErrorPageError {}
+
+/// 模板占位符的取值
+#[derive(Debug, Clone)]
+pub struct PageVars<'a> {
+    pub status: u16,
+    pub error: &'a str,
+    pub message: &'a str,
+    pub request_id: Uuid,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedPage {
+    pub content_type: String,
+    pub body: String,
+}
+
+pub struct ErrorPages {
+    enabled: bool,
+    tenant_header: String,
+    max_body_bytes: usize,
+    max_pages_per_tenant: usize,
+    tenants: ArcSwap<HashMap<String, Vec<ErrorPage>>>,
+}
+
+impl ErrorPages {
+    pub fn from_config(cfg: &ErrorPagesConfig) -> Result<Self, ErrorPageError> {
+        let pages = Self {
+            enabled: cfg.enabled,
+            tenant_header: cfg.tenant_header.clone(),
+            max_body_bytes: cfg.max_body_bytes,
+            max_pages_per_tenant: cfg.max_pages_per_tenant,
+            tenants: ArcSwap::from_pointee(HashMap::new()),
+        };
+        for (tenant, list) in &cfg.tenants {
+            pages.validate(tenant, list)?;
+        }
+        pages.tenants.store(Arc::new(cfg.tenants.clone()));
+        Ok(pages)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn tenant_header(&self) -> &str {
+        &self.tenant_header
+    }
+
+    pub fn all(&self) -> HashMap<String, Vec<ErrorPage>> {
+        self.tenants.load().as_ref().clone()
+    }
+
+    pub fn pages(&self, tenant: &str) -> Option<Vec<ErrorPage>> {
+        self.tenants.load().get(tenant).cloned()
+    }
+
+    /// 整体替换某租户的模板；校验失败时保持原模板不变
+    pub fn set_pages(&self, tenant: &str, pages: Vec<ErrorPage>) -> Result<(), ErrorPageError> {
+        self.validate(tenant, &pages)?;
+        self.tenants.rcu(|current| {
+            let mut next = HashMap::clone(current);
+            next.insert(tenant.to_string(), pages.clone());
+            next
+        });
+        Ok(())
+    }
+
+    /// 删除某租户的模板；返回之前是否存在
+    pub fn remove(&self, tenant: &str) -> bool {
+        let previous = self.tenants.rcu(|current| {
+            let mut next = HashMap::clone(current);
+            next.remove(tenant);
+            next
+        });
+        previous.contains_key(tenant)
+    }
+
+    /// 为 `tenant` 选出与 `accept` 最匹配的 `vars.status` 模板并渲染；未启用、无模板或客户端更偏好 JSON 时返回 None
+    pub fn render(&self, tenant: Option<&str>, accept: Option<&str>, vars: &PageVars) -> Option<RenderedPage> {
+        if !self.enabled {
+            return None;
+        }
+        let tenants = self.tenants.load();
+        let pages = tenants.get(tenant?)?;
+        let page = negotiate(pages.iter().filter(|p| p.status == vars.status), accept)?;
+        Some(RenderedPage { content_type: page.content_type.clone(), body: render_template(&page.body, vars) })
+    }
+
+    fn validate(&self, tenant: &str, pages: &[ErrorPage]) -> Result<(), ErrorPageError> {
+        let invalid = |reason: String| Err(ErrorPageError { tenant: tenant.to_string(), reason });
+        if tenant.trim().is_empty() {
+            return invalid("tenant must not be empty".into());
+        }
+        if pages.len() > self.max_pages_per_tenant {
+            return invalid(format!("at most {} pages per tenant", self.max_pages_per_tenant));
+        }
+        for (i, page) in pages.iter().enumerate() {
+            if !PAGE_STATUSES.contains(&page.status) {
+                return invalid(format!("pages[{}]: status must be one of {:?}", i, PAGE_STATUSES));
+            }
+            if page.body.len() > self.max_body_bytes {
+                return invalid(format!("pages[{}]: body exceeds {} bytes", i, self.max_body_bytes));
+            }
+            let media = media_type(&page.content_type);
+            if !media.contains('/') || page.content_type.chars().any(|c| c.is_control()) {
+                return invalid(format!("pages[{}]: invalid content_type {:?}", i, page.content_type));
+            }
+            if pages[..i].iter().any(|p| p.status == page.status && media_type(&p.content_type).eq_ignore_ascii_case(media)) {
+                return invalid(format!("pages[{}]: duplicate {} {}", i, page.status, media));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `text/html; charset=utf-8` -> `text/html`
+fn media_type(content_type: &str) -> &str {
+    content_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// Accept 中 `media` 的 q 值（取最具体的匹配项）；未列出为 0，没有 Accept 时为 1
+fn accept_q(accept: Option<&str>, media: &str) -> f32 {
+    let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else { return 1.0 };
+    let main_type = media.split('/').next().unwrap_or_default();
+    let mut best: Option<(u8, f32)> = None;
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let range = params.next().unwrap_or_default().trim();
+        let q = params.find_map(|p| p.trim().strip_prefix("q=")).and_then(|q| q.trim().parse().ok()).unwrap_or(1.0);
+        let specificity = if range.eq_ignore_ascii_case(media) {
+            2
+        } else if range.strip_suffix("/*").is_some_and(|t| t.eq_ignore_ascii_case(main_type)) {
+            1
+        } else if range == "*/*" {
+            0
+        } else {
+            continue;
+        };
+        if best.is_none_or(|(s, _)| specificity > s) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map_or(0.0, |(_, q)| q)
+}
+
+/// q 最高的模板（相同时取先配置的）；低于默认 JSON 的 q 时不选
+fn negotiate<'a>(pages: impl Iterator<Item = &'a ErrorPage>, accept: Option<&str>) -> Option<&'a ErrorPage> {
+    let mut best: Option<(&ErrorPage, f32)> = None;
+    for page in pages {
+        let q = accept_q(accept, media_type(&page.content_type));
+        if q > 0.0 && best.is_none_or(|(_, b)| q > b) {
+            best = Some((page, q));
+        }
+    }
+    let (page, q) = best?;
+    let is_json = media_type(&page.content_type).eq_ignore_ascii_case(DEFAULT_MEDIA_TYPE);
+    (is_json || q >= accept_q(accept, DEFAULT_MEDIA_TYPE)).then_some(page)
+}
+
+fn render_template(template: &str, vars: &PageVars) -> String {
+    template
+        .replace("{{status}}", &vars.status.to_string())
+        .replace("{{error}}", vars.error)
+        .replace("{{message}}", vars.message)
+        .replace("{{request_id}}", &vars.request_id.to_string())
+        .replace("{{retry_after}}", &vars.retry_after.map(|s| s.to_string()).unwrap_or_default())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TenantPages {
+    pages: Vec<ErrorPage>,
+}
+
+/// `GET /error-pages`：全部租户；`GET/PUT/DELETE /error-pages/:tenant`：查看、整体替换与删除某租户的模板
+pub fn admin_routes(error_pages: Arc<ErrorPages>) -> Router {
+    let (get_pages, put_pages, delete_pages) = (Arc::clone(&error_pages), Arc::clone(&error_pages), Arc::clone(&error_pages));
+    Router::new()
+        .route("/error-pages", get(move || async move { Json(error_pages.all()) }))
+        .route(
+            "/error-pages/:tenant",
+            get(move |Path(tenant): Path<String>| async move {
+                get_pages.pages(&tenant).map(|pages| Json(TenantPages { pages })).ok_or(StatusCode::NOT_FOUND)
+            })
+            .put(move |Path(tenant): Path<String>, Json(input): Json<TenantPages>| async move {
+                let count = input.pages.len();
+                put_pages.set_pages(&tenant, input.pages).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+                info!(event = "error_pages_changed", tenant = %tenant, pages = count, "tenant error pages replaced");
+                Ok::<_, (StatusCode, String)>(Json(TenantPages { pages: put_pages.pages(&tenant).unwrap_or_default() }))
+            })
+            .delete(move |Path(tenant): Path<String>| async move {
+                if !delete_pages.remove(&tenant) {
+                    return StatusCode::NOT_FOUND;
+                }
+                info!(event = "error_pages_changed", tenant = %tenant, pages = 0, "tenant error pages removed");
+                StatusCode::NO_CONTENT
+            }),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(status: u16, content_type: &str, body: &str) -> ErrorPage {
+        ErrorPage { status, content_type: content_type.into(), body: body.into() }
+    }
+
+    fn error_pages(tenants: HashMap<String, Vec<ErrorPage>>) -> ErrorPages {
+        ErrorPages::from_config(&ErrorPagesConfig { enabled: true, tenants, ..Default::default() }).unwrap()
+    }
+
+    fn vars(status: u16) -> PageVars<'static> {
+        PageVars { status, error: "rate_limited", message: "Too many requests", request_id: Uuid::nil(), retry_after: Some(3) }
+    }
+
+    #[test]
+    fn negotiates_by_accept_and_falls_back_to_json() {
+        let pages = error_pages(HashMap::from([(
+            "acme".to_string(),
+            vec![page(429, "text/html; charset=utf-8", "<h1>{{status}} {{error}}</h1><p>retry in {{retry_after}}s</p>"), page(429, "text/plain", "slow down")],
+        )]));
+        let html = pages.render(Some("acme"), Some("text/html,application/xhtml+xml,*/*;q=0.8"), &vars(429)).unwrap();
+        assert_eq!(html.body, "<h1>429 rate_limited</h1><p>retry in 3s</p>");
+        assert_eq!(html.content_type, "text/html; charset=utf-8");
+        assert_eq!(pages.render(Some("acme"), Some("text/plain"), &vars(429)).unwrap().body, "slow down");
+        // 没有 Accept 时取第一个模板
+        assert!(pages.render(Some("acme"), None, &vars(429)).unwrap().content_type.starts_with("text/html"));
+        // 客户端要 JSON、其他租户或其他状态码：使用默认 JSON
+        assert!(pages.render(Some("acme"), Some("application/json"), &vars(429)).is_none());
+        assert!(pages.render(Some("acme"), Some("application/json, text/html;q=0.5"), &vars(429)).is_none());
+        assert!(pages.render(Some("other"), None, &vars(429)).is_none());
+        assert!(pages.render(Some("acme"), None, &vars(503)).is_none());
+        assert!(pages.render(None, None, &vars(429)).is_none());
+    }
+
+    #[test]
+    fn enforces_limits_and_keeps_previous_pages() {
+        let pages = ErrorPages::from_config(&ErrorPagesConfig { enabled: true, max_body_bytes: 16, max_pages_per_tenant: 2, ..Default::default() }).unwrap();
+        pages.set_pages("acme", vec![page(503, "text/plain", "down")]).unwrap();
+        for bad in [
+            vec![page(500, "text/plain", "x")],
+            vec![page(503, "text/plain", "this body is way too long")],
+            vec![page(503, "plain", "x")],
+            vec![page(503, "text/plain", "a"), page(503, "text/plain; charset=utf-8", "b")],
+            vec![page(429, "text/plain", "a"), page(502, "text/plain", "b"), page(504, "text/plain", "c")],
+        ] {
+            assert!(pages.set_pages("acme", bad).is_err());
+        }
+        assert_eq!(pages.pages("acme"), Some(vec![page(503, "text/plain", "down")]));
+        assert!(pages.remove("acme"));
+        assert!(!pages.remove("acme"));
+    }
+}
@@ -0,0 +1,118 @@
+//! 网关自身产生的错误响应（限流、熔断、无可用上游、过载）：以 JSON body 返回机器可读的错误码，
+//! 客户端按 `error` 分支处理、按 `retry_after` 退避，`request_id` 与响应头 `X-Request-Id` 一致，便于对照网关日志。
+//!
+//! ```json
+//! {"error":"rate_limited","message":"Too many requests","retry_after":3,"request_id":"…"}
+//! ```
+
+use std::time::Duration;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatewayErrorCode {
+    /// 限流（429）
+    RateLimited,
+    /// 熔断器打开（503）
+    CircuitOpen,
+    /// 重试后仍没有可用上游（503）
+    NoUpstream,
+    /// 过载保护丢弃（503）
+    Overloaded,
+    /// 上游并发隔离已满（503）
+    BulkheadFull,
+}
+
+impl GatewayErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GatewayErrorCode::RateLimited => "rate_limited",
+            GatewayErrorCode::CircuitOpen => "circuit_open",
+            GatewayErrorCode::NoUpstream => "no_upstream",
+            GatewayErrorCode::Overloaded => "overloaded",
+            GatewayErrorCode::BulkheadFull => "bulkhead_full",
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        match self {
+            GatewayErrorCode::RateLimited => 429,
+            _ => 503,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            GatewayErrorCode::RateLimited => "Too many requests",
+            GatewayErrorCode::CircuitOpen => "Upstream temporarily unavailable (circuit open)",
+            GatewayErrorCode::NoUpstream => "No healthy upstream available",
+            GatewayErrorCode::Overloaded => "Gateway overloaded, request shed",
+            GatewayErrorCode::BulkheadFull => "Upstream concurrency limit reached",
+        }
+    }
+}
+
+impl std::fmt::Display for GatewayErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorBody {
+    pub error: &'static str,
+    pub message: &'static str,
+    /// 秒；与 Retry-After 响应头相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    pub request_id: String,
+}
+
+impl ErrorBody {
+    pub fn new(code: GatewayErrorCode, retry_after: Option<u64>, request_id: Uuid) -> Self {
+        Self { error: code.as_str(), message: code.message(), retry_after, request_id: request_id.to_string() }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// 上游失败（502/504 等）的错误码与说明，用于租户错误页模板
+pub fn upstream_error(status: u16) -> (&'static str, &'static str) {
+    match status {
+        502 => ("bad_gateway", "Invalid response from upstream"),
+        504 => ("gateway_timeout", "Upstream timed out"),
+        _ => ("gateway_error", "Gateway error"),
+    }
+}
+
+/// Retry-After 秒数：向上取整，至少 1 秒
+pub fn retry_after_secs(d: Duration) -> u64 {
+    (d.as_nanos().div_ceil(1_000_000_000) as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_is_machine_readable_json() {
+        let id = Uuid::new_v4();
+        let body: serde_json::Value = serde_json::from_str(&ErrorBody::new(GatewayErrorCode::RateLimited, Some(3), id).to_json()).unwrap();
+        assert_eq!(body["error"], "rate_limited");
+        assert_eq!(body["retry_after"], 3);
+        assert_eq!(body["request_id"], id.to_string());
+        assert!(body["message"].is_string());
+
+        let body: serde_json::Value = serde_json::from_str(&ErrorBody::new(GatewayErrorCode::NoUpstream, None, id).to_json()).unwrap();
+        assert_eq!(body["error"], "no_upstream");
+        assert!(body.get("retry_after").is_none());
+        assert_eq!(GatewayErrorCode::NoUpstream.status(), 503);
+        assert_eq!(retry_after_secs(Duration::ZERO), 1);
+        assert_eq!(retry_after_secs(Duration::from_millis(2_100)), 3);
+    }
+}
@@ -0,0 +1,292 @@
+//! 上游故障转移：路由声明主/备两个上游分组，主分组没有健康节点或熔断器打开时，
+//! 请求自动转发到备用分组（例如静态维护页服务或另一个地域），主分组恢复后切回。
+//! - `default` 分组即 `upstreams`，沿用网关的熔断器；其他分组各自健康检查与熔断（指标 upstream 标签为分组名）；
+//! - 每条路由只在状态切换时记录一次 `upstream_failover` / `upstream_restored` 事件，
+//!   并更新 `api_proxy_failover_active{route}` 与 `api_proxy_failover_transitions_total{route,to}`；
+//! - 熔断器在 open 的恢复窗口内视为不可用，窗口结束后请求回到主分组，由熔断器 half-open 试探。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use pingora_load_balancing::selection::RoundRobin;
+use pingora_load_balancing::{Backend, LoadBalancer};
+
+use crate::circuit_breaker::{CircuitBreaker, DEFAULT_UPSTREAM};
+use crate::config::{FailoverConfig, FailoverRoute};
+
+/// `upstreams` 对应的分组名
+pub const DEFAULT_GROUP: &str = DEFAULT_UPSTREAM;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverError {
+    pub subject: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for FailoverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid failover config {}: {}", self.subject, self.reason)
+    }
+}
+
+impl std::error::Error for FailoverError {}
+
+/// 启动时校验分组与路由；分组地址须为 `ip:port`
+pub fn validate(cfg: &FailoverConfig) -> Result<(), FailoverError> {
+    let invalid = |subject: &str, reason: &str| Err(FailoverError { subject: subject.to_string(), reason: reason.to_string() });
+    for (name, addrs) in &cfg.groups {
+        if name.is_empty() || name == DEFAULT_GROUP {
+            return invalid(name, "group name must be non-empty and not `default`");
+        }
+        if addrs.is_empty() {
+            return invalid(name, "group must list at least one upstream");
+        }
+        if let Some(addr) = addrs.iter().find(|a| a.parse::<std::net::SocketAddr>().is_err()) {
+            return invalid(name, &format!("upstream {} is not ip:port", addr));
+        }
+    }
+    let known = |group: &str| group == DEFAULT_GROUP || cfg.groups.contains_key(group);
+    for (i, route) in cfg.routes.iter().enumerate() {
+        let subject = route.path_prefix.as_str();
+        if !subject.starts_with('/') {
+            return invalid(subject, "path_prefix must start with '/'");
+        }
+        if cfg.routes[..i].iter().any(|r| r.path_prefix == route.path_prefix) {
+            return invalid(subject, "duplicate path_prefix");
+        }
+        if let Some(group) = [&route.primary, &route.fallback].into_iter().find(|g| !known(g)) {
+            return invalid(subject, &format!("unknown upstream group {}", group));
+        }
+        if route.primary == route.fallback {
+            return invalid(subject, "primary and fallback must differ");
+        }
+    }
+    Ok(())
+}
+
+/// 一个上游分组：负载均衡（含健康检查结果）与熔断器
+pub struct UpstreamGroup {
+    pub name: String,
+    pub balancer: Arc<LoadBalancer<RoundRobin>>,
+    pub breaker: CircuitBreaker,
+}
+
+impl UpstreamGroup {
+    pub fn new(name: impl Into<String>, balancer: Arc<LoadBalancer<RoundRobin>>, breaker: CircuitBreaker) -> Self {
+        Self { name: name.into(), balancer, breaker }
+    }
+
+    pub fn healthy(&self) -> usize {
+        let backends = self.balancer.backends();
+        backends.get_backend().iter().filter(|b| backends.ready(b)).count()
+    }
+
+    /// 处于 open 的恢复窗口内（half-open 不算，试探请求仍发往本分组）
+    pub fn breaker_open(&self) -> bool {
+        self.breaker.retry_after() > Duration::ZERO
+    }
+
+    /// 优先选择健康且未被 `accept` 排除的节点，都不满足时退回普通轮询
+    pub fn select(&self, accept: impl Fn(&Backend) -> bool) -> Option<Backend> {
+        self.balancer
+            .select_with(b"", 256, |backend, healthy| healthy && accept(backend))
+            .or_else(|| self.balancer.select(b"", 256))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverReason {
+    NoHealthyPeers,
+    CircuitOpen,
+}
+
+impl FailoverReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailoverReason::NoHealthyPeers => "no_healthy_peers",
+            FailoverReason::CircuitOpen => "circuit_open",
+        }
+    }
+}
+
+/// 主分组不可用的原因；可用时为 None
+pub fn failover_reason(healthy: usize, breaker_open: bool) -> Option<FailoverReason> {
+    if healthy == 0 {
+        Some(FailoverReason::NoHealthyPeers)
+    } else if breaker_open {
+        Some(FailoverReason::CircuitOpen)
+    } else {
+        None
+    }
+}
+
+/// 本次判断引起的路由状态切换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// 切到备用分组
+    Failover(FailoverReason),
+    /// 主分组恢复，切回
+    Restore,
+}
+
+/// 命中故障转移路由的请求应转发到的分组
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Routing {
+    pub route: String,
+    pub primary: String,
+    pub fallback: String,
+    /// 本次选用的分组（primary 或 fallback）
+    pub group: String,
+    pub transition: Option<Transition>,
+}
+
+impl Routing {
+    pub fn failed_over(&self) -> bool {
+        self.group == self.fallback
+    }
+}
+
+pub struct Failover {
+    enabled: bool,
+    routes: Vec<FailoverRoute>,
+    groups: HashMap<String, UpstreamGroup>,
+    /// path_prefix -> 是否已切到备用分组
+    active: HashMap<String, AtomicBool>,
+}
+
+impl Failover {
+    /// `groups` 须包含 `default` 与配置中的全部分组（由 bootstrap 构建并启动健康检查）
+    pub fn new(cfg: &FailoverConfig, groups: Vec<UpstreamGroup>) -> Result<Self, FailoverError> {
+        validate(cfg)?;
+        let groups: HashMap<String, UpstreamGroup> = groups.into_iter().map(|g| (g.name.clone(), g)).collect();
+        if let Some(route) = cfg.routes.iter().find(|r| !groups.contains_key(&r.primary) || !groups.contains_key(&r.fallback)) {
+            return Err(FailoverError { subject: route.path_prefix.clone(), reason: "upstream group was not built".to_string() });
+        }
+        let active = cfg.routes.iter().map(|r| (r.path_prefix.clone(), AtomicBool::new(false))).collect();
+        Ok(Self { enabled: cfg.enabled, routes: cfg.routes.clone(), groups, active })
+    }
+
+    /// 未启用：所有请求使用 `upstreams`
+    pub fn disabled() -> Self {
+        Self { enabled: false, routes: Vec::new(), groups: HashMap::new(), active: HashMap::new() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn group(&self, name: &str) -> Option<&UpstreamGroup> {
+        self.groups.get(name)
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &UpstreamGroup> {
+        self.groups.values()
+    }
+
+    /// 按最长 path_prefix 命中路由并决定分组；未启用或未命中时为 None（使用 `upstreams`）
+    pub fn route(&self, path: &str) -> Option<Routing> {
+        if !self.enabled {
+            return None;
+        }
+        let rule = self.routes.iter().filter(|r| path.starts_with(r.path_prefix.as_str())).max_by_key(|r| r.path_prefix.len())?;
+        let primary = self.groups.get(&rule.primary)?;
+        let reason = failover_reason(primary.healthy(), primary.breaker_open());
+        let was_active = self.active.get(&rule.path_prefix)?.swap(reason.is_some(), Ordering::AcqRel);
+        let transition = match (was_active, reason) {
+            (false, Some(reason)) => Some(Transition::Failover(reason)),
+            (true, None) => Some(Transition::Restore),
+            _ => None,
+        };
+        let group = if reason.is_some() { &rule.fallback } else { &rule.primary };
+        Some(Routing { route: rule.path_prefix.clone(), primary: rule.primary.clone(), fallback: rule.fallback.clone(), group: group.clone(), transition })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(name: &str, addrs: &[&str]) -> UpstreamGroup {
+        let lb = LoadBalancer::<RoundRobin>::try_from_iter(addrs.iter().copied()).expect("lb");
+        UpstreamGroup::new(name, Arc::new(lb), CircuitBreaker::for_upstream(name, 2, Duration::from_secs(30), 1, true))
+    }
+
+    fn config() -> FailoverConfig {
+        FailoverConfig {
+            enabled: true,
+            groups: HashMap::from([("maintenance".to_string(), vec!["127.0.0.1:9001".to_string()])]),
+            routes: vec![FailoverRoute { path_prefix: "/api".into(), primary: DEFAULT_GROUP.into(), fallback: "maintenance".into() }],
+        }
+    }
+
+    #[test]
+    fn validates_groups_and_routes() {
+        assert!(validate(&config()).is_ok());
+        let mut cfg = config();
+        cfg.routes[0].fallback = "eu-west".into();
+        assert!(validate(&cfg).unwrap_err().reason.contains("unknown upstream group"));
+        let mut cfg = config();
+        cfg.routes[0].fallback = DEFAULT_GROUP.into();
+        assert!(validate(&cfg).is_err());
+        let mut cfg = config();
+        cfg.groups.insert("bad".into(), vec!["localhost".into()]);
+        assert!(validate(&cfg).is_err());
+        let mut cfg = config();
+        cfg.groups.insert(DEFAULT_GROUP.into(), vec!["127.0.0.1:9002".into()]);
+        assert!(validate(&cfg).is_err());
+    }
+
+    #[test]
+    fn reason_prefers_missing_peers_over_open_breaker() {
+        assert_eq!(failover_reason(2, false), None);
+        assert_eq!(failover_reason(0, true), Some(FailoverReason::NoHealthyPeers));
+        assert_eq!(failover_reason(1, true), Some(FailoverReason::CircuitOpen));
+    }
+
+    fn failover() -> Failover {
+        Failover::new(&config(), vec![group(DEFAULT_GROUP, &["127.0.0.1:9000"]), group("maintenance", &["127.0.0.1:9001"])]).unwrap()
+    }
+
+    #[test]
+    fn open_breaker_fails_over_once() {
+        let failover = failover();
+        assert_eq!(failover.route("/health"), None);
+        let routed = failover.route("/api/orders").unwrap();
+        assert_eq!((routed.group.as_str(), routed.transition), (DEFAULT_GROUP, None));
+
+        let breaker = &failover.group(DEFAULT_GROUP).unwrap().breaker;
+        breaker.record_failure();
+        breaker.record_failure();
+        let routed = failover.route("/api/orders").unwrap();
+        assert!(routed.failed_over());
+        assert_eq!(routed.transition, Some(Transition::Failover(FailoverReason::CircuitOpen)));
+        assert_eq!(failover.route("/api/orders").unwrap().transition, None);
+    }
+
+    #[test]
+    fn restores_when_primary_peers_recover() {
+        let failover = failover();
+        let primary = failover.group(DEFAULT_GROUP).unwrap();
+        let peer = primary.balancer.backends().get_backend().iter().next().cloned().unwrap();
+        primary.balancer.backends().set_enable(&peer, false);
+        let routed = failover.route("/api/orders").unwrap();
+        assert_eq!((routed.group.as_str(), routed.transition), ("maintenance", Some(Transition::Failover(FailoverReason::NoHealthyPeers))));
+
+        primary.balancer.backends().set_enable(&peer, true);
+        let routed = failover.route("/api/orders").unwrap();
+        assert_eq!((routed.group.as_str(), routed.transition), (DEFAULT_GROUP, Some(Transition::Restore)));
+        assert!(!routed.failed_over());
+    }
+
+    #[test]
+    fn disabled_routes_nothing() {
+        let mut cfg = config();
+        cfg.enabled = false;
+        let failover = Failover::new(&cfg, vec![group(DEFAULT_GROUP, &["127.0.0.1:9000"]), group("maintenance", &["127.0.0.1:9001"])]).unwrap();
+        assert!(!failover.is_enabled());
+        assert_eq!(failover.route("/api/orders"), None);
+        assert_eq!(Failover::disabled().route("/api"), None);
+    }
+}
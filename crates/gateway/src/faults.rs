@@ -0,0 +1,234 @@
+//! 故障注入（混沌测试）：按路由以一定比例返回错误响应、延迟处理或直接关闭连接，
+//! 用于验证客户端与网关自身重试/熔断的行为。
+//! - 默认关闭；`fault_injection.enabled` 为 true 时规则才生效，admin 端口才提供 `GET/PUT/DELETE /faults`；
+//! - 规则可在运行时通过 admin 接口整体替换（不写回 config.json，重启后恢复配置文件中的规则）；
+//! - 注入的响应带 `X-Gateway-Fault`，并计入 `api_proxy_faults_injected_total{route,fault}`，不计入上游错误指标。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::config::{FaultInjectionConfig, FaultRule};
+
+/// 标记注入故障的响应头，取值 abort / delay
+pub const FAULT_HEADER: &str = "X-Gateway-Fault";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultError {
+    pub path_prefix: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for FaultError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid fault rule {}: {}", self.path_prefix, self.reason)
+    }
+}
+
+impl std::error::Error for FaultError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultAction {
+    /// 不转发，返回该状态码
+    Abort(u16),
+    /// 不返回响应，关闭连接
+    Reset,
+}
+
+impl FaultAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultAction::Abort(_) => "abort",
+            FaultAction::Reset => "reset",
+        }
+    }
+}
+
+/// 某个请求命中的故障：先等待 `delay`，再执行 `action`（None 时正常转发）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultPlan {
+    pub route: String,
+    pub delay: Option<Duration>,
+    pub action: Option<FaultAction>,
+    pub count_as_upstream_failure: bool,
+}
+
+pub struct FaultInjector {
+    enabled: bool,
+    rules: ArcSwap<Vec<FaultRule>>,
+}
+
+impl FaultInjector {
+    pub fn from_config(cfg: &FaultInjectionConfig) -> Result<Self, FaultError> {
+        validate(&cfg.rules)?;
+        Ok(Self { enabled: cfg.enabled, rules: ArcSwap::from_pointee(cfg.rules.clone()) })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn rules(&self) -> Vec<FaultRule> {
+        self.rules.load().as_ref().clone()
+    }
+
+    /// 整体替换规则；任一规则无效时保持原规则不变
+    pub fn set_rules(&self, rules: Vec<FaultRule>) -> Result<(), FaultError> {
+        validate(&rules)?;
+        self.rules.store(Arc::new(rules));
+        Ok(())
+    }
+
+    /// 为请求掷骰决定注入的故障；未启用、未命中规则或本次不注入时返回 None
+    pub fn plan(&self, path: &str) -> Option<FaultPlan> {
+        if !self.enabled {
+            return None;
+        }
+        let rules = self.rules.load();
+        let rule = rules.iter().filter(|r| path.starts_with(r.path_prefix.as_str())).max_by_key(|r| r.path_prefix.len())?;
+        let plan = plan_with(rule, &mut rand::thread_rng());
+        (plan.delay.is_some() || plan.action.is_some()).then_some(plan)
+    }
+}
+
+fn plan_with(rule: &FaultRule, rng: &mut impl Rng) -> FaultPlan {
+    let delay = (rule.delay_percent > 0.0 && rng.gen_range(0.0..100.0) < rule.delay_percent)
+        .then(|| Duration::from_millis(rule.delay_ms + rng.gen_range(0..=rule.delay_jitter_ms)));
+    let roll = rng.gen_range(0.0..100.0);
+    let action = if roll < rule.reset_percent {
+        Some(FaultAction::Reset)
+    } else if roll < rule.reset_percent + rule.abort_percent {
+        Some(FaultAction::Abort(rule.abort_status))
+    } else {
+        None
+    };
+    FaultPlan { route: rule.path_prefix.clone(), delay, action, count_as_upstream_failure: rule.count_as_upstream_failure }
+}
+
+fn validate(rules: &[FaultRule]) -> Result<(), FaultError> {
+    for rule in rules {
+        let invalid = |reason: &str| Err(FaultError { path_prefix: rule.path_prefix.clone(), reason: reason.to_string() });
+        if !rule.path_prefix.starts_with('/') {
+            return invalid("path_prefix must start with '/'");
+        }
+        let percents = [rule.abort_percent, rule.reset_percent, rule.delay_percent];
+        if percents.iter().any(|p| !(0.0..=100.0).contains(p)) {
+            return invalid("percentages must be between 0 and 100");
+        }
+        if rule.abort_percent + rule.reset_percent > 100.0 {
+            return invalid("abort_percent + reset_percent must not exceed 100");
+        }
+        if !(400..=599).contains(&rule.abort_status) {
+            return invalid("abort_status must be a 4xx or 5xx status");
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FaultRules {
+    rules: Vec<FaultRule>,
+}
+
+/// `GET/PUT/DELETE /faults`：查看、整体替换与清空故障注入规则
+pub fn admin_routes(injector: Arc<FaultInjector>) -> Router {
+    let (put_injector, delete_injector) = (Arc::clone(&injector), Arc::clone(&injector));
+    Router::new().route(
+        "/faults",
+        get(move || async move { Json(FaultRules { rules: injector.rules() }) })
+            .put(move |Json(input): Json<FaultRules>| async move {
+                let count = input.rules.len();
+                put_injector.set_rules(input.rules).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+                info!(event = "fault_rules_changed", rules = count, "fault injection rules replaced");
+                Ok::<_, (StatusCode, String)>(Json(FaultRules { rules: put_injector.rules() }))
+            })
+            .delete(move || async move {
+                let _ = delete_injector.set_rules(Vec::new());
+                info!(event = "fault_rules_changed", rules = 0, "fault injection rules cleared");
+                StatusCode::NO_CONTENT
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn rule(prefix: &str) -> FaultRule {
+        FaultRule {
+            path_prefix: prefix.into(),
+            abort_percent: 0.0,
+            abort_status: 503,
+            reset_percent: 0.0,
+            delay_percent: 0.0,
+            delay_ms: 0,
+            delay_jitter_ms: 0,
+            count_as_upstream_failure: false,
+        }
+    }
+
+    fn injector(enabled: bool, rules: Vec<FaultRule>) -> FaultInjector {
+        FaultInjector::from_config(&FaultInjectionConfig { enabled, rules }).unwrap()
+    }
+
+    #[test]
+    fn picks_longest_prefix_and_respects_enabled() {
+        let always_abort = FaultRule { abort_percent: 100.0, abort_status: 500, ..rule("/api/orders") };
+        let faults = injector(true, vec![FaultRule { abort_percent: 100.0, ..rule("/api") }, always_abort.clone()]);
+        assert_eq!(faults.plan("/api/orders/1").unwrap().action, Some(FaultAction::Abort(500)));
+        assert_eq!(faults.plan("/api/users").unwrap().action, Some(FaultAction::Abort(503)));
+        assert!(faults.plan("/health").is_none());
+        assert!(injector(false, vec![always_abort]).plan("/api/orders").is_none());
+    }
+
+    #[test]
+    fn percentages_split_reset_and_abort() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let r = FaultRule { reset_percent: 20.0, abort_percent: 30.0, ..rule("/") };
+        let (mut resets, mut aborts) = (0, 0);
+        for _ in 0..10_000 {
+            match plan_with(&r, &mut rng).action {
+                Some(FaultAction::Reset) => resets += 1,
+                Some(FaultAction::Abort(_)) => aborts += 1,
+                None => {}
+            }
+        }
+        assert!((1_700..2_300).contains(&resets), "resets {}", resets);
+        assert!((2_700..3_300).contains(&aborts), "aborts {}", aborts);
+    }
+
+    #[test]
+    fn delay_includes_jitter() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let r = FaultRule { delay_percent: 100.0, delay_ms: 100, delay_jitter_ms: 50, ..rule("/") };
+        for _ in 0..100 {
+            let plan = plan_with(&r, &mut rng);
+            let delay = plan.delay.unwrap();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150));
+            assert!(plan.action.is_none());
+        }
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected_and_keep_previous() {
+        let faults = injector(true, vec![rule("/a")]);
+        for bad in [
+            FaultRule { abort_percent: 101.0, ..rule("/b") },
+            FaultRule { abort_percent: 60.0, reset_percent: 50.0, ..rule("/b") },
+            FaultRule { abort_status: 200, ..rule("/b") },
+            rule("b"),
+        ] {
+            assert!(faults.set_rules(vec![bad]).is_err());
+        }
+        assert_eq!(faults.rules(), vec![rule("/a")]);
+    }
+}
@@ -0,0 +1,250 @@
+//! GeoIP 标注与国家维度的访问策略。
+//! - 请求按客户端 IP 查询国家（ISO 代码）与 ASN，写入 RequestCtx、访问日志，并以 `X-Geo-Country` / `X-Geo-Asn` 传给上游；
+//! - 按最长 path_prefix 命中的规则放行/拒绝（403）或按客户端限流（429）；
+//! - 后台线程按 `reload_interval_secs` 检查库文件修改时间，变化后原子替换（加载失败时保留旧库）。
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwapOption;
+use maxminddb::{geoip2, Reader};
+use tracing::{info, warn};
+
+use crate::config::{GeoIpConfig, GeoRule};
+use crate::observability::Metrics;
+use crate::rate_limiter::TokenBucket;
+
+pub const GEO_COUNTRY_HEADER: &str = "X-Geo-Country";
+pub const GEO_ASN_HEADER: &str = "X-Geo-Asn";
+/// 查不到国家时用于规则匹配的代码
+pub const UNKNOWN_COUNTRY: &str = "unknown";
+
+/// 超过该数量时整体清空限流桶
+const BUCKET_PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeoInfo {
+    pub country: Option<String>,
+    pub asn: Option<u32>,
+    pub as_org: Option<String>,
+}
+
+impl GeoInfo {
+    pub fn country_code(&self) -> &str {
+        self.country.as_deref().unwrap_or(UNKNOWN_COUNTRY)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoDecision {
+    Allow,
+    Denied,
+    RateLimited,
+}
+
+/// 单个 mmdb 文件
+struct Db {
+    name: &'static str,
+    path: Option<PathBuf>,
+    reader: ArcSwapOption<Reader<Vec<u8>>>,
+    modified: Mutex<Option<SystemTime>>,
+    metrics: Arc<Metrics>,
+}
+
+impl Db {
+    fn new(name: &'static str, path: Option<&str>, metrics: Arc<Metrics>) -> Self {
+        let db = Self { name, path: path.map(PathBuf::from), reader: ArcSwapOption::empty(), modified: Mutex::new(None), metrics };
+        db.reload_if_changed();
+        db
+    }
+
+    /// 文件修改时间变化时重新加载；返回是否替换了库
+    fn reload_if_changed(&self) -> bool {
+        let Some(path) = &self.path else { return false };
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut last = self.modified.lock().unwrap_or_else(|e| e.into_inner());
+        if modified.is_none() || *last == modified {
+            return false;
+        }
+        *last = modified;
+        match Reader::open_readfile(path) {
+            Ok(reader) => {
+                info!(event = "geoip_db_loaded", db = self.name, path = %path.display(), build_epoch = reader.metadata.build_epoch, "GeoIP database loaded");
+                self.reader.store(Some(Arc::new(reader)));
+                self.metrics.geoip_db_reloads_total.with_label_values(&[self.name, "ok"]).inc();
+                true
+            }
+            Err(e) => {
+                warn!(event = "geoip_db_load_failed", db = self.name, path = %path.display(), error = %e, "GeoIP database load failed; keeping previous");
+                self.metrics.geoip_db_reloads_total.with_label_values(&[self.name, "error"]).inc();
+                false
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Db {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Db").field("name", &self.name).field("path", &self.path).field("loaded", &self.reader.load().is_some()).finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct GeoIp {
+    cfg: GeoIpConfig,
+    country: Db,
+    asn: Db,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    metrics: Arc<Metrics>,
+}
+
+impl GeoIp {
+    pub fn new(cfg: GeoIpConfig) -> Self {
+        Self::with_metrics(cfg, Metrics::detached())
+    }
+
+    /// 库在构造时即加载，因此指标实例随构造注入（首次加载也计入）
+    pub fn with_metrics(cfg: GeoIpConfig, metrics: Arc<Metrics>) -> Self {
+        let db = |name, path: Option<&str>| Db::new(name, path.filter(|_| cfg.enabled), Arc::clone(&metrics));
+        let (country, asn) = (db("country", cfg.country_db.as_deref()), db("asn", cfg.asn_db.as_deref()));
+        Self { cfg, country, asn, buckets: Mutex::new(HashMap::new()), metrics }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.cfg.enabled
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> GeoInfo {
+        let mut info = GeoInfo::default();
+        if let Some(reader) = self.country.reader.load_full() {
+            if let Ok(c) = reader.lookup::<geoip2::Country>(ip) {
+                info.country = c.country.and_then(|c| c.iso_code).map(str::to_string);
+            }
+        }
+        if let Some(reader) = self.asn.reader.load_full() {
+            if let Ok(a) = reader.lookup::<geoip2::Asn>(ip) {
+                info.asn = a.autonomous_system_number;
+                info.as_org = a.autonomous_system_organization.map(str::to_string);
+            }
+        }
+        info
+    }
+
+    fn rule_for(&self, path: &str) -> Option<&GeoRule> {
+        self.cfg.rules.iter().filter(|r| path.starts_with(r.path_prefix.as_str())).max_by_key(|r| r.path_prefix.len())
+    }
+
+    pub fn check(&self, path: &str, geo: &GeoInfo, client: &str) -> GeoDecision {
+        let Some(rule) = self.rule_for(path) else { return GeoDecision::Allow };
+        let country = geo.country_code();
+        let listed = |list: &[String]| list.iter().any(|c| c.eq_ignore_ascii_case(country));
+        let decision = if (!rule.allow_countries.is_empty() && !listed(&rule.allow_countries)) || listed(&rule.deny_countries) {
+            GeoDecision::Denied
+        } else {
+            match &rule.rate_limit {
+                Some(rl) if rl.countries.is_empty() || listed(&rl.countries) => {
+                    let key = format!("{}|{}", rule.path_prefix, client);
+                    let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+                    if buckets.len() >= BUCKET_PRUNE_THRESHOLD && !buckets.contains_key(&key) {
+                        buckets.clear();
+                    }
+                    let bucket = buckets.entry(key).or_insert_with(|| TokenBucket::new(rl.burst.max(1), rl.requests_per_second.max(1)));
+                    if bucket.try_acquire(1) { GeoDecision::Allow } else { GeoDecision::RateLimited }
+                }
+                _ => GeoDecision::Allow,
+            }
+        };
+        match decision {
+            GeoDecision::Denied => self.metrics.geoip_rejected_total.with_label_values(&[country, "denied"]).inc(),
+            GeoDecision::RateLimited => self.metrics.geoip_rejected_total.with_label_values(&[country, "rate_limited"]).inc(),
+            GeoDecision::Allow => {}
+        }
+        decision
+    }
+
+    pub fn reload_changed(&self) {
+        self.country.reload_if_changed();
+        self.asn.reload_if_changed();
+    }
+
+    /// 后台线程检查库文件变化
+    pub fn spawn_reloader(self: &Arc<Self>) {
+        if !self.cfg.enabled || (self.cfg.country_db.is_none() && self.cfg.asn_db.is_none()) {
+            return;
+        }
+        let this = self.clone();
+        let interval = Duration::from_secs(self.cfg.reload_interval_secs.max(1));
+        let spawned = std::thread::Builder::new().name("geoip-reloader".into()).spawn(move || loop {
+            std::thread::sleep(interval);
+            this.reload_changed();
+        });
+        if let Err(e) = spawned {
+            warn!(error = %e, "failed to spawn geoip reloader; databases will not hot-reload");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GeoRateLimit;
+
+    fn geo(country: Option<&str>) -> GeoInfo {
+        GeoInfo { country: country.map(str::to_string), ..Default::default() }
+    }
+
+    fn rule(prefix: &str) -> GeoRule {
+        GeoRule { path_prefix: prefix.into(), allow_countries: Vec::new(), deny_countries: Vec::new(), rate_limit: None }
+    }
+
+    #[test]
+    fn allow_deny_and_longest_prefix() {
+        let mut eu_only = rule("/v1/payments");
+        eu_only.allow_countries = vec!["DE".into(), "fr".into()];
+        let mut deny = rule("/v1");
+        deny.deny_countries = vec!["KP".into(), UNKNOWN_COUNTRY.into()];
+        let g = GeoIp::new(GeoIpConfig { enabled: true, rules: vec![deny, eu_only], ..Default::default() });
+
+        assert_eq!(g.check("/v1/payments/1", &geo(Some("FR")), "c"), GeoDecision::Allow);
+        assert_eq!(g.check("/v1/payments/1", &geo(Some("US")), "c"), GeoDecision::Denied);
+        assert_eq!(g.check("/v1/users", &geo(Some("US")), "c"), GeoDecision::Allow);
+        assert_eq!(g.check("/v1/users", &geo(Some("KP")), "c"), GeoDecision::Denied);
+        assert_eq!(g.check("/v1/users", &geo(None), "c"), GeoDecision::Denied);
+        assert_eq!(g.check("/health", &geo(None), "c"), GeoDecision::Allow);
+    }
+
+    #[test]
+    fn rate_limits_listed_countries_per_client() {
+        let mut limited = rule("/");
+        limited.rate_limit = Some(GeoRateLimit { countries: vec!["BR".into()], requests_per_second: 1, burst: 1 });
+        let g = GeoIp::new(GeoIpConfig { enabled: true, rules: vec![limited], ..Default::default() });
+        assert_eq!(g.check("/x", &geo(Some("BR")), "a"), GeoDecision::Allow);
+        assert_eq!(g.check("/x", &geo(Some("BR")), "a"), GeoDecision::RateLimited);
+        assert_eq!(g.check("/x", &geo(Some("BR")), "b"), GeoDecision::Allow);
+        assert_eq!(g.check("/x", &geo(Some("US")), "a"), GeoDecision::Allow);
+        assert_eq!(g.check("/x", &geo(Some("US")), "a"), GeoDecision::Allow);
+    }
+
+    #[test]
+    fn missing_or_invalid_database_yields_empty_info() {
+        let dir = std::env::temp_dir().join(format!("geoip_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bad = dir.join("bad.mmdb");
+        std::fs::write(&bad, b"not a database").unwrap();
+        let cfg = GeoIpConfig {
+            enabled: true,
+            country_db: Some(bad.to_string_lossy().into()),
+            asn_db: Some(dir.join("missing.mmdb").to_string_lossy().into()),
+            ..Default::default()
+        };
+        let g = GeoIp::new(cfg);
+        assert_eq!(g.lookup("8.8.8.8".parse().unwrap()), GeoInfo::default());
+        assert_eq!(GeoInfo::default().country_code(), UNKNOWN_COUNTRY);
+        // 文件未变化时不重复加载
+        assert!(!g.country.reload_if_changed());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
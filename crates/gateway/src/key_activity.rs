@@ -0,0 +1,99 @@
+//! API key 最近使用时间：转发完成后按请求头中的 API key ID 记入内存，后台服务定期回写 `api_key.last_used_at`。
+//! - 请求路径只写内存，不等待数据库；同一 key 每个回写间隔最多一次写入；
+//! - 多个网关实例回写同一 key 时只会把时间往后推；进程退出时尚未回写的记录丢失。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use pingora_core::server::ShutdownWatch;
+use pingora_core::services::background::BackgroundService;
+use service::key_activity::ApiKeyActivity;
+use uuid::Uuid;
+
+use crate::config::KeyActivityConfig;
+
+pub struct KeyActivity {
+    header: String,
+    database_url: String,
+    interval: Duration,
+    activity: Arc<ApiKeyActivity>,
+}
+
+impl KeyActivity {
+    /// 启用但既未配置 `database_url` 也没有 `DATABASE_URL` 时返回错误
+    pub fn new(cfg: &KeyActivityConfig) -> Result<Self, String> {
+        let database_url = cfg
+            .database_url
+            .clone()
+            .or_else(|| std::env::var("DATABASE_URL").ok())
+            .filter(|u| !u.trim().is_empty())
+            .ok_or_else(|| "key_activity.database_url (or DATABASE_URL) is required when key_activity is enabled".to_string())?;
+        Ok(Self {
+            header: cfg.key_id_header.to_ascii_lowercase(),
+            database_url,
+            interval: Duration::from_secs(cfg.flush_interval_secs.max(1)),
+            activity: Arc::new(ApiKeyActivity::new()),
+        })
+    }
+
+    /// 供请求路径使用的记录端
+    pub fn recorder(&self) -> KeyActivityRecorder {
+        KeyActivityRecorder { header: self.header.clone(), activity: Arc::clone(&self.activity) }
+    }
+}
+
+#[async_trait]
+impl BackgroundService for KeyActivity {
+    async fn start(&self, mut shutdown: ShutdownWatch) {
+        tokio::select! {
+            _ = self.activity.run(&self.database_url, self.interval) => {}
+            _ = shutdown.changed() => {}
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyActivityRecorder {
+    header: String,
+    activity: Arc<ApiKeyActivity>,
+}
+
+impl KeyActivityRecorder {
+    pub fn header(&self) -> &str {
+        &self.header
+    }
+
+    /// 记一次使用；请求头取值不是 UUID 时忽略
+    pub fn record(&self, key_id: Option<&str>) {
+        if let Some(id) = key_id.and_then(|v| Uuid::parse_str(v.trim()).ok()) {
+            self.activity.record_now(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_only_valid_key_ids() {
+        let cfg = KeyActivityConfig { enabled: true, database_url: Some("postgres://localhost/gw".into()), key_id_header: "X-Api-Key-Id".into(), flush_interval_secs: 0 };
+        let activity = KeyActivity::new(&cfg).unwrap();
+        assert_eq!(activity.interval, Duration::from_secs(1));
+        let recorder = activity.recorder();
+        assert_eq!(recorder.header(), "x-api-key-id");
+        recorder.record(Some("not-a-uuid"));
+        recorder.record(None);
+        assert_eq!(activity.activity.pending(), 0);
+        let id = Uuid::new_v4().to_string();
+        recorder.record(Some(&id));
+        recorder.record(Some(&id));
+        assert_eq!(activity.activity.pending(), 1);
+
+        let missing = KeyActivityConfig { database_url: Some(" ".into()), ..cfg };
+        if std::env::var("DATABASE_URL").is_err() {
+            assert!(KeyActivity::new(&missing).is_err());
+        }
+    }
+}
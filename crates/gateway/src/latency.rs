@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use prometheus::HistogramVec;
+
+use crate::config::LatencyConfig;
+
+/// 未命中任何路由前缀的请求
+pub const OTHER_ROUTE: &str = "other";
+
+/// 按最长前缀归类路由
+pub fn route_label<'a>(routes: &'a [String], path: &str) -> &'a str {
+    routes
+        .iter()
+        .filter(|p| path.starts_with(p.as_str()))
+        .max_by_key(|p| p.len())
+        .map(String::as_str)
+        .unwrap_or(OTHER_ROUTE)
+}
+
+/// nearest-rank 分位数，`sorted` 须已升序
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteLatency {
+    pub route: String,
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub window_secs: u64,
+    pub routes: Vec<RouteLatency>,
+}
+
+/// 每个路由保留窗口内的原始耗时样本，汇总时排序计算分位数；同时写入 Prometheus 直方图
+pub struct LatencyTracker {
+    window: Duration,
+    max_samples: usize,
+    enabled: bool,
+    histogram: HistogramVec,
+    samples: Mutex<HashMap<String, VecDeque<(Instant, f64)>>>,
+}
+
+impl LatencyTracker {
+    /// `histogram` 通常为 [`crate::observability::Metrics::route_request_duration`]
+    pub fn new(config: &LatencyConfig, histogram: HistogramVec) -> Self {
+        Self {
+            window: Duration::from_secs(config.window_secs.max(1)),
+            max_samples: config.max_samples_per_route.max(1),
+            enabled: config.enabled,
+            histogram,
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, route: &str, upstream: &str, elapsed: Duration) {
+        if !self.enabled {
+            return;
+        }
+        self.histogram.with_label_values(&[route, upstream]).observe(elapsed.as_secs_f64());
+        self.push(route, elapsed, Instant::now());
+    }
+
+    fn push(&self, route: &str, elapsed: Duration, now: Instant) {
+        let mut samples = self.samples.lock().expect("latency lock");
+        let q = samples.entry(route.to_string()).or_default();
+        while q.front().is_some_and(|(t, _)| now.duration_since(*t) > self.window) || q.len() >= self.max_samples {
+            q.pop_front();
+        }
+        q.push_back((now, elapsed.as_secs_f64() * 1000.0));
+    }
+
+    /// 窗口内各路由的 p50/p90/p99（毫秒），按请求数降序
+    pub fn summary(&self) -> LatencySummary {
+        self.summary_at(Instant::now())
+    }
+
+    fn summary_at(&self, now: Instant) -> LatencySummary {
+        let mut samples = self.samples.lock().expect("latency lock");
+        let mut routes = Vec::new();
+        samples.retain(|route, q| {
+            while q.front().is_some_and(|(t, _)| now.duration_since(*t) > self.window) {
+                q.pop_front();
+            }
+            if q.is_empty() {
+                return false;
+            }
+            let mut sorted: Vec<f64> = q.iter().map(|(_, ms)| *ms).collect();
+            sorted.sort_by(f64::total_cmp);
+            routes.push(RouteLatency {
+                route: route.clone(),
+                count: sorted.len(),
+                p50_ms: percentile(&sorted, 0.5),
+                p90_ms: percentile(&sorted, 0.9),
+                p99_ms: percentile(&sorted, 0.99),
+            });
+            true
+        });
+        routes.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.route.cmp(&b.route)));
+        LatencySummary { window_secs: self.window.as_secs(), routes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram() -> HistogramVec {
+        crate::observability::Metrics::new(vec![0.1, 1.0]).unwrap().route_request_duration
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let routes = vec!["/api".to_string(), "/api/users".to_string()];
+        assert_eq!(route_label(&routes, "/api/users/7"), "/api/users");
+        assert_eq!(route_label(&routes, "/api/orders"), "/api");
+        assert_eq!(route_label(&routes, "/health"), OTHER_ROUTE);
+    }
+
+    #[test]
+    fn percentiles_over_sliding_window() {
+        let tracker = LatencyTracker::new(&LatencyConfig { enabled: false, window_secs: 60, ..Default::default() }, histogram());
+        let t0 = Instant::now();
+        tracker.push("/old", Duration::from_millis(500), t0);
+        for ms in 1..=100 {
+            tracker.push("/api", Duration::from_millis(ms), t0 + Duration::from_secs(30));
+        }
+        let s = tracker.summary_at(t0 + Duration::from_secs(61));
+        assert_eq!(s.routes.len(), 1);
+        let r = &s.routes[0];
+        assert_eq!((r.route.as_str(), r.count), ("/api", 100));
+        assert_eq!((r.p50_ms, r.p90_ms, r.p99_ms), (50.0, 90.0, 99.0));
+    }
+
+    #[test]
+    fn caps_samples_per_route() {
+        let cfg = LatencyConfig { enabled: false, max_samples_per_route: 3, ..Default::default() };
+        let tracker = LatencyTracker::new(&cfg, histogram());
+        let now = Instant::now();
+        for ms in [100, 1, 2, 3] {
+            tracker.push("/api", Duration::from_millis(ms), now);
+        }
+        let r = &tracker.summary_at(now).routes[0];
+        assert_eq!((r.count, r.p99_ms), (3, 3.0));
+    }
+}
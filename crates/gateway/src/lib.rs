@@ -2,6 +2,35 @@ pub mod config;
 pub mod rate_limiter;
 pub mod circuit_breaker;
 pub mod retry;
+pub mod outlier;
+pub mod bulkhead;
+pub mod adaptive_limit;
+pub mod bandit;
+pub mod debug;
+pub mod load_shed;
 pub mod observability;
 pub mod proxy;
-pub mod bootstrap;
\ No newline at end of file
+pub mod bootstrap;
+pub mod upgrade;
+pub mod discovery;
+pub mod listeners;
+pub mod waf;
+pub mod bot;
+pub mod geoip;
+pub mod latency;
+pub mod upstream_auth;
+pub mod signing;
+pub mod secrets;
+pub mod reload;
+pub mod upload;
+pub mod content_type;
+pub mod recording;
+pub mod faults;
+pub mod failover;
+pub mod locality;
+pub mod cluster;
+pub mod key_activity;
+pub mod rate_limit_records;
+pub mod testing;
+pub mod errors;
+pub mod error_pages;
\ No newline at end of file
@@ -0,0 +1,123 @@
+//! 多监听端口（如 :6188 公网、:6443 TLS、:7000 内网），以及按 listener 限定路由/租户的可达范围。
+//! 所有 listener 共用同一个代理服务，请求所属 listener 由本地地址（server_addr）确定。
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+use crate::config::{ListenerConfig, ListenerScopeConfig, ListenerScopeRule};
+
+/// 已解析的监听地址
+#[derive(Debug, Clone)]
+pub struct Listeners {
+    entries: Vec<(String, SocketAddr)>,
+}
+
+impl Listeners {
+    pub fn from_config(listeners: &[ListenerConfig]) -> Result<Self, String> {
+        if listeners.is_empty() {
+            return Err("at least one listener is required".into());
+        }
+        let mut names = HashSet::new();
+        let mut entries = Vec::with_capacity(listeners.len());
+        for l in listeners {
+            if !names.insert(l.name.as_str()) {
+                return Err(format!("duplicate listener name {}", l.name));
+            }
+            let addr = l.addr.parse().map_err(|e| format!("listener {}: invalid addr {}: {}", l.name, l.addr, e))?;
+            entries.push((l.name.clone(), addr));
+        }
+        Ok(Self { entries })
+    }
+
+    /// 本地地址 -> listener 名；绑定在 0.0.0.0 / :: 的 listener 按端口匹配
+    pub fn name_for(&self, local: &SocketAddr) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, a)| a == local)
+            .or_else(|| self.entries.iter().find(|(_, a)| a.ip().is_unspecified() && a.port() == local.port()))
+            .map(|(n, _)| n.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|(n, _)| n == name)
+    }
+}
+
+/// 规则必须引用已定义的 listener，且至少限定路径或租户之一
+pub fn validate_scopes(scopes: &ListenerScopeConfig, listeners: &Listeners) -> Result<(), String> {
+    for (i, rule) in scopes.rules.iter().enumerate() {
+        if rule.path_prefix.is_none() && rule.tenant.is_none() {
+            return Err(format!("listener_scopes.rules[{}]: path_prefix or tenant is required", i));
+        }
+        if let Some(unknown) = rule.listeners.iter().find(|l| !listeners.contains(l)) {
+            return Err(format!("listener_scopes.rules[{}]: unknown listener {}", i, unknown));
+        }
+    }
+    Ok(())
+}
+
+fn matches(rule: &ListenerScopeRule, path: &str, tenant: Option<&str>) -> bool {
+    rule.path_prefix.as_deref().is_none_or(|p| path.starts_with(p))
+        && rule.tenant.as_deref().is_none_or(|t| tenant == Some(t))
+}
+
+/// 命中的每条规则都必须包含当前 listener；未命中任何规则的请求在所有 listener 上可达。
+/// 无法确定 listener 时，受限路由一律拒绝。
+pub fn is_allowed(scopes: &ListenerScopeConfig, listener: Option<&str>, path: &str, tenant: Option<&str>) -> bool {
+    scopes
+        .rules
+        .iter()
+        .filter(|r| matches(r, path, tenant))
+        .all(|r| listener.is_some_and(|l| r.listeners.iter().any(|x| x == l)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listeners() -> Listeners {
+        let cfg = |name: &str, addr: &str| ListenerConfig { name: name.into(), addr: addr.into(), tls: None };
+        Listeners::from_config(&[cfg("public", "0.0.0.0:6188"), cfg("tls", "0.0.0.0:6443"), cfg("internal", "127.0.0.1:7000")]).unwrap()
+    }
+
+    fn scopes() -> ListenerScopeConfig {
+        ListenerScopeConfig {
+            tenant_header: "x-tenant-id".into(),
+            rules: vec![
+                ListenerScopeRule { path_prefix: Some("/internal/".into()), tenant: None, listeners: vec!["internal".into()] },
+                ListenerScopeRule { path_prefix: None, tenant: Some("acme".into()), listeners: vec!["tls".into(), "internal".into()] },
+            ],
+        }
+    }
+
+    #[test]
+    fn resolves_listener_by_local_addr() {
+        let l = listeners();
+        assert_eq!(l.name_for(&"10.1.2.3:6188".parse().unwrap()), Some("public"));
+        assert_eq!(l.name_for(&"127.0.0.1:7000".parse().unwrap()), Some("internal"));
+        assert_eq!(l.name_for(&"10.1.2.3:7000".parse().unwrap()), None);
+
+        let dup = ListenerConfig { name: "public".into(), addr: "0.0.0.0:1".into(), tls: None };
+        assert!(Listeners::from_config(&[dup.clone(), dup]).is_err());
+        assert!(Listeners::from_config(&[]).is_err());
+    }
+
+    #[test]
+    fn scoped_routes_and_tenants() {
+        let s = scopes();
+        assert!(validate_scopes(&s, &listeners()).is_ok());
+        assert!(is_allowed(&s, Some("public"), "/v1/users", None));
+        assert!(!is_allowed(&s, Some("public"), "/internal/metrics", None));
+        assert!(is_allowed(&s, Some("internal"), "/internal/metrics", None));
+        assert!(!is_allowed(&s, None, "/internal/metrics", None));
+
+        assert!(!is_allowed(&s, Some("public"), "/v1/users", Some("acme")));
+        assert!(is_allowed(&s, Some("tls"), "/v1/users", Some("acme")));
+        // 两条规则都命中时需同时满足
+        assert!(!is_allowed(&s, Some("tls"), "/internal/metrics", Some("acme")));
+
+        let mut bad = scopes();
+        bad.rules.push(ListenerScopeRule { path_prefix: Some("/x".into()), tenant: None, listeners: vec!["admin".into()] });
+        assert!(validate_scopes(&bad, &listeners()).unwrap_err().contains("unknown listener admin"));
+    }
+}
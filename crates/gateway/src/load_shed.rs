@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::observability::Metrics;
+
+/// 请求优先级；过载时从低到高依次丢弃
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    /// 永不被主动丢弃
+    Critical,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Critical => "critical",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "normal" => Some(Priority::Normal),
+            "high" => Some(Priority::High),
+            "critical" => Some(Priority::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// 确定请求优先级：显式优先级头 > 租户等级头映射 > 默认优先级
+pub fn classify(
+    priority_header: Option<&str>,
+    tier_header: Option<&str>,
+    tiers: &HashMap<String, Priority>,
+    default: Priority,
+) -> Priority {
+    if let Some(p) = priority_header.and_then(Priority::parse) {
+        return p;
+    }
+    tier_header
+        .and_then(|t| tiers.get(&t.trim().to_ascii_lowercase()).copied())
+        .unwrap_or(default)
+}
+
+/// 客户端地址是否在 `trusted`（IP 或 CIDR）中。优先级头由客户端任意设置，
+/// 只有来自前置认证层等可信来源时才采用，其他来源按默认优先级处理；无法解析的条目忽略
+pub fn is_trusted_source(trusted: &[String], client: Option<IpAddr>) -> bool {
+    let Some(ip) = client else { return false };
+    trusted.iter().any(|s| {
+        let s = s.trim();
+        s.parse::<IpNet>().map(|net| net.contains(&ip)).or_else(|_| s.parse::<IpAddr>().map(|a| a == ip)).unwrap_or(false)
+    })
+}
+
+/// 在途请求计数，Drop 时减一
+#[derive(Debug)]
+pub struct InFlightGuard {
+    in_flight: Arc<AtomicUsize>,
+    metrics: Arc<Metrics>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.metrics.gateway_in_flight.dec();
+    }
+}
+
+/// 过载保护：负载 = max(CPU 利用率, 在途请求 / max_in_flight)，
+/// 负载超过某优先级的阈值时拒绝该优先级的请求。
+#[derive(Clone)]
+pub struct LoadShedder {
+    in_flight: Arc<AtomicUsize>,
+    /// CPU 利用率（0.0..=1.0，f64 bits）
+    cpu: Arc<AtomicU64>,
+    max_in_flight: usize,
+    thresholds: HashMap<Priority, f64>,
+    enabled: bool,
+    metrics: Arc<Metrics>,
+}
+
+impl LoadShedder {
+    pub fn new(max_in_flight: usize, thresholds: HashMap<Priority, f64>, enabled: bool) -> Self {
+        Self {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            cpu: Arc::new(AtomicU64::new(0f64.to_bits())),
+            max_in_flight: max_in_flight.max(1),
+            thresholds,
+            enabled,
+            metrics: Metrics::detached(),
+        }
+    }
+
+    /// 在途数、CPU 利用率与丢弃计数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn enter(&self) -> InFlightGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        self.metrics.gateway_in_flight.inc();
+        InFlightGuard { in_flight: self.in_flight.clone(), metrics: Arc::clone(&self.metrics) }
+    }
+
+    pub fn set_cpu_utilization(&self, value: f64) {
+        let v = value.clamp(0.0, 1.0);
+        self.cpu.store(v.to_bits(), Ordering::Relaxed);
+        self.metrics.gateway_cpu_utilization.set(v);
+    }
+
+    /// 当前负载（0.0 表示空闲，>= 1.0 表示饱和）
+    pub fn load(&self) -> f64 {
+        let cpu = f64::from_bits(self.cpu.load(Ordering::Relaxed));
+        let queue = self.in_flight.load(Ordering::Acquire) as f64 / self.max_in_flight as f64;
+        cpu.max(queue)
+    }
+
+    /// 是否应丢弃该优先级的请求（Critical 与未配置阈值的优先级不丢弃）
+    pub fn should_shed(&self, priority: Priority) -> bool {
+        if !self.enabled || priority == Priority::Critical {
+            return false;
+        }
+        let Some(threshold) = self.thresholds.get(&priority) else {
+            return false;
+        };
+        let load = self.load();
+        let shed = load >= *threshold;
+        if shed {
+            self.metrics.load_shed_total.with_label_values(&[priority.as_str()]).inc();
+            debug!(priority = priority.as_str(), load, threshold, "load shedding request");
+        }
+        shed
+    }
+
+    /// 后台线程周期性采样 CPU 利用率（仅 Linux，读取 /proc/stat）
+    pub fn spawn_cpu_sampler(&self, interval: Duration) {
+        if !self.enabled {
+            return;
+        }
+        let this = self.clone();
+        let spawned = std::thread::Builder::new().name("cpu-sampler".into()).spawn(move || {
+            let mut prev = read_cpu_times();
+            loop {
+                std::thread::sleep(interval);
+                let cur = read_cpu_times();
+                if let (Some(p), Some(c)) = (prev, cur) {
+                    this.set_cpu_utilization(cpu_utilization(p, c));
+                }
+                prev = cur;
+            }
+        });
+        if let Err(e) = spawned {
+            warn!(error = %e, "failed to spawn cpu sampler; shedding on queue depth only");
+        }
+    }
+}
+
+/// (busy, total) jiffies
+fn read_cpu_times() -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    parse_cpu_line(stat.lines().next()?)
+}
+
+fn parse_cpu_line(line: &str) -> Option<(u64, u64)> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "cpu" {
+        return None;
+    }
+    let values: Vec<u64> = parts.filter_map(|v| v.parse().ok()).collect();
+    if values.len() < 4 {
+        return None;
+    }
+    let total: u64 = values.iter().sum();
+    // idle + iowait
+    let idle = values[3] + values.get(4).copied().unwrap_or(0);
+    Some((total - idle, total))
+}
+
+fn cpu_utilization(prev: (u64, u64), cur: (u64, u64)) -> f64 {
+    let busy = cur.0.saturating_sub(prev.0) as f64;
+    let total = cur.1.saturating_sub(prev.1) as f64;
+    if total == 0.0 { 0.0 } else { busy / total }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> HashMap<Priority, f64> {
+        HashMap::from([(Priority::Low, 0.5), (Priority::Normal, 0.8), (Priority::High, 0.95)])
+    }
+
+    #[test]
+    fn test_classify_priority() {
+        let tiers = HashMap::from([("free".to_string(), Priority::Low), ("enterprise".to_string(), Priority::Critical)]);
+        assert_eq!(classify(Some("high"), Some("free"), &tiers, Priority::Normal), Priority::High);
+        assert_eq!(classify(None, Some("Enterprise"), &tiers, Priority::Normal), Priority::Critical);
+        assert_eq!(classify(Some("bogus"), Some("free"), &tiers, Priority::Normal), Priority::Low);
+        assert_eq!(classify(None, None, &tiers, Priority::Normal), Priority::Normal);
+    }
+
+    #[test]
+    fn only_trusted_sources_may_set_priority() {
+        let trusted = vec!["10.0.0.0/8".to_string(), "192.168.1.5".to_string(), "bogus".to_string()];
+        assert!(is_trusted_source(&trusted, Some("10.1.2.3".parse().unwrap())));
+        assert!(is_trusted_source(&trusted, Some("192.168.1.5".parse().unwrap())));
+        assert!(!is_trusted_source(&trusted, Some("203.0.113.9".parse().unwrap())));
+        assert!(!is_trusted_source(&trusted, None));
+        // 默认不信任任何来源
+        assert!(!is_trusted_source(&[], Some("10.1.2.3".parse().unwrap())));
+    }
+
+    #[test]
+    fn test_sheds_low_priority_first() {
+        let shedder = LoadShedder::new(10, thresholds(), true);
+        let guards: Vec<_> = (0..6).map(|_| shedder.enter()).collect();
+        // load = 0.6
+        assert!(shedder.should_shed(Priority::Low));
+        assert!(!shedder.should_shed(Priority::Normal));
+
+        shedder.set_cpu_utilization(0.99);
+        assert!(shedder.should_shed(Priority::High));
+        assert!(!shedder.should_shed(Priority::Critical));
+
+        drop(guards);
+        shedder.set_cpu_utilization(0.0);
+        assert!(!shedder.should_shed(Priority::Low));
+
+        let off = LoadShedder::new(1, thresholds(), false);
+        let _g = off.enter();
+        assert!(!off.should_shed(Priority::Low));
+    }
+
+    #[test]
+    fn test_cpu_utilization_from_proc_stat() {
+        let a = parse_cpu_line("cpu  100 0 100 700 100 0 0 0 0 0").unwrap();
+        let b = parse_cpu_line("cpu  200 0 200 800 100 0 0 0 0 0").unwrap();
+        assert_eq!(a, (200, 1000));
+        assert!((cpu_utilization(a, b) - 2.0 / 3.0).abs() < 1e-9);
+        assert!(parse_cpu_line("cpu0 1 2 3 4").is_none());
+    }
+}
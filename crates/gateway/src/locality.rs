@@ -0,0 +1,157 @@
+//! 就近选择上游：上游按 `locality.upstreams` 标注地域/可用区，选择时优先与网关同可用区的节点，
+//! 其次同地域，最后跨地域。
+//! - 某一级可用（健康且未被 outlier 摘除）节点数为 0 或占该级节点的比例低于 `min_healthy_percent` 时，
+//!   该级与下一级一起参与选择（溢出），直到跨地域即全部节点；
+//! - 只作用于 `upstreams`（`default` 分组），故障转移的备用分组不参与；
+//! - 每次转发按所选节点相对网关的位置计入 `api_proxy_locality_selections_total{locality}`
+//!   （zone / region / cross_region），跨地域流量即 `locality="cross_region"`。
+
+use std::collections::HashMap;
+
+use crate::config::{LocalityConfig, UpstreamLocality};
+
+/// 节点相对网关的位置；顺序即优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Locality {
+    SameZone,
+    SameRegion,
+    CrossRegion,
+}
+
+impl Locality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locality::SameZone => "zone",
+            Locality::SameRegion => "region",
+            Locality::CrossRegion => "cross_region",
+        }
+    }
+}
+
+/// 启动时校验：启用时须配置 `region`，百分比不超过 100
+pub fn validate(cfg: &LocalityConfig) -> Result<(), String> {
+    if cfg.enabled && cfg.region.trim().is_empty() {
+        return Err("locality.region is required when locality is enabled".to_string());
+    }
+    if cfg.min_healthy_percent > 100 {
+        return Err("locality.min_healthy_percent must be between 0 and 100".to_string());
+    }
+    if let Some(addr) = cfg.upstreams.iter().find(|(_, l)| l.region.trim().is_empty()).map(|(a, _)| a) {
+        return Err(format!("locality.upstreams.{} has an empty region", addr));
+    }
+    Ok(())
+}
+
+pub struct LocalitySelector {
+    enabled: bool,
+    region: String,
+    zone: Option<String>,
+    upstreams: HashMap<String, UpstreamLocality>,
+    min_healthy_percent: u64,
+}
+
+impl LocalitySelector {
+    pub fn new(cfg: &LocalityConfig) -> Self {
+        Self {
+            enabled: cfg.enabled,
+            region: cfg.region.clone(),
+            zone: cfg.zone.clone(),
+            upstreams: cfg.upstreams.clone(),
+            min_healthy_percent: cfg.min_healthy_percent as u64,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 上游相对网关的位置；未标注的上游视为同地域
+    pub fn locality(&self, addr: &str) -> Locality {
+        let Some(upstream) = self.upstreams.get(addr) else {
+            return Locality::SameRegion;
+        };
+        if upstream.region != self.region {
+            return Locality::CrossRegion;
+        }
+        match (&upstream.zone, &self.zone) {
+            (Some(a), Some(b)) if a == b => Locality::SameZone,
+            _ => Locality::SameRegion,
+        }
+    }
+
+    /// 本次可选节点的最远位置：`peers` 为 (地址, 是否可用)；返回 `Locality::CrossRegion` 即不限制
+    pub fn reach<'a>(&self, peers: impl IntoIterator<Item = (&'a str, bool)>) -> Locality {
+        // 每一级累计的（节点数，可用数）
+        let mut tiers = [(0u64, 0u64); 2];
+        for (addr, available) in peers {
+            let locality = self.locality(addr);
+            for (tier, counts) in [Locality::SameZone, Locality::SameRegion].iter().zip(tiers.iter_mut()) {
+                if locality <= *tier {
+                    counts.0 += 1;
+                    counts.1 += available as u64;
+                }
+            }
+        }
+        [Locality::SameZone, Locality::SameRegion]
+            .into_iter()
+            .zip(tiers)
+            .find(|(_, (total, available))| *available > 0 && *available * 100 >= *total * self.min_healthy_percent)
+            .map(|(tier, _)| tier)
+            .unwrap_or(Locality::CrossRegion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector() -> LocalitySelector {
+        let label = |region: &str, zone: Option<&str>| UpstreamLocality { region: region.into(), zone: zone.map(Into::into) };
+        LocalitySelector::new(&LocalityConfig {
+            enabled: true,
+            region: "eu-west".into(),
+            zone: Some("eu-west-1a".into()),
+            upstreams: HashMap::from([
+                ("10.0.0.1:80".to_string(), label("eu-west", Some("eu-west-1a"))),
+                ("10.0.0.2:80".to_string(), label("eu-west", Some("eu-west-1a"))),
+                ("10.0.1.1:80".to_string(), label("eu-west", Some("eu-west-1b"))),
+                ("10.9.0.1:80".to_string(), label("us-east", None)),
+            ]),
+            min_healthy_percent: 50,
+        })
+    }
+
+    #[test]
+    fn classifies_peers_relative_to_gateway() {
+        let s = selector();
+        assert_eq!(s.locality("10.0.0.1:80"), Locality::SameZone);
+        assert_eq!(s.locality("10.0.1.1:80"), Locality::SameRegion);
+        assert_eq!(s.locality("10.9.0.1:80"), Locality::CrossRegion);
+        assert_eq!(s.locality("192.168.0.1:80"), Locality::SameRegion);
+    }
+
+    #[test]
+    fn spills_over_when_local_capacity_is_insufficient() {
+        let s = selector();
+        let peers = |zone_a: [bool; 2], zone_b: bool| vec![("10.0.0.1:80", zone_a[0]), ("10.0.0.2:80", zone_a[1]), ("10.0.1.1:80", zone_b), ("10.9.0.1:80", true)];
+        assert_eq!(s.reach(peers([true, true], true)), Locality::SameZone);
+        // 一半可用仍满足 50%
+        assert_eq!(s.reach(peers([true, false], true)), Locality::SameZone);
+        // 同可用区全部不可用；同地域 3 个节点可用 1 个（33%），继续溢出到跨地域
+        assert_eq!(s.reach(peers([false, false], true)), Locality::CrossRegion);
+        assert_eq!(s.reach(peers([false, false], false)), Locality::CrossRegion);
+
+        let lenient = LocalitySelector { min_healthy_percent: 30, ..selector() };
+        assert_eq!(lenient.reach(peers([false, false], true)), Locality::SameRegion);
+    }
+
+    #[test]
+    fn validates_config() {
+        let mut cfg = LocalityConfig { enabled: true, ..LocalityConfig::default() };
+        assert!(validate(&cfg).is_err());
+        cfg.region = "eu-west".into();
+        assert!(validate(&cfg).is_ok());
+        cfg.min_healthy_percent = 101;
+        assert!(validate(&cfg).is_err());
+    }
+}
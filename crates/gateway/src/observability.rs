@@ -1,76 +1,316 @@
-use once_cell::sync::Lazy;
-use prometheus::{register_histogram, register_int_counter, Encoder, Histogram, IntCounter, TextEncoder};
-
-// Prometheus metrics (default registry)
-pub static REQUESTS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_requests_total",
-        "Total requests handled by proxy"
-    )
-    .expect("register requests_total")
-});
-
-pub static UPSTREAM_SELECTED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_upstream_selected_total",
-        "Total upstream selections"
-    )
-    .expect("register upstream_selected_total")
-});
-
-pub static UPSTREAM_ERRORS_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_upstream_errors_total",
-        "Total upstream selection errors"
-    )
-    .expect("register upstream_errors_total")
-});
-
-pub static REQUEST_DURATION: Lazy<Histogram> = Lazy::new(|| {
-    register_histogram!(
-        "api_proxy_request_duration_seconds",
-        "Request duration in seconds",
-        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
-    )
-    .expect("register request_duration")
-});
-
-pub static RATE_LIMITED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_rate_limited_total",
-        "Total requests rejected by rate limiter"
-    )
-    .expect("register rate_limited_total")
-});
-
-pub static CIRCUIT_BREAKER_OPEN_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_circuit_breaker_open_total",
-        "Total requests rejected by circuit breaker"
-    )
-    .expect("register circuit_breaker_open_total")
-});
-
-pub static RETRIES_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
-    register_int_counter!(
-        "api_proxy_retries_total",
-        "Total retry attempts"
-    )
-    .expect("register retries_total")
-});
+use std::collections::HashSet;
+use std::sync::Arc;
 
+use once_cell::sync::{Lazy, OnceCell};
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+use service::observability::metric_names as names;
+
+/// 1 KiB ~ 1 GiB
+const UPLOAD_BYTES_BUCKETS: [f64; 11] = [
+    1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0, 67108864.0, 268435456.0, 1073741824.0,
+];
+const REQUEST_DURATION_BUCKETS: [f64; 12] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+fn register<T: Collector + Clone + 'static>(registry: &Registry, metric: T) -> prometheus::Result<T> {
+    registry.register(Box::new(metric.clone()))?;
+    Ok(metric)
+}
+
+/// 网关 Prometheus 指标；名称与 /admin/observability/bundle 生成的规则共用。
+///
+/// 每个实例持有自己的 [`Registry`]，由 bootstrap 注入到 `LB`、各组件（`with_metrics`）与 admin server；
+/// 测试或同进程内多个网关实例各自 [`Metrics::new`]，互不影响。未注入的组件记到不导出的独立实例。
+pub struct Metrics {
+    registry: Registry,
+    pub requests_total: IntCounter,
+    pub upstream_selected_total: IntCounter,
+    pub upstream_errors_total: IntCounter,
+    pub request_duration: Histogram,
+    pub rate_limited_total: IntCounter,
+    /// 令牌桶当前剩余令牌（bucket 为桶名，网关全局桶为 global）
+    pub rate_limit_tokens: IntGaugeVec,
+    /// 限流拒绝次数（reason 为 token_bucket/spike_arrest）
+    pub rate_limit_rejections_total: IntCounterVec,
+    /// 上个统计窗口内被拒绝最多的 key 及其拒绝次数（只保留前 N 个）
+    pub rate_limit_top_rejected_keys: IntGaugeVec,
+    pub circuit_breaker_open_total: IntCounter,
+    /// 熔断器状态（0 closed / 1 open / 2 half-open），按 upstream 区分
+    pub circuit_breaker_state: IntGaugeVec,
+    pub circuit_breaker_transitions_total: IntCounterVec,
+    pub circuit_breaker_rejected_total: IntCounterVec,
+    /// half-open 期间排队等待试探名额的请求数
+    pub circuit_breaker_queue_depth: IntGaugeVec,
+    /// 经过 half-open 队列的请求（result 为 probe/closed/queue_full/timeout/reopened）
+    pub circuit_breaker_queued_total: IntCounterVec,
+    pub retries_total: IntCounter,
+    pub upstream_ejections_total: IntCounter,
+    pub upstream_ejected: IntGaugeVec,
+    pub upstream_ejected_hosts: IntGauge,
+    pub bulkhead_in_flight: IntGaugeVec,
+    pub bulkhead_rejected_total: IntCounterVec,
+    pub adaptive_concurrency_limit: IntGaugeVec,
+    pub adaptive_min_rtt_seconds: GaugeVec,
+    pub load_shed_total: IntCounterVec,
+    pub waf_rule_hits_total: IntCounterVec,
+    pub bot_requests_total: IntCounterVec,
+    pub geoip_rejected_total: IntCounterVec,
+    pub geoip_db_reloads_total: IntCounterVec,
+    pub listener_scope_denied_total: IntCounterVec,
+    pub k8s_discovered_backends: IntGauge,
+    pub bandit_reward: GaugeVec,
+    pub bandit_share: GaugeVec,
+    pub bandit_selections_total: IntCounterVec,
+    /// 按所选上游相对网关的位置（zone/region/cross_region）计数的转发次数
+    pub locality_selections_total: IntCounterVec,
+    pub gateway_in_flight: IntGauge,
+    pub gateway_cpu_utilization: Gauge,
+    /// 按路由/上游的请求耗时；桶来自 `latency.buckets`
+    pub route_request_duration: HistogramVec,
+    /// multipart 上传中每个 part 的字节数（route 为命中的 uploads 规则前缀）
+    pub upload_part_bytes: HistogramVec,
+    pub upload_rejected_total: IntCounterVec,
+    pub content_type_rejected_total: IntCounterVec,
+    pub unexpected_content_type_total: IntCounterVec,
+    /// 回放路由的请求数（result 为 hit/miss/passthrough）
+    pub playback_requests_total: IntCounterVec,
+    /// 故障注入次数（fault 为 abort/reset/delay），与真实故障区分
+    pub faults_injected_total: IntCounterVec,
+    /// 路由当前是否已切到备用上游分组（1 是 / 0 否）
+    pub failover_active: IntGaugeVec,
+    /// 故障转移状态切换次数（to 为 fallback/primary）
+    pub failover_transitions_total: IntCounterVec,
+    /// 集群模式下最近有消息的其他实例数
+    pub cluster_peers: IntGauge,
+    /// 集群频道消息数（direction 为 sent/received，kind 为 usage/circuit_open/ejected）
+    pub cluster_messages_total: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new(route_buckets: Vec<f64>) -> prometheus::Result<Self> {
+        Self::with_registry(Registry::new(), route_buckets)
+    }
+
+    /// 注册到给定 registry；同名指标已存在时返回错误
+    pub fn with_registry(registry: Registry, route_buckets: Vec<f64>) -> prometheus::Result<Self> {
+        Ok(Self {
+            requests_total: register(&registry, IntCounter::new(names::REQUESTS_TOTAL, "Total requests handled by proxy")?)?,
+            upstream_selected_total: register(&registry, IntCounter::new("api_proxy_upstream_selected_total", "Total upstream selections")?)?,
+            upstream_errors_total: register(&registry, IntCounter::new(names::UPSTREAM_ERRORS_TOTAL, "Total upstream selection errors")?)?,
+            request_duration: register(&registry, Histogram::with_opts(HistogramOpts::new(names::REQUEST_DURATION_SECONDS, "Request duration in seconds").buckets(REQUEST_DURATION_BUCKETS.to_vec()))?)?,
+            rate_limited_total: register(&registry, IntCounter::new(names::RATE_LIMITED_TOTAL, "Total requests rejected by rate limiter")?)?,
+            rate_limit_tokens: register(&registry, IntGaugeVec::new(Opts::new("api_proxy_rate_limit_tokens", "Tokens currently available in a rate limit bucket"), &["bucket"])?)?,
+            rate_limit_rejections_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_rate_limit_rejections_total", "Requests rejected by the rate limiter by reason"), &["reason"])?)?,
+            rate_limit_top_rejected_keys: register(&registry, IntGaugeVec::new(Opts::new("api_proxy_rate_limit_top_rejected_keys", "Rejections of the most rejected keys in the last window"), &["key"])?)?,
+            circuit_breaker_open_total: register(&registry, IntCounter::new(names::CIRCUIT_BREAKER_OPEN_TOTAL, "Total requests rejected by circuit breaker")?)?,
+            circuit_breaker_state: register(&registry, IntGaugeVec::new(Opts::new(names::CIRCUIT_BREAKER_STATE, "Circuit breaker state per upstream (0 closed, 1 open, 2 half-open)"), &["upstream"])?)?,
+            circuit_breaker_transitions_total: register(&registry, IntCounterVec::new(Opts::new(names::CIRCUIT_BREAKER_TRANSITIONS_TOTAL, "Circuit breaker state transitions"), &["upstream", "from", "to"])?)?,
+            circuit_breaker_rejected_total: register(&registry, IntCounterVec::new(Opts::new(names::CIRCUIT_BREAKER_REJECTED_TOTAL, "Calls rejected by an open or saturated half-open circuit breaker"), &["upstream"])?)?,
+            circuit_breaker_queue_depth: register(&registry, IntGaugeVec::new(Opts::new("api_proxy_circuit_breaker_queue_depth", "Requests parked waiting for a half-open probe slot"), &["upstream"])?)?,
+            circuit_breaker_queued_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_circuit_breaker_queued_total", "Requests admitted or rejected by the half-open queue"), &["upstream", "result"])?)?,
+            retries_total: register(&registry, IntCounter::new("api_proxy_retries_total", "Total retry attempts")?)?,
+            upstream_ejections_total: register(&registry, IntCounter::new("api_proxy_upstream_ejections_total", "Total upstream ejections by outlier detection")?)?,
+            upstream_ejected: register(&registry, IntGaugeVec::new(Opts::new(names::UPSTREAM_EJECTED, "Whether an upstream is currently ejected (1) or not (0)"), &["upstream"])?)?,
+            upstream_ejected_hosts: register(&registry, IntGauge::new("api_proxy_upstream_ejected_hosts", "Number of upstreams currently ejected")?)?,
+            bulkhead_in_flight: register(&registry, IntGaugeVec::new(Opts::new(names::BULKHEAD_IN_FLIGHT, "In-flight requests per upstream admitted by the bulkhead"), &["upstream"])?)?,
+            bulkhead_rejected_total: register(&registry, IntCounterVec::new(Opts::new(names::BULKHEAD_REJECTED_TOTAL, "Requests rejected by the bulkhead"), &["upstream", "reason"])?)?,
+            adaptive_concurrency_limit: register(&registry, IntGaugeVec::new(Opts::new("api_proxy_adaptive_concurrency_limit", "Current adaptive concurrency limit per upstream"), &["upstream"])?)?,
+            adaptive_min_rtt_seconds: register(&registry, GaugeVec::new(Opts::new("api_proxy_adaptive_min_rtt_seconds", "Baseline (minimum) latency observed per upstream by the adaptive limiter"), &["upstream"])?)?,
+            load_shed_total: register(&registry, IntCounterVec::new(Opts::new(names::LOAD_SHED_TOTAL, "Requests rejected by load shedding"), &["priority"])?)?,
+            waf_rule_hits_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_waf_rule_hits_total", "Requests matched by a WAF rule"), &["rule", "action"])?)?,
+            bot_requests_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_bot_requests_total", "Requests by bot classification (human/bot/allowed_bot/verified) and outcome"), &["class", "outcome"])?)?,
+            geoip_rejected_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_geoip_rejected_total", "Requests rejected by geo policies (reason: denied/rate_limited)"), &["country", "reason"])?)?,
+            geoip_db_reloads_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_geoip_db_reloads_total", "GeoIP database (re)loads by result"), &["db", "result"])?)?,
+            listener_scope_denied_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_listener_scope_denied_total", "Requests rejected because the route or tenant is not exposed on the listener"), &["listener"])?)?,
+            k8s_discovered_backends: register(&registry, IntGauge::new("api_proxy_k8s_discovered_backends", "Ready backends discovered from Kubernetes EndpointSlices")?)?,
+            bandit_reward: register(&registry, GaugeVec::new(Opts::new("api_proxy_bandit_reward", "Smoothed reward (0-1) per upstream used by the bandit selector"), &["upstream"])?)?,
+            bandit_share: register(&registry, GaugeVec::new(Opts::new("api_proxy_bandit_share", "Recent traffic share per upstream chosen by the bandit selector"), &["upstream"])?)?,
+            bandit_selections_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_bandit_selections_total", "Upstream selections made by the bandit selector"), &["upstream", "reason"])?)?,
+            locality_selections_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_locality_selections_total", "Forwarded requests by upstream locality relative to the gateway (zone/region/cross_region)"), &["locality"])?)?,
+            gateway_in_flight: register(&registry, IntGauge::new(names::IN_FLIGHT_REQUESTS, "Requests currently being processed by the gateway")?)?,
+            gateway_cpu_utilization: register(&registry, Gauge::new("api_proxy_cpu_utilization", "Host CPU utilization (0-1) sampled for load shedding")?)?,
+            route_request_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(names::ROUTE_REQUEST_DURATION_SECONDS, "Request duration in seconds by route prefix and upstream")
+                        .buckets(route_buckets),
+                    &["route", "upstream"],
+                )?,
+            )?,
+            upload_part_bytes: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new("api_proxy_upload_part_bytes", "Size of multipart upload parts by route and kind (file/field)")
+                        .buckets(UPLOAD_BYTES_BUCKETS.to_vec()),
+                    &["route", "kind"],
+                )?,
+            )?,
+            upload_rejected_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_upload_rejected_total", "Multipart uploads rejected by upload limits"), &["route", "reason"])?)?,
+            content_type_rejected_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_content_type_rejected_total", "Requests rejected with 415 because the content type is not accepted by the route"), &["route"])?)?,
+            unexpected_content_type_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_unexpected_content_type_total", "Upstream responses whose content type does not match the route declaration"), &["route", "upstream"])?)?,
+            playback_requests_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_playback_requests_total", "Requests on playback routes served from a recorded fixture"), &["route", "result"])?)?,
+            faults_injected_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_faults_injected_total", "Faults injected by fault_injection rules (not real upstream failures)"), &["route", "fault"])?)?,
+            failover_active: register(&registry, IntGaugeVec::new(Opts::new("api_proxy_failover_active", "Whether a route is currently served by its fallback upstream group"), &["route"])?)?,
+            failover_transitions_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_failover_transitions_total", "Failover state changes per route (to: fallback/primary)"), &["route", "to"])?)?,
+            cluster_peers: register(&registry, IntGauge::new("api_proxy_cluster_peers", "Other gateway instances seen on the cluster channel recently")?)?,
+            cluster_messages_total: register(&registry, IntCounterVec::new(Opts::new("api_proxy_cluster_messages_total", "Cluster channel messages by direction and kind"), &["direction", "kind"])?)?,
+            registry,
+        })
+    }
+
+    /// 未注入指标的组件使用的独立实例：记录但不导出
+    pub fn detached() -> Arc<Self> {
+        Arc::new(Self::new(prometheus::DEFAULT_BUCKETS.to_vec()).expect("register metrics in a fresh registry"))
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Prometheus 文本格式：本实例的指标，加上默认 registry 中的进程级指标（如 SIEM 导出计数）
+    pub fn encode(&self) -> (axum::http::StatusCode, String) {
+        let mut families = self.registry.gather();
+        let own: HashSet<String> = families.iter().map(|f| f.get_name().to_string()).collect();
+        families.extend(prometheus::gather().into_iter().filter(|f| !own.contains(f.get_name())));
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&families, &mut buffer) {
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("metrics encode error: {e}"),
+            );
+        }
+        (
+            axum::http::StatusCode::OK,
+            String::from_utf8(buffer).unwrap_or_default(),
+        )
+    }
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+static GLOBAL: OnceCell<Arc<Metrics>> = OnceCell::new();
+
+/// 进程级实例（注册到默认 registry），只供下方兼容旧名称的静态量使用；网关组件不再写入。
+/// `route_buckets` 须在首次使用前设置，之后的调用返回已有实例
+#[deprecated(note = "create a `Metrics` and inject it with `with_metrics`")]
+pub fn init_global(route_buckets: Vec<f64>) -> &'static Arc<Metrics> {
+    GLOBAL.get_or_init(|| {
+        Arc::new(Metrics::with_registry(prometheus::default_registry().clone(), route_buckets).expect("register gateway metrics"))
+    })
+}
+
+#[deprecated(note = "create a `Metrics` and inject it with `with_metrics`")]
+#[allow(deprecated)]
+pub fn global() -> &'static Arc<Metrics> {
+    init_global(prometheus::DEFAULT_BUCKETS.to_vec())
+}
+
+/// 兼容旧名称（已废弃）：指向进程级实例的同一组指标
+macro_rules! compat_metrics {
+    ($($name:ident: $ty:ty => $field:ident;)*) => {
+        $(
+            #[deprecated(note = "record through the injected `Metrics` instead")]
+            #[allow(deprecated)]
+            pub static $name: Lazy<$ty> = Lazy::new(|| global().$field.clone());
+        )*
+    };
+}
+
+compat_metrics! {
+    REQUESTS_TOTAL: IntCounter => requests_total;
+    UPSTREAM_SELECTED_TOTAL: IntCounter => upstream_selected_total;
+    UPSTREAM_ERRORS_TOTAL: IntCounter => upstream_errors_total;
+    REQUEST_DURATION: Histogram => request_duration;
+    RATE_LIMITED_TOTAL: IntCounter => rate_limited_total;
+    CIRCUIT_BREAKER_OPEN_TOTAL: IntCounter => circuit_breaker_open_total;
+    RETRIES_TOTAL: IntCounter => retries_total;
+    UPSTREAM_EJECTIONS_TOTAL: IntCounter => upstream_ejections_total;
+    UPSTREAM_EJECTED: IntGaugeVec => upstream_ejected;
+    UPSTREAM_EJECTED_HOSTS: IntGauge => upstream_ejected_hosts;
+    BULKHEAD_IN_FLIGHT: IntGaugeVec => bulkhead_in_flight;
+    BULKHEAD_REJECTED_TOTAL: IntCounterVec => bulkhead_rejected_total;
+    ADAPTIVE_CONCURRENCY_LIMIT: IntGaugeVec => adaptive_concurrency_limit;
+    ADAPTIVE_MIN_RTT_SECONDS: GaugeVec => adaptive_min_rtt_seconds;
+    LOAD_SHED_TOTAL: IntCounterVec => load_shed_total;
+    WAF_RULE_HITS_TOTAL: IntCounterVec => waf_rule_hits_total;
+    BOT_REQUESTS_TOTAL: IntCounterVec => bot_requests_total;
+    GEOIP_REJECTED_TOTAL: IntCounterVec => geoip_rejected_total;
+    GEOIP_DB_RELOADS_TOTAL: IntCounterVec => geoip_db_reloads_total;
+    LISTENER_SCOPE_DENIED_TOTAL: IntCounterVec => listener_scope_denied_total;
+    K8S_DISCOVERED_BACKENDS: IntGauge => k8s_discovered_backends;
+    BANDIT_REWARD: GaugeVec => bandit_reward;
+    BANDIT_SHARE: GaugeVec => bandit_share;
+    BANDIT_SELECTIONS_TOTAL: IntCounterVec => bandit_selections_total;
+    GATEWAY_IN_FLIGHT: IntGauge => gateway_in_flight;
+    GATEWAY_CPU_UTILIZATION: Gauge => gateway_cpu_utilization;
+}
+
+#[deprecated(note = "use `Metrics::route_request_duration` of the injected instance")]
+#[allow(deprecated)]
+pub fn route_request_duration() -> &'static HistogramVec {
+    &global().route_request_duration
+}
+
+#[deprecated(note = "use `Metrics::encode` of the injected instance")]
+#[allow(deprecated)]
 pub fn encode_metrics() -> (axum::http::StatusCode, String) {
-    let encoder = TextEncoder::new();
-    let metric_families = prometheus::gather();
-    let mut buffer = Vec::new();
-    if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
-        return (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            format!("metrics encode error: {e}"),
-        );
+    global().encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instances_are_isolated() {
+        let a = Metrics::new(vec![0.1, 1.0]).unwrap();
+        let b = Metrics::new(vec![0.1, 1.0]).unwrap();
+        a.requests_total.inc();
+        assert_eq!((a.requests_total.get(), b.requests_total.get()), (1, 0));
+        let (status, body) = a.encode();
+        assert!(status.is_success());
+        assert!(body.contains("api_proxy_requests_total 1"));
+    }
+
+    #[test]
+    fn components_record_into_their_injected_instance() {
+        use crate::load_shed::LoadShedder;
+        use crate::outlier::OutlierDetector;
+        use std::time::Duration;
+
+        let (a, b) = (Arc::new(Metrics::new(vec![1.0]).unwrap()), Arc::new(Metrics::new(vec![1.0]).unwrap()));
+        let detector = |m: &Arc<Metrics>| OutlierDetector::new(1, Duration::from_secs(10), Duration::from_secs(10), true).with_metrics(Arc::clone(m));
+        detector(&a).record_failure("10.0.0.1:80");
+        assert_eq!((a.upstream_ejections_total.get(), b.upstream_ejections_total.get()), (1, 0));
+        assert_eq!(a.upstream_ejected.with_label_values(&["10.0.0.1:80"]).get(), 1);
+        assert_eq!(b.upstream_ejected.with_label_values(&["10.0.0.1:80"]).get(), 0);
+
+        let shedder = LoadShedder::new(10, Default::default(), true).with_metrics(Arc::clone(&b));
+        let guard = shedder.enter();
+        assert_eq!((a.gateway_in_flight.get(), b.gateway_in_flight.get()), (0, 1));
+        drop(guard);
+        assert_eq!(b.gateway_in_flight.get(), 0);
+    }
+
+    #[test]
+    fn duplicate_registration_is_an_error() {
+        let registry = Registry::new();
+        Metrics::with_registry(registry.clone(), vec![1.0]).unwrap();
+        assert!(Metrics::with_registry(registry, vec![1.0]).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn shim_shares_global_instance() {
+        let before = global().retries_total.get();
+        RETRIES_TOTAL.inc();
+        assert_eq!(global().retries_total.get(), before + 1);
     }
-    (
-        axum::http::StatusCode::OK,
-        String::from_utf8(buffer).unwrap_or_default(),
-    )
-}
\ No newline at end of file
+}
@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::observability::Metrics;
+
+/// 单个上游的被动健康状态
+#[derive(Debug, Default, Clone)]
+struct HostState {
+    consecutive_failures: u32,
+    ejection_count: u32,
+    ejected_until: Option<Instant>,
+}
+
+/// 被动健康检查（outlier detection）：
+/// 上游连续返回 5xx / 超时达到阈值后，临时从负载均衡池中摘除，
+/// 摘除时长按 base * 2^(n-1) 指数增长，上限为 max。
+#[derive(Debug)]
+pub struct OutlierDetectorInner {
+    hosts: HashMap<String, HostState>,
+    consecutive_failures: u32,
+    base_ejection: Duration,
+    max_ejection: Duration,
+}
+
+impl OutlierDetectorInner {
+    pub fn new(consecutive_failures: u32, base_ejection: Duration, max_ejection: Duration) -> Self {
+        Self {
+            hosts: HashMap::new(),
+            consecutive_failures: consecutive_failures.max(1),
+            base_ejection,
+            max_ejection,
+        }
+    }
+
+    fn ejection_duration(&self, ejection_count: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(ejection_count.saturating_sub(1));
+        self.base_ejection
+            .checked_mul(factor)
+            .unwrap_or(self.max_ejection)
+            .min(self.max_ejection)
+    }
+
+    pub fn record_success(&mut self, upstream: &str) {
+        if let Some(state) = self.hosts.get_mut(upstream) {
+            state.consecutive_failures = 0;
+            // 摘除结束后恢复正常，逐步衰减摘除次数
+            if state.ejected_until.is_none() {
+                state.ejection_count = state.ejection_count.saturating_sub(1);
+            }
+        }
+    }
+
+    /// 记录一次失败，返回本次是否触发摘除
+    pub fn record_failure(&mut self, upstream: &str) -> bool {
+        let mut state = self.hosts.remove(upstream).unwrap_or_default();
+        let ejected = state.ejected_until.is_none() && {
+            state.consecutive_failures += 1;
+            state.consecutive_failures >= self.consecutive_failures
+        };
+        if !ejected {
+            debug!(upstream = %upstream, failures = state.consecutive_failures, "upstream failure recorded");
+            self.hosts.insert(upstream.to_string(), state);
+            return false;
+        }
+
+        state.ejection_count = state.ejection_count.saturating_add(1);
+        state.consecutive_failures = 0;
+        let count = state.ejection_count;
+        let duration = self.ejection_duration(count);
+        state.ejected_until = Some(Instant::now() + duration);
+        self.hosts.insert(upstream.to_string(), state);
+        warn!(event = "upstream_ejected", upstream = %upstream, ejection_count = count, duration_ms = %duration.as_millis(), "ejecting upstream after consecutive failures");
+        true
+    }
+
+    /// 其他实例报告该上游被摘除：未处于摘除期时按本地的摘除时长摘除，返回是否新摘除
+    pub fn eject(&mut self, upstream: &str) -> bool {
+        if self.is_ejected(upstream) {
+            return false;
+        }
+        let mut state = self.hosts.remove(upstream).unwrap_or_default();
+        state.ejection_count = state.ejection_count.saturating_add(1);
+        state.consecutive_failures = 0;
+        let duration = self.ejection_duration(state.ejection_count);
+        state.ejected_until = Some(Instant::now() + duration);
+        self.hosts.insert(upstream.to_string(), state);
+        warn!(event = "upstream_ejected", upstream = %upstream, duration_ms = %duration.as_millis(), source = "cluster", "ejecting upstream reported by a cluster peer");
+        true
+    }
+
+    /// 上游当前是否处于摘除期；到期的摘除会在此处被清理
+    pub fn is_ejected(&mut self, upstream: &str) -> bool {
+        let Some(state) = self.hosts.get_mut(upstream) else {
+            return false;
+        };
+        match state.ejected_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                state.ejected_until = None;
+                info!(event = "upstream_restored", upstream = %upstream, "upstream ejection expired");
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn ejected_hosts(&mut self) -> Vec<String> {
+        self.sweep().0
+    }
+
+    /// 清理所有到期的摘除，返回 (仍在摘除期的上游, 本次恢复的上游)
+    fn sweep(&mut self) -> (Vec<String>, Vec<String>) {
+        let keys: Vec<String> = self.hosts.keys().cloned().collect();
+        let (mut ejected, mut restored) = (Vec::new(), Vec::new());
+        for k in keys {
+            let was_ejected = self.hosts.get(&k).is_some_and(|s| s.ejected_until.is_some());
+            if self.is_ejected(&k) {
+                ejected.push(k);
+            } else if was_ejected {
+                restored.push(k);
+            }
+        }
+        (ejected, restored)
+    }
+}
+
+#[derive(Clone)]
+pub struct OutlierDetector {
+    // 选择上游时在同步回调中读取，因此使用 std Mutex
+    inner: Arc<Mutex<OutlierDetectorInner>>,
+    enabled: bool,
+    metrics: Arc<Metrics>,
+}
+
+impl OutlierDetector {
+    pub fn new(
+        consecutive_failures: u32,
+        base_ejection: Duration,
+        max_ejection: Duration,
+        enabled: bool,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(OutlierDetectorInner::new(
+                consecutive_failures,
+                base_ejection,
+                max_ejection,
+            ))),
+            enabled,
+            metrics: Metrics::detached(),
+        }
+    }
+
+    /// 摘除相关指标记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_ejected(&self, upstream: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let ejected = self.inner.lock().expect("outlier lock").is_ejected(upstream);
+        if !ejected {
+            self.metrics.upstream_ejected.with_label_values(&[upstream]).set(0);
+        }
+        ejected
+    }
+
+    pub fn record_success(&self, upstream: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.inner.lock().expect("outlier lock").record_success(upstream);
+    }
+
+    pub fn record_failure(&self, upstream: &str) {
+        if !self.enabled {
+            return;
+        }
+        let mut inner = self.inner.lock().expect("outlier lock");
+        if inner.record_failure(upstream) {
+            self.metrics.upstream_ejections_total.inc();
+            self.metrics.upstream_ejected.with_label_values(&[upstream]).set(1);
+        }
+        self.record_sweep(&mut inner);
+    }
+
+    /// 见 [`OutlierDetectorInner::eject`]
+    pub fn eject(&self, upstream: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let mut inner = self.inner.lock().expect("outlier lock");
+        let ejected = inner.eject(upstream);
+        if ejected {
+            self.metrics.upstream_ejections_total.inc();
+            self.metrics.upstream_ejected.with_label_values(&[upstream]).set(1);
+        }
+        self.record_sweep(&mut inner);
+        ejected
+    }
+
+    pub fn ejected_hosts(&self) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut inner = self.inner.lock().expect("outlier lock");
+        self.record_sweep(&mut inner)
+    }
+
+    /// 清理到期摘除：恢复的上游 `upstream_ejected` 归零，并刷新摘除总数
+    fn record_sweep(&self, inner: &mut OutlierDetectorInner) -> Vec<String> {
+        let (ejected, restored) = inner.sweep();
+        for host in &restored {
+            self.metrics.upstream_ejected.with_label_values(&[host]).set(0);
+        }
+        self.metrics.upstream_ejected_hosts.set(ejected.len() as i64);
+        ejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_ejects_after_consecutive_failures() {
+        let detector = OutlierDetector::new(3, Duration::from_millis(100), Duration::from_secs(1), true);
+
+        detector.record_failure("10.0.0.1:80");
+        detector.record_failure("10.0.0.1:80");
+        assert!(!detector.is_ejected("10.0.0.1:80"));
+
+        detector.record_failure("10.0.0.1:80");
+        assert!(detector.is_ejected("10.0.0.1:80"));
+        assert!(!detector.is_ejected("10.0.0.2:80"));
+        assert_eq!(detector.ejected_hosts(), vec!["10.0.0.1:80".to_string()]);
+    }
+
+    #[test]
+    fn test_success_resets_consecutive_failures() {
+        let detector = OutlierDetector::new(2, Duration::from_millis(100), Duration::from_secs(1), true);
+
+        detector.record_failure("10.0.0.1:80");
+        detector.record_success("10.0.0.1:80");
+        detector.record_failure("10.0.0.1:80");
+        assert!(!detector.is_ejected("10.0.0.1:80"));
+    }
+
+    #[test]
+    fn test_peer_ejection() {
+        let detector = OutlierDetector::new(3, Duration::from_millis(100), Duration::from_secs(1), true);
+        assert!(detector.eject("10.0.0.1:80"));
+        assert!(detector.is_ejected("10.0.0.1:80"));
+        assert!(!detector.eject("10.0.0.1:80"));
+    }
+
+    #[test]
+    fn test_ejection_expires() {
+        let detector = OutlierDetector::new(1, Duration::from_millis(30), Duration::from_secs(1), true);
+
+        detector.record_failure("10.0.0.1:80");
+        assert!(detector.is_ejected("10.0.0.1:80"));
+
+        sleep(Duration::from_millis(40));
+        assert!(!detector.is_ejected("10.0.0.1:80"));
+    }
+
+    #[test]
+    fn test_expired_ejection_resets_gauge_in_ejected_hosts() {
+        let metrics = Metrics::detached();
+        let detector = OutlierDetector::new(1, Duration::from_millis(30), Duration::from_secs(1), true)
+            .with_metrics(Arc::clone(&metrics));
+        let gauge = || metrics.upstream_ejected.with_label_values(&["10.0.0.1:80"]).get();
+
+        detector.record_failure("10.0.0.1:80");
+        assert_eq!((gauge(), metrics.upstream_ejected_hosts.get()), (1, 1));
+
+        sleep(Duration::from_millis(40));
+        assert!(detector.ejected_hosts().is_empty());
+        assert_eq!((gauge(), metrics.upstream_ejected_hosts.get()), (0, 0));
+    }
+
+    #[test]
+    fn test_ejection_time_grows_exponentially() {
+        let inner = OutlierDetectorInner::new(1, Duration::from_secs(10), Duration::from_secs(60));
+        assert_eq!(inner.ejection_duration(1), Duration::from_secs(10));
+        assert_eq!(inner.ejection_duration(2), Duration::from_secs(20));
+        assert_eq!(inner.ejection_duration(3), Duration::from_secs(40));
+        assert_eq!(inner.ejection_duration(4), Duration::from_secs(60));
+        assert_eq!(inner.ejection_duration(40), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_outlier_detector_disabled() {
+        let detector = OutlierDetector::new(1, Duration::from_secs(10), Duration::from_secs(60), false);
+
+        for _ in 0..10 {
+            detector.record_failure("10.0.0.1:80");
+        }
+        assert!(!detector.is_ejected("10.0.0.1:80"));
+    }
+}
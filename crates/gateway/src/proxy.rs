@@ -1,37 +1,153 @@
 use async_trait::async_trait;
 use arc_swap::ArcSwap;
 use pingora_core::upstreams::peer::HttpPeer;
-use pingora_core::Result;
-use pingora_http::RequestHeader;
+use pingora_core::{ErrorSource, ErrorType, Result};
+use pingora_http::{RequestHeader, ResponseHeader};
 use pingora_load_balancing::selection::RoundRobin;
-use pingora_load_balancing::LoadBalancer;
-use pingora_proxy::{ProxyHttp, Session};
+use pingora_load_balancing::{Backend, LoadBalancer};
+use pingora_proxy::{FailToProxy, ProxyHttp, Session};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
+use common::feature_flags;
+use common::time::SharedClock;
 
-use crate::circuit_breaker::CircuitBreaker;
-use crate::config::ProxyConfig;
-use crate::observability::{
-    CIRCUIT_BREAKER_OPEN_TOTAL, REQUESTS_TOTAL, REQUEST_DURATION, RETRIES_TOTAL,
-    UPSTREAM_ERRORS_TOTAL, UPSTREAM_SELECTED_TOTAL,
-};
-use crate::rate_limiter::RateLimiter;
+use crate::adaptive_limit::{AdaptiveLimiter, AdaptivePermit};
+use crate::bandit::BanditSelector;
+use crate::bot::{BotDetector, ClientRequest, Outcome, BOT_SCORE_HEADER, CHALLENGE_COOKIE};
+use crate::bulkhead::{Bulkhead, BulkheadPermit};
+use crate::circuit_breaker::{CircuitBreaker, ProbePermit};
+use crate::config::{ProxyConfig, RecordingMode};
+use crate::content_type;
+use crate::faults::{FaultAction, FaultInjector, FAULT_HEADER};
+use crate::failover::{self, Failover, Transition};
+use crate::geoip::{GeoDecision, GeoInfo, GeoIp, GEO_ASN_HEADER, GEO_COUNTRY_HEADER};
+use crate::debug::{self, Decision, DEBUG_HEADER, DEBUG_TIMESTAMP_HEADER, DRY_RUN_HEADER};
+use crate::error_pages::{ErrorPages, PageVars, RenderedPage, PAGE_STATUSES};
+use crate::errors::{self, ErrorBody, GatewayErrorCode, JSON_CONTENT_TYPE};
+use crate::key_activity::KeyActivityRecorder;
+use crate::latency::{self, LatencyTracker};
+use crate::listeners::{self, Listeners};
+use crate::locality::{Locality, LocalitySelector};
+use crate::load_shed::{self, InFlightGuard, LoadShedder, Priority};
+use crate::observability::Metrics;
+use crate::recording::{Capture, RecordedResponse, Recording, PLAYBACK_HEADER};
+use crate::outlier::OutlierDetector;
+use crate::rate_limiter::{RateLimitStatus, RateLimiter};
 use crate::retry::{retry_with_policy, RetryPolicy, RetryableError};
+use crate::signing::{self, RequestSigner, UNSIGNED_PAYLOAD};
+use crate::upload::{self, MultipartInspector, UploadViolation};
+use crate::secrets::UpstreamSecrets;
+use crate::waf::{Waf, WAF_TAGS_HEADER};
 
 pub struct LB {
     pub load_balancer: Arc<LoadBalancer<RoundRobin>>,
     pub rate_limiter: RateLimiter,
     pub circuit_breaker: CircuitBreaker,
+    /// 按路由的主/备上游分组（未启用时所有请求使用 load_balancer）
+    pub failover: Arc<Failover>,
     pub retry_policy: RetryPolicy,
+    pub outlier_detector: OutlierDetector,
+    pub bulkhead: Bulkhead,
+    pub adaptive_limiter: AdaptiveLimiter,
+    pub load_shedder: LoadShedder,
+    pub bandit: BanditSelector,
+    /// 就近选择 `upstreams` 中的节点（未启用时不限制）
+    pub locality: LocalitySelector,
+    pub listeners: Arc<Listeners>,
+    pub waf: Waf,
+    pub bot_detector: BotDetector,
+    pub geoip: Arc<GeoIp>,
+    pub latency: Arc<LatencyTracker>,
+    /// 按上游注入的认证凭据与 request_signing 签名器（配置热加载时重建）
+    pub secrets: Arc<UpstreamSecrets>,
+    /// 流量录制/回放路由（启动时加载）
+    pub recording: Arc<Recording>,
+    /// 按路由注入的故障（混沌测试，默认关闭）
+    pub faults: Arc<FaultInjector>,
+    pub error_pages: Arc<ErrorPages>,
+    /// 启用 key_activity 时按请求头记录 API key 的最近使用时间
+    pub key_activity: Option<KeyActivityRecorder>,
+    /// 本实例的指标（admin `/metrics` 导出同一 registry）
+    pub metrics: Arc<Metrics>,
+    /// 调试请求与转发签名的时间戳（允许的时钟偏差据此判断）
+    pub clock: SharedClock,
     pub config: Arc<ArcSwap<ProxyConfig>>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct RequestCtx {
     pub start: std::time::Instant,
     pub request_id: Uuid,
     pub upstream_addr: Option<String>,
+    /// 是否已向 outlier detector 上报本次请求结果
+    pub outcome_recorded: bool,
+    /// bulkhead 并发名额，请求结束（ctx 释放）时归还
+    pub bulkhead_permit: Option<BulkheadPermit>,
+    /// 自适应并发名额（启用 adaptive_concurrency 时替代 bulkhead_permit）
+    pub adaptive_permit: Option<AdaptivePermit>,
+    /// 是否因 bulkhead 饱和被拒绝
+    pub bulkhead_rejected: bool,
+    /// 是否在响应头中返回网关决策信息
+    pub debug: bool,
+    /// 只评估处理流程，不转发
+    pub dry_run: bool,
+    pub decision: Decision,
+    pub priority: Priority,
+    /// 计入网关在途请求数（过载判断），ctx 释放时归还
+    pub in_flight: Option<InFlightGuard>,
+    /// 请求开始时生效的配置版本，拒绝/完成日志中携带以关联控制面变更
+    pub config_revision: Option<i64>,
+    /// 请求进入的 listener 名
+    pub listener: Option<String>,
+    /// 命中的 WAF tag 规则
+    pub waf_tags: Vec<String>,
+    /// 机器人识别分数（启用 bot_detection 时）
+    pub bot_score: Option<u32>,
+    /// 客户端国家/ASN（启用 geoip 时）
+    pub geo: Option<GeoInfo>,
+    /// 本请求是否走 bandit 上游选择（特性开关 `gateway.bandit_selection`，未定义时为 true）
+    pub bandit: bool,
+    /// 请求签名用的 body 摘要（启用 request_signing 时）
+    pub body_sha256: Option<String>,
+    /// 命中 uploads 规则的 multipart 请求，在 request_body_filter 中逐块检查
+    pub upload: Option<MultipartInspector>,
+    /// record 模式路由的请求/响应，响应结束时写入 fixture
+    pub recording: Option<Capture>,
+    /// 本请求被注入了延迟（响应带 `X-Gateway-Fault: delay`）
+    pub fault_delayed: bool,
+    /// 限流检查结果，用于 `X-RateLimit-*` 响应头（`rate_limit.headers` 关闭时为 None）
+    pub rate_limit: Option<RateLimitStatus>,
+    /// 命中故障转移路由时本请求使用的上游分组；None 时使用 `upstreams`
+    pub upstream_group: Option<String>,
+    /// half-open 试探名额，请求结束（ctx 释放）时归还
+    pub probe_permit: Option<ProbePermit>,
+}
+
+impl Default for RequestCtx {
+    fn default() -> Self {
+        RequestCtx { start: std::time::Instant::now(), request_id: Uuid::new_v4(), upstream_addr: None, outcome_recorded: false, bulkhead_permit: None, adaptive_permit: None, bulkhead_rejected: false, debug: false, dry_run: false, decision: Decision::default(), priority: Priority::Normal, in_flight: None, config_revision: None, listener: None, waf_tags: Vec::new(), bot_score: None, geo: None, bandit: true, body_sha256: None, upload: None, recording: None, fault_delayed: false, rate_limit: None, upstream_group: None, probe_permit: None }
+    }
+}
+
+/// 转发前改写上游请求头：Host、请求 ID 与 WAF/GeoIP/机器人识别结果
+pub fn rewrite_upstream_headers(upstream_request: &mut RequestHeader, upstream_host: &str, ctx: &RequestCtx) {
+    upstream_request.insert_header("Host", upstream_host).unwrap();
+    // 传播请求ID到上游，便于链路追踪
+    upstream_request.insert_header("X-Request-Id", &ctx.request_id.to_string()).ok();
+    if !ctx.waf_tags.is_empty() {
+        upstream_request.insert_header(WAF_TAGS_HEADER, ctx.waf_tags.join(",")).ok();
+    }
+    if let Some(geo) = &ctx.geo {
+        upstream_request.insert_header(GEO_COUNTRY_HEADER, geo.country_code()).ok();
+        if let Some(asn) = geo.asn {
+            upstream_request.insert_header(GEO_ASN_HEADER, asn.to_string()).ok();
+        }
+    }
+    if let Some(score) = ctx.bot_score {
+        upstream_request.insert_header(BOT_SCORE_HEADER, score.to_string()).ok();
+    }
 }
 
 fn summarize_query(uri: &str) -> Vec<String> {
@@ -49,13 +165,80 @@ fn summarize_query(uri: &str) -> Vec<String> {
     }
 }
 
+impl LB {
+    fn reject_upload(&self, ctx: &mut RequestCtx, route: &str, violation: &UploadViolation) {
+        self.metrics.upload_rejected_total.with_label_values(&[route, violation.as_str()]).inc();
+        warn!(event = "upload_rejected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, route, reason = violation.as_str(), error = %violation, "Upload rejected by upload limits");
+        ctx.decision.limiter = "upload";
+    }
+
+    /// 本请求所用分组的熔断器
+    fn breaker(&self, ctx: &RequestCtx) -> &CircuitBreaker {
+        ctx.upstream_group.as_deref().and_then(|g| self.failover.group(g)).map(|g| &g.breaker).unwrap_or(&self.circuit_breaker)
+    }
+
+    /// 跳过被动健康检查摘除的上游；若全部被摘除则退回主动健康检查结果（备用分组同样处理，不参与 bandit）
+    /// 启用 locality 时只在就近的节点中选择，本地可用节点不足时才溢出到更远的节点
+    fn select_backend(&self, group: Option<&str>, use_bandit: bool) -> Option<Backend> {
+        if let Some(group) = group.filter(|g| *g != failover::DEFAULT_GROUP).and_then(|g| self.failover.group(g)) {
+            return group.select(|backend| !self.outlier_detector.is_ejected(&backend.addr.to_string()));
+        }
+        let backends = self.load_balancer.backends();
+        let available = |b: &Backend| backends.ready(b) && !self.outlier_detector.is_ejected(&b.addr.to_string());
+        let reach = if self.locality.is_enabled() {
+            let peers = backends.get_backend().iter().map(|b| (b.addr.to_string(), available(b))).collect::<Vec<_>>();
+            self.locality.reach(peers.iter().map(|(addr, ok)| (addr.as_str(), *ok)))
+        } else {
+            Locality::CrossRegion
+        };
+        let nearby = |b: &Backend| reach == Locality::CrossRegion || self.locality.locality(&b.addr.to_string()) <= reach;
+        if use_bandit && self.bandit.is_enabled() {
+            let candidates: Vec<Backend> = backends
+                .get_backend()
+                .iter()
+                .filter(|b| available(b) && nearby(b))
+                .cloned()
+                .collect();
+            let addrs: Vec<String> = candidates.iter().map(|b| b.addr.to_string()).collect();
+            if let Some(i) = self.bandit.select(&addrs) {
+                return Some(candidates[i].clone());
+            }
+        }
+        self.load_balancer
+            .select_with(b"", 256, |backend, healthy| {
+                healthy && !self.outlier_detector.is_ejected(&backend.addr.to_string()) && nearby(backend)
+            })
+            .or_else(|| self.load_balancer.select(b"", 256))
+    }
+
+    /// 租户为该状态码配置了与 Accept 匹配的错误页时返回渲染结果
+    fn error_page(&self, session: &Session, vars: &PageVars) -> Option<RenderedPage> {
+        if !self.error_pages.is_enabled() {
+            return None;
+        }
+        let headers = &session.req_header().headers;
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        self.error_pages.render(header(self.error_pages.tenant_header()), header("accept"), vars)
+    }
+
+    /// 网关自身的错误响应：默认为 JSON body（见 `errors`），租户配置了错误页时用错误页
+    async fn respond_gateway_error(&self, session: &mut Session, code: GatewayErrorCode, retry_after: Option<u64>, request_id: Uuid, extra: &[(&'static str, String)]) -> Result<()> {
+        let vars = PageVars { status: code.status(), error: code.as_str(), message: code.message(), request_id, retry_after };
+        let (content_type, body) = match self.error_page(session, &vars) {
+            Some(page) => (page.content_type, page.body),
+            None => (JSON_CONTENT_TYPE.to_string(), ErrorBody::new(code, retry_after, request_id).to_json()),
+        };
+        write_error_response(session, code.status(), &content_type, body, retry_after, request_id, extra).await
+    }
+}
+
 #[async_trait]
 impl ProxyHttp for LB {
     type CTX = RequestCtx;
 
     fn new_ctx(&self) -> Self::CTX {
-        REQUESTS_TOTAL.inc();
-        RequestCtx { start: std::time::Instant::now(), request_id: Uuid::new_v4(), upstream_addr: None }
+        self.metrics.requests_total.inc();
+        RequestCtx::default()
     }
 
     async fn request_filter(&self, session: &mut Session, ctx: &mut Self::CTX) -> Result<bool> {
@@ -63,68 +246,454 @@ impl ProxyHttp for LB {
         let method = session.req_header().method.to_string();
         let uri = session.req_header().uri.to_string();
         let query_keys = summarize_query(&uri);
+        let uri = self.config.load().redaction.redact_uri(&uri).into_owned();
+        ctx.listener = session
+            .server_addr()
+            .and_then(|a| a.as_inet())
+            .and_then(|a| self.listeners.name_for(a))
+            .map(str::to_string);
         info!(
             event = "request_start",
             request_id = %ctx.request_id,
+            listener = ?ctx.listener,
             method = %method,
             uri = %uri,
             query_keys = ?query_keys,
             "incoming request"
         );
-        // Check rate limiting
-        if !self.rate_limiter.check_rate_limit().await {
-            crate::observability::RATE_LIMITED_TOTAL.inc();
-            warn!(event = "rate_limited", request_id = %ctx.request_id, reason = "rate limiter", "Request rejected by rate limiter");
-            let _ = session.respond_error(429).await;
+        let (scope_denied, untrusted_priority) = {
+            let config = self.config.load();
+            ctx.config_revision = config.revision;
+            let header = |name: &str| session.req_header().headers.get(name).and_then(|v| v.to_str().ok());
+            let scopes = &config.listener_scopes;
+            let denied = !listeners::is_allowed(scopes, ctx.listener.as_deref(), session.req_header().uri.path(), header(&scopes.tenant_header));
+            let now = self.clock.now().timestamp();
+            ctx.debug = debug::is_debug_request(&config.debug, &method, session.req_header().uri.path(), header(DEBUG_HEADER), header(DEBUG_TIMESTAMP_HEADER), now);
+            ctx.dry_run = ctx.debug && debug::is_dry_run(header(DRY_RUN_HEADER));
+            if ctx.debug {
+                ctx.decision.route = latency::route_label(&config.latency.routes, session.req_header().uri.path()).to_string();
+            }
+            ctx.bandit = config.feature_flags.enabled_or(feature_flags::GATEWAY_BANDIT, header(&scopes.tenant_header), true);
+            let shed = &config.load_shedding;
+            let client = session.client_addr().and_then(|a| a.as_inet()).map(|a| a.ip());
+            let trusted = load_shed::is_trusted_source(&shed.trusted_sources, client);
+            ctx.priority = if trusted {
+                load_shed::classify(header(&shed.priority_header), header(&shed.tier_header), &shed.tiers, shed.default_priority)
+            } else {
+                shed.default_priority
+            };
+            (denied, (!trusted).then(|| [shed.priority_header.clone(), shed.tier_header.clone()]))
+        };
+        // 不可信来源的优先级头不转发给上游，避免下游据此提权
+        if let Some(names) = untrusted_priority {
+            for name in &names {
+                session.req_header_mut().remove_header(name.as_str());
+            }
+        }
+
+        // 限定在其他 listener 上的路由/租户：按不存在处理
+        if scope_denied {
+            let listener = ctx.listener.as_deref().unwrap_or("unknown");
+            self.metrics.listener_scope_denied_total.with_label_values(&[listener]).inc();
+            warn!(event = "listener_scope_denied", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, listener, "Route not exposed on this listener");
+            ctx.decision.limiter = "listener_scope";
+            let _ = session.respond_error(404).await;
             return Ok(true);
         }
-        debug!(event = "rate_limit_pass", request_id = %ctx.request_id, "rate limiter allowed request");
 
-        // Check circuit breaker
-        if !self.circuit_breaker.can_execute().await {
-            CIRCUIT_BREAKER_OPEN_TOTAL.inc();
-            warn!(event = "circuit_open", request_id = %ctx.request_id, reason = "circuit breaker", "Request rejected by circuit breaker");
-            let _ = session.respond_error(503).await;
+        if self.waf.is_enabled() {
+            let client = session.client_addr().and_then(|a| a.as_inet()).map(|a| a.ip().to_string()).unwrap_or_default();
+            let req = session.req_header();
+            let target = req.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+            let verdict = self.waf.evaluate(&method, target, &client, |name| req.headers.get(name).and_then(|v| v.to_str().ok()));
+            ctx.waf_tags = verdict.tags;
+            if let Some((rule, status)) = verdict.rejected {
+                warn!(target: "security", event = "waf_rejected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, rule = %rule, status, client = %client, "Request rejected by WAF rule");
+                ctx.decision.limiter = "waf";
+                let result = if ctx.dry_run { respond_dry_run(session, status, &ctx.decision).await } else { session.respond_error(status).await };
+                if let Err(err) = result {
+                    error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send waf response");
+                }
+                return Ok(true);
+            }
+        }
+
+        if self.geoip.is_enabled() {
+            let client = session.client_addr().and_then(|a| a.as_inet()).map(|a| a.ip());
+            let geo = client.map(|ip| self.geoip.lookup(ip)).unwrap_or_default();
+            let client = client.map(|ip| ip.to_string()).unwrap_or_default();
+            let decision = self.geoip.check(session.req_header().uri.path(), &geo, &client);
+            if decision != GeoDecision::Allow {
+                let status = if decision == GeoDecision::RateLimited { 429 } else { 403 };
+                warn!(target: "security", event = "geo_rejected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, country = geo.country_code(), asn = ?geo.asn, status, client = %client, "Request rejected by geo policy");
+                ctx.decision.limiter = "geo";
+                let result = if ctx.dry_run { respond_dry_run(session, status, &ctx.decision).await } else { session.respond_error(status).await };
+                if let Err(err) = result {
+                    error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send geo policy response");
+                }
+                return Ok(true);
+            }
+            ctx.geo = Some(geo);
+        }
+
+        if self.bot_detector.is_enabled() {
+            let client = session.client_addr().and_then(|a| a.as_inet()).map(|a| a.ip().to_string()).unwrap_or_default();
+            let req = session.req_header();
+            let names: Vec<&str> = req.headers.keys().map(|k| k.as_str()).collect();
+            let client_req = ClientRequest { client_ip: &client, method: &method, header_names: &names };
+            let verdict = self.bot_detector.inspect(&client_req, |name| req.headers.get(name).and_then(|v| v.to_str().ok()));
+            ctx.bot_score = Some(verdict.score);
+            if verdict.outcome != Outcome::Pass {
+                warn!(
+                    target: "security",
+                    event = "bot_rejected",
+                    request_id = %ctx.request_id,
+                    config_revision = ?ctx.config_revision,
+                    outcome = verdict.outcome.as_str(),
+                    score = verdict.score,
+                    reasons = ?verdict.reasons,
+                    fingerprint = %verdict.fingerprint,
+                    client = %client,
+                    "Request rejected by bot detection"
+                );
+                ctx.decision.limiter = "bot";
+                let result = match (&verdict.outcome, ctx.dry_run) {
+                    (Outcome::Throttled, true) => respond_dry_run(session, 429, &ctx.decision).await,
+                    (_, true) => respond_dry_run(session, 403, &ctx.decision).await,
+                    (Outcome::Throttled, false) => session.respond_error(429).await,
+                    (Outcome::Challenge(token), false) => respond_bot_challenge(session, token).await,
+                    _ => session.respond_error(403).await,
+                };
+                if let Err(err) = result {
+                    error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send bot detection response");
+                }
+                return Ok(true);
+            }
+        }
+
+        // 过载保护：低优先级请求先被丢弃
+        if self.load_shedder.should_shed(ctx.priority) {
+            warn!(event = "load_shed", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, priority = ctx.priority.as_str(), load = self.load_shedder.load(), "Request shed due to overload");
+            ctx.decision.limiter = "load_shed";
+            let result = if ctx.dry_run {
+                respond_dry_run(session, 503, &ctx.decision).await
+            } else {
+                let retry_after = self.config.load().load_shedding.retry_after_secs;
+                self.respond_gateway_error(session, GatewayErrorCode::Overloaded, Some(retry_after), ctx.request_id, &[]).await
+            };
+            if let Err(err) = result {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send 503 response");
+            }
+            return Ok(true);
+        }
+        ctx.in_flight = Some(self.load_shedder.enter());
+        // Check rate limiting（spike arrest 按 API key / 客户端 IP 区分）
+        // key 与租户头只采用可信来源（前置 key 校验层）的值，否则客户端可随意更换 key 绕过限流
+        // 指标标签中 API key 只保留摘要前缀，客户端 IP 原样保留
+        let (limit_key, limit_label, limit_tenant) = {
+            let config = self.config.load();
+            let rl = &config.rate_limit;
+            let client = session.client_addr().and_then(|a| a.as_inet()).map(|a| a.ip());
+            let trusted = load_shed::is_trusted_source(&rl.trusted_sources, client);
+            let header = |name: &str| session.req_header().headers.get(name).and_then(|v| v.to_str().ok()).filter(|_| trusted);
+            let tenant = header(&rl.tenant_header).and_then(|v| Uuid::parse_str(v.trim()).ok());
+            match header(&rl.spike_arrest_key_header) {
+                Some(key) => (key.to_string(), format!("key:{}", &signing::body_sha256(key.as_bytes())[..12]), tenant),
+                None => {
+                    let ip = client.map(|ip| ip.to_string()).unwrap_or_default();
+                    (ip.clone(), format!("ip:{}", ip), tenant)
+                }
+            }
+        };
+        // dry-run 只预览限流结果，不扣减真实流量的额度
+        let rate_limit = if ctx.dry_run {
+            self.rate_limiter.preview(&limit_key, limit_tenant)
+        } else {
+            self.rate_limiter.check(&limit_key, &limit_label, limit_tenant)
+        };
+        let rate_limit_headers = self.config.load().rate_limit.headers;
+        if rate_limit_headers {
+            ctx.rate_limit = rate_limit;
+        }
+        if let Some((status, reason)) = rate_limit.and_then(|s| s.rejected.map(|r| (s, r))) {
+            self.metrics.rate_limited_total.inc();
+            warn!(event = "rate_limited", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, reason = reason.as_str(), key = %limit_label, "Request rejected by rate limiter");
+            ctx.decision.limiter = "rate_limited";
+            if ctx.dry_run {
+                let _ = respond_dry_run(session, 429, &ctx.decision).await;
+                return Ok(true);
+            }
+            let extra = if rate_limit_headers { status.headers().to_vec() } else { Vec::new() };
+            if let Err(err) = self.respond_gateway_error(session, GatewayErrorCode::RateLimited, Some(status.retry_after_secs()), ctx.request_id, &extra).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send 429 response");
+            }
             return Ok(true);
         }
+        debug!(event = "rate_limit_pass", request_id = %ctx.request_id, "rate limiter allowed request");
+
+        // 故障转移：主分组无健康节点或熔断打开时改用备用分组，随后按所用分组的熔断器判断
+        if let Some(routing) = self.failover.route(session.req_header().uri.path()) {
+            let route = routing.route.as_str();
+            match routing.transition {
+                Some(Transition::Failover(reason)) => {
+                    self.metrics.failover_active.with_label_values(&[route]).set(1);
+                    self.metrics.failover_transitions_total.with_label_values(&[route, "fallback"]).inc();
+                    warn!(event = "upstream_failover", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, route, primary = %routing.primary, fallback = %routing.fallback, reason = reason.as_str(), "Primary upstream group unavailable; failing over");
+                }
+                Some(Transition::Restore) => {
+                    self.metrics.failover_active.with_label_values(&[route]).set(0);
+                    self.metrics.failover_transitions_total.with_label_values(&[route, "primary"]).inc();
+                    info!(event = "upstream_restored", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, route, primary = %routing.primary, fallback = %routing.fallback, "Primary upstream group restored");
+                }
+                None => {}
+            }
+            ctx.upstream_group = Some(routing.group);
+        }
+
+        // Check circuit breaker（half-open 时可能排队等待试探名额）；dry-run 只预览判定，不迁移状态、不占用试探名额
+        let admitted = if ctx.dry_run { self.breaker(ctx).preview().map(|()| None) } else { self.breaker(ctx).admit().await };
+        ctx.probe_permit = match admitted {
+            Ok(permit) => permit,
+            Err(rejection) => {
+                self.metrics.circuit_breaker_open_total.inc();
+                warn!(event = "circuit_open", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, reason = "circuit breaker", rejection = rejection.as_str(), "Request rejected by circuit breaker");
+                ctx.decision.limiter = "circuit_open";
+                if ctx.dry_run {
+                    let _ = respond_dry_run(session, 503, &ctx.decision).await;
+                    return Ok(true);
+                }
+                let retry_after = errors::retry_after_secs(self.breaker(ctx).retry_after());
+                if let Err(err) = self.respond_gateway_error(session, GatewayErrorCode::CircuitOpen, Some(retry_after), ctx.request_id, &[]).await {
+                    error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send 503 response");
+                }
+                return Ok(true);
+            }
+        };
         debug!(event = "circuit_ok", request_id = %ctx.request_id, "circuit breaker allows execution");
 
+        // dry-run：只预览上游选择结果，不占用 bulkhead 名额，也不转发
+        if ctx.dry_run {
+            ctx.decision.upstream = self.select_backend(ctx.upstream_group.as_deref(), ctx.bandit).map(|b| b.addr.to_string());
+            let mut status = if ctx.decision.upstream.is_some() { 200 } else {
+                ctx.decision.limiter = "no_upstream";
+                GatewayErrorCode::NoUpstream.status()
+            };
+            // 回放路由按录制结果预览缓存状态；请求不会转发，读取请求体不影响上游
+            if let Some((_, route)) = self.recording.route_for(session.req_header().uri.path()).filter(|(_, r)| r.rule.mode == RecordingMode::Playback) {
+                let body = read_request_body(session).await?;
+                let (method, uri, headers) = request_parts(session.req_header());
+                let redaction = &self.config.load().redaction;
+                match self.recording.playback(route, &method, &uri, &headers, &body, redaction) {
+                    Some(recorded) => {
+                        (ctx.decision.cache, ctx.decision.limiter, status) = ("HIT", "playback", recorded.status);
+                    }
+                    None if route.rule.passthrough_on_miss => ctx.decision.cache = "MISS",
+                    None => (ctx.decision.cache, ctx.decision.limiter, status) = ("MISS", "playback", 404),
+                }
+            }
+            info!(event = "dry_run", request_id = %ctx.request_id, decision = ?ctx.decision, "dry-run request evaluated");
+            if let Err(err) = respond_dry_run(session, status, &ctx.decision).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send dry-run response");
+            }
+            return Ok(true);
+        }
+
+        let content_type_rejected = {
+            let config = self.config.load();
+            let req = session.req_header();
+            let header = |name: &str| req.headers.get(name).and_then(|v| v.to_str().ok());
+            content_type::rule_for(&config.content_types, req.uri.path())
+                .filter(|rule| {
+                    let has_body = content_type::has_body(header("content-length"), header("transfer-encoding"));
+                    !rule.accepts_request(header("content-type"), has_body)
+                })
+                .map(|rule| (rule.path_prefix.clone(), header("content-type").unwrap_or_default().to_string()))
+        };
+        if let Some((route, received)) = content_type_rejected {
+            self.metrics.content_type_rejected_total.with_label_values(&[route.as_str()]).inc();
+            warn!(event = "content_type_rejected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, route = %route, content_type = %received, "Request content type not accepted by route");
+            ctx.decision.limiter = "content_type";
+            let _ = session.respond_error(415).await;
+            return Ok(true);
+        }
+
+        let upload = {
+            let config = self.config.load();
+            let req = session.req_header();
+            let content_type = req.headers.get("content-type").and_then(|v| v.to_str().ok());
+            upload::inspector_for(&config.uploads, req.uri.path(), content_type).map_err(|violation| {
+                let route = upload::rule_for(&config.uploads, req.uri.path()).map(|r| r.path_prefix.clone()).unwrap_or_default();
+                (route, violation)
+            })
+        };
+        match upload {
+            Ok(inspector) => ctx.upload = inspector,
+            Err((route, violation)) => {
+                self.reject_upload(ctx, &route, &violation);
+                let _ = session.respond_error(violation.status()).await;
+                return Ok(true);
+            }
+        }
+
+        // 故障注入：先延迟，再按规则返回错误或关闭连接；注入的错误不计入上游失败，除非规则要求计入熔断器
+        if let Some(plan) = self.faults.plan(session.req_header().uri.path()) {
+            let route = plan.route.as_str();
+            if let Some(delay) = plan.delay {
+                self.metrics.faults_injected_total.with_label_values(&[route, "delay"]).inc();
+                debug!(event = "fault_injected", request_id = %ctx.request_id, route, fault = "delay", delay_ms = delay.as_millis() as u64, "injecting delay");
+                ctx.fault_delayed = true;
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(action) = plan.action {
+                self.metrics.faults_injected_total.with_label_values(&[route, action.as_str()]).inc();
+                if plan.count_as_upstream_failure {
+                    self.breaker(ctx).record_failure();
+                }
+                warn!(event = "fault_injected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, route, fault = action.as_str(), "Injected fault instead of forwarding");
+                ctx.decision.limiter = "fault_injection";
+                match action {
+                    FaultAction::Abort(status) => {
+                        if let Err(err) = respond_fault(session, status).await {
+                            error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send injected fault response");
+                        }
+                        return Ok(true);
+                    }
+                    // 不写响应：fail_to_proxy 对下游 ConnectionClosed 不回写，连接随即关闭
+                    FaultAction::Reset => return Err(pingora_core::Error::new_down(ErrorType::ConnectionClosed)),
+                }
+            }
+        }
+
+        // 录制/回放需要完整请求体：与签名一样读入重试缓冲区，随后原样转发给上游
+        let mut buffered_body = None;
+        if let Some((idx, route)) = self.recording.route_for(session.req_header().uri.path()) {
+            let body = read_request_body(session).await?;
+            let (method, uri, headers) = request_parts(session.req_header());
+            let prefix = route.rule.path_prefix.as_str();
+            match route.rule.mode {
+                RecordingMode::Playback => {
+                    let redaction = &self.config.load().redaction;
+                    if let Some(recorded) = self.recording.playback(route, &method, &uri, &headers, &body, redaction) {
+                        self.metrics.playback_requests_total.with_label_values(&[prefix, "hit"]).inc();
+                        (ctx.decision.limiter, ctx.decision.cache) = ("playback", "HIT");
+                        debug!(event = "playback_hit", request_id = %ctx.request_id, route = %prefix, status = recorded.status, "serving recorded response");
+                        respond_playback(session, recorded).await?;
+                        return Ok(true);
+                    }
+                    ctx.decision.cache = "MISS";
+                    if !route.rule.passthrough_on_miss {
+                        self.metrics.playback_requests_total.with_label_values(&[prefix, "miss"]).inc();
+                        warn!(event = "playback_miss", request_id = %ctx.request_id, route = %prefix, method = %method, uri = %redaction.redact_uri(&uri), "No recorded response matches request");
+                        ctx.decision.limiter = "playback";
+                        respond_playback_miss(session).await?;
+                        return Ok(true);
+                    }
+                    self.metrics.playback_requests_total.with_label_values(&[prefix, "passthrough"]).inc();
+                }
+                RecordingMode::Record => {
+                    ctx.recording = self.recording.start_capture(idx, &method, &uri, headers, body.clone());
+                }
+            }
+            buffered_body = Some(body);
+        }
+
+        if let Some(signer) = self.secrets.signer() {
+            ctx.body_sha256 = Some(match &buffered_body {
+                Some(body) if signer.should_hash_body(Some(body.len())) => signing::body_sha256(body),
+                Some(_) => UNSIGNED_PAYLOAD.to_string(),
+                None => digest_request_body(session, &signer).await?,
+            });
+        }
+
         Ok(false)
     }
 
+    async fn request_body_filter(
+        &self,
+        _session: &mut Session,
+        body: &mut Option<bytes::Bytes>,
+        end_of_stream: bool,
+        ctx: &mut Self::CTX,
+    ) -> Result<()> {
+        let Some(inspector) = ctx.upload.as_mut() else {
+            return Ok(());
+        };
+        let mut result = body.as_deref().map_or(Ok(()), |chunk| inspector.feed(chunk));
+        if result.is_ok() && end_of_stream {
+            result = inspector.finish();
+        }
+        for part in inspector.drain_completed() {
+            let kind = if part.is_file { "file" } else { "field" };
+            self.metrics.upload_part_bytes.with_label_values(&[inspector.route(), kind]).observe(part.bytes as f64);
+        }
+        if let Err(violation) = result {
+            let route = inspector.route().to_string();
+            ctx.upload = None;
+            self.reject_upload(ctx, &route, &violation);
+            return Err(pingora_core::Error::explain(ErrorType::HTTPStatus(violation.status()), violation.to_string()));
+        }
+        Ok(())
+    }
+
     async fn upstream_peer(
         &self,
         _session: &mut Session,
         ctx: &mut Self::CTX,
     ) -> Result<Box<HttpPeer>> {
         debug!(event = "upstream_select_start", request_id = %ctx.request_id, "selecting upstream peer");
+        let attempts = AtomicU32::new(0);
+        let use_bandit = ctx.bandit;
+        let group = ctx.upstream_group.clone();
         let select_upstream = || async {
-            match self.load_balancer.select(b"", 256) {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            match self.select_backend(group.as_deref(), use_bandit) {
                 Some(upstream) => {
-                    UPSTREAM_SELECTED_TOTAL.inc();
+                    self.metrics.upstream_selected_total.inc();
                     debug!(event = "upstream_selected", peer = %format!("{:?}", upstream), "upstream peer selected");
                     let addr_str = format!("{}", upstream.addr);
                     let peer = Box::new(HttpPeer::new(upstream, false, String::new()));
                     Ok::<(Box<HttpPeer>, String), RetryableError>((peer, addr_str))
                 }
                 None => {
-                    UPSTREAM_ERRORS_TOTAL.inc();
+                    self.metrics.upstream_errors_total.inc();
                     Err(RetryableError::retryable("no upstream available".to_string()))
                 }
             }
         };
 
-        match retry_with_policy(&self.retry_policy, select_upstream).await {
+        let selected = retry_with_policy(&self.retry_policy, select_upstream).await;
+        ctx.decision.retries = attempts.load(Ordering::Relaxed).saturating_sub(1);
+        match selected {
             Ok((peer, addr)) => {
-                self.circuit_breaker.record_success().await;
+                ctx.decision.upstream = Some(addr.clone());
+                // 按上游隔离并发，避免单个慢上游占满全部 worker
+                let admitted = if self.adaptive_limiter.is_enabled() {
+                    self.adaptive_limiter.try_acquire(&addr).map(|p| ctx.adaptive_permit = p)
+                } else {
+                    self.bulkhead.acquire(&addr).await.map(|p| ctx.bulkhead_permit = p)
+                };
+                match admitted {
+                    Ok(()) => {}
+                    Err(e) => {
+                        warn!(event = "bulkhead_rejected", request_id = %ctx.request_id, config_revision = ?ctx.config_revision, upstream = %addr, reason = %e, "Request rejected by bulkhead");
+                        ctx.bulkhead_rejected = true;
+                        ctx.decision.limiter = "bulkhead_rejected";
+                        return Err(pingora_core::Error::explain(ErrorType::HTTPStatus(503), e.to_string()));
+                    }
+                }
+                self.breaker(ctx).record_success();
+                if self.locality.is_enabled() && ctx.upstream_group.as_deref().is_none_or(|g| g == failover::DEFAULT_GROUP) {
+                    self.metrics.locality_selections_total.with_label_values(&[self.locality.locality(&addr).as_str()]).inc();
+                }
                 ctx.upstream_addr = Some(addr.clone());
                 info!(event = "forward_start", request_id = %ctx.request_id, upstream = %addr, "forwarding request to upstream");
                 debug!(event = "upstream_select_end", request_id = %ctx.request_id, "upstream selection succeeded");
                 Ok(peer)
             }
             Err(e) => {
-                self.circuit_breaker.record_failure().await;
-                RETRIES_TOTAL.inc();
+                ctx.decision.limiter = "no_upstream";
+                self.breaker(ctx).record_failure();
+                self.metrics.retries_total.inc();
                 error!(event = "upstream_select_failed", request_id = %ctx.request_id, error = %e, "Failed to select upstream after retries");
                 Err(pingora_core::Error::new_str("upstream selection failed"))
             }
@@ -138,25 +707,83 @@ impl ProxyHttp for LB {
         ctx: &mut Self::CTX,
     ) -> Result<()> {
         let config = self.config.load();
-        if let Some(first_upstream) = config.upstreams.first() {
-            upstream_request.insert_header("Host", first_upstream).unwrap();
-        } else {
-            upstream_request.insert_header("Host", "127.0.0.1:8080").unwrap();
-        }
-        // 传播请求ID到上游，便于链路追踪
-        upstream_request.insert_header("X-Request-Id", &ctx.request_id.to_string()).ok();
+        let group = ctx.upstream_group.as_deref().and_then(|g| config.failover.groups.get(g));
+        let host = group.unwrap_or(&config.upstreams).first().map(String::as_str).unwrap_or("127.0.0.1:8080");
+        rewrite_upstream_headers(upstream_request, host, ctx);
         debug!(event = "header_injected", request_id = %ctx.request_id, upstream = %ctx.upstream_addr.as_deref().unwrap_or(""), "injected Host and X-Request-Id headers to upstream request");
+        if let Some(addr) = ctx.upstream_addr.as_deref() {
+            match self.secrets.auth().header_for(addr).await {
+                Ok(Some((name, value))) => {
+                    upstream_request.insert_header(name, value)?;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!(event = "upstream_auth_failed", request_id = %ctx.request_id, upstream = %addr, error = %e, "Failed to obtain upstream credentials");
+                    return Err(pingora_core::Error::explain(ErrorType::HTTPStatus(502), e.to_string()));
+                }
+            }
+        }
+        if let (Some(signer), Some(digest)) = (self.secrets.signer(), ctx.body_sha256.as_deref()) {
+            let now = self.clock.now().timestamp();
+            signer.apply(upstream_request, now, digest)?;
+        }
         Ok(())
     }
 
     async fn response_filter(
         &self,
-        _session: &mut Session,
+        session: &mut Session,
         upstream_response: &mut pingora_http::ResponseHeader,
         ctx: &mut Self::CTX,
     ) -> Result<()> {
         let duration = ctx.start.elapsed();
-        REQUEST_DURATION.observe(duration.as_secs_f64());
+        self.metrics.request_duration.observe(duration.as_secs_f64());
+        {
+            let config = self.config.load();
+            let route = latency::route_label(&config.latency.routes, session.req_header().uri.path());
+            self.latency.record(route, ctx.upstream_addr.as_deref().unwrap_or("none"), duration);
+        }
+        if let Some(addr) = ctx.upstream_addr.as_deref() {
+            let failed = upstream_response.status.is_server_error();
+            if failed {
+                self.outlier_detector.record_failure(addr);
+            } else {
+                self.outlier_detector.record_success(addr);
+            }
+            if let Some(permit) = ctx.adaptive_permit.as_mut() {
+                permit.record(!failed);
+            }
+            self.bandit.record(addr, duration, !failed);
+            ctx.outcome_recorded = true;
+        }
+        {
+            let config = self.config.load();
+            let status = upstream_response.status.as_u16();
+            let bodyless = status == 204 || status == 304 || session.req_header().method == "HEAD";
+            if let Some(rule) = content_type::rule_for(&config.content_types, session.req_header().uri.path()).filter(|_| !bodyless) {
+                let received = upstream_response.headers.get("content-type").and_then(|v| v.to_str().ok());
+                if !rule.accepts_response(received) {
+                    let upstream = ctx.upstream_addr.as_deref().unwrap_or("none");
+                    self.metrics.unexpected_content_type_total.with_label_values(&[rule.path_prefix.as_str(), upstream]).inc();
+                    warn!(event = "unexpected_content_type", request_id = %ctx.request_id, route = %rule.path_prefix, upstream, status, content_type = received.unwrap_or(""), expected = ?rule.response, "Upstream response content type does not match route");
+                }
+            }
+        }
+        if let Some(capture) = ctx.recording.as_mut() {
+            let headers = upstream_response.headers.iter().map(|(k, v)| (k.as_str().to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned())).collect();
+            capture.set_response(upstream_response.status.as_u16(), headers);
+        }
+        if ctx.fault_delayed {
+            upstream_response.insert_header(FAULT_HEADER, "delay")?;
+        }
+        if let Some(status) = &ctx.rate_limit {
+            for (name, value) in status.headers() {
+                upstream_response.insert_header(name, value)?;
+            }
+        }
+        if ctx.debug {
+            ctx.decision.apply_headers(upstream_response)?;
+        }
         info!(
             event = "response_headers",
             request_id = %ctx.request_id,
@@ -167,6 +794,77 @@ impl ProxyHttp for LB {
         Ok(())
     }
 
+    fn response_body_filter(
+        &self,
+        _session: &mut Session,
+        body: &mut Option<bytes::Bytes>,
+        end_of_stream: bool,
+        ctx: &mut Self::CTX,
+    ) -> Result<Option<std::time::Duration>> {
+        if let (Some(capture), Some(chunk)) = (ctx.recording.as_mut(), body.as_deref()) {
+            capture.feed_response(chunk, self.recording.max_body_bytes());
+        }
+        if end_of_stream {
+            if let Some(capture) = ctx.recording.take() {
+                self.recording.finish_capture(capture, &self.config.load().redaction);
+            }
+        }
+        Ok(None)
+    }
+
+    async fn fail_to_proxy(
+        &self,
+        session: &mut Session,
+        e: &pingora_core::Error,
+        ctx: &mut Self::CTX,
+    ) -> FailToProxy {
+        if ctx.bulkhead_rejected {
+            let retry_after = self.config.load().bulkhead.retry_after_secs;
+            if let Err(err) = self.respond_gateway_error(session, GatewayErrorCode::BulkheadFull, Some(retry_after), ctx.request_id, &[]).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send 503 response");
+            }
+            return FailToProxy { error_code: 503, can_reuse_downstream: false };
+        }
+        // 重试后仍选不到上游：503 + JSON，熔断器因此打开时 Retry-After 取其恢复剩余时间
+        if ctx.decision.limiter == "no_upstream" {
+            let retry_after = errors::retry_after_secs(self.breaker(ctx).retry_after());
+            if let Err(err) = self.respond_gateway_error(session, GatewayErrorCode::NoUpstream, Some(retry_after), ctx.request_id, &[]).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send 503 response");
+            }
+            return FailToProxy { error_code: 503, can_reuse_downstream: false };
+        }
+
+        // 与 pingora 默认行为一致，但上游超时返回 504
+        let code = match e.etype() {
+            ErrorType::HTTPStatus(code) => *code,
+            _ => match e.esource() {
+                ErrorSource::Upstream => match e.etype() {
+                    ErrorType::ConnectTimedout | ErrorType::ReadTimedout | ErrorType::WriteTimedout => 504,
+                    _ => 502,
+                },
+                ErrorSource::Downstream => match e.etype() {
+                    ErrorType::WriteError | ErrorType::ReadError | ErrorType::ConnectionClosed => 0,
+                    _ => 400,
+                },
+                ErrorSource::Internal | ErrorSource::Unset => 500,
+            },
+        };
+        let page = PAGE_STATUSES.contains(&code).then(|| {
+            let (error, message) = errors::upstream_error(code);
+            self.error_page(session, &PageVars { status: code, error, message, request_id: ctx.request_id, retry_after: None })
+        });
+        if let Some(page) = page.flatten() {
+            if let Err(err) = write_error_response(session, code, &page.content_type, page.body, None, ctx.request_id, &[]).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send error page");
+            }
+        } else if code > 0 {
+            if let Err(err) = session.respond_error(code).await {
+                error!(event = "respond_failed", request_id = %ctx.request_id, error = %err, "failed to send error response");
+            }
+        }
+        FailToProxy { error_code: code, can_reuse_downstream: false }
+    }
+
     async fn logging(
         &self,
         session: &mut Session,
@@ -176,15 +874,33 @@ impl ProxyHttp for LB {
         let duration = ctx.start.elapsed();
         let method = session.req_header().method.to_string();
         let uri = session.req_header().uri.to_string();
+        let uri = self.config.load().redaction.redact_uri(&uri).into_owned();
+        if let Some(recorder) = &self.key_activity {
+            recorder.record(session.req_header().headers.get(recorder.header()).and_then(|v| v.to_str().ok()));
+        }
 
         if let Some(err) = e {
+            // 连接失败/超时等没有响应头的错误同样计入上游失败
+            if !ctx.outcome_recorded {
+                if let Some(addr) = ctx.upstream_addr.as_deref() {
+                    self.outlier_detector.record_failure(addr);
+                    self.bandit.record(addr, duration, false);
+                }
+                if let Some(permit) = ctx.adaptive_permit.as_mut() {
+                    permit.record(false);
+                }
+                ctx.outcome_recorded = true;
+            }
             error!(
                 event = "request_error",
                 request_id = %ctx.request_id,
+                config_revision = ?ctx.config_revision,
                 method = %method,
                 uri = %uri,
                 duration_ms = %duration.as_millis(),
                 upstream = %ctx.upstream_addr.as_deref().unwrap_or(""),
+                country = ?ctx.geo.as_ref().and_then(|g| g.country.as_deref()),
+                asn = ?ctx.geo.as_ref().and_then(|g| g.asn),
                 error = %err,
                 "request failed with error"
             );
@@ -192,12 +908,119 @@ impl ProxyHttp for LB {
             info!(
                 event = "request_end",
                 request_id = %ctx.request_id,
+                config_revision = ?ctx.config_revision,
                 method = %method,
                 uri = %uri,
                 duration_ms = %duration.as_millis(),
                 upstream = %ctx.upstream_addr.as_deref().unwrap_or(""),
+                waf_tags = ?ctx.waf_tags,
+                country = ?ctx.geo.as_ref().and_then(|g| g.country.as_deref()),
+                asn = ?ctx.geo.as_ref().and_then(|g| g.asn),
                 "request completed"
             );
         }
     }
-}
\ No newline at end of file
+}
+
+/// 请求签名用的 body 摘要：带 Content-Length 的小 body 读入 pingora 重试缓冲区（随后原样转发给上游），
+/// chunked 或超过 max_body_bytes 的 body 记为 UNSIGNED-PAYLOAD
+async fn digest_request_body(session: &mut Session, signer: &RequestSigner) -> Result<String> {
+    let headers = &session.req_header().headers;
+    let content_length = if headers.contains_key("transfer-encoding") {
+        None
+    } else {
+        Some(headers.get("content-length").and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok()).unwrap_or(0))
+    };
+    if !signer.should_hash_body(content_length) {
+        return Ok(UNSIGNED_PAYLOAD.to_string());
+    }
+    if content_length == Some(0) {
+        return Ok(signing::body_sha256(b""));
+    }
+    Ok(signing::body_sha256(&read_request_body(session).await?))
+}
+
+/// 读取完整请求体（保留在 pingora 重试缓冲区中，随后原样转发给上游）
+async fn read_request_body(session: &mut Session) -> Result<Vec<u8>> {
+    session.enable_retry_buffering();
+    let mut body = Vec::new();
+    while let Some(chunk) = session.read_request_body().await? {
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// 录制/回放匹配用的方法、路径与查询参数、请求头
+fn request_parts(req: &RequestHeader) -> (String, String, Vec<(String, String)>) {
+    let uri = req.uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/").to_string();
+    let headers = req.headers.iter().map(|(k, v)| (k.as_str().to_string(), String::from_utf8_lossy(v.as_bytes()).into_owned())).collect();
+    (req.method.to_string(), uri, headers)
+}
+
+/// 回放命中：返回录制的状态码、响应头与 body
+async fn respond_playback(session: &mut Session, recorded: &RecordedResponse) -> Result<()> {
+    let body = recorded.body.bytes().unwrap_or_default();
+    let mut resp = ResponseHeader::build(recorded.status, Some(recorded.headers.len() + 2))?;
+    for (name, value) in &recorded.headers {
+        resp.append_header(name.clone(), value.as_str())?;
+    }
+    resp.insert_header(PLAYBACK_HEADER, "hit")?;
+    resp.insert_header("Content-Length", body.len().to_string())?;
+    if body.is_empty() {
+        return session.write_response_header(Box::new(resp), true).await;
+    }
+    session.write_response_header(Box::new(resp), false).await?;
+    session.write_response_body(Some(bytes::Bytes::from(body)), true).await
+}
+
+/// 回放未命中且不允许透传：404
+async fn respond_playback_miss(session: &mut Session) -> Result<()> {
+    let mut resp = ResponseHeader::build(404, Some(2))?;
+    resp.insert_header(PLAYBACK_HEADER, "miss")?;
+    resp.insert_header("Content-Length", "0")?;
+    session.write_response_header(Box::new(resp), true).await
+}
+
+/// 故障注入的错误响应：无 body，带 `X-Gateway-Fault: abort`
+async fn respond_fault(session: &mut Session, status: u16) -> Result<()> {
+    let mut resp = ResponseHeader::build(status, Some(2))?;
+    resp.insert_header(FAULT_HEADER, "abort")?;
+    resp.insert_header("Content-Length", "0")?;
+    session.write_response_header(Box::new(resp), true).await
+}
+
+/// 错误响应：带 Retry-After（有值时）与 X-Request-Id，`extra` 为附加响应头
+async fn write_error_response(session: &mut Session, status: u16, content_type: &str, body: String, retry_after: Option<u64>, request_id: Uuid, extra: &[(&'static str, String)]) -> Result<()> {
+    let mut resp = ResponseHeader::build(status, Some(extra.len() + 4))?;
+    for (name, value) in extra {
+        resp.insert_header(*name, value.as_str())?;
+    }
+    if let Some(secs) = retry_after {
+        resp.insert_header("Retry-After", secs.to_string())?;
+    }
+    resp.insert_header("X-Request-Id", request_id.to_string())?;
+    resp.insert_header("Content-Type", content_type)?;
+    resp.insert_header("Content-Length", body.len().to_string())?;
+    session.write_response_header(Box::new(resp), false).await?;
+    session.write_response_body(Some(bytes::Bytes::from(body)), true).await
+}
+
+/// 疑似机器人：403 并下发 challenge cookie，能保存并回传 cookie 的客户端重试即可通过
+async fn respond_bot_challenge(session: &mut Session, token: &str) -> Result<()> {
+    let mut resp = ResponseHeader::build(403, Some(3))?;
+    resp.insert_header("Set-Cookie", format!("{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=3600", CHALLENGE_COOKIE, token))?;
+    resp.insert_header("X-Bot-Challenge", "cookie")?;
+    resp.insert_header("Content-Length", "0")?;
+    session.write_response_header(Box::new(resp), true).await
+}
+
+/// dry-run 响应：以 JSON 返回决策信息，`status` 为正常处理时的预期状态码
+async fn respond_dry_run(session: &mut Session, status: u16, decision: &Decision) -> Result<()> {
+    let body = serde_json::json!({ "dry_run": true, "status": status, "decision": decision }).to_string();
+    let mut resp = ResponseHeader::build(200, Some(8))?;
+    decision.apply_headers(&mut resp)?;
+    resp.insert_header("Content-Type", "application/json")?;
+    resp.insert_header("Content-Length", body.len().to_string())?;
+    session.write_response_header(Box::new(resp), false).await?;
+    session.write_response_body(Some(bytes::Bytes::from(body)), true).await
+}
@@ -0,0 +1,59 @@
+//! 按租户的 spike arrest 间隔：定期从控制面 `rate_limit` 表读取 `spike_arrest_interval_ms`，替换限流器中的租户间隔。
+//! - 请求路径只读内存；数据库不可用或读取失败时保留上一次的结果；
+//! - 没有记录或记录未配置间隔的租户按全局 `spike_arrest_interval_ms` 处理。
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use pingora_core::server::ShutdownWatch;
+use pingora_core::services::background::BackgroundService;
+use service::db::ratelimit_service;
+
+use crate::config::RateLimitRecordsConfig;
+use crate::rate_limiter::RateLimiter;
+
+pub struct RateLimitRecords {
+    database_url: String,
+    interval: Duration,
+    limiter: RateLimiter,
+}
+
+impl RateLimitRecords {
+    /// 启用但既未配置 `database_url` 也没有 `DATABASE_URL` 时返回错误
+    pub fn new(cfg: &RateLimitRecordsConfig, limiter: RateLimiter) -> Result<Self, String> {
+        let database_url = cfg
+            .database_url
+            .clone()
+            .or_else(|| std::env::var("DATABASE_URL").ok())
+            .filter(|u| !u.trim().is_empty())
+            .ok_or_else(|| "rate_limit.records.database_url (or DATABASE_URL) is required when rate_limit.records is enabled".to_string())?;
+        Ok(Self { database_url, interval: Duration::from_secs(cfg.refresh_secs.max(1)), limiter })
+    }
+}
+
+#[async_trait]
+impl BackgroundService for RateLimitRecords {
+    async fn start(&self, mut shutdown: ShutdownWatch) {
+        let apply = |intervals| self.limiter.set_tenant_spike_intervals(intervals);
+        tokio::select! {
+            _ = ratelimit_service::sync_spike_arrest_intervals(&self.database_url, self.interval, apply) => {}
+            _ = shutdown.changed() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_database_url() {
+        let cfg = RateLimitRecordsConfig { enabled: true, database_url: Some("postgres://localhost/gw".into()), refresh_secs: 0 };
+        let records = RateLimitRecords::new(&cfg, RateLimiter::new(10, 10, true)).unwrap();
+        assert_eq!(records.interval, Duration::from_secs(1));
+        if std::env::var("DATABASE_URL").is_err() {
+            let missing = RateLimitRecordsConfig { database_url: Some(" ".into()), ..cfg };
+            assert!(RateLimitRecords::new(&missing, RateLimiter::new(10, 10, true)).is_err());
+        }
+    }
+}
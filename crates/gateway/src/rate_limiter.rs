@@ -1,115 +1,457 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use arc_swap::ArcSwap;
+use dashmap::DashMap;
+use prometheus::IntGauge;
 use tracing::{debug, warn};
+use uuid::Uuid;
 
+use crate::observability::Metrics;
+
+/// 令牌桶与 server 端管理接口限流共用，实现位于 common
+pub use common::rate_limit::TokenBucket;
+
+/// 超过该数量时清理已过期的 key
+const SPIKE_ARREST_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Spike arrest：同一 key 相邻两次放行至少间隔 `interval`（如每 10ms 最多 1 个），
+/// 把突发流量平滑为匀速，而不是像令牌桶那样一次性放行整个 burst。
+/// key 分片存放，不同 key 的请求互不阻塞。
 #[derive(Debug)]
-pub struct TokenBucket {
-    capacity: u64,
-    tokens: u64,
-    refill_rate: u64, // tokens per second
-    last_refill: Instant,
+pub struct SpikeArrest {
+    interval: Duration,
+    next_allowed: DashMap<String, Instant>,
 }
 
-impl TokenBucket {
-    pub fn new(capacity: u64, refill_rate: u64) -> Self {
-        Self {
-            capacity,
-            tokens: capacity,
-            refill_rate,
-            last_refill: Instant::now(),
+/// 一次放行占用的名额，后续检查拒绝时用 [`SpikeArrest::release`] 归还
+#[derive(Debug, Clone, Copy)]
+struct SpikeSlot {
+    reserved: Instant,
+    previous: Option<Instant>,
+}
+
+impl SpikeArrest {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, next_allowed: DashMap::new() }
+    }
+
+    pub fn try_acquire(&self, key: &str) -> bool {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: &str, now: Instant) -> bool {
+        self.check_at(key, self.interval, now).is_ok()
+    }
+
+    /// 放行返回占用的名额；拒绝时返回距下次放行的等待时间
+    fn check_at(&self, key: &str, interval: Duration, now: Instant) -> Result<SpikeSlot, Duration> {
+        let reserved = now + interval;
+        if let Some(mut next) = self.next_allowed.get_mut(key) {
+            if now < *next {
+                debug!(key, wait_ms = (*next - now).as_millis() as u64, "Spike arrest rejected request");
+                return Err(*next - now);
+            }
+            let previous = Some(*next);
+            *next = reserved;
+            return Ok(SpikeSlot { reserved, previous });
+        }
+        if self.next_allowed.len() >= SPIKE_ARREST_PRUNE_THRESHOLD {
+            self.next_allowed.retain(|_, next| *next > now);
+        }
+        // 并发的首个请求只放行一个
+        match self.next_allowed.entry(key.to_string()) {
+            dashmap::mapref::entry::Entry::Occupied(_) => Err(interval),
+            dashmap::mapref::entry::Entry::Vacant(e) => {
+                e.insert(reserved);
+                Ok(SpikeSlot { reserved, previous: None })
+            }
         }
     }
 
-    pub fn try_acquire(&mut self, tokens: u64) -> bool {
-        self.refill();
-        
-        if self.tokens >= tokens {
-            self.tokens -= tokens;
-            debug!("Token acquired, remaining: {}", self.tokens);
-            true
-        } else {
-            warn!("Rate limit exceeded, tokens: {}, requested: {}", self.tokens, tokens);
-            false
+    /// 只查询不占用：距下次放行的等待时间，可放行时为 None
+    fn wait_at(&self, key: &str, now: Instant) -> Option<Duration> {
+        self.next_allowed.get(key).filter(|next| now < **next).map(|next| *next - now)
+    }
+
+    /// 归还名额：恢复占用前的状态；其间已被其他请求占用时不动
+    fn release(&self, key: &str, slot: SpikeSlot) {
+        match slot.previous {
+            Some(previous) => {
+                if let Some(mut next) = self.next_allowed.get_mut(key) {
+                    if *next == slot.reserved {
+                        *next = previous;
+                    }
+                }
+            }
+            None => {
+                self.next_allowed.remove_if(key, |_, next| *next == slot.reserved);
+            }
         }
     }
+}
 
-    fn refill(&mut self) {
-        let now = Instant::now();
-        let elapsed = now.duration_since(self.last_refill);
-        let tokens_to_add = (elapsed.as_secs_f64() * self.refill_rate as f64) as u64;
-        
-        if tokens_to_add > 0 {
-            self.tokens = (self.tokens + tokens_to_add).min(self.capacity);
-            self.last_refill = now;
-            debug!("Refilled {} tokens, current: {}", tokens_to_add, self.tokens);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    TokenBucket,
+    SpikeArrest,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectReason::TokenBucket => "token_bucket",
+            RejectReason::SpikeArrest => "spike_arrest",
+        }
+    }
+}
+
+/// 一次限流检查的结果，对应 `X-RateLimit-*` 响应头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// 桶容量（burst）
+    pub limit: u64,
+    pub remaining: u64,
+    /// 桶补满的时间；spike arrest 拒绝时为距下次放行的时间
+    pub reset: Duration,
+    /// 被拒绝时距下次可放行的时间（放行时为 0）
+    pub retry_after: Duration,
+    pub rejected: Option<RejectReason>,
+}
+
+impl RateLimitStatus {
+    pub fn allowed(&self) -> bool {
+        self.rejected.is_none()
+    }
+
+    /// `X-RateLimit-Reset`：向上取整的秒数
+    pub fn reset_secs(&self) -> u64 {
+        ceil_secs(self.reset)
+    }
+
+    /// `Retry-After`：至少 1 秒
+    pub fn retry_after_secs(&self) -> u64 {
+        ceil_secs(self.retry_after).max(1)
+    }
+
+    pub fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("X-RateLimit-Limit", self.limit.to_string()),
+            ("X-RateLimit-Remaining", self.remaining.to_string()),
+            ("X-RateLimit-Reset", self.reset_secs().to_string()),
+        ]
+    }
+}
+
+fn ceil_secs(d: Duration) -> u64 {
+    d.as_nanos().div_ceil(1_000_000_000) as u64
+}
+
+/// 超过该数量时不再记录新 key，直到窗口结束
+const REJECTION_TRACKER_MAX_KEYS: usize = 10_000;
+
+/// 按 key 统计窗口内的拒绝次数，窗口结束时把前 N 个导出为指标，避免按 key 打点导致标签基数失控
+#[derive(Debug, Default)]
+pub struct RejectionTracker {
+    counts: DashMap<String, u64>,
+}
+
+impl RejectionTracker {
+    pub fn record(&self, key: &str) {
+        if let Some(mut count) = self.counts.get_mut(key) {
+            *count += 1;
+            return;
         }
+        if self.counts.len() < REJECTION_TRACKER_MAX_KEYS {
+            *self.counts.entry(key.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// 取出拒绝最多的 `n` 个 key 并清空统计
+    pub fn drain_top(&self, n: usize) -> Vec<(String, u64)> {
+        let mut all: Vec<(String, u64)> = self.counts.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        self.counts.clear();
+        all.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        all.truncate(n);
+        all
     }
 }
 
 #[derive(Clone)]
 pub struct RateLimiter {
-    bucket: Arc<Mutex<TokenBucket>>,
+    bucket: Arc<TokenBucket>,
+    spike_arrest: Arc<SpikeArrest>,
+    /// 租户 -> spike arrest 间隔，取自控制面 rate_limit 记录，覆盖全局间隔
+    tenant_spike_intervals: Arc<ArcSwap<HashMap<Uuid, Duration>>>,
+    rejections: Arc<RejectionTracker>,
+    /// 上次集群同步以来全局桶放行的令牌数
+    admitted: Arc<AtomicU64>,
+    /// 全局桶的剩余令牌 gauge（预先取出，避免热路径上按标签查找）
+    tokens: IntGauge,
     enabled: bool,
+    metrics: Arc<Metrics>,
 }
 
 impl RateLimiter {
     pub fn new(requests_per_second: u64, burst_size: u64, enabled: bool) -> Self {
+        let metrics = Metrics::detached();
+        let tokens = metrics.rate_limit_tokens.with_label_values(&["global"]);
+        tokens.set(burst_size as i64);
         Self {
-            bucket: Arc::new(Mutex::new(TokenBucket::new(burst_size, requests_per_second))),
+            bucket: Arc::new(TokenBucket::new(burst_size, requests_per_second)),
+            spike_arrest: Arc::new(SpikeArrest::new(Duration::ZERO)),
+            tenant_spike_intervals: Arc::new(ArcSwap::from_pointee(HashMap::new())),
+            rejections: Arc::new(RejectionTracker::default()),
+            admitted: Arc::new(AtomicU64::new(0)),
+            tokens,
             enabled,
+            metrics,
+        }
+    }
+
+    /// 剩余令牌、拒绝计数与拒绝最多的 key 记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.tokens = metrics.rate_limit_tokens.with_label_values(&["global"]);
+        self.tokens.set(self.bucket.available() as i64);
+        self.metrics = metrics;
+        self
+    }
+
+    /// 全局桶改为每 `interval` 补充一个令牌（毫秒级间隔，可表示非整数速率）；None 或 0 时保持每秒速率
+    pub fn with_refill_interval(mut self, interval: Option<Duration>) -> Self {
+        if let Some(interval) = interval.filter(|i| !i.is_zero()) {
+            self.bucket = Arc::new(TokenBucket::with_interval(self.bucket.capacity(), interval));
         }
+        self
+    }
+
+    /// 全局 spike arrest 间隔；为 0 时只对配置了间隔的租户生效
+    pub fn with_spike_arrest(mut self, interval: Duration) -> Self {
+        self.spike_arrest = Arc::new(SpikeArrest::new(interval));
+        self
+    }
+
+    /// 替换按租户的 spike arrest 间隔（见 [`crate::rate_limit_records`]）
+    pub fn set_tenant_spike_intervals(&self, intervals: HashMap<Uuid, Duration>) {
+        self.tenant_spike_intervals.store(Arc::new(intervals));
+    }
+
+    fn spike_interval(&self, tenant: Option<Uuid>) -> Duration {
+        tenant
+            .and_then(|t| self.tenant_spike_intervals.load().get(&t).copied())
+            .unwrap_or(self.spike_arrest.interval)
     }
 
-    pub async fn check_rate_limit(&self) -> bool {
+    pub fn check_rate_limit(&self) -> bool {
         if !self.enabled {
             return true;
         }
+        self.admit(self.bucket.try_acquire(1))
+    }
+
+    fn admit(&self, allowed: bool) -> bool {
+        if allowed {
+            self.admitted.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    /// 取出并清零上次调用以来全局桶放行的令牌数（集群模式广播给其他实例）
+    pub fn take_admitted(&self) -> u64 {
+        self.admitted.swap(0, Ordering::AcqRel)
+    }
+
+    /// 扣除其他实例放行的令牌，使整个集群共用一个全局额度
+    pub fn consume_shared(&self, tokens: u64) {
+        if !self.enabled || tokens == 0 {
+            return;
+        }
+        self.bucket.consume(tokens);
+        self.tokens.set(self.bucket.available() as i64);
+    }
+
+    /// 先按 key 做 spike arrest，再走全局令牌桶
+    pub fn check_rate_limit_for(&self, key: &str) -> bool {
+        self.check(key, key, None).is_none_or(|status| status.allowed())
+    }
+
+    /// 同 [`Self::check_rate_limit_for`]，并返回响应头所需的剩余额度；未启用时返回 None。
+    /// `tenant` 配置了 spike arrest 间隔时按该间隔，否则按全局间隔；令牌桶拒绝时归还 spike arrest 名额。
+    /// 被拒绝时按 `label`（不含凭据的 key 表示）计入拒绝统计
+    pub fn check(&self, key: &str, label: &str, tenant: Option<Uuid>) -> Option<RateLimitStatus> {
+        if !self.enabled {
+            return None;
+        }
+        let limit = self.bucket.capacity();
+        let interval = self.spike_interval(tenant);
+        let spike = (!interval.is_zero()).then(|| self.spike_arrest.check_at(key, interval, Instant::now()));
+        let status = match spike {
+            Some(Err(wait)) => RateLimitStatus { limit, remaining: 0, reset: wait, retry_after: wait, rejected: Some(RejectReason::SpikeArrest) },
+            Some(Ok(_)) | None => {
+                let allowed = self.admit(self.bucket.try_acquire(1));
+                let remaining = self.bucket.available();
+                self.tokens.set(remaining as i64);
+                if let (false, Some(Ok(slot))) = (allowed, spike) {
+                    self.spike_arrest.release(key, slot);
+                }
+                let (retry_after, rejected) = if allowed { (Duration::ZERO, None) } else { (self.bucket.wait_time(1), Some(RejectReason::TokenBucket)) };
+                RateLimitStatus { limit, remaining, reset: self.bucket.time_to_full(), retry_after, rejected }
+            }
+        };
+        if let Some(reason) = status.rejected {
+            self.metrics.rate_limit_rejections_total.with_label_values(&[reason.as_str()]).inc();
+            self.rejections.record(label);
+        }
+        Some(status)
+    }
+
+    /// 同 [`Self::check`] 的判定，但不扣令牌、不占用 spike arrest 名额、不计入拒绝统计（供 dry-run 预览）
+    pub fn preview(&self, key: &str, tenant: Option<Uuid>) -> Option<RateLimitStatus> {
+        if !self.enabled {
+            return None;
+        }
+        let limit = self.bucket.capacity();
+        let interval = self.spike_interval(tenant);
+        if let Some(wait) = (!interval.is_zero()).then(|| self.spike_arrest.wait_at(key, Instant::now())).flatten() {
+            return Some(RateLimitStatus { limit, remaining: 0, reset: wait, retry_after: wait, rejected: Some(RejectReason::SpikeArrest) });
+        }
+        let available = self.bucket.available();
+        let (remaining, retry_after, rejected) = if available >= 1 {
+            (available - 1, Duration::ZERO, None)
+        } else {
+            (0, self.bucket.wait_time(1), Some(RejectReason::TokenBucket))
+        };
+        Some(RateLimitStatus { limit, remaining, reset: self.bucket.time_to_full(), retry_after, rejected })
+    }
 
-        let mut bucket = self.bucket.lock().await;
-        bucket.try_acquire(1)
+    /// 每个 `interval` 把窗口内被拒绝最多的 `top_n` 个 key 导出到 `api_proxy_rate_limit_top_rejected_keys`
+    pub fn spawn_top_keys_exporter(&self, top_n: usize, interval: Duration) {
+        if !self.enabled || top_n == 0 {
+            return;
+        }
+        let rejections = Arc::clone(&self.rejections);
+        let metrics = Arc::clone(&self.metrics);
+        let spawned = std::thread::Builder::new().name("rate-limit-top-keys".into()).spawn(move || loop {
+            std::thread::sleep(interval);
+            let top = rejections.drain_top(top_n);
+            metrics.rate_limit_top_rejected_keys.reset();
+            for (key, count) in top {
+                metrics.rate_limit_top_rejected_keys.with_label_values(&[&key]).set(count as i64);
+            }
+        });
+        if let Err(e) = spawned {
+            warn!(error = %e, "failed to spawn rate limit top keys exporter");
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::time::{sleep, Duration};
 
     #[tokio::test]
-    async fn test_token_bucket_basic() {
-        let mut bucket = TokenBucket::new(10, 5);
-        
-        // Should be able to acquire tokens initially
-        assert!(bucket.try_acquire(5));
-        assert!(bucket.try_acquire(5));
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(10, 5, true);
         
-        // Should fail when bucket is empty
-        assert!(!bucket.try_acquire(1));
+        // Should allow initial requests
+        assert!(limiter.check_rate_limit());
+        assert!(limiter.check_rate_limit());
     }
 
-    #[tokio::test]
-    async fn test_token_bucket_refill() {
-        let mut bucket = TokenBucket::new(10, 10); // 10 tokens per second
-        
-        // Drain the bucket
-        assert!(bucket.try_acquire(10));
-        assert!(!bucket.try_acquire(1));
-        
-        // Wait for refill
-        sleep(Duration::from_millis(1100)).await;
-        
-        // Should have refilled
-        assert!(bucket.try_acquire(10));
+    #[test]
+    fn test_spike_arrest_spaces_requests_per_key() {
+        let spike = SpikeArrest::new(Duration::from_millis(10));
+        let t0 = Instant::now();
+        assert!(spike.try_acquire_at("a", t0));
+        assert!(!spike.try_acquire_at("a", t0 + Duration::from_millis(5)));
+        // 其它 key 互不影响
+        assert!(spike.try_acquire_at("b", t0 + Duration::from_millis(5)));
+        assert!(spike.try_acquire_at("a", t0 + Duration::from_millis(10)));
+        assert!(!spike.try_acquire_at("a", t0 + Duration::from_millis(19)));
     }
 
     #[tokio::test]
-    async fn test_rate_limiter() {
-        let limiter = RateLimiter::new(10, 5, true);
-        
-        // Should allow initial requests
-        assert!(limiter.check_rate_limit().await);
-        assert!(limiter.check_rate_limit().await);
+    async fn test_rate_limiter_with_spike_arrest() {
+        let limiter = RateLimiter::new(1000, 100, true).with_spike_arrest(Duration::from_secs(60));
+        assert!(limiter.check_rate_limit_for("k1"));
+        assert!(!limiter.check_rate_limit_for("k1"));
+        assert!(limiter.check_rate_limit_for("k2"));
+
+        let plain = RateLimiter::new(1000, 100, true).with_spike_arrest(Duration::ZERO);
+        assert!(plain.check_rate_limit_for("k1"));
+        assert!(plain.check_rate_limit_for("k1"));
+    }
+
+    #[test]
+    fn test_tenant_spike_interval_overrides_global() {
+        let limiter = RateLimiter::new(1000, 100, true);
+        let tenant = Uuid::new_v4();
+        limiter.set_tenant_spike_intervals(HashMap::from([(tenant, Duration::from_secs(60))]));
+        assert!(limiter.check_rate_limit_for("k1") && limiter.check_rate_limit_for("k1"));
+        assert!(limiter.check("k2", "k2", Some(tenant)).unwrap().allowed());
+        assert_eq!(limiter.check("k2", "k2", Some(tenant)).unwrap().rejected, Some(RejectReason::SpikeArrest));
+        assert!(limiter.check("k3", "k3", Some(Uuid::new_v4())).unwrap().allowed());
+        assert!(limiter.check("k3", "k3", Some(Uuid::new_v4())).unwrap().allowed());
+    }
+
+    #[test]
+    fn test_rate_limit_status_and_rejection_reasons() {
+        let limiter = RateLimiter::new(1, 2, true).with_spike_arrest(Duration::from_secs(60));
+        let first = limiter.check("k1", "key:1", None).unwrap();
+        assert!(first.allowed());
+        assert_eq!((first.limit, first.remaining, first.reset_secs()), (2, 1, 1));
+        let spiked = limiter.check("k1", "key:1", None).unwrap();
+        assert_eq!(spiked.rejected, Some(RejectReason::SpikeArrest));
+        assert_eq!((spiked.remaining, spiked.reset_secs(), spiked.retry_after_secs()), (0, 60, 60));
+        assert!(limiter.check("k2", "key:2", None).unwrap().allowed());
+        let drained = limiter.check("k3", "key:3", None).unwrap();
+        assert_eq!(drained.rejected, Some(RejectReason::TokenBucket));
+        assert_eq!(drained.headers()[1], ("X-RateLimit-Remaining", "0".to_string()));
+        assert_eq!(drained.retry_after_secs(), 1);
+        // 令牌桶拒绝的请求不占用 spike arrest 名额
+        assert_eq!(limiter.check("k3", "key:3", None).unwrap().rejected, Some(RejectReason::TokenBucket));
+        assert!(RateLimiter::new(1, 1, false).check("k", "k", None).is_none());
+    }
+
+    #[test]
+    fn test_preview_does_not_consume() {
+        let limiter = RateLimiter::new(1, 1, true).with_spike_arrest(Duration::from_secs(60));
+        for _ in 0..3 {
+            let preview = limiter.preview("k", None).unwrap();
+            assert!(preview.allowed());
+            assert_eq!(preview.remaining, 0);
+        }
+        assert!(limiter.check("k", "k", None).unwrap().allowed());
+        assert_eq!(limiter.preview("k", None).unwrap().rejected, Some(RejectReason::SpikeArrest));
+        assert_eq!(limiter.preview("other", None).unwrap().rejected, Some(RejectReason::TokenBucket));
+        assert!(RateLimiter::new(1, 1, false).preview("k", None).is_none());
+    }
+
+    #[test]
+    fn test_refill_interval_overrides_rate() {
+        // 2.5 个/秒：补满两个令牌约需 800ms
+        let limiter = RateLimiter::new(1000, 2, true).with_refill_interval(Some(Duration::from_millis(400)));
+        assert!(limiter.check_rate_limit());
+        let status = limiter.check("k", "k", None).unwrap();
+        assert!(status.reset > Duration::from_millis(790) && status.reset <= Duration::from_millis(800), "{:?}", status.reset);
+        assert!(!limiter.check_rate_limit());
+
+        let unchanged = RateLimiter::new(1000, 2, true).with_refill_interval(Some(Duration::ZERO));
+        assert!(unchanged.check_rate_limit() && unchanged.check_rate_limit());
+        assert!(unchanged.bucket.wait_time(1) <= Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_rejection_tracker_keeps_top_keys() {
+        let tracker = RejectionTracker::default();
+        for (key, n) in [("a", 3), ("b", 5), ("c", 1)] {
+            for _ in 0..n {
+                tracker.record(key);
+            }
+        }
+        assert_eq!(tracker.drain_top(2), vec![("b".to_string(), 5), ("a".to_string(), 3)]);
+        assert!(tracker.drain_top(2).is_empty());
     }
 
     #[tokio::test]
@@ -118,7 +460,30 @@ mod tests {
         
         // Should always allow when disabled
         for _ in 0..100 {
-            assert!(limiter.check_rate_limit().await);
+            assert!(limiter.check_rate_limit());
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shared_usage_drains_global_bucket() {
+        let limiter = RateLimiter::new(1, 3, true);
+        assert!(limiter.check_rate_limit());
+        assert_eq!(limiter.take_admitted(), 1);
+        assert_eq!(limiter.take_admitted(), 0);
+        limiter.consume_shared(2);
+        assert!(!limiter.check_rate_limit());
+    }
+
+    #[test]
+    fn test_metrics_go_to_injected_instance() {
+        let metrics = Metrics::detached();
+        let limiter = RateLimiter::new(1, 2, true).with_metrics(Arc::clone(&metrics));
+        let tokens = || metrics.rate_limit_tokens.with_label_values(&["global"]).get();
+        assert_eq!(tokens(), 2);
+        assert!(limiter.check("k", "k", None).unwrap().rejected.is_none());
+        assert!(limiter.check("k", "k", None).unwrap().rejected.is_none());
+        assert_eq!(limiter.check("k", "k", None).unwrap().rejected, Some(RejectReason::TokenBucket));
+        assert_eq!(tokens(), 0);
+        assert_eq!(metrics.rate_limit_rejections_total.with_label_values(&[RejectReason::TokenBucket.as_str()]).get(), 1);
+    }
+}
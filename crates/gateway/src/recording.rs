@@ -0,0 +1,503 @@
+//! 流量录制与回放：按路由把请求/响应对写入 fixture 文件，或用 fixture 代替上游（客户端离线集成测试）。
+//! - 规则与 fixture 在启动时加载：record 模式清空并重建 fixture，playback 模式读取 fixture，文件缺失或格式错误时启动失败；
+//! - 请求按方法、路径、排序后的查询参数、`match_headers` 与（可选）请求体摘要匹配；开启 `redact` 时录制与匹配两侧都先脱敏；
+//! - 同一请求录到多次时按录制顺序依次返回，之后一直返回最后一个，回放结果确定；
+//! - 每录一条即整体重写 fixture（临时文件 + rename），进程中途退出也不会留下半个 JSON。
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::redaction::RedactionConfig;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::{RecordingConfig, RecordingMode, RecordingRule};
+use crate::signing;
+
+/// 回放响应上的标记头：`hit` 或 `miss`
+pub const PLAYBACK_HEADER: &str = "X-Gateway-Playback";
+pub const FIXTURE_VERSION: u32 = 1;
+
+/// 录制时不保存的响应头，回放时由网关按实际 body 重新生成
+const SKIPPED_RESPONSE_HEADERS: &[&str] = &["connection", "keep-alive", "transfer-encoding", "content-length"];
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io { fixture: String, reason: String },
+    Invalid { fixture: String, reason: String },
+}
+
+impl std::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io { fixture, reason } => write!(f, "recording fixture {}: {}", fixture, reason),
+            RecordingError::Invalid { fixture, reason } => write!(f, "invalid recording fixture {}: {}", fixture, reason),
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+/// UTF-8 body 存为 `body`（便于手工编辑），其他存为 `body_base64`；空 body 两者都省略
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_base64: Option<String>,
+}
+
+impl RecordedBody {
+    pub fn encode(bytes: &[u8], redaction: Option<&RedactionConfig>) -> Self {
+        if bytes.is_empty() {
+            return Self::default();
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self { body: Some(redaction.map_or_else(|| text.to_string(), |r| r.redact_body(text))), body_base64: None },
+            Err(_) => Self { body: None, body_base64: Some(STANDARD.encode(bytes)) },
+        }
+    }
+
+    pub fn bytes(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        match (&self.body, &self.body_base64) {
+            (Some(text), _) => Ok(text.as_bytes().to_vec()),
+            (None, Some(b64)) => STANDARD.decode(b64),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// 路径与查询参数
+    pub uri: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    pub body: RecordedBody,
+}
+
+impl RecordedRequest {
+    /// 头名统一小写；`redaction` 为 Some 时对 URI、敏感头与 body 脱敏
+    pub fn new(method: &str, uri: &str, headers: &[(String, String)], body: &[u8], redaction: Option<&RedactionConfig>) -> Self {
+        let uri = redaction.map_or(Cow::Borrowed(uri), |r| r.redact_uri(uri)).into_owned();
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.to_ascii_lowercase();
+                let value = redaction.map_or(value.as_str(), |r| r.header_value(&name, value)).to_string();
+                (name, value)
+            })
+            .collect();
+        Self { method: method.to_ascii_uppercase(), uri, headers, body: RecordedBody::encode(body, redaction) }
+    }
+
+    /// 回放匹配键：方法、路径、排序后的查询参数、`match_headers` 的值，以及 `match_body` 时的 body 摘要
+    pub fn match_key(&self, rule: &RecordingRule) -> String {
+        let (path, query) = self.uri.split_once('?').unwrap_or((self.uri.as_str(), ""));
+        let mut params: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+        params.sort_unstable();
+        let headers: Vec<String> = rule
+            .match_headers
+            .iter()
+            .map(|name| {
+                let values: Vec<&str> = self.headers.iter().filter(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str()).collect();
+                format!("{}={}", name.to_ascii_lowercase(), values.join(","))
+            })
+            .collect();
+        let body = if rule.match_body { signing::body_sha256(&self.body.bytes().unwrap_or_default()) } else { String::new() };
+        format!("{} {}?{}\n{}\n{}", self.method, path, params.join("&"), headers.join("\n"), body)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    pub body: RecordedBody,
+}
+
+impl RecordedResponse {
+    pub fn new(status: u16, headers: &[(String, String)], body: &[u8], redaction: Option<&RedactionConfig>) -> Self {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .filter(|(name, _)| !SKIPPED_RESPONSE_HEADERS.contains(&name.as_str()))
+            .map(|(name, value)| {
+                let value = redaction.map_or(value.as_str(), |r| r.header_value(&name, value)).to_string();
+                (name, value)
+            })
+            .collect();
+        Self { status, headers, body: RecordedBody::encode(body, redaction) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub version: u32,
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self { version: FIXTURE_VERSION, interactions: Vec::new() }
+    }
+}
+
+/// 一个请求的录制状态，随 RequestCtx 释放
+#[derive(Debug)]
+pub struct Capture {
+    /// `Recording::routes` 下标
+    route: usize,
+    method: String,
+    uri: String,
+    headers: Vec<(String, String)>,
+    request_body: Vec<u8>,
+    status: Option<u16>,
+    response_headers: Vec<(String, String)>,
+    response_body: Vec<u8>,
+    oversized: bool,
+}
+
+impl Capture {
+    pub fn set_response(&mut self, status: u16, headers: Vec<(String, String)>) {
+        self.status = Some(status);
+        self.response_headers = headers;
+    }
+
+    pub fn feed_response(&mut self, chunk: &[u8], max_body_bytes: usize) {
+        if self.oversized || self.response_body.len() + chunk.len() > max_body_bytes {
+            self.oversized = true;
+            self.response_body = Vec::new();
+            return;
+        }
+        self.response_body.extend_from_slice(chunk);
+    }
+}
+
+/// 回放：每个匹配键对应按录制顺序排列的响应与游标
+pub struct Player {
+    responses: HashMap<String, (Vec<RecordedResponse>, AtomicUsize)>,
+}
+
+impl Player {
+    pub fn new(rule: &RecordingRule, fixture: Fixture) -> Self {
+        let mut responses: HashMap<String, (Vec<RecordedResponse>, AtomicUsize)> = HashMap::new();
+        for interaction in fixture.interactions {
+            let key = interaction.request.match_key(rule);
+            responses.entry(key).or_insert_with(|| (Vec::new(), AtomicUsize::new(0))).0.push(interaction.response);
+        }
+        Self { responses }
+    }
+
+    pub fn lookup(&self, request: &RecordedRequest, rule: &RecordingRule) -> Option<&RecordedResponse> {
+        let (list, cursor) = self.responses.get(&request.match_key(rule))?;
+        let i = cursor.fetch_add(1, Ordering::Relaxed).min(list.len() - 1);
+        list.get(i)
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.values().map(|(list, _)| list.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+/// 录制：内存中保存全部交互，每次追加后整体重写 fixture
+pub struct Recorder {
+    path: PathBuf,
+    fixture: Mutex<Fixture>,
+    /// 串行化写文件，保证最后落盘的是最新内容
+    write_lock: Mutex<()>,
+}
+
+impl Recorder {
+    /// 创建（或清空）fixture 文件
+    pub fn create(path: impl Into<PathBuf>) -> Result<Self, RecordingError> {
+        let recorder = Self { path: path.into(), fixture: Mutex::new(Fixture::default()), write_lock: Mutex::new(()) };
+        if let Some(dir) = recorder.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| recorder.io_error(e))?;
+        }
+        recorder.flush().map_err(|e| recorder.io_error(e))?;
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn io_error(&self, e: std::io::Error) -> RecordingError {
+        RecordingError::Io { fixture: self.path.display().to_string(), reason: e.to_string() }
+    }
+
+    pub fn interactions(&self) -> usize {
+        self.fixture.lock().map(|f| f.interactions.len()).unwrap_or_default()
+    }
+
+    /// 追加一条交互；在 tokio 运行时内异步写文件，否则同步写
+    pub fn record(self: &Arc<Self>, interaction: Interaction) {
+        if let Ok(mut fixture) = self.fixture.lock() {
+            fixture.interactions.push(interaction);
+        }
+        let recorder = Arc::clone(self);
+        let write = move || {
+            if let Err(e) = recorder.flush() {
+                error!(event = "recording_write_failed", fixture = %recorder.path.display(), error = %e, "Failed to write recording fixture");
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(write)),
+            Err(_) => write(),
+        }
+    }
+
+    pub fn flush(&self) -> std::io::Result<()> {
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let json = {
+            let fixture = self.fixture.lock().unwrap_or_else(|e| e.into_inner());
+            serde_json::to_vec_pretty(&*fixture)?
+        };
+        let tmp = PathBuf::from(format!("{}.tmp", self.path.display()));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+}
+
+pub enum RouteState {
+    Record(Arc<Recorder>),
+    Playback(Player),
+}
+
+pub struct RecordingRoute {
+    pub rule: RecordingRule,
+    pub state: RouteState,
+}
+
+impl RecordingRoute {
+    fn redaction<'a>(&self, redaction: &'a RedactionConfig) -> Option<&'a RedactionConfig> {
+        self.rule.redact.then_some(redaction)
+    }
+}
+
+/// 启动时按 `recording` 配置构建；之后修改规则需重启
+pub struct Recording {
+    routes: Vec<RecordingRoute>,
+    max_body_bytes: usize,
+}
+
+impl Recording {
+    pub fn disabled() -> Self {
+        Self { routes: Vec::new(), max_body_bytes: 0 }
+    }
+
+    pub fn from_config(cfg: &RecordingConfig) -> Result<Self, RecordingError> {
+        if !cfg.enabled {
+            return Ok(Self::disabled());
+        }
+        let mut fixtures = HashSet::new();
+        let mut routes = Vec::with_capacity(cfg.rules.len());
+        for rule in &cfg.rules {
+            let invalid = |reason: &str| RecordingError::Invalid { fixture: rule.fixture.clone(), reason: reason.to_string() };
+            if !fixtures.insert(rule.fixture.as_str()) {
+                return Err(invalid("fixture is used by more than one rule"));
+            }
+            let state = match rule.mode {
+                RecordingMode::Record => {
+                    let recorder = Recorder::create(&rule.fixture)?;
+                    info!(event = "recording_started", route = %rule.path_prefix, fixture = %rule.fixture, "recording upstream traffic");
+                    RouteState::Record(Arc::new(recorder))
+                }
+                RecordingMode::Playback => {
+                    let text = std::fs::read_to_string(&rule.fixture).map_err(|e| RecordingError::Io { fixture: rule.fixture.clone(), reason: e.to_string() })?;
+                    let fixture: Fixture = serde_json::from_str(&text).map_err(|e| invalid(&e.to_string()))?;
+                    if fixture.version != FIXTURE_VERSION {
+                        return Err(invalid(&format!("unsupported version {}", fixture.version)));
+                    }
+                    if let Some(bad) = fixture.interactions.iter().find(|i| i.request.body.bytes().is_err() || i.response.body.bytes().is_err()) {
+                        return Err(invalid(&format!("body_base64 of {} {} is not valid base64", bad.request.method, bad.request.uri)));
+                    }
+                    let player = Player::new(rule, fixture);
+                    info!(event = "playback_loaded", route = %rule.path_prefix, fixture = %rule.fixture, responses = player.len(), "serving route from recorded fixture");
+                    RouteState::Playback(player)
+                }
+            };
+            routes.push(RecordingRoute { rule: rule.clone(), state });
+        }
+        Ok(Self { routes, max_body_bytes: cfg.max_body_bytes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// 按最长前缀选择路由，返回下标与路由
+    pub fn route_for(&self, path: &str) -> Option<(usize, &RecordingRoute)> {
+        self.routes.iter().enumerate().filter(|(_, r)| path.starts_with(r.rule.path_prefix.as_str())).max_by_key(|(_, r)| r.rule.path_prefix.len())
+    }
+
+    /// 回放查找；请求按路由的 `redact` 设置先脱敏再匹配
+    pub fn playback<'a>(&self, route: &'a RecordingRoute, method: &str, uri: &str, headers: &[(String, String)], body: &[u8], redaction: &RedactionConfig) -> Option<&'a RecordedResponse> {
+        let RouteState::Playback(player) = &route.state else { return None };
+        let request = RecordedRequest::new(method, uri, headers, body, route.redaction(redaction));
+        player.lookup(&request, &route.rule)
+    }
+
+    /// record 模式的路由开始录制本请求；body 超过 `max_body_bytes` 时只记警告、不录
+    pub fn start_capture(&self, route: usize, method: &str, uri: &str, headers: Vec<(String, String)>, body: Vec<u8>) -> Option<Capture> {
+        if !matches!(self.routes.get(route)?.state, RouteState::Record(_)) {
+            return None;
+        }
+        let oversized = body.len() > self.max_body_bytes;
+        let request_body = if oversized { Vec::new() } else { body };
+        Some(Capture {
+            route,
+            method: method.to_string(),
+            uri: uri.to_string(),
+            headers,
+            request_body,
+            status: None,
+            response_headers: Vec::new(),
+            response_body: Vec::new(),
+            oversized,
+        })
+    }
+
+    /// 响应结束时写入 fixture；未收到上游响应或 body 超限的请求不录
+    pub fn finish_capture(&self, capture: Capture, redaction: &RedactionConfig) {
+        let Some(route) = self.routes.get(capture.route) else { return };
+        let RouteState::Record(recorder) = &route.state else { return };
+        let Some(status) = capture.status else { return };
+        if capture.oversized {
+            warn!(event = "recording_skipped", route = %route.rule.path_prefix, method = %capture.method, limit = self.max_body_bytes, "Body exceeds recording max_body_bytes, interaction not recorded");
+            return;
+        }
+        let redaction = route.redaction(redaction);
+        recorder.record(Interaction {
+            request: RecordedRequest::new(&capture.method, &capture.uri, &capture.headers, &capture.request_body, redaction),
+            response: RecordedResponse::new(status, &capture.response_headers, &capture.response_body, redaction),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(fixture: &Path, mode: RecordingMode) -> RecordingRule {
+        RecordingRule {
+            path_prefix: "/api".into(),
+            mode,
+            fixture: fixture.display().to_string(),
+            match_headers: vec!["x-tenant".into()],
+            match_body: true,
+            passthrough_on_miss: false,
+            redact: true,
+        }
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn temp_fixture() -> PathBuf {
+        std::env::temp_dir().join(format!("gateway-recording-{}", uuid::Uuid::new_v4())).join("fixture.json")
+    }
+
+    #[test]
+    fn match_key_ignores_query_order_and_unlisted_headers() {
+        let r = rule(Path::new("unused.json"), RecordingMode::Playback);
+        let a = RecordedRequest::new("get", "/api/items?b=2&a=1", &headers(&[("X-Tenant", "t1"), ("user-agent", "curl")]), b"", None);
+        let b = RecordedRequest::new("GET", "/api/items?a=1&b=2", &headers(&[("x-tenant", "t1")]), b"", None);
+        assert_eq!(a.match_key(&r), b.match_key(&r));
+        let other_tenant = RecordedRequest::new("GET", "/api/items?a=1&b=2", &headers(&[("x-tenant", "t2")]), b"", None);
+        assert_ne!(a.match_key(&r), other_tenant.match_key(&r));
+        let with_body = RecordedRequest::new("GET", "/api/items?a=1&b=2", &headers(&[("x-tenant", "t1")]), b"{}", None);
+        assert_ne!(a.match_key(&r), with_body.match_key(&r));
+        assert_eq!(a.match_key(&RecordingRule { match_body: false, ..r.clone() }), with_body.match_key(&RecordingRule { match_body: false, ..r }));
+    }
+
+    #[test]
+    fn bodies_round_trip_as_text_or_base64() {
+        let text = RecordedBody::encode(b"hello", None);
+        assert_eq!((text.body.as_deref(), text.body_base64.as_deref()), (Some("hello"), None));
+        let binary = RecordedBody::encode(&[0xff, 0x00, 0x10], None);
+        assert!(binary.body.is_none());
+        assert_eq!(binary.bytes().unwrap(), vec![0xff, 0x00, 0x10]);
+        assert_eq!(RecordedBody::encode(b"", None), RecordedBody::default());
+    }
+
+    #[test]
+    fn recorded_fixture_plays_back_in_order() {
+        let path = temp_fixture();
+        let redaction = RedactionConfig::default();
+        let cfg = RecordingConfig { enabled: true, rules: vec![rule(&path, RecordingMode::Record)], max_body_bytes: 1024 };
+        let recording = Recording::from_config(&cfg).unwrap();
+        let (idx, _) = recording.route_for("/api/orders").unwrap();
+        for (status, body) in [(201, "first"), (200, "second")] {
+            let req_headers = headers(&[("authorization", "Bearer secret"), ("x-tenant", "t1")]);
+            let mut capture = recording.start_capture(idx, "POST", "/api/orders?token=abc", req_headers, br#"{"item":1}"#.to_vec()).unwrap();
+            capture.set_response(status, headers(&[("content-type", "text/plain"), ("content-length", "5")]));
+            capture.feed_response(body.as_bytes(), 1024);
+            recording.finish_capture(capture, &redaction);
+        }
+        // body 超限的交互不录
+        let mut big = recording.start_capture(idx, "GET", "/api/big", Vec::new(), Vec::new()).unwrap();
+        big.set_response(200, Vec::new());
+        big.feed_response(&[b'x'; 2048], 1024);
+        recording.finish_capture(big, &redaction);
+
+        let saved: Fixture = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.interactions.len(), 2);
+        let first = &saved.interactions[0];
+        assert!(!first.request.uri.contains("abc"));
+        assert!(first.request.headers.iter().any(|(k, v)| k == "authorization" && v != "Bearer secret"));
+        assert!(first.response.headers.iter().all(|(k, _)| k != "content-length"));
+
+        let playback = Recording::from_config(&RecordingConfig { rules: vec![rule(&path, RecordingMode::Playback)], ..cfg }).unwrap();
+        let (_, route) = playback.route_for("/api/orders").unwrap();
+        let live = headers(&[("authorization", "Bearer other"), ("x-tenant", "t1")]);
+        let play = || playback.playback(route, "POST", "/api/orders?token=xyz", &live, br#"{"item":1}"#, &redaction).map(|r| (r.status, r.body.body.clone()));
+        assert_eq!(play(), Some((201, Some("first".into()))));
+        assert_eq!(play(), Some((200, Some("second".into()))));
+        assert_eq!(play(), Some((200, Some("second".into()))));
+        assert!(playback.playback(route, "POST", "/api/orders", &live, b"{}", &redaction).is_none());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn invalid_fixtures_fail_startup() {
+        let missing = temp_fixture();
+        let cfg = |rules| RecordingConfig { enabled: true, rules, max_body_bytes: 1024 };
+        assert!(matches!(Recording::from_config(&cfg(vec![rule(&missing, RecordingMode::Playback)])), Err(RecordingError::Io { .. })));
+
+        std::fs::create_dir_all(missing.parent().unwrap()).unwrap();
+        std::fs::write(&missing, r#"{"version":2,"interactions":[]}"#).unwrap();
+        assert!(matches!(Recording::from_config(&cfg(vec![rule(&missing, RecordingMode::Playback)])), Err(RecordingError::Invalid { .. })));
+
+        let dup = vec![rule(&missing, RecordingMode::Record), RecordingRule { path_prefix: "/other".into(), ..rule(&missing, RecordingMode::Record) }];
+        assert!(matches!(Recording::from_config(&cfg(dup)), Err(RecordingError::Invalid { .. })));
+        assert!(Recording::from_config(&RecordingConfig::default()).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(missing.parent().unwrap());
+    }
+}
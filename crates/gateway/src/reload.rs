@@ -0,0 +1,52 @@
+//! 配置热加载：`POST /config/reload`（admin 端口）重新读取配置文件，替换按请求读取的共享配置并重建上游凭据与请求签名。
+//! 监听端口与各组件的启动参数不随之改变，需要时走平滑升级（见 upgrade）；新配置无效时保持原状态不变。
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::Router;
+use tracing::{info, warn};
+
+use crate::config::ProxyConfig;
+use crate::listeners::{self, Listeners};
+use crate::secrets::UpstreamSecrets;
+
+pub struct ConfigReloader {
+    path: String,
+    config: Arc<ArcSwap<ProxyConfig>>,
+    secrets: Arc<UpstreamSecrets>,
+    listeners: Arc<Listeners>,
+}
+
+impl ConfigReloader {
+    pub fn new(path: impl Into<String>, config: Arc<ArcSwap<ProxyConfig>>, secrets: Arc<UpstreamSecrets>, listeners: Arc<Listeners>) -> Self {
+        Self { path: path.into(), config, secrets, listeners }
+    }
+
+    /// 读取并校验配置文件，先重建凭据再替换共享配置
+    pub fn reload(&self) -> anyhow::Result<()> {
+        let mut config = ProxyConfig::load_resolved(&self.path).map_err(|e| anyhow::anyhow!("load {}: {}", self.path, e))?.config;
+        // 监听端口在启动时固定（含 GatewayBuilder 追加的），scope 规则须指向其中之一
+        config.listeners = self.config.load().listeners.clone();
+        listeners::validate_scopes(&config.listener_scopes, &self.listeners).map_err(anyhow::Error::msg)?;
+        self.secrets.reload(&config)?;
+        self.config.store(Arc::new(config));
+        Ok(())
+    }
+}
+
+pub fn admin_routes(reloader: Arc<ConfigReloader>) -> Router {
+    Router::new().route(
+        "/config/reload",
+        post(move || async move {
+            if let Err(e) = reloader.reload() {
+                warn!(event = "config_reload_failed", path = %reloader.path, error = %format!("{:#}", e), "config reload rejected; keeping the current config");
+                return Err((StatusCode::BAD_REQUEST, format!("{:#}", e)));
+            }
+            info!(event = "config_reloaded", path = %reloader.path, "config reloaded");
+            Ok::<_, (StatusCode, String)>(StatusCode::NO_CONTENT)
+        }),
+    )
+}
@@ -49,7 +49,7 @@ impl RetryPolicy {
         sleep(backoff_duration).await;
     }
 
-    pub fn should_retry(&self, attempt: u32, error: &dyn std::error::Error) -> bool {
+    pub fn should_retry(&self, attempt: u32, error: &(dyn std::error::Error + 'static)) -> bool {
         if !self.enabled {
             return false;
         }
@@ -59,6 +59,11 @@ impl RetryPolicy {
             return false;
         }
 
+        // Explicitly classified errors take precedence over string matching
+        if let Some(e) = error.downcast_ref::<RetryableError>() {
+            return e.is_retryable;
+        }
+
         // Check if error is retryable
         let error_str = error.to_string().to_lowercase();
         let is_retryable = error_str.contains("timeout") 
@@ -119,7 +124,7 @@ pub async fn retry_with_policy<F, Fut, T, E>(
 where
     F: FnMut() -> Fut,
     Fut: std::future::Future<Output = Result<T, E>>,
-    E: std::error::Error,
+    E: std::error::Error + 'static,
 {
     let mut last_error = None;
     
@@ -259,4 +264,23 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(counter.load(Ordering::SeqCst), 1); // Only one attempt when disabled
     }
+
+    #[test]
+    fn test_explicit_classification_overrides_message() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(10), true);
+
+        // Peer selection reports "no upstream available" while every host is ejected;
+        // a retry may land on a host whose ejection has expired.
+        let no_upstream = RetryableError::retryable("no upstream available".to_string());
+        assert!(policy.should_retry(1, &no_upstream));
+
+        // Without the marker the message alone decides
+        let plain = std::io::Error::other("no upstream available");
+        assert!(!policy.should_retry(1, &plain));
+
+        // An explicit non-retryable error wins over a matching keyword
+        let fatal = RetryableError::non_retryable("connection refused by policy".to_string());
+        assert!(!policy.should_retry(1, &fatal));
+        assert!(policy.should_retry(1, &std::io::Error::other("connection refused by policy")));
+    }
 }
\ No newline at end of file
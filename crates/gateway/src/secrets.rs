@@ -0,0 +1,73 @@
+//! 上游凭据（upstream_auth）与请求签名密钥（request_signing）：用 `encryption` 主密钥解密后按请求读取。
+//! 配置热加载时整体重建；新配置无效（主密钥缺失、密文无法解密等）时保留原有凭据。
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use arc_swap::{ArcSwap, ArcSwapOption};
+
+use crate::config::ProxyConfig;
+use crate::signing::RequestSigner;
+use crate::upstream_auth::UpstreamAuth;
+
+pub struct UpstreamSecrets {
+    auth: ArcSwap<UpstreamAuth>,
+    signer: ArcSwapOption<RequestSigner>,
+}
+
+impl UpstreamSecrets {
+    pub fn from_config(config: &ProxyConfig) -> anyhow::Result<Self> {
+        let (auth, signer) = build(config)?;
+        Ok(Self { auth: ArcSwap::from_pointee(auth), signer: ArcSwapOption::new(signer.map(Arc::new)) })
+    }
+
+    /// 按新配置重建；任一部分无效时保持原凭据不变。已换取的 OAuth2 token 随旧凭据一起丢弃
+    pub fn reload(&self, config: &ProxyConfig) -> anyhow::Result<()> {
+        let (auth, signer) = build(config)?;
+        self.auth.store(Arc::new(auth));
+        self.signer.store(signer.map(Arc::new));
+        Ok(())
+    }
+
+    pub fn auth(&self) -> Arc<UpstreamAuth> {
+        self.auth.load_full()
+    }
+
+    /// 启用 request_signing 时的签名器
+    pub fn signer(&self) -> Option<Arc<RequestSigner>> {
+        self.signer.load_full()
+    }
+}
+
+fn build(config: &ProxyConfig) -> anyhow::Result<(UpstreamAuth, Option<RequestSigner>)> {
+    let keys = config.encryption.master_keys().context("invalid encryption config")?;
+    let auth = UpstreamAuth::new(&config.upstream_auth, keys.as_ref(), config.request_timeout()).context("invalid upstream_auth")?;
+    let signer = RequestSigner::from_config(&config.request_signing, keys.as_ref()).context("invalid request_signing")?;
+    Ok((auth, signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::UpstreamCredential;
+
+    #[tokio::test]
+    async fn server_sealed_values_open_in_the_gateway() {
+        // 控制面与网关配置同一主密钥；控制面安装的密钥（/admin/encryption/seal 所用）加密的值网关能解密
+        let mut encryption = configs::EncryptionConfig::default();
+        encryption.master_key = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=".into();
+        let server = encryption.server_keys("server-jwt-secret-0123").unwrap();
+
+        let mut config = ProxyConfig::default();
+        config.encryption = encryption;
+        config.upstream_auth.insert("10.0.0.1:80".into(), UpstreamCredential::Bearer { token: server.seal(b"s3cret").unwrap() });
+        config.request_signing.enabled = true;
+        config.request_signing.secret = server.seal(b"signing-secret-0123456789").unwrap();
+        let secrets = UpstreamSecrets::from_config(&config).unwrap();
+        assert_eq!(secrets.auth().header_for("10.0.0.1:80").await.unwrap(), Some(("authorization".into(), "Bearer s3cret".into())));
+        assert!(secrets.signer().is_some());
+
+        // 未配置主密钥时控制面拒绝启动，不再由 JWT_SECRET 派生网关拿不到的密钥
+        assert!(configs::EncryptionConfig::default().server_keys("server-jwt-secret-0123").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! 转发请求签名：上游据此确认流量经过网关（内网中其他服务也能直连上游时）。
+//! - 签名串：`{timestamp}\n{METHOD}\n{path?query}\n{body_sha256}`，HMAC-SHA256 后 hex 编码；
+//! - body 不超过 `max_body_bytes` 且带 Content-Length 时在 request_filter 中读完并计算 SHA-256（pingora 重试缓冲区转发原 body），
+//!   chunked 或更大的 body 记为 `UNSIGNED-PAYLOAD`，上游可按需拒绝。
+
+use hmac::{Hmac, Mac};
+use pingora_http::RequestHeader;
+use sha2::{Digest, Sha256};
+
+use crate::config::RequestSigningConfig;
+use crate::upstream_auth::{reveal, UpstreamAuthError};
+
+pub const SIGNATURE_HEADER: &str = "x-gateway-signature";
+pub const TIMESTAMP_HEADER: &str = "x-gateway-timestamp";
+pub const CONTENT_SHA256_HEADER: &str = "x-gateway-content-sha256";
+pub const KEY_ID_HEADER: &str = "x-gateway-key-id";
+/// body 未参与签名
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+/// pingora 重试缓冲区大小，超过后无法原样转发已读取的 body
+const RETRY_BUFFER_LIMIT: usize = 64 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct RequestSigner {
+    key_id: String,
+    secret: Vec<u8>,
+    max_body_bytes: usize,
+}
+
+fn string_to_sign(timestamp: i64, method: &str, target: &str, body_sha256: &str) -> String {
+    format!("{}\n{}\n{}\n{}", timestamp, method.to_ascii_uppercase(), target, body_sha256)
+}
+
+/// 空 body 或完整 body 的 SHA-256（hex）
+pub fn body_sha256(body: &[u8]) -> String {
+    hex::encode(Sha256::digest(body))
+}
+
+impl RequestSigner {
+    /// 未启用时为 None；secret 可为控制面主密钥加密后的 `v1:` 值
+    pub fn from_config(cfg: &RequestSigningConfig, keys: Option<&common::crypto::MasterKeys>) -> Result<Option<Self>, UpstreamAuthError> {
+        if !cfg.enabled {
+            return Ok(None);
+        }
+        let secret = reveal("request_signing", &cfg.secret, keys)?;
+        if secret.len() < 16 {
+            return Err(UpstreamAuthError::Config { upstream: "request_signing".into(), reason: "secret must be at least 16 bytes".into() });
+        }
+        Ok(Some(Self { key_id: cfg.key_id.clone(), secret: secret.into_bytes(), max_body_bytes: cfg.max_body_bytes.min(RETRY_BUFFER_LIMIT) }))
+    }
+
+    /// 该请求的 body 是否读入内存参与签名：需有 Content-Length（chunked 为 None）且不超过 max_body_bytes
+    pub fn should_hash_body(&self, content_length: Option<usize>) -> bool {
+        content_length.is_some_and(|n| n <= self.max_body_bytes)
+    }
+
+    pub fn sign(&self, timestamp: i64, method: &str, target: &str, body_sha256: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("hmac accepts any key length");
+        mac.update(string_to_sign(timestamp, method, target, body_sha256).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 写入时间戳、body 摘要、key id 与签名（覆盖客户端伪造的同名头）
+    pub fn apply(&self, req: &mut RequestHeader, timestamp: i64, body_sha256: &str) -> pingora_core::Result<()> {
+        let method = req.method.to_string();
+        let target = req.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/").to_string();
+        let signature = self.sign(timestamp, &method, &target, body_sha256);
+        req.insert_header(TIMESTAMP_HEADER, timestamp.to_string())?;
+        req.insert_header(CONTENT_SHA256_HEADER, body_sha256)?;
+        req.insert_header(KEY_ID_HEADER, self.key_id.as_str())?;
+        req.insert_header(SIGNATURE_HEADER, format!("v1={}", signature))?;
+        Ok(())
+    }
+}
+
+/// 上游收到的签名相关字段
+#[derive(Debug, Clone, Copy)]
+pub struct SignedRequest<'a> {
+    pub timestamp: i64,
+    pub method: &'a str,
+    /// path + query，保持原始编码
+    pub target: &'a str,
+    pub body_sha256: &'a str,
+    /// `X-Gateway-Signature` 的值（`v1=<hex>`）
+    pub signature: &'a str,
+}
+
+/// 上游校验（Rust 服务可直接复用）：签名正确且时间戳与 now 相差不超过 tolerance_secs
+pub fn verify(secret: &[u8], req: &SignedRequest<'_>, now: i64, tolerance_secs: i64) -> bool {
+    if (now - req.timestamp).abs() > tolerance_secs {
+        return false;
+    }
+    let Some(sig) = req.signature.trim().strip_prefix("v1=").and_then(|s| hex::decode(s).ok()) else {
+        return false;
+    };
+    let mut mac = HmacSha256::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(string_to_sign(req.timestamp, req.method, req.target, req.body_sha256).as_bytes());
+    mac.verify_slice(&sig).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef-signing";
+
+    fn signer() -> RequestSigner {
+        let cfg = RequestSigningConfig { enabled: true, secret: SECRET.into(), ..Default::default() };
+        RequestSigner::from_config(&cfg, None).unwrap().unwrap()
+    }
+
+    #[test]
+    fn signed_request_verifies_and_detects_tampering() {
+        let s = signer();
+        let body = body_sha256(b"{\"amount\":10}");
+        let mut req = RequestHeader::build("POST", b"/v1/pay?x=1", None).unwrap();
+        s.apply(&mut req, 1_700_000_000, &body).unwrap();
+        let sig = req.headers.get(SIGNATURE_HEADER).unwrap().to_str().unwrap().to_string();
+        assert_eq!(req.headers.get(KEY_ID_HEADER).unwrap(), "default");
+
+        let ok = |method: &str, target: &str, body_sha256: &str, now: i64| {
+            let req = SignedRequest { timestamp: 1_700_000_000, method, target, body_sha256, signature: &sig };
+            verify(SECRET.as_bytes(), &req, now, 300)
+        };
+        assert!(ok("POST", "/v1/pay?x=1", &body, 1_700_000_100));
+        assert!(!ok("POST", "/v1/pay?x=2", &body, 1_700_000_100));
+        assert!(!ok("PUT", "/v1/pay?x=1", &body, 1_700_000_100));
+        assert!(!ok("POST", "/v1/pay?x=1", &body_sha256(b"{\"amount\":99}"), 1_700_000_100));
+        // 重放窗口之外
+        assert!(!ok("POST", "/v1/pay?x=1", &body, 1_700_001_000));
+    }
+
+    #[test]
+    fn body_hashing_limits_and_config_checks() {
+        let s = signer();
+        assert!(s.should_hash_body(Some(0)));
+        assert!(s.should_hash_body(Some(64 * 1024)));
+        assert!(!s.should_hash_body(Some(64 * 1024 + 1)));
+        assert!(!s.should_hash_body(None));
+
+        assert!(RequestSigner::from_config(&RequestSigningConfig::default(), None).unwrap().is_none());
+        let weak = RequestSigningConfig { enabled: true, secret: "short".into(), ..Default::default() };
+        assert!(RequestSigner::from_config(&weak, None).is_err());
+    }
+}
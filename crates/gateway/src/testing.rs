@@ -0,0 +1,188 @@
+//! 集成测试工具：内置 echo 上游 + 以子进程运行的网关 + 带断言的 HTTP 客户端，测试不再依赖外部站点。
+//! - 网关使用真实二进制（`cargo build --bin gateway`，或 `GATEWAY_BIN` 指定路径），配置写入临时目录；
+//! - 监听端口与 admin 端口都取随机空闲端口，多个测试可并行；`TestGateway` 释放时结束进程并清理目录；
+//! - echo 上游把方法、路径、查询参数、请求头与 body 以 JSON 返回，`TestResponse::echoed` 解析后即可断言转发结果。
+
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use common::echo::EchoResponse;
+use reqwest::header::HeaderMap;
+
+use crate::config::{ListenerConfig, ProxyConfig};
+
+/// 网关启动等待上限
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 网关二进制：`GATEWAY_BIN`，否则为工作区 target/debug/gateway
+pub fn gateway_bin() -> PathBuf {
+    std::env::var_os("GATEWAY_BIN")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../../target/debug/gateway"))
+}
+
+/// 取一个当前空闲的本地端口
+pub fn free_addr() -> std::io::Result<SocketAddr> {
+    TcpListener::bind("127.0.0.1:0")?.local_addr()
+}
+
+/// 在当前 tokio runtime 上启动 echo 上游（随机端口）
+pub async fn spawn_echo() -> anyhow::Result<SocketAddr> {
+    common::echo::spawn("127.0.0.1:0").await
+}
+
+/// 以 `upstream` 为唯一上游的测试配置：关闭限流（不依赖 Redis）
+pub fn config_for(upstream: SocketAddr) -> ProxyConfig {
+    let mut cfg = ProxyConfig { upstreams: vec![upstream.to_string()], ..Default::default() };
+    cfg.rate_limit.enabled = false;
+    cfg
+}
+
+/// 子进程网关；释放时结束进程并删除临时目录
+pub struct TestGateway {
+    child: Child,
+    dir: PathBuf,
+    pub addr: SocketAddr,
+    pub admin_addr: SocketAddr,
+}
+
+impl TestGateway {
+    /// 按 `cfg` 启动网关并等待监听端口可连接；`listeners` 与 upgrade 文件路径会被替换为临时值
+    pub async fn start(mut cfg: ProxyConfig) -> anyhow::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("gateway-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let (addr, admin_addr) = (free_addr()?, free_addr()?);
+        cfg.listeners = vec![ListenerConfig { name: "public".into(), addr: addr.to_string(), tls: None }];
+        cfg.upgrade.upgrade_sock = dir.join("upgrade.sock").to_string_lossy().into_owned();
+        cfg.upgrade.pid_file = dir.join("gateway.pid").to_string_lossy().into_owned();
+        std::fs::write(dir.join("config.json"), serde_json::to_vec_pretty(&cfg)?)?;
+
+        let bin = gateway_bin();
+        let child = Command::new(&bin)
+            .current_dir(&dir)
+            .env("GATEWAY_ADMIN_ADDR", admin_addr.to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("spawn {} (run `cargo build --bin gateway` or set GATEWAY_BIN): {}", bin.display(), e))?;
+        let mut gateway = Self { child, dir, addr, admin_addr };
+
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while tokio::net::TcpStream::connect(addr).await.is_err() {
+            if let Some(status) = gateway.child.try_wait()? {
+                anyhow::bail!("gateway exited during startup: {}", status);
+            }
+            if Instant::now() > deadline {
+                anyhow::bail!("gateway did not listen on {} within {:?}", addr, STARTUP_TIMEOUT);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        gateway.wait_ready(deadline).await?;
+        Ok(gateway)
+    }
+
+    /// 等待 `/readyz`（上游健康检查通过）
+    async fn wait_ready(&mut self, deadline: Instant) -> anyhow::Result<()> {
+        let url = format!("http://{}/readyz", self.admin_addr);
+        loop {
+            if matches!(reqwest::get(&url).await, Ok(r) if r.status().is_success()) {
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                anyhow::bail!("gateway not ready within {:?}", STARTUP_TIMEOUT);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    pub fn client(&self) -> TestClient {
+        TestClient::new(format!("http://{}", self.addr))
+    }
+
+    /// 临时目录（含 config.json），用于放置 fixture 等文件
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for TestGateway {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 面向 base URL 的请求客户端
+#[derive(Clone)]
+pub struct TestClient {
+    base: String,
+    http: reqwest::Client,
+}
+
+impl TestClient {
+    pub fn new(base: impl Into<String>) -> Self {
+        Self { base: base.into(), http: reqwest::Client::new() }
+    }
+
+    pub fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http.request(method, format!("{}{}", self.base, path))
+    }
+
+    pub async fn get(&self, path: &str) -> anyhow::Result<TestResponse> {
+        TestResponse::from(self.request(reqwest::Method::GET, path).send().await?).await
+    }
+
+    pub async fn post(&self, path: &str, body: impl Into<reqwest::Body>) -> anyhow::Result<TestResponse> {
+        TestResponse::from(self.request(reqwest::Method::POST, path).body(body).send().await?).await
+    }
+
+    pub async fn send(&self, builder: reqwest::RequestBuilder) -> anyhow::Result<TestResponse> {
+        TestResponse::from(builder.send().await?).await
+    }
+}
+
+/// 已读完 body 的响应；断言方法失败时 panic 并带上响应内容
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl TestResponse {
+    pub async fn from(resp: reqwest::Response) -> anyhow::Result<Self> {
+        let status = resp.status().as_u16();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await?.to_vec();
+        Ok(Self { status, headers, body })
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    #[track_caller]
+    pub fn assert_status(&self, status: u16) -> &Self {
+        assert_eq!(self.status, status, "unexpected status; body: {}", self.text());
+        self
+    }
+
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, value: &str) -> &Self {
+        assert_eq!(self.header(name), Some(value), "header {}", name);
+        self
+    }
+
+    /// 解析 echo 上游返回的请求描述
+    #[track_caller]
+    pub fn echoed(&self) -> EchoResponse {
+        serde_json::from_slice(&self.body).unwrap_or_else(|e| panic!("not an echo response ({}): {}", e, self.text()))
+    }
+}
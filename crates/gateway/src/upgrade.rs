@@ -0,0 +1,75 @@
+//! 零停机升级：基于 Pingora 的 graceful upgrade，监听 socket 经 `upgrade_sock` 交接给新进程。
+//!
+//! 1. 新二进制以 `--upgrade`（`-u`）启动，在 `upgrade_sock` 上等待旧进程的监听 fd；
+//! 2. 向旧进程发送 `SIGQUIT`（PID 见 `pid_file`）；
+//! 3. 旧进程交出 fd 后停止 accept，在 grace period 内处理完存量请求后退出。
+//!
+//! 期间监听端口始终处于 listen 状态，客户端不会遇到 connection refused。
+//! `SIGTERM` 为普通的优雅退出，`SIGINT` 立即退出。
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use pingora_core::server::configuration::ServerConf;
+
+use crate::config::UpgradeConfig;
+
+/// 触发升级的信号
+pub const UPGRADE_SIGNAL: &str = "QUIT";
+
+/// 升级相关的 Pingora 进程配置
+pub fn server_conf(cfg: &UpgradeConfig) -> ServerConf {
+    ServerConf {
+        upgrade_sock: cfg.upgrade_sock.clone(),
+        pid_file: cfg.pid_file.clone(),
+        grace_period_seconds: Some(cfg.grace_period_secs),
+        graceful_shutdown_timeout_seconds: Some(cfg.graceful_shutdown_timeout_secs),
+        ..Default::default()
+    }
+}
+
+/// 非 daemon 模式下 Pingora 不写 PID 文件，由网关自己写入（新进程会覆盖旧进程的 PID）
+pub fn write_pid_file(path: impl AsRef<Path>) -> io::Result<()> {
+    std::fs::write(path, format!("{}\n", std::process::id()))
+}
+
+pub fn read_pid(path: impl AsRef<Path>) -> io::Result<u32> {
+    let raw = std::fs::read_to_string(path)?;
+    raw.trim().parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid pid {:?}: {}", raw.trim(), e)))
+}
+
+/// 向 PID 文件中的进程发送 SIGQUIT，返回其 PID。新进程需已以 `--upgrade` 启动。
+pub fn signal_upgrade(pid_file: impl AsRef<Path>) -> io::Result<u32> {
+    let pid = read_pid(pid_file)?;
+    let status = Command::new("kill").arg(format!("-{}", UPGRADE_SIGNAL)).arg(pid.to_string()).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("kill -{} {} failed: {}", UPGRADE_SIGNAL, pid, status)));
+    }
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_conf_carries_upgrade_settings() {
+        let cfg = UpgradeConfig { grace_period_secs: 5, ..Default::default() };
+        let conf = server_conf(&cfg);
+        assert_eq!(conf.upgrade_sock, cfg.upgrade_sock);
+        assert_eq!(conf.pid_file, cfg.pid_file);
+        assert_eq!(conf.grace_period_seconds, Some(5));
+        assert_eq!(conf.graceful_shutdown_timeout_seconds, Some(10));
+    }
+
+    #[test]
+    fn pid_file_roundtrip() {
+        let path = std::env::temp_dir().join(format!("gateway_pid_{}", std::process::id()));
+        write_pid_file(&path).unwrap();
+        assert_eq!(read_pid(&path).unwrap(), std::process::id());
+        std::fs::write(&path, "not-a-pid").unwrap();
+        assert_eq!(read_pid(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,368 @@
+//! multipart/form-data 上传检查（流式）。
+//! - 按最长 path_prefix 命中 `uploads.rules` 中的一条规则；
+//! - request_body_filter 中逐块解析 boundary，只保留跨块匹配所需的尾部字节与当前 part 的头部，不缓冲整个 body；
+//! - 超过 part 数或单个 part 大小返回 413，文件类型不在白名单返回 415，格式错误返回 400。
+
+use crate::config::{UploadConfig, UploadRule};
+use crate::content_type::{media_type, media_type_matches};
+
+/// 单个 part 头部的最大字节数
+const MAX_PART_HEADER_BYTES: usize = 8 * 1024;
+/// 文件 part 未声明 Content-Type 时按此类型检查白名单
+const DEFAULT_FILE_CONTENT_TYPE: &str = "application/octet-stream";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadViolation {
+    TooManyParts { limit: usize },
+    PartTooLarge { limit: u64 },
+    ContentTypeNotAllowed { content_type: String },
+    Malformed { reason: &'static str },
+}
+
+impl UploadViolation {
+    pub fn status(&self) -> u16 {
+        match self {
+            UploadViolation::TooManyParts { .. } | UploadViolation::PartTooLarge { .. } => 413,
+            UploadViolation::ContentTypeNotAllowed { .. } => 415,
+            UploadViolation::Malformed { .. } => 400,
+        }
+    }
+
+    /// 指标 reason 标签
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UploadViolation::TooManyParts { .. } => "too_many_parts",
+            UploadViolation::PartTooLarge { .. } => "part_too_large",
+            UploadViolation::ContentTypeNotAllowed { .. } => "content_type",
+            UploadViolation::Malformed { .. } => "malformed",
+        }
+    }
+}
+
+impl std::fmt::Display for UploadViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadViolation::TooManyParts { limit } => write!(f, "multipart body has more than {} parts", limit),
+            UploadViolation::PartTooLarge { limit } => write!(f, "multipart part exceeds {} bytes", limit),
+            UploadViolation::ContentTypeNotAllowed { content_type } => write!(f, "file content type {} is not allowed", content_type),
+            UploadViolation::Malformed { reason } => write!(f, "malformed multipart body: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for UploadViolation {}
+
+/// 按最长前缀选择规则
+pub fn rule_for<'a>(cfg: &'a UploadConfig, path: &str) -> Option<&'a UploadRule> {
+    cfg.rules.iter().filter(|r| path.starts_with(r.path_prefix.as_str())).max_by_key(|r| r.path_prefix.len())
+}
+
+/// 从 `multipart/form-data; boundary=...` 中取 boundary；其他类型为 None
+pub fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        k.trim().eq_ignore_ascii_case("boundary").then(|| v.trim().trim_matches('"').to_string())
+    }).filter(|b| !b.is_empty() && b.len() <= 70)
+}
+
+/// 命中规则的 multipart 请求返回检查器；未启用、未命中规则或不是 multipart 时为 None。
+/// 声明为 multipart 却缺少合法 boundary 时返回错误
+pub fn inspector_for(cfg: &UploadConfig, path: &str, content_type: Option<&str>) -> Result<Option<MultipartInspector>, UploadViolation> {
+    if !cfg.enabled {
+        return Ok(None);
+    }
+    let (Some(rule), Some(content_type)) = (rule_for(cfg, path), content_type) else {
+        return Ok(None);
+    };
+    let is_multipart = content_type.split(';').next().is_some_and(|t| t.trim().eq_ignore_ascii_case("multipart/form-data"));
+    if !is_multipart {
+        return Ok(None);
+    }
+    let boundary = multipart_boundary(content_type).ok_or(UploadViolation::Malformed { reason: "missing boundary" })?;
+    Ok(Some(MultipartInspector::new(rule, &boundary)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 第一个 boundary 之前
+    Preamble,
+    /// 刚匹配到 boundary，等待 `\r\n`（下一个 part）或 `--`（结束）
+    AfterDelimiter,
+    Headers,
+    Body,
+    /// 结束 boundary 之后的内容忽略
+    Epilogue,
+}
+
+/// 已结束的 part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartSummary {
+    pub bytes: u64,
+    /// 带 filename 的文件 part
+    pub is_file: bool,
+}
+
+#[derive(Debug)]
+pub struct MultipartInspector {
+    /// 命中规则的 path_prefix（指标 route 标签）
+    route: String,
+    /// `\r\n--{boundary}`
+    delimiter: Vec<u8>,
+    max_parts: Option<usize>,
+    max_part_bytes: Option<u64>,
+    allowed_content_types: Vec<String>,
+    state: State,
+    pending: Vec<u8>,
+    parts: usize,
+    part_bytes: u64,
+    part_is_file: bool,
+    completed: Vec<PartSummary>,
+}
+
+impl MultipartInspector {
+    pub fn new(rule: &UploadRule, boundary: &str) -> Self {
+        Self {
+            route: rule.path_prefix.clone(),
+            delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            max_parts: rule.max_parts,
+            max_part_bytes: rule.max_file_bytes,
+            allowed_content_types: rule.allowed_content_types.iter().map(|t| t.trim().to_ascii_lowercase()).collect(),
+            state: State::Preamble,
+            // body 以 `--boundary` 开头，补上 CRLF 后与后续分隔符统一匹配
+            pending: b"\r\n".to_vec(),
+            parts: 0,
+            part_bytes: 0,
+            part_is_file: false,
+            completed: Vec::new(),
+        }
+    }
+
+    pub fn route(&self) -> &str {
+        &self.route
+    }
+
+    /// 处理一块 body；出错后不应再继续调用
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), UploadViolation> {
+        self.pending.extend_from_slice(chunk);
+        let mut pos = 0;
+        loop {
+            let rest = &self.pending[pos..];
+            match self.state {
+                State::Preamble | State::Body => {
+                    if let Some(i) = find(rest, &self.delimiter) {
+                        if self.state == State::Body {
+                            self.add_part_bytes(i as u64)?;
+                            self.finish_part();
+                        }
+                        pos += i + self.delimiter.len();
+                        self.state = State::AfterDelimiter;
+                    } else {
+                        // 保留可能构成分隔符前缀的尾部
+                        let keep = rest.len().min(self.delimiter.len() - 1);
+                        let consumed = rest.len() - keep;
+                        if self.state == State::Body {
+                            self.add_part_bytes(consumed as u64)?;
+                        }
+                        pos += consumed;
+                        break;
+                    }
+                }
+                State::AfterDelimiter => {
+                    // boundary 行末允许空白（RFC 2046 transport padding）
+                    let pad = rest.iter().take_while(|b| **b == b' ' || **b == b'\t').count();
+                    let rest = &rest[pad..];
+                    if rest.len() < 2 {
+                        pos += pad;
+                        break;
+                    }
+                    match &rest[..2] {
+                        b"--" => self.state = State::Epilogue,
+                        b"\r\n" => self.start_part()?,
+                        _ => return Err(UploadViolation::Malformed { reason: "invalid boundary line" }),
+                    }
+                    pos += pad + 2;
+                }
+                State::Headers => {
+                    // 没有头部的 part
+                    if rest.starts_with(b"\r\n") {
+                        pos += 2;
+                        self.check_part_headers("")?;
+                        self.state = State::Body;
+                        continue;
+                    }
+                    let Some(i) = find(rest, b"\r\n\r\n") else {
+                        if rest.len() > MAX_PART_HEADER_BYTES {
+                            return Err(UploadViolation::Malformed { reason: "part headers too large" });
+                        }
+                        break;
+                    };
+                    if i > MAX_PART_HEADER_BYTES {
+                        return Err(UploadViolation::Malformed { reason: "part headers too large" });
+                    }
+                    let headers = String::from_utf8_lossy(&rest[..i]).into_owned();
+                    pos += i + 4;
+                    self.check_part_headers(&headers)?;
+                    self.state = State::Body;
+                }
+                State::Epilogue => {
+                    pos = self.pending.len();
+                    break;
+                }
+            }
+        }
+        self.pending.drain(..pos);
+        Ok(())
+    }
+
+    /// body 结束时调用：缺少结束 boundary 视为格式错误
+    pub fn finish(&mut self) -> Result<(), UploadViolation> {
+        if self.state == State::Epilogue {
+            Ok(())
+        } else {
+            Err(UploadViolation::Malformed { reason: "missing closing boundary" })
+        }
+    }
+
+    /// 取出自上次调用以来结束的 part（用于指标）
+    pub fn drain_completed(&mut self) -> Vec<PartSummary> {
+        std::mem::take(&mut self.completed)
+    }
+
+    fn start_part(&mut self) -> Result<(), UploadViolation> {
+        self.parts += 1;
+        if let Some(limit) = self.max_parts.filter(|l| self.parts > *l) {
+            return Err(UploadViolation::TooManyParts { limit });
+        }
+        self.part_bytes = 0;
+        self.part_is_file = false;
+        self.state = State::Headers;
+        Ok(())
+    }
+
+    fn check_part_headers(&mut self, headers: &str) -> Result<(), UploadViolation> {
+        let mut content_type = None;
+        for line in headers.split("\r\n") {
+            let Some((name, value)) = line.split_once(':') else { continue };
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-disposition") {
+                self.part_is_file = value.split(';').skip(1).any(|p| {
+                    let p = p.trim().to_ascii_lowercase();
+                    p.starts_with("filename=") || p.starts_with("filename*=")
+                });
+            } else if name.eq_ignore_ascii_case("content-type") {
+                content_type = Some(media_type(value));
+            }
+        }
+        if self.part_is_file && !self.allowed_content_types.is_empty() {
+            let content_type = content_type.unwrap_or_else(|| DEFAULT_FILE_CONTENT_TYPE.to_string());
+            if !self.allowed_content_types.iter().any(|allowed| media_type_matches(allowed, &content_type)) {
+                return Err(UploadViolation::ContentTypeNotAllowed { content_type });
+            }
+        }
+        Ok(())
+    }
+
+    fn add_part_bytes(&mut self, n: u64) -> Result<(), UploadViolation> {
+        self.part_bytes += n;
+        match self.max_part_bytes {
+            Some(limit) if self.part_bytes > limit => Err(UploadViolation::PartTooLarge { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    fn finish_part(&mut self) {
+        self.completed.push(PartSummary { bytes: self.part_bytes, is_file: self.part_is_file });
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(max_parts: Option<usize>, max_file_bytes: Option<u64>, allowed: &[&str]) -> UploadRule {
+        UploadRule {
+            path_prefix: "/upload".into(),
+            max_parts,
+            max_file_bytes,
+            allowed_content_types: allowed.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn body(parts: &[(&str, Option<&str>, &str)]) -> Vec<u8> {
+        let mut out = String::from("preamble\r\n");
+        for (name, file, data) in parts {
+            out.push_str("--XyZ\r\n");
+            match file {
+                Some(ct) => out.push_str(&format!("Content-Disposition: form-data; name=\"{}\"; filename=\"f.bin\"\r\nContent-Type: {}\r\n\r\n", name, ct)),
+                None => out.push_str(&format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name)),
+            }
+            out.push_str(data);
+            out.push_str("\r\n");
+        }
+        out.push_str("--XyZ--\r\n");
+        out.into_bytes()
+    }
+
+    /// 按 chunk 大小切块喂入，模拟任意位置被拆开的 boundary
+    fn inspect(rule: &UploadRule, body: &[u8], chunk: usize) -> Result<Vec<PartSummary>, UploadViolation> {
+        let mut inspector = MultipartInspector::new(rule, "XyZ");
+        for c in body.chunks(chunk) {
+            inspector.feed(c)?;
+        }
+        inspector.finish()?;
+        Ok(inspector.drain_completed())
+    }
+
+    #[test]
+    fn parses_parts_across_chunk_boundaries() {
+        let data = "a".repeat(100);
+        let b = body(&[("title", None, "hello"), ("file", Some("image/png"), &data)]);
+        let expected = vec![PartSummary { bytes: 5, is_file: false }, PartSummary { bytes: 100, is_file: true }];
+        for chunk in [1, 2, 3, 7, 64, b.len()] {
+            assert_eq!(inspect(&rule(None, None, &[]), &b, chunk).unwrap(), expected, "chunk size {}", chunk);
+        }
+        // 截断的 body
+        assert!(matches!(inspect(&rule(None, None, &[]), &b[..b.len() - 10], 16), Err(UploadViolation::Malformed { .. })));
+    }
+
+    #[test]
+    fn enforces_part_count_size_and_content_type() {
+        let b = body(&[("a", None, "1"), ("b", None, "2"), ("c", None, "3")]);
+        assert_eq!(inspect(&rule(Some(2), None, &[]), &b, 5), Err(UploadViolation::TooManyParts { limit: 2 }));
+
+        let big = "x".repeat(1000);
+        let b = body(&[("file", Some("image/png"), &big)]);
+        let err = inspect(&rule(None, Some(999), &[]), &b, 100).unwrap_err();
+        assert_eq!((err.status(), err.as_str()), (413, "part_too_large"));
+        assert!(inspect(&rule(None, Some(1000), &[]), &b, 100).is_ok());
+
+        let allow = rule(None, None, &["image/*", "application/pdf"]);
+        assert!(inspect(&allow, &body(&[("f", Some("image/jpeg"), "x"), ("note", None, "plain field")]), 8).is_ok());
+        let err = inspect(&allow, &body(&[("f", Some("application/x-msdownload"), "MZ")]), 8).unwrap_err();
+        assert_eq!(err.status(), 415);
+    }
+
+    #[test]
+    fn selects_rule_and_boundary() {
+        let cfg = UploadConfig {
+            enabled: true,
+            rules: vec![rule(Some(1), None, &[]), UploadRule { path_prefix: "/upload/avatars".into(), ..rule(Some(2), None, &[]) }],
+        };
+        assert_eq!(rule_for(&cfg, "/upload/avatars/1").unwrap().max_parts, Some(2));
+        assert_eq!(multipart_boundary("multipart/form-data; boundary=\"XyZ\""), Some("XyZ".into()));
+        assert!(inspector_for(&cfg, "/upload", Some("application/json")).unwrap().is_none());
+        assert!(inspector_for(&cfg, "/other", Some("multipart/form-data; boundary=XyZ")).unwrap().is_none());
+        assert!(inspector_for(&cfg, "/upload", Some("multipart/form-data; boundary=XyZ")).unwrap().is_some());
+        assert!(inspector_for(&cfg, "/upload", Some("multipart/form-data")).is_err());
+        let disabled = UploadConfig { enabled: false, ..cfg };
+        assert!(inspector_for(&disabled, "/upload", Some("multipart/form-data; boundary=XyZ")).unwrap().is_none());
+    }
+}
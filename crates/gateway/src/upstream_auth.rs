@@ -0,0 +1,242 @@
+//! 上游认证凭据注入。
+//! - 按选中的上游地址查找凭据，在 upstream_request_filter 中写入请求头（覆盖客户端自带的同名头）；
+//! - 静态凭据（Bearer / Basic / API key）启动时解密并预先拼好请求头；
+//! - OAuth2 client credentials 在首次使用或即将过期时换取 token，同一上游的并发请求只刷新一次。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::crypto::{self, MasterKeys};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::config::UpstreamCredential;
+
+/// token 响应未带 expires_in 时的缓存时间
+const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+pub enum UpstreamAuthError {
+    /// 凭据无法解密等配置问题，启动时报告
+    Config { upstream: String, reason: String },
+    /// 换取 OAuth2 token 失败，本次请求返回 502
+    Token { url: String, reason: String },
+}
+
+impl std::fmt::Display for UpstreamAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpstreamAuthError::Config { upstream, reason } => write!(f, "upstream_auth {}: {}", upstream, reason),
+            UpstreamAuthError::Token { url, reason } => write!(f, "token request to {} failed: {}", url, reason),
+        }
+    }
+}
+
+impl std::error::Error for UpstreamAuthError {}
+
+enum Credential {
+    Static { header: String, value: String },
+    OAuth2(OAuth2Client),
+}
+
+struct OAuth2Client {
+    token_url: String,
+    form: Vec<(&'static str, String)>,
+    refresh_skew: Duration,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    header: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+pub struct UpstreamAuth {
+    credentials: HashMap<String, Credential>,
+    client: reqwest::Client,
+}
+
+/// 密文用主密钥解密；未加密的值原样使用并告警
+pub fn reveal(upstream: &str, value: &str, keys: Option<&MasterKeys>) -> Result<String, UpstreamAuthError> {
+    let fail = |reason: String| UpstreamAuthError::Config { upstream: upstream.to_string(), reason };
+    if !crypto::is_sealed(value) {
+        if !value.is_empty() {
+            warn!(upstream, "plaintext secret in gateway config; seal it with POST /admin/encryption/seal");
+        }
+        return Ok(value.to_string());
+    }
+    let keys = keys.ok_or_else(|| fail("encrypted value but encryption.master_key is not configured".into()))?;
+    let plain = keys.open(value).map_err(|e| fail(e.to_string()))?;
+    String::from_utf8(plain).map_err(|_| fail("decrypted value is not UTF-8".into()))
+}
+
+impl UpstreamAuth {
+    pub fn new(config: &HashMap<String, UpstreamCredential>, keys: Option<&MasterKeys>, timeout: Duration) -> Result<Self, UpstreamAuthError> {
+        let mut credentials = HashMap::new();
+        for (upstream, cred) in config {
+            let reveal = |v: &str| reveal(upstream, v, keys);
+            let credential = match cred {
+                UpstreamCredential::Bearer { token } => Credential::Static { header: "authorization".into(), value: format!("Bearer {}", reveal(token)?) },
+                UpstreamCredential::Basic { username, password } => {
+                    let pair = format!("{}:{}", username, reveal(password)?);
+                    Credential::Static { header: "authorization".into(), value: format!("Basic {}", STANDARD.encode(pair)) }
+                }
+                UpstreamCredential::ApiKey { header, value } => Credential::Static { header: header.to_ascii_lowercase(), value: reveal(value)? },
+                UpstreamCredential::Oauth2ClientCredentials { token_url, client_id, client_secret, scope, audience, refresh_skew_secs } => {
+                    let mut form = vec![("grant_type", "client_credentials".to_string()), ("client_id", client_id.clone()), ("client_secret", reveal(client_secret)?)];
+                    form.extend(scope.clone().map(|s| ("scope", s)));
+                    form.extend(audience.clone().map(|a| ("audience", a)));
+                    Credential::OAuth2(OAuth2Client { token_url: token_url.clone(), form, refresh_skew: Duration::from_secs(*refresh_skew_secs), cached: Mutex::new(None) })
+                }
+            };
+            credentials.insert(upstream.clone(), credential);
+        }
+        let client = reqwest::Client::builder().timeout(timeout).build().map_err(|e| UpstreamAuthError::Config { upstream: "*".into(), reason: e.to_string() })?;
+        Ok(Self { credentials, client })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.credentials.is_empty()
+    }
+
+    /// 该上游需要注入的请求头（小写名, 值）；未配置凭据时为 None
+    pub async fn header_for(&self, upstream: &str) -> Result<Option<(String, String)>, UpstreamAuthError> {
+        match self.credentials.get(upstream) {
+            None => Ok(None),
+            Some(Credential::Static { header, value }) => Ok(Some((header.clone(), value.clone()))),
+            Some(Credential::OAuth2(oauth)) => Ok(Some(("authorization".into(), oauth.token(&self.client).await?))),
+        }
+    }
+}
+
+impl OAuth2Client {
+    async fn token(&self, client: &reqwest::Client) -> Result<String, UpstreamAuthError> {
+        // 持锁刷新：token 过期瞬间的并发请求只发一次 token 请求
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref().filter(|t| Instant::now() < t.expires_at) {
+            return Ok(token.header.clone());
+        }
+        let fail = |reason: String| UpstreamAuthError::Token { url: self.token_url.clone(), reason };
+        let resp = client.post(&self.token_url).form(&self.form).send().await.map_err(|e| fail(e.to_string()))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            warn!(event = "upstream_token_failed", token_url = %self.token_url, status = status.as_u16(), "upstream token request rejected");
+            return Err(fail(format!("status {}", status)));
+        }
+        let body: TokenResponse = resp.json().await.map_err(|e| fail(e.to_string()))?;
+        let ttl = body.expires_in.map(Duration::from_secs).unwrap_or(DEFAULT_TOKEN_TTL);
+        let token_type = body.token_type.filter(|t| !t.eq_ignore_ascii_case("bearer")).unwrap_or_else(|| "Bearer".into());
+        let header = format!("{} {}", token_type, body.access_token);
+        info!(event = "upstream_token_refreshed", token_url = %self.token_url, ttl_secs = ttl.as_secs(), "upstream access token refreshed");
+        *cached = Some(CachedToken { header: header.clone(), expires_at: Instant::now() + ttl.saturating_sub(self.refresh_skew) });
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::crypto::SecretBox;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn auth(entries: Vec<(&str, UpstreamCredential)>, keys: Option<&MasterKeys>) -> Result<UpstreamAuth, UpstreamAuthError> {
+        let config = entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        UpstreamAuth::new(&config, keys, Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn static_credentials_decrypt_sealed_values() {
+        let keys = MasterKeys::new("k1", SecretBox::derive_from("gateway-test"));
+        let sealed = keys.seal(b"s3cret").unwrap();
+        let a = auth(
+            vec![
+                ("10.0.0.1:80", UpstreamCredential::Bearer { token: sealed.clone() }),
+                ("10.0.0.2:80", UpstreamCredential::Basic { username: "svc".into(), password: "pw".into() }),
+                ("10.0.0.3:80", UpstreamCredential::ApiKey { header: "X-Api-Key".into(), value: sealed.clone() }),
+            ],
+            Some(&keys),
+        )
+        .unwrap();
+
+        assert_eq!(a.header_for("10.0.0.1:80").await.unwrap(), Some(("authorization".into(), "Bearer s3cret".into())));
+        assert_eq!(a.header_for("10.0.0.2:80").await.unwrap(), Some(("authorization".into(), "Basic c3ZjOnB3".into())));
+        assert_eq!(a.header_for("10.0.0.3:80").await.unwrap(), Some(("x-api-key".into(), "s3cret".into())));
+        assert_eq!(a.header_for("10.0.0.9:80").await.unwrap(), None);
+
+        // 密文需要主密钥
+        assert!(auth(vec![("10.0.0.1:80", UpstreamCredential::Bearer { token: sealed })], None).is_err());
+    }
+
+    #[tokio::test]
+    async fn oauth2_token_is_cached_until_expiry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let app = axum::Router::new().route(
+            "/token",
+            axum::routing::post(move |body: String| {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    assert!(body.contains("grant_type=client_credentials") && body.contains("client_secret=cs"));
+                    axum::Json(serde_json::json!({ "access_token": format!("t{}", n), "token_type": "bearer", "expires_in": 3600 }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let cred = UpstreamCredential::Oauth2ClientCredentials {
+            token_url: format!("http://{}/token", addr),
+            client_id: "gw".into(),
+            client_secret: "cs".into(),
+            scope: Some("read".into()),
+            audience: None,
+            refresh_skew_secs: 30,
+        };
+        let a = Arc::new(auth(vec![("10.0.0.1:80", cred)], None).unwrap());
+        let results = fetch_concurrently(&a).await;
+        assert!(results.iter().all(|h| h == "Bearer t1"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    async fn fetch_concurrently(a: &Arc<UpstreamAuth>) -> Vec<String> {
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let a = Arc::clone(a);
+                tokio::spawn(async move { a.header_for("10.0.0.1:80").await.unwrap().unwrap().1 })
+            })
+            .collect();
+        let mut out = Vec::new();
+        for t in tasks {
+            out.push(t.await.unwrap());
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn oauth2_token_error_is_reported() {
+        let cred = UpstreamCredential::Oauth2ClientCredentials {
+            token_url: "http://127.0.0.1:1/token".into(),
+            client_id: "gw".into(),
+            client_secret: "cs".into(),
+            scope: None,
+            audience: None,
+            refresh_skew_secs: 30,
+        };
+        let a = auth(vec![("10.0.0.1:80", cred)], None).unwrap();
+        assert!(matches!(a.header_for("10.0.0.1:80").await, Err(UpstreamAuthError::Token { .. })));
+    }
+}
@@ -0,0 +1,262 @@
+//! WAF 风格的请求检查：按方法、请求目标、请求头、User-Agent、body 大小匹配规则，
+//! 命中后拦截（block）、按客户端限流（rate_limit）或打标签（tag，以 `X-Waf-Tags` 传给上游）。
+//! 规则在启动时编译，每条规则的命中数见 `api_proxy_waf_rule_hits_total{rule,action}`。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use regex::Regex;
+
+use crate::config::{WafAction, WafConfig, WafRuleConfig};
+use crate::observability::Metrics;
+use crate::rate_limiter::TokenBucket;
+
+/// 打标签结果传给上游的请求头
+pub const WAF_TAGS_HEADER: &str = "X-Waf-Tags";
+
+/// 单条规则的客户端桶超过该数量时整体清空
+const BUCKET_PRUNE_THRESHOLD: usize = 10_000;
+
+/// 内置规则：常见 SQL 注入、XSS、路径穿越与扫描器 User-Agent
+pub fn default_rules() -> Vec<WafRuleConfig> {
+    let rule = |id: &str, path: Option<&str>, user_agent: Option<&str>| WafRuleConfig {
+        id: id.to_string(),
+        action: WafAction::Block,
+        methods: Vec::new(),
+        path: path.map(str::to_string),
+        headers: HashMap::new(),
+        user_agent: user_agent.map(str::to_string),
+        max_body_bytes: None,
+        status: None,
+        requests_per_second: None,
+        burst: None,
+    };
+    vec![
+        rule(
+            "default.sqli",
+            Some(r"(?i)(union(\s|%20|\+)+(all(\s|%20|\+)+)?select|'(\s|%20|\+)*or(\s|%20|\+)+'?1'?(\s|%20|\+)*=|information_schema|sleep(\(|%28)\d+(\)|%29)|;(\s|%20)*drop(\s|%20|\+)+table)"),
+            None,
+        ),
+        rule("default.xss", Some(r"(?i)(<|%3c)(\s|%20)*script|javascript(:|%3a)|onerror(\s|%20)*(=|%3d)"), None),
+        rule("default.path_traversal", Some(r"(?i)(\.\.|%2e%2e)(/|%2f|\\|%5c)|/etc/passwd"), None),
+        rule("default.scanner", None, Some(r"(?i)(sqlmap|nikto|nmap|masscan|acunetix|nessus|wpscan|dirbuster|zgrab)")),
+    ]
+}
+
+#[derive(Debug)]
+struct Rule {
+    id: String,
+    action: WafAction,
+    methods: Vec<String>,
+    path: Option<Regex>,
+    headers: Vec<(String, Regex)>,
+    user_agent: Option<Regex>,
+    max_body_bytes: Option<u64>,
+    status: u16,
+    rate: Option<(u64, u64)>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+fn compile(id: &str, field: &str, pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("waf rule {}: invalid {} regex: {}", id, field, e))
+}
+
+impl Rule {
+    fn new(cfg: &WafRuleConfig) -> Result<Self, String> {
+        let id = &cfg.id;
+        if cfg.methods.is_empty() && cfg.path.is_none() && cfg.headers.is_empty() && cfg.user_agent.is_none() && cfg.max_body_bytes.is_none() {
+            return Err(format!("waf rule {}: at least one condition is required", id));
+        }
+        let rate = match cfg.action {
+            WafAction::RateLimit => {
+                let rps = cfg.requests_per_second.filter(|r| *r > 0).ok_or_else(|| format!("waf rule {}: rate_limit requires requests_per_second", id))?;
+                Some((rps, cfg.burst.unwrap_or(rps).max(1)))
+            }
+            _ => None,
+        };
+        let mut headers = Vec::with_capacity(cfg.headers.len());
+        for (name, pattern) in &cfg.headers {
+            headers.push((name.to_ascii_lowercase(), compile(id, name, pattern)?));
+        }
+        Ok(Self {
+            id: id.clone(),
+            action: cfg.action,
+            methods: cfg.methods.iter().map(|m| m.to_ascii_uppercase()).collect(),
+            path: cfg.path.as_deref().map(|p| compile(id, "path", p)).transpose()?,
+            headers,
+            user_agent: cfg.user_agent.as_deref().map(|p| compile(id, "user_agent", p)).transpose()?,
+            max_body_bytes: cfg.max_body_bytes,
+            status: cfg.status.unwrap_or(403),
+            rate,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn matches<'h>(&self, method: &str, target: &str, header: &impl Fn(&str) -> Option<&'h str>) -> bool {
+        (self.methods.is_empty() || self.methods.iter().any(|m| m.eq_ignore_ascii_case(method)))
+            && self.path.as_ref().is_none_or(|re| re.is_match(target))
+            && self.headers.iter().all(|(name, re)| header(name).is_some_and(|v| re.is_match(v)))
+            && self.user_agent.as_ref().is_none_or(|re| header("user-agent").is_some_and(|v| re.is_match(v)))
+            && self
+                .max_body_bytes
+                .is_none_or(|max| header("content-length").and_then(|v| v.parse::<u64>().ok()).is_some_and(|len| len > max))
+    }
+
+    /// 令牌桶按客户端隔离
+    fn acquire(&self, client: &str) -> bool {
+        let Some((rps, burst)) = self.rate else { return true };
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= BUCKET_PRUNE_THRESHOLD && !buckets.contains_key(client) {
+            buckets.clear();
+        }
+        buckets.entry(client.to_string()).or_insert_with(|| TokenBucket::new(burst, rps)).try_acquire(1)
+    }
+}
+
+/// 一次检查的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verdict {
+    /// (规则 ID, 响应码)；限流规则超限时为 429
+    pub rejected: Option<(String, u16)>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Waf {
+    enabled: bool,
+    rules: Vec<Rule>,
+    metrics: Arc<Metrics>,
+}
+
+impl Waf {
+    pub fn new(cfg: &WafConfig) -> Result<Self, String> {
+        let builtin = if cfg.default_rules { default_rules() } else { Vec::new() };
+        let mut ids = std::collections::HashSet::new();
+        let mut rules = Vec::new();
+        for rule in builtin.iter().chain(&cfg.rules) {
+            if !ids.insert(rule.id.as_str()) {
+                return Err(format!("duplicate waf rule id {}", rule.id));
+            }
+            rules.push(Rule::new(rule)?);
+        }
+        Ok(Self { enabled: cfg.enabled, rules, metrics: Metrics::detached() })
+    }
+
+    /// 规则命中计数记录到注入的实例
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 按配置顺序评估：tag 累积，遇到 block 或超限的 rate_limit 即停止
+    pub fn evaluate<'h>(&self, method: &str, target: &str, client: &str, header: impl Fn(&str) -> Option<&'h str>) -> Verdict {
+        let mut verdict = Verdict::default();
+        if !self.enabled {
+            return verdict;
+        }
+        for rule in self.rules.iter().filter(|r| r.matches(method, target, &header)) {
+            let action = match rule.action {
+                WafAction::Block => "block",
+                WafAction::RateLimit => "rate_limit",
+                WafAction::Tag => "tag",
+            };
+            self.metrics.waf_rule_hits_total.with_label_values(&[&rule.id, action]).inc();
+            match rule.action {
+                WafAction::Tag => verdict.tags.push(rule.id.clone()),
+                WafAction::Block => {
+                    verdict.rejected = Some((rule.id.clone(), rule.status));
+                    break;
+                }
+                WafAction::RateLimit => {
+                    if !rule.acquire(client) {
+                        verdict.rejected = Some((rule.id.clone(), 429));
+                        break;
+                    }
+                }
+            }
+        }
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(id: &str, action: WafAction) -> WafRuleConfig {
+        WafRuleConfig {
+            id: id.into(),
+            action,
+            methods: Vec::new(),
+            path: None,
+            headers: HashMap::new(),
+            user_agent: None,
+            max_body_bytes: None,
+            status: None,
+            requests_per_second: None,
+            burst: None,
+        }
+    }
+
+    fn headers(pairs: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<&'static str> {
+        move |name| pairs.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| *v)
+    }
+
+    #[test]
+    fn default_pack_blocks_common_attacks() {
+        let waf = Waf::new(&WafConfig { enabled: true, ..Default::default() }).unwrap();
+        let rejected = |target: &str, ua: &'static [(&'static str, &'static str)]| waf.evaluate("GET", target, "1.1.1.1", headers(ua)).rejected.map(|(id, _)| id);
+        assert_eq!(rejected("/items?id=1%20UNION%20SELECT%20password", &[]).as_deref(), Some("default.sqli"));
+        assert_eq!(rejected("/search?q=%3Cscript%3Ealert(1)", &[]).as_deref(), Some("default.xss"));
+        assert_eq!(rejected("/static/../../etc/passwd", &[]).as_deref(), Some("default.path_traversal"));
+        assert_eq!(rejected("/", &[("user-agent", "sqlmap/1.7")]).as_deref(), Some("default.scanner"));
+        assert_eq!(rejected("/v1/users?page=2", &[("user-agent", "curl/8.0")]), None);
+
+        let off = Waf::new(&WafConfig::default()).unwrap();
+        assert_eq!(off.evaluate("GET", "/etc/passwd", "", headers(&[])), Verdict::default());
+    }
+
+    #[test]
+    fn custom_rules_tag_block_and_rate_limit() {
+        let mut tag = custom("internal-client", WafAction::Tag);
+        tag.headers.insert("X-Client".into(), "^internal-".into());
+        let mut big = custom("big-upload", WafAction::Block);
+        big.methods = vec!["post".into()];
+        big.max_body_bytes = Some(1024);
+        big.status = Some(413);
+        let mut limited = custom("login", WafAction::RateLimit);
+        limited.path = Some("^/login".into());
+        limited.requests_per_second = Some(1);
+        limited.burst = Some(1);
+        let cfg = WafConfig { enabled: true, default_rules: false, rules: vec![tag, big, limited] };
+        let waf = Waf::new(&cfg).unwrap();
+
+        let v = waf.evaluate("POST", "/upload", "c1", headers(&[("x-client", "internal-batch"), ("content-length", "4096")]));
+        assert_eq!(v.tags, vec!["internal-client"]);
+        assert_eq!(v.rejected, Some(("big-upload".to_string(), 413)));
+        assert_eq!(waf.evaluate("GET", "/upload", "c1", headers(&[("content-length", "4096")])), Verdict::default());
+
+        assert!(waf.evaluate("POST", "/login", "c1", headers(&[])).rejected.is_none());
+        assert_eq!(waf.evaluate("POST", "/login", "c1", headers(&[])).rejected, Some(("login".to_string(), 429)));
+        // 按客户端隔离
+        assert!(waf.evaluate("POST", "/login", "c2", headers(&[])).rejected.is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let mut bad = custom("bad", WafAction::Block);
+        bad.path = Some("(".into());
+        let cfg = |rules| WafConfig { enabled: true, default_rules: false, rules };
+        assert!(Waf::new(&cfg(vec![bad])).unwrap_err().contains("invalid path regex"));
+        assert!(Waf::new(&cfg(vec![custom("empty", WafAction::Tag)])).unwrap_err().contains("at least one condition"));
+        let mut rl = custom("rl", WafAction::RateLimit);
+        rl.path = Some("/".into());
+        assert!(Waf::new(&cfg(vec![rl])).is_err());
+        let dup = WafConfig { enabled: true, default_rules: true, rules: default_rules() };
+        assert!(Waf::new(&dup).unwrap_err().contains("duplicate"));
+    }
+}
@@ -0,0 +1,40 @@
+//! 网关 + 内置 echo 上游的端到端测试，不依赖外部网络。
+//! 需要真实的 gateway 二进制：`cargo build --bin gateway && cargo test -p gateway --test echo_harness -- --include-ignored`
+
+use gateway::config::EchoConfig;
+use gateway::testing::{config_for, free_addr, spawn_echo, TestClient, TestGateway};
+
+#[tokio::test]
+async fn echo_upstream_reflects_request_through_client() {
+    let echo = spawn_echo().await.unwrap();
+    let client = TestClient::new(format!("http://{}", echo));
+    let resp = client.send(client.request(reqwest::Method::PUT, "/items/7?status=202").header("x-trace", "abc").body("payload")).await.unwrap();
+    let echoed = resp.assert_status(202).assert_header("content-type", "application/json").echoed();
+    assert_eq!((echoed.method.as_str(), echoed.path.as_str()), ("PUT", "/items/7"));
+    assert_eq!(echoed.headers["x-trace"], "abc");
+    assert_eq!((echoed.body.as_str(), echoed.body_len), ("payload", 7));
+}
+
+#[tokio::test]
+#[ignore = "spawns the real gateway binary"]
+async fn gateway_forwards_to_echo_upstream() {
+    let echo = spawn_echo().await.unwrap();
+    let gateway = TestGateway::start(config_for(echo)).await.unwrap();
+    let echoed = gateway.client().post("/orders/1?page=2", "hello").await.unwrap().assert_status(200).echoed();
+    assert_eq!((echoed.method.as_str(), echoed.path.as_str()), ("POST", "/orders/1"));
+    assert_eq!(echoed.query["page"], "2");
+    assert_eq!(echoed.body, "hello");
+    // 网关注入的请求 ID 到达上游
+    assert!(echoed.headers.contains_key("x-request-id"));
+}
+
+#[tokio::test]
+#[ignore = "spawns the real gateway binary"]
+async fn gateway_starts_builtin_echo_from_config() {
+    let echo_addr = free_addr().unwrap();
+    let mut cfg = config_for(echo_addr);
+    cfg.echo = EchoConfig { enabled: true, listen: echo_addr.to_string() };
+    let gateway = TestGateway::start(cfg).await.unwrap();
+    let resp = gateway.client().get("/health/check?status=204").await.unwrap();
+    resp.assert_status(204);
+}
@@ -0,0 +1,123 @@
+//! 零停机升级：持续压测的同时用新进程（`--upgrade`）接管监听 socket，期间不应出现失败请求。
+//! 需要真实的 gateway 二进制与固定端口 6188/9188：
+//! `cargo build --bin gateway && cargo test -p gateway --test upgrade -- --ignored`
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use gateway::config::{ProxyConfig, UpgradeConfig};
+use gateway::testing::gateway_bin;
+use gateway::upgrade;
+
+const GATEWAY_ADDR: &str = "127.0.0.1:6188";
+
+/// 每个连接返回固定 200 的上游
+fn spawn_upstream() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+            });
+        }
+    });
+    addr
+}
+
+fn request_ok() -> bool {
+    let Ok(mut stream) = TcpStream::connect(GATEWAY_ADDR) else { return false };
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    if stream.write_all(b"GET /upgrade-test HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").is_err() {
+        return false;
+    }
+    let mut resp = String::new();
+    stream.read_to_string(&mut resp).is_ok() && resp.starts_with("HTTP/1.1 200")
+}
+
+fn spawn_gateway(dir: &Path, upgrade: bool) -> Child {
+    let mut cmd = Command::new(gateway_bin());
+    cmd.current_dir(dir).stdout(Stdio::null()).stderr(Stdio::null());
+    if upgrade {
+        cmd.arg("--upgrade");
+    }
+    cmd.spawn().expect("spawn gateway (build it first or set GATEWAY_BIN)")
+}
+
+fn wait_until(timeout: Duration, mut f: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if f() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+#[test]
+#[ignore = "spawns the real gateway binary on fixed ports"]
+fn upgrade_under_load_drops_no_requests() {
+    let dir = std::env::temp_dir().join(format!("gateway_upgrade_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut cfg = ProxyConfig {
+        upstreams: vec![spawn_upstream()],
+        upgrade: UpgradeConfig {
+            upgrade_sock: dir.join("upgrade.sock").to_string_lossy().into_owned(),
+            pid_file: dir.join("gateway.pid").to_string_lossy().into_owned(),
+            grace_period_secs: 2,
+            graceful_shutdown_timeout_secs: 2,
+        },
+        ..Default::default()
+    };
+    cfg.rate_limit.enabled = false;
+    std::fs::write(dir.join("config.json"), serde_json::to_vec_pretty(&cfg).unwrap()).unwrap();
+
+    let mut old = spawn_gateway(&dir, false);
+    assert!(wait_until(Duration::from_secs(10), request_ok), "old gateway did not come up");
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (ok, failed) = (Arc::new(AtomicU64::new(0)), Arc::new(AtomicU64::new(0)));
+    let load: Vec<_> = (0..8)
+        .map(|_| {
+            let (stop, ok, failed) = (stop.clone(), ok.clone(), failed.clone());
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let counter = if request_ok() { &ok } else { &failed };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(500));
+    let mut new = spawn_gateway(&dir, true);
+    // 新进程先在 upgrade_sock 上等待，再通知旧进程交出 socket
+    thread::sleep(Duration::from_secs(1));
+    let old_pid = upgrade::signal_upgrade(&cfg.upgrade.pid_file).expect("signal old gateway");
+    assert_eq!(old_pid, old.id());
+    assert!(wait_until(Duration::from_secs(15), || old.try_wait().unwrap().is_some()), "old gateway did not exit");
+    thread::sleep(Duration::from_millis(500));
+
+    stop.store(true, Ordering::Relaxed);
+    for t in load {
+        t.join().unwrap();
+    }
+    let new_pid = upgrade::read_pid(&cfg.upgrade.pid_file).unwrap();
+    let _ = Command::new("kill").arg(new.id().to_string()).status();
+    let _ = new.wait();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(new_pid, new.id());
+    assert!(ok.load(Ordering::Relaxed) > 0);
+    assert_eq!(failed.load(Ordering::Relaxed), 0, "requests failed during upgrade");
+}
@@ -11,6 +11,32 @@ mod m20220101_000016_create_route;
 mod m20220101_000017_create_request_log;
 mod m20220101_000018_create_user_credentials;
 mod m20220101_000019_create_proxy_api;
+mod m20220101_000020_create_config_backup;
+mod m20220101_000021_add_ratelimit_spike_arrest;
+mod m20220101_000022_add_proxy_api_mock;
+mod m20220101_000023_add_maintenance_and_suspension;
+mod m20220101_000024_add_route_blue_green;
+mod m20220101_000025_create_config_revision;
+mod m20220101_000026_add_proxy_api_grpc_method;
+mod m20220101_000027_add_request_log_geo;
+mod m20220101_000028_create_webhook;
+mod m20220101_000029_add_apikey_expires_at;
+mod m20220101_000030_create_metrics_rollup;
+mod m20220101_000031_create_admin_stores;
+mod m20220101_000032_create_feature_flag;
+mod m20220101_000033_create_jwt_signing_key;
+mod m20220101_000034_encrypt_sensitive_columns;
+mod m20220101_000035_create_request_payload;
+mod m20220101_000036_create_route_capture;
+mod m20220101_000037_add_request_log_api_key_index;
+mod m20220101_000038_create_metrics_rollup_dimension;
+mod m20220101_000039_create_scheduled_report_run;
+mod m20220101_000040_add_ratelimit_algorithm;
+mod m20220101_000041_create_auth_session;
+mod m20220101_000042_add_auth_session_client;
+mod m20220101_000043_add_tenant_email_verification;
+mod m20220101_000044_create_invitation;
+mod m20220101_000045_rename_jwt_signing_key_secret;
 mod m20220101_000002_add_indexes;
 
 pub struct Migrator;
@@ -28,6 +54,32 @@ impl MigratorTrait for Migrator {
             Box::new(m20220101_000016_create_route::Migration),
             Box::new(m20220101_000017_create_request_log::Migration),
             Box::new(m20220101_000019_create_proxy_api::Migration),
+            Box::new(m20220101_000020_create_config_backup::Migration),
+            Box::new(m20220101_000021_add_ratelimit_spike_arrest::Migration),
+            Box::new(m20220101_000022_add_proxy_api_mock::Migration),
+            Box::new(m20220101_000023_add_maintenance_and_suspension::Migration),
+            Box::new(m20220101_000024_add_route_blue_green::Migration),
+            Box::new(m20220101_000025_create_config_revision::Migration),
+            Box::new(m20220101_000026_add_proxy_api_grpc_method::Migration),
+            Box::new(m20220101_000027_add_request_log_geo::Migration),
+            Box::new(m20220101_000028_create_webhook::Migration),
+            Box::new(m20220101_000029_add_apikey_expires_at::Migration),
+            Box::new(m20220101_000030_create_metrics_rollup::Migration),
+            Box::new(m20220101_000031_create_admin_stores::Migration),
+            Box::new(m20220101_000032_create_feature_flag::Migration),
+            Box::new(m20220101_000033_create_jwt_signing_key::Migration),
+            Box::new(m20220101_000034_encrypt_sensitive_columns::Migration),
+            Box::new(m20220101_000035_create_request_payload::Migration),
+            Box::new(m20220101_000036_create_route_capture::Migration),
+            Box::new(m20220101_000037_add_request_log_api_key_index::Migration),
+            Box::new(m20220101_000038_create_metrics_rollup_dimension::Migration),
+            Box::new(m20220101_000039_create_scheduled_report_run::Migration),
+            Box::new(m20220101_000040_add_ratelimit_algorithm::Migration),
+            Box::new(m20220101_000041_create_auth_session::Migration),
+            Box::new(m20220101_000042_add_auth_session_client::Migration),
+            Box::new(m20220101_000043_add_tenant_email_verification::Migration),
+            Box::new(m20220101_000044_create_invitation::Migration),
+            Box::new(m20220101_000045_rename_jwt_signing_key_secret::Migration),
             // Indexes should always be applied last
             Box::new(m20220101_000002_add_indexes::Migration),
         ]
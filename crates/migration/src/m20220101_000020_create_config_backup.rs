@@ -0,0 +1,50 @@
+//! Create `config_backup` table.
+//! Stores point-in-time JSON snapshots of the gateway configuration for restore.
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConfigBackup::Table)
+                    .if_not_exists()
+                    .col(uuid(ConfigBackup::Id).primary_key())
+                    .col(string_len(ConfigBackup::Reason, 32).not_null())
+                    .col(text(ConfigBackup::Snapshot).not_null())
+                    .col(integer(ConfigBackup::ItemCount).not_null())
+                    .col(timestamp_with_time_zone(ConfigBackup::CreatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Retention pruning and listing are ordered by creation time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_config_backup_created_at")
+                    .table(ConfigBackup::Table)
+                    .col(ConfigBackup::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ConfigBackup::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ConfigBackup {
+    Table,
+    Id,
+    Reason,
+    Snapshot,
+    ItemCount,
+    CreatedAt,
+}
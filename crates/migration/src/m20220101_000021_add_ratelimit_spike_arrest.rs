@@ -0,0 +1,37 @@
+//! Add `rate_limit.spike_arrest_interval_ms`.
+//! When set, allowed requests per key are spaced at least this many milliseconds apart.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RateLimit::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RateLimit::SpikeArrestIntervalMs).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RateLimit::Table)
+                    .drop_column(RateLimit::SpikeArrestIntervalMs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RateLimit {
+    Table,
+    SpikeArrestIntervalMs,
+}
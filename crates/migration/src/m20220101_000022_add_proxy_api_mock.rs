@@ -0,0 +1,47 @@
+//! Add mock-mode columns to `proxy_api`.
+//! A proxy API in mock mode answers with the configured status/headers/body instead of forwarding.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProxyApi::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MockEnabled).boolean().not_null().default(false))
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MockStatus).integer().null())
+                    // JSON object: header name -> value template
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MockHeaders).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MockBody).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProxyApi::Table)
+                    .drop_column(ProxyApi::MockEnabled)
+                    .drop_column(ProxyApi::MockStatus)
+                    .drop_column(ProxyApi::MockHeaders)
+                    .drop_column(ProxyApi::MockBody)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProxyApi {
+    Table,
+    MockEnabled,
+    MockStatus,
+    MockHeaders,
+    MockBody,
+}
@@ -0,0 +1,69 @@
+//! Maintenance windows for `proxy_api` and a kill switch for `tenant`.
+//! - proxy_api: disabled or inside [maintenance_start, maintenance_end) -> 503 with maintenance_message
+//! - tenant: suspended -> all of the tenant's traffic is blocked
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProxyApi::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MaintenanceStart).timestamp_with_time_zone().null())
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MaintenanceEnd).timestamp_with_time_zone().null())
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::MaintenanceMessage).text().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Tenant::Suspended).boolean().not_null().default(false))
+                    .add_column_if_not_exists(ColumnDef::new(Tenant::SuspendedReason).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .drop_column(Tenant::Suspended)
+                    .drop_column(Tenant::SuspendedReason)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProxyApi::Table)
+                    .drop_column(ProxyApi::MaintenanceStart)
+                    .drop_column(ProxyApi::MaintenanceEnd)
+                    .drop_column(ProxyApi::MaintenanceMessage)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProxyApi {
+    Table,
+    MaintenanceStart,
+    MaintenanceEnd,
+    MaintenanceMessage,
+}
+
+#[derive(DeriveIden)]
+enum Tenant {
+    Table,
+    Suspended,
+    SuspendedReason,
+}
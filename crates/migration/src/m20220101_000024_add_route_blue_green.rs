@@ -0,0 +1,48 @@
+//! Blue/green slots on `route`.
+//! - blue_upstream_id / green_upstream_id: the two deployment targets
+//! - active_color: which slot `upstream_id` currently points at
+//! - previous_upstream_id: target before the last cutover (instant rollback)
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Route::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Route::BlueUpstreamId).uuid().null())
+                    .add_column_if_not_exists(ColumnDef::new(Route::GreenUpstreamId).uuid().null())
+                    .add_column_if_not_exists(ColumnDef::new(Route::ActiveColor).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(Route::PreviousUpstreamId).uuid().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Route::Table)
+                    .drop_column(Route::BlueUpstreamId)
+                    .drop_column(Route::GreenUpstreamId)
+                    .drop_column(Route::ActiveColor)
+                    .drop_column(Route::PreviousUpstreamId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Route {
+    Table,
+    BlueUpstreamId,
+    GreenUpstreamId,
+    ActiveColor,
+    PreviousUpstreamId,
+}
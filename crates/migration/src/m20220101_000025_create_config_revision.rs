@@ -0,0 +1,69 @@
+//! Create `config_revision` table and stamp revisions on `request_log`.
+//! - config_revision: one row per control-plane change event (incident timeline)
+//! - request_log.config_revision: revision active when the request was handled
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConfigRevision::Table)
+                    .if_not_exists()
+                    .col(big_integer(ConfigRevision::Id).primary_key())
+                    .col(string_len(ConfigRevision::Kind, 32).not_null())
+                    .col(uuid_null(ConfigRevision::EntityId))
+                    .col(text_null(ConfigRevision::Detail))
+                    .col(timestamp_with_time_zone(ConfigRevision::CreatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Timeline queries are ordered by creation time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_config_revision_created_at")
+                    .table(ConfigRevision::Table)
+                    .col(ConfigRevision::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RequestLog::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RequestLog::ConfigRevision).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(RequestLog::Table).drop_column(RequestLog::ConfigRevision).to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(ConfigRevision::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ConfigRevision {
+    Table,
+    Id,
+    Kind,
+    EntityId,
+    Detail,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RequestLog {
+    Table,
+    ConfigRevision,
+}
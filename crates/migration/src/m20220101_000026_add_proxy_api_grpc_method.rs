@@ -0,0 +1,32 @@
+//! Add `grpc_method` to `proxy_api`.
+//! When set (`package.Service/Method`), JSON requests are transcoded to gRPC for the upstream.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProxyApi::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ProxyApi::GrpcMethod).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(ProxyApi::Table).drop_column(ProxyApi::GrpcMethod).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProxyApi {
+    Table,
+    GrpcMethod,
+}
@@ -0,0 +1,41 @@
+//! Add GeoIP enrichment columns to `request_log`.
+//! - country: ISO 3166-1 alpha-2 code of the client IP
+//! - asn: autonomous system number of the client IP
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RequestLog::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RequestLog::Country).string_len(8).null())
+                    .add_column_if_not_exists(ColumnDef::new(RequestLog::Asn).big_integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RequestLog::Table)
+                    .drop_column(RequestLog::Country)
+                    .drop_column(RequestLog::Asn)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestLog {
+    Table,
+    Country,
+    Asn,
+}
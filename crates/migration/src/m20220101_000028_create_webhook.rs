@@ -0,0 +1,124 @@
+//! Create `webhook` and `webhook_delivery` tables.
+//! - webhook: tenant subscriptions (target URL, signing secret, subscribed events)
+//! - webhook_delivery: one row per event per subscription; the delivery worker retries pending rows
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Webhook::Table)
+                    .if_not_exists()
+                    .col(uuid(Webhook::Id).primary_key())
+                    .col(uuid(Webhook::TenantId).not_null())
+                    .col(text(Webhook::Url).not_null())
+                    .col(string_len(Webhook::Secret, 128).not_null())
+                    .col(text(Webhook::Events).not_null())
+                    .col(boolean(Webhook::Enabled).not_null().default(true))
+                    .col(timestamp_with_time_zone(Webhook::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(Webhook::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_tenant")
+                            .from(Webhook::Table, Webhook::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(uuid(WebhookDelivery::Id).primary_key())
+                    .col(uuid(WebhookDelivery::WebhookId).not_null())
+                    .col(string_len(WebhookDelivery::Event, 64).not_null())
+                    .col(text(WebhookDelivery::Payload).not_null())
+                    .col(string_len(WebhookDelivery::Status, 16).not_null())
+                    .col(integer(WebhookDelivery::Attempts).not_null().default(0))
+                    .col(timestamp_with_time_zone(WebhookDelivery::NextAttemptAt).not_null())
+                    .col(integer_null(WebhookDelivery::ResponseStatus))
+                    .col(text_null(WebhookDelivery::LastError))
+                    .col(timestamp_with_time_zone(WebhookDelivery::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone_null(WebhookDelivery::DeliveredAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_delivery_webhook")
+                            .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                            .to(Webhook::Table, Webhook::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The worker polls pending rows by due time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_status_next_attempt")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_webhook_id")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::WebhookId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(WebhookDelivery::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Webhook::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhook {
+    Table,
+    Id,
+    TenantId,
+    Url,
+    Secret,
+    Events,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    WebhookId,
+    Event,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    ResponseStatus,
+    LastError,
+    CreatedAt,
+    DeliveredAt,
+}
+
+#[derive(DeriveIden)]
+enum Tenant {
+    Table,
+    Id,
+}
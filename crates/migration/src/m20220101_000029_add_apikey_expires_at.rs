@@ -0,0 +1,42 @@
+//! Optional expiry for `api_key`.
+//! - api_key.expires_at: keys nearing this time are reported by the expiry scheduler (GET /admin/expiring)
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ApiKey::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ApiKey::ExpiresAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_apikey_expires_at")
+                    .table(ApiKey::Table)
+                    .col(ApiKey::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("idx_apikey_expires_at").table(ApiKey::Table).to_owned()).await?;
+        manager
+            .alter_table(Table::alter().table(ApiKey::Table).drop_column(ApiKey::ExpiresAt).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiKey {
+    Table,
+    ExpiresAt,
+}
@@ -0,0 +1,73 @@
+//! Create `metrics_rollup` table.
+//! Per-minute request aggregates per route (tenant denormalized) written by the background
+//! aggregator; GET /admin/metrics/query reads it to draw charts without Prometheus.
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricsRollup::Table)
+                    .if_not_exists()
+                    .col(timestamp_with_time_zone(MetricsRollup::Bucket).not_null())
+                    .col(uuid(MetricsRollup::RouteId).not_null())
+                    .col(uuid(MetricsRollup::TenantId).not_null())
+                    .col(big_integer(MetricsRollup::Requests).not_null())
+                    .col(big_integer(MetricsRollup::Errors).not_null())
+                    .col(big_integer(MetricsRollup::LatencySumMs).not_null())
+                    .col(integer(MetricsRollup::LatencyMaxMs).not_null())
+                    .col(double(MetricsRollup::LatencyP50Ms).not_null())
+                    .col(double(MetricsRollup::LatencyP95Ms).not_null())
+                    .col(double(MetricsRollup::LatencyP99Ms).not_null())
+                    .primary_key(Index::create().col(MetricsRollup::Bucket).col(MetricsRollup::RouteId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tenant charts and retention scan by time
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_metrics_rollup_tenant_bucket")
+                    .table(MetricsRollup::Table)
+                    .col(MetricsRollup::TenantId)
+                    .col(MetricsRollup::Bucket)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_metrics_rollup_route_bucket")
+                    .table(MetricsRollup::Table)
+                    .col(MetricsRollup::RouteId)
+                    .col(MetricsRollup::Bucket)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MetricsRollup::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MetricsRollup {
+    Table,
+    Bucket,
+    RouteId,
+    TenantId,
+    Requests,
+    Errors,
+    LatencySumMs,
+    LatencyMaxMs,
+    LatencyP50Ms,
+    LatencyP95Ms,
+    LatencyP99Ms,
+}
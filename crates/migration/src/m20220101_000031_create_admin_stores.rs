@@ -0,0 +1,75 @@
+//! Create `admin_api_key` and `managed_api` tables.
+//! Database backend for the admin key store and /admin/apis (`[storage] backend = "database"`),
+//! shared by every server replica instead of per-process JSON files.
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminApiKey::Table)
+                    .if_not_exists()
+                    .col(string_len(AdminApiKey::UserName, 255).primary_key())
+                    .col(string_len(AdminApiKey::ApiKey, 255).not_null())
+                    .col(timestamp_with_time_zone(AdminApiKey::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(AdminApiKey::UpdatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+        // The admin middleware looks keys up by value
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_admin_api_key_api_key")
+                    .table(AdminApiKey::Table)
+                    .col(AdminApiKey::ApiKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ManagedApi::Table)
+                    .if_not_exists()
+                    .col(uuid(ManagedApi::Id).primary_key())
+                    .col(string_len(ManagedApi::EndpointUrl, 1024).not_null())
+                    .col(string_len(ManagedApi::Method, 16).not_null())
+                    .col(text(ManagedApi::ForwardTarget).not_null())
+                    .col(boolean(ManagedApi::RequireApiKey).not_null().default(false))
+                    .col(timestamp_with_time_zone(ManagedApi::CreatedAt).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ManagedApi::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(AdminApiKey::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AdminApiKey {
+    Table,
+    UserName,
+    ApiKey,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ManagedApi {
+    Table,
+    Id,
+    EndpointUrl,
+    Method,
+    ForwardTarget,
+    RequireApiKey,
+    CreatedAt,
+}
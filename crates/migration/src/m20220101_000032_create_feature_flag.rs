@@ -0,0 +1,102 @@
+//! Create `feature_flag` and `feature_flag_override` tables.
+//! - feature_flag: named switch with a default and a percentage rollout over tenants
+//! - feature_flag_override: per-tenant forced on/off, takes precedence over the rollout
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlag::Table)
+                    .if_not_exists()
+                    .col(uuid(FeatureFlag::Id).primary_key())
+                    .col(string_len(FeatureFlag::Name, 128).unique_key().not_null())
+                    .col(text_null(FeatureFlag::Description))
+                    .col(boolean(FeatureFlag::DefaultEnabled).not_null().default(false))
+                    .col(small_integer(FeatureFlag::RolloutPercent).not_null().default(0))
+                    .col(timestamp_with_time_zone(FeatureFlag::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(FeatureFlag::UpdatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlagOverride::Table)
+                    .if_not_exists()
+                    .col(uuid(FeatureFlagOverride::Id).primary_key())
+                    .col(uuid(FeatureFlagOverride::FlagId).not_null())
+                    .col(uuid(FeatureFlagOverride::TenantId).not_null())
+                    .col(boolean(FeatureFlagOverride::Enabled).not_null())
+                    .col(timestamp_with_time_zone(FeatureFlagOverride::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_feature_flag_override_flag")
+                            .from(FeatureFlagOverride::Table, FeatureFlagOverride::FlagId)
+                            .to(FeatureFlag::Table, FeatureFlag::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_feature_flag_override_tenant")
+                            .from(FeatureFlagOverride::Table, FeatureFlagOverride::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One override per (flag, tenant)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_feature_flag_override_unique")
+                    .table(FeatureFlagOverride::Table)
+                    .col(FeatureFlagOverride::FlagId)
+                    .col(FeatureFlagOverride::TenantId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(FeatureFlagOverride::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(FeatureFlag::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlag {
+    Table,
+    Id,
+    Name,
+    Description,
+    DefaultEnabled,
+    RolloutPercent,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlagOverride {
+    Table,
+    Id,
+    FlagId,
+    TenantId,
+    Enabled,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tenant {
+    Table,
+    Id,
+}
@@ -0,0 +1,46 @@
+//! Create `jwt_signing_key`: login token signing keys addressed by `kid`.
+//! - exactly one row is `active` (signs new tokens); superseded keys keep verifying until `verify_until`
+//! - key material is stored encrypted (`secret_ciphertext`); EdDSA keys also keep their public half
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JwtSigningKey::Table)
+                    .if_not_exists()
+                    .col(uuid(JwtSigningKey::Id).primary_key())
+                    .col(string_len(JwtSigningKey::Kid, 64).unique_key().not_null())
+                    .col(string_len(JwtSigningKey::Algorithm, 16).not_null())
+                    .col(text(JwtSigningKey::SecretCiphertext).not_null())
+                    .col(text_null(JwtSigningKey::PublicKey))
+                    .col(boolean(JwtSigningKey::Active).not_null().default(false))
+                    .col(timestamp_with_time_zone(JwtSigningKey::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone_null(JwtSigningKey::VerifyUntil))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(JwtSigningKey::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JwtSigningKey {
+    Table,
+    Id,
+    Kid,
+    Algorithm,
+    SecretCiphertext,
+    PublicKey,
+    Active,
+    CreatedAt,
+    VerifyUntil,
+}
@@ -0,0 +1,27 @@
+//! Widen `webhook.secret` to text: encrypted values (`v1:{key_id}:{base64}`) are longer than the plaintext.
+//! Existing plaintext secrets stay readable and are rewritten by the re-encryption job.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Webhook::Table).modify_column(ColumnDef::new(Webhook::Secret).text().not_null()).to_owned())
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Webhook::Table).modify_column(ColumnDef::new(Webhook::Secret).string_len(128).not_null()).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhook {
+    Table,
+    Secret,
+}
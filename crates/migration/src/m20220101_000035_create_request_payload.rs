@@ -0,0 +1,65 @@
+//! Create `request_payload`: captured request/response of a `request_log` row (one-to-one, cascade delete).
+//! - headers are JSON objects, bodies raw bytes; both are redacted and size-capped before insert
+//! - `*_truncated` marks bodies cut at the capture limit; truncated requests cannot be replayed
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RequestPayload::Table)
+                    .if_not_exists()
+                    .col(big_integer(RequestPayload::RequestLogId).primary_key())
+                    .col(string_len(RequestPayload::Method, 16).not_null())
+                    .col(text(RequestPayload::Uri).not_null())
+                    .col(text(RequestPayload::RequestHeaders).not_null())
+                    .col(blob_null(RequestPayload::RequestBody))
+                    .col(boolean(RequestPayload::RequestTruncated).not_null().default(false))
+                    .col(integer_null(RequestPayload::ResponseStatus))
+                    .col(text_null(RequestPayload::ResponseHeaders))
+                    .col(blob_null(RequestPayload::ResponseBody))
+                    .col(boolean(RequestPayload::ResponseTruncated).not_null().default(false))
+                    .col(timestamp_with_time_zone(RequestPayload::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_request_payload_request_log")
+                            .from(RequestPayload::Table, RequestPayload::RequestLogId)
+                            .to(RequestLog::Table, RequestLog::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RequestPayload::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestPayload {
+    Table,
+    RequestLogId,
+    Method,
+    Uri,
+    RequestHeaders,
+    RequestBody,
+    RequestTruncated,
+    ResponseStatus,
+    ResponseHeaders,
+    ResponseBody,
+    ResponseTruncated,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RequestLog {
+    Table,
+    Id,
+}
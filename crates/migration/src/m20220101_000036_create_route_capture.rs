@@ -0,0 +1,53 @@
+//! Create `route_capture`: per-route request/response body capture settings (see `request_payload`).
+//! - a missing row means capture is off for the route
+//! - `sample_percent` 0-100 of the route's logged requests are captured, bodies cut at `max_bytes`
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RouteCapture::Table)
+                    .if_not_exists()
+                    .col(uuid(RouteCapture::RouteId).primary_key())
+                    .col(small_integer(RouteCapture::SamplePercent).not_null())
+                    .col(integer(RouteCapture::MaxBytes).not_null())
+                    .col(boolean(RouteCapture::CaptureResponse).not_null().default(true))
+                    .col(timestamp_with_time_zone(RouteCapture::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_route_capture_route")
+                            .from(RouteCapture::Table, RouteCapture::RouteId)
+                            .to(Route::Table, Route::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(RouteCapture::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RouteCapture {
+    Table,
+    RouteId,
+    SamplePercent,
+    MaxBytes,
+    CaptureResponse,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Route {
+    Table,
+    Id,
+}
@@ -0,0 +1,33 @@
+//! Index `request_log (api_key_id, timestamp)` for per-key usage queries.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_log_api_key_timestamp")
+                    .table(RequestLog::Table)
+                    .col(RequestLog::ApiKeyId)
+                    .col(RequestLog::Timestamp)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("idx_log_api_key_timestamp").table(RequestLog::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RequestLog {
+    Table,
+    ApiKeyId,
+    Timestamp,
+}
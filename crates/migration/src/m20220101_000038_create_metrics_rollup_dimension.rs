@@ -0,0 +1,62 @@
+//! Create `metrics_rollup_dimension` table.
+//! Per-minute request aggregates keyed by API key or client IP, written next to `metrics_rollup`
+//! by the same aggregator; GET /admin/reports/top ranks these dimensions from it.
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricsRollupDimension::Table)
+                    .if_not_exists()
+                    .col(timestamp_with_time_zone(MetricsRollupDimension::Bucket).not_null())
+                    .col(string_len(MetricsRollupDimension::Dimension, 16).not_null())
+                    .col(string_len(MetricsRollupDimension::Key, 64).not_null())
+                    .col(big_integer(MetricsRollupDimension::Requests).not_null())
+                    .col(big_integer(MetricsRollupDimension::Errors).not_null())
+                    .col(integer(MetricsRollupDimension::LatencyMaxMs).not_null())
+                    .col(double(MetricsRollupDimension::LatencyP99Ms).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(MetricsRollupDimension::Bucket)
+                            .col(MetricsRollupDimension::Dimension)
+                            .col(MetricsRollupDimension::Key),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Reports scan one dimension over a time range
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_metrics_rollup_dimension_bucket")
+                    .table(MetricsRollupDimension::Table)
+                    .col(MetricsRollupDimension::Dimension)
+                    .col(MetricsRollupDimension::Bucket)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MetricsRollupDimension::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MetricsRollupDimension {
+    Table,
+    Bucket,
+    Dimension,
+    Key,
+    Requests,
+    Errors,
+    LatencyMaxMs,
+    LatencyP99Ms,
+}
@@ -0,0 +1,57 @@
+//! Create `scheduled_report_run` table.
+//! One row per tenant and report period already generated, so scheduled daily/weekly reports are
+//! sent once even across restarts and replicas.
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScheduledReportRun::Table)
+                    .if_not_exists()
+                    .col(uuid(ScheduledReportRun::TenantId).not_null())
+                    .col(string_len(ScheduledReportRun::Period, 16).not_null())
+                    .col(timestamp_with_time_zone(ScheduledReportRun::PeriodStart).not_null())
+                    .col(timestamp_with_time_zone(ScheduledReportRun::CreatedAt).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ScheduledReportRun::TenantId)
+                            .col(ScheduledReportRun::Period)
+                            .col(ScheduledReportRun::PeriodStart),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_scheduled_report_run_tenant")
+                            .from(ScheduledReportRun::Table, ScheduledReportRun::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ScheduledReportRun::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ScheduledReportRun {
+    Table,
+    TenantId,
+    Period,
+    PeriodStart,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tenant {
+    Table,
+    Id,
+}
@@ -0,0 +1,37 @@
+//! Add `rate_limit.algorithm`: token_bucket (default) | sliding_window | fixed_window.
+//! Window algorithms enforce `requests_per_minute` strictly per minute and ignore `burst`.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RateLimit::Table)
+                    .add_column_if_not_exists(ColumnDef::new(RateLimit::Algorithm).string().not_null().default("token_bucket"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RateLimit::Table)
+                    .drop_column(RateLimit::Algorithm)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RateLimit {
+    Table,
+    Algorithm,
+}
@@ -0,0 +1,63 @@
+//! Create `auth_session`: one row per login token issued by `/auth/login`.
+//! - tokens carry the row id as `sid`; a revoked (`revoked_at`) or expired session rejects its token before `exp`
+//! - counted per user to enforce `[auth] max_sessions_per_user`
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthSession::Table)
+                    .if_not_exists()
+                    .col(uuid(AuthSession::Id).primary_key())
+                    .col(uuid(AuthSession::UserId).not_null())
+                    .col(uuid(AuthSession::TenantId).not_null())
+                    .col(timestamp_with_time_zone(AuthSession::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(AuthSession::ExpiresAt).not_null())
+                    .col(timestamp_with_time_zone_null(AuthSession::RevokedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_auth_session_user")
+                            .from(AuthSession::Table, AuthSession::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_auth_session_user")
+                    .table(AuthSession::Table)
+                    .col(AuthSession::UserId)
+                    .col(AuthSession::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuthSession::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuthSession {
+    Table,
+    Id,
+    UserId,
+    TenantId,
+    CreatedAt,
+    ExpiresAt,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum User { Table, Id }
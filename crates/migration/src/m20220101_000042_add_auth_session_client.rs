@@ -0,0 +1,43 @@
+//! Add client metadata to `auth_session` for security review: `user_agent` and `ip_address` recorded at
+//! login, `last_seen_at` refreshed (at most once a minute) by requests authenticated with the session.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuthSession::Table)
+                    .add_column_if_not_exists(ColumnDef::new(AuthSession::UserAgent).string().null())
+                    .add_column_if_not_exists(ColumnDef::new(AuthSession::IpAddress).string().null())
+                    .add_column_if_not_exists(ColumnDef::new(AuthSession::LastSeenAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuthSession::Table)
+                    .drop_column(AuthSession::UserAgent)
+                    .drop_column(AuthSession::IpAddress)
+                    .drop_column(AuthSession::LastSeenAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuthSession {
+    Table,
+    UserAgent,
+    IpAddress,
+    LastSeenAt,
+}
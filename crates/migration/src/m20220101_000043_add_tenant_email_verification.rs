@@ -0,0 +1,38 @@
+//! Add `tenant.email_verification`: off (default) | optional | required.
+//! With optional/required, registration creates `unverified` users and sends a verification token;
+//! required also blocks login until the email is verified.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Tenant::EmailVerification).string().not_null().default("off"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .drop_column(Tenant::EmailVerification)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Tenant {
+    Table,
+    EmailVerification,
+}
@@ -0,0 +1,91 @@
+//! Invitation-based onboarding.
+//! - `invitation`: one row per invite; only the SHA-256 of the token is stored, the token is shown once
+//! - `user.role`: member (default) | admin; invited users take the invitation's role
+//! - `tenant.invite_only`: when set, `/auth/register` is closed and users join through invitations only
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Invitation::Table)
+                    .if_not_exists()
+                    .col(uuid(Invitation::Id).primary_key())
+                    .col(uuid(Invitation::TenantId).not_null())
+                    .col(string_len(Invitation::Email, 255).not_null())
+                    .col(string_len(Invitation::Role, 32).not_null())
+                    .col(string_len(Invitation::TokenHash, 64).unique_key().not_null())
+                    .col(timestamp_with_time_zone(Invitation::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(Invitation::ExpiresAt).not_null())
+                    .col(timestamp_with_time_zone_null(Invitation::AcceptedAt))
+                    .col(uuid_null(Invitation::AcceptedUserId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_invitation_tenant")
+                            .from(Invitation::Table, Invitation::TenantId)
+                            .to(Tenant::Table, Tenant::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_invitation_tenant")
+                    .table(Invitation::Table)
+                    .col(Invitation::TenantId)
+                    .col(Invitation::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(ColumnDef::new(User::Role).string().not_null().default("member"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Tenant::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Tenant::InviteOnly).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(Table::alter().table(Tenant::Table).drop_column(Tenant::InviteOnly).to_owned()).await?;
+        manager.alter_table(Table::alter().table(User::Table).drop_column(User::Role).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Invitation::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Invitation {
+    Table,
+    Id,
+    TenantId,
+    Email,
+    Role,
+    TokenHash,
+    CreatedAt,
+    ExpiresAt,
+    AcceptedAt,
+    AcceptedUserId,
+}
+
+#[derive(DeriveIden)]
+enum Tenant { Table, Id, InviteOnly }
+
+#[derive(DeriveIden)]
+enum User { Table, Role }
@@ -0,0 +1,33 @@
+//! Rename `jwt_signing_key.secret_ciphertext` to `secret`: the column now holds an encrypted column value
+//! (`v1:{key_id}:...`, see `models::encrypted`). Rows written before keep the legacy `v1:{base64}` format and are
+//! read with the legacy key until the re-encryption job rewrites them.
+//! Databases already created with the column named `secret` are left as they are.
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !manager.has_column("jwt_signing_key", "secret_ciphertext").await? {
+            return Ok(());
+        }
+        manager
+            .alter_table(Table::alter().table(JwtSigningKey::Table).rename_column(JwtSigningKey::SecretCiphertext, JwtSigningKey::Secret).to_owned())
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(JwtSigningKey::Table).rename_column(JwtSigningKey::Secret, JwtSigningKey::SecretCiphertext).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JwtSigningKey {
+    Table,
+    SecretCiphertext,
+    Secret,
+}
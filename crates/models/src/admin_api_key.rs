@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Admin API key (`user -> api_key`), database backend of the admin key store.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "admin_api_key")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_name: String,
+    #[serde(skip_serializing)]
+    pub api_key: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
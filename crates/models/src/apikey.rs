@@ -16,6 +16,9 @@ pub struct Model {
     pub status: String,
     pub created_at: DateTimeWithTimeZone,
     pub last_used_at: Option<DateTimeWithTimeZone>,
+    /// None = never expires
+    #[serde(default)]
+    pub expires_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -46,6 +49,7 @@ pub async fn create(db: &DatabaseConnection, user_id: Uuid, key_hash: &str) -> R
         status: Set("active".into()),
         created_at: Set(Utc::now().into()),
         last_used_at: Set(None),
+        expires_at: Set(None),
     };
     am.insert(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))
 }
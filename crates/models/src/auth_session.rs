@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::user;
+
+/// A login token issued to a user; the token carries `id` as its `sid` claim.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "auth_session")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub created_at: DateTimeWithTimeZone,
+    pub expires_at: DateTimeWithTimeZone,
+    /// Set by logout, `DELETE /auth/sessions/{id}` or when evicted by the per-user session limit
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+    /// Client that logged in; kept for security review
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Last request authenticated with the session, refreshed at most once a minute
+    pub last_seen_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { User }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::User => Entity::belongs_to(user::Entity).from(Column::UserId).to(user::Column::Id).into() }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
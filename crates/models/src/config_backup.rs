@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+/// Full configuration snapshot; `snapshot` holds the serialized JSON document.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "config_backup")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// scheduled / pre_apply / pre_restore / manual
+    pub reason: String,
+    #[sea_orm(column_type = "Text")]
+    pub snapshot: String,
+    pub item_count: i32,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
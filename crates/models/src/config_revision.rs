@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+/// One control-plane change; `id` is the revision number stamped into data-plane logs.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "config_revision")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: i64,
+    /// proxy_api / upstream / rate_limit / tenant / route_cutover / cache_flush
+    pub kind: String,
+    pub entity_id: Option<Uuid>,
+    /// JSON with event specific fields (e.g. cutover from/to)
+    #[sea_orm(column_type = "Text", nullable)]
+    pub detail: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
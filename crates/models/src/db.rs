@@ -129,8 +129,8 @@ pub async fn connect() -> Result<DatabaseConnection> {
     connect_with_config(&DATABASE_CONFIG).await
 }
 
-/// Connect to database with custom configuration
-pub async fn connect_with_config(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+/// Validated pool options for `config`
+fn connect_options(config: &DatabaseConfig) -> Result<ConnectOptions> {
     // 校验 URL 是否已通过环境变量提供
     if config.url.trim().is_empty() {
         return Err(anyhow::anyhow!(
@@ -147,6 +147,17 @@ pub async fn connect_with_config(config: &DatabaseConfig) -> Result<DatabaseConn
         .max_lifetime(config.max_lifetime)
         .acquire_timeout(config.acquire_timeout)
         .sqlx_logging(config.sqlx_logging);
+    Ok(opt)
+}
+
+/// Single connection attempt; callers apply their own retry/backoff policy
+pub async fn connect_once(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+    Ok(Database::connect(connect_options(config)?).await?)
+}
+
+/// Connect to database with custom configuration
+pub async fn connect_with_config(config: &DatabaseConfig) -> Result<DatabaseConnection> {
+    let opt = connect_options(config)?;
     
     // Retry mechanism
     let max_retries = 3;
@@ -0,0 +1,147 @@
+//! 加密列类型：构造时用 `common::crypto::installed()` 的当前主密钥加密，读库时透明解密。
+//! 加密上线前写入的明文仍可读取，由重加密任务改写为密文；未安装主密钥时拒绝构造新值，不会写入明文。
+
+use std::fmt;
+use std::ops::Deref;
+
+use common::crypto::CryptoError;
+use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr};
+use sea_orm::{ColIdx, DbErr, QueryResult, TryGetError, TryGetable, Value};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 明文与其库中形式；加密在构造时完成，写库（`Into<Value>`）不会失败
+#[derive(Clone)]
+pub struct EncryptedString {
+    plain: String,
+    stored: String,
+}
+
+impl EncryptedString {
+    /// 用当前主密钥加密；未安装主密钥时返回 [`CryptoError::NoMasterKey`]
+    pub fn new(plaintext: impl Into<String>) -> Result<Self, CryptoError> {
+        let plain = plaintext.into();
+        let keys = common::crypto::installed().ok_or(CryptoError::NoMasterKey)?;
+        let stored = keys.seal(plain.as_bytes())?;
+        Ok(Self { plain, stored })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.plain
+    }
+
+    pub fn into_inner(self) -> String {
+        self.plain
+    }
+
+    /// 库中的值（密文，或加密上线前的明文）
+    pub fn stored(&self) -> &str {
+        &self.stored
+    }
+
+    /// 数据库中的值 -> 明文
+    pub fn from_stored(stored: String) -> Result<Self, CryptoError> {
+        if !common::crypto::is_sealed(&stored) {
+            return Ok(Self { plain: stored.clone(), stored });
+        }
+        let keys = common::crypto::installed().ok_or(CryptoError::NoMasterKey)?;
+        let plain = String::from_utf8(keys.open(&stored)?).map_err(|_| CryptoError::Malformed)?;
+        Ok(Self { plain, stored })
+    }
+}
+
+impl Deref for EncryptedString {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.plain
+    }
+}
+
+/// 按明文比较（同一明文每次加密的密文不同）
+impl PartialEq for EncryptedString {
+    fn eq(&self, other: &Self) -> bool {
+        self.plain == other.plain
+    }
+}
+
+impl Eq for EncryptedString {}
+
+/// 不在日志中输出明文
+impl fmt::Debug for EncryptedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptedString(***)")
+    }
+}
+
+impl Serialize for EncryptedString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.plain)
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptedString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<EncryptedString> for Value {
+    fn from(v: EncryptedString) -> Self {
+        Value::String(Some(Box::new(v.stored)))
+    }
+}
+
+impl TryGetable for EncryptedString {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let stored = <String as TryGetable>::try_get_by(res, index)?;
+        Self::from_stored(stored).map_err(|e| TryGetError::DbErr(DbErr::Type(format!("encrypted column: {}", e))))
+    }
+}
+
+impl ValueType for EncryptedString {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::String(Some(s)) => Self::from_stored(*s).map_err(|_| ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "EncryptedString".to_owned()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::Text
+    }
+}
+
+impl Nullable for EncryptedString {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plaintext_rows_stay_readable() {
+        // 加密上线前的明文按原样读取，写回时仍是原值
+        let legacy = EncryptedString::from_stored("legacy-secret".into()).unwrap();
+        assert_eq!((legacy.as_str(), legacy.stored()), ("legacy-secret", "legacy-secret"));
+        assert_eq!(format!("{:?}", legacy), "EncryptedString(***)");
+    }
+
+    #[test]
+    fn new_values_are_sealed_before_conversion() {
+        crate::testing::install_test_master_key();
+        let v = EncryptedString::new("s3cr3t").unwrap();
+        assert!(common::crypto::is_sealed(v.stored()) && !v.stored().contains("s3cr3t"));
+        let Value::String(Some(stored)) = Value::from(v.clone()) else { panic!("string value") };
+        assert_eq!(EncryptedString::from_stored(*stored).unwrap(), v);
+    }
+}
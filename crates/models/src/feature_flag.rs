@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::feature_flag_override;
+
+/// Named feature switch: per-tenant overrides first, then `rollout_percent` of tenants, then `default_enabled`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "feature_flag")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub name: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    pub default_enabled: bool,
+    /// 0-100
+    pub rollout_percent: i16,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Override }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::Override => Entity::has_many(feature_flag_override::Entity).into() }
+    }
+}
+
+impl Related<feature_flag_override::Entity> for Entity {
+    fn to() -> RelationDef { Relation::Override.def() }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
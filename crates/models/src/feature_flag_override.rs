@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::{feature_flag, tenant};
+
+/// Forces a flag on or off for one tenant regardless of the rollout.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "feature_flag_override")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Flag, Tenant }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Relation::Flag => Entity::belongs_to(feature_flag::Entity).from(Column::FlagId).to(feature_flag::Column::Id).into(),
+            Relation::Tenant => Entity::belongs_to(tenant::Entity).from(Column::TenantId).to(tenant::Column::Id).into(),
+        }
+    }
+}
+
+impl Related<feature_flag::Entity> for Entity {
+    fn to() -> RelationDef { Relation::Flag.def() }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::tenant;
+
+/// An invitation to join a tenant with a role; accepted through `/auth/accept-invite`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "invitation")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    /// `user::ROLE_*` given to the user created on acceptance
+    pub role: String,
+    /// Hex SHA-256 of the invite token; the token itself is never stored
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub expires_at: DateTimeWithTimeZone,
+    pub accepted_at: Option<DateTimeWithTimeZone>,
+    pub accepted_user_id: Option<Uuid>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Tenant }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::Tenant => Entity::belongs_to(tenant::Entity).from(Column::TenantId).to(tenant::Column::Id).into() }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
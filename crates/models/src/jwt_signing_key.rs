@@ -0,0 +1,37 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::encrypted::EncryptedString;
+
+/// Login token signing key. The active key signs new tokens; superseded keys verify until `verify_until`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "jwt_signing_key")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub kid: String,
+    /// HS256 / EdDSA
+    pub algorithm: String,
+    /// 密钥材料（HS256 密钥或 Ed25519 PKCS#8）的 base64，加密存储，不对外输出
+    #[serde(skip_serializing)]
+    #[sea_orm(column_type = "Text")]
+    pub secret: EncryptedString,
+    /// EdDSA 公钥（base64url）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub public_key: Option<String>,
+    pub active: bool,
+    pub created_at: DateTimeWithTimeZone,
+    /// 为空表示一直可用于校验（当前签名密钥）
+    pub verify_until: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,14 +1,32 @@
 pub mod errors;
 pub mod db;
+pub mod encrypted;
 pub mod tenant;
 pub mod user;
 pub mod user_credentials;
+pub mod auth_session;
+pub mod invitation;
 pub mod apikey;
 pub mod upstream;
 pub mod ratelimit;
 pub mod route;
 pub mod request_log;
+pub mod request_payload;
+pub mod route_capture;
 pub mod proxy_api;
+pub mod config_backup;
+pub mod config_revision;
+pub mod webhook;
+pub mod webhook_delivery;
+pub mod metrics_rollup;
+pub mod metrics_rollup_dimension;
+pub mod scheduled_report_run;
+pub mod admin_api_key;
+pub mod managed_api;
+pub mod feature_flag;
+pub mod feature_flag_override;
+pub mod jwt_signing_key;
+pub mod testing;
 
 #[cfg(test)]
 mod tests;
@@ -46,8 +64,8 @@ mod legacy_tests {
         let u = user::create(&db, t.id, "bob@example.com", "Bob").await.expect("create user"); success += 1;
         user::soft_delete(&db, u.id).await.expect("soft delete"); success += 1;
         user::hard_delete(&db, u.id).await.expect("hard delete"); success += 1;
-        let _up = upstream::create(&db, "jsonplaceholder", "https://jsonplaceholder.typicode.com").await.expect("create upstream"); success += 1;
-        let rl = ratelimit::ActiveModel { id: Set(Uuid::new_v4()), tenant_id: Set(Some(t.id)), requests_per_minute: Set(60), burst: Set(10), created_at: Set(Utc::now().into()) };
+        let _up = upstream::create(&db, "echo", "http://127.0.0.1:9099").await.expect("create upstream"); success += 1;
+        let rl = ratelimit::ActiveModel { id: Set(Uuid::new_v4()), tenant_id: Set(Some(t.id)), requests_per_minute: Set(60), burst: Set(10), created_at: Set(Utc::now().into()), spike_arrest_interval_ms: Set(None), algorithm: Set("token_bucket".to_string()) };
         let _rlm = rl.insert(&txn).await.expect("insert ratelimit"); success += 1;
 
         let duration = start.elapsed();
@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+/// API registered via /admin/apis, database backend of the API management store.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "managed_api")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub endpoint_url: String,
+    pub method: String,
+    #[sea_orm(column_type = "Text")]
+    pub forward_target: String,
+    pub require_api_key: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
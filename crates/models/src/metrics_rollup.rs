@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+/// Per-minute request aggregate for one route; `bucket` is the start of the minute (UTC).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "metrics_rollup")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub bucket: DateTimeWithTimeZone,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub route_id: Uuid,
+    pub tenant_id: Uuid,
+    pub requests: i64,
+    pub errors: i64,
+    pub latency_sum_ms: i64,
+    pub latency_max_ms: i32,
+    #[sea_orm(column_type = "Double")]
+    pub latency_p50_ms: f64,
+    #[sea_orm(column_type = "Double")]
+    pub latency_p95_ms: f64,
+    #[sea_orm(column_type = "Double")]
+    pub latency_p99_ms: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
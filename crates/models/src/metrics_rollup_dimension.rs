@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const DIMENSION_API_KEY: &str = "api_key";
+pub const DIMENSION_CLIENT_IP: &str = "client_ip";
+
+/// Per-minute request aggregate for one API key or client IP; `key` is the key id or the IP.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "metrics_rollup_dimension")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub bucket: DateTimeWithTimeZone,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub dimension: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub key: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub latency_max_ms: i32,
+    #[sea_orm(column_type = "Double")]
+    pub latency_p99_ms: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef { panic!("no relations defined here") }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -18,6 +18,27 @@ pub struct Model {
     pub enabled: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    /// Mock 模式：返回配置的静态响应而不转发
+    #[serde(default)]
+    pub mock_enabled: bool,
+    #[serde(default)]
+    pub mock_status: Option<i32>,
+    /// JSON 对象（header -> 值模板）
+    #[serde(default)]
+    pub mock_headers: Option<String>,
+    #[serde(default)]
+    pub mock_body: Option<String>,
+    /// 维护窗口 [start, end)：窗口内返回 503；任一端为空表示不限
+    #[serde(default)]
+    pub maintenance_start: Option<DateTimeWithTimeZone>,
+    #[serde(default)]
+    pub maintenance_end: Option<DateTimeWithTimeZone>,
+    /// 停用/维护时 503 响应的 payload（JSON 或纯文本）
+    #[serde(default)]
+    pub maintenance_message: Option<String>,
+    /// gRPC 转码：`package.Service/Method`，设置后 JSON 请求按描述符转为 gRPC 调用
+    #[serde(default)]
+    pub grpc_method: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -52,11 +73,12 @@ pub fn validate_endpoint_url(p: &str) -> Result<(), errors::ModelError> {
     Ok(())
 }
 
+/// http(s) 且目标主机在出站 allowlist（`[egress]`）内
 pub fn validate_forward_target(u: &str) -> Result<(), errors::ModelError> {
     if !(u.starts_with("http://") || u.starts_with("https://")) {
         return Err(errors::ModelError::Validation("forward_target must start with http(s)".into()));
     }
-    Ok(())
+    common::egress::global().check_url(u).map_err(|e| errors::ModelError::Validation(format!("forward_target: {}", e)))
 }
 
 pub async fn create(
@@ -82,6 +104,14 @@ pub async fn create(
         enabled: Set(true),
         created_at: Set(now),
         updated_at: Set(now),
+        mock_enabled: Set(false),
+        mock_status: Set(None),
+        mock_headers: Set(None),
+        mock_body: Set(None),
+        maintenance_start: Set(None),
+        maintenance_end: Set(None),
+        maintenance_message: Set(None),
+        grpc_method: Set(None),
     };
     am.insert(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))
 }
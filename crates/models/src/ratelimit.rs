@@ -2,6 +2,8 @@ use sea_orm::entity::prelude::*;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
 
+use common::rate_limit::{Algorithm, Limiter};
+
 use crate::tenant;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -13,6 +15,15 @@ pub struct Model {
     pub requests_per_minute: i32,
     pub burst: i32,
     pub created_at: DateTimeWithTimeZone,
+    /// Spike arrest: minimum spacing between allowed requests per key (ms); None disables it
+    pub spike_arrest_interval_ms: Option<i32>,
+    /// token_bucket | sliding_window | fixed_window; see [`common::rate_limit::Algorithm`]
+    #[serde(default = "default_algorithm")]
+    pub algorithm: String,
+}
+
+fn default_algorithm() -> String {
+    Algorithm::TokenBucket.as_str().to_string()
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -26,6 +37,24 @@ impl RelationTrait for Relation {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    pub fn spike_arrest_interval(&self) -> Option<std::time::Duration> {
+        self.spike_arrest_interval_ms
+            .filter(|ms| *ms > 0)
+            .map(|ms| std::time::Duration::from_millis(ms as u64))
+    }
+
+    /// Unknown values (e.g. written by a newer version) fall back to the token bucket
+    pub fn algorithm_kind(&self) -> Algorithm {
+        self.algorithm.parse().unwrap_or_default()
+    }
+
+    /// A limiter enforcing this record: `requests_per_minute` per minute, bursting up to `burst` with the token bucket
+    pub fn limiter(&self) -> Limiter {
+        Limiter::new(self.algorithm_kind(), self.requests_per_minute.max(0) as u64, self.burst.max(0) as u64, std::time::Duration::from_secs(60))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,9 +69,17 @@ mod tests {
             requests_per_minute: 60,
             burst: 10,
             created_at: Utc::now().into(),
+            spike_arrest_interval_ms: Some(10),
+            algorithm: "token_bucket".into(),
         };
         assert_eq!(m.requests_per_minute, 60);
         assert_eq!(m.burst, 10);
         assert!(m.tenant_id.is_none());
+        assert_eq!(m.spike_arrest_interval(), Some(std::time::Duration::from_millis(10)));
+        assert_eq!(Model { spike_arrest_interval_ms: Some(0), ..m.clone() }.spike_arrest_interval(), None);
+        assert_eq!(m.algorithm_kind(), Algorithm::TokenBucket);
+        let strict = Model { algorithm: "sliding_window".into(), ..m.clone() };
+        assert_eq!(strict.limiter().algorithm(), Algorithm::SlidingWindow);
+        assert_eq!(Model { algorithm: "unknown".into(), ..m }.algorithm_kind(), Algorithm::TokenBucket);
     }
 }
\ No newline at end of file
@@ -17,6 +17,15 @@ pub struct Model {
     pub error_message: Option<String>,
     pub client_ip: Option<String>,
     pub timestamp: DateTimeWithTimeZone,
+    /// 处理该请求时生效的控制面配置版本
+    #[serde(default)]
+    pub config_revision: Option<i64>,
+    /// 客户端 IP 的国家代码（GeoIP）
+    #[serde(default)]
+    pub country: Option<String>,
+    /// 客户端 IP 的 ASN
+    #[serde(default)]
+    pub asn: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -51,6 +60,9 @@ mod tests {
             error_message: None,
             client_ip: Some("127.0.0.1".into()),
             timestamp: Utc::now().into(),
+            config_revision: Some(7),
+            country: Some("DE".into()),
+            asn: Some(3320),
         };
         assert_eq!(m.status_code, 200);
         assert!(m.success);
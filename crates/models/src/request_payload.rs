@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::request_log;
+
+/// 请求体捕获：与 request_log 一对一，保存重放所需的请求与当时的上游响应（已脱敏、按上限截断）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "request_payload")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub request_log_id: i64,
+    pub method: String,
+    /// 路径 + 查询串（不含 scheme/host），重放时拼接到目标 base URL 之后
+    #[sea_orm(column_type = "Text")]
+    pub uri: String,
+    /// JSON 对象 `{name: value}`，同名多值以 ", " 连接
+    #[sea_orm(column_type = "Text")]
+    pub request_headers: String,
+    #[sea_orm(column_type = "Blob", nullable)]
+    pub request_body: Option<Vec<u8>>,
+    pub request_truncated: bool,
+    pub response_status: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub response_headers: Option<String>,
+    #[sea_orm(column_type = "Blob", nullable)]
+    pub response_body: Option<Vec<u8>>,
+    pub response_truncated: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    pub fn request_header_map(&self) -> BTreeMap<String, String> {
+        serde_json::from_str(&self.request_headers).unwrap_or_default()
+    }
+
+    pub fn response_header_map(&self) -> Option<BTreeMap<String, String>> {
+        self.response_headers.as_deref().map(|h| serde_json::from_str(h).unwrap_or_default())
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { RequestLog }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Relation::RequestLog => Entity::belongs_to(request_log::Entity).from(Column::RequestLogId).to(request_log::Column::Id).into(),
+        }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -18,6 +18,17 @@ pub struct Model {
     pub circuit_breaker_threshold: i32,
     pub rate_limit_id: Option<Uuid>,
     pub created_at: DateTimeWithTimeZone,
+    /// 蓝绿部署的两个目标；upstream_id 始终指向 active_color 对应的一侧
+    #[serde(default)]
+    pub blue_upstream_id: Option<Uuid>,
+    #[serde(default)]
+    pub green_upstream_id: Option<Uuid>,
+    /// "blue" / "green"；未配置蓝绿时为空
+    #[serde(default)]
+    pub active_color: Option<String>,
+    /// 上一次切换前的 upstream，用于立即回滚
+    #[serde(default)]
+    pub previous_upstream_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -54,6 +65,10 @@ mod tests {
             circuit_breaker_threshold: 5,
             rate_limit_id: None,
             created_at: Utc::now().into(),
+            blue_upstream_id: None,
+            green_upstream_id: None,
+            active_color: None,
+            previous_upstream_id: None,
         };
         assert_eq!(m.method, "GET");
         assert_eq!(m.path, "/api");
@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::route;
+
+/// 路由的请求体捕获设置；没有记录表示不捕获
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "route_capture")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub route_id: Uuid,
+    /// 0-100，按请求随机抽样
+    pub sample_percent: i16,
+    /// 请求体与响应体各自的保存上限（脱敏后的字节数）
+    pub max_bytes: i32,
+    /// 是否同时保存上游响应（重放比较需要）
+    pub capture_response: bool,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Route }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::Route => Entity::belongs_to(route::Entity).from(Column::RouteId).to(route::Column::Id).into() }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
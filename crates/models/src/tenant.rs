@@ -12,6 +12,35 @@ pub struct Model {
     pub id: Uuid,
     pub name: String,
     pub created_at: DateTimeWithTimeZone,
+    /// Kill switch：暂停后该租户的所有流量被拦截
+    #[serde(default)]
+    pub suspended: bool,
+    #[serde(default)]
+    pub suspended_reason: Option<String>,
+    /// off | optional | required（见 `EMAIL_VERIFICATION_*`）
+    #[serde(default = "default_email_verification")]
+    pub email_verification: String,
+    /// 仅允许通过邀请加入：开启后 `/auth/register` 拒绝自助注册
+    #[serde(default)]
+    pub invite_only: bool,
+}
+
+/// 注册即激活，不发送验证邮件
+pub const EMAIL_VERIFICATION_OFF: &str = "off";
+/// 发送验证邮件，未验证也可登录
+pub const EMAIL_VERIFICATION_OPTIONAL: &str = "optional";
+/// 发送验证邮件，验证前不能登录
+pub const EMAIL_VERIFICATION_REQUIRED: &str = "required";
+
+fn default_email_verification() -> String {
+    EMAIL_VERIFICATION_OFF.to_string()
+}
+
+pub fn validate_email_verification(policy: &str) -> Result<(), errors::ModelError> {
+    match policy {
+        EMAIL_VERIFICATION_OFF | EMAIL_VERIFICATION_OPTIONAL | EMAIL_VERIFICATION_REQUIRED => Ok(()),
+        other => Err(errors::ModelError::Validation(format!("email_verification must be off, optional or required (got {})", other))),
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -37,6 +66,10 @@ pub async fn create(db: &DatabaseConnection, name: &str) -> Result<Model, errors
         id: Set(Uuid::new_v4()),
         name: Set(name.to_string()),
         created_at: Set(Utc::now().into()),
+        suspended: Set(false),
+        suspended_reason: Set(None),
+        email_verification: Set(EMAIL_VERIFICATION_OFF.into()),
+        invite_only: Set(false),
     };
     am.insert(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))
 }
@@ -0,0 +1,163 @@
+//! 测试数据库：每个测试一个独立 schema（search_path），迁移在该 schema 内执行，测试之间互不干扰、可并行运行。
+//! - 地址取 `DATABASE_URL`（或配置文件中的数据库地址）；未设置且启用 `testcontainers` 特性时，进程内首次使用会启动一个 Postgres 容器，各测试共享容器、schema 各自独立；
+//! - 两者都不可用时 [`TestDb::create`] 返回 [`TestDbError::Unavailable`]，调用方据此跳过测试；
+//! - 加密列写入需要主密钥，[`TestDb::create`] 在尚未安装时安装固定的测试主密钥；
+//! - [`TestDb::cleanup`] 删除 schema；未显式调用时在 `TestDb` 释放（包括测试 panic）时删除；设置 `KEEP_TEST_SCHEMA=1` 时保留，便于排查。
+
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbErr};
+use migration::MigratorTrait;
+use uuid::Uuid;
+
+use crate::db::DatabaseConfig;
+
+/// 测试 schema 名前缀
+pub const SCHEMA_PREFIX: &str = "test_";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TestDbError {
+    #[error("no test database: set DATABASE_URL or enable the `testcontainers` feature")]
+    Unavailable,
+    #[error("start postgres container: {0}")]
+    Container(String),
+    #[error(transparent)]
+    Db(#[from] DbErr),
+}
+
+/// 尚未安装主密钥时安装固定的测试主密钥（`key_id` 为 `test`）
+pub fn install_test_master_key() {
+    use common::crypto::{MasterKeys, SecretBox};
+    if common::crypto::installed().is_none() {
+        common::crypto::install(MasterKeys::new("test", SecretBox::derive_from("test-master-key")));
+    }
+}
+
+/// 一个测试独占的 schema 及指向它的连接池
+pub struct TestDb {
+    pub db: DatabaseConnection,
+    pub schema: String,
+    url: String,
+    dropped: bool,
+}
+
+impl TestDb {
+    /// 新建唯一 schema 并执行全部迁移
+    pub async fn create() -> Result<Self, TestDbError> {
+        Self::create_with(&DatabaseConfig { max_connections: 5, min_connections: 0, ..Default::default() }).await
+    }
+
+    /// 同 [`Self::create`]，连接池参数取自 `config`（`url` 被忽略），用于连接池相关测试
+    pub async fn create_with(config: &DatabaseConfig) -> Result<Self, TestDbError> {
+        install_test_master_key();
+        let url = database_url().await?;
+        let schema = format!("{}{}", SCHEMA_PREFIX, Uuid::new_v4().simple());
+        let admin = Database::connect(url.as_str()).await?;
+        admin.execute_unprepared(&format!("CREATE SCHEMA \"{}\"", schema)).await?;
+        admin.close().await?;
+
+        // public 保留在 search_path 中，以便使用其中安装的扩展；表都建在本 schema
+        let mut opt = ConnectOptions::new(url.as_str());
+        opt.max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .connect_timeout(config.connect_timeout)
+            .idle_timeout(config.idle_timeout)
+            .max_lifetime(config.max_lifetime)
+            .acquire_timeout(config.acquire_timeout)
+            .sqlx_logging(config.sqlx_logging)
+            .set_schema_search_path(format!("{},public", schema));
+        let db = Database::connect(opt).await?;
+        migration::Migrator::up(&db, None).await?;
+        Ok(Self { db, schema, url, dropped: false })
+    }
+
+    /// 关闭连接并删除 schema
+    pub async fn cleanup(mut self) -> Result<(), DbErr> {
+        self.dropped = true;
+        self.db.close_by_ref().await?;
+        if keep_schema(&self.schema) {
+            return Ok(());
+        }
+        drop_schema(&self.url, &self.schema).await
+    }
+}
+
+impl std::ops::Deref for TestDb {
+    type Target = DatabaseConnection;
+
+    fn deref(&self) -> &DatabaseConnection {
+        &self.db
+    }
+}
+
+impl Drop for TestDb {
+    /// 未调用 `cleanup` 时在独立线程中删除 schema（Drop 中不能 await，且可能位于 tokio runtime 内）
+    fn drop(&mut self) {
+        if self.dropped || keep_schema(&self.schema) {
+            return;
+        }
+        let (url, schema) = (self.url.clone(), self.schema.clone());
+        let handle = std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+            rt.block_on(drop_schema(&url, &schema)).map_err(std::io::Error::other)
+        });
+        if let Ok(Err(e)) = handle.join() {
+            eprintln!("drop test schema {}: {}", self.schema, e);
+        }
+    }
+}
+
+fn keep_schema(schema: &str) -> bool {
+    let keep = std::env::var("KEEP_TEST_SCHEMA").is_ok();
+    if keep {
+        eprintln!("keeping test schema {}", schema);
+    }
+    keep
+}
+
+async fn drop_schema(url: &str, schema: &str) -> Result<(), DbErr> {
+    let admin = Database::connect(url).await?;
+    admin.execute_unprepared(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema)).await?;
+    admin.close().await
+}
+
+async fn database_url() -> Result<String, TestDbError> {
+    let _ = dotenvy::dotenv();
+    let configured = std::env::var("DATABASE_URL").ok().or_else(|| DatabaseConfig::from_file().map(|c| c.url));
+    if let Some(url) = configured.filter(|u| !u.trim().is_empty()) {
+        return Ok(url);
+    }
+    #[cfg(feature = "testcontainers")]
+    {
+        container::url().await
+    }
+    #[cfg(not(feature = "testcontainers"))]
+    {
+        Err(TestDbError::Unavailable)
+    }
+}
+
+#[cfg(feature = "testcontainers")]
+mod container {
+    use testcontainers_modules::postgres::Postgres;
+    use testcontainers_modules::testcontainers::runners::AsyncRunner;
+    use testcontainers_modules::testcontainers::ContainerAsync;
+    use tokio::sync::OnceCell;
+
+    use super::TestDbError;
+
+    /// 进程内共享的容器；进程退出后由 testcontainers 回收
+    static POSTGRES: OnceCell<(ContainerAsync<Postgres>, String)> = OnceCell::const_new();
+
+    pub async fn url() -> Result<String, TestDbError> {
+        let (_, url) = POSTGRES
+            .get_or_try_init(|| async {
+                let err = |e: &dyn std::fmt::Display| TestDbError::Container(e.to_string());
+                let container = Postgres::default().start().await.map_err(|e| err(&e))?;
+                let host = container.get_host().await.map_err(|e| err(&e))?;
+                let port = container.get_host_port_ipv4(5432).await.map_err(|e| err(&e))?;
+                let url = format!("postgres://postgres:postgres@{}:{}/postgres", host, port);
+                Ok::<_, TestDbError>((container, url))
+            })
+            .await?;
+        Ok(url.clone())
+    }
+}
@@ -1,21 +1,10 @@
-use crate::db::connect;
+use super::setup_test_db;
 use crate::{tenant, user, apikey, upstream, ratelimit, route, request_log};
-use sea_orm::{DatabaseConnection, DatabaseBackend, Statement, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait, ConnectionTrait};
+use sea_orm::{DatabaseBackend, Statement, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait, ConnectionTrait};
 use anyhow::Result;
-use migration::MigratorTrait;
 use uuid::Uuid;
 use std::time::Instant;
 
-/// Setup test database with migrations
-async fn setup_test_db() -> Result<DatabaseConnection> {
-    let db = connect().await?;
-    
-    // Run migrations if needed
-    migration::Migrator::up(&db, None).await?;
-    
-    Ok(db)
-}
-
 /// Test tenant CRUD operations
 #[tokio::test]
 async fn test_tenant_crud() -> Result<()> {
@@ -23,7 +12,7 @@ async fn test_tenant_crud() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Test Create
     let tenant_name = format!("test_tenant_{}", Uuid::new_v4());
@@ -70,7 +59,7 @@ async fn test_user_crud() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Create a tenant first
     let tenant_name = format!("user_test_tenant_{}", Uuid::new_v4());
@@ -129,7 +118,7 @@ async fn test_apikey_crud() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Setup prerequisites
     let tenant_name = format!("apikey_test_tenant_{}", Uuid::new_v4());
@@ -180,7 +169,7 @@ async fn test_upstream_crud() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Test Create Upstream
     let up_name = format!("upstream_{}", Uuid::new_v4());
@@ -215,7 +204,7 @@ async fn test_ratelimit_crud() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Setup prerequisites (optional tenant)
     let tenant_name = format!("ratelimit_test_tenant_{}", Uuid::new_v4());
@@ -231,6 +220,8 @@ async fn test_ratelimit_crud() -> Result<()> {
         requests_per_minute: Set(requests_per_minute),
         burst: Set(burst),
         created_at: Set(chrono::Utc::now().into()),
+        spike_arrest_interval_ms: Set(None),
+        algorithm: Set("fixed_window".to_string()),
     };
     let created_ratelimit = rl.insert(&db).await?;
     
@@ -246,6 +237,7 @@ async fn test_ratelimit_crud() -> Result<()> {
     let found_ratelimit = found_ratelimit.unwrap();
     assert_eq!(found_ratelimit.id, created_ratelimit.id);
     assert_eq!(found_ratelimit.requests_per_minute, requests_per_minute);
+    assert_eq!(found_ratelimit.algorithm_kind(), common::rate_limit::Algorithm::FixedWindow);
     
     // Test Soft Delete
     // Delete via Entity (no soft-delete/hard-delete helpers)
@@ -265,7 +257,7 @@ async fn test_crud_performance() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Test tenant creation performance
     let iterations = 10;
@@ -321,7 +313,7 @@ async fn test_batch_operations() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     // Create multiple tenants in sequence
     let batch_size = 5;
@@ -12,12 +12,18 @@ pub mod performance_tests;
 /// Proxy API entity tests
 pub mod proxy_api_tests;
 
+/// 每个测试独立的 schema（已执行迁移）；返回的 `TestDb` 需保持存活，释放时删除 schema
+pub(crate) async fn setup_test_db() -> anyhow::Result<(sea_orm::DatabaseConnection, crate::testing::TestDb)> {
+    // 测试上游（echo）在环回地址上，默认的出站策略会拒绝
+    common::egress::install(common::egress::EgressPolicy::allow_all());
+    let test_db = crate::testing::TestDb::create().await?;
+    Ok((test_db.db.clone(), test_db))
+}
+
 /// Integration tests combining multiple components
 pub mod integration_tests {
-    use crate::db::connect;
     use crate::{tenant, user, apikey, upstream, ratelimit, route};
     use sea_orm::{EntityTrait, ActiveModelTrait, ConnectionTrait};
-    use migration::MigratorTrait;
     use anyhow::Result;
     use uuid::Uuid;
     
@@ -28,8 +34,7 @@ pub mod integration_tests {
             return Ok(());
         }
 
-        let db = connect().await?;
-        migration::Migrator::up(&db, None).await?;
+        let (db, _schema) = super::setup_test_db().await?;
         
         // Create tenant
         let tenant_name = format!("workflow_tenant_{}", Uuid::new_v4());
@@ -56,6 +61,8 @@ pub mod integration_tests {
             requests_per_minute: sea_orm::Set(1000),
             burst: sea_orm::Set(50),
             created_at: sea_orm::Set(chrono::Utc::now().into()),
+            spike_arrest_interval_ms: sea_orm::Set(None),
+            algorithm: sea_orm::Set("token_bucket".to_string()),
         };
         let test_ratelimit = rl.insert(&db).await?;
         
@@ -71,6 +78,10 @@ pub mod integration_tests {
             circuit_breaker_threshold: sea_orm::Set(5000),
             rate_limit_id: sea_orm::Set(Some(test_ratelimit.id)),
             created_at: sea_orm::Set(chrono::Utc::now().into()),
+            blue_upstream_id: sea_orm::Set(None),
+            green_upstream_id: sea_orm::Set(None),
+            active_color: sea_orm::Set(None),
+            previous_upstream_id: sea_orm::Set(None),
         };
         let test_route = rt.insert(&db).await?;
         
@@ -119,8 +130,7 @@ pub mod integration_tests {
             return Ok(());
         }
 
-        let db = connect().await?;
-        migration::Migrator::up(&db, None).await?;
+        let (db, _schema) = super::setup_test_db().await?;
         
         // Create tenant with multiple users
         let tenant_name = format!("consistency_tenant_{}", Uuid::new_v4());
@@ -1,20 +1,14 @@
-use crate::db::{connect, connect_with_config, DatabaseConfig, get_pool_stats};
+use super::setup_test_db;
+use crate::db::{DatabaseConfig, get_pool_stats};
+use crate::testing::TestDb;
 use crate::tenant;
-use sea_orm::{DatabaseConnection, DatabaseBackend, Statement, EntityTrait, ConnectionTrait};
-use migration::MigratorTrait;
+use sea_orm::{DatabaseBackend, Statement, EntityTrait, ConnectionTrait};
 use anyhow::Result;
 use uuid::Uuid;
 use std::time::{Duration, Instant};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 
-/// Setup test database
-async fn setup_test_db() -> Result<DatabaseConnection> {
-    let db = connect().await?;
-    migration::Migrator::up(&db, None).await?;
-    Ok(db)
-}
-
 /// Test connection pool performance under load
 #[tokio::test]
 async fn test_connection_pool_performance() -> Result<()> {
@@ -27,8 +21,8 @@ async fn test_connection_pool_performance() -> Result<()> {
     config.max_connections = 10;
     config.min_connections = 2;
     
-    let db = connect_with_config(&config).await?;
-    migration::Migrator::up(&db, None).await?;
+    let test_db = TestDb::create_with(&config).await?;
+    let db = test_db.db.clone();
     
     let concurrent_tasks = 20;
     let queries_per_task = 10;
@@ -124,8 +118,7 @@ async fn test_resource_cleanup() -> Result<()> {
         println!("Resource cleanup cycle {}", cycle + 1);
         
         // Create connection
-        let db = connect().await?;
-        migration::Migrator::up(&db, None).await?;
+        let (db, _schema) = setup_test_db().await?;
         
         // Use connection for multiple operations
         let mut tenant_ids = vec![];
@@ -170,8 +163,8 @@ async fn test_connection_pool_exhaustion() -> Result<()> {
     config.max_connections = 3; // Very small pool
     config.acquire_timeout = Duration::from_millis(500);
     
-    let db = connect_with_config(&config).await?;
-    migration::Migrator::up(&db, None).await?;
+    let test_db = TestDb::create_with(&config).await?;
+    let db = test_db.db.clone();
     
     println!("Testing connection pool exhaustion with {} max connections", config.max_connections);
     
@@ -248,7 +241,7 @@ async fn test_operation_benchmarks() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     println!("Running database operation benchmarks...");
     
@@ -316,7 +309,7 @@ async fn test_bulk_operation_memory() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     println!("Testing memory usage during bulk operations...");
     
@@ -370,8 +363,8 @@ async fn test_connection_timeout_under_load() -> Result<()> {
     config.acquire_timeout = Duration::from_millis(1000);
     config.connect_timeout = Duration::from_millis(5000);
     
-    let db = connect_with_config(&config).await?;
-    migration::Migrator::up(&db, None).await?;
+    let test_db = TestDb::create_with(&config).await?;
+    let db = test_db.db.clone();
     
     println!("Testing connection timeouts under load...");
     
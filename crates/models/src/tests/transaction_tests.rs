@@ -1,18 +1,23 @@
-use crate::db::connect;
+use super::setup_test_db;
 use crate::tenant;
-use sea_orm::{DatabaseConnection, TransactionTrait, DatabaseBackend, Statement, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait, ConnectionTrait};
+use sea_orm::{TransactionTrait, DatabaseBackend, Statement, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait, ConnectionTrait};
 use chrono::Utc;
-use migration::MigratorTrait;
 use anyhow::Result;
 use uuid::Uuid;
 use std::sync::Arc;
 use tokio::sync::Barrier;
 
-/// Setup test database
-async fn setup_test_db() -> Result<DatabaseConnection> {
-    let db = connect().await?;
-    migration::Migrator::up(&db, None).await?;
-    Ok(db)
+/// Active model for a new tenant named `name` with default settings
+fn tenant_active(name: &str) -> tenant::ActiveModel {
+    tenant::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        name: Set(name.to_string()),
+        created_at: Set(Utc::now().into()),
+        suspended: Set(false),
+        suspended_reason: Set(None),
+        email_verification: Set(tenant::EMAIL_VERIFICATION_OFF.into()),
+        invite_only: Set(false),
+    }
 }
 
 /// Test basic transaction commit
@@ -22,7 +27,7 @@ async fn test_transaction_commit() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant_name = format!("tx_commit_test_{}", Uuid::new_v4());
     let mut tenant_id = None;
@@ -31,7 +36,7 @@ async fn test_transaction_commit() -> Result<()> {
     let txn = db.begin().await?;
     
     // Create tenant within transaction (ActiveModel insert on txn)
-    let am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+    let am = tenant_active(&tenant_name);
     let created_tenant = am.insert(&txn).await?;
     tenant_id = Some(created_tenant.id);
     
@@ -59,7 +64,7 @@ async fn test_transaction_rollback() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant_name = format!("tx_rollback_test_{}", Uuid::new_v4());
     let mut created_tenant_id = None;
@@ -68,7 +73,7 @@ async fn test_transaction_rollback() -> Result<()> {
     let txn = db.begin().await?;
     
     // Create tenant within transaction
-    let am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+    let am = tenant_active(&tenant_name);
     let created_tenant = am.insert(&txn).await?;
     created_tenant_id = Some(created_tenant.id);
     
@@ -97,7 +102,7 @@ async fn test_nested_transactions() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant1_name = format!("nested_tx_1_{}", Uuid::new_v4());
     let tenant2_name = format!("nested_tx_2_{}", Uuid::new_v4());
@@ -107,7 +112,7 @@ async fn test_nested_transactions() -> Result<()> {
     let outer_txn = db.begin().await?;
     
     // Create first tenant
-    let am1 = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant1_name.clone()), created_at: Set(Utc::now().into()) };
+    let am1 = tenant_active(&tenant1_name);
     let tenant1 = am1.insert(&outer_txn).await?;
     cleanup_ids.push(tenant1.id);
     
@@ -115,7 +120,7 @@ async fn test_nested_transactions() -> Result<()> {
     let inner_txn = outer_txn.begin().await?;
     
     // Create second tenant in inner transaction
-    let am2 = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant2_name.clone()), created_at: Set(Utc::now().into()) };
+    let am2 = tenant_active(&tenant2_name);
     let tenant2 = am2.insert(&inner_txn).await?;
     
     // Rollback inner transaction only
@@ -148,7 +153,7 @@ async fn test_transaction_error_handling() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant_name = format!("tx_error_test_{}", Uuid::new_v4());
     
@@ -157,12 +162,12 @@ async fn test_transaction_error_handling() -> Result<()> {
         let txn = db.begin().await?;
         
         // Create valid tenant
-        let am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+        let am = tenant_active(&tenant_name);
         let _tenant = am.insert(&txn).await?;
         
         // Try to create duplicate tenant (should fail due to unique constraint)
         // Attempt duplicate insert (name has unique index)
-        let am_dup = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+        let am_dup = tenant_active(&tenant_name);
         let _duplicate = am_dup.insert(&txn).await?;
         
         txn.commit().await?;
@@ -187,7 +192,7 @@ async fn test_concurrent_transactions() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     let db = Arc::new(db);
     
     let num_tasks = 5;
@@ -209,11 +214,7 @@ async fn test_concurrent_transactions() -> Result<()> {
             let txn = db_clone.begin().await?;
             
             // Create tenant via ActiveModel on the transaction
-            let am = tenant::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                name: Set(tenant_name.clone()),
-                created_at: Set(Utc::now().into()),
-            };
+            let am = tenant_active(&tenant_name);
             let tenant = am.insert(&txn).await?;
             
             // Small delay to increase chance of concurrency issues
@@ -254,13 +255,13 @@ async fn test_transaction_isolation() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant_name = format!("isolation_test_{}", Uuid::new_v4());
     let mut cleanup_id = None;
     
     // Create a tenant first
-    let initial_am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+    let initial_am = tenant_active(&tenant_name);
     let initial_tenant = initial_am.insert(&db).await?;
     cleanup_id = Some(initial_tenant.id);
     
@@ -302,7 +303,7 @@ async fn test_long_running_transaction() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let tenant_name = format!("long_tx_test_{}", Uuid::new_v4());
     let mut cleanup_id = None;
@@ -311,7 +312,7 @@ async fn test_long_running_transaction() -> Result<()> {
     let txn = db.begin().await?;
     
     // Create tenant
-    let am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+    let am = tenant_active(&tenant_name);
     let tenant = am.insert(&txn).await?;
     cleanup_id = Some(tenant.id);
     
@@ -345,7 +346,7 @@ async fn test_multi_operation_transaction() -> Result<()> {
         return Ok(());
     }
 
-    let db = setup_test_db().await?;
+    let (db, _schema) = setup_test_db().await?;
     
     let mut cleanup_ids = vec![];
     
@@ -355,7 +356,7 @@ async fn test_multi_operation_transaction() -> Result<()> {
     // Create multiple tenants in single transaction
     for i in 0..3 {
         let tenant_name = format!("multi_op_tenant_{}_{}", i, Uuid::new_v4());
-    let am = tenant::ActiveModel { id: Set(Uuid::new_v4()), name: Set(tenant_name.clone()), created_at: Set(Utc::now().into()) };
+    let am = tenant_active(&tenant_name);
     let tenant = am.insert(&txn).await?;
         cleanup_ids.push(tenant.id);
     }
@@ -15,6 +15,8 @@ pub struct Model {
     pub email: String,
     pub name: String,
     pub status: String,
+    /// `ROLE_*`；邀请加入的用户取邀请上的角色
+    pub role: String,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
     pub deleted_at: Option<DateTimeWithTimeZone>,
@@ -33,6 +35,20 @@ impl RelationTrait for Relation {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+pub const STATUS_ACTIVE: &str = "active";
+/// Registered in a tenant with email verification; becomes active once the email is verified
+pub const STATUS_UNVERIFIED: &str = "unverified";
+
+pub const ROLE_MEMBER: &str = "member";
+pub const ROLE_ADMIN: &str = "admin";
+
+pub fn validate_role(role: &str) -> Result<(), errors::ModelError> {
+    match role {
+        ROLE_MEMBER | ROLE_ADMIN => Ok(()),
+        other => Err(errors::ModelError::Validation(format!("role must be member or admin (got {})", other))),
+    }
+}
+
 pub fn validate_email(email: &str) -> Result<(), errors::ModelError> {
     if !email.contains('@') {
         Err(errors::ModelError::Validation("invalid email".into()))
@@ -50,15 +66,21 @@ pub fn validate_name(name: &str) -> Result<(), errors::ModelError> {
 }
 
 pub async fn create(db: &DatabaseConnection, tenant_id: Uuid, email: &str, name: &str) -> Result<Model, errors::ModelError> {
+    create_with_status(db, tenant_id, email, name, STATUS_ACTIVE, ROLE_MEMBER).await
+}
+
+pub async fn create_with_status(db: &DatabaseConnection, tenant_id: Uuid, email: &str, name: &str, status: &str, role: &str) -> Result<Model, errors::ModelError> {
     validate_email(email)?;
     validate_name(name)?;
+    validate_role(role)?;
     let now = Utc::now().into();
     let am = ActiveModel {
         id: Set(Uuid::new_v4()),
         tenant_id: Set(tenant_id),
         email: Set(email.to_string()),
         name: Set(name.to_string()),
-        status: Set("active".into()),
+        status: Set(status.to_string()),
+        role: Set(role.to_string()),
         created_at: Set(now),
         updated_at: Set(now),
         deleted_at: Set(None),
@@ -91,6 +113,13 @@ mod tests {
     fn validate_name_accepts_normal() {
         assert!(validate_name("Bob").is_ok());
     }
+
+    #[test]
+    fn validate_role_accepts_known_roles() {
+        assert!(validate_role(ROLE_MEMBER).is_ok());
+        assert!(validate_role(ROLE_ADMIN).is_ok());
+        assert!(matches!(validate_role("owner"), Err(errors::ModelError::Validation(_))));
+    }
 }
 
 pub async fn soft_delete(db: &DatabaseConnection, id: Uuid) -> Result<(), errors::ModelError> {
@@ -100,6 +129,21 @@ pub async fn soft_delete(db: &DatabaseConnection, id: Uuid) -> Result<(), errors
     Ok(())
 }
 
+pub async fn set_status(db: &DatabaseConnection, id: Uuid, status: &str) -> Result<Model, errors::ModelError> {
+    let mut found: ActiveModel = Entity::find_by_id(id).one(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))?.ok_or_else(|| errors::ModelError::Validation("user not found".into()))?.into();
+    found.status = Set(status.to_string());
+    found.updated_at = Set(Utc::now().into());
+    found.update(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))
+}
+
+pub async fn set_role(db: &DatabaseConnection, id: Uuid, role: &str) -> Result<Model, errors::ModelError> {
+    validate_role(role)?;
+    let mut found: ActiveModel = Entity::find_by_id(id).one(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))?.ok_or_else(|| errors::ModelError::Validation("user not found".into()))?.into();
+    found.role = Set(role.to_string());
+    found.updated_at = Set(Utc::now().into());
+    found.update(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))
+}
+
 pub async fn hard_delete(db: &DatabaseConnection, id: Uuid) -> Result<(), errors::ModelError> {
     Entity::delete_by_id(id).exec(db).await.map_err(|e| errors::ModelError::Db(e.to_string()))?;
     Ok(())
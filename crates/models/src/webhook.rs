@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::encrypted::EncryptedString;
+use crate::tenant;
+
+/// Tenant webhook subscription; payloads are signed with `secret` (HMAC-SHA256).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "webhook")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    #[sea_orm(column_type = "Text")]
+    pub url: String,
+    /// 加密存储
+    #[serde(skip_serializing)]
+    #[sea_orm(column_type = "Text")]
+    pub secret: EncryptedString,
+    /// Comma separated event names, e.g. `circuit_breaker.opened,config.changed`
+    #[sea_orm(column_type = "Text")]
+    pub events: String,
+    pub enabled: bool,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Tenant }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::Tenant => Entity::belongs_to(tenant::Entity).from(Column::TenantId).to(tenant::Column::Id).into() }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn event_list(&self) -> Vec<&str> {
+        self.events.split(',').map(str::trim).filter(|e| !e.is_empty()).collect()
+    }
+
+    pub fn subscribes_to(&self, event: &str) -> bool {
+        self.event_list().contains(&event)
+    }
+}
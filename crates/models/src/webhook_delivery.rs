@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use serde::{Deserialize, Serialize};
+
+use crate::webhook;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_DELIVERED: &str = "delivered";
+pub const STATUS_FAILED: &str = "failed";
+
+/// One event delivery attempt chain for a webhook (the delivery log).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "webhook_delivery")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    /// JSON body sent to the subscriber
+    #[sea_orm(column_type = "Text")]
+    pub payload: String,
+    /// pending / delivered / failed
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTimeWithTimeZone,
+    /// HTTP status of the last attempt
+    pub response_status: Option<i32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub delivered_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation { Webhook }
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self { Relation::Webhook => Entity::belongs_to(webhook::Entity).from(Column::WebhookId).to(webhook::Column::Id).into() }
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,81 @@
+//! Cookie 登录的 CSRF 防护（double-submit）：登录时同时下发可被页面脚本读取的 `csrf_token` Cookie，
+//! 以 auth_token Cookie 认证的写请求必须在 `X-CSRF-Token` 头中带上同一个值。
+//! 跨站页面能让浏览器自动附带 Cookie，但读不到 Cookie 内容，也就伪造不了请求头。
+//! 使用 `Authorization: Bearer` 的调用方（CLI、服务间调用）不受影响。
+
+use axum::http::{header, HeaderMap, Method};
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use rand::{distributions::Alphanumeric, Rng};
+
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+pub fn new_token() -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect()
+}
+
+/// 非 HttpOnly：前端脚本需要读取后放入请求头
+pub fn cookie(token: String) -> Cookie<'static> {
+    let mut cookie = Cookie::new(CSRF_COOKIE, token);
+    cookie.set_path("/");
+    cookie.set_http_only(false);
+    cookie.set_same_site(SameSite::Strict);
+    cookie
+}
+
+/// GET / HEAD / OPTIONS 不改变状态，不做检查
+pub fn is_state_changing(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|part| part.trim().strip_prefix(name)?.strip_prefix('='))
+        .filter(|v| !v.is_empty())
+}
+
+/// 请求头与 Cookie 中的 token 一致（常量时间比较）
+pub fn verify(headers: &HeaderMap) -> bool {
+    let (Some(expected), Some(got)) = (cookie_value(headers, CSRF_COOKIE), headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok())) else {
+        return false;
+    };
+    common::crypto::constant_time_eq(expected.as_bytes(), got.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(cookie: &str, token: Option<&'static str>) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        if let Some(t) = token {
+            h.insert(CSRF_HEADER, HeaderValue::from_static(t));
+        }
+        h
+    }
+
+    #[test]
+    fn header_must_match_cookie() {
+        assert!(verify(&headers("auth_token=jwt; csrf_token=abc123", Some("abc123"))));
+        assert!(!verify(&headers("auth_token=jwt; csrf_token=abc123", Some("abc124"))));
+        assert!(!verify(&headers("auth_token=jwt; csrf_token=abc123", None)));
+        assert!(!verify(&headers("auth_token=jwt", Some("abc123"))));
+        // 前缀相同的其他 Cookie 不算
+        assert!(!verify(&headers("csrf_token_old=abc123", Some("abc123"))));
+    }
+
+    #[test]
+    fn only_writes_are_checked() {
+        assert!(!is_state_changing(&Method::GET));
+        assert!(!is_state_changing(&Method::OPTIONS));
+        assert!(is_state_changing(&Method::POST));
+        assert!(is_state_changing(&Method::DELETE));
+        assert_eq!(new_token().len(), 32);
+    }
+}
@@ -35,12 +35,13 @@ impl IntoResponse for StartupError {
     }
 }
 
-#[derive(Debug, Serialize)]
+/// JSON:API 风格的错误响应
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct JsonApiErrorBody {
     pub errors: Vec<JsonApiErrorItem>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct JsonApiErrorItem {
     pub status: u16,
     pub title: String,
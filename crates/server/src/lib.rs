@@ -3,5 +3,10 @@ pub mod startup;
 pub mod proxy_apis;
 pub mod errors;
 pub mod openapi;
+pub mod seed;
+pub mod shutdown;
+pub mod rate_limit;
+pub mod csrf;
+pub mod test_support;
 
-pub use startup::run;
\ No newline at end of file
+pub use startup::run;
@@ -1,4 +1,6 @@
-use utoipa::OpenApi;
+use chrono::{DateTime, FixedOffset};
+use utoipa::openapi::OpenApi as OpenApiSpec;
+use utoipa::{Modify, OpenApi};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -11,9 +13,30 @@ pub struct RegisterRequest { pub tenant_id: Uuid, pub email: String, pub name: S
 #[derive(utoipa::ToSchema)]
 pub struct LoginRequest { pub tenant_id: Uuid, pub email: String, pub password: String }
 
+#[derive(utoipa::ToSchema)]
+pub struct AcceptInviteRequest { pub token: String, pub name: String, pub password: String }
+
+#[derive(utoipa::ToSchema)]
+pub struct InviteRequest {
+    pub email: String,
+    /// member / admin
+    pub role: String,
+}
+
 #[derive(utoipa::ToSchema)]
 pub struct ApiKeyRecordDoc { pub user: String, pub api_key: String }
 
+#[derive(utoipa::ToSchema)]
+pub struct ApiKeyRecordPageDoc {
+    pub items: Vec<ApiKeyRecordDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(utoipa::ToSchema)]
 pub struct CreateProxyApiInputDoc {
     pub tenant_id: Option<String>,
@@ -32,30 +55,709 @@ pub struct UpdateProxyApiInputDoc {
     pub enabled: Option<bool>,
 }
 
+// ---- 响应体：models / service 不依赖 utoipa，这里按其 JSON 形状定义文档用的 schema ----
+
+#[derive(utoipa::ToSchema)]
+pub struct ProxyApiDoc {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub require_api_key: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+    pub mock_enabled: bool,
+    pub mock_status: Option<i32>,
+    /// JSON 对象（header -> 值模板）
+    pub mock_headers: Option<String>,
+    pub mock_body: Option<String>,
+    pub maintenance_start: Option<DateTime<FixedOffset>>,
+    pub maintenance_end: Option<DateTime<FixedOffset>>,
+    pub maintenance_message: Option<String>,
+    pub grpc_method: Option<String>,
+}
+
+/// 分页列表的公共字段见 `common::pagination::Page`
+#[derive(utoipa::ToSchema)]
+pub struct ProxyApiPageDoc {
+    pub items: Vec<ProxyApiDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    /// 传给下一次请求的 `cursor`
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct TenantDoc {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub suspended: bool,
+    pub suspended_reason: Option<String>,
+    /// off / optional / required
+    pub email_verification: String,
+    /// 开启后只能通过邀请加入
+    pub invite_only: bool,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct TenantPageDoc {
+    pub items: Vec<TenantDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RouteDoc {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub upstream_id: Uuid,
+    pub timeout_ms: i32,
+    pub retry_max_attempts: i32,
+    pub circuit_breaker_threshold: i32,
+    pub rate_limit_id: Option<Uuid>,
+    pub created_at: DateTime<FixedOffset>,
+    pub blue_upstream_id: Option<Uuid>,
+    pub green_upstream_id: Option<Uuid>,
+    /// blue / green
+    pub active_color: Option<String>,
+    pub previous_upstream_id: Option<Uuid>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RoutePageDoc {
+    pub items: Vec<RouteDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RequestLogDoc {
+    pub id: i64,
+    pub route_id: Uuid,
+    pub api_key_id: Option<Uuid>,
+    pub status_code: i32,
+    pub latency_ms: i32,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub client_ip: Option<String>,
+    pub timestamp: DateTime<FixedOffset>,
+    pub config_revision: Option<i64>,
+    pub country: Option<String>,
+    pub asn: Option<i64>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RouteCaptureDoc {
+    pub route_id: Uuid,
+    pub sample_percent: i16,
+    pub max_bytes: i32,
+    pub capture_response: bool,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct StatusChangeDoc {
+    pub recorded: u16,
+    pub replayed: u16,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct HeaderChangeDoc {
+    pub name: String,
+    pub recorded: Option<String>,
+    pub replayed: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct FieldChangeDoc {
+    /// `$.a.b[0]`
+    pub path: String,
+    #[schema(value_type = Option<Object>)]
+    pub recorded: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub replayed: Option<serde_json::Value>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct BodyDiffDoc {
+    pub recorded_bytes: u64,
+    pub replayed_bytes: u64,
+    pub recorded_truncated: bool,
+    pub changes: Vec<FieldChangeDoc>,
+    pub first_difference_at: Option<u64>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ReplayDiffDoc {
+    pub identical: bool,
+    pub status: Option<StatusChangeDoc>,
+    pub headers: Vec<HeaderChangeDoc>,
+    pub body: Option<BodyDiffDoc>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ReplayedResponseDoc {
+    pub status: u16,
+    pub headers: std::collections::BTreeMap<String, String>,
+    pub body: String,
+    pub body_bytes: u64,
+    pub latency_ms: u64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ReplayResultDoc {
+    pub request_log_id: i64,
+    pub target_url: String,
+    pub replayed: ReplayedResponseDoc,
+    pub diff: Option<ReplayDiffDoc>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RouteCutoverDoc {
+    pub route: RouteDoc,
+    pub from_upstream_id: Uuid,
+    pub to_upstream_id: Uuid,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ApiAuthInfoDoc {
+    pub require_api_key: bool,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ApiRecordInputDoc {
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub auth: ApiAuthInfoDoc,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ApiRecordDoc {
+    pub id: Uuid,
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub auth: ApiAuthInfoDoc,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ApiRecordPageDoc {
+    pub items: Vec<ApiRecordDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct WebhookDoc {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub url: String,
+    /// 逗号分隔的事件名
+    pub events: String,
+    pub enabled: bool,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+/// 创建时唯一一次返回签名密钥
+#[derive(utoipa::ToSchema)]
+pub struct CreatedWebhookDoc {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub url: String,
+    pub events: String,
+    pub enabled: bool,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+    pub secret: String,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct WebhookPageDoc {
+    pub items: Vec<WebhookDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct WebhookDeliveryDoc {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    /// 发送给订阅方的 JSON body
+    pub payload: String,
+    /// pending / delivered / failed
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<FixedOffset>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub delivered_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct WebhookDeliveryPageDoc {
+    pub items: Vec<WebhookDeliveryDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct FeatureFlagDoc {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_enabled: bool,
+    /// 0-100
+    pub rollout_percent: i16,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct FeatureFlagOverrideDoc {
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct FeatureFlagDetailDoc {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub default_enabled: bool,
+    pub rollout_percent: i16,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+    pub overrides: Vec<FeatureFlagOverrideDoc>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct FlagEvaluationDoc {
+    pub enabled: bool,
+    /// override / rollout / default / unknown
+    pub reason: String,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct JwtSigningKeyDoc {
+    pub id: Uuid,
+    pub kid: String,
+    /// HS256 / EdDSA
+    pub algorithm: String,
+    /// EdDSA 公钥（base64url）
+    pub public_key: Option<String>,
+    pub active: bool,
+    pub created_at: DateTime<FixedOffset>,
+    pub verify_until: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct JobProgressDoc {
+    pub done: u64,
+    pub total: u64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct JobInfoDoc {
+    pub id: Uuid,
+    pub kind: String,
+    /// queued / running / succeeded / failed / cancelled
+    pub status: String,
+    pub progress: JobProgressDoc,
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: DateTime<FixedOffset>,
+    pub started_at: Option<DateTime<FixedOffset>>,
+    pub finished_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct BackupSummaryDoc {
+    pub id: Uuid,
+    pub reason: String,
+    pub item_count: i32,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct BackupPageDoc {
+    pub items: Vec<BackupSummaryDoc>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub total_pages: u64,
+    pub has_more: bool,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct RestoreReportDoc {
+    pub backup_id: Uuid,
+    /// 恢复前自动创建的快照
+    pub pre_restore_backup_id: Uuid,
+    pub tenants: u64,
+    pub upstreams: u64,
+    pub rate_limits: u64,
+    pub routes: u64,
+    pub proxy_apis: u64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct MetricsPointDoc {
+    pub ts: DateTime<FixedOffset>,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_avg_ms: f64,
+    pub latency_max_ms: i32,
+    pub latency_p50_ms: f64,
+    pub latency_p95_ms: f64,
+    pub latency_p99_ms: f64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct KeyRouteUsageDoc {
+    pub route_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub p95_ms: Option<f64>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct KeyUsageDoc {
+    pub api_key_id: Uuid,
+    pub from: DateTime<FixedOffset>,
+    pub to: DateTime<FixedOffset>,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    /// 429 responses
+    pub rate_limited: i64,
+    /// avg_ms / p50_ms / p95_ms / p99_ms / max_ms
+    #[schema(value_type = Object)]
+    pub latency: serde_json::Value,
+    /// `[{"status_code": 200, "requests": 10}]`, most frequent first
+    #[schema(value_type = Vec<Object>)]
+    pub status_codes: Vec<serde_json::Value>,
+    pub top_routes: Vec<KeyRouteUsageDoc>,
+    pub last_request_at: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct TopEntryDoc {
+    pub rank: u32,
+    /// Route / tenant / API key id, or the client IP
+    pub key: String,
+    /// `GET /orders` for routes, the tenant name for tenants
+    pub label: Option<String>,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_p99_ms: f64,
+    /// Fraction of all requests in the range
+    pub share: f64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct TopReportDoc {
+    /// route / tenant / api_key / client_ip
+    pub dimension: String,
+    /// requests / errors / latency_p99
+    pub metric: String,
+    pub from: DateTime<FixedOffset>,
+    pub to: DateTime<FixedOffset>,
+    /// `{"requests": 1200, "errors": 30, "error_rate": 0.025}` over all traffic in the range
+    #[schema(value_type = Object)]
+    pub totals: serde_json::Value,
+    pub items: Vec<TopEntryDoc>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct TenantReportDoc {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    /// daily / weekly
+    pub period: String,
+    pub from: DateTime<FixedOffset>,
+    pub to: DateTime<FixedOffset>,
+    /// requests / errors / error_rate / latency_avg_ms / latency_p99_ms
+    #[schema(value_type = Object)]
+    pub totals: serde_json::Value,
+    /// `[{"route_id", "method", "path", "requests", "errors", "error_rate", "latency_p99_ms"}]`, most requests first
+    #[schema(value_type = Vec<Object>)]
+    pub top_endpoints: Vec<serde_json::Value>,
+    /// `[{"route_id", "method", "path", "requests_per_minute", "peak_requests_per_minute", "peak_utilization"}]` for rate limited routes
+    #[schema(value_type = Vec<Object>)]
+    pub quota: Vec<serde_json::Value>,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct LogFilterStateDoc {
+    pub filter: String,
+    pub initial: String,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ReadinessCheckDoc {
+    pub name: String,
+    /// ok / warn / fail / skipped
+    pub status: String,
+    pub detail: Option<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(utoipa::ToSchema)]
+pub struct ReadinessDoc {
+    /// ready / not_ready
+    pub status: String,
+    pub checks: Vec<ReadinessCheckDoc>,
+    #[schema(value_type = Vec<Object>)]
+    pub stores: Vec<serde_json::Value>,
+    pub multi_replica_warning: bool,
+}
+
 #[derive(OpenApi)]
 #[openapi(
     paths(
         crate::routes::health,
+        crate::routes::livez,
+        crate::routes::readyz,
         crate::routes::auth::register,
+        crate::routes::auth::verify_email,
+        crate::routes::auth::resend_verification,
+        crate::routes::auth::accept_invite,
+        crate::routes::signup::signup,
+        crate::routes::auth::create_invitation,
+        crate::routes::auth::list_invitations,
+        crate::routes::auth::revoke_invitation,
         crate::routes::auth::login,
+        crate::routes::auth::logout,
+        crate::routes::auth::me,
+        crate::routes::auth::list_sessions,
+        crate::routes::auth::revoke_session,
+        crate::routes::auth::list_own_api_keys,
+        crate::routes::auth::admin_user_sessions,
+        crate::routes::auth::admin_user_api_keys,
         crate::routes::admin::list_api_keys,
         crate::routes::admin::set_api_key,
+        crate::routes::admin::delete_api_key,
+        crate::routes::apis::list_apis,
+        crate::routes::apis::create_api,
+        crate::routes::apis::get_api,
+        crate::routes::apis::update_api,
+        crate::routes::apis::delete_api,
         crate::routes::proxy_apis::list,
         crate::routes::proxy_apis::create,
+        crate::routes::proxy_apis::openapi,
         crate::routes::proxy_apis::get,
         crate::routes::proxy_apis::update,
         crate::routes::proxy_apis::delete,
+        crate::routes::proxy_apis::set_mock,
+        crate::routes::proxy_apis::set_maintenance,
+        crate::routes::proxy_apis::set_grpc_method,
+        crate::routes::tenants::list,
+        crate::routes::tenants::create,
+        crate::routes::tenants::set_suspension,
+        crate::routes::tenants::set_email_verification,
+        crate::routes::tenants::set_registration,
+        crate::routes::route_table::list,
+        crate::routes::blue_green::set_blue_green,
+        crate::routes::blue_green::cutover,
+        crate::routes::blue_green::rollback,
+        crate::routes::capture::get,
+        crate::routes::capture::set,
+        crate::routes::capture::delete,
+        crate::routes::observability::bundle,
+        crate::routes::system::config,
+        crate::routes::revisions::timeline,
+        crate::routes::jobs::list,
+        crate::routes::jobs::get,
+        crate::routes::jobs::cancel,
+        crate::routes::jobs::import_proxy_apis,
+        crate::routes::jobs::export_tenant,
+        crate::routes::jobs::revoke_api_keys,
+        crate::routes::jobs::reencrypt_columns,
+        crate::routes::encryption::seal,
+        crate::routes::cache::stats,
+        crate::routes::cache::flush,
+        crate::routes::backups::list,
+        crate::routes::backups::create,
+        crate::routes::backups::get,
+        crate::routes::backups::restore,
+        crate::routes::webhooks::list,
+        crate::routes::webhooks::create,
+        crate::routes::webhooks::get,
+        crate::routes::webhooks::update,
+        crate::routes::webhooks::delete,
+        crate::routes::webhooks::deliveries,
+        crate::routes::webhooks::emit,
+        crate::routes::feature_flags::list,
+        crate::routes::feature_flags::create,
+        crate::routes::feature_flags::get,
+        crate::routes::feature_flags::update,
+        crate::routes::feature_flags::delete,
+        crate::routes::feature_flags::set_override,
+        crate::routes::feature_flags::remove_override,
+        crate::routes::feature_flags::evaluate,
+        crate::routes::feature_flags::rules,
+        crate::routes::jwt_keys::list,
+        crate::routes::jwt_keys::rotate,
+        crate::routes::jwt_keys::retire,
+        crate::routes::expiry::list,
+        crate::routes::stats::stats,
+        crate::routes::request_logs::list,
+        crate::routes::request_logs::stream,
+        crate::routes::request_logs::replay,
+        crate::routes::rollups::query,
+        crate::routes::key_usage::usage,
+        crate::routes::reports::top,
+        crate::routes::reports::tenant_report,
+        crate::routes::log_level::get,
+        crate::routes::log_level::set,
+        crate::routes::profiling::profile,
+        crate::routes::profiling::heap,
+        crate::routes::dev::seed,
     ),
     components(
         schemas(
             HealthResponse,
             RegisterRequest,
             LoginRequest,
+            AcceptInviteRequest,
+            InviteRequest,
             ApiKeyRecordDoc,
+            ApiKeyRecordPageDoc,
             CreateProxyApiInputDoc,
             UpdateProxyApiInputDoc,
+            ProxyApiDoc,
+            ProxyApiPageDoc,
+            TenantDoc,
+            TenantPageDoc,
+            RouteDoc,
+            RoutePageDoc,
+            RequestLogDoc,
+            RouteCaptureDoc,
+            StatusChangeDoc,
+            HeaderChangeDoc,
+            FieldChangeDoc,
+            BodyDiffDoc,
+            ReplayDiffDoc,
+            ReplayedResponseDoc,
+            ReplayResultDoc,
+            RouteCutoverDoc,
+            ApiAuthInfoDoc,
+            ApiRecordInputDoc,
+            ApiRecordDoc,
+            ApiRecordPageDoc,
+            WebhookDoc,
+            CreatedWebhookDoc,
+            WebhookPageDoc,
+            WebhookDeliveryDoc,
+            WebhookDeliveryPageDoc,
+            FeatureFlagDoc,
+            FeatureFlagOverrideDoc,
+            FeatureFlagDetailDoc,
+            FlagEvaluationDoc,
+            JwtSigningKeyDoc,
+            JobProgressDoc,
+            JobInfoDoc,
+            BackupSummaryDoc,
+            BackupPageDoc,
+            RestoreReportDoc,
+            MetricsPointDoc,
+            KeyUsageDoc,
+            TopReportDoc,
+            TopEntryDoc,
+            TenantReportDoc,
+            KeyRouteUsageDoc,
+            LogFilterStateDoc,
+            ReadinessCheckDoc,
+            ReadinessDoc,
+            crate::errors::JsonApiErrorBody,
+            crate::errors::JsonApiErrorItem,
+            crate::routes::admin::ApiKeyRecord,
+            crate::routes::auth::RegisterOutput,
+            crate::routes::auth::LoginOutput,
+            crate::routes::auth::MeOutput,
+            crate::routes::auth::SessionOutput,
+            crate::routes::auth::VerifyOutput,
+            crate::routes::auth::ResendVerificationInput,
+            crate::routes::auth::AcceptInviteOutput,
+            crate::routes::signup::SignupRequest,
+            crate::routes::signup::SignupOutput,
+            crate::routes::auth::InvitationOutput,
+            crate::routes::auth::ApiKeyOutput,
+            crate::routes::cache::CacheStatsOutput,
+            crate::routes::expiry::ExpiringOutput,
+            crate::routes::revisions::TimelineOutput,
+            crate::routes::system::ConfigOutput,
+            crate::routes::webhooks::EmitEventOutput,
+            crate::routes::dev::SeedInput,
+            crate::routes::proxy_apis::SetMockInput,
+            crate::routes::proxy_apis::SetMaintenanceInput,
+            crate::routes::proxy_apis::SetGrpcMethodInput,
+            crate::routes::tenants::CreateTenantInput,
+            crate::routes::tenants::SetSuspensionInput,
+            crate::routes::tenants::SetEmailVerificationInput,
+            crate::routes::tenants::SetRegistrationInput,
+            crate::routes::blue_green::SetBlueGreenInput,
+            crate::routes::blue_green::CutoverInput,
+            crate::routes::request_logs::ReplayInput,
+            crate::routes::capture::SetCaptureInputDoc,
+            crate::routes::jobs::ImportProxyApisInput,
+            crate::routes::jobs::RevokeApiKeysInput,
+            crate::routes::webhooks::CreateWebhookInputDoc,
+            crate::routes::webhooks::UpdateWebhookInputDoc,
+            crate::routes::webhooks::EmitEventInput,
+            crate::routes::feature_flags::CreateFlagInputDoc,
+            crate::routes::feature_flags::UpdateFlagInputDoc,
+            crate::routes::feature_flags::SetOverrideInput,
+            crate::routes::jwt_keys::RotateKeyInput,
+            crate::routes::encryption::SealInput,
+            crate::routes::encryption::SealOutput,
+            crate::routes::log_level::LogLevelInput,
         )
     ),
+    modifiers(&ShortSchemaRefs),
     tags(
         (name = "health"),
         (name = "auth"),
@@ -63,4 +765,127 @@ pub struct UpdateProxyApiInputDoc {
         (name = "proxy")
     )
 )]
-pub struct ApiDoc;
\ No newline at end of file
+pub struct ApiDoc;
+
+/// utoipa 把 `body = crate::openapi::X` 生成为 `#/components/schemas/crate.openapi.X`，
+/// 而 components 中注册名是 `X`；统一截掉模块前缀，保证生成的客户端能解析引用
+struct ShortSchemaRefs;
+
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+fn shorten_refs(v: &mut serde_json::Value) {
+    match v {
+        serde_json::Value::Object(map) => {
+            if let Some(serde_json::Value::String(r)) = map.get_mut("$ref") {
+                if let Some(name) = r.strip_prefix(SCHEMA_REF_PREFIX) {
+                    if let Some((_, short)) = name.rsplit_once('.') {
+                        *r = format!("{}{}", SCHEMA_REF_PREFIX, short);
+                    }
+                }
+            }
+            map.values_mut().for_each(shorten_refs);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(shorten_refs),
+        _ => {}
+    }
+}
+
+impl Modify for ShortSchemaRefs {
+    fn modify(&self, openapi: &mut OpenApiSpec) {
+        let Ok(mut value) = serde_json::to_value(&*openapi) else { return };
+        shorten_refs(&mut value);
+        if let Ok(spec) = serde_json::from_value(value) {
+            *openapi = spec;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::collections::HashSet;
+
+    fn spec() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    }
+
+    fn collect_refs(v: &Value, out: &mut Vec<String>) {
+        match v {
+            Value::Object(map) => {
+                if let Some(Value::String(r)) = map.get("$ref") {
+                    out.push(r.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, out));
+            }
+            Value::Array(items) => items.iter().for_each(|v| collect_refs(v, out)),
+            _ => {}
+        }
+    }
+
+    /// routes.rs 中注册的 (方法, OpenAPI 路径)；`/api/*path` 由 proxy_api 配置驱动，见 /admin/proxy-apis/openapi.json
+    fn registered_routes() -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        for line in include_str!("routes.rs").lines() {
+            let Some(rest) = line.trim().strip_prefix(".route(\"") else { continue };
+            let Some((path, handlers)) = rest.split_once('"') else { continue };
+            if path.starts_with("/api/") {
+                continue;
+            }
+            let path: Vec<String> = path
+                .split('/')
+                .map(|seg| seg.strip_prefix(':').map(|p| format!("{{{}}}", p)).unwrap_or_else(|| seg.to_string()))
+                .collect();
+            let path = path.join("/");
+            for method in ["get", "post", "put", "delete", "patch"] {
+                let call = format!("{}(", method);
+                let found = handlers.match_indices(&call).any(|(i, _)| i == 0 || !handlers.as_bytes()[i - 1].is_ascii_alphanumeric() && handlers.as_bytes()[i - 1] != b'_');
+                if found {
+                    out.push((method.to_string(), path.clone()));
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn every_schema_reference_resolves() {
+        let spec = spec();
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        assert!(!refs.is_empty());
+        for r in refs {
+            let name = r.strip_prefix("#/components/schemas/").unwrap_or_else(|| panic!("unexpected ref {}", r));
+            assert!(spec["components"]["schemas"][name].is_object(), "dangling schema reference {}", r);
+        }
+    }
+
+    #[test]
+    fn every_registered_route_is_documented() {
+        let spec = spec();
+        let routes = registered_routes();
+        assert!(routes.len() > 50, "failed to parse routes.rs");
+        let missing: Vec<_> = routes.iter().filter(|(m, p)| !spec["paths"][p.as_str()][m.as_str()].is_object()).collect();
+        assert!(missing.is_empty(), "routes missing from openapi.rs: {:?}", missing);
+    }
+
+    #[test]
+    fn operations_have_unique_ids_and_typed_success_bodies() {
+        let spec = spec();
+        let mut ids = HashSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for (method, op) in item.as_object().unwrap() {
+                let id = op["operationId"].as_str().unwrap_or_else(|| panic!("{} {} has no operationId", method, path));
+                assert!(ids.insert(id.to_string()), "duplicate operationId {}", id);
+                let responses = op["responses"].as_object().unwrap();
+                assert!(!responses.is_empty(), "{} {} has no responses", method, path);
+                // 返回 JSON 的成功响应须声明 body；204 与非 JSON 响应（pprof、livez）除外
+                for (status, resp) in responses {
+                    if let Some(json) = resp["content"].get("application/json") {
+                        assert!(json["schema"].is_object(), "{} {} {} has empty schema", method, path, status);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! /admin 与 /auth 的按调用方限流，超出返回 429 + `Retry-After`。
+//! 调用方依次取 JWT subject（全局 Bearer 中间件写入的 claims）、`X-API-Key`、客户端 IP；
+//! 登录与注册不带凭据，按客户端 IP 走更严格的单独令牌桶。
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use common::rate_limit::KeyedLimiter;
+use configs::AdminRateLimitConfig;
+use service::auth::domain::TokenClaims;
+use tracing::warn;
+
+use crate::errors::JsonApiError;
+
+pub struct AdminRateLimiter {
+    general: KeyedLimiter,
+    login: KeyedLimiter,
+}
+
+impl AdminRateLimiter {
+    pub fn new(cfg: &AdminRateLimitConfig) -> Self {
+        Self {
+            general: KeyedLimiter::per_second(cfg.requests_per_second, cfg.burst),
+            login: KeyedLimiter::per_minute(cfg.login_per_minute, cfg.login_burst),
+        }
+    }
+
+    /// 本请求使用的桶（名称、key、限流器）
+    fn bucket_for(&self, req: &Request) -> (&'static str, String, &KeyedLimiter) {
+        if is_login(req.uri().path()) {
+            ("login", format!("ip:{}", client_ip(req).unwrap_or_default()), &self.login)
+        } else {
+            ("general", principal(req), &self.general)
+        }
+    }
+}
+
+fn is_login(path: &str) -> bool {
+    path == "/auth/login" || path == "/auth/register" || path == "/auth/verify/resend" || path == "/auth/accept-invite" || path == "/signup"
+}
+
+fn client_ip(req: &Request) -> Option<String> {
+    req.extensions().get::<ConnectInfo<SocketAddr>>().map(|ci| ci.0.ip().to_string())
+}
+
+/// 限流 key；API Key 只保留哈希，不在内存里按明文索引
+fn principal(req: &Request) -> String {
+    if let Some(claims) = req.extensions().get::<TokenClaims>() {
+        return format!("user:{}", claims.sub);
+    }
+    if let Some(key) = req.headers().get("X-API-Key").and_then(|v| v.to_str().ok()).filter(|k| !k.is_empty()) {
+        return format!("key:{:016x}", common::lock::lock_key(key));
+    }
+    client_ip(req).map(|ip| format!("ip:{}", ip)).unwrap_or_else(|| "anonymous".to_string())
+}
+
+pub async fn limit(State(limiter): State<Arc<AdminRateLimiter>>, req: Request, next: Next) -> Response {
+    let (bucket, key, keyed) = limiter.bucket_for(&req);
+    match keyed.check(&key) {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            warn!(path = %req.uri().path(), principal = %key, bucket, retry_after, "admin request rate limited");
+            let mut resp = JsonApiError::new(StatusCode::TOO_MANY_REQUESTS, "Too Many Requests", Some(format!("retry after {}s", retry_after))).into_response();
+            resp.headers_mut().insert(RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+            resp
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> Request {
+        Request::builder().uri(path).body(axum::body::Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn principal_prefers_subject_then_key_then_ip() {
+        let mut req = request("/admin/stats");
+        req.extensions_mut().insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+        assert_eq!(principal(&req), "ip:10.0.0.1");
+        req.headers_mut().insert("X-API-Key", HeaderValue::from_static("secret"));
+        assert!(principal(&req).starts_with("key:") && !principal(&req).contains("secret"));
+        req.extensions_mut().insert(TokenClaims { sub: "alice@example.com".into(), uid: None, tid: None, exp: 0, iss: None, aud: None, sid: None });
+        assert_eq!(principal(&req), "user:alice@example.com");
+        assert_eq!(principal(&request("/admin/stats")), "anonymous");
+    }
+
+    #[test]
+    fn login_bucket_is_stricter() {
+        let cfg = AdminRateLimitConfig { login_per_minute: 1, login_burst: 1, ..Default::default() };
+        let limiter = AdminRateLimiter::new(&cfg);
+        let check = |path: &str| {
+            let req = request(path);
+            let (bucket, key, keyed) = limiter.bucket_for(&req);
+            (bucket, keyed.check(&key))
+        };
+        assert_eq!(check("/auth/login"), ("login", Ok(())));
+        let (_, limited) = check("/auth/register");
+        assert!(limited.unwrap_err() > std::time::Duration::from_secs(59));
+        assert_eq!(check("/admin/stats"), ("general", Ok(())));
+        assert_eq!(check("/auth/me"), ("general", Ok(())));
+    }
+}
@@ -2,62 +2,165 @@ pub mod auth;
 pub mod admin;
 pub mod apis;
 pub mod proxy_apis;
+pub mod cache;
+pub mod backups;
+pub mod dev;
+pub mod demo;
+pub mod tenants;
+pub mod observability;
+pub mod blue_green;
+pub mod system;
+pub mod revisions;
+pub mod jobs;
+pub mod webhooks;
+pub mod expiry;
+pub mod stats;
+pub mod rollups;
+pub mod key_usage;
+pub mod reports;
+pub mod log_level;
+pub mod profiling;
+pub mod feature_flags;
+pub mod jwt_keys;
+pub mod encryption;
+pub mod route_table;
+pub mod request_logs;
+pub mod capture;
+pub mod signup;
 
-use std::sync::Arc;
 
 use axum::{
-    extract::Path,
-    routing::{delete, get, post},
+    extract::State,
+    http::StatusCode,
+    routing::{any, delete, get, post},
     Json, Router,
 };
-use service::file::admin_kv_store::ApiKeysStore;
+use serde::Serialize;
+use service::storage::health::StoreHealth;
 use tower_http::{
     cors::CorsLayer,
     services::{ServeDir, ServeFile},
-    trace::{TraceLayer, DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, DefaultOnFailure},
+    trace::{TraceLayer, DefaultOnRequest, DefaultOnResponse, DefaultOnFailure},
 };
 use tracing::Level;
 use axum::middleware;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use common::{posts, types::Health};
+use common::diagnostics::{redis_check, timed, Check, CheckStatus, Readiness};
+use common::types::Health;
+use sea_orm::DatabaseConnection;
 
 use self::auth::ServerState;
-use crate::errors::ApiError;
 
 #[utoipa::path(get, path = "/health", tag = "health", responses((status = 200, description = "Service OK", body = crate::openapi::HealthResponse)))]
 pub async fn health() -> Json<Health> {
     Json(Health { status: "ok" })
 }
 
-async fn get_posts() -> Result<Json<serde_json::Value>, ApiError> {
-    let json = posts::fetch_posts()
-        .await
-        .map_err(|e| ApiError(e.to_string()))?;
-    Ok(Json(json))
+/// 存活检查：不访问任何依赖
+#[utoipa::path(get, path = "/livez", tag = "health", responses((status = 200, description = "Process alive")))]
+pub async fn livez() -> &'static str {
+    "OK"
 }
 
-async fn get_post(Path(id): Path<u32>) -> Result<Json<serde_json::Value>, ApiError> {
-    let json = posts::fetch_post(id)
-        .await
-        .map_err(|e| ApiError(e.to_string()))?;
-    Ok(Json(json))
+#[derive(Debug, Serialize)]
+pub struct ReadyOutput {
+    /// ready / not_ready
+    pub status: &'static str,
+    pub checks: Vec<Check>,
+    pub stores: Vec<StoreHealth>,
+    /// JSON 文件存储在多副本部署下不共享
+    pub multi_replica_warning: bool,
+}
+
+async fn database_check(db: &DatabaseConnection) -> Check {
+    match db.ping().await {
+        Ok(()) => Check::ok("database", None),
+        Err(e) => Check::fail("database", e.to_string()),
+    }
+}
+
+async fn migrations_check(db: &DatabaseConnection) -> Check {
+    use migration::MigratorTrait;
+    match migration::Migrator::get_pending_migrations(db).await {
+        Ok(pending) if pending.is_empty() => Check::ok("migrations", None),
+        Ok(pending) => Check::fail("migrations", format!("pending: {}", pending.iter().map(|m| m.name().to_string()).collect::<Vec<_>>().join(", "))),
+        Err(e) => Check::fail("migrations", e.to_string()),
+    }
+}
+
+/// 重新解析 config.toml + 环境变量，确认当前配置仍然有效（重启前可发现问题）
+async fn config_check() -> Check {
+    match configs::resolve_default().and_then(|r| r.config.database.validate()) {
+        Ok(()) => Check::ok("config", None),
+        Err(e) => Check::fail("config", e.to_string()),
+    }
+}
+
+/// 服务端没有上游也能提供管理台，只提示不判定为未就绪
+async fn upstreams_check(db: &DatabaseConnection) -> Check {
+    use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+    match models::upstream::Entity::find().filter(models::upstream::Column::Active.eq(true)).count(db).await {
+        Ok(0) => Check::new("upstreams", CheckStatus::Warn, Some("no active upstreams".to_string())),
+        Ok(n) => Check::ok("upstreams", Some(format!("{} active", n))),
+        Err(e) => Check::fail("upstreams", e.to_string()),
+    }
+}
+
+/// 就绪检查：数据库连通、迁移已执行、配置有效、admin 存储可写、上游与 Redis（如配置）；任一失败或正在停机返回 503
+#[utoipa::path(get, path = "/readyz", tag = "health", responses((status = 200, description = "Ready, with per-check detail", body = crate::openapi::ReadinessDoc), (status = 503, description = "A check failed")))]
+pub async fn readyz(State(state): State<ServerState>) -> (StatusCode, Json<ReadyOutput>) {
+    let stores = vec![state.admin_kv_store.health().await, state.api_mgmt_store.health().await];
+    let mut checks = vec![
+        timed("database", database_check(&state.db)).await,
+        timed("migrations", migrations_check(&state.db)).await,
+        timed("config", config_check()).await,
+        timed("upstreams", upstreams_check(&state.db)).await,
+        redis_check().await,
+    ];
+    for s in &stores {
+        let name = format!("store:{}", s.name);
+        checks.push(if s.is_healthy() { Check::ok(&name, None) } else { Check::fail(&name, s.last_error.clone().unwrap_or_else(|| "not writable".to_string())) });
+    }
+    if state.shutdown.is_draining() {
+        checks.push(Check::fail("shutdown", "draining connections"));
+    }
+    let readiness = Readiness::new(checks);
+    let out = ReadyOutput {
+        status: readiness.status,
+        checks: readiness.checks.clone(),
+        stores,
+        multi_replica_warning: service::storage::health::declared_replicas() > 1,
+    };
+    (readiness.status_code(), Json(out))
+}
+
+/// Prometheus 指标（默认 registry）；只由 `[server] metrics_addr` 的内部端口提供，不挂在公开路由上
+pub fn encode_metrics() -> (StatusCode, String) {
+    use prometheus::Encoder;
+    let mut buf = Vec::new();
+    match prometheus::TextEncoder::new().encode(&prometheus::gather(), &mut buf) {
+        Ok(()) => (StatusCode::OK, String::from_utf8_lossy(&buf).into_owned()),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("metrics encode error: {e}")),
+    }
 }
 
 /// Build the full application router, including public, protected, and admin routes
-pub fn build_router(_admin_store: Arc<ApiKeysStore>, cors: CorsLayer, state: ServerState) -> Router {
+pub fn build_router(cors: CorsLayer, state: ServerState) -> Router {
     let static_dir = ServeDir::new("frontend").fallback(ServeFile::new("frontend/index.html"));
 
     // Public routes (static + health)
     let public = Router::new()
         .nest_service("/", static_dir)
-        .route("/health", get(health));
+        .route("/health", get(health))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .with_state(state.clone());
 
-    // Protected API routes (API Key required)
+    // Protected API routes (API Key required)：由 proxy_api 配置驱动（如 /api/posts -> echo 上游）
     let api = Router::new()
-        .route("/api/posts", get(get_posts))
-        .route("/api/posts/:id", get(get_post))
+        .route("/api/*path", any(demo::forward))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             admin::require_api_key_state,
@@ -67,20 +170,118 @@ pub fn build_router(_admin_store: Arc<ApiKeysStore>, cors: CorsLayer, state: Ser
     // Auth routes (cookie-based)
     let auth_routes = Router::new()
         .route("/auth/register", post(auth::register))
+        .route("/auth/verify", get(auth::verify_email))
+        .route("/auth/verify/resend", post(auth::resend_verification))
+        .route("/auth/accept-invite", post(auth::accept_invite))
         .route("/auth/login", post(auth::login))
-        .route("/auth/logout", post(auth::logout));
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/me", get(auth::me))
+        .route("/auth/sessions", get(auth::list_sessions))
+        .route("/auth/sessions/:id", delete(auth::revoke_session))
+        .route("/auth/api-keys", get(auth::list_own_api_keys));
+    // 自助开通租户：config.toml [signup] 开启时才注册
+    let auth_routes = if state.config.config.signup.enabled {
+        auth_routes.route("/signup", post(signup::signup))
+    } else {
+        auth_routes
+    };
 
     // Admin routes
     let admin_routes = Router::new()
         .route("/admin/api-keys", get(admin::list_api_keys).post(admin::set_api_key))
-        .route("/admin/api-keys/:user", delete(admin::delete_api_key))
+        // 同一位置的路径参数需同名：`:id` 在 DELETE 中是 KV 存储的用户名，在 usage 中是 api_key 表的 ID
+        .route("/admin/api-keys/:id", delete(admin::delete_api_key))
+        .route("/admin/api-keys/:id/usage", get(key_usage::usage))
+        .route("/admin/api-keys/revoke", post(jobs::revoke_api_keys))
+        .route("/admin/invitations", get(auth::list_invitations).post(auth::create_invitation))
+        .route("/admin/invitations/:id", delete(auth::revoke_invitation))
+        .route("/admin/users/:id/sessions", get(auth::admin_user_sessions))
+        .route("/admin/users/:id/api-keys", get(auth::admin_user_api_keys))
+        .route("/admin/encryption/reencrypt", post(jobs::reencrypt_columns))
+        .route("/admin/encryption/seal", post(encryption::seal))
         // API 管理（CRUD）
         .route("/admin/apis", get(apis::list_apis).post(apis::create_api))
         .route("/admin/apis/:id", get(apis::get_api).put(apis::update_api).delete(apis::delete_api))
         // Proxy API 管理（数据库驱动 CRUD）
         .route("/admin/proxy-apis", get(proxy_apis::list).post(proxy_apis::create))
+        .route("/admin/proxy-apis/import", post(jobs::import_proxy_apis))
+        .route("/admin/proxy-apis/openapi.json", get(proxy_apis::openapi))
         .route("/admin/proxy-apis/:id", get(proxy_apis::get).put(proxy_apis::update).delete(proxy_apis::delete))
-        .with_state(state.clone());
+        .route("/admin/proxy-apis/:id/mock", axum::routing::put(proxy_apis::set_mock))
+        .route("/admin/proxy-apis/:id/maintenance", axum::routing::put(proxy_apis::set_maintenance))
+        .route("/admin/proxy-apis/:id/grpc", axum::routing::put(proxy_apis::set_grpc_method))
+        .route("/admin/tenants", get(tenants::list).post(tenants::create))
+        .route("/admin/tenants/:id/suspension", axum::routing::put(tenants::set_suspension))
+        .route("/admin/tenants/:id/email-verification", axum::routing::put(tenants::set_email_verification))
+        .route("/admin/tenants/:id/registration", axum::routing::put(tenants::set_registration))
+        .route("/admin/tenants/:id/export", post(jobs::export_tenant))
+        .route("/admin/tenants/:id/report", get(reports::tenant_report))
+        .route("/admin/routes", get(route_table::list))
+        .route("/admin/routes/:id/blue-green", axum::routing::put(blue_green::set_blue_green))
+        .route("/admin/routes/:id/cutover", post(blue_green::cutover))
+        .route("/admin/routes/:id/rollback", post(blue_green::rollback))
+        .route("/admin/routes/:id/capture", get(capture::get).put(capture::set).delete(capture::delete))
+        .route("/admin/observability/bundle", get(observability::bundle))
+        .route("/admin/system/config", get(system::config))
+        .route("/admin/config-revisions", get(revisions::timeline))
+        // 后台任务：进度查询与取消
+        .route("/admin/jobs", get(jobs::list))
+        .route("/admin/jobs/:id", get(jobs::get).delete(jobs::cancel))
+        .route("/admin/cache", get(cache::stats))
+        .route("/admin/cache/flush", post(cache::flush))
+        // 配置快照与恢复
+        .route("/admin/config-backups", get(backups::list).post(backups::create))
+        .route("/admin/config-backups/:id", get(backups::get))
+        .route("/admin/config-backups/:id/restore", post(backups::restore))
+        // 租户 webhook 订阅与投递记录
+        .route("/admin/webhooks", get(webhooks::list).post(webhooks::create))
+        .route("/admin/webhooks/:id", get(webhooks::get).put(webhooks::update).delete(webhooks::delete))
+        .route("/admin/webhooks/:id/deliveries", get(webhooks::deliveries))
+        .route("/admin/webhook-events", post(webhooks::emit))
+        // 特性开关：默认值、百分比灰度与租户覆盖
+        .route("/admin/feature-flags", get(feature_flags::list).post(feature_flags::create))
+        .route("/admin/feature-flags/:name", get(feature_flags::get).put(feature_flags::update).delete(feature_flags::delete))
+        .route("/admin/feature-flags/:name/overrides/:tenant_id", axum::routing::put(feature_flags::set_override).delete(feature_flags::remove_override))
+        .route("/admin/feature-flags/:name/evaluate", get(feature_flags::evaluate))
+        .route("/admin/feature-flag-rules", get(feature_flags::rules))
+        .route("/admin/jwt-keys", get(jwt_keys::list))
+        .route("/admin/jwt-keys/rotate", post(jwt_keys::rotate))
+        .route("/admin/jwt-keys/:kid", axum::routing::delete(jwt_keys::retire))
+        .route("/admin/expiring", get(expiry::list))
+        .route("/admin/stats", get(stats::stats))
+        .route("/admin/request-logs", get(request_logs::list))
+        .route("/admin/request-logs/stream", get(request_logs::stream))
+        .route("/admin/request-logs/:id/replay", post(request_logs::replay))
+        .route("/admin/metrics/query", get(rollups::query))
+        .route("/admin/reports/top", get(reports::top))
+        .route("/admin/log-level", get(log_level::get).put(log_level::set));
+    // 开发模式专用：生产环境不注册
+    let admin_routes = if state.dev.enabled {
+        admin_routes.route("/admin/dev/seed", post(dev::seed))
+    } else {
+        admin_routes
+    };
+    // 剖析端点：config.toml [profiling] 开启时才注册
+    let admin_routes = if state.config.config.profiling.enabled {
+        admin_routes
+            .route("/admin/debug/pprof/profile", get(profiling::profile))
+            .route("/admin/debug/pprof/heap", get(profiling::heap))
+    } else {
+        admin_routes
+    }
+    .with_state(state.clone());
+
+    // /admin 与 /auth 按调用方限流（config.toml [admin_rate_limit]）
+    let rate_limit_cfg = &state.config.config.admin_rate_limit;
+    let (auth_routes, admin_routes) = if rate_limit_cfg.enabled {
+        let limiter = std::sync::Arc::new(crate::rate_limit::AdminRateLimiter::new(rate_limit_cfg));
+        (
+            auth_routes.route_layer(middleware::from_fn_with_state(std::sync::Arc::clone(&limiter), crate::rate_limit::limit)),
+            admin_routes.route_layer(middleware::from_fn_with_state(limiter, crate::rate_limit::limit)),
+        )
+    } else {
+        (auth_routes, admin_routes)
+    };
 
     // OpenAPI doc
     let openapi = crate::openapi::ApiDoc::openapi();
@@ -101,12 +302,16 @@ pub fn build_router(_admin_store: Arc<ApiKeysStore>, cors: CorsLayer, state: Ser
         .layer(cors)
         .layer(
             TraceLayer::new_for_http()
-                // 每次请求创建 span，包含方法和路径等，日志级别为 INFO
-                .make_span_with(
-                    DefaultMakeSpan::new()
-                        .level(Level::INFO)
-                        .include_headers(false),
-                )
+                // 每次请求创建 span，包含方法和路径等，日志级别为 INFO；查询串中的敏感参数先脱敏
+                .make_span_with(|req: &axum::http::Request<axum::body::Body>| {
+                    let uri = req.uri().to_string();
+                    tracing::info_span!(
+                        "request",
+                        method = %req.method(),
+                        uri = %common::redaction::global().redact_uri(&uri),
+                        version = ?req.version(),
+                    )
+                })
                 // 请求到达时打点
                 .on_request(
                     DefaultOnRequest::new()
@@ -1,27 +1,38 @@
-use axum::{extract::{Path, State, Request}, http::StatusCode, Json};
+use axum::{extract::{Path, Query, State, Request}, http::StatusCode, Json};
+use common::pagination::{Page, Pagination};
 use serde::{Deserialize, Serialize};
 use axum::middleware::Next;
 use axum::response::Response;
 
-use crate::routes::auth;
+use crate::{errors::JsonApiError, routes::auth};
 // use proper attribute form: #[utoipa::path] on handlers
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
 pub struct ApiKeyRecord {
     pub user: String,
     pub api_key: String,
 }
 
-#[utoipa::path(get, path = "/admin/api-keys", tag = "admin", responses((status = 200, description = "OK")))]
-pub async fn list_api_keys(State(state): State<auth::ServerState>) -> Json<Vec<ApiKeyRecord>> {
-    let store = state.admin_kv_store.clone();
-    let items = store
-        .list()
-        .await
-        .into_iter()
-        .map(|(user, key)| ApiKeyRecord { user, api_key: key })
-        .collect::<Vec<_>>();
-    Json(items)
+/// 分页列出 API key，按用户排序
+#[utoipa::path(
+    get, path = "/admin/api-keys", tag = "admin",
+    params(crate::routes::tenants::ListQuery),
+    responses(
+        (status = 200, description = "OK", body = crate::openapi::ApiKeyRecordPageDoc),
+        (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn list_api_keys(
+    State(state): State<auth::ServerState>,
+    Query(q): Query<crate::routes::tenants::ListQuery>,
+) -> Result<Json<Page<ApiKeyRecord>>, JsonApiError> {
+    let invalid = |detail: String| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(detail));
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page).map_err(|e| invalid(e.to_string()))?;
+    let page = state.admin_kv_store.list_page(opts).await.map_err(|e| match e {
+        service::errors::ServiceError::Pagination(_) => invalid(e.to_string()),
+        _ => JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error", Some(e.to_string())),
+    })?;
+    Ok(Json(page.map(|(user, api_key)| ApiKeyRecord { user, api_key })))
 }
 
 #[utoipa::path(post, path = "/admin/api-keys", tag = "admin", request_body = crate::openapi::ApiKeyRecordDoc, responses((status = 200, description = "OK"), (status = 400, description = "Bad Request")))]
@@ -40,6 +51,7 @@ pub async fn set_api_key(
     Ok(Json(serde_json::json!({"ok": true})))
 }
 
+#[utoipa::path(delete, path = "/admin/api-keys/{user}", tag = "admin", params(("user" = String, Path, description = "User the key belongs to")), responses((status = 204, description = "Deleted"), (status = 404, description = "Not Found")))]
 pub async fn delete_api_key(
     State(state): State<auth::ServerState>,
     Path(user): Path<String>,
@@ -84,13 +96,16 @@ pub async fn require_api_key_state(
 
     let key = match key {
         Some(k) if !k.trim().is_empty() => k,
-        _ => return Err(StatusCode::UNAUTHORIZED),
+        _ => {
+            tracing::warn!(target: "security", action = "api_key_missing", path = %req.uri().path(), "request without admin API key");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
     };
 
     if !store.contains_value(&key).await {
+        tracing::warn!(target: "security", action = "api_key_rejected", path = %req.uri().path(), "unknown admin API key");
         return Err(StatusCode::UNAUTHORIZED);
     }
 
     Ok(next.run(req).await)
 }
-// delete is not documented yet; can be added with #[utoipa::path]
\ No newline at end of file
@@ -1,16 +1,38 @@
-use axum::{extract::{Path, State}, http::StatusCode, Json};
-use service::file::api_management::{ApiRecord, ApiRecordInput};
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use common::pagination::{Page, Pagination};
+use service::admin::api_mgmt_store::{ApiRecord, ApiRecordInput};
 use uuid::Uuid;
 
-use crate::{errors::JsonApiError, routes::auth::ServerState};
+use crate::{errors::JsonApiError, routes::auth::ServerState, routes::tenants::ListQuery};
 
-/// 列出所有 API 记录
-pub async fn list_apis(State(state): State<ServerState>) -> Json<Vec<ApiRecord>> {
-    let store = state.api_mgmt_store.clone();
-    Json(store.list().await)
+/// 分页列出 API 记录，按创建时间排序
+#[utoipa::path(
+    get, path = "/admin/apis", tag = "admin",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "API records", body = crate::openapi::ApiRecordPageDoc),
+        (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn list_apis(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<ApiRecord>>, JsonApiError> {
+    let invalid = |detail: String| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(detail));
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page).map_err(|e| invalid(e.to_string()))?;
+    state.api_mgmt_store.list_page(opts).await.map(Json).map_err(|e| match e {
+        service::errors::ServiceError::Pagination(_) => invalid(e.to_string()),
+        _ => JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error", Some(e.to_string())),
+    })
 }
 
 /// 创建 API 记录
+#[utoipa::path(
+    post, path = "/admin/apis", tag = "admin",
+    request_body = crate::openapi::ApiRecordInputDoc,
+    responses(
+        (status = 200, description = "Created", body = crate::openapi::ApiRecordDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Internal Server Error", body = crate::errors::JsonApiErrorBody)
+    )
+)]
 pub async fn create_api(
     State(state): State<ServerState>,
     Json(input): Json<ApiRecordInput>,
@@ -25,6 +47,11 @@ pub async fn create_api(
 }
 
 /// 获取指定 API 记录
+#[utoipa::path(
+    get, path = "/admin/apis/{id}", tag = "admin",
+    params(("id" = Uuid, Path, description = "API record ID")),
+    responses((status = 200, description = "OK", body = crate::openapi::ApiRecordDoc), (status = 404, description = "Not Found"))
+)]
 pub async fn get_api(
     State(state): State<ServerState>,
     Path(id): Path<Uuid>,
@@ -37,6 +64,17 @@ pub async fn get_api(
 }
 
 /// 更新指定 API 记录
+#[utoipa::path(
+    put, path = "/admin/apis/{id}", tag = "admin",
+    params(("id" = Uuid, Path, description = "API record ID")),
+    request_body = crate::openapi::ApiRecordInputDoc,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::ApiRecordDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Internal Server Error", body = crate::errors::JsonApiErrorBody)
+    )
+)]
 pub async fn update_api(
     State(state): State<ServerState>,
     Path(id): Path<Uuid>,
@@ -53,6 +91,11 @@ pub async fn update_api(
 }
 
 /// 删除指定 API 记录
+#[utoipa::path(
+    delete, path = "/admin/apis/{id}", tag = "admin",
+    params(("id" = Uuid, Path, description = "API record ID")),
+    responses((status = 204, description = "Deleted"), (status = 404, description = "Not Found"), (status = 500, description = "Delete Failed"))
+)]
 pub async fn delete_api(
     State(state): State<ServerState>,
     Path(id): Path<Uuid>,
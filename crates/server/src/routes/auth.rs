@@ -1,22 +1,52 @@
-use axum::{Json, extract::{State, Request}, http::StatusCode, middleware::Next, response::Response};
-use axum_extra::extract::cookie::{Cookie, CookieJar};
+//! 注册、邀请加入、邮箱验证、登录、登出、当前用户、会话管理与全局 Bearer 校验中间件。
+//! 业务逻辑（校验、查重、密码哈希、签发与校验 JWT）都在 service::auth，这里只做 HTTP 适配。
+
+use axum::{Extension, Json, extract::{ConnectInfo, Path, Query, State, Request}, http::{HeaderMap, StatusCode}, middleware::Next, response::Response};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use configs::SameSitePolicy;
 use serde::{Deserialize, Serialize};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, ActiveModelTrait, Set};
+use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
-use service::{auth::{domain::{ LoginInput, RegisterInput}, service::{AuthConfig, AuthService}}, admin::{kv_store::AdminKvStore, api_mgmt_store::ApiManagementStore}};
+use service::{auth::{domain::{AcceptInviteInput, InvitationRecord, InviteInput, LoginInput, RegisterInput, SessionClient, SessionRecord, TokenClaims}, errors::AuthError, notify::WebhookVerificationNotifier, service::{AuthConfig, AuthService, SessionLimit, SessionLimitPolicy, TokenPolicy}}, admin::{kv_store::AdminKvStore, api_mgmt_store::ApiManagementStore}};
 use service::auth::repo::seaorm::SeaOrmAuthRepository;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use argon2::{Argon2, password_hash::{PasswordHasher, SaltString}};
-use rand::rngs::OsRng;
-use models::{user, user_credentials, tenant};
-use chrono::Utc;
-use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
-// use proper attribute form: #[utoipa::path] on handlers
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct ServerAuthConfig {
-    pub jwt_secret: String,
+    /// config.toml [auth]：签名密钥、令牌有效期、iss/aud 与 Cookie 属性（jwt_secret / cookie_secure 由启动流程按环境补齐）
+    pub settings: configs::AuthConfig,
+}
+
+impl ServerAuthConfig {
+    /// 设置 auth_token / csrf_token 共用的 Domain、Secure 与 Max-Age
+    fn apply_cookie_attrs(&self, cookie: &mut Cookie<'static>) {
+        cookie.set_path("/");
+        if let Some(domain) = &self.settings.cookie_domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie.set_secure(self.settings.cookie_secure.unwrap_or(false));
+        cookie.set_max_age(time::Duration::seconds(self.settings.access_token_ttl_secs as i64));
+    }
+
+    fn token_policy(&self) -> TokenPolicy {
+        TokenPolicy {
+            ttl: std::time::Duration::from_secs(self.settings.access_token_ttl_secs),
+            issuer: self.settings.issuer.clone(),
+            audience: self.settings.audience.clone(),
+        }
+    }
+
+    fn session_limit(&self) -> SessionLimit {
+        SessionLimit {
+            max_sessions: self.settings.max_sessions_per_user,
+            policy: match self.settings.session_limit_policy {
+                configs::SessionLimitPolicy::Reject => SessionLimitPolicy::Reject,
+                configs::SessionLimitPolicy::RevokeOldest => SessionLimitPolicy::RevokeOldest,
+            },
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -26,128 +56,397 @@ pub struct ServerState {
     pub admin_kv_store: std::sync::Arc<dyn AdminKvStore>,
     pub api_mgmt_store: std::sync::Arc<dyn ApiManagementStore>,
     pub proxy_api_svc: std::sync::Arc<service::proxy_api::service::ProxyApiService<service::proxy_api::repository::SeaOrmProxyApiRepository>>,
+    pub events: service::events::EventBus,
+    pub read_cache: std::sync::Arc<service::cache::ReadCache>,
+    pub backups: std::sync::Arc<service::backup::ConfigBackupService>,
+    /// 开发模式（config.toml [dev]）
+    pub dev: configs::DevConfig,
+    /// SLO 目标（config.toml [slo]）
+    pub slo: service::observability::SloTargets,
+    /// 启动时解析的生效配置及各值来源（GET /admin/system/config）
+    pub config: std::sync::Arc<configs::resolver::Resolved<configs::AppConfig>>,
+    /// 长耗时管理操作的后台任务（GET /admin/jobs/{id}）
+    pub jobs: std::sync::Arc<service::jobs::JobManager>,
+    /// gRPC-JSON 转码描述符（config.toml [grpc]）；未配置时为 None
+    pub grpc: Option<std::sync::Arc<service::proxy_api::grpc::GrpcTranscoder>>,
+    /// 租户 webhook 订阅与投递
+    pub webhooks: std::sync::Arc<service::webhooks::WebhookService>,
+    /// 凭据到期扫描（GET /admin/expiring）
+    pub expiry: std::sync::Arc<service::expiry::ExpiryScanner>,
+    /// 管理台统计（GET /admin/stats，短 TTL 缓存）
+    pub stats: std::sync::Arc<service::stats::StatsService>,
+    /// `/api/*` 转发的上游客户端（config.toml [http_client]）
+    pub upstream: std::sync::Arc<crate::routes::demo::UpstreamClients>,
+    /// 优雅停机状态；停机开始后 /readyz 返回 not_ready
+    pub shutdown: std::sync::Arc<crate::shutdown::Shutdown>,
+    /// 按租户灰度的特性开关（/admin/feature-flags）
+    pub feature_flags: std::sync::Arc<service::feature_flags::FeatureFlagService>,
+    /// 轮换签名密钥（/admin/jwt-keys）
+    pub jwt_keys: std::sync::Arc<service::jwt_keys::JwtKeyService>,
+    /// `/api/*` 请求完成后的实时日志（GET /admin/request-logs/stream）
+    pub request_log_stream: service::request_log_stream::RequestLogStream,
+    /// 请求日志存储（config.toml [request_log]：Postgres 或 ClickHouse）
+    pub request_logs: std::sync::Arc<dyn service::request_log_repo::RequestLogRepository>,
+    /// 按路由抽样捕获请求/响应体（config.toml [capture]，/admin/routes/{id}/capture）
+    pub capture: std::sync::Arc<service::capture::CaptureService>,
+    /// 与 now 比较的判断（维护窗口、到期扫描、验证令牌有效期）使用的时钟；测试可注入 MockClock
+    pub clock: common::time::SharedClock,
 }
 
 // RegisterInput is provided by service::auth::domain
 
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 pub struct RegisterOutput { pub user_id: Uuid }
 
 // LoginInput is provided by service::auth::domain
 
-#[derive(Serialize)]
-pub struct MeOutput { pub user_id: Uuid, pub email: String, pub name: String }
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct VerifyOutput { pub user_id: Uuid, pub email: String, pub status: String }
 
-#[derive(Serialize)]
-pub struct LoginOutput { pub user_id: Uuid, pub email: String, pub name: String, pub token: String }
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct VerifyQuery {
+    /// 经 `user.verification_requested` webhook 下发的验证令牌
+    pub token: String,
+}
 
-// Token creation handled by AuthService
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ResendVerificationInput { pub tenant_id: Uuid, pub email: String }
 
-#[utoipa::path(post, path = "/auth/register", tag = "auth", request_body = crate::openapi::RegisterRequest, responses((status = 200, description = "Registered"), (status = 400, description = "Bad Request"), (status = 409, description = "Conflict")))]
-pub async fn register(State(state): State<ServerState>, Json(input): Json<RegisterInput>) -> Result<Json<RegisterOutput>, (StatusCode, String)> {
-    // Validate using models helpers
-    if let Err(e) = user::validate_email(&input.email) { return Err((StatusCode::BAD_REQUEST, e.to_string())); }
-    if let Err(e) = user::validate_name(&input.name) { return Err((StatusCode::BAD_REQUEST, e.to_string())); }
-    if input.password.len() < 8 { return Err((StatusCode::BAD_REQUEST, "password too short (>=8)".into())); }
-
-    // Ensure not duplicated for tenant + email
-    let existing = user::Entity::find()
-        .filter(user::Column::TenantId.eq(input.tenant_id))
-        .filter(user::Column::Email.eq(input.email.clone()))
-        .one(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    if existing.is_some() { return Err((StatusCode::CONFLICT, "user already exists".into())); }
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MeOutput { pub user_id: Uuid, pub email: String, pub name: String, pub role: String }
 
-    // Ensure tenant exists (FK constraint). Create if missing with generated name.
-    let maybe_tenant = tenant::Entity::find_by_id(input.tenant_id)
-        .one(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    if maybe_tenant.is_none() {
-        let am = tenant::ActiveModel {
-            id: Set(input.tenant_id),
-            name: Set(format!("auto-tenant-{}", input.tenant_id)),
-            created_at: Set(Utc::now().into()),
-        };
-        am.insert(&state.db)
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct AcceptInviteOutput { pub user_id: Uuid, pub tenant_id: Uuid, pub email: String, pub role: String }
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct InvitationOutput {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub accepted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub accepted_user_id: Option<Uuid>,
+    /// 邀请令牌，仅在创建时返回一次（库中只保存哈希），由管理员转交被邀请人
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl InvitationOutput {
+    fn new(i: InvitationRecord, token: Option<String>) -> Self {
+        Self {
+            id: i.id,
+            tenant_id: i.tenant_id,
+            email: i.email,
+            role: i.role,
+            created_at: i.created_at,
+            expires_at: i.expires_at,
+            accepted_at: i.accepted_at,
+            accepted_user_id: i.accepted_user_id,
+            token,
+        }
     }
+}
 
-    // Create user
-    let created = user::create(&state.db, input.tenant_id, &input.email, &input.name)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
-
-    // Hash password and upsert credentials
-    let salt = SaltString::generate(&mut OsRng);
-    let argon = Argon2::default();
-    let hash = argon.hash_password(input.password.as_bytes(), &salt)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .to_string();
-    let _cred = user_credentials::upsert_password(&state.db, created.id, hash, "argon2")
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+/// 邀请请求；租户取自调用方令牌
+#[derive(Deserialize)]
+pub struct InviteRequest {
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct LoginOutput { pub user_id: Uuid, pub email: String, pub name: String, pub token: String, pub csrf_token: String }
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct SessionOutput {
+    pub id: Uuid,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// 登录时的 User-Agent 与客户端 IP
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// 最近一次使用该会话的请求（精度一分钟）；登录后尚未使用为 null
+    pub last_seen_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 是否为本次请求所用的会话
+    pub current: bool,
+}
+
+impl SessionOutput {
+    fn new(s: SessionRecord, current: Option<Uuid>) -> Self {
+        Self {
+            id: s.id,
+            created_at: s.created_at,
+            expires_at: s.expires_at,
+            user_agent: s.client.user_agent,
+            ip_address: s.client.ip_address,
+            last_seen_at: s.last_seen_at,
+            current: current == Some(s.id),
+        }
+    }
+}
 
-    Ok(Json(RegisterOutput { user_id: created.id }))
+/// API key 概要（不含哈希），供安全审查
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ApiKeyOutput {
+    pub id: Uuid,
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// 网关异步回写，可能滞后一个回写间隔（`key_activity.flush_interval_secs`）
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[utoipa::path(post, path = "/auth/login", tag = "auth", request_body = crate::openapi::LoginRequest, responses((status = 200, description = "Logged In"), (status = 401, description = "Unauthorized")))]
-pub async fn login(State(state): State<ServerState>, jar: CookieJar, Json(input): Json<LoginInput>) -> Result<(CookieJar, Json<LoginOutput>), (StatusCode, String)> {
+impl From<models::apikey::Model> for ApiKeyOutput {
+    fn from(k: models::apikey::Model) -> Self {
+        let utc = |t: sea_orm::prelude::DateTimeWithTimeZone| t.with_timezone(&chrono::Utc);
+        Self { id: k.id, status: k.status, created_at: utc(k.created_at), last_used_at: k.last_used_at.map(utc), expires_at: k.expires_at.map(utc) }
+    }
+}
+
+/// 会话上记录的 User-Agent 最长字符数
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// 按当前状态构造 AuthService；仓库只持有连接池句柄，按请求构造开销可忽略
+fn auth_service(state: &ServerState) -> AuthService<SeaOrmAuthRepository> {
     let repo = Arc::new(SeaOrmAuthRepository { db: state.db.clone() });
-    let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some(state.auth.jwt_secret.clone()), password_algorithm: "argon2".into() });
-    let session = svc.login(input).await.map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
-    let user = session.user;
-    if let Some(token) = session.token {
-        let mut cookie = Cookie::new("auth_token", token.clone());
-        cookie.set_path("/");
-        cookie.set_http_only(true);
-        cookie.set_secure(false);
-        cookie.set_same_site(axum_extra::extract::cookie::SameSite::Lax);
-        let jar = jar.add(cookie);
-        let out = LoginOutput { user_id: user.id, email: user.email, name: user.name, token };
-        return Ok((jar, Json(out)));
+    AuthService::new(repo, AuthConfig { jwt_secret: Some(state.auth.settings.jwt_secret.clone()), password_algorithm: "argon2".into() })
+        .with_token_policy(state.auth.token_policy())
+        .with_keyring(state.jwt_keys.current())
+        .with_session_limit(state.auth.session_limit())
+        .with_verification(
+            Arc::new(WebhookVerificationNotifier::new(Arc::clone(&state.webhooks), state.auth.settings.email_verification_url.clone())),
+            std::time::Duration::from_secs(state.auth.settings.email_verification_ttl_secs),
+        )
+        .with_invitation_ttl(std::time::Duration::from_secs(state.auth.settings.invitation_ttl_secs))
+}
+
+/// 平台级管理端点（签名密钥、主密钥、剖析）的角色校验：需要 admin 角色，返回 JSON:API 错误
+pub(crate) async fn require_admin(state: &ServerState, claims: &TokenClaims) -> Result<(), crate::errors::JsonApiError> {
+    auth_service(state).require_admin(claims).await.map(|_| ()).map_err(|e| {
+        let (status, detail) = auth_error(e);
+        crate::errors::JsonApiError::new(status, status.canonical_reason().unwrap_or("Error"), Some(detail))
+    })
+}
+
+fn auth_error(e: AuthError) -> (StatusCode, String) {
+    let status = match &e {
+        AuthError::Validation(_) => StatusCode::BAD_REQUEST,
+        AuthError::Conflict | AuthError::SessionLimit => StatusCode::CONFLICT,
+        AuthError::NotFound => StatusCode::NOT_FOUND,
+        AuthError::Unauthorized => StatusCode::UNAUTHORIZED,
+        AuthError::EmailNotVerified | AuthError::InviteRequired | AuthError::Forbidden => StatusCode::FORBIDDEN,
+        AuthError::HashError(_) | AuthError::TokenError(_) | AuthError::Repository(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Authorization: Bearer 优先，缺失时回退 auth_token Cookie。
+/// 格式错误为 Err(401)，两者都没有为 Ok(None)
+fn request_token(headers: &HeaderMap) -> Result<Option<String>, StatusCode> {
+    if let Some(h) = headers.get(axum::http::header::AUTHORIZATION).and_then(|v| v.to_str().ok()) {
+        return match h.strip_prefix("Bearer ") {
+            Some(t) => Ok(Some(t.to_string())),
+            None => {
+                tracing::warn!(authz = %h, "invalid Authorization format (expect Bearer)");
+                Err(StatusCode::UNAUTHORIZED)
+            }
+        };
     }
-    Err((StatusCode::INTERNAL_SERVER_ERROR, "token generation failed".into()))
+    let cookie_header = headers.get(axum::http::header::COOKIE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    Ok(cookie_header
+        .split(';')
+        .find_map(|part| part.trim().strip_prefix("auth_token="))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string))
 }
 
-pub async fn logout(jar: CookieJar) -> (CookieJar, StatusCode) {
-    let jar = jar.remove(Cookie::from("auth_token"));
-    (jar, StatusCode::NO_CONTENT)
+#[utoipa::path(post, path = "/auth/register", tag = "auth", request_body = crate::openapi::RegisterRequest, responses((status = 200, description = "Registered", body = RegisterOutput), (status = 400, description = "Bad Request"), (status = 403, description = "Tenant is invite-only"), (status = 409, description = "Conflict")))]
+pub async fn register(State(state): State<ServerState>, Json(input): Json<RegisterInput>) -> Result<Json<RegisterOutput>, (StatusCode, String)> {
+    let user = auth_service(&state).register(input).await.map_err(auth_error)?;
+    Ok(Json(RegisterOutput { user_id: user.id }))
+}
+
+/// 邮箱验证：令牌有效时激活用户，重复验证同样返回 200
+#[utoipa::path(get, path = "/auth/verify", tag = "auth", params(VerifyQuery), responses((status = 200, description = "Verified", body = VerifyOutput), (status = 400, description = "Invalid or expired token")))]
+pub async fn verify_email(State(state): State<ServerState>, Query(q): Query<VerifyQuery>) -> Result<Json<VerifyOutput>, (StatusCode, String)> {
+    let user = auth_service(&state).verify_email(&q.token).await.map_err(auth_error)?;
+    Ok(Json(VerifyOutput { user_id: user.id, email: user.email, status: user.status }))
+}
+
+/// 重新发送验证令牌；无论邮箱是否存在、是否已验证都返回 202，避免暴露注册情况
+#[utoipa::path(post, path = "/auth/verify/resend", tag = "auth", request_body = ResendVerificationInput, responses((status = 202, description = "Sent if the user exists and is unverified")))]
+pub async fn resend_verification(State(state): State<ServerState>, Json(input): Json<ResendVerificationInput>) -> Result<StatusCode, (StatusCode, String)> {
+    auth_service(&state).resend_verification(input.tenant_id, &input.email).await.map_err(auth_error)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// 凭邀请令牌完成注册：租户、邮箱与角色取自邀请，用户直接激活；invite-only 租户同样可用
+#[utoipa::path(post, path = "/auth/accept-invite", tag = "auth", request_body = crate::openapi::AcceptInviteRequest, responses((status = 200, description = "Registered", body = AcceptInviteOutput), (status = 400, description = "Invalid or expired invitation"), (status = 409, description = "Conflict")))]
+pub async fn accept_invite(State(state): State<ServerState>, Json(input): Json<AcceptInviteInput>) -> Result<Json<AcceptInviteOutput>, (StatusCode, String)> {
+    let user = auth_service(&state).accept_invite(input).await.map_err(auth_error)?;
+    Ok(Json(AcceptInviteOutput { user_id: user.id, tenant_id: user.tenant_id, email: user.email, role: user.role }))
+}
+
+#[utoipa::path(post, path = "/auth/login", tag = "auth", request_body = crate::openapi::LoginRequest, responses((status = 200, description = "Logged In", body = LoginOutput), (status = 401, description = "Unauthorized"), (status = 403, description = "Email not verified"), (status = 409, description = "Session limit reached")))]
+pub async fn login(State(state): State<ServerState>, peer: Option<ConnectInfo<SocketAddr>>, headers: HeaderMap, jar: CookieJar, Json(input): Json<LoginInput>) -> Result<(CookieJar, Json<LoginOutput>), (StatusCode, String)> {
+    let (tenant_id, email) = (input.tenant_id, input.email.clone());
+    let client = SessionClient {
+        user_agent: headers.get(axum::http::header::USER_AGENT).and_then(|v| v.to_str().ok()).map(|ua| ua.chars().take(MAX_USER_AGENT_CHARS).collect()),
+        ip_address: peer.map(|ConnectInfo(addr)| addr.ip().to_string()),
+    };
+    let session = auth_service(&state).login_from(input, client).await.map_err(|e| {
+        if matches!(e, AuthError::Unauthorized | AuthError::NotFound) {
+            tracing::warn!(target: "security", action = "login_failed", tenant_id = %tenant_id, email = %email, "login failed");
+        }
+        auth_error(e)
+    })?;
+    let user = session.user;
+    let token = session.token.ok_or_else(|| (StatusCode::INTERNAL_SERVER_ERROR, "token generation failed".to_string()))?;
+    let mut cookie = Cookie::new("auth_token", token.clone());
+    state.auth.apply_cookie_attrs(&mut cookie);
+    cookie.set_http_only(true);
+    cookie.set_same_site(match state.auth.settings.cookie_same_site {
+        SameSitePolicy::Strict => SameSite::Strict,
+        SameSitePolicy::Lax => SameSite::Lax,
+        SameSitePolicy::None => SameSite::None,
+    });
+    // double-submit CSRF token：以 Cookie 认证的写请求需在 X-CSRF-Token 头中回传
+    let csrf_token = crate::csrf::new_token();
+    let mut csrf_cookie = crate::csrf::cookie(csrf_token.clone());
+    state.auth.apply_cookie_attrs(&mut csrf_cookie);
+    let out = LoginOutput { user_id: user.id, email: user.email, name: user.name, token, csrf_token };
+    Ok((jar.add(cookie).add(csrf_cookie), Json(out)))
 }
 
-pub async fn me(State(_state): State<ServerState>, jar: CookieJar) -> Result<Json<MeOutput>, (StatusCode, String)> {
-    if let Some(tok) = jar.get("auth_token") {
-        // For simplicity, we trust the cookie exists; a full implementation would decode/verify JWT.
-        // Here we only return 204 if missing.
-        // Token decoding could be added for stricter checks.
-        let _ = tok; // placeholder
-        return Err((StatusCode::NOT_IMPLEMENTED, "decode not implemented".into()));
+#[utoipa::path(post, path = "/auth/logout", tag = "auth", responses((status = 204, description = "Session revoked, auth and CSRF cookies cleared")))]
+pub async fn logout(State(state): State<ServerState>, claims: Option<Extension<TokenClaims>>, jar: CookieJar) -> (CookieJar, StatusCode) {
+    // 撤销当前会话；失败不影响清除 Cookie
+    if let Some((uid, sid)) = claims.and_then(|Extension(c)| c.uid.zip(c.sid)) {
+        if let Err(e) = auth_service(&state).revoke_session(uid, sid).await {
+            tracing::warn!(session_id = %sid, err = %e, "failed to revoke session on logout");
+        }
     }
-    Err((StatusCode::UNAUTHORIZED, "no auth".into()))
+    // 删除时 Path / Domain 需与写入时一致
+    let removal = |name: &'static str| {
+        let mut c = Cookie::from(name);
+        c.set_path("/");
+        if let Some(domain) = &state.auth.settings.cookie_domain {
+            c.set_domain(domain.clone());
+        }
+        c
+    };
+    let jar = jar.remove(removal("auth_token")).remove(removal(crate::csrf::CSRF_COOKIE));
+    (jar, StatusCode::NO_CONTENT)
+}
+
+/// 当前登录用户（Bearer 或 auth_token Cookie）
+#[utoipa::path(get, path = "/auth/me", tag = "auth", responses((status = 200, description = "Current user", body = MeOutput), (status = 401, description = "Unauthorized")))]
+pub async fn me(State(state): State<ServerState>, headers: HeaderMap) -> Result<Json<MeOutput>, (StatusCode, String)> {
+    let token = request_token(&headers)
+        .map_err(|s| (s, "invalid Authorization header".to_string()))?
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "no auth".to_string()))?;
+    let user = auth_service(&state).current_user(&token).await.map_err(auth_error)?;
+    Ok(Json(MeOutput { user_id: user.id, email: user.email, name: user.name, role: user.role }))
 }
-#[derive(Debug, Deserialize)]
-struct Claims {
-    sub: Option<String>,
-    exp: Option<usize>,
-    iat: Option<usize>,
+
+/// 当前用户的有效会话，最早的在前
+#[utoipa::path(get, path = "/auth/sessions", tag = "auth", responses((status = 200, description = "Active sessions", body = [SessionOutput]), (status = 401, description = "Unauthorized")))]
+pub async fn list_sessions(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Result<Json<Vec<SessionOutput>>, (StatusCode, String)> {
+    let uid = claims.uid.ok_or_else(|| auth_error(AuthError::Unauthorized))?;
+    let sessions = auth_service(&state).sessions(uid).await.map_err(auth_error)?;
+    Ok(Json(sessions.into_iter().map(|s| SessionOutput::new(s, claims.sid)).collect()))
+}
+
+/// 撤销当前用户的某个会话；该会话的令牌随即失效
+#[utoipa::path(delete, path = "/auth/sessions/{id}", tag = "auth", params(("id" = Uuid, Path, description = "Session id")), responses((status = 204, description = "Revoked"), (status = 401, description = "Unauthorized"), (status = 404, description = "Not Found")))]
+pub async fn revoke_session(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Path(id): Path<Uuid>) -> Result<StatusCode, (StatusCode, String)> {
+    let uid = claims.uid.ok_or_else(|| auth_error(AuthError::Unauthorized))?;
+    auth_service(&state).revoke_session(uid, id).await.map_err(auth_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 当前用户的 API key 及最近使用时间
+#[utoipa::path(get, path = "/auth/api-keys", tag = "auth", responses((status = 200, description = "API keys of the current user", body = [ApiKeyOutput]), (status = 401, description = "Unauthorized")))]
+pub async fn list_own_api_keys(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Result<Json<Vec<ApiKeyOutput>>, (StatusCode, String)> {
+    let uid = claims.uid.ok_or_else(|| auth_error(AuthError::Unauthorized))?;
+    user_api_keys(&state, uid).await
+}
+
+/// 管理端：本租户某个用户的有效会话（含登录 IP / User-Agent / 最近活动），供安全审查；需要 admin 角色
+#[utoipa::path(get, path = "/admin/users/{id}/sessions", tag = "admin", params(("id" = Uuid, Path, description = "User id")), responses((status = 200, description = "Active sessions", body = [SessionOutput]), (status = 403, description = "Admin role required"), (status = 404, description = "Not Found")))]
+pub async fn admin_user_sessions(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Path(id): Path<Uuid>) -> Result<Json<Vec<SessionOutput>>, (StatusCode, String)> {
+    let svc = auth_service(&state);
+    let admin = svc.require_admin(&claims).await.map_err(auth_error)?;
+    let user = svc.tenant_user(admin.tenant_id, id).await.map_err(auth_error)?;
+    let sessions = svc.sessions(user.id).await.map_err(auth_error)?;
+    Ok(Json(sessions.into_iter().map(|s| SessionOutput::new(s, None)).collect()))
+}
+
+/// 管理端：本租户某个用户的 API key 及最近使用时间；需要 admin 角色
+#[utoipa::path(get, path = "/admin/users/{id}/api-keys", tag = "admin", params(("id" = Uuid, Path, description = "User id")), responses((status = 200, description = "API keys of the user", body = [ApiKeyOutput]), (status = 403, description = "Admin role required"), (status = 404, description = "Not Found")))]
+pub async fn admin_user_api_keys(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Path(id): Path<Uuid>) -> Result<Json<Vec<ApiKeyOutput>>, (StatusCode, String)> {
+    let svc = auth_service(&state);
+    let admin = svc.require_admin(&claims).await.map_err(auth_error)?;
+    let user = svc.tenant_user(admin.tenant_id, id).await.map_err(auth_error)?;
+    user_api_keys(&state, user.id).await
+}
+
+/// 管理端：邀请邮箱以指定角色加入调用方所在租户，返回一次性邀请令牌（有效期 `auth.invitation_ttl_secs`）；需要 admin 角色
+#[utoipa::path(post, path = "/admin/invitations", tag = "admin", request_body = crate::openapi::InviteRequest, responses((status = 201, description = "Invited", body = InvitationOutput), (status = 400, description = "Bad Request"), (status = 403, description = "Admin role required"), (status = 409, description = "Already a member")))]
+pub async fn create_invitation(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Json(req): Json<InviteRequest>) -> Result<(StatusCode, Json<InvitationOutput>), (StatusCode, String)> {
+    let svc = auth_service(&state);
+    let admin = svc.require_admin(&claims).await.map_err(auth_error)?;
+    let (invitation, token) = svc.invite(InviteInput { tenant_id: admin.tenant_id, email: req.email, role: req.role }).await.map_err(auth_error)?;
+    Ok((StatusCode::CREATED, Json(InvitationOutput::new(invitation, Some(token)))))
+}
+
+/// 管理端：调用方所在租户的邀请（含已接受与已过期），最新的在前；需要 admin 角色
+#[utoipa::path(get, path = "/admin/invitations", tag = "admin", responses((status = 200, description = "Invitations", body = [InvitationOutput]), (status = 403, description = "Admin role required")))]
+pub async fn list_invitations(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Result<Json<Vec<InvitationOutput>>, (StatusCode, String)> {
+    let svc = auth_service(&state);
+    let admin = svc.require_admin(&claims).await.map_err(auth_error)?;
+    let invitations = svc.invitations(admin.tenant_id).await.map_err(auth_error)?;
+    Ok(Json(invitations.into_iter().map(|i| InvitationOutput::new(i, None)).collect()))
+}
+
+/// 管理端：撤回本租户的邀请，令牌随即失效；需要 admin 角色
+#[utoipa::path(delete, path = "/admin/invitations/{id}", tag = "admin", params(("id" = Uuid, Path, description = "Invitation id")), responses((status = 204, description = "Revoked"), (status = 403, description = "Admin role required"), (status = 404, description = "Not Found")))]
+pub async fn revoke_invitation(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Path(id): Path<Uuid>) -> Result<StatusCode, (StatusCode, String)> {
+    let svc = auth_service(&state);
+    let admin = svc.require_admin(&claims).await.map_err(auth_error)?;
+    svc.revoke_invitation(admin.tenant_id, id).await.map_err(auth_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn user_api_keys(state: &ServerState, user_id: Uuid) -> Result<Json<Vec<ApiKeyOutput>>, (StatusCode, String)> {
+    let keys = service::db::apikey_service::list_api_keys_by_user(&state.db, user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(keys.into_iter().map(ApiKeyOutput::from).collect()))
 }
 
 /// 全局中间件：除健康检查与预检外，校验 Authorization: Bearer <token>
-/// 缺失 token 返回 400，非法或过期返回 401；失败记录日志
+/// 缺失 token 返回 400，非法或过期返回 401，Cookie 认证的写请求缺少 CSRF token 返回 403；失败记录日志
 pub async fn require_bearer_token_state(
     State(state): State<ServerState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let path = req.uri().path();
     let method = req.method().clone();
 
-    // 白名单：健康检查、登录与注册、Swagger 文档、CORS 预检
+    // 白名单：健康检查、登录、注册、邀请注册、邮箱验证与自助开通、Swagger 文档、CORS 预检
     if path == "/health"
+        || path == "/livez"
+        || path == "/readyz"
         || path == "/auth/login"
         || path == "/auth/register"
+        || path == "/auth/verify"
+        || path == "/auth/verify/resend"
+        || path == "/auth/accept-invite"
+        || path == "/signup"
         || path.starts_with("/docs")
         || path.starts_with("/api-docs")
         || method == axum::http::Method::OPTIONS {
@@ -155,56 +454,29 @@ pub async fn require_bearer_token_state(
     }
 
     // 读取 Authorization 头；如缺失则回退从 Cookie 中解析 auth_token
-    let token = {
-        let authz = req
-            .headers()
-            .get(axum::http::header::AUTHORIZATION)
-            .and_then(|v| v.to_str().ok());
-
-        if let Some(h) = authz {
-            let prefix = "Bearer ";
-            if !h.starts_with(prefix) {
-                tracing::warn!(path = %path, authz = %h, "invalid Authorization format (expect Bearer)");
-                return Err(StatusCode::UNAUTHORIZED);
-            }
-            h[prefix.len()..].to_string()
-        } else {
-            // Cookie 回退：解析 Cookie 头获取 auth_token
-            let cookie_header = req
-                .headers()
-                .get(axum::http::header::COOKIE)
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-
-            let mut token_val: Option<String> = None;
-            for part in cookie_header.split(';') {
-                let kv = part.trim();
-                if let Some(rest) = kv.strip_prefix("auth_token=") {
-                    token_val = Some(rest.to_string());
-                    break;
-                }
-            }
-
-            match token_val {
-                Some(t) if !t.is_empty() => t,
-                _ => {
-                    tracing::warn!(path = %path, "missing Authorization header and auth_token cookie");
-                    return Err(StatusCode::BAD_REQUEST);
-                }
-            }
+    let token = match request_token(req.headers()) {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            tracing::warn!(path = %path, "missing Authorization header and auth_token cookie");
+            return Err(StatusCode::BAD_REQUEST);
         }
+        Err(status) => return Err(status),
     };
-    let key = DecodingKey::from_secret(state.auth.jwt_secret.as_bytes());
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.validate_exp = true;
-
-    match decode::<Claims>(&token, &key, &validation) {
-        Ok(_data) => {
-            // 可按需将 claims 注入 request 扩展供后续使用
+    // 仅凭 Cookie 认证的写请求需通过 CSRF 校验；Bearer 头由调用方显式设置，不受跨站请求影响
+    let cookie_auth = !req.headers().contains_key(axum::http::header::AUTHORIZATION);
+    if cookie_auth && crate::csrf::is_state_changing(&method) && !crate::csrf::verify(req.headers()) {
+        tracing::warn!(target: "security", action = "csrf_rejected", path = %path, method = %method, "missing or mismatched CSRF token on cookie-authenticated request");
+        return Err(StatusCode::FORBIDDEN);
+    }
+    // 带 sid 的令牌还要求会话未被撤销
+    match auth_service(&state).authenticate(&token).await {
+        // claims 注入 request 扩展，供限流等后续中间件识别调用方
+        Ok(claims) => {
+            req.extensions_mut().insert(claims);
             Ok(next.run(req).await)
         }
         Err(e) => {
-            tracing::error!(path = %path, err = %e, "token validation failed");
+            tracing::error!(target: "security", action = "token_rejected", path = %path, method = %method, err = %e, "token validation failed");
             Err(StatusCode::UNAUTHORIZED)
         }
     }
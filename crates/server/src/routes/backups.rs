@@ -0,0 +1,69 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::Deserialize;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use common::pagination::{Page, Pagination};
+use service::backup::{BackupSummary, ConfigSnapshot, RestoreReport};
+use service::errors::ServiceError;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ListQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+fn map_err(e: ServiceError, title: &str) -> JsonApiError {
+    match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        ServiceError::Pagination(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())),
+        _ => { error!(err = %e, "{}", title); JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, title, Some(e.to_string())) }
+    }
+}
+
+#[utoipa::path(
+    get, path = "/admin/config-backups", tag = "admin", operation_id = "list_backups",
+    params(ListQuery),
+    responses((status = 200, description = "Backups, newest first", body = crate::openapi::BackupPageDoc), (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<BackupSummary>>, JsonApiError> {
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page)
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())))?;
+    state.backups.list(opts).await.map(Json).map_err(|e| map_err(e, "List Failed"))
+}
+
+/// 手动创建配置快照
+#[utoipa::path(post, path = "/admin/config-backups", tag = "admin", operation_id = "create_backup", responses((status = 200, description = "Created", body = crate::openapi::BackupSummaryDoc), (status = 500, description = "Backup Failed", body = crate::errors::JsonApiErrorBody)))]
+pub async fn create(State(state): State<ServerState>) -> Result<Json<BackupSummary>, JsonApiError> {
+    let b = state.backups.snapshot("manual").await.map_err(|e| map_err(e, "Backup Failed"))?;
+    info!(backup_id = %b.id, "admin created config backup");
+    Ok(Json(b))
+}
+
+#[utoipa::path(
+    get, path = "/admin/config-backups/{id}", tag = "admin", operation_id = "get_backup",
+    params(("id" = Uuid, Path, description = "Backup ID")),
+    responses((status = 200, description = "Snapshot content", body = Object), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<ConfigSnapshot>, JsonApiError> {
+    match state.backups.get(id).await {
+        Ok(Some(s)) => Ok(Json(s)),
+        Ok(None) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", None)),
+        Err(e) => Err(map_err(e, "Get Failed")),
+    }
+}
+
+/// 从快照恢复：重建被删除的记录并覆盖已修改的记录；恢复前自动快照当前状态
+#[utoipa::path(
+    post, path = "/admin/config-backups/{id}/restore", tag = "admin",
+    params(("id" = Uuid, Path, description = "Backup ID")),
+    responses((status = 200, description = "Restored", body = crate::openapi::RestoreReportDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody), (status = 500, description = "Restore Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn restore(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<RestoreReport>, JsonApiError> {
+    let report = state.backups.restore(id).await.map_err(|e| map_err(e, "Restore Failed"))?;
+    info!(backup_id = %id, pre_restore = %report.pre_restore_backup_id, "admin restored config backup");
+    Ok(Json(report))
+}
@@ -0,0 +1,109 @@
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use service::db::route_service::{self, DeploymentColor, RouteCutover};
+use service::errors::ServiceError;
+use service::events::ConfigEvent;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetBlueGreenInput {
+    pub blue_upstream_id: Uuid,
+    pub green_upstream_id: Uuid,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct CutoverInput {
+    /// "blue" / "green"；省略时切到当前未生效的一侧
+    #[schema(value_type = Option<String>)]
+    pub target: Option<DeploymentColor>,
+}
+
+fn map_err(e: ServiceError, what: &str) -> JsonApiError {
+    match e {
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::CONFLICT, "Conflict", Some(e.to_string())),
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        e => {
+            error!(err = %e, "{} failed", what);
+            JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))
+        }
+    }
+}
+
+/// 切换成功后记录审计日志，失效路由缓存并广播事件
+async fn publish(state: &ServerState, action: &str, c: &RouteCutover) {
+    info!(target: "audit", action, route_id = %c.route.id, from = %c.from_upstream_id, to = %c.to_upstream_id, color = ?c.route.active_color, "route_cutover");
+    state.read_cache.apply(&state.events, ConfigEvent::RouteCutover { id: c.route.id, from: c.from_upstream_id, to: c.to_upstream_id }).await;
+}
+
+/// 配置路由的蓝/绿两个 upstream（不切换流量）
+#[utoipa::path(
+    put, path = "/admin/routes/{id}/blue-green", tag = "admin",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    request_body = SetBlueGreenInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::RouteDoc),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "Invalid blue/green configuration", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_blue_green(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetBlueGreenInput>) -> Result<Json<models::route::Model>, JsonApiError> {
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let route = route_service::set_route_blue_green(change.txn(), id, input.blue_upstream_id, input.green_upstream_id).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(route)
+    };
+    let route = changed.await.map_err(|e| map_err(e, "set blue/green"))?;
+    state.read_cache.apply(&state.events, ConfigEvent::RouteChanged { id }).await;
+    Ok(Json(route))
+}
+
+/// 原子切换到 blue 或 green，并记录切换前的 upstream 以便回滚
+#[utoipa::path(
+    post, path = "/admin/routes/{id}/cutover", tag = "admin",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    request_body = CutoverInput,
+    responses(
+        (status = 200, description = "Switched", body = crate::openapi::RouteCutoverDoc),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "Nothing to switch", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn cutover(State(state): State<ServerState>, Path(id): Path<Uuid>, input: Option<Json<CutoverInput>>) -> Result<Json<RouteCutover>, JsonApiError> {
+    let target = input.and_then(|Json(i)| i.target);
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let c = route_service::cutover_route(change.txn(), id, target).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(c)
+    };
+    let c = changed.await.map_err(|e| map_err(e, "route cutover"))?;
+    publish(&state, "cutover", &c).await;
+    Ok(Json(c))
+}
+
+/// 立即回滚到上一次切换前的 upstream
+#[utoipa::path(
+    post, path = "/admin/routes/{id}/rollback", tag = "admin",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    responses(
+        (status = 200, description = "Rolled back", body = crate::openapi::RouteCutoverDoc),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "No previous upstream", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn rollback(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<RouteCutover>, JsonApiError> {
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let c = route_service::rollback_route(change.txn(), id).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(c)
+    };
+    let c = changed.await.map_err(|e| map_err(e, "route rollback"))?;
+    publish(&state, "rollback", &c).await;
+    Ok(Json(c))
+}
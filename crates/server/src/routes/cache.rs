@@ -0,0 +1,28 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use tracing::info;
+
+use service::cache::CacheStats;
+use service::events::ConfigEvent;
+
+use crate::routes::auth::ServerState;
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CacheStatsOutput {
+    #[schema(value_type = Vec<Object>)]
+    pub caches: Vec<CacheStats>,
+}
+
+#[utoipa::path(get, path = "/admin/cache", tag = "admin", operation_id = "cache_stats", responses((status = 200, description = "Read cache statistics", body = CacheStatsOutput)))]
+pub async fn stats(State(state): State<ServerState>) -> Json<CacheStatsOutput> {
+    Json(CacheStatsOutput { caches: state.read_cache.stats().await })
+}
+
+/// 手动清空读缓存；通过事件总线广播，其他订阅者同样会失效
+#[utoipa::path(post, path = "/admin/cache/flush", tag = "admin", responses((status = 200, description = "Flushed", body = CacheStatsOutput)))]
+pub async fn flush(State(state): State<ServerState>) -> Json<CacheStatsOutput> {
+    state.read_cache.flush().await;
+    state.events.publish(ConfigEvent::CacheFlushed);
+    info!("admin flushed read cache");
+    Json(CacheStatsOutput { caches: state.read_cache.stats().await })
+}
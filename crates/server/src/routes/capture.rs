@@ -0,0 +1,67 @@
+//! 路由的请求体捕获设置（抽样比例、大小上限）；捕获内容供 `/admin/request-logs/{id}/replay` 使用
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use models::route_capture;
+use service::capture::SetCaptureInput;
+use service::errors::ServiceError;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetCaptureInputDoc {
+    /// 0-100；0 表示暂停捕获但保留设置
+    pub sample_percent: i16,
+    /// 请求体与响应体各自的上限（字节，脱敏后计算）；省略时取 `[capture] default_max_bytes`
+    pub max_bytes: Option<i32>,
+    /// 默认 true；重放比较需要捕获响应
+    pub capture_response: Option<bool>,
+}
+
+fn map_err(e: ServiceError, title: &str) -> JsonApiError {
+    match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string())),
+        _ => { error!(err = %e, "{}", title); JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, title, Some(e.to_string())) }
+    }
+}
+
+#[utoipa::path(
+    get, path = "/admin/routes/{id}/capture", tag = "admin", operation_id = "get_route_capture",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    responses((status = 200, description = "Capture settings", body = crate::openapi::RouteCaptureDoc), (status = 404, description = "Capture not configured for the route", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<route_capture::Model>, JsonApiError> {
+    state.capture.get(id).await.map(Json).map_err(|e| map_err(e, "Get Failed"))
+}
+
+/// 开启或修改捕获；`[capture] enabled = false` 时设置会保存但不生效
+#[utoipa::path(
+    put, path = "/admin/routes/{id}/capture", tag = "admin", operation_id = "set_route_capture",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    request_body = SetCaptureInputDoc,
+    responses(
+        (status = 200, description = "Saved", body = crate::openapi::RouteCaptureDoc),
+        (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Route not found", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetCaptureInput>) -> Result<Json<route_capture::Model>, JsonApiError> {
+    let saved = state.capture.set(id, input).await.map_err(|e| map_err(e, "Update Failed"))?;
+    info!(target: "audit", action = "route_capture_set", route_id = %id, sample_percent = saved.sample_percent, max_bytes = saved.max_bytes, globally_enabled = state.capture.enabled(), "route_capture_set");
+    Ok(Json(saved))
+}
+
+#[utoipa::path(
+    delete, path = "/admin/routes/{id}/capture", tag = "admin", operation_id = "delete_route_capture",
+    params(("id" = Uuid, Path, description = "Route ID")),
+    responses((status = 204, description = "Capture turned off"), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn delete(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<StatusCode, JsonApiError> {
+    state.capture.remove(id).await.map_err(|e| map_err(e, "Delete Failed"))?;
+    info!(target: "audit", action = "route_capture_removed", route_id = %id, "route_capture_removed");
+    Ok(StatusCode::NO_CONTENT)
+}
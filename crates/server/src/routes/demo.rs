@@ -0,0 +1,297 @@
+//! `/api/*` 数据面演示路由：按 proxy_api 配置（method + endpoint_url）转发到 forward_target，
+//! 不再在代码里硬编码任何外部站点。mock 模式下直接返回配置的静态响应。
+//! 租户暂停、API 停用或处于维护窗口时返回 503。
+//! 配置了 grpc_method 的 API 走 REST → gRPC 转码：JSON 请求编码后以 HTTP/2 调用上游，响应解码回 JSON。
+//! endpoint_url 中的路径参数以 `X-Path-{Name}` 头传给上游，mock 模板中可用 `{{path.<param>}}` 引用。
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use axum::{
+    body::Bytes,
+    extract::{ConnectInfo, State},
+    http::{header::{CONTENT_TYPE, RETRY_AFTER}, HeaderMap, HeaderValue, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use common::feature_flags;
+use common::http_client::{HttpClientError, HttpClientFactory, UpstreamClient, UpstreamRequest};
+use tracing::{debug, error, warn};
+
+use service::proxy_api::{maintenance::{self, Blocked}, mock::MockResponse, rewrite, service::ResolvedRoute};
+use service::request_log_stream::LiveRequestLog;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+/// 拒绝响应中返回导致拒绝的配置版本
+const CONFIG_REVISION_HEADER: &str = "x-config-revision";
+
+/// 转发用的上游客户端，启动时按 config.toml [http_client] 构建一次，所有请求共享连接池
+#[derive(Clone)]
+pub struct UpstreamClients {
+    pub http: UpstreamClient,
+    /// gRPC 转码：HTTP/2 prior knowledge；需要读取 trailers，直接使用 reqwest 客户端
+    pub grpc: reqwest::Client,
+}
+
+impl UpstreamClients {
+    pub fn new(factory: &HttpClientFactory) -> Result<Self, HttpClientError> {
+        let grpc = factory.builder()?.http2_prior_knowledge().build().map_err(|e| HttpClientError::Build(e.to_string()))?;
+        Ok(Self { http: factory.upstream()?, grpc })
+    }
+}
+
+/// 本次请求命中的 API 与处理方式，供实时请求日志使用
+#[derive(Default)]
+struct Handled {
+    api: Option<(uuid::Uuid, uuid::Uuid)>,
+    outcome: &'static str,
+    config_revision: i64,
+}
+
+pub async fn forward(
+    State(state): State<ServerState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, JsonApiError> {
+    let started = Instant::now();
+    let mut handled = Handled { outcome: "forwarded", ..Default::default() };
+    let result = handle(&state, &mut handled, method.clone(), &uri, &headers, body).await;
+    // 未匹配到 proxy API 的请求不属于任何租户，不进入实时日志
+    if let Some((tenant_id, proxy_api_id)) = handled.api {
+        let (status, error) = match &result {
+            Ok(resp) => (resp.status().as_u16(), None),
+            Err(e) => (e.status.as_u16(), e.detail.clone().or_else(|| Some(e.title.clone()))),
+        };
+        let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        state.request_log_stream.publish(LiveRequestLog {
+            timestamp: chrono::Utc::now(),
+            tenant_id,
+            proxy_api_id,
+            method: method.to_string(),
+            path: common::redaction::global().redact_uri(path).into_owned(),
+            status,
+            latency_ms: started.elapsed().as_millis() as u64,
+            client_ip: peer.map(|ConnectInfo(addr)| addr.ip().to_string()),
+            outcome: if result.is_err() { "error" } else { handled.outcome }.to_string(),
+            error: error.map(|e| common::redaction::global().redact_text(&e)),
+            config_revision: handled.config_revision,
+        });
+    }
+    result
+}
+
+async fn handle(state: &ServerState, handled: &mut Handled, method: Method, uri: &Uri, headers: &HeaderMap, body: Bytes) -> Result<Response, JsonApiError> {
+    let resolved = state.proxy_api_svc.resolve(method.as_str(), uri.path()).await.map_err(|e| {
+        error!(err = %e, "resolve proxy api failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Resolve Failed", Some(e.to_string()))
+    })?;
+    let Some(route) = resolved else {
+        return Err(JsonApiError::new(
+            StatusCode::NOT_FOUND,
+            "Not Found",
+            Some(format!("no proxy API for {} {}; create one via /admin/proxy-apis", method, uri.path())),
+        ));
+    };
+    let ResolvedRoute { api, params, .. } = &route;
+    handled.api = Some((api.tenant_id, api.id));
+    // 特性开关 server.read_cache 关闭的租户绕过读缓存直接查库（未定义时走缓存）
+    let tenant = if state.feature_flags.is_enabled(feature_flags::SERVER_READ_CACHE, api.tenant_id, true).await {
+        state.read_cache.get_tenant(&state.db, api.tenant_id).await
+    } else {
+        service::db::tenant_service::get_tenant(&state.db, api.tenant_id).await
+    };
+    let tenant = tenant.map_err(|e| {
+        error!(err = %e, "load tenant failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Resolve Failed", Some(e.to_string()))
+    })?;
+    let config_revision = state.events.current_revision();
+    handled.config_revision = config_revision;
+    let now = state.clock.now();
+    if let Some(blocked) = maintenance::check(tenant.as_ref(), api, now) {
+        // 引用最近一次改动该 API 或租户的配置版本，便于与 /admin/config-revisions 对照
+        let caused_by = match blocked {
+            Blocked::TenantSuspended { .. } => state.events.revision_of(api.tenant_id),
+            _ => state.events.revision_of(api.id),
+        };
+        warn!(
+            proxy_api_id = %api.id,
+            tenant_id = %api.tenant_id,
+            reason = blocked.as_str(),
+            config_revision,
+            caused_by_revision = ?caused_by,
+            path_params = ?params,
+            "request blocked"
+        );
+        handled.outcome = "blocked";
+        let mut out = blocked_response(&blocked, now);
+        out.headers_mut().insert(CONFIG_REVISION_HEADER, HeaderValue::from(caused_by.unwrap_or(config_revision)));
+        return Ok(out);
+    }
+    if let Some(mock) = MockResponse::from_model(api) {
+        handled.outcome = "mock";
+        return Ok(mock_response(&mock, &method, uri, headers, params));
+    }
+    if let Some(grpc_method) = api.grpc_method.as_deref() {
+        handled.outcome = "grpc";
+        return grpc_forward(state, &route, grpc_method, uri.query(), &body, config_revision).await;
+    }
+    let mut target = route.target.clone();
+    if let Some(q) = uri.query() {
+        target = format!("{}{}{}", target, if target.contains('?') { '&' } else { '?' }, q);
+    }
+
+    let logged_target = common::redaction::global().redact_uri(&target).into_owned();
+    debug!(proxy_api_id = %api.id, target = %logged_target, path_params = ?params, "demo forward");
+    let mut req = UpstreamRequest::new(method, target);
+    req.body = body;
+    if let Some(ct) = headers.get(CONTENT_TYPE) {
+        req.headers.insert(CONTENT_TYPE, ct.clone());
+    }
+    for (name, value) in params {
+        let header = rewrite::header_name(name).and_then(|h| axum::http::HeaderName::try_from(h).ok());
+        if let (Some(h), Ok(v)) = (header, HeaderValue::from_str(value)) {
+            req.headers.insert(h, v);
+        }
+    }
+    let resp = state.upstream.http.send(req).await.map_err(|e| {
+        warn!(proxy_api_id = %api.id, target = %logged_target, path_params = ?params, config_revision, err = %e, "demo forward failed");
+        JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string()))
+    })?;
+
+    let content_type = resp.content_type().and_then(|c| c.parse::<HeaderValue>().ok());
+    let mut out = (resp.status, resp.body).into_response();
+    if let Some(ct) = content_type {
+        out.headers_mut().insert(CONTENT_TYPE, ct);
+    }
+    Ok(out)
+}
+
+/// REST → gRPC unary 调用；gRPC 状态码映射为 HTTP 状态，错误体为 `{code, message}`
+async fn grpc_forward(
+    state: &ServerState,
+    route: &ResolvedRoute,
+    grpc_method: &str,
+    query: Option<&str>,
+    body: &[u8],
+    config_revision: i64,
+) -> Result<Response, JsonApiError> {
+    let ResolvedRoute { api, target, params } = route;
+    let transcoder = state.grpc.as_ref().ok_or_else(|| {
+        JsonApiError::new(StatusCode::NOT_IMPLEMENTED, "Transcoding Unavailable", Some("no [grpc] descriptor_sets configured".into()))
+    })?;
+    let method = transcoder.method(grpc_method).map_err(|e| {
+        error!(proxy_api_id = %api.id, grpc_method, err = %e, "grpc method not found");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Transcoding Failed", Some(e.to_string()))
+    })?;
+    let payload = transcoder
+        .encode_request(&method, body, params, query)
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Request", Some(e.to_string())))?;
+    // gRPC 路径固定为 /package.Service/Method，forward_target 只取 origin
+    let mut url = reqwest::Url::parse(target).map_err(|e| JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string())))?;
+    url.set_path(&service::proxy_api::grpc::grpc_path(grpc_method));
+    url.set_query(None);
+    common::egress::global()
+        .check_url(url.as_str())
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string())))?;
+
+    let resp = state
+        .upstream
+        .grpc
+        .post(url.clone())
+        .header(CONTENT_TYPE, "application/grpc")
+        .header("te", "trailers")
+        .body(payload)
+        .send()
+        .await
+        .map_err(|e| {
+            warn!(proxy_api_id = %api.id, %url, config_revision, err = %e, "grpc forward failed");
+            JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string()))
+        })?;
+    let http_status = resp.status();
+    let header_status = grpc_header(resp.headers(), "grpc-status");
+    let header_message = grpc_header(resp.headers(), "grpc-message");
+    // trailers 只能通过 http_body 读取
+    let collected = http_body_util::BodyExt::collect(axum::http::Response::<reqwest::Body>::from(resp).into_body())
+        .await
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string())))?;
+    let trailers = collected.trailers().cloned().unwrap_or_default();
+    let bytes = collected.to_bytes();
+
+    let code = match grpc_header(&trailers, "grpc-status").or(header_status) {
+        Some(c) => c.parse::<i32>().unwrap_or(2),
+        None if !http_status.is_success() => {
+            return Err(JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(format!("upstream returned HTTP {}", http_status))));
+        }
+        // 缺少 grpc-status 视为 UNKNOWN
+        None => 2,
+    };
+    if code != 0 {
+        let message = grpc_header(&trailers, "grpc-message").or(header_message).unwrap_or_default();
+        warn!(proxy_api_id = %api.id, grpc_method, grpc_status = code, config_revision, "grpc call failed");
+        let status = StatusCode::from_u16(service::proxy_api::grpc::http_status(code)).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        return Ok((status, axum::Json(serde_json::json!({ "code": code, "message": message }))).into_response());
+    }
+    let json = transcoder.decode_response(&method, &bytes).map_err(|e| {
+        warn!(proxy_api_id = %api.id, grpc_method, err = %e, "grpc response decode failed");
+        JsonApiError::new(StatusCode::BAD_GATEWAY, "Bad Gateway", Some(e.to_string()))
+    })?;
+    Ok(axum::Json(json).into_response())
+}
+
+fn grpc_header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn blocked_response(blocked: &Blocked, now: chrono::DateTime<chrono::Utc>) -> Response {
+    blocked.record();
+    let body = blocked.payload();
+    let content_type = if serde_json::from_str::<serde_json::Value>(&body).is_ok() { "application/json" } else { "text/plain; charset=utf-8" };
+    let mut out = (StatusCode::SERVICE_UNAVAILABLE, body).into_response();
+    out.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if let Blocked::Maintenance { until: Some(until), .. } = blocked {
+        let secs = (*until - now).num_seconds().max(1);
+        out.headers_mut().insert(RETRY_AFTER, HeaderValue::from(secs));
+    }
+    out
+}
+
+fn mock_response(mock: &MockResponse, method: &Method, uri: &Uri, headers: &HeaderMap, params: &[(String, String)]) -> Response {
+    let query: Vec<(String, String)> = uri
+        .query()
+        .map(|q| {
+            q.split('&')
+                .filter_map(|kv| kv.split_once('=').or(Some((kv, ""))))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let rendered = mock.render(|key| match key {
+        "method" => Some(method.to_string()),
+        "path" => Some(uri.path().to_string()),
+        "query" => Some(uri.query().unwrap_or_default().to_string()),
+        "now" => Some(chrono::Utc::now().to_rfc3339()),
+        _ => {
+            if let Some(name) = key.strip_prefix("query.") {
+                query.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            } else if let Some(name) = key.strip_prefix("path.") {
+                params.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+            } else if let Some(name) = key.strip_prefix("header.") {
+                headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+            } else {
+                None
+            }
+        }
+    });
+    let status = StatusCode::from_u16(rendered.status).unwrap_or(StatusCode::OK);
+    let mut out = (status, rendered.body).into_response();
+    for (k, v) in rendered.headers {
+        // 模板渲染后可能不再是合法的 header，跳过即可
+        if let (Ok(name), Ok(value)) = (k.parse::<axum::http::HeaderName>(), v.parse::<axum::http::HeaderValue>()) {
+            out.headers_mut().insert(name, value);
+        }
+    }
+    out
+}
@@ -0,0 +1,52 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use service::errors::ServiceError;
+use service::events::ConfigEvent;
+use service::seed::{SeedOptions, SeedReport};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct SeedInput {
+    pub tenant_name: Option<String>,
+    pub request_logs: Option<usize>,
+}
+
+/// 写入演示数据（仅开发模式注册该路由）；返回演示账号密码与明文 API Key
+#[utoipa::path(
+    post, path = "/admin/dev/seed", tag = "admin",
+    request_body = SeedInput,
+    responses((status = 200, description = "Seeded", body = Object), (status = 409, description = "Already Seeded", body = crate::errors::JsonApiErrorBody), (status = 500, description = "Seed Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn seed(State(state): State<ServerState>, input: Option<Json<SeedInput>>) -> Result<Json<SeedReport>, JsonApiError> {
+    let input = input.map(|Json(i)| i).unwrap_or_default();
+    let defaults = SeedOptions::default();
+    let opts = SeedOptions {
+        tenant_name: input.tenant_name.unwrap_or(defaults.tenant_name),
+        echo_base_url: state.dev.echo_base_url.clone(),
+        request_logs: input.request_logs.unwrap_or(defaults.request_logs).min(10_000),
+        ..defaults
+    };
+    match crate::seed::seed_and_register(&state.db, state.admin_kv_store.as_ref(), &opts).await {
+        Ok(report) => {
+            info!(tenant_id = %report.tenant_id, "admin seeded demo data");
+            // 与其他写操作一样发布变更，读缓存与 webhook 订阅方随之更新
+            let events = std::iter::once(ConfigEvent::TenantChanged { id: report.tenant_id })
+                .chain(report.upstream_ids.iter().map(|&id| ConfigEvent::UpstreamChanged { id }))
+                .chain(std::iter::once(ConfigEvent::RateLimitChanged { id: report.rate_limit_id }))
+                .chain(report.route_ids.iter().map(|&id| ConfigEvent::RouteChanged { id }))
+                .chain(report.proxy_api_ids.iter().map(|&id| ConfigEvent::ProxyApiChanged { id }));
+            for event in events {
+                state.read_cache.apply(&state.events, event).await;
+            }
+            Ok(Json(report))
+        }
+        Err(ServiceError::Validation(msg)) => Err(JsonApiError::new(StatusCode::CONFLICT, "Already Seeded", Some(msg))),
+        Err(e) => {
+            error!(err = %e, "seed failed");
+            Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Seed Failed", Some(e.to_string())))
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use service::auth::domain::TokenClaims;
+
+use crate::errors::JsonApiError;
+use crate::routes::auth::{self, ServerState};
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SealInput {
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SealOutput {
+    /// `v1:<key_id>:...`，可直接写入网关 config.json 的 upstream_auth
+    pub sealed: String,
+    pub key_id: String,
+}
+
+/// 用当前主密钥加密一个值（网关上游凭据等配置文件中的密钥）；不落库、不记录明文。需要 admin 角色
+#[utoipa::path(
+    post, path = "/admin/encryption/seal", tag = "admin",
+    request_body = SealInput,
+    responses((status = 200, description = "Sealed value", body = SealOutput), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody), (status = 503, description = "Encryption not configured", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn seal(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Json(input): Json<SealInput>) -> Result<Json<SealOutput>, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    let keys = common::crypto::installed()
+        .ok_or_else(|| JsonApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Encryption Not Configured", None))?;
+    let sealed = keys
+        .seal(input.value.as_bytes())
+        .map_err(|e| JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Seal Failed", Some(e.to_string())))?;
+    info!(key_id = %keys.primary_id(), "admin sealed a configuration secret");
+    Ok(Json(SealOutput { sealed, key_id: keys.primary_id().to_string() }))
+}
@@ -0,0 +1,39 @@
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use service::expiry::ExpiringItem;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ExpiringQuery {
+    /// 覆盖配置的提前天数
+    pub within_days: Option<u32>,
+    pub tenant_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ExpiringOutput {
+    /// 按到期时间升序；已过期的 days_left 为负
+    #[schema(value_type = Vec<Object>)]
+    pub items: Vec<ExpiringItem>,
+}
+
+/// 临近到期的 API Key、JWT 签名密钥与 TLS 证书
+#[utoipa::path(
+    get, path = "/admin/expiring", tag = "admin", operation_id = "list_expiring",
+    params(ExpiringQuery),
+    responses((status = 200, description = "Credentials within their notification lead time", body = ExpiringOutput), (status = 500, description = "Scan Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ExpiringQuery>) -> Result<Json<ExpiringOutput>, JsonApiError> {
+    let mut items = state.expiry.scan(state.clock.now(), q.within_days).await.map_err(|e| {
+        error!(err = %e, "expiry scan failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Scan Failed", Some(e.to_string()))
+    })?;
+    if let Some(t) = q.tenant_id {
+        items.retain(|i| i.tenant_id == Some(t));
+    }
+    Ok(Json(ExpiringOutput { items }))
+}
@@ -0,0 +1,147 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use common::feature_flags::{Evaluation, FlagSet};
+use models::{feature_flag, feature_flag_override};
+use service::errors::ServiceError;
+use service::feature_flags::{CreateFlagInput, FlagDetail, UpdateFlagInput};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct CreateFlagInputDoc {
+    /// 小写字母、数字与 `_.-`，如 `gateway.bandit_selection`
+    pub name: String,
+    pub description: Option<String>,
+    pub default_enabled: bool,
+    /// 0-100，按租户稳定分桶
+    pub rollout_percent: i16,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct UpdateFlagInputDoc {
+    pub description: Option<String>,
+    pub default_enabled: Option<bool>,
+    pub rollout_percent: Option<i16>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetOverrideInput {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct EvaluateQuery {
+    /// 不传时只看默认值
+    pub tenant_id: Option<Uuid>,
+}
+
+fn map_err(e: ServiceError, title: &str) -> JsonApiError {
+    match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string())),
+        _ => { error!(err = %e, "{}", title); JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, title, Some(e.to_string())) }
+    }
+}
+
+#[utoipa::path(
+    get, path = "/admin/feature-flags", tag = "admin", operation_id = "list_feature_flags",
+    responses((status = 200, description = "Feature flags by name, with tenant overrides", body = [crate::openapi::FeatureFlagDetailDoc]))
+)]
+pub async fn list(State(state): State<ServerState>) -> Result<Json<Vec<FlagDetail>>, JsonApiError> {
+    state.feature_flags.list().await.map(Json).map_err(|e| map_err(e, "List Failed"))
+}
+
+/// 判定顺序：租户覆盖 → 百分比灰度 → 默认值
+#[utoipa::path(
+    post, path = "/admin/feature-flags", tag = "admin", operation_id = "create_feature_flag",
+    request_body = CreateFlagInputDoc,
+    responses((status = 200, description = "Created", body = crate::openapi::FeatureFlagDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn create(State(state): State<ServerState>, Json(input): Json<CreateFlagInput>) -> Result<Json<feature_flag::Model>, JsonApiError> {
+    let created = state.feature_flags.create(input).await.map_err(|e| map_err(e, "Create Failed"))?;
+    info!(flag = %created.name, "admin created feature flag");
+    Ok(Json(created))
+}
+
+#[utoipa::path(
+    get, path = "/admin/feature-flags/{name}", tag = "admin", operation_id = "get_feature_flag",
+    params(("name" = String, Path, description = "Flag name")),
+    responses((status = 200, description = "Feature flag with tenant overrides", body = crate::openapi::FeatureFlagDetailDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get(State(state): State<ServerState>, Path(name): Path<String>) -> Result<Json<FlagDetail>, JsonApiError> {
+    state.feature_flags.get(&name).await.map(Json).map_err(|e| map_err(e, "Get Failed"))
+}
+
+#[utoipa::path(
+    put, path = "/admin/feature-flags/{name}", tag = "admin", operation_id = "update_feature_flag",
+    params(("name" = String, Path, description = "Flag name")),
+    request_body = UpdateFlagInputDoc,
+    responses((status = 200, description = "Updated", body = crate::openapi::FeatureFlagDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn update(State(state): State<ServerState>, Path(name): Path<String>, Json(input): Json<UpdateFlagInput>) -> Result<Json<feature_flag::Model>, JsonApiError> {
+    let updated = state.feature_flags.update(&name, input).await.map_err(|e| map_err(e, "Update Failed"))?;
+    info!(flag = %name, default_enabled = updated.default_enabled, rollout_percent = updated.rollout_percent, "admin updated feature flag");
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete, path = "/admin/feature-flags/{name}", tag = "admin", operation_id = "delete_feature_flag",
+    params(("name" = String, Path, description = "Flag name")),
+    responses((status = 204, description = "Deleted"), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn delete(State(state): State<ServerState>, Path(name): Path<String>) -> Result<StatusCode, JsonApiError> {
+    state.feature_flags.delete(&name).await.map_err(|e| map_err(e, "Delete Failed"))?;
+    info!(flag = %name, "admin deleted feature flag");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 对单个租户强制开/关，优先于灰度与默认值
+#[utoipa::path(
+    put, path = "/admin/feature-flags/{name}/overrides/{tenant_id}", tag = "admin",
+    params(("name" = String, Path, description = "Flag name"), ("tenant_id" = Uuid, Path, description = "Tenant ID")),
+    request_body = SetOverrideInput,
+    responses((status = 200, description = "Override set", body = crate::openapi::FeatureFlagOverrideDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn set_override(
+    State(state): State<ServerState>,
+    Path((name, tenant_id)): Path<(String, Uuid)>,
+    Json(input): Json<SetOverrideInput>,
+) -> Result<Json<feature_flag_override::Model>, JsonApiError> {
+    let ov = state.feature_flags.set_override(&name, tenant_id, input.enabled).await.map_err(|e| map_err(e, "Override Failed"))?;
+    info!(flag = %name, tenant_id = %tenant_id, enabled = input.enabled, "admin set feature flag override");
+    Ok(Json(ov))
+}
+
+#[utoipa::path(
+    delete, path = "/admin/feature-flags/{name}/overrides/{tenant_id}", tag = "admin",
+    params(("name" = String, Path, description = "Flag name"), ("tenant_id" = Uuid, Path, description = "Tenant ID")),
+    responses((status = 204, description = "Override removed"), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn remove_override(State(state): State<ServerState>, Path((name, tenant_id)): Path<(String, Uuid)>) -> Result<StatusCode, JsonApiError> {
+    state.feature_flags.remove_override(&name, tenant_id).await.map_err(|e| map_err(e, "Override Failed"))?;
+    info!(flag = %name, tenant_id = %tenant_id, "admin removed feature flag override");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 某租户的判定结果与来源（override / rollout / default / unknown）
+#[utoipa::path(
+    get, path = "/admin/feature-flags/{name}/evaluate", tag = "admin",
+    params(("name" = String, Path, description = "Flag name"), EvaluateQuery),
+    responses((status = 200, description = "Evaluation result", body = crate::openapi::FlagEvaluationDoc))
+)]
+pub async fn evaluate(State(state): State<ServerState>, Path(name): Path<String>, Query(q): Query<EvaluateQuery>) -> Result<Json<Evaluation>, JsonApiError> {
+    state.feature_flags.evaluate(&name, q.tenant_id).await.map(Json).map_err(|e| map_err(e, "Evaluate Failed"))
+}
+
+/// 全部开关的判定规则，格式与网关 config.json 的 `feature_flags` 相同，可直接下发
+#[utoipa::path(
+    get, path = "/admin/feature-flag-rules", tag = "admin",
+    responses((status = 200, description = "Flag rules (name, default_enabled, rollout_percent, overrides)", body = Object))
+)]
+pub async fn rules(State(state): State<ServerState>) -> Result<Json<FlagSet>, JsonApiError> {
+    let set = state.feature_flags.snapshot().await.map_err(|e| map_err(e, "List Failed"))?;
+    Ok(Json(set.as_ref().clone()))
+}
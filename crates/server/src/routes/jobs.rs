@@ -0,0 +1,166 @@
+//! 长耗时管理操作统一走后台任务：提交后返回 `202 Accepted` + 任务 ID，
+//! 通过 GET /admin/jobs/{id} 查询进度与结果，DELETE /admin/jobs/{id} 取消。
+
+use axum::{
+    extract::{Path, State},
+    http::{header::{LOCATION, RETRY_AFTER}, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::info;
+use uuid::Uuid;
+
+use service::auth::domain::TokenClaims;
+use service::jobs::{JobInfo, QueueFull};
+
+use crate::{errors::JsonApiError, routes::{auth::{self, ServerState}, proxy_apis::CreateProxyApiInput}};
+
+/// 队列已满时建议的重试间隔（秒）
+const QUEUE_FULL_RETRY_AFTER_SECS: u32 = 5;
+
+fn accepted(job: JobInfo) -> Response {
+    let location = format!("/admin/jobs/{}", job.id);
+    let mut out = (StatusCode::ACCEPTED, Json(job)).into_response();
+    if let Ok(v) = HeaderValue::from_str(&location) {
+        out.headers_mut().insert(LOCATION, v);
+    }
+    out
+}
+
+fn queue_full(e: QueueFull) -> Response {
+    let mut out = JsonApiError::new(StatusCode::TOO_MANY_REQUESTS, "Too Many Jobs", Some(e.to_string())).into_response();
+    out.headers_mut().insert(RETRY_AFTER, HeaderValue::from(QUEUE_FULL_RETRY_AFTER_SECS));
+    out
+}
+
+fn submitted(r: Result<JobInfo, QueueFull>) -> Response {
+    r.map_or_else(queue_full, accepted)
+}
+
+#[utoipa::path(get, path = "/admin/jobs", tag = "admin", operation_id = "list_jobs", responses((status = 200, description = "Jobs, newest first", body = [crate::openapi::JobInfoDoc])))]
+pub async fn list(State(state): State<ServerState>) -> Json<Vec<JobInfo>> {
+    Json(state.jobs.list())
+}
+
+#[utoipa::path(
+    get, path = "/admin/jobs/{id}", tag = "admin", operation_id = "get_job",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses((status = 200, description = "Job status, progress and result", body = crate::openapi::JobInfoDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<JobInfo>, JsonApiError> {
+    state.jobs.get(id).map(Json).ok_or_else(|| JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", None))
+}
+
+/// 请求取消：排队中的任务直接取消，运行中的任务在处理下一项前停止（已完成的部分保留）
+#[utoipa::path(
+    delete, path = "/admin/jobs/{id}", tag = "admin",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses((status = 200, description = "Cancellation requested", body = crate::openapi::JobInfoDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn cancel(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<JobInfo>, JsonApiError> {
+    state.jobs.cancel(id).map(Json).ok_or_else(|| JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", None))
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct ImportProxyApisInput {
+    #[schema(value_type = Vec<Object>)]
+    pub items: Vec<CreateProxyApiInput>,
+}
+
+/// 批量导入 proxy API；单条失败不影响其余条目，结果中列出失败项
+#[utoipa::path(
+    post, path = "/admin/proxy-apis/import", tag = "proxy",
+    request_body = ImportProxyApisInput,
+    responses((status = 202, description = "Job accepted", body = crate::openapi::JobInfoDoc), (status = 429, description = "Too Many Jobs"))
+)]
+pub async fn import_proxy_apis(State(state): State<ServerState>, Json(input): Json<ImportProxyApisInput>) -> Response {
+    let svc = state.proxy_api_svc.clone();
+    let db = state.db.clone();
+    submitted(state.jobs.submit("proxy_api_import", move |ctx| async move {
+        ctx.set_total(input.items.len() as u64);
+        let (mut created, mut failed) = (Vec::new(), Vec::new());
+        for (index, item) in input.items.iter().enumerate() {
+            if ctx.is_cancelled() {
+                break;
+            }
+            let tid = item.tenant_id.as_deref().and_then(|s| Uuid::parse_str(s).ok()).unwrap_or_else(Uuid::new_v4);
+            match svc.create(tid, &item.endpoint_url, &item.method, &item.forward_target, item.require_api_key, &db).await {
+                Ok(m) => created.push(m.id),
+                Err(e) => failed.push(json!({ "index": index, "error": e.to_string() })),
+            }
+            ctx.advance(1);
+        }
+        info!(job_id = %ctx.id(), created = created.len(), failed = failed.len(), "proxy_api_import_done");
+        Ok(json!({ "created": created, "failed": failed }))
+    }))
+}
+
+/// 导出租户配置（租户、路由、引用的上游、限流规则、proxy API），结果为快照 JSON
+#[utoipa::path(
+    post, path = "/admin/tenants/{id}/export", tag = "admin",
+    params(("id" = Uuid, Path, description = "Tenant ID")),
+    responses((status = 202, description = "Job accepted", body = crate::openapi::JobInfoDoc), (status = 429, description = "Too Many Jobs"))
+)]
+pub async fn export_tenant(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Response {
+    submitted(state.jobs.submit("tenant_export", move |ctx| async move {
+        ctx.set_total(1);
+        let snapshot = backups.capture().await?.for_tenant(id);
+        if snapshot.tenants.is_empty() {
+            return Err(service::errors::ServiceError::not_found("tenant"));
+        }
+        ctx.advance(1);
+        serde_json::to_value(snapshot).map_err(|e| service::errors::ServiceError::Validation(e.to_string()))
+    }))
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct RevokeApiKeysInput {
+    /// 要吊销 API Key 的用户
+    pub users: Vec<String>,
+}
+
+/// 批量吊销 API Key
+#[utoipa::path(
+    post, path = "/admin/api-keys/revoke", tag = "admin",
+    request_body = RevokeApiKeysInput,
+    responses((status = 202, description = "Job accepted", body = crate::openapi::JobInfoDoc), (status = 429, description = "Too Many Jobs"))
+)]
+pub async fn revoke_api_keys(State(state): State<ServerState>, Json(input): Json<RevokeApiKeysInput>) -> Response {
+    let store = state.admin_kv_store.clone();
+    submitted(state.jobs.submit("api_key_revoke", move |ctx| async move {
+        ctx.set_total(input.users.len() as u64);
+        let (mut revoked, mut missing) = (Vec::new(), Vec::new());
+        for user in input.users {
+            if ctx.is_cancelled() {
+                break;
+            }
+            if store.delete(&user).await? {
+                revoked.push(user);
+            } else {
+                missing.push(user);
+            }
+            ctx.advance(1);
+        }
+        info!(job_id = %ctx.id(), revoked = revoked.len(), missing = missing.len(), "api_key_revoke_done");
+        Ok(json!({ "revoked": revoked, "missing": missing }))
+    }))
+}
+
+/// 轮换主密钥后把加密字段改写为当前主密钥（未加密的旧数据同时加密）；改写所有租户的数据，需要 admin 角色
+#[utoipa::path(
+    post, path = "/admin/encryption/reencrypt", tag = "admin",
+    responses((status = 202, description = "Job accepted", body = crate::openapi::JobInfoDoc), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody), (status = 429, description = "Too Many Jobs"))
+)]
+pub async fn reencrypt_columns(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Response {
+    if let Err(e) = auth::require_admin(&state, &claims).await {
+        return e.into_response();
+    }
+    let db = state.db.clone();
+    submitted(state.jobs.submit("reencrypt_columns", move |ctx| async move {
+        let report = service::encryption::reencrypt_all(&db, Some(&ctx)).await?;
+        info!(job_id = %ctx.id(), key_id = %report.key_id, "reencrypt_columns_done");
+        serde_json::to_value(report).map_err(|e| service::errors::ServiceError::Validation(e.to_string()))
+    }))
+}
@@ -0,0 +1,65 @@
+use axum::{extract::{Path, State}, http::StatusCode, Extension, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use models::jwt_signing_key;
+use service::auth::domain::TokenClaims;
+use service::auth::keys::KeyAlgorithm;
+use service::errors::ServiceError;
+
+use crate::{errors::JsonApiError, routes::auth::{self, ServerState}};
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct RotateKeyInput {
+    /// HS256（默认）或 EdDSA
+    pub algorithm: Option<String>,
+}
+
+fn map_err(e: ServiceError, title: &str) -> JsonApiError {
+    match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string())),
+        _ => { error!(err = %e, "{}", title); JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, title, Some(e.to_string())) }
+    }
+}
+
+/// 签名密钥列表（不含密钥材料；EdDSA 附带公钥）；需要 admin 角色
+#[utoipa::path(
+    get, path = "/admin/jwt-keys", tag = "admin", operation_id = "list_jwt_keys",
+    responses((status = 200, description = "Signing keys, newest first", body = [crate::openapi::JwtSigningKeyDoc]), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Result<Json<Vec<jwt_signing_key::Model>>, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    state.jwt_keys.list().await.map(Json).map_err(|e| map_err(e, "List Failed"))
+}
+
+/// 生成新密钥并用于签发；旧密钥在宽限期内仍可校验。需要 admin 角色
+#[utoipa::path(
+    post, path = "/admin/jwt-keys/rotate", tag = "admin",
+    request_body = RotateKeyInput,
+    responses((status = 200, description = "New active key", body = crate::openapi::JwtSigningKeyDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn rotate(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Json(input): Json<RotateKeyInput>) -> Result<Json<jwt_signing_key::Model>, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    let algorithm = match input.algorithm.as_deref() {
+        None => KeyAlgorithm::HS256,
+        Some(a) => KeyAlgorithm::parse(a)
+            .ok_or_else(|| JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(format!("unsupported algorithm {} (HS256 or EdDSA)", a))))?,
+    };
+    let key = state.jwt_keys.rotate(algorithm).await.map_err(|e| map_err(e, "Rotate Failed"))?;
+    info!(kid = %key.kid, algorithm = %key.algorithm, "admin rotated jwt signing key");
+    Ok(Json(key))
+}
+
+/// 立即停止接受该密钥签发的令牌（不能是当前签名密钥）；需要 admin 角色
+#[utoipa::path(
+    delete, path = "/admin/jwt-keys/{kid}", tag = "admin",
+    params(("kid" = String, Path, description = "Key ID")),
+    responses((status = 204, description = "Retired"), (status = 400, description = "Active key", body = crate::errors::JsonApiErrorBody), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn retire(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Path(kid): Path<String>) -> Result<StatusCode, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    state.jwt_keys.retire(&kid).await.map_err(|e| map_err(e, "Retire Failed"))?;
+    info!(kid = %kid, "admin retired jwt signing key");
+    Ok(StatusCode::NO_CONTENT)
+}
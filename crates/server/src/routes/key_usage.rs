@@ -0,0 +1,51 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use service::errors::ServiceError;
+use service::key_usage::{KeyUsage, KeyUsageQuery};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_RANGE_SECS: i64 = 86_400;
+const DEFAULT_TOP: u32 = 10;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct KeyUsageParams {
+    /// RFC 3339，默认 `to` 前 24 小时
+    pub from: Option<DateTime<Utc>>,
+    /// RFC 3339，默认当前时间；范围最长 31 天
+    pub to: Option<DateTime<Utc>>,
+    /// 请求最多的路由数量（1-100，默认 10）
+    pub top: Option<u32>,
+}
+
+/// 单个 API key 在时间范围内的用量：请求数、错误率、429 次数、状态码分布、延迟统计与热门路由（基于 request_log）
+#[utoipa::path(
+    get, path = "/admin/api-keys/{id}/usage", tag = "admin", operation_id = "api_key_usage",
+    params(("id" = Uuid, Path, description = "API key ID (request_log.api_key_id)"), KeyUsageParams),
+    responses(
+        (status = 200, description = "Usage of the key in [from, to)", body = crate::openapi::KeyUsageDoc),
+        (status = 400, description = "Invalid Query", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn usage(State(state): State<ServerState>, Path(id): Path<Uuid>, Query(q): Query<KeyUsageParams>) -> Result<Json<KeyUsage>, JsonApiError> {
+    let to = q.to.unwrap_or_else(Utc::now);
+    let query = KeyUsageQuery {
+        api_key_id: id,
+        from: q.from.unwrap_or(to - chrono::Duration::seconds(DEFAULT_RANGE_SECS)),
+        to,
+        top: q.top.unwrap_or(DEFAULT_TOP),
+    };
+    state.request_logs.key_usage(&query).await.map(Json).map_err(|e| match e {
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Query", Some(e.to_string())),
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(format!("api key {} not found", id))),
+        _ => {
+            error!(err = %e, api_key_id = %id, "api key usage query failed");
+            JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+        }
+    })
+}
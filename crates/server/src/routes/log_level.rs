@@ -0,0 +1,41 @@
+use axum::{http::StatusCode, Json};
+use serde::Deserialize;
+use tracing::info;
+
+use common::utils::logging::{self, LogFilterError, LogFilterState};
+
+use crate::errors::JsonApiError;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct LogLevelInput {
+    /// EnvFilter 规则，如 `info,service::webhooks=debug`；为 null 时恢复启动时的规则
+    pub filter: Option<String>,
+}
+
+fn map_err(e: LogFilterError) -> JsonApiError {
+    match e {
+        LogFilterError::Invalid(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Filter", Some(e.to_string())),
+        LogFilterError::NotInstalled => JsonApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Log Level Unavailable", Some(e.to_string())),
+    }
+}
+
+/// 当前日志过滤规则与启动时的规则
+#[utoipa::path(
+    get, path = "/admin/log-level", tag = "admin", operation_id = "get_log_level",
+    responses((status = 200, description = "Current and initial filter", body = crate::openapi::LogFilterStateDoc), (status = 503, description = "Log Level Unavailable", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get() -> Result<Json<LogFilterState>, JsonApiError> {
+    logging::current_filter().map(Json).map_err(map_err)
+}
+
+/// 运行时调整日志级别（不重启）；网关在 admin 端口提供同样的 `PUT /log-level`
+#[utoipa::path(
+    put, path = "/admin/log-level", tag = "admin",
+    request_body = LogLevelInput,
+    responses((status = 200, description = "Filter applied", body = crate::openapi::LogFilterStateDoc), (status = 400, description = "Invalid Filter", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn set(Json(input): Json<LogLevelInput>) -> Result<Json<LogFilterState>, JsonApiError> {
+    let state = logging::set_filter(input.filter.as_deref()).map_err(map_err)?;
+    info!(filter = %state.filter, "admin changed log filter");
+    Ok(Json(state))
+}
@@ -0,0 +1,21 @@
+use axum::{extract::State, http::StatusCode, Json};
+use tracing::error;
+
+use service::observability::{self, ObservabilityBundle};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+/// 按当前上游/路由配置与 [slo] 目标生成 Prometheus 告警规则和 Grafana dashboard JSON
+#[utoipa::path(
+    get, path = "/admin/observability/bundle", tag = "admin",
+    responses(
+        (status = 200, description = "Prometheus rule groups and Grafana dashboard model", body = Object),
+        (status = 500, description = "Generate Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn bundle(State(state): State<ServerState>) -> Result<Json<ObservabilityBundle>, JsonApiError> {
+    observability::load_bundle(&state.db, &state.slo).await.map(Json).map_err(|e| {
+        error!(err = %e, "generate observability bundle failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Generate Failed", Some(e.to_string()))
+    })
+}
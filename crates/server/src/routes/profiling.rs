@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use tracing::info;
+
+use common::profiling::{self, Profile, ProfileParams, ProfilingError};
+use service::auth::domain::TokenClaims;
+
+use crate::errors::JsonApiError;
+use crate::routes::auth::{self, ServerState};
+
+fn map_err(e: ProfilingError) -> JsonApiError {
+    let (status, title) = match e {
+        ProfilingError::Unsupported(_) => (StatusCode::NOT_IMPLEMENTED, "Profiling Unsupported"),
+        ProfilingError::Busy => (StatusCode::CONFLICT, "Profile In Progress"),
+        ProfilingError::InvalidParams(_) => (StatusCode::BAD_REQUEST, "Invalid Parameters"),
+        ProfilingError::Failed(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Profiling Failed"),
+    };
+    JsonApiError::new(status, title, Some(e.to_string()))
+}
+
+fn into_response(p: Profile, filename: &str) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, p.content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", filename)),
+        ],
+        p.body,
+    )
+        .into_response()
+}
+
+/// CPU 剖析：采样 `seconds` 秒后返回火焰图（SVG）或 pprof protobuf；需要 admin 角色
+#[utoipa::path(
+    get, path = "/admin/debug/pprof/profile", tag = "admin",
+    params(
+        ("seconds" = Option<u64>, Query, description = "Sampling duration, 1..=60 (default 10)"),
+        ("frequency" = Option<i32>, Query, description = "Samples per second (default 99)"),
+        ("format" = Option<String>, Query, description = "flamegraph | proto")
+    ),
+    responses(
+        (status = 200, description = "Profile"),
+        (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "Profile In Progress", body = crate::errors::JsonApiErrorBody),
+        (status = 501, description = "Profiling Unsupported", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn profile(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>, Query(params): Query<ProfileParams>) -> Result<Response, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    info!(seconds = ?params.seconds, format = ?params.format, "admin started cpu profile");
+    let p = profiling::cpu_profile(&params).await.map_err(map_err)?;
+    let filename = if p.content_type == "image/svg+xml" { "cpu.svg" } else { "cpu.pb" };
+    Ok(into_response(p, filename))
+}
+
+/// 堆剖析：jemalloc `prof.dump` 输出，用 `jeprof` 分析；需要 admin 角色
+#[utoipa::path(
+    get, path = "/admin/debug/pprof/heap", tag = "admin",
+    responses((status = 200, description = "Heap profile"), (status = 403, description = "Admin role required", body = crate::errors::JsonApiErrorBody), (status = 501, description = "Profiling Unsupported", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn heap(State(state): State<ServerState>, Extension(claims): Extension<TokenClaims>) -> Result<Response, JsonApiError> {
+    auth::require_admin(&state, &claims).await?;
+    let p = tokio::task::spawn_blocking(profiling::heap_profile)
+        .await
+        .map_err(|e| map_err(ProfilingError::Failed(e.to_string())))?
+        .map_err(map_err)?;
+    Ok(into_response(p, "heap.prof"))
+}
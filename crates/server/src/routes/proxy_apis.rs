@@ -3,12 +3,23 @@ use serde::{Deserialize, Serialize};
 use tracing::{info, error};
 use uuid::Uuid;
 
+use common::pagination::{Page, Pagination};
+use service::proxy_api::{maintenance::MaintenanceWindow, mock::MockResponse};
+
 // removed direct DB tenant operations; handled by service layer
 use crate::{errors::JsonApiError, routes::auth::ServerState};
 // use proper attribute form: #[utoipa::path] on handlers
 
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
-pub struct ListQuery { pub tenant_id: Option<Uuid> }
+pub struct ListQuery {
+    pub tenant_id: Option<Uuid>,
+    /// 1-based page index (default 1)
+    pub page: Option<u32>,
+    /// items per page (default 20, max configured by `pagination.max_per_page`)
+    pub per_page: Option<u32>,
+    /// opaque cursor from a previous `next_cursor`; overrides page/per_page
+    pub cursor: Option<String>,
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateProxyApiInput {
@@ -31,27 +42,31 @@ pub struct UpdateProxyApiInput {
 }
 
 #[utoipa::path(
-    get, path = "/admin/proxy-apis", tag = "proxy",
+    get, path = "/admin/proxy-apis", tag = "proxy", operation_id = "list_proxy_apis",
     params(ListQuery),
     responses(
-        (status = 200, description = "List OK"),
-        (status = 500, description = "List Failed")
+        (status = 200, description = "List OK", body = crate::openapi::ProxyApiPageDoc),
+        (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "List Failed", body = crate::errors::JsonApiErrorBody)
     )
 )]
-pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Vec<models::proxy_api::Model>>, JsonApiError> {
-    match state.proxy_api_svc.list(q.tenant_id).await {
-        Ok(list) => { info!(count = list.len(), "list proxy apis"); Ok(Json(list)) }
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<models::proxy_api::Model>>, JsonApiError> {
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page)
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())))?;
+    match state.proxy_api_svc.list_page(q.tenant_id, opts).await {
+        Ok(page) => { info!(count = page.items.len(), total = page.total, "list proxy apis"); Ok(Json(page)) }
+        Err(e @ service::errors::ServiceError::Pagination(_)) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string()))),
         Err(e) => Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "List Failed", Some(e.to_string()))),
     }
 }
 
 #[utoipa::path(
-    post, path = "/admin/proxy-apis", tag = "proxy",
+    post, path = "/admin/proxy-apis", tag = "proxy", operation_id = "create_proxy_api",
     request_body = crate::openapi::CreateProxyApiInputDoc,
     responses(
-        (status = 200, description = "Created"),
-        (status = 400, description = "Validation Error"),
-        (status = 500, description = "Create Failed")
+        (status = 200, description = "Created", body = crate::openapi::ProxyApiDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Create Failed", body = crate::errors::JsonApiErrorBody)
     )
 )]
 pub async fn create(State(state): State<ServerState>, Json(input): Json<CreateProxyApiInput>) -> Result<Json<models::proxy_api::Model>, JsonApiError> {
@@ -74,11 +89,34 @@ pub async fn create(State(state): State<ServerState>, Json(input): Json<CreatePr
     }
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct OpenApiQuery {
+    pub tenant_id: Uuid,
+}
+
+/// 该租户已启用的 proxy API 生成的数据面 OpenAPI 3.0 文档（路径、方法、API key 要求），不含上游地址
+#[utoipa::path(
+    get, path = "/admin/proxy-apis/openapi.json", tag = "proxy",
+    params(OpenApiQuery),
+    responses(
+        (status = 200, description = "OpenAPI document", body = Object),
+        (status = 400, description = "Missing tenant_id", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "List Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn openapi(State(state): State<ServerState>, Query(q): Query<OpenApiQuery>) -> Result<Json<serde_json::Value>, JsonApiError> {
+    let apis = state.proxy_api_svc.list(Some(q.tenant_id)).await.map_err(|e| {
+        error!(err = %e, tenant_id = %q.tenant_id, "list proxy apis for openapi failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "List Failed", Some(e.to_string()))
+    })?;
+    Ok(Json(service::proxy_api::openapi::document(q.tenant_id, &apis)))
+}
+
 #[utoipa::path(
-    get, path = "/admin/proxy-apis/{id}", tag = "proxy",
+    get, path = "/admin/proxy-apis/{id}", tag = "proxy", operation_id = "get_proxy_api",
     params(("id" = Uuid, Path, description = "Proxy API ID")),
     responses(
-        (status = 200, description = "OK"),
+        (status = 200, description = "OK", body = crate::openapi::ProxyApiDoc),
         (status = 404, description = "Not Found")
     )
 )]
@@ -91,14 +129,14 @@ pub async fn get(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Resu
 }
 
 #[utoipa::path(
-    put, path = "/admin/proxy-apis/{id}", tag = "proxy",
+    put, path = "/admin/proxy-apis/{id}", tag = "proxy", operation_id = "update_proxy_api",
     params(("id" = Uuid, Path, description = "Proxy API ID")),
     request_body = crate::openapi::UpdateProxyApiInputDoc,
     responses(
-        (status = 200, description = "Updated"),
-        (status = 400, description = "Validation Error"),
-        (status = 404, description = "Not Found"),
-        (status = 500, description = "Update Failed")
+        (status = 200, description = "Updated", body = crate::openapi::ProxyApiDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
     )
 )]
 pub async fn update(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<UpdateProxyApiInput>) -> Result<Json<models::proxy_api::Model>, JsonApiError> {
@@ -121,8 +159,102 @@ pub async fn update(State(state): State<ServerState>, Path(id): Path<Uuid>, Json
     }
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SetMockInput {
+    pub enabled: bool,
+    /// 省略时沿用已保存的 mock 响应
+    #[schema(value_type = Option<Object>)]
+    pub response: Option<MockResponse>,
+}
+
+/// 开启/关闭 mock 模式：返回配置的 status/headers/body（支持 {{method}}、{{path}}、{{query.x}}、{{header.x}}、{{now}} 模板）
+#[utoipa::path(
+    put, path = "/admin/proxy-apis/{id}/mock", tag = "proxy",
+    params(("id" = Uuid, Path, description = "Proxy API ID")),
+    request_body = SetMockInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::ProxyApiDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_mock(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetMockInput>) -> Result<Json<models::proxy_api::Model>, JsonApiError> {
+    match state.proxy_api_svc.set_mock(id, input.enabled, input.response).await {
+        Ok(m) => Ok(Json(m)),
+        Err(e @ (service::errors::ServiceError::Validation(_) | service::errors::ServiceError::Model(_))) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Error", Some(e.to_string()))),
+        Err(e @ service::errors::ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set proxy api mock failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetGrpcMethodInput {
+    /// `package.Service/Method`（需在 [grpc] descriptor_sets 中定义，且为 unary 方法）；为空则关闭转码
+    pub grpc_method: Option<String>,
+}
+
+/// REST → gRPC 转码：该 API 的 JSON 请求编码为 grpc_method 的输入消息，响应解码回 JSON
+#[utoipa::path(
+    put, path = "/admin/proxy-apis/{id}/grpc", tag = "proxy",
+    params(("id" = Uuid, Path, description = "Proxy API ID")),
+    request_body = SetGrpcMethodInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::ProxyApiDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_grpc_method(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetGrpcMethodInput>) -> Result<Json<models::proxy_api::Model>, JsonApiError> {
+    let grpc_method = input.grpc_method.map(|m| m.trim().trim_start_matches('/').to_string()).filter(|m| !m.is_empty());
+    if let Some(m) = &grpc_method {
+        let transcoder = state.grpc.as_ref().ok_or_else(|| {
+            JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Error", Some("gRPC transcoding is not configured; set [grpc] descriptor_sets".into()))
+        })?;
+        transcoder.method(m).map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Error", Some(e.to_string())))?;
+    }
+    match state.proxy_api_svc.set_grpc_method(id, grpc_method).await {
+        Ok(m) => Ok(Json(m)),
+        Err(e @ service::errors::ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set proxy api grpc method failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetMaintenanceInput {
+    /// 维护开始时间（RFC 3339）；为空表示立即生效
+    pub start: Option<chrono::DateTime<chrono::Utc>>,
+    /// 维护结束时间；为空表示直到手动清除
+    pub end: Option<chrono::DateTime<chrono::Utc>>,
+    /// 停用或维护期间返回的 503 body；为空时返回默认 JSON
+    pub message: Option<String>,
+}
+
+/// 设置维护窗口（start/end 均为空则清除）；停用（enabled=false）与维护期间都返回 503
+#[utoipa::path(
+    put, path = "/admin/proxy-apis/{id}/maintenance", tag = "proxy",
+    params(("id" = Uuid, Path, description = "Proxy API ID")),
+    request_body = SetMaintenanceInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::ProxyApiDoc),
+        (status = 400, description = "Validation Error", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_maintenance(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetMaintenanceInput>) -> Result<Json<models::proxy_api::Model>, JsonApiError> {
+    let window = MaintenanceWindow { start: input.start, end: input.end, message: input.message };
+    match state.proxy_api_svc.set_maintenance(id, window).await {
+        Ok(m) => Ok(Json(m)),
+        Err(e @ service::errors::ServiceError::Validation(_)) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Error", Some(e.to_string()))),
+        Err(e @ service::errors::ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set proxy api maintenance failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
+
 #[utoipa::path(
-    delete, path = "/admin/proxy-apis/{id}", tag = "proxy",
+    delete, path = "/admin/proxy-apis/{id}", tag = "proxy", operation_id = "delete_proxy_api",
     params(("id" = Uuid, Path, description = "Proxy API ID")),
     responses(
         (status = 204, description = "Deleted"),
@@ -0,0 +1,108 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, response::{Html, IntoResponse, Response}, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use service::errors::ServiceError;
+use service::reports::{Dimension, Metric, TopQuery, TopReport};
+use service::scheduled_reports::ReportPeriod;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_RANGE: &str = "24h";
+const DEFAULT_LIMIT: u32 = 10;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct TopParams {
+    /// route / tenant / api_key / client_ip
+    pub dimension: String,
+    /// requests / errors / latency_p99，默认 requests
+    pub metric: Option<String>,
+    /// 截至 `to` 的时间范围，如 `1h`、`24h`、`7d`（默认 24h，最长 31 天）
+    pub range: Option<String>,
+    /// RFC 3339，默认当前时间
+    pub to: Option<DateTime<Utc>>,
+    /// 返回条数（1-100，默认 10）
+    pub limit: Option<u32>,
+}
+
+fn invalid(e: ServiceError) -> JsonApiError {
+    JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Query", Some(e.to_string()))
+}
+
+/// 按维度排名的 Top-N 报表（路由 / 租户 / API key / 客户端 IP），附全量合计与占比（基于分钟汇总）
+#[utoipa::path(
+    get, path = "/admin/reports/top", tag = "admin",
+    params(TopParams),
+    responses((status = 200, description = "Ranked entries, highest first", body = crate::openapi::TopReportDoc), (status = 400, description = "Invalid Query", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn top(State(state): State<ServerState>, Query(q): Query<TopParams>) -> Result<Json<TopReport>, JsonApiError> {
+    let to = q.to.unwrap_or_else(Utc::now);
+    let query = TopQuery {
+        dimension: Dimension::parse(&q.dimension).map_err(invalid)?,
+        metric: Metric::parse(q.metric.as_deref().unwrap_or("requests")).map_err(invalid)?,
+        from: to - service::reports::parse_range(q.range.as_deref().unwrap_or(DEFAULT_RANGE)).map_err(invalid)?,
+        to,
+        limit: q.limit.unwrap_or(DEFAULT_LIMIT),
+    };
+    service::reports::top(&state.db, &query).await.map(Json).map_err(|e| match e {
+        ServiceError::Validation(_) => invalid(e),
+        _ => {
+            error!(err = %e, "top report query failed");
+            JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+        }
+    })
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct TenantReportParams {
+    /// daily / weekly，默认 daily
+    pub period: Option<String>,
+    /// json / html，默认 json
+    pub format: Option<String>,
+    /// 统计该时刻之前最近一个完整周期（RFC 3339），默认当前时间
+    pub at: Option<DateTime<Utc>>,
+    /// 请求最多的路由数量（1-100，默认 10）
+    pub top: Option<u32>,
+}
+
+/// 预览租户的定期报表（与 `report.generated` webhook 投递的内容相同）：合计、热门路由与限流用量
+#[utoipa::path(
+    get, path = "/admin/tenants/{id}/report", tag = "admin",
+    params(("id" = Uuid, Path, description = "Tenant ID"), TenantReportParams),
+    responses(
+        (status = 200, description = "Report of the last completed period; HTML when format=html", body = crate::openapi::TenantReportDoc),
+        (status = 400, description = "Invalid Query", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn tenant_report(State(state): State<ServerState>, Path(id): Path<Uuid>, Query(q): Query<TenantReportParams>) -> Result<Response, JsonApiError> {
+    let bad = |detail: String| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Query", Some(detail));
+    let period = match q.period.as_deref() {
+        None => ReportPeriod::Daily,
+        Some(p) => ReportPeriod::parse(p).ok_or_else(|| bad(format!("unknown period {}; expected daily or weekly", p)))?,
+    };
+    let html = match q.format.as_deref() {
+        None | Some("json") => false,
+        Some("html") => true,
+        Some(f) => return Err(bad(format!("unknown format {}; expected json or html", f))),
+    };
+    let top = q.top.unwrap_or(DEFAULT_LIMIT);
+    if top == 0 || top > service::reports::MAX_LIMIT {
+        return Err(bad(format!("top must be between 1 and {}", service::reports::MAX_LIMIT)));
+    }
+    let (from, to) = period.last_completed(q.at.unwrap_or_else(Utc::now));
+    let report = service::scheduled_reports::generate(&state.db, id, period, from, to, top).await.map_err(|e| match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(format!("tenant {} not found", id))),
+        _ => {
+            error!(err = %e, tenant_id = %id, "tenant report failed");
+            JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+        }
+    })?;
+    if html {
+        Ok(Html(service::scheduled_reports::render_html(&report)).into_response())
+    } else {
+        Ok(Json(report).into_response())
+    }
+}
@@ -0,0 +1,231 @@
+//! 请求日志查询：按租户 / 路由过滤，`after_id` 增量拉取；
+//! `/admin/request-logs/stream` 以 SSE 实时推送 `/api/*` 请求（不经数据库，连接建立前的请求不会补发）；
+//! `/admin/request-logs/{id}/replay` 按捕获的请求重放并与当时的响应比较。
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::time::Instant;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use common::http_client::UpstreamRequest;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use service::db::request_log_service::{self, RequestLogFilter};
+use service::replay::{self, ReplayDiff, Snapshot};
+use service::request_log_stream::LiveLogFilter;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct LogsQuery {
+    pub tenant_id: Option<Uuid>,
+    pub route_id: Option<Uuid>,
+    /// 只返回 id 大于该值的记录；省略时返回最近的 limit 条
+    pub after_id: Option<i64>,
+    /// 默认 100，最大 1000
+    pub limit: Option<u64>,
+}
+
+/// 请求日志，按 id 升序
+#[utoipa::path(
+    get, path = "/admin/request-logs", tag = "admin", operation_id = "list_request_logs",
+    params(LogsQuery),
+    responses((status = 200, description = "Request log entries, oldest first", body = [crate::openapi::RequestLogDoc]), (status = 500, description = "Query Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<LogsQuery>) -> Result<Json<Vec<models::request_log::Model>>, JsonApiError> {
+    let filter = RequestLogFilter { tenant_id: q.tenant_id, route_id: q.route_id, after_id: q.after_id };
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    state.request_logs.list_recent(&filter, limit).await.map(Json).map_err(|e| {
+        error!(err = %e, "list request logs failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+    })
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct StreamQuery {
+    pub tenant_id: Option<Uuid>,
+    pub proxy_api_id: Option<Uuid>,
+    /// 只推送路径以此开头的请求，如 `/api/orders`
+    pub path_prefix: Option<String>,
+    /// 只推送状态码不小于该值的请求，如 400 只看错误
+    pub min_status: Option<u16>,
+}
+
+/// 实时请求日志（SSE）：每条匹配的请求一个 `log` 事件（JSON）；
+/// 消费过慢时丢弃积压并发送 `lagged` 事件，data 为丢弃条数
+#[utoipa::path(
+    get, path = "/admin/request-logs/stream", tag = "admin", operation_id = "stream_request_logs",
+    params(StreamQuery),
+    responses((status = 200, description = "Server-sent events: `log` (LiveRequestLog JSON) and `lagged` (number of skipped entries)", body = String, content_type = "text/event-stream"))
+)]
+pub async fn stream(State(state): State<ServerState>, Query(q): Query<StreamQuery>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let filter = LiveLogFilter { tenant_id: q.tenant_id, proxy_api_id: q.proxy_api_id, path_prefix: q.path_prefix, min_status: q.min_status };
+    let rx = state.request_log_stream.subscribe();
+    info!(filter = ?filter, subscribers = state.request_log_stream.subscriber_count(), "request log stream opened");
+    let events = futures::stream::unfold((rx, filter), |(mut rx, filter)| async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(entry) if filter.matches(&entry) => match Event::default().event("log").json_data(&*entry) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        error!(err = %e, "encode live request log failed");
+                        continue;
+                    }
+                },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => Event::default().event("lagged").data(skipped.to_string()),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), (rx, filter)));
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// 重放结果中响应体最多返回的字节数
+const MAX_REPLAY_BODY: usize = 64 * 1024;
+
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct ReplayInput {
+    /// 目标 base URL（如 `http://staging:8080`），拼接捕获的路径与查询串；省略时发往路由当前的 upstream
+    pub target: Option<String>,
+    /// 覆盖或补充请求头；捕获时已脱敏的凭据（Authorization 等）不会重发，需在此提供
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// 比较时额外忽略的响应头
+    #[serde(default)]
+    pub ignore_headers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// 已脱敏；超过 64 KiB 时截断
+    pub body: String,
+    pub body_bytes: usize,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResult {
+    pub request_log_id: i64,
+    pub target_url: String,
+    pub replayed: ReplayedResponse,
+    /// 捕获时没有记录响应（如上游不可达）时为空
+    pub diff: Option<ReplayDiff>,
+}
+
+fn bad_request(detail: String) -> JsonApiError {
+    JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Replay", Some(detail))
+}
+
+fn query_failed(e: service::errors::ServiceError) -> JsonApiError {
+    error!(err = %e, "load replay source failed");
+    JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+}
+
+/// 目标 base URL：显式指定时校验 scheme，否则取路由当前 upstream
+async fn replay_base(state: &ServerState, route_id: Uuid, target: Option<String>) -> Result<String, JsonApiError> {
+    if let Some(target) = target {
+        let url = reqwest::Url::parse(&target).map_err(|e| bad_request(format!("invalid target: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(bad_request(format!("unsupported target scheme: {}", url.scheme())));
+        }
+        return Ok(target);
+    }
+    let route = state.read_cache.get_route(&state.db, route_id).await.map_err(query_failed)?;
+    let route = route.ok_or_else(|| JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(format!("route {} no longer exists; pass a target", route_id))))?;
+    let upstream = state.read_cache.get_upstream(&state.db, route.upstream_id).await.map_err(query_failed)?;
+    upstream.map(|u| u.base_url).ok_or_else(|| JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(format!("upstream {} not found; pass a target", route.upstream_id))))
+}
+
+/// 按捕获的请求重新发往当前 upstream 或指定目标，返回响应与状态码/响应头/响应体差异（需路由开启请求体捕获）
+#[utoipa::path(
+    post, path = "/admin/request-logs/{id}/replay", tag = "admin", operation_id = "replay_request_log",
+    params(("id" = i64, Path, description = "Request log ID")),
+    request_body = ReplayInput,
+    responses(
+        (status = 200, description = "Replayed", body = crate::openapi::ReplayResultDoc),
+        (status = 400, description = "Invalid target or headers", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "No captured payload, or the captured request body was truncated", body = crate::errors::JsonApiErrorBody),
+        (status = 502, description = "Replay Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn replay(State(state): State<ServerState>, Path(id): Path<i64>, input: Option<Json<ReplayInput>>) -> Result<Json<ReplayResult>, JsonApiError> {
+    let input = input.map(|Json(i)| i).unwrap_or_default();
+    let log = state.request_logs.get(id).await.map_err(query_failed)?;
+    let log = log.ok_or_else(|| JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(format!("request log {} not found", id))))?;
+    let payload = request_log_service::get_payload(&state.db, id).await.map_err(query_failed)?.ok_or_else(|| {
+        JsonApiError::new(StatusCode::CONFLICT, "Body Capture Disabled", Some(format!("request log {} has no captured payload; enable body capture on route {}", id, log.route_id)))
+    })?;
+    if payload.request_truncated {
+        return Err(JsonApiError::new(StatusCode::CONFLICT, "Request Truncated", Some("captured request body exceeded the capture limit".into())));
+    }
+    let method = Method::from_bytes(payload.method.as_bytes()).map_err(|e| bad_request(format!("captured method: {}", e)))?;
+
+    let base = replay_base(&state, log.route_id, input.target).await?;
+    let url = format!("{}{}", base.trim_end_matches('/'), payload.uri);
+    let logged_url = common::redaction::global().redact_uri(&url).into_owned();
+    let mut req = UpstreamRequest::new(method, url);
+    req.body = payload.request_body.clone().unwrap_or_default().into();
+    let redaction = common::redaction::global();
+    for (name, value) in payload.request_header_map() {
+        if replay::SKIPPED_REQUEST_HEADERS.contains(&name.as_str()) || redaction.is_sensitive_header(&name) {
+            continue;
+        }
+        if let (Ok(n), Ok(v)) = (HeaderName::try_from(name.as_str()), HeaderValue::from_str(&value)) {
+            req.headers.insert(n, v);
+        }
+    }
+    for (name, value) in &input.headers {
+        let n = HeaderName::try_from(name.as_str()).map_err(|e| bad_request(format!("header {}: {}", name, e)))?;
+        let v = HeaderValue::from_str(value).map_err(|e| bad_request(format!("header {}: {}", name, e)))?;
+        req.headers.insert(n, v);
+    }
+
+    let started = Instant::now();
+    let resp = state.upstream.http.send(req).await.map_err(|e| {
+        warn!(request_log_id = id, target = %logged_url, err = %e, "request replay failed");
+        JsonApiError::new(StatusCode::BAD_GATEWAY, "Replay Failed", Some(e.to_string()))
+    })?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    info!(target: "audit", action = "request_replay", request_log_id = id, route_id = %log.route_id, target = %logged_url, status = resp.status.as_u16(), "request_replay");
+
+    let replayed = Snapshot { status: resp.status.as_u16(), headers: replay::header_snapshot(&resp.headers), body: replay::body_snapshot(&resp.body), truncated: false };
+    let diff = payload.response_status.map(|status| {
+        let recorded = Snapshot {
+            status: status as u16,
+            headers: payload.response_header_map().unwrap_or_default(),
+            body: payload.response_body.clone().unwrap_or_default(),
+            truncated: payload.response_truncated,
+        };
+        replay::diff(&recorded, &replayed, &input.ignore_headers)
+    });
+    let preview = &replayed.body[..replayed.body.len().min(MAX_REPLAY_BODY)];
+    Ok(Json(ReplayResult {
+        request_log_id: id,
+        target_url: logged_url,
+        replayed: ReplayedResponse {
+            status: replayed.status,
+            body: String::from_utf8_lossy(preview).into_owned(),
+            body_bytes: replayed.body.len(),
+            headers: replayed.headers,
+            latency_ms,
+        },
+        diff,
+    }))
+}
@@ -0,0 +1,42 @@
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use models::config_revision;
+use service::revisions;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct TimelineQuery {
+    /// 只返回大于该版本号的变更
+    pub after: Option<i64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TimelineOutput {
+    /// 当前生效的配置版本
+    pub current_revision: i64,
+    /// 新的在前
+    #[schema(value_type = Vec<Object>)]
+    pub items: Vec<config_revision::Model>,
+}
+
+/// 配置变更时间线：数据面日志中的 config_revision / caused_by_revision 可在此对照
+#[utoipa::path(
+    get, path = "/admin/config-revisions", tag = "admin",
+    params(TimelineQuery),
+    responses((status = 200, description = "Config revisions, newest first", body = TimelineOutput), (status = 500, description = "List Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn timeline(State(state): State<ServerState>, Query(q): Query<TimelineQuery>) -> Result<Json<TimelineOutput>, JsonApiError> {
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let items = revisions::list_revisions(&state.db, q.after, limit).await.map_err(|e| {
+        error!(err = %e, "list config revisions failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "List Failed", Some(e.to_string()))
+    })?;
+    Ok(Json(TimelineOutput { current_revision: state.events.current_revision(), items }))
+}
@@ -0,0 +1,51 @@
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use service::errors::ServiceError;
+use service::metrics_rollup::{MetricsPoint, MetricsQuery};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_RANGE_SECS: i64 = 3600;
+const DEFAULT_STEP_SECS: i64 = 60;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct MetricsQueryParams {
+    /// 路由 ID；不传时汇总全部路由
+    pub route: Option<Uuid>,
+    /// 租户 ID
+    pub tenant: Option<Uuid>,
+    /// RFC 3339，默认 `to` 前 1 小时
+    pub from: Option<DateTime<Utc>>,
+    /// RFC 3339，默认当前时间
+    pub to: Option<DateTime<Utc>>,
+    /// 步长（秒，60 的倍数，默认 60）
+    pub step: Option<i64>,
+}
+
+/// 按分钟汇总的时序指标：请求数、错误数/错误率、平均与 p50/p95/p99 延迟；无流量的步长补 0
+#[utoipa::path(
+    get, path = "/admin/metrics/query", tag = "admin",
+    params(MetricsQueryParams),
+    responses((status = 200, description = "One point per step, oldest first", body = [crate::openapi::MetricsPointDoc]), (status = 400, description = "Invalid Query", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn query(State(state): State<ServerState>, Query(q): Query<MetricsQueryParams>) -> Result<Json<Vec<MetricsPoint>>, JsonApiError> {
+    let to = q.to.unwrap_or_else(Utc::now);
+    let query = MetricsQuery {
+        route_id: q.route,
+        tenant_id: q.tenant,
+        from: q.from.unwrap_or(to - chrono::Duration::seconds(DEFAULT_RANGE_SECS)),
+        to,
+        step: q.step.unwrap_or(DEFAULT_STEP_SECS),
+    };
+    service::metrics_rollup::query(&state.db, &query).await.map(Json).map_err(|e| match e {
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Query", Some(e.to_string())),
+        _ => {
+            error!(err = %e, "metrics query failed");
+            JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Query Failed", Some(e.to_string()))
+        }
+    })
+}
@@ -0,0 +1,35 @@
+//! 路由列表（只读）；蓝绿切换见 blue_green.rs
+
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use serde::Deserialize;
+use tracing::error;
+use uuid::Uuid;
+
+use common::pagination::{Page, Pagination};
+use service::{db::route_service, errors::ServiceError};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ListQuery {
+    pub tenant_id: Uuid,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    /// 上一页返回的 `next_cursor`，优先于 page/per_page
+    pub cursor: Option<String>,
+}
+
+#[utoipa::path(
+    get, path = "/admin/routes", tag = "admin", operation_id = "list_routes",
+    params(ListQuery),
+    responses((status = 200, description = "Routes of the tenant", body = crate::openapi::RoutePageDoc), (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<models::route::Model>>, JsonApiError> {
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page)
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())))?;
+    match route_service::list_routes_by_tenant_paginated(&state.db, q.tenant_id, opts).await {
+        Ok(page) => Ok(Json(page)),
+        Err(e @ ServiceError::Pagination(_)) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "list routes failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "List Failed", Some(e.to_string()))) },
+    }
+}
@@ -0,0 +1,91 @@
+//! 自助开通租户：POST /signup（config.toml [signup] enabled 时注册）。
+//! 一次事务内创建租户、首个管理员、默认限流与初始 API key。`/auth/register` 与创建 proxy API
+//! 遇到不存在的租户 ID 仍会以 `auto-tenant-{id}` 建租户，该行为保持不变。
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use service::{errors::ServiceError, events::ConfigEvent, signup::{SignupDefaults, SignupInput}};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+/// 须携带 `signup.token` 的请求头
+const SIGNUP_TOKEN_HEADER: &str = "x-signup-token";
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SignupRequest {
+    pub tenant_name: String,
+    /// 首个管理员的邮箱与登录密码（>= 8 位）
+    pub email: String,
+    pub name: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SignupOutput {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub rate_limit_id: Uuid,
+    pub requests_per_minute: i32,
+    pub burst: i32,
+    pub api_key_id: Uuid,
+    /// 初始 API key 明文，仅在此返回一次（库中只存哈希）
+    pub api_key: String,
+    /// 之后用管理员邮箱与密码登录
+    pub login_path: String,
+}
+
+/// 自助开通租户
+#[utoipa::path(
+    post, path = "/signup", tag = "auth",
+    request_body = SignupRequest,
+    params(("x-signup-token" = String, Header, description = "与 signup.token 一致")),
+    responses(
+        (status = 201, description = "Tenant created", body = SignupOutput),
+        (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody),
+        (status = 403, description = "Missing or wrong signup token", body = crate::errors::JsonApiErrorBody),
+        (status = 409, description = "Tenant name or email already taken", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Signup Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn signup(State(state): State<ServerState>, headers: HeaderMap, Json(input): Json<SignupRequest>) -> Result<(StatusCode, Json<SignupOutput>), JsonApiError> {
+    let cfg = &state.config.config.signup;
+    // 配置校验保证开启时 token 已设置；缺失时同样拒绝
+    let expected = cfg.token.as_deref().unwrap_or_default();
+    let given = headers.get(SIGNUP_TOKEN_HEADER).and_then(|v| v.to_str().ok()).unwrap_or("");
+    if expected.is_empty() || !common::crypto::constant_time_eq(expected.as_bytes(), given.as_bytes()) {
+        warn!(tenant_name = %input.tenant_name, "signup rejected: bad signup token");
+        return Err(JsonApiError::new(StatusCode::FORBIDDEN, "Forbidden", Some("missing or invalid signup token".into())));
+    }
+    let input = SignupInput { tenant_name: input.tenant_name, email: input.email, name: input.name, password: input.password };
+    let defaults = SignupDefaults { requests_per_minute: cfg.requests_per_minute, burst: cfg.burst };
+    match service::signup::signup(&state.db, &input, defaults, state.clock.as_ref()).await {
+        Ok(r) => {
+            state.read_cache.apply(&state.events, ConfigEvent::TenantChanged { id: r.tenant_id }).await;
+            state.read_cache.apply(&state.events, ConfigEvent::RateLimitChanged { id: r.rate_limit_id }).await;
+            Ok((StatusCode::CREATED, Json(SignupOutput {
+                tenant_id: r.tenant_id,
+                tenant_name: r.tenant_name,
+                user_id: r.user_id,
+                email: r.email,
+                role: r.role,
+                rate_limit_id: r.rate_limit_id,
+                requests_per_minute: r.requests_per_minute,
+                burst: r.burst,
+                api_key_id: r.api_key_id,
+                api_key: r.api_key,
+                login_path: "/auth/login".into(),
+            })))
+        }
+        Err(e @ ServiceError::Conflict(_)) => Err(JsonApiError::new(StatusCode::CONFLICT, "Conflict", Some(e.to_string()))),
+        Err(e @ (ServiceError::Validation(_) | ServiceError::Model(models::errors::ModelError::Validation(_)))) => {
+            Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string())))
+        }
+        Err(e) => { error!(err = %e, "signup failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Signup Failed", Some(e.to_string()))) },
+    }
+}
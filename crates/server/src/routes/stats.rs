@@ -0,0 +1,28 @@
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use serde::Deserialize;
+use tracing::error;
+
+use service::stats::DashboardStats;
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+const DEFAULT_TOP: u32 = 10;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct StatsQuery {
+    /// 流量最高的路由数量（1-100，默认 10）
+    pub top: Option<u32>,
+}
+
+/// 管理台概览：近 1 小时/1 天请求量与错误率、热门路由及 p95/p99、活跃租户、熔断中的路由、限流次数
+#[utoipa::path(
+    get, path = "/admin/stats", tag = "admin", operation_id = "dashboard_stats",
+    params(StatsQuery),
+    responses((status = 200, description = "Aggregate gateway statistics (cached briefly)", body = Object), (status = 500, description = "Stats Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn stats(State(state): State<ServerState>, Query(q): Query<StatsQuery>) -> Result<Json<DashboardStats>, JsonApiError> {
+    state.stats.get(q.top.unwrap_or(DEFAULT_TOP)).await.map(Json).map_err(|e| {
+        error!(err = %e, "compute admin stats failed");
+        JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Stats Failed", Some(e.to_string()))
+    })
+}
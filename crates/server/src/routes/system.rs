@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use configs::resolver::Provenance;
+
+use crate::routes::auth::ServerState;
+
+#[derive(Debug, Deserialize)]
+pub struct ConfigQuery {
+    /// 同时返回每个生效值的来源
+    #[serde(default)]
+    pub include_provenance: bool,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ConfigOutput {
+    /// 生效配置（敏感值已脱敏）
+    #[schema(value_type = Object)]
+    pub config: serde_json::Value,
+    /// 来源优先级，由低到高
+    #[schema(value_type = Vec<String>)]
+    pub precedence: [&'static str; 4],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub provenance: Option<BTreeMap<String, Provenance>>,
+}
+
+/// 查看启动时解析的生效配置；`include_provenance=true` 时附带每个值的来源与被覆盖的来源
+#[utoipa::path(
+    get, path = "/admin/system/config", tag = "admin",
+    params(("include_provenance" = Option<bool>, Query, description = "Include the source of every effective value")),
+    responses((status = 200, description = "Effective configuration", body = ConfigOutput))
+)]
+pub async fn config(State(state): State<ServerState>, Query(q): Query<ConfigQuery>) -> Json<ConfigOutput> {
+    Json(ConfigOutput {
+        config: state.config.redacted_config(),
+        precedence: ["default", "file", "dot_env", "env"],
+        provenance: q.include_provenance.then(|| state.config.redacted_provenance()),
+    })
+}
@@ -0,0 +1,163 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use common::pagination::{Page, Pagination};
+use service::{db::tenant_service, errors::ServiceError, events::ConfigEvent};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ListQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    /// 上一页返回的 `next_cursor`，优先于 page/per_page
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct CreateTenantInput {
+    pub name: String,
+}
+
+/// 租户列表，按名称排序
+#[utoipa::path(
+    get, path = "/admin/tenants", tag = "admin", operation_id = "list_tenants",
+    params(ListQuery),
+    responses((status = 200, description = "Tenants", body = crate::openapi::TenantPageDoc), (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<models::tenant::Model>>, JsonApiError> {
+    let opts = Pagination::resolve(q.page, q.per_page, q.cursor.as_deref(), state.config.config.pagination.max_per_page)
+        .map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())))?;
+    match tenant_service::list_tenants_paginated(&state.db, opts).await {
+        Ok(page) => Ok(Json(page)),
+        Err(e @ ServiceError::Pagination(_)) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "list tenants failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "List Failed", Some(e.to_string()))) },
+    }
+}
+
+#[utoipa::path(
+    post, path = "/admin/tenants", tag = "admin", operation_id = "create_tenant",
+    request_body = CreateTenantInput,
+    responses((status = 200, description = "Created", body = crate::openapi::TenantDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn create(State(state): State<ServerState>, Json(input): Json<CreateTenantInput>) -> Result<Json<models::tenant::Model>, JsonApiError> {
+    match tenant_service::create_tenant(&state.db, input.name.trim()).await {
+        Ok(t) => {
+            info!(tenant_id = %t.id, name = %t.name, "tenant_created");
+            state.read_cache.apply(&state.events, ConfigEvent::TenantChanged { id: t.id }).await;
+            Ok(Json(t))
+        }
+        Err(e @ ServiceError::Model(models::errors::ModelError::Validation(_))) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "create tenant failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Create Failed", Some(e.to_string()))) },
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetSuspensionInput {
+    pub suspended: bool,
+    /// 暂停原因，会出现在被拦截请求的响应中
+    pub reason: Option<String>,
+}
+
+/// 租户 kill switch：暂停后该租户所有 `/api/*` 流量返回 503
+#[utoipa::path(
+    put, path = "/admin/tenants/{id}/suspension", tag = "admin",
+    params(("id" = Uuid, Path, description = "Tenant ID")),
+    request_body = SetSuspensionInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::TenantDoc),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_suspension(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetSuspensionInput>) -> Result<Json<models::tenant::Model>, JsonApiError> {
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let t = tenant_service::set_tenant_suspended(change.txn(), id, input.suspended, input.reason).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(t)
+    };
+    match changed.await {
+        Ok(t) => {
+            info!(tenant_id = %id, suspended = t.suspended, "tenant_suspension_updated");
+            state.read_cache.apply(&state.events, ConfigEvent::TenantChanged { id }).await;
+            Ok(Json(t))
+        }
+        Err(e @ ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set tenant suspension failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetEmailVerificationInput {
+    /// off：注册即激活；optional：发送验证令牌但不拦截登录；required：未验证用户禁止登录
+    pub policy: String,
+}
+
+/// 租户邮箱验证策略
+#[utoipa::path(
+    put, path = "/admin/tenants/{id}/email-verification", tag = "admin",
+    params(("id" = Uuid, Path, description = "Tenant ID")),
+    request_body = SetEmailVerificationInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::TenantDoc),
+        (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_email_verification(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetEmailVerificationInput>) -> Result<Json<models::tenant::Model>, JsonApiError> {
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let t = tenant_service::set_tenant_email_verification(change.txn(), id, &input.policy).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(t)
+    };
+    match changed.await {
+        Ok(t) => {
+            info!(tenant_id = %id, policy = %t.email_verification, "tenant_email_verification_updated");
+            state.read_cache.apply(&state.events, ConfigEvent::TenantChanged { id }).await;
+            Ok(Json(t))
+        }
+        Err(e @ ServiceError::Model(models::errors::ModelError::Validation(_))) => Err(JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string()))),
+        Err(e @ ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set tenant email verification failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct SetRegistrationInput {
+    /// true：关闭 `/auth/register` 自助注册，只能通过 `/admin/invitations` 邀请加入
+    pub invite_only: bool,
+}
+
+/// 租户注册方式
+#[utoipa::path(
+    put, path = "/admin/tenants/{id}/registration", tag = "admin",
+    params(("id" = Uuid, Path, description = "Tenant ID")),
+    request_body = SetRegistrationInput,
+    responses(
+        (status = 200, description = "Updated", body = crate::openapi::TenantDoc),
+        (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody),
+        (status = 500, description = "Update Failed", body = crate::errors::JsonApiErrorBody)
+    )
+)]
+pub async fn set_registration(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<SetRegistrationInput>) -> Result<Json<models::tenant::Model>, JsonApiError> {
+    let changed = async {
+        let change = state.backups.pre_apply(id).await?;
+        let t = tenant_service::set_tenant_invite_only(change.txn(), id, input.invite_only).await?;
+        change.commit().await?;
+        Ok::<_, ServiceError>(t)
+    };
+    match changed.await {
+        Ok(t) => {
+            info!(tenant_id = %id, invite_only = t.invite_only, "tenant_registration_updated");
+            state.read_cache.apply(&state.events, ConfigEvent::TenantChanged { id }).await;
+            Ok(Json(t))
+        }
+        Err(e @ ServiceError::NotFound(_)) => Err(JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string()))),
+        Err(e) => { error!(err = %e, "set tenant registration failed"); Err(JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Update Failed", Some(e.to_string()))) },
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use common::pagination::{Page, Pagination};
+use models::{webhook, webhook_delivery};
+use service::errors::ServiceError;
+use service::webhooks::{CreateWebhookInput, CreatedWebhook, UpdateWebhookInput, WebhookEvent};
+
+use crate::{errors::JsonApiError, routes::auth::ServerState};
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct ListQuery {
+    pub tenant_id: Option<Uuid>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct DeliveriesQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct CreateWebhookInputDoc {
+    pub tenant_id: Uuid,
+    pub url: String,
+    /// circuit_breaker.opened / quota.exceeded / upstream.unhealthy / config.changed / api_key.expiring / certificate.expiring / signing_key.expiring / report.generated / user.verification_requested
+    pub events: Vec<String>,
+    /// 签名密钥；不传时自动生成，只在创建时返回
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct UpdateWebhookInputDoc {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+    /// 轮换签名密钥
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct EmitEventInput {
+    pub tenant_id: Uuid,
+    pub event: String,
+    /// 事件详情，原样放入投递内容的 `data`
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct EmitEventOutput {
+    pub queued: usize,
+}
+
+fn map_err(e: ServiceError, title: &str) -> JsonApiError {
+    match e {
+        ServiceError::NotFound(_) => JsonApiError::new(StatusCode::NOT_FOUND, "Not Found", Some(e.to_string())),
+        ServiceError::Validation(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Validation Failed", Some(e.to_string())),
+        ServiceError::Pagination(_) => JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())),
+        _ => { error!(err = %e, "{}", title); JsonApiError::new(StatusCode::INTERNAL_SERVER_ERROR, title, Some(e.to_string())) }
+    }
+}
+
+fn pagination(state: &ServerState, page: Option<u32>, per_page: Option<u32>, cursor: Option<&str>) -> Result<Pagination, JsonApiError> {
+    Pagination::resolve(page, per_page, cursor, state.config.config.pagination.max_per_page).map_err(|e| JsonApiError::new(StatusCode::BAD_REQUEST, "Invalid Pagination", Some(e.to_string())))
+}
+
+#[utoipa::path(
+    get, path = "/admin/webhooks", tag = "admin", operation_id = "list_webhooks",
+    params(ListQuery),
+    responses((status = 200, description = "Webhooks, newest first (secrets omitted)", body = crate::openapi::WebhookPageDoc), (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn list(State(state): State<ServerState>, Query(q): Query<ListQuery>) -> Result<Json<Page<webhook::Model>>, JsonApiError> {
+    let opts = pagination(&state, q.page, q.per_page, q.cursor.as_deref())?;
+    state.webhooks.list(q.tenant_id, opts).await.map(Json).map_err(|e| map_err(e, "List Failed"))
+}
+
+/// 订阅事件；投递内容以 `X-Webhook-Signature: sha256=<hex>` 签名（HMAC-SHA256，签名串为 `{timestamp}.{body}`）
+#[utoipa::path(
+    post, path = "/admin/webhooks", tag = "admin", operation_id = "create_webhook",
+    request_body = CreateWebhookInputDoc,
+    responses((status = 200, description = "Created, includes the signing secret", body = crate::openapi::CreatedWebhookDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn create(State(state): State<ServerState>, Json(input): Json<CreateWebhookInput>) -> Result<Json<CreatedWebhook>, JsonApiError> {
+    let created = state.webhooks.create(input).await.map_err(|e| map_err(e, "Create Failed"))?;
+    info!(webhook_id = %created.webhook.id, tenant_id = %created.webhook.tenant_id, "admin created webhook");
+    Ok(Json(created))
+}
+
+#[utoipa::path(
+    get, path = "/admin/webhooks/{id}", tag = "admin", operation_id = "get_webhook",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses((status = 200, description = "Webhook", body = crate::openapi::WebhookDoc), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn get(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<Json<webhook::Model>, JsonApiError> {
+    state.webhooks.get(id).await.map(Json).map_err(|e| map_err(e, "Get Failed"))
+}
+
+#[utoipa::path(
+    put, path = "/admin/webhooks/{id}", tag = "admin", operation_id = "update_webhook",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    request_body = UpdateWebhookInputDoc,
+    responses((status = 200, description = "Updated", body = crate::openapi::WebhookDoc), (status = 400, description = "Validation Failed", body = crate::errors::JsonApiErrorBody), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn update(State(state): State<ServerState>, Path(id): Path<Uuid>, Json(input): Json<UpdateWebhookInput>) -> Result<Json<webhook::Model>, JsonApiError> {
+    let updated = state.webhooks.update(id, input).await.map_err(|e| map_err(e, "Update Failed"))?;
+    info!(webhook_id = %id, enabled = updated.enabled, "admin updated webhook");
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    delete, path = "/admin/webhooks/{id}", tag = "admin", operation_id = "delete_webhook",
+    params(("id" = Uuid, Path, description = "Webhook ID")),
+    responses((status = 204, description = "Deleted"), (status = 404, description = "Not Found", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn delete(State(state): State<ServerState>, Path(id): Path<Uuid>) -> Result<StatusCode, JsonApiError> {
+    state.webhooks.delete(id).await.map_err(|e| map_err(e, "Delete Failed"))?;
+    info!(webhook_id = %id, "admin deleted webhook");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 投递记录：状态、尝试次数、最近一次响应码与错误
+#[utoipa::path(
+    get, path = "/admin/webhooks/{id}/deliveries", tag = "admin",
+    params(("id" = Uuid, Path, description = "Webhook ID"), DeliveriesQuery),
+    responses((status = 200, description = "Deliveries, newest first", body = crate::openapi::WebhookDeliveryPageDoc), (status = 400, description = "Invalid Pagination", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn deliveries(State(state): State<ServerState>, Path(id): Path<Uuid>, Query(q): Query<DeliveriesQuery>) -> Result<Json<Page<webhook_delivery::Model>>, JsonApiError> {
+    let opts = pagination(&state, q.page, q.per_page, q.cursor.as_deref())?;
+    state.webhooks.deliveries(id, opts).await.map(Json).map_err(|e| map_err(e, "List Failed"))
+}
+
+/// 上报事件（网关熔断、配额超限、上游不健康等），按租户订阅排队投递
+#[utoipa::path(
+    post, path = "/admin/webhook-events", tag = "admin",
+    request_body = EmitEventInput,
+    responses((status = 200, description = "Queued deliveries", body = EmitEventOutput), (status = 400, description = "Unknown event", body = crate::errors::JsonApiErrorBody))
+)]
+pub async fn emit(State(state): State<ServerState>, Json(input): Json<EmitEventInput>) -> Result<Json<EmitEventOutput>, JsonApiError> {
+    let event = WebhookEvent::parse(&input.event)
+        .ok_or_else(|| JsonApiError::new(StatusCode::BAD_REQUEST, "Unknown Event", Some(input.event.clone())))?;
+    let queued = state.webhooks.emit(input.tenant_id, event, input.data).await.map_err(|e| map_err(e, "Emit Failed"))?;
+    Ok(Json(EmitEventOutput { queued }))
+}
@@ -0,0 +1,36 @@
+//! Development seed data wiring: creates the demo data set and registers the demo API keys
+//! in the admin key store so they work against `/api/*` immediately.
+
+use anyhow::Context;
+use service::admin::kv_store::AdminKvStore;
+use service::errors::ServiceError;
+use service::seed::{seed_demo, SeedOptions, SeedReport};
+use sea_orm::DatabaseConnection;
+use tracing::info;
+
+pub async fn seed_and_register(db: &DatabaseConnection, admin_store: &dyn AdminKvStore, opts: &SeedOptions) -> Result<SeedReport, ServiceError> {
+    let report = seed_demo(db, opts).await?;
+    for u in &report.users {
+        admin_store.set(u.email.clone(), u.api_key.clone()).await?;
+    }
+    Ok(report)
+}
+
+/// `seed` 命令入口：仅在 config.toml `[dev] enabled = true` 时执行，结果以 JSON 打印到 stdout
+pub async fn run_cli() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cfg = configs::load_default().unwrap_or_default();
+    if !cfg.dev.enabled {
+        anyhow::bail!("seed is only available in dev mode; set [dev] enabled = true in config.toml");
+    }
+    // 仅 dev 模式可达：按 dev 补齐 [egress]，echo 上游在环回地址上
+    common::egress::install(common::egress::EgressPolicy::from_config(&cfg.egress.clone().resolve_for(true)).context("invalid [egress]")?);
+    service::runtime::ensure_env("frontend", "data").await?;
+    let db = models::db::connect().await?;
+    let (admin_store, _) = crate::startup::open_admin_stores(&cfg.storage, &db).await?;
+    let opts = SeedOptions { echo_base_url: cfg.dev.echo_base_url, ..SeedOptions::default() };
+    let report = seed_and_register(&db, admin_store.as_ref(), &opts).await?;
+    info!(tenant_id = %report.tenant_id, "seed completed");
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
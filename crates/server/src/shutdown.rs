@@ -0,0 +1,97 @@
+//! 优雅停机：收到 SIGTERM / SIGINT 后先把 /readyz 翻为 not_ready，停止接受新连接，
+//! 在 `[server] drain_timeout_secs` 内等待进行中的请求完成，最后冲刷后台任务。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tracing::info;
+
+/// 进程级停机状态；ServerState 持有一份，/readyz 据此返回 not_ready
+pub struct Shutdown {
+    draining: AtomicBool,
+    tx: watch::Sender<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self { draining: AtomicBool::new(false), tx: watch::channel(false).0 }
+    }
+
+    /// 已开始停机（就绪检查失败，负载均衡摘除本实例）
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// 开始停机；重复调用无副作用
+    pub fn trigger(&self, reason: &str) {
+        if !self.draining.swap(true, Ordering::AcqRel) {
+            info!(reason, "shutdown triggered; readiness flipped to not_ready");
+            self.tx.send_replace(true);
+        }
+    }
+
+    /// 等待停机开始
+    pub async fn triggered(&self) {
+        let mut rx = self.tx.subscribe();
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+
+    /// 后台监听 SIGINT（Ctrl+C）与 SIGTERM（unix）
+    pub fn listen_for_signals(self: &Arc<Self>) {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let reason = signal().await;
+            this.trigger(reason);
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut term) => tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = term.recv() => "SIGTERM",
+        },
+        Err(e) => {
+            tracing::warn!(err = %e, "install SIGTERM handler failed; only Ctrl+C triggers shutdown");
+            let _ = tokio::signal::ctrl_c().await;
+            "SIGINT"
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "SIGINT"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn trigger_flips_draining_and_wakes_waiters() {
+        let s = Arc::new(Shutdown::new());
+        assert!(!s.is_draining());
+        let waiter = tokio::spawn({
+            let s = Arc::clone(&s);
+            async move { s.triggered().await }
+        });
+        s.trigger("test");
+        s.trigger("again");
+        assert!(s.is_draining());
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        // 停机后再订阅也会立即返回
+        s.triggered().await;
+    }
+}
@@ -1,29 +1,178 @@
-use std::{env, net::SocketAddr, sync::Arc};
+use std::{env, future::IntoFuture, net::SocketAddr, sync::Arc};
 
+use anyhow::Context;
 use axum::Router;
-use common::utils::logging::init_logging_default;
+use common::http_client::HttpClientFactory;
+use common::utils::logging::{init_logging, LoggingConfig};
 use dotenvy::dotenv;
-use tower_http::cors::CorsLayer;
 use tracing::info;
 
 use crate::routes::{self, auth};
+use crate::shutdown::Shutdown;
+use common::lock::{DistributedLock, LocalLock, PgAdvisoryLock, RedisLock};
+use configs::{LockBackend, LockConfig, StorageBackend, StorageConfig};
+use sea_orm::DatabaseConnection;
 use service::{
+    db::{admin_kv_store::DbApiKeysStore, api_management_store::DbApiStore},
     file::{admin_kv_store::ApiKeysStore, api_management::ApiStore},
     admin::{kv_store::AdminKvStore, api_mgmt_store::ApiManagementStore},
     proxy_api::{repository::SeaOrmProxyApiRepository, service::ProxyApiService},
+    backup::ConfigBackupService,
+    cache::{ReadCache, ReadCacheOptions},
+    events::EventBus,
+    request_log_repo::{
+        clickhouse::{ClickHouseOptions, ClickHouseRequestLogRepository},
+        RequestLogRepository, SeaOrmRequestLogRepository,
+    },
     runtime,
 };
 
-/// Initialize logging via shared common utils
-fn init_logging() {
-    init_logging_default();
+mod sequence;
+
+// runtime checks moved to service::runtime
+
+/// GET /admin/stats 结果缓存时间
+const STATS_CACHE_TTL_SECS: u64 = 15;
+
+/// config.toml [expiry] -> 扫描参数；无法解析的租户 ID 与时间忽略并告警
+fn expiry_options(cfg: &configs::ExpiryConfig) -> service::expiry::ExpiryOptions {
+    let mut tenant_lead_days = std::collections::HashMap::new();
+    for (tenant, days) in &cfg.tenant_lead_days {
+        match uuid::Uuid::parse_str(tenant) {
+            Ok(id) => {
+                tenant_lead_days.insert(id, *days);
+            }
+            Err(_) => tracing::warn!(tenant = %tenant, "expiry.tenant_lead_days key is not a tenant id; ignored"),
+        }
+    }
+    let jwt_signing_keys = cfg
+        .jwt_signing_keys
+        .iter()
+        .filter_map(|k| match chrono::DateTime::parse_from_rfc3339(&k.expires_at) {
+            Ok(t) => Some((k.kid.clone(), t.with_timezone(&chrono::Utc))),
+            Err(e) => {
+                tracing::warn!(kid = %k.kid, error = %e, "expiry.jwt_signing_keys expires_at is not RFC 3339; ignored");
+                None
+            }
+        })
+        .collect();
+    let operator_tenant_id = cfg.operator_tenant_id.as_deref().and_then(|t| match uuid::Uuid::parse_str(t) {
+        Ok(id) => Some(id),
+        Err(_) => {
+            tracing::warn!(tenant = %t, "expiry.operator_tenant_id is not a tenant id; ignored");
+            None
+        }
+    });
+    service::expiry::ExpiryOptions {
+        interval: std::time::Duration::from_secs(cfg.interval_secs.max(1)),
+        lead_days: cfg.lead_days,
+        tenant_lead_days,
+        certificates: cfg.certificates.clone(),
+        jwt_signing_keys,
+        operator_tenant_id,
+    }
 }
 
-fn build_cors() -> CorsLayer {
-    CorsLayer::very_permissive()
+/// config.toml [anomaly] -> 检测参数；租户键不是合法 UUID 时忽略
+fn anomaly_options(cfg: &configs::AnomalyConfig) -> service::anomaly::AnomalyOptions {
+    use service::anomaly::{AnomalyOptions, AnomalyThresholds, WebhookFormat};
+    use std::time::Duration;
+    let merge = |base: &AnomalyThresholds, o: &configs::AnomalyThresholdsConfig| AnomalyThresholds {
+        min_requests: o.min_requests.unwrap_or(base.min_requests),
+        error_rate_increase: o.error_rate_increase.unwrap_or(base.error_rate_increase),
+        latency_ratio: o.latency_ratio.unwrap_or(base.latency_ratio),
+        traffic_ratio: o.traffic_ratio.unwrap_or(base.traffic_ratio),
+    };
+    let thresholds = merge(&AnomalyThresholds::default(), &cfg.thresholds);
+    let mut tenant_thresholds = std::collections::HashMap::new();
+    for (tenant, o) in &cfg.tenants {
+        match uuid::Uuid::parse_str(tenant) {
+            Ok(id) => {
+                tenant_thresholds.insert(id, merge(&thresholds, o));
+            }
+            Err(_) => tracing::warn!(tenant = %tenant, "anomaly.tenants key is not a tenant id; ignored"),
+        }
+    }
+    AnomalyOptions {
+        interval: Duration::from_secs(cfg.interval_secs.max(1)),
+        window: Duration::from_secs(cfg.window_secs.max(1)),
+        baseline_windows: cfg.baseline_windows.max(1),
+        cooldown: Duration::from_secs(cfg.cooldown_secs),
+        webhook_url: cfg.webhook_url.clone(),
+        webhook_format: if cfg.webhook_format.eq_ignore_ascii_case("slack") { WebhookFormat::Slack } else { WebhookFormat::Json },
+        thresholds,
+        tenant_thresholds,
+    }
 }
 
-// runtime checks moved to service::runtime
+/// config.toml [log_export] -> sink 列表；S3 凭据未配置时读取 AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY
+fn log_sinks(cfg: &configs::LogExportConfig) -> anyhow::Result<Vec<Arc<dyn service::log_export::LogSink>>> {
+    use service::log_export::{HttpFormat, HttpSink, KafkaRestSink, LogSink, S3Sink, S3Target};
+    let client = HttpClientFactory::new(common::http_client::HttpClientConfig { timeout_ms: cfg.timeout_secs.max(1) * 1000, ..Default::default() })
+        .with_egress(common::egress::global())
+        .build()?;
+    let mut sinks: Vec<Arc<dyn LogSink>> = Vec::new();
+    for sink in &cfg.sinks {
+        let name = sink.name();
+        let built: Arc<dyn LogSink> = match sink {
+            configs::LogSinkConfig::Http { url, format, headers, .. } => {
+                let format = match format.as_str() {
+                    "ndjson" => HttpFormat::Ndjson,
+                    "json" => HttpFormat::JsonArray,
+                    other => anyhow::bail!("log_export sink {}: unknown format {:?} (ndjson | json)", name, other),
+                };
+                Arc::new(HttpSink::new(name, client.clone(), url.clone(), headers.clone(), format))
+            }
+            configs::LogSinkConfig::Kafka { rest_url, topic, headers, .. } => Arc::new(KafkaRestSink::new(name, client.clone(), rest_url, topic, headers.clone())),
+            configs::LogSinkConfig::S3 { endpoint, bucket, prefix, region, access_key_id, secret_access_key, path_style, .. } => {
+                let or_env = |v: &String, var: &str| if v.is_empty() { env::var(var).unwrap_or_default() } else { v.clone() };
+                let target = S3Target {
+                    endpoint: endpoint.clone(),
+                    bucket: bucket.clone(),
+                    prefix: prefix.clone(),
+                    region: region.clone(),
+                    access_key_id: or_env(access_key_id, "AWS_ACCESS_KEY_ID"),
+                    secret_access_key: or_env(secret_access_key, "AWS_SECRET_ACCESS_KEY"),
+                    path_style: *path_style,
+                };
+                if target.access_key_id.is_empty() || target.secret_access_key.is_empty() {
+                    anyhow::bail!("log_export sink {}: S3 credentials missing (access_key_id / secret_access_key or AWS_* env)", name);
+                }
+                Arc::new(S3Sink::new(name, client.clone(), target))
+            }
+        };
+        sinks.push(built);
+    }
+    Ok(sinks)
+}
+
+/// 按 `[request_log] backend` 打开请求日志存储；ClickHouse 表不存在时创建，连接失败直接启动失败
+async fn open_request_logs(cfg: &configs::RequestLogConfig, db: &DatabaseConnection) -> anyhow::Result<Arc<dyn RequestLogRepository>> {
+    let repo: Arc<dyn RequestLogRepository> = match cfg.backend {
+        configs::RequestLogBackend::Postgres => Arc::new(SeaOrmRequestLogRepository { db: db.clone() }),
+        configs::RequestLogBackend::Clickhouse => {
+            let ch = &cfg.clickhouse;
+            let repo = ClickHouseRequestLogRepository::new(
+                db.clone(),
+                ClickHouseOptions {
+                    url: ch.url.clone(),
+                    database: ch.database.clone(),
+                    table: ch.table.clone(),
+                    user: ch.user.clone(),
+                    password: ch.password.clone(),
+                    ttl_days: ch.ttl_days,
+                    async_insert: ch.async_insert,
+                    wait_for_async_insert: ch.wait_for_async_insert,
+                    timeout: std::time::Duration::from_secs(ch.timeout_secs.max(1)),
+                },
+            )?;
+            repo.ensure_schema().await.context("prepare clickhouse request_log table")?;
+            Arc::new(repo)
+        }
+    };
+    info!(backend = repo.backend(), "request log store opened");
+    Ok(repo)
+}
 
 /// Load host/port from configs or env vars, with sensible fallbacks
 fn load_bind_addr() -> anyhow::Result<SocketAddr> {
@@ -45,46 +194,345 @@ fn load_bind_addr() -> anyhow::Result<SocketAddr> {
 }
 
 /// Public entry: build the app and run the HTTP server
+/// 按 `[storage] backend` 打开 admin 存储；database 后端依赖迁移 m20220101_000031。
+/// 调用方已连上数据库，`auto` 因此解析为 database；首次切换时导入原 JSON 文件中的数据
+pub(crate) async fn open_admin_stores(
+    cfg: &StorageConfig,
+    db: &DatabaseConnection,
+) -> anyhow::Result<(Arc<dyn AdminKvStore>, Arc<dyn ApiManagementStore>)> {
+    let backend = cfg.backend.resolve(true);
+    let (admin, apis): (Arc<dyn AdminKvStore>, Arc<dyn ApiManagementStore>) = match backend {
+        StorageBackend::Database => {
+            let (keys, apis) = (DbApiKeysStore::new(db.clone()), DbApiStore::new(db.clone()));
+            if cfg.import_from_files {
+                service::storage::import::import_json_stores(&cfg.api_keys_path, &cfg.apis_path, &keys, &apis).await?;
+            }
+            (keys, apis)
+        }
+        _ => {
+            // JSON 文件存储仅限单副本
+            service::storage::health::warn_if_multi_replica(&["api_keys", "apis"]);
+            (ApiKeysStore::new(&cfg.api_keys_path).await?, ApiStore::new(&cfg.apis_path).await?)
+        }
+    };
+    info!(backend = ?backend, "admin stores opened");
+    Ok((admin, apis))
+}
+
+/// 按 `[lock] backend` 选择定时任务的分布式锁；`none` 为进程内锁（每个副本各自执行）
+pub(crate) fn open_lock(cfg: &LockConfig, db: &DatabaseConnection) -> anyhow::Result<Arc<dyn DistributedLock>> {
+    let redis_url = cfg.redis_url.clone().or_else(|| env::var(common::diagnostics::REDIS_URL_ENV).ok());
+    let lock: Arc<dyn DistributedLock> = match (cfg.backend, redis_url) {
+        (LockBackend::None, _) => Arc::new(LocalLock::default()),
+        (LockBackend::Redis, None) => anyhow::bail!("[lock] backend = \"redis\" requires redis_url or REDIS_URL"),
+        (LockBackend::Redis | LockBackend::Auto, Some(url)) => Arc::new(RedisLock::from_url(&url, cfg.key_prefix.clone())?),
+        (LockBackend::Postgres | LockBackend::Auto, _) => Arc::new(PgAdvisoryLock::new(db.clone())),
+    };
+    info!(backend = lock.backend(), "scheduler lock opened");
+    Ok(lock)
+}
+
 pub async fn run() -> anyhow::Result<()> {
     dotenv().ok();
-    init_logging();
+
+    // 默认值 < config.toml < .env < 环境变量，并保留每个值的来源；日志按 [logging] 初始化后再报告解析失败
+    let resolved = configs::resolve_default();
+    init_logging(&resolved.as_ref().map(|r| r.config.logging.clone()).unwrap_or_else(|_| LoggingConfig::default()));
+    // 解析失败直接终止：回退默认值会得到空 JWT 密钥（可伪造令牌）与放行一切的出站策略
+    let resolved = sequence::step("config", async { sequence::validate_config(resolved) }).await?;
+    sequence::step("metrics", async { sequence::register_metrics() }).await?;
 
     runtime::ensure_env("frontend", "data").await?;
 
-    // Admin state for API Key management
-    let admin_store_file = ApiKeysStore::new("data/api_keys.json").await?;
-    let admin_store: std::sync::Arc<dyn AdminKvStore> = admin_store_file.clone();
+    let app_cfg = resolved.config.clone();
+    common::redaction::install(app_cfg.redaction.clone());
+    common::egress::install(common::egress::EgressPolicy::from_config(&app_cfg.egress.clone().resolve_for(app_cfg.dev.enabled)).context("invalid [egress]")?);
+    let backup_cfg = app_cfg.backup.clone();
+    let slo_cfg = app_cfg.slo.clone();
+    let jobs_cfg = app_cfg.jobs.clone();
+    let grpc_cfg = app_cfg.grpc.clone();
+    let anomaly_cfg = app_cfg.anomaly.clone();
+    let webhooks_cfg = app_cfg.webhooks.clone();
+    let expiry_cfg = app_cfg.expiry.clone();
+    let rollup_cfg = app_cfg.metrics_rollup.clone();
+    let reports_cfg = app_cfg.scheduled_reports.clone();
+    let capture_cfg = app_cfg.capture.clone();
+    let log_export_cfg = app_cfg.log_export.clone();
+    let request_log_cfg = app_cfg.request_log.clone();
+    let http_client_cfg = app_cfg.http_client.clone();
+    // gRPC 转码描述符：配置了但无法加载时直接启动失败
+    let grpc = if grpc_cfg.descriptor_sets.is_empty() {
+        None
+    } else {
+        let t = service::proxy_api::grpc::GrpcTranscoder::from_files(&grpc_cfg.descriptor_sets)?;
+        info!(files = ?grpc_cfg.descriptor_sets, "grpc descriptor sets loaded");
+        Some(Arc::new(t))
+    };
+    let storage_cfg = app_cfg.storage.clone();
+    let cache_cfg = app_cfg.cache.clone();
+    let lock_cfg = app_cfg.lock.clone();
+    let startup_cfg = app_cfg.startup.clone();
+    let drain_timeout = std::time::Duration::from_secs(app_cfg.server.drain_timeout_secs);
+    let metrics_addr = app_cfg.server.metrics_addr.clone();
+    let cors_cfg = app_cfg.cors.clone();
+    let auth_cfg = app_cfg.auth.clone();
+    let encryption_cfg = app_cfg.encryption.clone();
+    let dev_cfg = app_cfg.dev;
+    let cors = common::cors::layer(&cors_cfg, dev_cfg.enabled);
+
+    // 开发模式：内置 echo 上游，作为演示数据的默认目标
+    #[cfg(feature = "dev")]
+    if dev_cfg.enabled && dev_cfg.embedded_echo {
+        match dev_cfg.echo_listen_addr() {
+            Some(addr) => { common::echo::spawn(&addr).await?; }
+            None => tracing::warn!(url = %dev_cfg.echo_base_url, "dev.echo_base_url has no host; embedded echo not started"),
+        }
+    }
+
+    // DB connection（config.toml [startup] 控制退避重试）
+    let db = sequence::step("database", sequence::connect_db(&startup_cfg)).await?;
+    // 定时任务与启动迁移每次只在一个副本上执行（config.toml [lock]）
+    let lock = sequence::step("lock", async { open_lock(&lock_cfg, &db) }).await?;
+    if startup_cfg.run_migrations {
+        sequence::step("migrations", sequence::run_migrations(&db, lock.as_ref())).await?;
+    }
 
-    // API 管理存储（文件持久化 data/apis.json）
-    let api_store_file = ApiStore::new("data/apis.json").await?;
-    let api_store: std::sync::Arc<dyn ApiManagementStore> = api_store_file.clone();
+    // 请求日志存储（config.toml [request_log]）
+    let request_logs = sequence::step("request_log_store", open_request_logs(&request_log_cfg, &db)).await?;
 
-    // DB connection
-    let db = models::db::connect().await?;
+    // Admin API Key 与 API 管理存储（config.toml [storage]）
+    let (admin_store, api_store) = sequence::step("admin_stores", open_admin_stores(&storage_cfg, &db)).await?;
+
+    // 认证设置（config.toml [auth]，密钥来自 JWT_SECRET）；开发模式下补齐默认密钥并关闭 Cookie Secure
+    let auth_settings = auth_cfg.resolve_for(dev_cfg.enabled);
+    if auth_settings.jwt_secret.is_empty() {
+        anyhow::bail!("JWT_SECRET is empty; refusing to start outside [dev] mode");
+    }
+    if auth_settings.jwt_secret == configs::DEV_JWT_SECRET {
+        tracing::warn!("JWT_SECRET not set; using the built-in development secret");
+    }
+    // 敏感字段加密主密钥（models 中的加密列据此透明加解密）；生产环境未配置时拒绝启动
+    let encryption_cfg = encryption_cfg.resolve_for(dev_cfg.enabled);
+    if encryption_cfg.master_key == configs::DEV_MASTER_KEY {
+        tracing::warn!("ENCRYPTION_MASTER_KEY not set; using the built-in development master key");
+    }
+    common::crypto::install(encryption_cfg.server_keys(&auth_settings.jwt_secret)?);
+    // 优雅停机状态（信号在开始监听端口后注册）
+    let shutdown = Arc::new(Shutdown::new());
+    // 时间判断统一使用的时钟（ServerState 与后台服务共用）
+    let clock = common::time::system_clock();
+    // 事件总线 + 控制面读缓存（变更事件触发失效）
+    let events = EventBus::default();
+    let read_cache = Arc::new(ReadCache::with_options(ReadCacheOptions {
+        ttl: (cache_cfg.ttl_secs > 0).then(|| std::time::Duration::from_secs(cache_cfg.ttl_secs)),
+        max_entries: (cache_cfg.max_entries > 0).then_some(cache_cfg.max_entries),
+    }));
+    read_cache.spawn_invalidator(&events);
+    // 配置版本：每个变更事件持久化为一条 revision，数据面日志引用其编号
+    if let Err(e) = service::revisions::spawn_recorder(db.clone(), &events).await {
+        tracing::warn!(err = %e, "config revision recorder not started");
+    }
+
+    // 配置快照：定时 + 变更前，保留最近 N 份
+    let backups = Arc::new(
+        ConfigBackupService::new(
+            db.clone(),
+            backup_cfg.retention,
+            std::time::Duration::from_secs(backup_cfg.pre_apply_min_interval_secs),
+        )
+        .with_events(events.clone())
+        .with_lock(Arc::clone(&lock)),
+    );
+    if backup_cfg.enabled {
+        backups.spawn_scheduler(std::time::Duration::from_secs(backup_cfg.interval_secs));
+    }
+
+    // 流量异常检测：错误率突增、延迟回退、租户流量激增
+    if anomaly_cfg.enabled {
+        let detector = Arc::new(service::anomaly::AnomalyDetector::new(db.clone(), anomaly_options(&anomaly_cfg))?.with_lock(Arc::clone(&lock)));
+        detector.spawn();
+    }
+
+    // 租户 webhook：配置变更自动作为 config.changed 投递，其他事件经 POST /admin/webhook-events 上报
+    let webhooks = Arc::new(service::webhooks::WebhookService::new(
+        db.clone(),
+        service::webhooks::DeliveryOptions {
+            poll_interval: std::time::Duration::from_secs(webhooks_cfg.poll_interval_secs.max(1)),
+            batch_size: webhooks_cfg.batch_size,
+            max_attempts: webhooks_cfg.max_attempts.max(1),
+            initial_backoff: std::time::Duration::from_secs(webhooks_cfg.initial_backoff_secs),
+            max_backoff: std::time::Duration::from_secs(webhooks_cfg.max_backoff_secs),
+            timeout: std::time::Duration::from_secs(webhooks_cfg.timeout_secs.max(1)),
+        },
+    )?);
+    if webhooks_cfg.enabled {
+        webhooks.spawn_worker();
+        webhooks.spawn_config_listener(&events);
+    }
+    // 凭据到期提醒：API Key 通知所属租户，证书与签名密钥通知运维租户
+    let expiry = Arc::new(service::expiry::ExpiryScanner::new(db.clone(), Arc::clone(&webhooks), expiry_options(&expiry_cfg)).with_lock(Arc::clone(&lock)).with_clock(Arc::clone(&clock)));
+    if expiry_cfg.enabled {
+        expiry.spawn();
+    }
+    // 定期报表：经 webhook 投递给订阅 report.generated 的租户
+    if reports_cfg.enabled {
+        let opts = service::scheduled_reports::ScheduleOptions {
+            interval: std::time::Duration::from_secs(reports_cfg.interval_secs.max(1)),
+            daily: reports_cfg.daily,
+            weekly: reports_cfg.weekly,
+            send_hour: reports_cfg.send_hour_utc,
+            top_endpoints: reports_cfg.top_endpoints,
+        };
+        Arc::new(service::scheduled_reports::ReportScheduler::new(db.clone(), Arc::clone(&webhooks), opts).with_lock(Arc::clone(&lock))).spawn();
+    }
+    if rollup_cfg.enabled {
+        let opts = service::metrics_rollup::RollupOptions {
+            interval: std::time::Duration::from_secs(rollup_cfg.interval_secs.max(1)),
+            lookback: std::time::Duration::from_secs(rollup_cfg.lookback_minutes * 60),
+            retention: std::time::Duration::from_secs(rollup_cfg.retention_days.max(1) * 86_400),
+        };
+        Arc::new(service::metrics_rollup::MetricsRollupService::new(db.clone(), opts).with_lock(Arc::clone(&lock))).spawn();
+    }
 
-    // JWT secret
-    let jwt_secret =
-        std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev-secret-change-me".to_string());
     let repo = SeaOrmProxyApiRepository { db: db.clone() };
-    let proxy_api_svc = std::sync::Arc::new(ProxyApiService::new(std::sync::Arc::new(repo)));
+    let mut proxy_api_svc = ProxyApiService::new(std::sync::Arc::new(repo)).with_cache(Arc::clone(&read_cache), events.clone());
+    if backup_cfg.enabled {
+        proxy_api_svc = proxy_api_svc.with_backups(Arc::clone(&backups));
+    }
+    let proxy_api_svc = std::sync::Arc::new(proxy_api_svc);
+    // 预热失败不影响启动，首个请求会按需加载
+    if startup_cfg.warm_caches && sequence::step("warm_caches", sequence::warm_caches(&db, &proxy_api_svc, &read_cache)).await.is_err() {
+        tracing::warn!("continuing with cold caches");
+    }
+
+    let stats = Arc::new(service::stats::StatsService::new(db.clone(), std::time::Duration::from_secs(STATS_CACHE_TTL_SECS)).with_clock(Arc::clone(&clock)));
+    // 代理地址非法等配置错误直接启动失败
+    let upstream = Arc::new(routes::demo::UpstreamClients::new(&HttpClientFactory::new(http_client_cfg).with_egress(common::egress::global()))?);
+
+    let feature_flags = Arc::new(service::feature_flags::FeatureFlagService::new(db.clone()));
+    let capture = Arc::new(service::capture::CaptureService::new(
+        db.clone(),
+        service::capture::CaptureOptions {
+            enabled: capture_cfg.enabled,
+            max_bytes_limit: capture_cfg.max_bytes_limit,
+            default_max_bytes: capture_cfg.default_max_bytes,
+        },
+    ));
+
+    // 轮换签名密钥（材料加密存储，见 [encryption]）
+    let grace = auth_settings.key_rotation_grace_secs.unwrap_or(auth_settings.access_token_ttl_secs);
+    let jwt_keys = Arc::new(service::jwt_keys::JwtKeyService::new(db.clone()).with_grace(std::time::Duration::from_secs(grace)));
+    match jwt_keys.reload().await {
+        Ok(ring) => info!(keys = ring.len(), active = ?ring.active().map(|k| k.kid.clone()), "jwt signing keys loaded"),
+        Err(e) => tracing::warn!(err = %e, "load jwt signing keys failed; using jwt_secret only"),
+    }
+    jwt_keys.spawn_refresh(std::time::Duration::from_secs(30));
+
+    // 请求日志导出：订阅实时日志，按批写入 Kafka / S3 / HTTP（config.toml [log_export]）
+    let request_log_stream = if log_export_cfg.enabled {
+        let stream = service::request_log_stream::RequestLogStream::new(log_export_cfg.buffer);
+        let opts = service::log_export::ExportOptions {
+            batch_size: log_export_cfg.batch_size,
+            flush_interval: std::time::Duration::from_secs(log_export_cfg.flush_interval_secs),
+            max_pending_batches: log_export_cfg.max_pending_batches,
+            max_retries: log_export_cfg.max_retries,
+            ..Default::default()
+        };
+        service::log_export::LogExporter::new(log_sinks(&log_export_cfg)?, opts).spawn(&stream);
+        stream
+    } else {
+        service::request_log_stream::RequestLogStream::default()
+    };
 
     let state = auth::ServerState {
         db,
-        auth: auth::ServerAuthConfig { jwt_secret },
+        auth: auth::ServerAuthConfig { settings: auth_settings },
         admin_kv_store: std::sync::Arc::clone(&admin_store),
         api_mgmt_store: std::sync::Arc::clone(&api_store),
         proxy_api_svc: std::sync::Arc::clone(&proxy_api_svc),
+        events,
+        read_cache,
+        backups,
+        dev: dev_cfg,
+        slo: service::observability::SloTargets {
+            availability: slo_cfg.availability,
+            latency_p99_ms: slo_cfg.latency_p99_ms,
+            alert_for: slo_cfg.alert_for,
+            max_rate_limited_ratio: slo_cfg.max_rate_limited_ratio,
+        },
+        config: Arc::new(resolved),
+        jobs: Arc::new(service::jobs::JobManager::new(jobs_cfg.max_concurrent, jobs_cfg.max_pending, jobs_cfg.retention)),
+        grpc,
+        webhooks,
+        expiry,
+        stats,
+        upstream,
+        shutdown: Arc::clone(&shutdown),
+        feature_flags,
+        jwt_keys,
+        request_log_stream,
+        request_logs,
+        capture,
+        clock,
     };
+    let db = state.db.clone();
+    let webhooks = webhooks_cfg.enabled.then(|| Arc::clone(&state.webhooks));
 
     // Build router
-    let cors = build_cors();
-    let app: Router = routes::build_router(Arc::clone(&admin_store_file), cors, state);
+    let app: Router = routes::build_router(cors, state);
 
     // Bind and serve
     let addr = load_bind_addr()?;
     info!(%addr, "starting server crate");
     println!("starting server crate at {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    let listener = sequence::step("bind", async { tokio::net::TcpListener::bind(addr).await.with_context(|| format!("bind {}", addr)) }).await?;
+    // 指标只在内部端口提供，公开端口不暴露
+    if !metrics_addr.is_empty() {
+        common::admin_http::spawn_admin_server(&metrics_addr, routes::encode_metrics);
+    }
+    shutdown.listen_for_signals();
+    serve_until_drained(listener, app, &shutdown, drain_timeout).await?;
+
+    // 停机收尾：最后投递一轮到期的 webhook，再关闭连接池
+    if let Some(webhooks) = webhooks {
+        match tokio::time::timeout(drain_timeout, webhooks.deliver_due()).await {
+            Ok(Ok(n)) => info!(delivered = n, "final webhook delivery pass done"),
+            Ok(Err(e)) => tracing::warn!(err = %e, "final webhook delivery pass failed"),
+            Err(_) => tracing::warn!("final webhook delivery pass timed out"),
+        }
+    }
+    if let Err(e) = db.close().await {
+        tracing::warn!(err = %e, "close database pool failed");
+    }
+    info!("server shut down gracefully");
     Ok(())
 }
+
+/// 停机开始后不再接受新连接，等待进行中的请求完成；超过 `drain_timeout` 时放弃剩余连接
+async fn serve_until_drained(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    shutdown: &Arc<Shutdown>,
+    drain_timeout: std::time::Duration,
+) -> anyhow::Result<()> {
+    let signal = {
+        let shutdown = Arc::clone(shutdown);
+        async move { shutdown.triggered().await }
+    };
+    // 注入 ConnectInfo：限流等按客户端 IP 区分调用方
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    let serve = axum::serve(listener, app).with_graceful_shutdown(signal).into_future();
+    tokio::pin!(serve);
+    tokio::select! {
+        res = &mut serve => return Ok(res?),
+        _ = shutdown.triggered() => {}
+    }
+    info!(timeout_secs = drain_timeout.as_secs(), "draining in-flight requests");
+    match tokio::time::timeout(drain_timeout, serve).await {
+        Ok(res) => Ok(res?),
+        Err(_) => {
+            tracing::warn!(timeout_secs = drain_timeout.as_secs(), "drain timeout reached; closing remaining connections");
+            Ok(())
+        }
+    }
+}
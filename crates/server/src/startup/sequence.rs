@@ -0,0 +1,121 @@
+//! 启动编排：校验配置 → 注册指标 → 连接数据库（指数退避）→ 迁移（可选）→ 预热缓存 → 监听端口。
+//! 每一步记录耗时；失败时错误带上步骤名（`startup step 'database' failed: ...`），不在启动中途 panic。
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context};
+use common::lock::{run_exclusive, DistributedLock};
+use configs::{resolver::Resolved, AppConfig, StartupConfig};
+use sea_orm::DatabaseConnection;
+use service::{cache::ReadCache, proxy_api::{repository::ProxyApiRepository, service::ProxyApiService}};
+use tracing::{error, info, warn};
+
+/// 执行一个启动步骤；失败时记录日志并把步骤名加入错误上下文
+pub(crate) async fn step<T, F>(name: &'static str, fut: F) -> anyhow::Result<T>
+where
+    F: Future<Output = anyhow::Result<T>>,
+{
+    let started = Instant::now();
+    match fut.await {
+        Ok(v) => {
+            info!(step = name, elapsed_ms = started.elapsed().as_millis() as u64, "startup step done");
+            Ok(v)
+        }
+        Err(e) => {
+            error!(step = name, elapsed_ms = started.elapsed().as_millis() as u64, err = %e, "startup step failed");
+            Err(e.context(format!("startup step '{}' failed", name)))
+        }
+    }
+}
+
+/// 配置必须能解析并通过完整校验（含非开发模式下 jwt_secret 非空）；任何失败都终止启动
+pub(crate) fn validate_config(resolved: anyhow::Result<Resolved<AppConfig>>) -> anyhow::Result<Resolved<AppConfig>> {
+    let resolved = resolved.context("resolve config")?;
+    resolved.config.clone().normalize_and_validate()?;
+    Ok(resolved)
+}
+
+/// 注册指标；重复注册等问题在这里以错误返回而不是在首个请求时 panic
+pub(crate) fn register_metrics() -> anyhow::Result<()> {
+    std::panic::catch_unwind(service::observability::register_metrics).map_err(|_| anyhow!("prometheus metric registration panicked"))
+}
+
+/// 第 `attempt` 次（从 1 开始）失败后的等待时间：initial * 2^(attempt-1)，不超过 max
+pub(crate) fn backoff(attempt: u32, initial: Duration, max: Duration) -> Duration {
+    initial.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(max)
+}
+
+/// 连接数据库，失败按指数退避重试 `db_connect_attempts` 次
+pub(crate) async fn connect_db(cfg: &StartupConfig) -> anyhow::Result<DatabaseConnection> {
+    let attempts = cfg.db_connect_attempts.max(1);
+    let (initial, max) = (Duration::from_millis(cfg.db_backoff_initial_ms), Duration::from_millis(cfg.db_backoff_max_ms));
+    let mut attempt = 1;
+    loop {
+        match models::db::connect_once(&models::db::DATABASE_CONFIG).await {
+            Ok(db) => return Ok(db),
+            Err(e) if attempt < attempts => {
+                let wait = backoff(attempt, initial, max);
+                warn!(attempt, attempts, wait_ms = wait.as_millis() as u64, err = %e, "database not reachable; retrying");
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.context(format!("database unreachable after {} attempts", attempts))),
+        }
+    }
+}
+
+/// 执行待处理迁移；其他副本持有锁时等待其完成后再检查一次（迁移本身幂等）
+pub(crate) async fn run_migrations(db: &DatabaseConnection, lock: &dyn DistributedLock) -> anyhow::Result<()> {
+    use migration::MigratorTrait;
+    const MAX_WAITS: u32 = 120;
+    for _ in 0..MAX_WAITS {
+        if let Some(res) = run_exclusive(Some(lock), "migrations", Duration::from_secs(600), migration::Migrator::up(db, None)).await {
+            return res.context("apply migrations");
+        }
+        info!("another replica is running migrations; waiting");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Err(anyhow!("migration lock still held after {}s", MAX_WAITS))
+}
+
+/// 预热数据面路由表与上游列表，第一个请求不再承担加载开销
+pub(crate) async fn warm_caches<R: ProxyApiRepository>(db: &DatabaseConnection, proxy_apis: &ProxyApiService<R>, cache: &ReadCache) -> anyhow::Result<()> {
+    let routes = proxy_apis.routes().await?;
+    let upstreams = cache.warm_upstreams(db).await?;
+    info!(routes = routes.len(), upstreams, "caches warmed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let (i, m) = (Duration::from_millis(500), Duration::from_secs(3));
+        let waits: Vec<u64> = (1..=5).map(|a| backoff(a, i, m).as_millis() as u64).collect();
+        assert_eq!(waits, vec![500, 1000, 2000, 3000, 3000]);
+        assert_eq!(backoff(64, i, m), m);
+    }
+
+    #[tokio::test]
+    async fn failed_step_names_itself() {
+        let err = step("database", async { Err::<(), _>(anyhow!("connection refused")) }).await.unwrap_err();
+        assert_eq!(err.to_string(), "startup step 'database' failed");
+        assert!(format!("{:#}", err).contains("connection refused"));
+        assert_eq!(step("metrics", async { Ok(7) }).await.unwrap(), 7);
+    }
+
+    #[test]
+    fn config_failures_abort_startup() {
+        // 解析失败不再回退默认值
+        assert!(validate_config(Err(anyhow!("bad toml"))).is_err());
+        // 非开发模式下 jwt_secret 为空
+        let mut cfg = AppConfig::default();
+        cfg.database.url = "postgres://localhost/app".into();
+        assert!(validate_config(Ok(Resolved { config: cfg.clone(), provenance: Default::default() })).is_err());
+        cfg.auth.jwt_secret = "0123456789abcdef-secret".into();
+        assert!(validate_config(Ok(Resolved { config: cfg, provenance: Default::default() })).is_ok());
+    }
+}
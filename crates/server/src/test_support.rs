@@ -0,0 +1,224 @@
+//! 集成测试用的进程内应用：独立数据库 schema（`models::testing`）+ 默认配置的 `ServerState` + 随机端口的 HTTP 服务。
+//! 每个 `TestApp` 有自己的 schema 与数据目录，测试可并行运行；没有可用数据库（或设置了 `SKIP_DB_TESTS`）时
+//! `build` 返回 `None`，调用方跳过即可。
+//!
+//! ```ignore
+//! let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
+//! let user = app.register_and_login().await?;
+//! app.set_api_key(&user, "svc", "k-1").await?;
+//! app.cleanup().await;
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use axum::Router;
+use common::time::{system_clock, SharedClock};
+use models::testing::{TestDb, TestDbError};
+use reqwest::StatusCode;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+use service::admin::{api_mgmt_store::ApiManagementStore, kv_store::AdminKvStore};
+use service::file::{admin_kv_store::ApiKeysStore, api_management::ApiStore};
+use service::proxy_api::{repository::SeaOrmProxyApiRepository, service::ProxyApiService};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::routes::{self, auth};
+
+/// 测试签发 JWT 使用的默认密钥
+pub const TEST_JWT_SECRET: &str = "test-secret";
+/// `register_and_login` 使用的密码
+pub const TEST_PASSWORD: &str = "S3curePass!";
+
+/// 以测试默认值组装 `ServerState`；文件存储放在 `data_dir`
+pub async fn build_state(db: DatabaseConnection, auth: auth::ServerAuthConfig, capture: service::capture::CaptureOptions, clock: SharedClock, data_dir: &std::path::Path) -> anyhow::Result<auth::ServerState> {
+    // 测试上游（echo）在环回地址上，默认的出站策略会拒绝
+    common::egress::install(common::egress::EgressPolicy::allow_all());
+    let admin_kv_store: Arc<dyn AdminKvStore> = ApiKeysStore::new(data_dir.join("api_keys.json")).await?;
+    let api_mgmt_store: Arc<dyn ApiManagementStore> = ApiStore::new(data_dir.join("apis.json")).await?;
+    let webhooks = Arc::new(service::webhooks::WebhookService::new(db.clone(), Default::default())?);
+    Ok(auth::ServerState {
+        db: db.clone(),
+        auth,
+        admin_kv_store,
+        api_mgmt_store,
+        proxy_api_svc: Arc::new(ProxyApiService::new(Arc::new(SeaOrmProxyApiRepository { db: db.clone() }))),
+        events: service::events::EventBus::default(),
+        read_cache: Arc::new(service::cache::ReadCache::new()),
+        backups: Arc::new(service::backup::ConfigBackupService::new(db.clone(), 10, std::time::Duration::from_secs(60))),
+        dev: configs::DevConfig::default(),
+        slo: service::observability::SloTargets::default(),
+        config: Arc::new(configs::resolver::Resolved::default()),
+        jobs: Arc::new(service::jobs::JobManager::default()),
+        grpc: None,
+        expiry: Arc::new(service::expiry::ExpiryScanner::new(db.clone(), Arc::clone(&webhooks), Default::default()).with_clock(Arc::clone(&clock))),
+        webhooks,
+        stats: Arc::new(service::stats::StatsService::new(db.clone(), std::time::Duration::from_secs(15)).with_clock(Arc::clone(&clock))),
+        upstream: Arc::new(routes::demo::UpstreamClients::new(&Default::default())?),
+        shutdown: Arc::new(crate::shutdown::Shutdown::new()),
+        feature_flags: Arc::new(service::feature_flags::FeatureFlagService::new(db.clone())),
+        jwt_keys: Arc::new(service::jwt_keys::JwtKeyService::new(db.clone())),
+        request_log_stream: Default::default(),
+        request_logs: Arc::new(service::request_log_repo::SeaOrmRequestLogRepository { db: db.clone() }),
+        capture: Arc::new(service::capture::CaptureService::new(db, capture)),
+        clock,
+    })
+}
+
+pub struct TestAppBuilder {
+    auth: auth::ServerAuthConfig,
+    capture: service::capture::CaptureOptions,
+    clock: SharedClock,
+}
+
+impl Default for TestAppBuilder {
+    fn default() -> Self {
+        Self {
+            auth: auth::ServerAuthConfig { settings: configs::AuthConfig { jwt_secret: TEST_JWT_SECRET.into(), ..Default::default() } },
+            capture: Default::default(),
+            clock: system_clock(),
+        }
+    }
+}
+
+impl TestAppBuilder {
+    pub fn with_auth(mut self, auth: auth::ServerAuthConfig) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn with_capture(mut self, capture: service::capture::CaptureOptions) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    /// 时间判断使用的时钟（如 `MockClock`，控制维护窗口与令牌到期）
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 创建 schema、执行迁移并在随机端口上启动服务；没有可用数据库时返回 `None`
+    pub async fn build(self) -> anyhow::Result<Option<TestApp>> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() {
+            return Ok(None);
+        }
+        let test_db = match TestDb::create().await {
+            Ok(db) => db,
+            Err(TestDbError::Unavailable) => {
+                eprintln!("skip: {}", TestDbError::Unavailable);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let data_dir = std::env::temp_dir().join(format!("server-test-{}", test_db.schema));
+        let state = build_state(test_db.db.clone(), self.auth, self.capture, self.clock, &data_dir).await?;
+        let router = routes::build_router(common::cors::layer(&common::cors::CorsConfig::default(), true), state.clone());
+
+        let listener = TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+        let app = router.clone();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                eprintln!("test server error: {}", e);
+            }
+        });
+        let client = reqwest::Client::builder().cookie_store(true).build()?;
+        Ok(Some(TestApp { addr, base_url: format!("http://{}", addr), router, state, client, test_db, data_dir }))
+    }
+}
+
+/// 已注册并登录的测试用户
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub password: String,
+    pub token: String,
+    pub csrf_token: String,
+}
+
+pub struct TestApp {
+    pub addr: SocketAddr,
+    /// `http://127.0.0.1:<port>`
+    pub base_url: String,
+    /// 同一个 router，可不经网络直接 `oneshot`
+    pub router: Router,
+    pub state: auth::ServerState,
+    client: reqwest::Client,
+    test_db: TestDb,
+    data_dir: PathBuf,
+}
+
+impl TestApp {
+    pub fn builder() -> TestAppBuilder {
+        TestAppBuilder::default()
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// 保存 Cookie 的客户端（登录后的 auth_token / csrf_token 自动回传）
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn db(&self) -> &DatabaseConnection {
+        &self.test_db.db
+    }
+
+    pub async fn register(&self, tenant_id: Uuid, email: &str, password: &str) -> anyhow::Result<reqwest::Response> {
+        let body = json!({"tenant_id": tenant_id, "email": email, "name": "Tester", "password": password});
+        Ok(self.client.post(self.url("/auth/register")).json(&body).send().await?)
+    }
+
+    pub async fn login(&self, tenant_id: Uuid, email: &str, password: &str) -> anyhow::Result<reqwest::Response> {
+        let body = json!({"tenant_id": tenant_id, "email": email, "password": password});
+        Ok(self.client.post(self.url("/auth/login")).json(&body).send().await?)
+    }
+
+    /// 在新租户下注册随机邮箱的用户并登录
+    pub async fn register_and_login(&self) -> anyhow::Result<TestUser> {
+        let tenant_id = Uuid::new_v4();
+        let email = format!("user_{}@example.com", Uuid::new_v4().simple());
+        let res = self.register(tenant_id, &email, TEST_PASSWORD).await?;
+        anyhow::ensure!(res.status() == StatusCode::OK, "register failed: {}", res.status());
+        let res = self.login(tenant_id, &email, TEST_PASSWORD).await?;
+        anyhow::ensure!(res.status() == StatusCode::OK, "login failed: {}", res.status());
+        let out: serde_json::Value = res.json().await?;
+        let field = |name: &str| out[name].as_str().map(str::to_string).with_context(|| format!("login response has no {}", name));
+        Ok(TestUser {
+            tenant_id,
+            user_id: field("user_id")?.parse()?,
+            email,
+            password: TEST_PASSWORD.into(),
+            token: field("token")?,
+            csrf_token: field("csrf_token")?,
+        })
+    }
+
+    /// 以 Bearer 令牌访问的请求（不依赖 Cookie，也无需 CSRF token）
+    pub fn authed(&self, user: &TestUser, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        reqwest::Client::new().request(method, self.url(path)).bearer_auth(&user.token)
+    }
+
+    /// 通过 `/admin/api-keys` 为 `name` 设置 API key
+    pub async fn set_api_key(&self, user: &TestUser, name: &str, api_key: &str) -> anyhow::Result<()> {
+        let res = self.authed(user, reqwest::Method::POST, "/admin/api-keys").json(&json!({"user": name, "api_key": api_key})).send().await?;
+        anyhow::ensure!(res.status().is_success(), "set api key failed: {}", res.status());
+        Ok(())
+    }
+
+    /// 删除 schema 与数据目录；不调用时 schema 残留（以 `test_` 开头）
+    pub async fn cleanup(self) {
+        let _ = std::fs::remove_dir_all(&self.data_dir);
+        if let Err(e) = self.test_db.cleanup().await {
+            eprintln!("test schema cleanup failed: {}", e);
+        }
+    }
+}
@@ -1,76 +1,10 @@
-use std::net::SocketAddr;
-use std::sync::Arc;
-
-use axum::{Router};
-use service::file::{admin_kv_store::ApiKeysStore, api_management::ApiStore};
-use service::admin::{kv_store::AdminKvStore, api_mgmt_store::ApiManagementStore};
-use service::proxy_api::{repository::SeaOrmProxyApiRepository, service::ProxyApiService};
-use tower_http::cors::CorsLayer;
-use tokio::net::TcpListener;
+//! 端到端测试：每个测试一个独立 schema 的进程内 server（`server::test_support`），没有可用数据库时跳过。
+
 use serde_json::json;
 use uuid::Uuid;
 use reqwest::StatusCode as HttpStatusCode;
-use migration::MigratorTrait;
-
-use server::routes::{self, auth};
-
-// Optional: use testcontainers for Postgres; skip if unavailable
-#[allow(unused_imports)]
-
-fn cors() -> CorsLayer { CorsLayer::very_permissive() }
-
-struct TestApp {
-    base_url: String,
-}
 
-async fn start_server() -> anyhow::Result<TestApp> {
-    // Ensure models prefer env over config file
-    std::env::set_var("CONFIG_PATH", "/nonexistent-config-for-tests.toml");
-
-    // Use DATABASE_URL from environment; if not present, skip tests gracefully
-    if std::env::var("DATABASE_URL").is_err() {
-        eprintln!("DATABASE_URL missing; skip e2e tests. Provide .env.test or env var.");
-        // Create a tiny server instance for public route check without DB
-        // but since most routes need DB, we return early
-        return Err(anyhow::anyhow!("missing DATABASE_URL"));
-    }
-
-    // Connect DB and run migrations
-    let db = models::db::connect().await?;
-    if let Err(e) = migration::Migrator::up(&db, None).await { eprintln!("migrations notice: {}", e); }
-
-    // Use isolated temp files for admin/api stores per test run
-    let temp_id = Uuid::new_v4();
-    let api_keys_path = format!("target/test-data/{}/api_keys.json", temp_id);
-    let apis_path = format!("target/test-data/{}/apis.json", temp_id);
-    let admin_store = ApiKeysStore::new(&api_keys_path).await?;
-    let api_store = ApiStore::new(&apis_path).await?;
-    // 转为 trait 对象供 ServerState 使用
-    let admin_kv_store: Arc<dyn AdminKvStore> = admin_store.clone();
-    let api_mgmt_store: Arc<dyn ApiManagementStore> = api_store.clone();
-
-    let state = auth::ServerState {
-        db: db.clone(),
-        auth: auth::ServerAuthConfig { jwt_secret: "test-secret".into() },
-        admin_kv_store: Arc::clone(&admin_kv_store),
-        api_mgmt_store: Arc::clone(&api_mgmt_store),
-        proxy_api_svc: {
-            let repo = SeaOrmProxyApiRepository { db: db.clone() };
-            Arc::new(ProxyApiService::new(Arc::new(repo)))
-        },
-    };
-
-    let app: Router = routes::build_router(admin_store.clone(), cors(), state);
-    let listener = TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await?;
-    let addr: SocketAddr = listener.local_addr()?;
-    let base_url = format!("http://{}:{}", addr.ip(), addr.port());
-
-    tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await { eprintln!("server error: {}", e); }
-    });
-
-    Ok(TestApp { base_url })
-}
+use server::test_support::TestApp;
 
 fn client() -> reqwest::Client {
     reqwest::Client::builder()
@@ -81,25 +15,18 @@ fn client() -> reqwest::Client {
 
 #[tokio::test]
 async fn e2e_public_health() -> anyhow::Result<()> {
-    if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-    let app = match start_server().await {
-        Ok(a) => a,
-        Err(_) => return Ok(()),
-    };
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
     let res = client().get(format!("{}/health", app.base_url)).send().await?;
     assert_eq!(res.status(), HttpStatusCode::OK);
     let body = res.json::<serde_json::Value>().await?;
     assert_eq!(body["status"], "ok");
+    app.cleanup().await;
     Ok(())
 }
 
 #[tokio::test]
 async fn e2e_auth_register_login_and_cookie() -> anyhow::Result<()> {
-    if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-    let app = match start_server().await {
-        Ok(a) => a,
-        Err(_) => return Ok(()),
-    };
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
     let c = client();
 
     let tid = Uuid::new_v4();
@@ -120,30 +47,24 @@ async fn e2e_auth_register_login_and_cookie() -> anyhow::Result<()> {
     assert_eq!(res.status(), HttpStatusCode::OK);
     let set_cookie = res.headers().get("set-cookie");
     assert!(set_cookie.is_some());
+    app.cleanup().await;
     Ok(())
 }
 
 #[tokio::test]
 async fn e2e_protected_without_token_denied() -> anyhow::Result<()> {
-    if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-    let app = match start_server().await {
-        Ok(a) => a,
-        Err(_) => return Ok(()),
-    };
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
     let c = reqwest::Client::new();
     let res = c.get(format!("{}/admin/apis", app.base_url)).send().await?;
     // Global middleware: missing Authorization and auth_token cookie -> 400
     assert_eq!(res.status(), HttpStatusCode::BAD_REQUEST);
+    app.cleanup().await;
     Ok(())
 }
 
 #[tokio::test]
 async fn e2e_protected_with_expired_token_unauthorized() -> anyhow::Result<()> {
-    if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-    let app = match start_server().await {
-        Ok(a) => a,
-        Err(_) => return Ok(()),
-    };
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
     let c = reqwest::Client::new();
 
     // Create an expired JWT token signed with test-secret
@@ -158,17 +79,13 @@ async fn e2e_protected_with_expired_token_unauthorized() -> anyhow::Result<()> {
         .header("Authorization", format!("Bearer {}", token))
         .send().await?;
     assert_eq!(res.status(), HttpStatusCode::UNAUTHORIZED);
+    app.cleanup().await;
     Ok(())
 }
 
 #[tokio::test]
 async fn e2e_admin_api_key_and_access_api_posts() -> anyhow::Result<()> {
-    if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-    if std::env::var("SKIP_EXTERNAL").is_ok() { return Ok(()); }
-    let app = match start_server().await {
-        Ok(a) => a,
-        Err(_) => return Ok(()),
-    };
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
     let c = client();
 
     // Login so we have JWT cookie for admin endpoints
@@ -180,22 +97,164 @@ async fn e2e_admin_api_key_and_access_api_posts() -> anyhow::Result<()> {
     let _ = c.post(format!("{}/auth/register", app.base_url))
         .json(&json!({"tenant_id": tid, "email": email, "name": name, "password": password}))
         .send().await?;
-    let _ = c.post(format!("{}/auth/login", app.base_url))
+    let login = c.post(format!("{}/auth/login", app.base_url))
         .json(&json!({"tenant_id": tid, "email": email, "password": password}))
+        .send().await?
+        .json::<serde_json::Value>().await?;
+    // Cookie 认证的写请求需回传 CSRF token，缺失时 403
+    let csrf = login["csrf_token"].as_str().unwrap_or_default().to_string();
+    let res = c.post(format!("{}/admin/api-keys", app.base_url))
+        .json(&json!({"user": "svc-user", "api_key": "k-123"}))
         .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::FORBIDDEN);
 
     // Set an API key via admin endpoint
     let res = c.post(format!("{}/admin/api-keys", app.base_url))
+        .header("X-CSRF-Token", &csrf)
         .json(&json!({"user": "svc-user", "api_key": "k-123"}))
         .send().await?;
     assert_eq!(res.status(), HttpStatusCode::OK);
 
+    // /api/posts 由 proxy_api 配置驱动，指向本地 echo 上游
+    let echo = common::echo::spawn("127.0.0.1:0").await?;
+    let endpoint = format!("/api/posts-{}", Uuid::new_v4().simple());
+    let res = c.post(format!("{}/admin/proxy-apis", app.base_url))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"tenant_id": tid, "endpoint_url": endpoint, "method": "GET", "forward_target": format!("http://{}/posts", echo), "require_api_key": true}))
+        .send().await?;
+    assert!(res.status().is_success());
+
     // Access protected API with both cookie (JWT) and X-API-Key header
-    let res = c.get(format!("{}/api/posts/1", app.base_url))
+    let res = c.get(format!("{}{}/1", app.base_url, endpoint))
         .header("X-API-Key", "k-123")
         .send().await?;
     assert_eq!(res.status(), HttpStatusCode::OK);
     let body = res.json::<serde_json::Value>().await?;
-    assert_eq!(body["id"], 1);
+    assert_eq!(body["path"], "/posts/1");
+
+    // mock 模式：不再转发，直接返回模板渲染后的响应
+    let api_id = c.get(format!("{}/admin/proxy-apis?tenant_id={}", app.base_url, tid)).send().await?
+        .json::<serde_json::Value>().await?["items"][0]["id"].as_str().unwrap_or_default().to_string();
+    let res = c.put(format!("{}/admin/proxy-apis/{}/mock", app.base_url, api_id))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"enabled": true, "response": {"status": 503, "headers": {"retry-after": "60"}, "body": "maintenance: {{method}} {{path}}"}}))
+        .send().await?;
+    assert!(res.status().is_success());
+    let res = c.get(format!("{}{}/1", app.base_url, endpoint))
+        .header("X-API-Key", "k-123")
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()["retry-after"], "60");
+    assert_eq!(res.text().await?, format!("maintenance: GET {}/1", endpoint));
+
+    // 维护窗口：关闭 mock 后，窗口内返回配置的 503 payload
+    let res = c.put(format!("{}/admin/proxy-apis/{}/mock", app.base_url, api_id))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"enabled": false}))
+        .send().await?;
+    assert!(res.status().is_success());
+    let res = c.put(format!("{}/admin/proxy-apis/{}/maintenance", app.base_url, api_id))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"start": chrono::Utc::now() - chrono::Duration::minutes(1), "end": chrono::Utc::now() + chrono::Duration::hours(1), "message": "{\"error\":\"down for upgrade\"}"}))
+        .send().await?;
+    assert!(res.status().is_success());
+    let res = c.get(format!("{}{}/1", app.base_url, endpoint))
+        .header("X-API-Key", "k-123")
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::SERVICE_UNAVAILABLE);
+    assert!(res.headers().contains_key("retry-after"));
+    assert_eq!(res.json::<serde_json::Value>().await?["error"], "down for upgrade");
+    let res = c.put(format!("{}/admin/proxy-apis/{}/maintenance", app.base_url, api_id))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({}))
+        .send().await?;
+    assert!(res.status().is_success());
+
+    // 租户 kill switch
+    let res = c.put(format!("{}/admin/tenants/{}/suspension", app.base_url, tid))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"suspended": true, "reason": "billing"}))
+        .send().await?;
+    assert!(res.status().is_success());
+    let res = c.get(format!("{}{}/1", app.base_url, endpoint))
+        .header("X-API-Key", "k-123")
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::SERVICE_UNAVAILABLE);
+    // 拒绝响应引用暂停租户的那次配置变更
+    let caused_by = res.headers()["x-config-revision"].to_str()?.to_string();
+    assert_eq!(res.json::<serde_json::Value>().await?["error"], "tenant_suspended");
+    let timeline = c.get(format!("{}/admin/config-revisions", app.base_url)).send().await?
+        .json::<serde_json::Value>().await?;
+    assert_eq!(timeline["current_revision"].to_string(), caused_by);
+    let res = c.put(format!("{}/admin/tenants/{}/suspension", app.base_url, tid))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"suspended": false}))
+        .send().await?;
+    assert!(res.status().is_success());
+    let res = c.get(format!("{}{}/1", app.base_url, endpoint))
+        .header("X-API-Key", "k-123")
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::OK);
+
+    // 监控配置随当前配置生成
+    let bundle = c.get(format!("{}/admin/observability/bundle", app.base_url)).send().await?
+        .json::<serde_json::Value>().await?;
+    assert_eq!(bundle["prometheus_rules"]["groups"][0]["name"], "api_proxy_slo");
+    assert_eq!(bundle["grafana_dashboard"]["uid"], "api-proxy-gateway");
+
+    // 生效配置及来源
+    let sys = c.get(format!("{}/admin/system/config?include_provenance=true", app.base_url)).send().await?
+        .json::<serde_json::Value>().await?;
+    assert_eq!(sys["precedence"][0], "default");
+    assert_eq!(sys["provenance"]["server.port"]["source"]["kind"], "default");
+    let sys = c.get(format!("{}/admin/system/config", app.base_url)).send().await?
+        .json::<serde_json::Value>().await?;
+    assert!(sys.get("provenance").is_none());
+
+    // 批量吊销走后台任务：202 + Location，轮询 /admin/jobs/{id} 获取结果
+    c.post(format!("{}/admin/api-keys", app.base_url))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"user": "bulk-user", "api_key": "k-bulk"}))
+        .send().await?;
+    let res = c.post(format!("{}/admin/api-keys/revoke", app.base_url))
+        .header("X-CSRF-Token", &csrf)
+        .json(&json!({"users": ["bulk-user", "nobody"]}))
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::ACCEPTED);
+    let location = res.headers()["location"].to_str()?.to_string();
+    let mut job = serde_json::Value::Null;
+    for _ in 0..50 {
+        job = c.get(format!("{}{}", app.base_url, location)).send().await?.json().await?;
+        if job["status"] == "succeeded" { break; }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["result"]["revoked"], json!(["bulk-user"]));
+    assert_eq!(job["progress"]["done"], 2);
+
+    // 未配置的路径返回 404，而不是访问外部站点
+    let res = c.get(format!("{}/api/unconfigured-{}", app.base_url, Uuid::new_v4()))
+        .header("X-API-Key", "k-123")
+        .send().await?;
+    assert_eq!(res.status(), HttpStatusCode::NOT_FOUND);
     Ok(())
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn e2e_platform_admin_endpoints_require_admin_role() -> anyhow::Result<()> {
+    let Some(app) = TestApp::builder().build().await? else { return Ok(()) };
+    // 自助注册的用户是 member：平台级密钥端点一律 403
+    let member = app.register_and_login().await?;
+    let endpoints = [
+        (reqwest::Method::GET, "/admin/jwt-keys"),
+        (reqwest::Method::POST, "/admin/jwt-keys/rotate"),
+        (reqwest::Method::DELETE, "/admin/jwt-keys/some-kid"),
+        (reqwest::Method::POST, "/admin/encryption/seal"),
+        (reqwest::Method::POST, "/admin/encryption/reencrypt"),
+    ];
+    for (method, path) in endpoints {
+        let res = app.authed(&member, method, path).json(&json!({"value": "s3cret"})).send().await?;
+        assert_eq!(res.status(), HttpStatusCode::FORBIDDEN, "{}", path);
+    }
+    app.cleanup().await;
+    Ok(())
+}
@@ -1,15 +1,73 @@
 use crate::errors::ServiceError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::pagination::{Page, Pagination};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::file::api_management::{ApiRecord, ApiRecordInput};
+use crate::storage::health::StoreHealth;
+
+/// 认证信息定义：目前支持是否需要 API Key，后续可扩展为更多类型
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthInfo {
+    pub require_api_key: bool,
+}
+
+/// API 记录结构：用于描述被代理/转发的 API
+/// - endpoint_url: 例如 `/api/v1/orders`
+/// - method: `GET|POST|PUT|DELETE|PATCH|HEAD|OPTIONS`
+/// - forward_target: 例如 `https://upstream.example.com`
+/// - auth: 认证要求，目前仅包含是否需要 API Key
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiRecord {
+    pub id: Uuid,
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub auth: AuthInfo,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 创建/更新输入模型：不包含 id/created_at，由服务端生成
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiRecordInput {
+    pub endpoint_url: String,
+    pub method: String,
+    pub forward_target: String,
+    pub auth: AuthInfo,
+}
+
+impl ApiRecordInput {
+    /// 统一校验：方法、路径、目标地址
+    pub fn validate(&self) -> Result<(), ServiceError> {
+        let method_up = self.method.to_ascii_uppercase();
+        let valid_methods = [
+            "GET","POST","PUT","DELETE","PATCH","HEAD","OPTIONS"
+        ];
+        if !valid_methods.contains(&method_up.as_str()) {
+            return Err(ServiceError::Validation("invalid HTTP method".into()));
+        }
+        if !(self.endpoint_url.starts_with('/')) {
+            return Err(ServiceError::Validation("endpoint_url must start with '/'".into()));
+        }
+        if !(self.forward_target.starts_with("http://") || self.forward_target.starts_with("https://")) {
+            return Err(ServiceError::Validation("forward_target must start with http(s)".into()));
+        }
+        Ok(())
+    }
+}
 
 /// Trait abstraction for API management storage (CRUD of forward/proxy configs).
+/// Implementations: [`crate::file::api_management::ApiStore`] (JSON file) and
+/// [`crate::db::api_management_store::DbApiStore`] (database).
 #[async_trait]
 pub trait ApiManagementStore: Send + Sync {
     async fn list(&self) -> Vec<ApiRecord>;
+    /// 分页列出，按创建时间排序
+    async fn list_page(&self, opts: Pagination) -> Result<Page<ApiRecord>, ServiceError>;
     async fn get(&self, id: Uuid) -> Option<ApiRecord>;
     async fn create(&self, input: ApiRecordInput) -> Result<ApiRecord, ServiceError>;
     async fn update(&self, id: Uuid, input: ApiRecordInput) -> Result<ApiRecord, ServiceError>;
     async fn delete(&self, id: Uuid) -> Result<bool, ServiceError>;
+    async fn health(&self) -> StoreHealth;
 }
\ No newline at end of file
@@ -1,12 +1,19 @@
 use crate::errors::ServiceError;
 use async_trait::async_trait;
+use common::pagination::{Page, Pagination};
+
+use crate::storage::health::StoreHealth;
 
 /// Trait abstraction for Admin API key storage.
-/// Implementations can be file-backed, database-backed, or remote KV.
+/// Implementations: [`crate::file::admin_kv_store::ApiKeysStore`] (JSON file) and
+/// [`crate::db::admin_kv_store::DbApiKeysStore`] (database).
 #[async_trait]
 pub trait AdminKvStore: Send + Sync {
     async fn list(&self) -> Vec<(String, String)>;
+    /// One page of `(user, api_key)` pairs ordered by user
+    async fn list_page(&self, opts: Pagination) -> Result<Page<(String, String)>, ServiceError>;
     async fn set(&self, user: String, api_key: String) -> Result<(), ServiceError>;
     async fn delete(&self, user: &str) -> Result<bool, ServiceError>;
     async fn contains_value(&self, value: &str) -> bool;
+    async fn health(&self) -> StoreHealth;
 }
\ No newline at end of file
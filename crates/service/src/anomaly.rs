@@ -0,0 +1,454 @@
+//! Traffic anomaly detection over recent `request_log` rows.
+//! - Current window `[now - window, now)` is compared with the `baseline_windows` windows before it.
+//! - Per route: error-rate spike (absolute increase over baseline) and p95 latency regression (ratio).
+//! - Per tenant: traffic surge (current count vs. average baseline window).
+//! - Thresholds can be overridden per tenant; the same anomaly is alerted at most once per cooldown.
+//! - Alerts go to a webhook as a generic JSON document or a Slack-compatible `{"text": ...}` payload.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, JoinType, QueryFilter, QuerySelect, RelationTrait};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::http_client::{HttpClientConfig, HttpClientFactory};
+use common::lock::{run_exclusive, DistributedLock};
+use common::time::{system_clock, SharedClock};
+use models::request_log;
+
+use crate::errors::ServiceError;
+
+pub static ANOMALIES_DETECTED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "api_proxy_anomalies_detected_total",
+        "Traffic anomalies detected over request_log, by kind",
+        &["kind"]
+    )
+    .expect("register anomalies_detected_total")
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyThresholds {
+    pub min_requests: u64,
+    pub error_rate_increase: f64,
+    pub latency_ratio: f64,
+    pub traffic_ratio: f64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self { min_requests: 20, error_rate_increase: 0.1, latency_ratio: 2.0, traffic_ratio: 3.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    Json,
+    Slack,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnomalyOptions {
+    pub interval: Duration,
+    pub window: Duration,
+    pub baseline_windows: u32,
+    pub cooldown: Duration,
+    pub webhook_url: Option<String>,
+    pub webhook_format: WebhookFormat,
+    pub thresholds: AnomalyThresholds,
+    pub tenant_thresholds: HashMap<Uuid, AnomalyThresholds>,
+}
+
+impl Default for AnomalyOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            window: Duration::from_secs(300),
+            baseline_windows: 12,
+            cooldown: Duration::from_secs(1800),
+            webhook_url: None,
+            webhook_format: WebhookFormat::Json,
+            thresholds: AnomalyThresholds::default(),
+            tenant_thresholds: HashMap::new(),
+        }
+    }
+}
+
+impl AnomalyOptions {
+    fn thresholds_for(&self, tenant_id: Uuid) -> &AnomalyThresholds {
+        self.tenant_thresholds.get(&tenant_id).unwrap_or(&self.thresholds)
+    }
+}
+
+/// One request_log row with the owning tenant.
+#[derive(Debug, Clone)]
+pub struct LogSample {
+    pub tenant_id: Uuid,
+    pub route_id: Uuid,
+    pub at: DateTime<Utc>,
+    pub latency_ms: i32,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    ErrorRateSpike,
+    LatencyRegression,
+    TrafficSurge,
+}
+
+impl AnomalyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnomalyKind::ErrorRateSpike => "error_rate_spike",
+            AnomalyKind::LatencyRegression => "latency_regression",
+            AnomalyKind::TrafficSurge => "traffic_surge",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub tenant_id: Uuid,
+    /// None for tenant-level anomalies
+    pub route_id: Option<Uuid>,
+    pub observed: f64,
+    pub baseline: f64,
+    pub threshold: f64,
+    pub requests: u64,
+}
+
+impl Anomaly {
+    fn key(&self) -> String {
+        format!("{}:{}:{}", self.kind.as_str(), self.tenant_id, self.route_id.map(|r| r.to_string()).unwrap_or_default())
+    }
+
+    fn summary(&self) -> String {
+        let scope = match self.route_id {
+            Some(r) => format!("tenant {} route {}", self.tenant_id, r),
+            None => format!("tenant {}", self.tenant_id),
+        };
+        match self.kind {
+            AnomalyKind::ErrorRateSpike => format!(
+                "error rate spike on {}: {:.1}% (baseline {:.1}%, {} requests)",
+                scope,
+                self.observed * 100.0,
+                self.baseline * 100.0,
+                self.requests
+            ),
+            AnomalyKind::LatencyRegression => {
+                format!("p95 latency regression on {}: {:.0}ms (baseline {:.0}ms)", scope, self.observed, self.baseline)
+            }
+            AnomalyKind::TrafficSurge => format!(
+                "traffic surge on {}: {} requests in window (baseline avg {:.1})",
+                scope, self.requests, self.baseline
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    requests: u64,
+    errors: u64,
+    latencies: Vec<i32>,
+}
+
+impl Stats {
+    fn add(&mut self, s: &LogSample) {
+        self.requests += 1;
+        self.errors += u64::from(!s.success);
+        self.latencies.push(s.latency_ms);
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.requests == 0 { 0.0 } else { self.errors as f64 / self.requests as f64 }
+    }
+
+    fn p95(&mut self) -> f64 {
+        if self.latencies.is_empty() {
+            return 0.0;
+        }
+        self.latencies.sort_unstable();
+        let idx = ((self.latencies.len() as f64 * 0.95).ceil() as usize).clamp(1, self.latencies.len()) - 1;
+        self.latencies[idx] as f64
+    }
+}
+
+/// Pure detection over samples covering the current window and its baseline.
+pub fn detect(samples: &[LogSample], now: DateTime<Utc>, opts: &AnomalyOptions) -> Vec<Anomaly> {
+    let window = chrono::Duration::from_std(opts.window).unwrap_or_else(|_| chrono::Duration::seconds(300));
+    let current_start = now - window;
+    let baseline_start = current_start - window * opts.baseline_windows.max(1) as i32;
+
+    let mut routes: HashMap<(Uuid, Uuid), (Stats, Stats)> = HashMap::new();
+    let mut tenants: HashMap<Uuid, (u64, u64)> = HashMap::new();
+    for s in samples.iter().filter(|s| s.at >= baseline_start && s.at < now) {
+        let current = s.at >= current_start;
+        let entry = routes.entry((s.tenant_id, s.route_id)).or_default();
+        let t = tenants.entry(s.tenant_id).or_default();
+        if current {
+            entry.0.add(s);
+            t.0 += 1;
+        } else {
+            entry.1.add(s);
+            t.1 += 1;
+        }
+    }
+
+    let mut out = Vec::new();
+    for ((tenant_id, route_id), (mut cur, mut base)) in routes {
+        let th = opts.thresholds_for(tenant_id);
+        if cur.requests < th.min_requests {
+            continue;
+        }
+        let (rate, base_rate) = (cur.error_rate(), base.error_rate());
+        if rate - base_rate >= th.error_rate_increase {
+            out.push(Anomaly {
+                kind: AnomalyKind::ErrorRateSpike,
+                tenant_id,
+                route_id: Some(route_id),
+                observed: rate,
+                baseline: base_rate,
+                threshold: th.error_rate_increase,
+                requests: cur.requests,
+            });
+        }
+        if base.requests >= th.min_requests {
+            let (p95, base_p95) = (cur.p95(), base.p95());
+            if base_p95 > 0.0 && p95 >= base_p95 * th.latency_ratio {
+                out.push(Anomaly {
+                    kind: AnomalyKind::LatencyRegression,
+                    tenant_id,
+                    route_id: Some(route_id),
+                    observed: p95,
+                    baseline: base_p95,
+                    threshold: th.latency_ratio,
+                    requests: cur.requests,
+                });
+            }
+        }
+    }
+    for (tenant_id, (cur, base)) in tenants {
+        let th = opts.thresholds_for(tenant_id);
+        let avg = base as f64 / opts.baseline_windows.max(1) as f64;
+        // 没有基线的新租户不判断
+        if cur >= th.min_requests && base > 0 && cur as f64 >= avg * th.traffic_ratio {
+            out.push(Anomaly {
+                kind: AnomalyKind::TrafficSurge,
+                tenant_id,
+                route_id: None,
+                observed: cur as f64,
+                baseline: avg,
+                threshold: th.traffic_ratio,
+                requests: cur,
+            });
+        }
+    }
+    out.sort_by_key(|a| (a.tenant_id, a.route_id, a.kind.as_str()));
+    out
+}
+
+/// Webhook body for a batch of anomalies.
+pub fn payload(format: WebhookFormat, anomalies: &[Anomaly], detected_at: DateTime<Utc>) -> Value {
+    match format {
+        WebhookFormat::Json => json!({
+            "source": "api_proxy_gateway",
+            "detected_at": detected_at,
+            "anomalies": anomalies.iter().map(|a| {
+                let mut v = serde_json::to_value(a).unwrap_or_default();
+                v["summary"] = Value::String(a.summary());
+                v
+            }).collect::<Vec<_>>(),
+        }),
+        WebhookFormat::Slack => {
+            let lines: Vec<String> = anomalies.iter().map(|a| format!("• {}", a.summary())).collect();
+            json!({ "text": format!(":rotating_light: {} traffic anomal{} detected\n{}", anomalies.len(), if anomalies.len() == 1 { "y" } else { "ies" }, lines.join("\n")) })
+        }
+    }
+}
+
+pub struct AnomalyDetector {
+    db: DatabaseConnection,
+    opts: AnomalyOptions,
+    client: reqwest::Client,
+    last_alerted: Mutex<HashMap<String, DateTime<Utc>>>,
+    lock: Option<Arc<dyn DistributedLock>>,
+    clock: SharedClock,
+}
+
+impl AnomalyDetector {
+    /// The alert webhook client follows the process egress policy.
+    pub fn new(db: DatabaseConnection, opts: AnomalyOptions) -> Result<Self, ServiceError> {
+        let client = HttpClientFactory::new(HttpClientConfig { timeout_ms: 10_000, ..Default::default() })
+            .with_egress(common::egress::global())
+            .build()
+            .map_err(|e| ServiceError::Validation(format!("anomaly webhook client: {}", e)))?;
+        Ok(Self { db, opts, client, last_alerted: Mutex::new(HashMap::new()), lock: None, clock: system_clock() })
+    }
+
+    /// Run the scheduled analysis on one replica at a time.
+    pub fn with_lock(mut self, lock: Arc<dyn DistributedLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    async fn load_samples(&self, since: DateTime<Utc>) -> Result<Vec<LogSample>, ServiceError> {
+        let rows: Vec<(Uuid, Uuid, chrono::DateTime<chrono::FixedOffset>, i32, bool)> = request_log::Entity::find()
+            .select_only()
+            .column(models::route::Column::TenantId)
+            .column(request_log::Column::RouteId)
+            .column(request_log::Column::Timestamp)
+            .column(request_log::Column::LatencyMs)
+            .column(request_log::Column::Success)
+            .join(JoinType::InnerJoin, request_log::Relation::Route.def())
+            .filter(request_log::Column::Timestamp.gte(since))
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|(tenant_id, route_id, at, latency_ms, success)| LogSample { tenant_id, route_id, at: at.with_timezone(&Utc), latency_ms, success })
+            .collect())
+    }
+
+    /// Drop anomalies alerted within the cooldown.
+    fn fresh(&self, anomalies: Vec<Anomaly>, now: DateTime<Utc>) -> Vec<Anomaly> {
+        let cooldown = chrono::Duration::from_std(self.opts.cooldown).unwrap_or_default();
+        let mut last = self.last_alerted.lock().unwrap_or_else(|e| e.into_inner());
+        last.retain(|_, at| now - *at < cooldown);
+        anomalies
+            .into_iter()
+            .filter(|a| {
+                let key = a.key();
+                if last.contains_key(&key) {
+                    return false;
+                }
+                last.insert(key, now);
+                true
+            })
+            .collect()
+    }
+
+    /// One analysis pass; returns the anomalies that were alerted.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<Vec<Anomaly>, ServiceError> {
+        let span = self.opts.window * (self.opts.baseline_windows.max(1) + 1);
+        let since = now - chrono::Duration::from_std(span).unwrap_or_default();
+        let samples = self.load_samples(since).await?;
+        let anomalies = self.fresh(detect(&samples, now, &self.opts), now);
+        if anomalies.is_empty() {
+            return Ok(anomalies);
+        }
+        for a in &anomalies {
+            ANOMALIES_DETECTED_TOTAL.with_label_values(&[a.kind.as_str()]).inc();
+            warn!(kind = a.kind.as_str(), tenant_id = %a.tenant_id, route_id = ?a.route_id, observed = a.observed, baseline = a.baseline, "traffic_anomaly");
+        }
+        if let Some(url) = &self.opts.webhook_url {
+            let body = payload(self.opts.webhook_format, &anomalies, now);
+            match self.client.post(url).json(&body).send().await {
+                Ok(resp) if resp.status().is_success() => info!(count = anomalies.len(), "anomaly_alert_sent"),
+                Ok(resp) => warn!(status = %resp.status(), "anomaly_alert_rejected"),
+                Err(e) => warn!(error = %e, "anomaly_alert_failed"),
+            }
+        }
+        Ok(anomalies)
+    }
+
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.opts.interval);
+            loop {
+                ticker.tick().await;
+                if let Some(Err(e)) = run_exclusive(this.lock.as_deref(), "anomaly_analysis", this.opts.interval, this.run_once(this.clock.now())).await {
+                    warn!(error = %e, "anomaly_analysis_failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(tenant: Uuid, route: Uuid, start: DateTime<Utc>, n: usize, spacing_secs: i64, latency_ms: i32, errors_every: usize) -> Vec<LogSample> {
+        (0..n)
+            .map(|i| LogSample {
+                tenant_id: tenant,
+                route_id: route,
+                at: start + chrono::Duration::seconds(i as i64 * spacing_secs),
+                latency_ms,
+                success: errors_every == 0 || i % errors_every != 0,
+            })
+            .collect()
+    }
+
+    fn opts() -> AnomalyOptions {
+        AnomalyOptions { window: Duration::from_secs(60), baseline_windows: 4, ..Default::default() }
+    }
+
+    #[test]
+    fn detects_error_spike_latency_regression_and_surge() {
+        let now = Utc::now();
+        let (t, r) = (Uuid::new_v4(), Uuid::new_v4());
+        // baseline: 4 windows * 30 requests, 50ms, no errors
+        let mut logs = samples(t, r, now - chrono::Duration::seconds(300), 120, 2, 50, 0);
+        // current window: 120 requests, 200ms, every 2nd fails
+        logs.extend(samples(t, r, now - chrono::Duration::seconds(59), 120, 0, 200, 2));
+
+        let found = detect(&logs, now, &opts());
+        let kinds: Vec<AnomalyKind> = found.iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, vec![AnomalyKind::TrafficSurge, AnomalyKind::ErrorRateSpike, AnomalyKind::LatencyRegression]);
+        assert_eq!(found[1].observed, 0.5);
+        assert_eq!((found[2].observed, found[2].baseline), (200.0, 50.0));
+        assert_eq!(found[0].baseline, 30.0);
+    }
+
+    #[test]
+    fn steady_traffic_and_tenant_overrides() {
+        let now = Utc::now();
+        let (t, r) = (Uuid::new_v4(), Uuid::new_v4());
+        let logs = samples(t, r, now - chrono::Duration::seconds(300), 150, 2, 50, 10);
+        assert!(detect(&logs, now, &opts()).is_empty());
+
+        // 10% errors everywhere; a strict tenant threshold flags nothing without a baseline increase
+        let mut strict = opts();
+        strict.tenant_thresholds.insert(t, AnomalyThresholds { error_rate_increase: 0.0, ..Default::default() });
+        assert_eq!(detect(&logs, now, &strict).iter().map(|a| a.kind).collect::<Vec<_>>(), vec![AnomalyKind::ErrorRateSpike]);
+    }
+
+    #[test]
+    fn payload_formats() {
+        let a = Anomaly {
+            kind: AnomalyKind::TrafficSurge,
+            tenant_id: Uuid::nil(),
+            route_id: None,
+            observed: 90.0,
+            baseline: 10.0,
+            threshold: 3.0,
+            requests: 90,
+        };
+        let slack = payload(WebhookFormat::Slack, std::slice::from_ref(&a), Utc::now());
+        assert!(slack["text"].as_str().unwrap().contains("traffic surge on tenant 00000000-0000-0000-0000-000000000000: 90 requests"));
+        let generic = payload(WebhookFormat::Json, &[a], Utc::now());
+        assert_eq!(generic["anomalies"][0]["kind"], "traffic_surge");
+        assert!(generic["anomalies"][0]["summary"].is_string());
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,6 +19,30 @@ pub struct LoginInput {
     pub password: String,
 }
 
+/// Invitation to join a tenant (admin side); the token is returned once by [`crate::auth::service::AuthService::invite`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteInput {
+    pub tenant_id: Uuid,
+    pub email: String,
+    /// `models::user::ROLE_*`
+    pub role: String,
+}
+
+/// Completing registration from an invitation; tenant, email and role come from the invitation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptInviteInput {
+    pub token: String,
+    pub name: String,
+    pub password: String,
+}
+
+/// Client a login came from, recorded on the session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
 /// Domain user (business view)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthUser {
@@ -25,6 +50,80 @@ pub struct AuthUser {
     pub tenant_id: Uuid,
     pub email: String,
     pub name: String,
+    /// `models::user::STATUS_*`
+    pub status: String,
+    /// `models::user::ROLE_*`
+    pub role: String,
+}
+
+impl AuthUser {
+    pub fn is_verified(&self) -> bool {
+        self.status != models::user::STATUS_UNVERIFIED
+    }
+}
+
+/// Tenant policy for verifying emails at registration (`tenant.email_verification`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailVerificationPolicy {
+    /// New users are active immediately
+    #[default]
+    Off,
+    /// New users get a verification token but may log in before verifying
+    Optional,
+    /// New users cannot log in until verified
+    Required,
+}
+
+impl EmailVerificationPolicy {
+    /// Unknown values are treated as `Off`, matching the column default
+    pub fn parse(s: &str) -> Self {
+        match s {
+            models::tenant::EMAIL_VERIFICATION_OPTIONAL => Self::Optional,
+            models::tenant::EMAIL_VERIFICATION_REQUIRED => Self::Required,
+            _ => Self::Off,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => models::tenant::EMAIL_VERIFICATION_OFF,
+            Self::Optional => models::tenant::EMAIL_VERIFICATION_OPTIONAL,
+            Self::Required => models::tenant::EMAIL_VERIFICATION_REQUIRED,
+        }
+    }
+}
+
+/// Claims of an email verification token. There is deliberately no `sub`, so the token never
+/// decodes as [`TokenClaims`], and it is signed with a key derived for this purpose only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationClaims {
+    pub uid: Uuid,
+    pub tid: Uuid,
+    pub email: String,
+    pub purpose: String,
+    pub exp: usize,
+}
+
+/// Stored invitation; only the SHA-256 of its token is kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvitationRecord {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub email: String,
+    pub role: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub accepted_user_id: Option<Uuid>,
+}
+
+impl InvitationRecord {
+    pub fn is_pending(&self, now: DateTime<Utc>) -> bool {
+        self.accepted_at.is_none() && self.expires_at > now
+    }
 }
 
 /// Domain credentials (hashed)
@@ -40,4 +139,44 @@ pub struct Credentials {
 pub struct AuthSession {
     pub user: AuthUser,
     pub token: Option<String>,
-}
\ No newline at end of file
+    /// Server-side session the token is bound to (`sid`); None when no token was issued
+    pub session_id: Option<Uuid>,
+}
+
+/// Server-side record of an issued login token; revoking it rejects the token before `exp`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub client: SessionClient,
+    /// Last request authenticated with the session; None until the first one after login
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+impl SessionRecord {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+/// JWT claims issued at login; `uid`/`tid` are absent in tokens minted elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenClaims {
+    pub sub: String,
+    #[serde(default)]
+    pub uid: Option<Uuid>,
+    #[serde(default)]
+    pub tid: Option<Uuid>,
+    pub exp: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Session id (see [`SessionRecord`]); tokens without it are not tracked and cannot be revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+}
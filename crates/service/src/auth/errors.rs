@@ -11,6 +11,14 @@ pub enum AuthError {
     NotFound,
     #[error("invalid credentials")]
     Unauthorized,
+    #[error("too many active sessions")]
+    SessionLimit,
+    #[error("email not verified")]
+    EmailNotVerified,
+    #[error("tenant accepts new users by invitation only")]
+    InviteRequired,
+    #[error("admin role required")]
+    Forbidden,
     #[error("hashing error: {0}")]
     HashError(String),
     #[error("token error: {0}")]
@@ -27,6 +35,10 @@ impl AuthError {
             AuthError::Conflict => 1002,
             AuthError::NotFound => 1003,
             AuthError::Unauthorized => 1004,
+            AuthError::SessionLimit => 1005,
+            AuthError::EmailNotVerified => 1006,
+            AuthError::InviteRequired => 1007,
+            AuthError::Forbidden => 1008,
             AuthError::HashError(_) => 1101,
             AuthError::TokenError(_) => 1102,
             AuthError::Repository(_) => 1200,
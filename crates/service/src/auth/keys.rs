@@ -0,0 +1,159 @@
+//! Signing keys for login tokens, addressed by `kid`.
+//! - The active key signs new tokens and puts its `kid` in the JWT header.
+//! - Verification picks the key by `kid`; superseded keys are accepted until their `verify_until`.
+//! - Tokens without a `kid` (issued before rotation was enabled) fall back to the static `jwt_secret`,
+//!   but only until `legacy_until` once the ring has an active key (first key's creation + grace).
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+
+use super::errors::AuthError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    HS256,
+    EdDSA,
+}
+
+impl KeyAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyAlgorithm::HS256 => "HS256",
+            KeyAlgorithm::EdDSA => "EdDSA",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "HS256" => Some(KeyAlgorithm::HS256),
+            "EdDSA" => Some(KeyAlgorithm::EdDSA),
+            _ => None,
+        }
+    }
+
+    pub fn jwt(&self) -> Algorithm {
+        match self {
+            KeyAlgorithm::HS256 => Algorithm::HS256,
+            KeyAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+}
+
+/// Private key material: the HS256 secret, or an Ed25519 PKCS#8 document.
+pub struct KeyMaterial {
+    pub algorithm: KeyAlgorithm,
+    pub secret: Vec<u8>,
+}
+
+impl KeyMaterial {
+    pub fn generate(algorithm: KeyAlgorithm) -> Result<Self, AuthError> {
+        let rng = SystemRandom::new();
+        let secret = match algorithm {
+            KeyAlgorithm::HS256 => {
+                let mut secret = vec![0u8; 32];
+                rng.fill(&mut secret).map_err(|_| AuthError::TokenError("rng failure".into()))?;
+                secret
+            }
+            KeyAlgorithm::EdDSA => Ed25519KeyPair::generate_pkcs8(&rng)
+                .map_err(|_| AuthError::TokenError("ed25519 key generation failed".into()))?
+                .as_ref()
+                .to_vec(),
+        };
+        Ok(Self { algorithm, secret })
+    }
+
+    /// Base64url public key for EdDSA (the JWK `x` value); None for HS256.
+    pub fn public_key(&self) -> Result<Option<String>, AuthError> {
+        match self.algorithm {
+            KeyAlgorithm::HS256 => Ok(None),
+            KeyAlgorithm::EdDSA => {
+                let pair = Ed25519KeyPair::from_pkcs8(&self.secret).map_err(|_| AuthError::TokenError("invalid ed25519 key".into()))?;
+                Ok(Some(URL_SAFE_NO_PAD.encode(pair.public_key().as_ref())))
+            }
+        }
+    }
+
+    fn encoding_key(&self) -> EncodingKey {
+        match self.algorithm {
+            KeyAlgorithm::HS256 => EncodingKey::from_secret(&self.secret),
+            KeyAlgorithm::EdDSA => EncodingKey::from_ed_der(&self.secret),
+        }
+    }
+
+    fn decoding_key(&self) -> Result<DecodingKey, AuthError> {
+        match self.algorithm {
+            KeyAlgorithm::HS256 => Ok(DecodingKey::from_secret(&self.secret)),
+            KeyAlgorithm::EdDSA => {
+                let x = self.public_key()?.unwrap_or_default();
+                DecodingKey::from_ed_components(&x).map_err(|e| AuthError::TokenError(e.to_string()))
+            }
+        }
+    }
+}
+
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: KeyAlgorithm,
+    pub(crate) encoding: EncodingKey,
+}
+
+struct VerifyingKey {
+    algorithm: KeyAlgorithm,
+    decoding: DecodingKey,
+    until: Option<DateTime<Utc>>,
+}
+
+#[derive(Default)]
+pub struct KeyRing {
+    active: Option<SigningKey>,
+    verifiers: HashMap<String, VerifyingKey>,
+    legacy_until: Option<DateTime<Utc>>,
+}
+
+impl KeyRing {
+    /// Add a key; `active` makes it the signing key, `verify_until` bounds its use for verification.
+    pub fn insert(&mut self, kid: &str, material: &KeyMaterial, active: bool, verify_until: Option<DateTime<Utc>>) -> Result<(), AuthError> {
+        if active {
+            self.active = Some(SigningKey { kid: kid.to_string(), algorithm: material.algorithm, encoding: material.encoding_key() });
+        }
+        let verifier = VerifyingKey { algorithm: material.algorithm, decoding: material.decoding_key()?, until: verify_until };
+        self.verifiers.insert(kid.to_string(), verifier);
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<&SigningKey> {
+        self.active.as_ref()
+    }
+
+    /// End of the window in which tokens without `kid` are still verified with `jwt_secret`.
+    pub fn set_legacy_until(&mut self, until: Option<DateTime<Utc>>) {
+        self.legacy_until = until;
+    }
+
+    /// Tokens without `kid` are accepted while no key is active, then only before `legacy_until`.
+    pub(crate) fn accepts_legacy(&self, now: DateTime<Utc>) -> bool {
+        self.active.is_none() || self.legacy_until.is_some_and(|until| now < until)
+    }
+
+    /// Key for `kid` if it is still inside its verification window.
+    pub(crate) fn verifier(&self, kid: &str, now: DateTime<Utc>) -> Option<(Algorithm, &DecodingKey)> {
+        self.verifiers
+            .get(kid)
+            .filter(|v| v.until.is_none_or(|until| now < until))
+            .map(|v| (v.algorithm.jwt(), &v.decoding))
+    }
+
+    pub fn len(&self) -> usize {
+        self.verifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verifiers.is_empty()
+    }
+}
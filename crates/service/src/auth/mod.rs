@@ -6,6 +6,8 @@ pub mod domain;
 pub mod errors;
 pub mod repository;
 pub mod service;
+pub mod keys;
+pub mod notify;
 pub mod repo;
 
 pub use service::AuthService;
\ No newline at end of file
@@ -0,0 +1,72 @@
+//! Delivery of email verification tokens.
+//! - The service does not send mail itself: the token goes out through the notification subsystem
+//!   (webhooks) as `user.verification_requested`, and the tenant's endpoint mails the link to the user.
+//! - Payloads are kept in `webhook_delivery` like any other event, so tokens are short-lived.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use tracing::warn;
+
+use super::domain::AuthUser;
+use super::errors::AuthError;
+use crate::webhooks::{WebhookEvent, WebhookService};
+
+#[async_trait]
+pub trait VerificationNotifier: Send + Sync {
+    async fn send(&self, user: &AuthUser, token: &str, expires_at: DateTime<Utc>) -> Result<(), AuthError>;
+}
+
+/// Emits `user.verification_requested` to the user's tenant.
+pub struct WebhookVerificationNotifier {
+    webhooks: Arc<WebhookService>,
+    /// Link base; the payload carries `verify_url = {base}?token=...` when set
+    verify_url: Option<String>,
+}
+
+impl WebhookVerificationNotifier {
+    pub fn new(webhooks: Arc<WebhookService>, verify_url: Option<String>) -> Self {
+        Self { webhooks, verify_url }
+    }
+}
+
+#[async_trait]
+impl VerificationNotifier for WebhookVerificationNotifier {
+    async fn send(&self, user: &AuthUser, token: &str, expires_at: DateTime<Utc>) -> Result<(), AuthError> {
+        let data = json!({
+            "user_id": user.id,
+            "email": user.email,
+            "name": user.name,
+            "token": token,
+            "verify_url": self.verify_url.as_deref().map(|base| verify_link(base, token)),
+            "expires_at": expires_at,
+        });
+        let queued = self
+            .webhooks
+            .emit(user.tenant_id, WebhookEvent::UserVerificationRequested, data)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        if queued == 0 {
+            warn!(tenant_id = %user.tenant_id, user_id = %user.id, "no webhook subscribed to user.verification_requested; verification token not delivered");
+        }
+        Ok(())
+    }
+}
+
+fn verify_link(base: &str, token: &str) -> String {
+    let sep = if base.contains('?') { '&' } else { '?' };
+    format!("{}{}token={}", base, sep, token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_verify_link() {
+        assert_eq!(verify_link("https://portal.example.com/verify", "t.k.n"), "https://portal.example.com/verify?token=t.k.n");
+        assert_eq!(verify_link("https://portal.example.com/verify?lang=en", "t"), "https://portal.example.com/verify?lang=en&token=t");
+    }
+}
@@ -1,2 +1 @@
-#[cfg(feature = "seaorm")]
-pub mod seaorm;
\ No newline at end of file
+pub mod seaorm;
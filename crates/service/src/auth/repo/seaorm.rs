@@ -1,7 +1,10 @@
-use sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter, QueryOrder, Set};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::Expr;
 use uuid::Uuid;
 
-use crate::auth::domain::{AuthUser, Credentials};
+use crate::auth::domain::{AuthUser, Credentials, EmailVerificationPolicy, InvitationRecord, SessionClient, SessionRecord};
 use crate::auth::errors::AuthError;
 use crate::auth::repository::AuthRepository;
 
@@ -9,6 +12,37 @@ pub struct SeaOrmAuthRepository {
     pub db: DatabaseConnection,
 }
 
+fn auth_user(u: models::user::Model) -> AuthUser {
+    AuthUser { id: u.id, tenant_id: u.tenant_id, email: u.email, name: u.name, status: u.status, role: u.role }
+}
+
+fn session_record(m: models::auth_session::Model) -> SessionRecord {
+    SessionRecord {
+        id: m.id,
+        user_id: m.user_id,
+        tenant_id: m.tenant_id,
+        created_at: m.created_at.with_timezone(&Utc),
+        expires_at: m.expires_at.with_timezone(&Utc),
+        revoked_at: m.revoked_at.map(|t| t.with_timezone(&Utc)),
+        client: SessionClient { user_agent: m.user_agent, ip_address: m.ip_address },
+        last_seen_at: m.last_seen_at.map(|t| t.with_timezone(&Utc)),
+    }
+}
+
+fn invitation_record(m: models::invitation::Model) -> InvitationRecord {
+    InvitationRecord {
+        id: m.id,
+        tenant_id: m.tenant_id,
+        email: m.email,
+        role: m.role,
+        token_hash: m.token_hash,
+        created_at: m.created_at.with_timezone(&Utc),
+        expires_at: m.expires_at.with_timezone(&Utc),
+        accepted_at: m.accepted_at.map(|t| t.with_timezone(&Utc)),
+        accepted_user_id: m.accepted_user_id,
+    }
+}
+
 #[async_trait::async_trait]
 impl AuthRepository for SeaOrmAuthRepository {
     async fn find_user_by_tenant_email(&self, tenant_id: Uuid, email: &str) -> Result<Option<AuthUser>, AuthError> {
@@ -18,14 +52,68 @@ impl AuthRepository for SeaOrmAuthRepository {
             .one(&self.db)
             .await
             .map_err(|e| AuthError::Repository(e.to_string()))?;
-        Ok(res.map(|u| AuthUser { id: u.id, tenant_id: u.tenant_id, email: u.email, name: u.name }))
+        Ok(res.map(auth_user))
     }
 
-    async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str) -> Result<AuthUser, AuthError> {
-        let created = models::user::create(&self.db, tenant_id, email, name)
+    async fn find_user_by_id(&self, user_id: Uuid) -> Result<Option<AuthUser>, AuthError> {
+        let res = models::user::Entity::find_by_id(user_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.map(auth_user))
+    }
+
+    async fn ensure_tenant(&self, tenant_id: Uuid) -> Result<(), AuthError> {
+        let existing = models::tenant::Entity::find_by_id(tenant_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        if existing.is_none() {
+            // user.tenant_id 有外键约束；首次注册时以生成的名称创建租户
+            models::tenant::ActiveModel {
+                id: Set(tenant_id),
+                name: Set(format!("auto-tenant-{}", tenant_id)),
+                created_at: Set(chrono::Utc::now().into()),
+                suspended: Set(false),
+                suspended_reason: Set(None),
+                email_verification: Set(models::tenant::EMAIL_VERIFICATION_OFF.into()),
+                invite_only: Set(false),
+            }
+            .insert(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str, status: &str, role: &str) -> Result<AuthUser, AuthError> {
+        let created = models::user::create_with_status(&self.db, tenant_id, email, name, status, role)
             .await
             .map_err(|e| AuthError::Validation(e.to_string()))?;
-        Ok(AuthUser { id: created.id, tenant_id: created.tenant_id, email: created.email, name: created.name })
+        Ok(auth_user(created))
+    }
+
+    async fn set_user_status(&self, user_id: Uuid, status: &str) -> Result<(), AuthError> {
+        models::user::set_status(&self.db, user_id, status)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn email_verification_policy(&self, tenant_id: Uuid) -> Result<EmailVerificationPolicy, AuthError> {
+        let tenant = models::tenant::Entity::find_by_id(tenant_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(tenant.map(|t| EmailVerificationPolicy::parse(&t.email_verification)).unwrap_or_default())
+    }
+
+    async fn tenant_invite_only(&self, tenant_id: Uuid) -> Result<Option<bool>, AuthError> {
+        let tenant = models::tenant::Entity::find_by_id(tenant_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(tenant.map(|t| t.invite_only))
     }
 
     async fn get_credentials(&self, user_id: Uuid) -> Result<Option<Credentials>, AuthError> {
@@ -43,4 +131,122 @@ impl AuthRepository for SeaOrmAuthRepository {
             .map_err(|e| AuthError::Repository(e.to_string()))?;
         Ok(Credentials { user_id: c.user_id, password_hash: c.password_hash, password_algorithm: c.password_algorithm })
     }
-}
\ No newline at end of file
+
+    async fn create_session(&self, session: &SessionRecord) -> Result<(), AuthError> {
+        models::auth_session::ActiveModel {
+            id: Set(session.id),
+            user_id: Set(session.user_id),
+            tenant_id: Set(session.tenant_id),
+            created_at: Set(session.created_at.into()),
+            expires_at: Set(session.expires_at.into()),
+            revoked_at: Set(session.revoked_at.map(Into::into)),
+            user_agent: Set(session.client.user_agent.clone()),
+            ip_address: Set(session.client.ip_address.clone()),
+            last_seen_at: Set(session.last_seen_at.map(Into::into)),
+        }
+        .insert(&self.db)
+        .await
+        .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<SessionRecord>, AuthError> {
+        let res = models::auth_session::Entity::find_by_id(session_id)
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.map(session_record))
+    }
+
+    async fn active_sessions(&self, user_id: Uuid, now: DateTime<Utc>) -> Result<Vec<SessionRecord>, AuthError> {
+        let res = models::auth_session::Entity::find()
+            .filter(models::auth_session::Column::UserId.eq(user_id))
+            .filter(models::auth_session::Column::RevokedAt.is_null())
+            .filter(models::auth_session::Column::ExpiresAt.gt(now))
+            .order_by_asc(models::auth_session::Column::CreatedAt)
+            .all(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.into_iter().map(session_record).collect())
+    }
+
+    async fn revoke_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError> {
+        let res = models::auth_session::Entity::update_many()
+            .col_expr(models::auth_session::Column::RevokedAt, Expr::value(DateTimeWithTimeZone::from(now)))
+            .filter(models::auth_session::Column::Id.eq(session_id))
+            .filter(models::auth_session::Column::RevokedAt.is_null())
+            .exec(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.rows_affected > 0)
+    }
+
+    async fn touch_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<(), AuthError> {
+        models::auth_session::Entity::update_many()
+            .col_expr(models::auth_session::Column::LastSeenAt, Expr::value(DateTimeWithTimeZone::from(now)))
+            .filter(models::auth_session::Column::Id.eq(session_id))
+            .exec(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn create_invitation(&self, invitation: &InvitationRecord) -> Result<(), AuthError> {
+        models::invitation::ActiveModel {
+            id: Set(invitation.id),
+            tenant_id: Set(invitation.tenant_id),
+            email: Set(invitation.email.clone()),
+            role: Set(invitation.role.clone()),
+            token_hash: Set(invitation.token_hash.clone()),
+            created_at: Set(invitation.created_at.into()),
+            expires_at: Set(invitation.expires_at.into()),
+            accepted_at: Set(invitation.accepted_at.map(Into::into)),
+            accepted_user_id: Set(invitation.accepted_user_id),
+        }
+        .insert(&self.db)
+        .await
+        .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn find_invitation_by_token_hash(&self, token_hash: &str) -> Result<Option<InvitationRecord>, AuthError> {
+        let res = models::invitation::Entity::find()
+            .filter(models::invitation::Column::TokenHash.eq(token_hash.to_string()))
+            .one(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.map(invitation_record))
+    }
+
+    async fn list_invitations(&self, tenant_id: Uuid) -> Result<Vec<InvitationRecord>, AuthError> {
+        let res = models::invitation::Entity::find()
+            .filter(models::invitation::Column::TenantId.eq(tenant_id))
+            .order_by_desc(models::invitation::Column::CreatedAt)
+            .all(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.into_iter().map(invitation_record).collect())
+    }
+
+    async fn mark_invitation_accepted(&self, invitation_id: Uuid, user_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError> {
+        let res = models::invitation::Entity::update_many()
+            .col_expr(models::invitation::Column::AcceptedAt, Expr::value(DateTimeWithTimeZone::from(now)))
+            .col_expr(models::invitation::Column::AcceptedUserId, Expr::value(user_id))
+            .filter(models::invitation::Column::Id.eq(invitation_id))
+            .filter(models::invitation::Column::AcceptedAt.is_null())
+            .exec(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.rows_affected > 0)
+    }
+
+    async fn delete_invitation(&self, tenant_id: Uuid, invitation_id: Uuid) -> Result<bool, AuthError> {
+        let res = models::invitation::Entity::delete_many()
+            .filter(models::invitation::Column::Id.eq(invitation_id))
+            .filter(models::invitation::Column::TenantId.eq(tenant_id))
+            .exec(&self.db)
+            .await
+            .map_err(|e| AuthError::Repository(e.to_string()))?;
+        Ok(res.rows_affected > 0)
+    }
+}
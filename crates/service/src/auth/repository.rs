@@ -1,17 +1,44 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use super::domain::{AuthUser, Credentials};
+use super::domain::{AuthUser, Credentials, EmailVerificationPolicy, InvitationRecord, SessionRecord};
 use super::errors::AuthError;
 
 /// Repository abstraction for auth-related persistence.
 #[async_trait]
 pub trait AuthRepository: Send + Sync {
     async fn find_user_by_tenant_email(&self, tenant_id: Uuid, email: &str) -> Result<Option<AuthUser>, AuthError>;
-    async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str) -> Result<AuthUser, AuthError>;
+    async fn find_user_by_id(&self, user_id: Uuid) -> Result<Option<AuthUser>, AuthError>;
+    /// Create the tenant on first registration if it does not exist yet
+    async fn ensure_tenant(&self, tenant_id: Uuid) -> Result<(), AuthError>;
+    /// `status` is one of `models::user::STATUS_*`, `role` one of `models::user::ROLE_*`
+    async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str, status: &str, role: &str) -> Result<AuthUser, AuthError>;
+    async fn set_user_status(&self, user_id: Uuid, status: &str) -> Result<(), AuthError>;
+    /// Tenants that do not exist yet (first registration) use the default policy
+    async fn email_verification_policy(&self, tenant_id: Uuid) -> Result<EmailVerificationPolicy, AuthError>;
+    /// `tenant.invite_only`; None when the tenant does not exist
+    async fn tenant_invite_only(&self, tenant_id: Uuid) -> Result<Option<bool>, AuthError>;
 
     async fn get_credentials(&self, user_id: Uuid) -> Result<Option<Credentials>, AuthError>;
     async fn upsert_password(&self, user_id: Uuid, password_hash: String, password_algorithm: String) -> Result<Credentials, AuthError>;
+
+    async fn create_session(&self, session: &SessionRecord) -> Result<(), AuthError>;
+    async fn find_session(&self, session_id: Uuid) -> Result<Option<SessionRecord>, AuthError>;
+    /// Sessions of the user neither revoked nor expired at `now`, oldest first
+    async fn active_sessions(&self, user_id: Uuid, now: DateTime<Utc>) -> Result<Vec<SessionRecord>, AuthError>;
+    /// Returns false when the session does not exist or was already revoked
+    async fn revoke_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError>;
+    async fn touch_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<(), AuthError>;
+
+    async fn create_invitation(&self, invitation: &InvitationRecord) -> Result<(), AuthError>;
+    async fn find_invitation_by_token_hash(&self, token_hash: &str) -> Result<Option<InvitationRecord>, AuthError>;
+    /// Newest first
+    async fn list_invitations(&self, tenant_id: Uuid) -> Result<Vec<InvitationRecord>, AuthError>;
+    /// Returns false when the invitation was already accepted (concurrent acceptance)
+    async fn mark_invitation_accepted(&self, invitation_id: Uuid, user_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError>;
+    /// Returns false when the invitation does not exist in `tenant_id`
+    async fn delete_invitation(&self, tenant_id: Uuid, invitation_id: Uuid) -> Result<bool, AuthError>;
 }
 
 /// Simple in-memory mock repository for tests and doc examples
@@ -24,6 +51,20 @@ pub mod mock {
     pub struct MockAuthRepository {
         users: Mutex<HashMap<(Uuid, String), AuthUser>>, // key: (tenant_id, email)
         creds: Mutex<HashMap<Uuid, Credentials>>,        // key: user_id
+        sessions: Mutex<Vec<SessionRecord>>,
+        policies: Mutex<HashMap<Uuid, EmailVerificationPolicy>>,
+        invite_only: Mutex<HashMap<Uuid, bool>>,
+        invitations: Mutex<Vec<InvitationRecord>>,
+    }
+
+    impl MockAuthRepository {
+        pub fn set_email_verification_policy(&self, tenant_id: Uuid, policy: EmailVerificationPolicy) {
+            self.policies.lock().unwrap().insert(tenant_id, policy);
+        }
+
+        pub fn set_invite_only(&self, tenant_id: Uuid, invite_only: bool) {
+            self.invite_only.lock().unwrap().insert(tenant_id, invite_only);
+        }
     }
 
     #[async_trait]
@@ -33,16 +74,41 @@ pub mod mock {
             Ok(users.get(&(tenant_id, email.to_string())).cloned())
         }
 
-        async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str) -> Result<AuthUser, AuthError> {
+        async fn find_user_by_id(&self, user_id: Uuid) -> Result<Option<AuthUser>, AuthError> {
+            let users = self.users.lock().unwrap();
+            Ok(users.values().find(|u| u.id == user_id).cloned())
+        }
+
+        async fn ensure_tenant(&self, _tenant_id: Uuid) -> Result<(), AuthError> {
+            Ok(())
+        }
+
+        async fn create_user(&self, tenant_id: Uuid, email: &str, name: &str, status: &str, role: &str) -> Result<AuthUser, AuthError> {
             let mut users = self.users.lock().unwrap();
             if users.contains_key(&(tenant_id, email.to_string())) {
                 return Err(AuthError::Conflict);
             }
-            let user = AuthUser { id: Uuid::new_v4(), tenant_id, email: email.to_string(), name: name.to_string() };
+            let user = AuthUser { id: Uuid::new_v4(), tenant_id, email: email.to_string(), name: name.to_string(), status: status.to_string(), role: role.to_string() };
             users.insert((tenant_id, email.to_string()), user.clone());
             Ok(user)
         }
 
+        async fn set_user_status(&self, user_id: Uuid, status: &str) -> Result<(), AuthError> {
+            let mut users = self.users.lock().unwrap();
+            let user = users.values_mut().find(|u| u.id == user_id).ok_or(AuthError::NotFound)?;
+            user.status = status.to_string();
+            Ok(())
+        }
+
+        async fn email_verification_policy(&self, tenant_id: Uuid) -> Result<EmailVerificationPolicy, AuthError> {
+            Ok(self.policies.lock().unwrap().get(&tenant_id).copied().unwrap_or_default())
+        }
+
+        // Every tenant exists in the mock
+        async fn tenant_invite_only(&self, tenant_id: Uuid) -> Result<Option<bool>, AuthError> {
+            Ok(Some(self.invite_only.lock().unwrap().get(&tenant_id).copied().unwrap_or(false)))
+        }
+
         async fn get_credentials(&self, user_id: Uuid) -> Result<Option<Credentials>, AuthError> {
             let creds = self.creds.lock().unwrap();
             Ok(creds.get(&user_id).cloned())
@@ -54,5 +120,72 @@ pub mod mock {
             creds.insert(user_id, c.clone());
             Ok(c)
         }
+
+        async fn create_session(&self, session: &SessionRecord) -> Result<(), AuthError> {
+            self.sessions.lock().unwrap().push(session.clone());
+            Ok(())
+        }
+
+        async fn find_session(&self, session_id: Uuid) -> Result<Option<SessionRecord>, AuthError> {
+            Ok(self.sessions.lock().unwrap().iter().find(|s| s.id == session_id).cloned())
+        }
+
+        async fn active_sessions(&self, user_id: Uuid, now: DateTime<Utc>) -> Result<Vec<SessionRecord>, AuthError> {
+            let mut active: Vec<_> = self.sessions.lock().unwrap().iter().filter(|s| s.user_id == user_id && s.is_active(now)).cloned().collect();
+            active.sort_by_key(|s| s.created_at);
+            Ok(active)
+        }
+
+        async fn revoke_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError> {
+            let mut sessions = self.sessions.lock().unwrap();
+            match sessions.iter_mut().find(|s| s.id == session_id && s.revoked_at.is_none()) {
+                Some(s) => {
+                    s.revoked_at = Some(now);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        async fn touch_session(&self, session_id: Uuid, now: DateTime<Utc>) -> Result<(), AuthError> {
+            if let Some(s) = self.sessions.lock().unwrap().iter_mut().find(|s| s.id == session_id) {
+                s.last_seen_at = Some(now);
+            }
+            Ok(())
+        }
+
+        async fn create_invitation(&self, invitation: &InvitationRecord) -> Result<(), AuthError> {
+            self.invitations.lock().unwrap().push(invitation.clone());
+            Ok(())
+        }
+
+        async fn find_invitation_by_token_hash(&self, token_hash: &str) -> Result<Option<InvitationRecord>, AuthError> {
+            Ok(self.invitations.lock().unwrap().iter().find(|i| i.token_hash == token_hash).cloned())
+        }
+
+        async fn list_invitations(&self, tenant_id: Uuid) -> Result<Vec<InvitationRecord>, AuthError> {
+            let mut list: Vec<_> = self.invitations.lock().unwrap().iter().filter(|i| i.tenant_id == tenant_id).cloned().collect();
+            list.sort_by_key(|i| std::cmp::Reverse(i.created_at));
+            Ok(list)
+        }
+
+        async fn mark_invitation_accepted(&self, invitation_id: Uuid, user_id: Uuid, now: DateTime<Utc>) -> Result<bool, AuthError> {
+            let mut invitations = self.invitations.lock().unwrap();
+            match invitations.iter_mut().find(|i| i.id == invitation_id && i.accepted_at.is_none()) {
+                Some(i) => {
+                    i.accepted_at = Some(now);
+                    i.accepted_user_id = Some(user_id);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+
+        async fn delete_invitation(&self, tenant_id: Uuid, invitation_id: Uuid) -> Result<bool, AuthError> {
+            let mut invitations = self.invitations.lock().unwrap();
+            let before = invitations.len();
+            invitations.retain(|i| !(i.id == invitation_id && i.tenant_id == tenant_id));
+            Ok(invitations.len() < before)
+        }
     }
 }
\ No newline at end of file
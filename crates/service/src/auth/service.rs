@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
 use argon2::{Argon2, password_hash::{PasswordHasher, PasswordVerifier, SaltString}, PasswordHash};
-use jsonwebtoken::{encode, Header as JwtHeader, EncodingKey};
-use rand::rngs::OsRng;
-use tracing::{info, debug, instrument};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, Header as JwtHeader, EncodingKey, Validation};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use common::time::{system_clock, SharedClock};
+use tracing::{info, debug, instrument, warn};
+use uuid::Uuid;
 
-use super::domain::{RegisterInput, LoginInput, AuthUser, AuthSession};
+use super::domain::{RegisterInput, LoginInput, AuthUser, AuthSession, AcceptInviteInput, EmailVerificationPolicy, InvitationRecord, InviteInput, SessionClient, SessionRecord, TokenClaims, VerificationClaims};
 use super::errors::AuthError;
+use super::keys::KeyRing;
+use super::notify::VerificationNotifier;
 use super::repository::AuthRepository;
 
 /// Auth service configuration
@@ -16,14 +21,108 @@ pub struct AuthConfig {
     pub password_algorithm: String,
 }
 
+/// Lifetime and `iss`/`aud` claims of issued tokens; when set, verification requires the same values.
+#[derive(Debug, Clone)]
+pub struct TokenPolicy {
+    pub ttl: std::time::Duration,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+impl Default for TokenPolicy {
+    fn default() -> Self {
+        Self { ttl: std::time::Duration::from_secs(12 * 3600), issuer: None, audience: None }
+    }
+}
+
+/// `purpose` claim of email verification tokens; also salts their signing key
+const VERIFY_EMAIL_PURPOSE: &str = "verify_email";
+
+/// Requests within this long after `last_seen_at` do not write it again
+const LAST_SEEN_RESOLUTION: chrono::Duration = chrono::Duration::seconds(60);
+
+/// What login does when the user already has `max_sessions` active sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionLimitPolicy {
+    /// Fail the login with [`AuthError::SessionLimit`]
+    Reject,
+    /// Revoke the oldest sessions to make room
+    #[default]
+    RevokeOldest,
+}
+
+/// Cap on concurrently active sessions per user; `max_sessions == 0` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionLimit {
+    pub max_sessions: u32,
+    pub policy: SessionLimitPolicy,
+}
+
 /// Auth business service independent of web framework
 pub struct AuthService<R: AuthRepository> {
     repo: Arc<R>,
     cfg: AuthConfig,
+    clock: SharedClock,
+    policy: TokenPolicy,
+    keyring: Option<Arc<KeyRing>>,
+    session_limit: SessionLimit,
+    notifier: Option<Arc<dyn VerificationNotifier>>,
+    verification_ttl: chrono::Duration,
+    invitation_ttl: chrono::Duration,
 }
 
 impl<R: AuthRepository> AuthService<R> {
-    pub fn new(repo: Arc<R>, cfg: AuthConfig) -> Self { Self { repo, cfg } }
+    pub fn new(repo: Arc<R>, cfg: AuthConfig) -> Self {
+        Self {
+            repo,
+            cfg,
+            clock: system_clock(),
+            policy: TokenPolicy::default(),
+            keyring: None,
+            session_limit: SessionLimit::default(),
+            notifier: None,
+            verification_ttl: chrono::Duration::hours(24),
+            invitation_ttl: chrono::Duration::days(7),
+        }
+    }
+
+    /// Override the time source (token expiry is computed from it).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Override token lifetime and issuer/audience claims.
+    pub fn with_token_policy(mut self, policy: TokenPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sign with the ring's active key (`kid` in the header) and verify by `kid`;
+    /// `jwt_secret` remains the signer when the ring has no active key and the verifier for tokens without `kid`.
+    pub fn with_keyring(mut self, keyring: Arc<KeyRing>) -> Self {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    /// Limit concurrently active sessions per user.
+    pub fn with_session_limit(mut self, limit: SessionLimit) -> Self {
+        self.session_limit = limit;
+        self
+    }
+
+    /// Deliver email verification tokens (valid for `ttl`) for tenants with email verification enabled.
+    pub fn with_verification(mut self, notifier: Arc<dyn VerificationNotifier>, ttl: std::time::Duration) -> Self {
+        self.notifier = Some(notifier);
+        self.verification_ttl = chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::hours(24));
+        self
+    }
+
+    /// Override how long invitations stay valid (default 7 days).
+    pub fn with_invitation_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.invitation_ttl = chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::days(7));
+        self
+    }
 
     /// Register a new user with a hashed password.
     ///
@@ -40,26 +139,190 @@ impl<R: AuthRepository> AuthService<R> {
     /// ```
     #[instrument(skip(self, input), fields(email = %input.email, tenant_id = %input.tenant_id))]
     pub async fn register(&self, input: RegisterInput) -> Result<AuthUser, AuthError> {
-        if input.password.len() < 8 {
-            return Err(AuthError::Validation("password too short (>=8)".into()));
+        models::user::validate_email(&input.email).map_err(|e| AuthError::Validation(e.to_string()))?;
+        models::user::validate_name(&input.name).map_err(|e| AuthError::Validation(e.to_string()))?;
+        validate_password(&input.password)?;
+        if self.repo.tenant_invite_only(input.tenant_id).await? == Some(true) {
+            info!("registration rejected: tenant is invite-only");
+            return Err(AuthError::InviteRequired);
         }
         if let Some(existing) = self.repo.find_user_by_tenant_email(input.tenant_id, &input.email).await? {
             debug!("user exists: {}", existing.email);
             return Err(AuthError::Conflict);
         }
 
-        let user = self.repo.create_user(input.tenant_id, &input.email, &input.name).await?;
-        let salt = SaltString::generate(&mut OsRng);
-        let hash = Argon2::default()
-            .hash_password(input.password.as_bytes(), &salt)
-            .map_err(|e| AuthError::HashError(e.to_string()))?
-            .to_string();
+        self.repo.ensure_tenant(input.tenant_id).await?;
+        let status = match self.repo.email_verification_policy(input.tenant_id).await? {
+            EmailVerificationPolicy::Off => models::user::STATUS_ACTIVE,
+            EmailVerificationPolicy::Optional | EmailVerificationPolicy::Required => models::user::STATUS_UNVERIFIED,
+        };
+        let user = self.repo.create_user(input.tenant_id, &input.email, &input.name, status, models::user::ROLE_MEMBER).await?;
+        let _cred = self.repo.upsert_password(user.id, hash_password(&input.password)?, self.cfg.password_algorithm.clone()).await?;
+        info!(user_id = %user.id, tenant_id = %user.tenant_id, email = %user.email, status = %user.status, "user_registered");
+        if !user.is_verified() {
+            // 投递失败不影响注册，用户可通过 resend_verification 重新获取
+            if let Err(e) = self.send_verification(&user).await {
+                warn!(user_id = %user.id, err = %e, "failed to send verification token");
+            }
+        }
+        Ok(user)
+    }
+
+    async fn send_verification(&self, user: &AuthUser) -> Result<(), AuthError> {
+        let Some(notifier) = &self.notifier else {
+            warn!(user_id = %user.id, tenant_id = %user.tenant_id, "email verification enabled for tenant but no notifier configured");
+            return Ok(());
+        };
+        let (token, expires_at) = self.issue_verification_token(user)?;
+        notifier.send(user, &token, expires_at).await
+    }
+
+    fn verification_key(&self) -> Result<Vec<u8>, AuthError> {
+        let secret = self.cfg.jwt_secret.as_ref().ok_or_else(|| AuthError::TokenError("jwt secret not configured".into()))?;
+        Ok(format!("{}:{}", secret, VERIFY_EMAIL_PURPOSE).into_bytes())
+    }
+
+    /// Signed token proving control of `user.email`; see [`VerificationClaims`].
+    pub fn issue_verification_token(&self, user: &AuthUser) -> Result<(String, chrono::DateTime<chrono::Utc>), AuthError> {
+        let expires_at = self.clock.now() + self.verification_ttl;
+        let claims = VerificationClaims {
+            uid: user.id,
+            tid: user.tenant_id,
+            email: user.email.clone(),
+            purpose: VERIFY_EMAIL_PURPOSE.to_string(),
+            exp: expires_at.timestamp() as usize,
+        };
+        let token = encode(&JwtHeader::default(), &claims, &EncodingKey::from_secret(&self.verification_key()?))
+            .map_err(|e| AuthError::TokenError(e.to_string()))?;
+        Ok((token, expires_at))
+    }
 
-        let _cred = self.repo.upsert_password(user.id, hash, self.cfg.password_algorithm.clone()).await?;
-        info!(user_id = %user.id, tenant_id = %user.tenant_id, email = %user.email, "user_registered");
+    /// Activate the user a verification token was issued to; verifying twice is not an error.
+    /// Tokens issued before the user's email changed are rejected.
+    pub async fn verify_email(&self, token: &str) -> Result<AuthUser, AuthError> {
+        let invalid = || AuthError::Validation("invalid or expired verification token".into());
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.validate_aud = false;
+        let claims = decode::<VerificationClaims>(token, &DecodingKey::from_secret(&self.verification_key()?), &validation)
+            .map_err(|e| {
+                debug!(err = %e, "verification token rejected");
+                invalid()
+            })?
+            .claims;
+        if claims.purpose != VERIFY_EMAIL_PURPOSE {
+            return Err(invalid());
+        }
+        let mut user = self.repo
+            .find_user_by_id(claims.uid)
+            .await?
+            .filter(|u| u.tenant_id == claims.tid && u.email == claims.email)
+            .ok_or_else(invalid)?;
+        if !user.is_verified() {
+            self.repo.set_user_status(user.id, models::user::STATUS_ACTIVE).await?;
+            user.status = models::user::STATUS_ACTIVE.to_string();
+            info!(user_id = %user.id, tenant_id = %user.tenant_id, "email_verified");
+        }
+        Ok(user)
+    }
+
+    /// Send a new verification token to an unverified user; unknown or already verified users are ignored
+    /// so the endpoint does not reveal which emails are registered.
+    pub async fn resend_verification(&self, tenant_id: Uuid, email: &str) -> Result<(), AuthError> {
+        match self.repo.find_user_by_tenant_email(tenant_id, email).await? {
+            Some(user) if !user.is_verified() => self.send_verification(&user).await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Invite `input.email` into an existing tenant with `input.role`.
+    /// Returns the stored invitation and its token; only the token's hash is kept, so it cannot be shown again.
+    #[instrument(skip(self, input), fields(email = %input.email, tenant_id = %input.tenant_id))]
+    pub async fn invite(&self, input: InviteInput) -> Result<(InvitationRecord, String), AuthError> {
+        models::user::validate_email(&input.email).map_err(|e| AuthError::Validation(e.to_string()))?;
+        models::user::validate_role(&input.role).map_err(|e| AuthError::Validation(e.to_string()))?;
+        if self.repo.tenant_invite_only(input.tenant_id).await?.is_none() {
+            return Err(AuthError::NotFound);
+        }
+        if self.repo.find_user_by_tenant_email(input.tenant_id, &input.email).await?.is_some() {
+            return Err(AuthError::Conflict);
+        }
+        let mut raw = [0u8; 32];
+        OsRng.fill_bytes(&mut raw);
+        let token = hex::encode(raw);
+        let now = self.clock.now();
+        let invitation = InvitationRecord {
+            id: Uuid::new_v4(),
+            tenant_id: input.tenant_id,
+            email: input.email,
+            role: input.role,
+            token_hash: invite_token_hash(&token),
+            created_at: now,
+            expires_at: now + self.invitation_ttl,
+            accepted_at: None,
+            accepted_user_id: None,
+        };
+        self.repo.create_invitation(&invitation).await?;
+        info!(invitation_id = %invitation.id, role = %invitation.role, "user_invited");
+        Ok((invitation, token))
+    }
+
+    /// Complete registration from an invitation: the user is created active in the invitation's tenant
+    /// with its role (the invitation reached the email, so no separate verification), regardless of `invite_only`.
+    #[instrument(skip(self, input))]
+    pub async fn accept_invite(&self, input: AcceptInviteInput) -> Result<AuthUser, AuthError> {
+        models::user::validate_name(&input.name).map_err(|e| AuthError::Validation(e.to_string()))?;
+        validate_password(&input.password)?;
+        let now = self.clock.now();
+        let invitation = self.repo
+            .find_invitation_by_token_hash(&invite_token_hash(&input.token))
+            .await?
+            .filter(|i| i.is_pending(now))
+            .ok_or_else(|| AuthError::Validation("invalid or expired invitation".into()))?;
+        if self.repo.find_user_by_tenant_email(invitation.tenant_id, &invitation.email).await?.is_some() {
+            return Err(AuthError::Conflict);
+        }
+        let user = self.repo
+            .create_user(invitation.tenant_id, &invitation.email, &input.name, models::user::STATUS_ACTIVE, &invitation.role)
+            .await?;
+        self.repo.upsert_password(user.id, hash_password(&input.password)?, self.cfg.password_algorithm.clone()).await?;
+        if !self.repo.mark_invitation_accepted(invitation.id, user.id, now).await? {
+            warn!(invitation_id = %invitation.id, "invitation accepted concurrently");
+        }
+        info!(user_id = %user.id, tenant_id = %user.tenant_id, invitation_id = %invitation.id, role = %user.role, "invitation_accepted");
         Ok(user)
     }
 
+    /// Invitations of a tenant, newest first (pending, accepted and expired).
+    pub async fn invitations(&self, tenant_id: Uuid) -> Result<Vec<InvitationRecord>, AuthError> {
+        self.repo.list_invitations(tenant_id).await
+    }
+
+    /// Withdraw an invitation of `tenant_id`; its token stops working immediately.
+    pub async fn revoke_invitation(&self, tenant_id: Uuid, invitation_id: Uuid) -> Result<(), AuthError> {
+        if !self.repo.delete_invitation(tenant_id, invitation_id).await? {
+            return Err(AuthError::NotFound);
+        }
+        info!(invitation_id = %invitation_id, "invitation_revoked");
+        Ok(())
+    }
+
+    /// The caller behind `claims` if they hold the admin role; the role is read from the user record,
+    /// so demotions apply to tokens already issued.
+    pub async fn require_admin(&self, claims: &TokenClaims) -> Result<AuthUser, AuthError> {
+        let uid = claims.uid.ok_or(AuthError::Unauthorized)?;
+        let user = self.repo.find_user_by_id(uid).await?.ok_or(AuthError::Unauthorized)?;
+        if user.role != models::user::ROLE_ADMIN {
+            info!(user_id = %user.id, role = %user.role, "admin action rejected: not an admin");
+            return Err(AuthError::Forbidden);
+        }
+        Ok(user)
+    }
+
+    /// A user of `tenant_id`; users of other tenants are reported as not found.
+    pub async fn tenant_user(&self, tenant_id: Uuid, user_id: Uuid) -> Result<AuthUser, AuthError> {
+        self.repo.find_user_by_id(user_id).await?.filter(|u| u.tenant_id == tenant_id).ok_or(AuthError::NotFound)
+    }
+
     /// Authenticate a user and optionally issue a token.
     ///
     /// # Examples
@@ -75,8 +338,13 @@ impl<R: AuthRepository> AuthService<R> {
     /// assert_eq!(session.user.email, "u@e.com");
     /// assert!(session.token.is_some());
     /// ```
-    #[instrument(skip(self, input), fields(email = %input.email, tenant_id = %input.tenant_id))]
     pub async fn login(&self, input: LoginInput) -> Result<AuthSession, AuthError> {
+        self.login_from(input, SessionClient::default()).await
+    }
+
+    /// [`Self::login`], recording the client (user agent, IP) on the issued session.
+    #[instrument(skip(self, input, client), fields(email = %input.email, tenant_id = %input.tenant_id))]
+    pub async fn login_from(&self, input: LoginInput, client: SessionClient) -> Result<AuthSession, AuthError> {
         let user = self.repo
             .find_user_by_tenant_email(input.tenant_id, &input.email)
             .await?
@@ -91,16 +359,443 @@ impl<R: AuthRepository> AuthService<R> {
         if Argon2::default().verify_password(input.password.as_bytes(), &parsed).is_err() {
             return Err(AuthError::Unauthorized);
         }
+        if !user.is_verified() && self.repo.email_verification_policy(user.tenant_id).await? == EmailVerificationPolicy::Required {
+            info!(user_id = %user.id, "login rejected: email not verified");
+            return Err(AuthError::EmailNotVerified);
+        }
 
         let mut token = None;
-        if let Some(secret) = &self.cfg.jwt_secret {
-            #[derive(serde::Serialize)]
-            struct Claims { sub: String, uid: String, tid: String, exp: usize }
-            let exp = (chrono::Utc::now() + chrono::Duration::hours(12)).timestamp() as usize;
-            let claims = Claims { sub: user.email.clone(), uid: user.id.to_string(), tid: user.tenant_id.to_string(), exp };
-            token = Some(encode(&JwtHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).map_err(|e| AuthError::TokenError(e.to_string()))?);
+        let mut session_id = None;
+        let active = self.keyring.as_ref().and_then(|r| r.active());
+        if active.is_some() || self.cfg.jwt_secret.is_some() {
+            let now = self.clock.now();
+            let ttl = chrono::Duration::from_std(self.policy.ttl).unwrap_or_else(|_| chrono::Duration::hours(12));
+            let session = self.open_session(&user, client, now, now + ttl).await?;
+            let claims = TokenClaims {
+                sub: user.email.clone(),
+                uid: Some(user.id),
+                tid: Some(user.tenant_id),
+                exp: session.expires_at.timestamp() as usize,
+                iss: self.policy.issuer.clone(),
+                aud: self.policy.audience.clone(),
+                sid: Some(session.id),
+            };
+            session_id = Some(session.id);
+            let encoded = match (active, &self.cfg.jwt_secret) {
+                (Some(key), _) => {
+                    let mut header = JwtHeader::new(key.algorithm.jwt());
+                    header.kid = Some(key.kid.clone());
+                    encode(&header, &claims, &key.encoding)
+                }
+                (None, Some(secret)) => encode(&JwtHeader::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())),
+                (None, None) => unreachable!("checked above"),
+            };
+            token = Some(encoded.map_err(|e| AuthError::TokenError(e.to_string()))?);
         }
 
-        Ok(AuthSession { user, token })
+        Ok(AuthSession { user, token, session_id })
     }
-}
\ No newline at end of file
+
+    /// Record a new session for `user`, first applying the per-user session limit.
+    async fn open_session(&self, user: &AuthUser, client: SessionClient, now: chrono::DateTime<chrono::Utc>, expires_at: chrono::DateTime<chrono::Utc>) -> Result<SessionRecord, AuthError> {
+        let max = self.session_limit.max_sessions as usize;
+        if max > 0 {
+            let active = self.repo.active_sessions(user.id, now).await?;
+            if active.len() >= max {
+                if self.session_limit.policy == SessionLimitPolicy::Reject {
+                    info!(user_id = %user.id, active = active.len(), "login rejected: session limit reached");
+                    return Err(AuthError::SessionLimit);
+                }
+                for evicted in &active[..=active.len() - max] {
+                    self.repo.revoke_session(evicted.id, now).await?;
+                    info!(user_id = %user.id, session_id = %evicted.id, "session revoked: session limit reached");
+                }
+            }
+        }
+        let session = SessionRecord { id: Uuid::new_v4(), user_id: user.id, tenant_id: user.tenant_id, created_at: now, expires_at, revoked_at: None, client, last_seen_at: None };
+        self.repo.create_session(&session).await?;
+        Ok(session)
+    }
+
+    /// Verify a token's signature (key chosen by `kid`), expiry and (when configured) issuer/audience.
+    pub fn verify_token(&self, token: &str) -> Result<TokenClaims, AuthError> {
+        let kid = decode_header(token).map_err(|e| {
+            debug!(err = %e, "token rejected");
+            AuthError::Unauthorized
+        })?.kid;
+        let legacy;
+        let (algorithm, key) = match (kid, &self.keyring) {
+            (Some(kid), Some(ring)) => ring.verifier(&kid, self.clock.now()).ok_or_else(|| {
+                debug!(kid = %kid, "token signed with unknown or retired key");
+                AuthError::Unauthorized
+            })?,
+            (Some(_), None) => return Err(AuthError::Unauthorized),
+            (None, Some(ring)) if !ring.accepts_legacy(self.clock.now()) => {
+                debug!("token without kid rejected: legacy grace period over");
+                return Err(AuthError::Unauthorized);
+            }
+            (None, _) => {
+                let secret = self.cfg.jwt_secret.as_ref().ok_or_else(|| AuthError::TokenError("jwt secret not configured".into()))?;
+                legacy = DecodingKey::from_secret(secret.as_bytes());
+                (Algorithm::HS256, &legacy)
+            }
+        };
+        let mut validation = Validation::new(algorithm);
+        validation.validate_exp = true;
+        let mut required = vec!["exp"];
+        if let Some(iss) = &self.policy.issuer {
+            validation.set_issuer(&[iss]);
+            required.push("iss");
+        }
+        match &self.policy.audience {
+            Some(aud) => {
+                validation.set_audience(&[aud]);
+                required.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required);
+        decode::<TokenClaims>(token, key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                debug!(err = %e, "token rejected");
+                AuthError::Unauthorized
+            })
+    }
+
+    /// [`Self::verify_token`], and for tokens bound to a session (`sid`) also require the session to be active.
+    /// Refreshes the session's `last_seen_at` when it is older than a minute.
+    pub async fn authenticate(&self, token: &str) -> Result<TokenClaims, AuthError> {
+        let claims = self.verify_token(token)?;
+        if let Some(sid) = claims.sid {
+            let now = self.clock.now();
+            let session = match self.repo.find_session(sid).await? {
+                Some(s) if s.is_active(now) => s,
+                _ => {
+                    debug!(session_id = %sid, "token rejected: session revoked or expired");
+                    return Err(AuthError::Unauthorized);
+                }
+            };
+            if session.last_seen_at.map_or(true, |t| now - t >= LAST_SEEN_RESOLUTION) {
+                self.repo.touch_session(sid, now).await?;
+            }
+        }
+        Ok(claims)
+    }
+
+    /// Resolve the user a login token was issued to.
+    pub async fn current_user(&self, token: &str) -> Result<AuthUser, AuthError> {
+        let claims = self.authenticate(token).await?;
+        let uid = claims.uid.ok_or(AuthError::Unauthorized)?;
+        self.repo.find_user_by_id(uid).await?.ok_or(AuthError::Unauthorized)
+    }
+
+    /// Active sessions of a user, oldest first.
+    pub async fn sessions(&self, user_id: Uuid) -> Result<Vec<SessionRecord>, AuthError> {
+        self.repo.active_sessions(user_id, self.clock.now()).await
+    }
+
+    /// Revoke one of the user's sessions; other users' sessions are reported as not found.
+    pub async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<(), AuthError> {
+        match self.repo.find_session(session_id).await? {
+            Some(session) if session.user_id == user_id => {
+                self.repo.revoke_session(session_id, self.clock.now()).await?;
+                info!(user_id = %user_id, session_id = %session_id, "session revoked");
+                Ok(())
+            }
+            _ => Err(AuthError::NotFound),
+        }
+    }
+}
+
+pub(crate) fn validate_password(password: &str) -> Result<(), AuthError> {
+    if password.len() < 8 {
+        return Err(AuthError::Validation("password too short (>=8)".into()));
+    }
+    Ok(())
+}
+
+pub(crate) fn hash_password(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| AuthError::HashError(e.to_string()))?
+        .to_string())
+}
+
+/// Invite tokens are 256-bit random, so an unsalted hash is enough to look them up
+fn invite_token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::repository::mock::MockAuthRepository;
+    use chrono::TimeZone;
+    use common::time::{Clock, MockClock};
+    use jsonwebtoken::{decode, DecodingKey, Validation};
+
+    #[tokio::test]
+    async fn token_expiry_follows_injected_clock() {
+        let fixed = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() })
+            .with_clock(Arc::new(MockClock::new(fixed)));
+        let tid = uuid::Uuid::new_v4();
+        svc.register(RegisterInput { tenant_id: tid, email: "c@e.com".into(), name: "C".into(), password: "Passw0rd".into() }).await.unwrap();
+        let session = svc.login(LoginInput { tenant_id: tid, email: "c@e.com".into(), password: "Passw0rd".into() }).await.unwrap();
+
+        let mut validation = Validation::default();
+        validation.validate_exp = false;
+        let data = decode::<serde_json::Value>(&session.token.unwrap(), &DecodingKey::from_secret(b"secret"), &validation).unwrap();
+        assert_eq!(data.claims["exp"].as_i64(), Some((fixed + chrono::Duration::hours(12)).timestamp()));
+    }
+
+    #[tokio::test]
+    async fn issuer_and_audience_are_enforced() {
+        let repo = Arc::new(MockAuthRepository::default());
+        let cfg = AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() };
+        let policy = TokenPolicy { ttl: std::time::Duration::from_secs(300), issuer: Some("api-proxy".into()), audience: Some("admin".into()) };
+        let svc = AuthService::new(repo.clone(), cfg.clone()).with_token_policy(policy.clone());
+        let tid = uuid::Uuid::new_v4();
+        svc.register(RegisterInput { tenant_id: tid, email: "e@e.com".into(), name: "E".into(), password: "Passw0rd".into() }).await.unwrap();
+        let token = svc.login(LoginInput { tenant_id: tid, email: "e@e.com".into(), password: "Passw0rd".into() }).await.unwrap().token.unwrap();
+        let claims = svc.verify_token(&token).unwrap();
+        assert_eq!((claims.iss.as_deref(), claims.aud.as_deref()), (Some("api-proxy"), Some("admin")));
+
+        // 同一密钥、不同受众或未带 iss/aud 的令牌均被拒绝
+        let other = AuthService::new(repo.clone(), cfg.clone()).with_token_policy(TokenPolicy { audience: Some("portal".into()), ..policy });
+        assert!(matches!(other.verify_token(&token), Err(AuthError::Unauthorized)));
+        let plain = AuthService::new(repo, cfg).login(LoginInput { tenant_id: tid, email: "e@e.com".into(), password: "Passw0rd".into() }).await.unwrap().token.unwrap();
+        assert!(matches!(svc.verify_token(&plain), Err(AuthError::Unauthorized)));
+    }
+
+    #[tokio::test]
+    async fn rotated_keys_verify_until_their_grace_ends() {
+        use crate::auth::keys::{KeyAlgorithm, KeyMaterial};
+        // exp 按真实时间校验，时钟从当前时间起步
+        let now = chrono::Utc::now();
+        let clock = Arc::new(MockClock::new(now));
+        let repo = Arc::new(MockAuthRepository::default());
+        let cfg = AuthConfig { jwt_secret: Some("legacy".into()), password_algorithm: "argon2".into() };
+        let tid = uuid::Uuid::new_v4();
+        let login = LoginInput { tenant_id: tid, email: "f@e.com".into(), password: "Passw0rd".into() };
+        let plain = AuthService::new(repo.clone(), cfg.clone());
+        plain.register(RegisterInput { tenant_id: tid, email: "f@e.com".into(), name: "F".into(), password: "Passw0rd".into() }).await.unwrap();
+        let legacy_token = plain.login(login.clone()).await.unwrap().token.unwrap();
+
+        let old = KeyMaterial::generate(KeyAlgorithm::HS256).unwrap();
+        let mut ring = KeyRing::default();
+        ring.insert("k1", &old, true, None).unwrap();
+        let svc = AuthService::new(repo.clone(), cfg.clone()).with_clock(clock.clone()).with_keyring(Arc::new(ring));
+        let old_token = svc.login(login.clone()).await.unwrap().token.unwrap();
+        assert_eq!(jsonwebtoken::decode_header(&old_token).unwrap().kid.as_deref(), Some("k1"));
+
+        // 轮换到 EdDSA：k1 与无 kid 的旧令牌（走 jwt_secret）都只在宽限期内可校验
+        let new = KeyMaterial::generate(KeyAlgorithm::EdDSA).unwrap();
+        let mut ring = KeyRing::default();
+        ring.insert("k1", &old, false, Some(now + chrono::Duration::minutes(5))).unwrap();
+        ring.insert("k2", &new, true, None).unwrap();
+        ring.set_legacy_until(Some(now + chrono::Duration::minutes(5)));
+        let svc = AuthService::new(repo, cfg).with_clock(clock.clone()).with_keyring(Arc::new(ring));
+        let new_token = svc.login(login).await.unwrap().token.unwrap();
+        assert_eq!(jsonwebtoken::decode_header(&new_token).unwrap().alg, Algorithm::EdDSA);
+        assert!(svc.verify_token(&new_token).is_ok());
+        assert!(svc.verify_token(&old_token).is_ok());
+        assert!(svc.verify_token(&legacy_token).is_ok());
+        clock.advance(chrono::Duration::minutes(6));
+        assert!(matches!(svc.verify_token(&old_token), Err(AuthError::Unauthorized)));
+        assert!(matches!(svc.verify_token(&legacy_token), Err(AuthError::Unauthorized)));
+        assert!(svc.verify_token(&new_token).is_ok());
+    }
+
+    #[tokio::test]
+    async fn session_limit_revokes_oldest_or_rejects() {
+        let clock = Arc::new(MockClock::new(chrono::Utc::now()));
+        let repo = Arc::new(MockAuthRepository::default());
+        let cfg = AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() };
+        let limit = SessionLimit { max_sessions: 2, policy: SessionLimitPolicy::RevokeOldest };
+        let svc = AuthService::new(repo.clone(), cfg.clone()).with_clock(clock.clone()).with_session_limit(limit);
+        let tid = uuid::Uuid::new_v4();
+        let user = svc.register(RegisterInput { tenant_id: tid, email: "g@e.com".into(), name: "G".into(), password: "Passw0rd".into() }).await.unwrap();
+        let login = LoginInput { tenant_id: tid, email: "g@e.com".into(), password: "Passw0rd".into() };
+        let mut tokens = Vec::new();
+        for _ in 0..3 {
+            tokens.push(svc.login(login.clone()).await.unwrap().token.unwrap());
+            clock.advance(chrono::Duration::seconds(1));
+        }
+        // 第三次登录挤掉最早的会话
+        assert!(matches!(svc.authenticate(&tokens[0]).await, Err(AuthError::Unauthorized)));
+        assert!(svc.authenticate(&tokens[1]).await.is_ok() && svc.authenticate(&tokens[2]).await.is_ok());
+        assert_eq!(svc.sessions(user.id).await.unwrap().len(), 2);
+
+        let strict = AuthService::new(repo, cfg).with_clock(clock).with_session_limit(SessionLimit { policy: SessionLimitPolicy::Reject, ..limit });
+        assert!(matches!(strict.login(login).await, Err(AuthError::SessionLimit)));
+    }
+
+    #[tokio::test]
+    async fn revoked_session_rejects_its_token() {
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() });
+        let tid = uuid::Uuid::new_v4();
+        let user = svc.register(RegisterInput { tenant_id: tid, email: "h@e.com".into(), name: "H".into(), password: "Passw0rd".into() }).await.unwrap();
+        let session = svc.login(LoginInput { tenant_id: tid, email: "h@e.com".into(), password: "Passw0rd".into() }).await.unwrap();
+        let (token, sid) = (session.token.unwrap(), session.session_id.unwrap());
+        assert_eq!(svc.authenticate(&token).await.unwrap().sid, Some(sid));
+
+        assert!(matches!(svc.revoke_session(uuid::Uuid::new_v4(), sid).await, Err(AuthError::NotFound)));
+        svc.revoke_session(user.id, sid).await.unwrap();
+        // 签名与 exp 仍然有效，但会话已撤销
+        assert!(svc.verify_token(&token).is_ok());
+        assert!(matches!(svc.current_user(&token).await, Err(AuthError::Unauthorized)));
+        assert!(svc.sessions(user.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn session_records_client_and_last_seen() {
+        let clock = Arc::new(MockClock::new(chrono::Utc::now()));
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() }).with_clock(clock.clone());
+        let tid = uuid::Uuid::new_v4();
+        let user = svc.register(RegisterInput { tenant_id: tid, email: "i@e.com".into(), name: "I".into(), password: "Passw0rd".into() }).await.unwrap();
+        let client = SessionClient { user_agent: Some("curl/8.5".into()), ip_address: Some("203.0.113.7".into()) };
+        let token = svc.login_from(LoginInput { tenant_id: tid, email: "i@e.com".into(), password: "Passw0rd".into() }, client.clone()).await.unwrap().token.unwrap();
+        let last_seen = || async { svc.sessions(user.id).await.unwrap()[0].last_seen_at };
+        assert_eq!(svc.sessions(user.id).await.unwrap()[0].client, client);
+        assert_eq!(last_seen().await, None);
+
+        let first = clock.now();
+        svc.authenticate(&token).await.unwrap();
+        assert_eq!(last_seen().await, Some(first));
+        // 一分钟内的请求不再写入
+        clock.advance(chrono::Duration::seconds(30));
+        svc.authenticate(&token).await.unwrap();
+        assert_eq!(last_seen().await, Some(first));
+        clock.advance(chrono::Duration::seconds(30));
+        svc.authenticate(&token).await.unwrap();
+        assert_eq!(last_seen().await, Some(clock.now()));
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        tokens: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl VerificationNotifier for RecordingNotifier {
+        async fn send(&self, _user: &AuthUser, token: &str, _expires_at: chrono::DateTime<chrono::Utc>) -> Result<(), AuthError> {
+            self.tokens.lock().unwrap().push(token.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn email_verification_gates_login_per_tenant_policy() {
+        let repo = Arc::new(MockAuthRepository::default());
+        let notifier = Arc::new(RecordingNotifier::default());
+        let svc = AuthService::new(repo.clone(), AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() })
+            .with_verification(notifier.clone(), std::time::Duration::from_secs(3600));
+        let (required, optional) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        repo.set_email_verification_policy(required, EmailVerificationPolicy::Required);
+        repo.set_email_verification_policy(optional, EmailVerificationPolicy::Optional);
+        let register = |tenant_id| RegisterInput { tenant_id, email: "j@e.com".into(), name: "J".into(), password: "Passw0rd".into() };
+        let login = |tenant_id| LoginInput { tenant_id, email: "j@e.com".into(), password: "Passw0rd".into() };
+
+        let user = svc.register(register(required)).await.unwrap();
+        assert!(!user.is_verified());
+        assert!(matches!(svc.login(login(required)).await, Err(AuthError::EmailNotVerified)));
+        let token = notifier.tokens.lock().unwrap()[0].clone();
+        // 验证令牌不能当作登录令牌使用，登录令牌也不能用于验证
+        assert!(svc.verify_token(&token).is_err());
+        assert_eq!(svc.verify_email(&token).await.unwrap().status, models::user::STATUS_ACTIVE);
+        assert!(svc.verify_email(&token).await.is_ok());
+        let session = svc.login(login(required)).await.unwrap();
+        assert!(matches!(svc.verify_email(&session.token.unwrap()).await, Err(AuthError::Validation(_))));
+
+        // optional：未验证也可登录；resend 对已验证或不存在的用户静默忽略
+        assert!(!svc.register(register(optional)).await.unwrap().is_verified());
+        assert!(svc.login(login(optional)).await.is_ok());
+        svc.resend_verification(optional, "j@e.com").await.unwrap();
+        svc.resend_verification(required, "j@e.com").await.unwrap();
+        svc.resend_verification(optional, "nobody@e.com").await.unwrap();
+        assert_eq!(notifier.tokens.lock().unwrap().len(), 3);
+
+        // 默认策略 off：注册即激活，不发送令牌
+        assert!(svc.register(register(uuid::Uuid::new_v4())).await.unwrap().is_verified());
+        assert_eq!(notifier.tokens.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn invitations_bind_tenant_and_role() {
+        let fixed = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = Arc::new(MockClock::new(fixed));
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo.clone(), AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() })
+            .with_clock(clock.clone())
+            .with_invitation_ttl(std::time::Duration::from_secs(3600));
+        let tid = uuid::Uuid::new_v4();
+        repo.set_invite_only(tid, true);
+
+        // invite-only 租户拒绝自助注册
+        let register = RegisterInput { tenant_id: tid, email: "k@e.com".into(), name: "K".into(), password: "Passw0rd".into() };
+        assert!(matches!(svc.register(register).await, Err(AuthError::InviteRequired)));
+        assert!(matches!(svc.invite(InviteInput { tenant_id: tid, email: "k@e.com".into(), role: "owner".into() }).await, Err(AuthError::Validation(_))));
+
+        let (invitation, token) = svc.invite(InviteInput { tenant_id: tid, email: "k@e.com".into(), role: models::user::ROLE_ADMIN.into() }).await.unwrap();
+        assert_ne!(invitation.token_hash, token);
+        let accept = |token: &str| AcceptInviteInput { token: token.into(), name: "K".into(), password: "Passw0rd".into() };
+        assert!(matches!(svc.accept_invite(accept("bogus")).await, Err(AuthError::Validation(_))));
+        let user = svc.accept_invite(accept(&token)).await.unwrap();
+        assert_eq!((user.tenant_id, user.email.as_str(), user.role.as_str()), (tid, "k@e.com", models::user::ROLE_ADMIN));
+        assert!(user.is_verified());
+        assert!(svc.login(LoginInput { tenant_id: tid, email: "k@e.com".into(), password: "Passw0rd".into() }).await.is_ok());
+        // 邀请只能使用一次；已是成员的邮箱不能再邀请
+        assert!(matches!(svc.accept_invite(accept(&token)).await, Err(AuthError::Validation(_))));
+        assert!(matches!(svc.invite(InviteInput { tenant_id: tid, email: "k@e.com".into(), role: models::user::ROLE_MEMBER.into() }).await, Err(AuthError::Conflict)));
+
+        // 过期或撤销的邀请失效
+        let (_, expired) = svc.invite(InviteInput { tenant_id: tid, email: "l@e.com".into(), role: models::user::ROLE_MEMBER.into() }).await.unwrap();
+        clock.advance(chrono::Duration::hours(2));
+        assert!(matches!(svc.accept_invite(accept(&expired)).await, Err(AuthError::Validation(_))));
+        let (revoked, token) = svc.invite(InviteInput { tenant_id: tid, email: "m@e.com".into(), role: models::user::ROLE_MEMBER.into() }).await.unwrap();
+        // 其他租户不能撤回
+        assert!(matches!(svc.revoke_invitation(uuid::Uuid::new_v4(), revoked.id).await, Err(AuthError::NotFound)));
+        svc.revoke_invitation(tid, revoked.id).await.unwrap();
+        assert!(matches!(svc.accept_invite(accept(&token)).await, Err(AuthError::Validation(_))));
+        assert!(matches!(svc.revoke_invitation(tid, revoked.id).await, Err(AuthError::NotFound)));
+        assert_eq!(svc.invitations(tid).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn admin_actions_require_admin_role_in_tenant() {
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() });
+        let tid = uuid::Uuid::new_v4();
+        let member = svc.register(RegisterInput { tenant_id: tid, email: "n@e.com".into(), name: "N".into(), password: "Passw0rd".into() }).await.unwrap();
+        let (_, token) = svc.invite(InviteInput { tenant_id: tid, email: "o@e.com".into(), role: models::user::ROLE_ADMIN.into() }).await.unwrap();
+        let admin = svc.accept_invite(AcceptInviteInput { token, name: "O".into(), password: "Passw0rd".into() }).await.unwrap();
+        let claims = |uid| TokenClaims { sub: String::new(), uid: Some(uid), tid: Some(tid), exp: 0, iss: None, aud: None, sid: None };
+
+        assert!(matches!(svc.require_admin(&claims(member.id)).await, Err(AuthError::Forbidden)));
+        assert!(matches!(svc.require_admin(&TokenClaims { uid: None, ..claims(admin.id) }).await, Err(AuthError::Unauthorized)));
+        assert_eq!(svc.require_admin(&claims(admin.id)).await.unwrap().id, admin.id);
+        // 只能查看本租户用户
+        assert_eq!(svc.tenant_user(tid, member.id).await.unwrap().id, member.id);
+        assert!(matches!(svc.tenant_user(uuid::Uuid::new_v4(), member.id).await, Err(AuthError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn current_user_requires_valid_token() {
+        let repo = Arc::new(MockAuthRepository::default());
+        let svc = AuthService::new(repo, AuthConfig { jwt_secret: Some("secret".into()), password_algorithm: "argon2".into() });
+        let tid = uuid::Uuid::new_v4();
+        let bad_email = RegisterInput { tenant_id: tid, email: "nope".into(), name: "D".into(), password: "Passw0rd".into() };
+        assert!(matches!(svc.register(bad_email).await, Err(AuthError::Validation(_))));
+        let user = svc.register(RegisterInput { tenant_id: tid, email: "d@e.com".into(), name: "D".into(), password: "Passw0rd".into() }).await.unwrap();
+        let session = svc.login(LoginInput { tenant_id: tid, email: "d@e.com".into(), password: "Passw0rd".into() }).await.unwrap();
+
+        assert_eq!(svc.current_user(&session.token.unwrap()).await.unwrap().id, user.id);
+        assert!(matches!(svc.current_user("not-a-jwt").await, Err(AuthError::Unauthorized)));
+    }
+}
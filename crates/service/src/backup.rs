@@ -0,0 +1,459 @@
+//! Configuration backup snapshots.
+//! - Periodic and pre-apply snapshots of routes/tenants/upstreams/rate limits/proxy APIs.
+//! - Pre-apply snapshots are written in the transaction of the change they precede.
+//! - Retention keeps the newest N snapshots.
+//! - Restore upserts snapshot rows in one transaction, recovering accidental deletions
+//!   without a point-in-time restore of the whole database.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, IdenStatic, IntoActiveModel, Iterable,
+    PrimaryKeyToColumn, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::lock::{run_exclusive, DistributedLock};
+use common::pagination::{Page, Pagination};
+use common::time::{system_clock, SharedClock};
+use models::{config_backup, proxy_api, ratelimit, route, tenant, upstream};
+
+use crate::errors::ServiceError;
+use crate::events::{ConfigEvent, EventBus};
+
+/// Rows per INSERT when restoring (keeps well below the Postgres bind-parameter limit).
+const RESTORE_CHUNK: usize = 500;
+
+/// Serialized configuration document stored in `config_backup.snapshot`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub tenants: Vec<tenant::Model>,
+    pub upstreams: Vec<upstream::Model>,
+    pub rate_limits: Vec<ratelimit::Model>,
+    pub routes: Vec<route::Model>,
+    pub proxy_apis: Vec<proxy_api::Model>,
+}
+
+impl ConfigSnapshot {
+    pub fn item_count(&self) -> usize {
+        self.tenants.len() + self.upstreams.len() + self.rate_limits.len() + self.routes.len() + self.proxy_apis.len()
+    }
+
+    /// Rows owned by one tenant plus the upstreams its routes point at (tenant export).
+    pub fn for_tenant(&self, tenant_id: Uuid) -> ConfigSnapshot {
+        let routes: Vec<route::Model> = self.routes.iter().filter(|r| r.tenant_id == tenant_id).cloned().collect();
+        let upstream_ids: std::collections::HashSet<Uuid> = routes
+            .iter()
+            .flat_map(|r| [Some(r.upstream_id), r.blue_upstream_id, r.green_upstream_id, r.previous_upstream_id])
+            .flatten()
+            .collect();
+        ConfigSnapshot {
+            tenants: self.tenants.iter().filter(|t| t.id == tenant_id).cloned().collect(),
+            upstreams: self.upstreams.iter().filter(|u| upstream_ids.contains(&u.id)).cloned().collect(),
+            rate_limits: self.rate_limits.iter().filter(|r| r.tenant_id == Some(tenant_id)).cloned().collect(),
+            routes,
+            proxy_apis: self.proxy_apis.iter().filter(|p| p.tenant_id == tenant_id).cloned().collect(),
+        }
+    }
+}
+
+/// Backup listing entry (without the snapshot body).
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub id: Uuid,
+    pub reason: String,
+    pub item_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<config_backup::Model> for BackupSummary {
+    fn from(m: config_backup::Model) -> Self {
+        Self { id: m.id, reason: m.reason, item_count: m.item_count, created_at: m.created_at.with_timezone(&Utc) }
+    }
+}
+
+/// Rows written per entity by a restore.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RestoreReport {
+    pub backup_id: Uuid,
+    /// Snapshot of the state that was overwritten.
+    pub pre_restore_backup_id: Uuid,
+    pub tenants: usize,
+    pub upstreams: usize,
+    pub rate_limits: usize,
+    pub routes: usize,
+    pub proxy_apis: usize,
+}
+
+/// Takes, prunes and restores configuration snapshots.
+pub struct ConfigBackupService {
+    db: DatabaseConnection,
+    /// Snapshots kept per reason.
+    retention: usize,
+    pre_apply_min_interval: Duration,
+    /// Last committed pre-apply snapshot per changed entity.
+    last_pre_apply: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+    events: Option<EventBus>,
+    clock: SharedClock,
+    lock: Option<Arc<dyn DistributedLock>>,
+}
+
+impl ConfigBackupService {
+    pub fn new(db: DatabaseConnection, retention: usize, pre_apply_min_interval: Duration) -> Self {
+        Self {
+            db,
+            retention: retention.max(1),
+            pre_apply_min_interval,
+            last_pre_apply: Mutex::new(HashMap::new()),
+            events: None,
+            clock: system_clock(),
+            lock: None,
+        }
+    }
+
+    /// Run the scheduled snapshot on one replica at a time.
+    pub fn with_lock(mut self, lock: Arc<dyn DistributedLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    /// Restores publish `CacheFlushed` so cached reads pick up the restored rows.
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Read the current configuration.
+    pub async fn capture(&self) -> Result<ConfigSnapshot, ServiceError> {
+        capture_in(&self.db).await
+    }
+
+    /// Store a snapshot of the current configuration and apply retention.
+    pub async fn snapshot(&self, reason: &str) -> Result<BackupSummary, ServiceError> {
+        let saved = self.insert_snapshot(&self.db, reason).await?;
+        self.prune_logged().await?;
+        Ok(saved.into())
+    }
+
+    /// Open the transaction of a change to `entity` and snapshot the configuration in it
+    /// first, unless a snapshot for `entity` was committed within `pre_apply_min_interval`.
+    /// The change must be written through [`PreApply::txn`] so it commits or rolls back
+    /// together with its snapshot; a failed snapshot fails the change.
+    pub async fn pre_apply(&self, entity: Uuid) -> Result<PreApply<'_>, ServiceError> {
+        let txn = self.db.begin().await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        let snapshotted = !self.recently_snapshotted(entity);
+        if snapshotted {
+            self.insert_snapshot(&txn, "pre_apply").await?;
+        }
+        Ok(PreApply { backups: self, txn, entity, snapshotted })
+    }
+
+    fn recently_snapshotted(&self, entity: Uuid) -> bool {
+        let now = self.clock.now();
+        let last = self.last_pre_apply.lock().expect("pre_apply lock");
+        last.get(&entity).is_some_and(|prev| (now - *prev).to_std().unwrap_or_default() < self.pre_apply_min_interval)
+    }
+
+    fn record_pre_apply(&self, entity: Uuid) {
+        let now = self.clock.now();
+        let mut last = self.last_pre_apply.lock().expect("pre_apply lock");
+        // 过期条目不再影响节流，顺便清掉避免无限增长
+        last.retain(|_, prev| (now - *prev).to_std().unwrap_or_default() < self.pre_apply_min_interval);
+        last.insert(entity, now);
+    }
+
+    async fn insert_snapshot<C: ConnectionTrait>(&self, db: &C, reason: &str) -> Result<config_backup::Model, ServiceError> {
+        let snap = capture_in(db).await?;
+        let body = serde_json::to_string(&snap).map_err(|e| ServiceError::Validation(e.to_string()))?;
+        let am = config_backup::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            reason: Set(reason.to_string()),
+            snapshot: Set(body),
+            item_count: Set(snap.item_count() as i32),
+            created_at: Set(self.clock.now_fixed()),
+        };
+        let saved = am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        info!(backup_id = %saved.id, reason, items = saved.item_count, "config_backup_created");
+        Ok(saved)
+    }
+
+    async fn prune_logged(&self) -> Result<(), ServiceError> {
+        let pruned = self.prune().await?;
+        if pruned > 0 {
+            info!(pruned, retention = self.retention, "config_backup_pruned");
+        }
+        Ok(())
+    }
+
+    /// Delete all but the newest `retention` snapshots of each reason, so a burst of
+    /// pre-apply snapshots cannot evict the scheduled ones; returns the number deleted.
+    pub async fn prune(&self) -> Result<u64, ServiceError> {
+        use sea_orm::{ColumnTrait, QueryFilter};
+        let reasons: Vec<String> = config_backup::Entity::find()
+            .select_only()
+            .column(config_backup::Column::Reason)
+            .distinct()
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))?;
+        let mut expired: Vec<Uuid> = Vec::new();
+        for reason in reasons {
+            let ids: Vec<Uuid> = config_backup::Entity::find()
+                .select_only()
+                .column(config_backup::Column::Id)
+                .filter(config_backup::Column::Reason.eq(reason))
+                .order_by_desc(config_backup::Column::CreatedAt)
+                .offset(self.retention as u64)
+                .into_tuple()
+                .all(&self.db)
+                .await
+                .map_err(|e| ServiceError::Db(e.to_string()))?;
+            expired.extend(ids);
+        }
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        let res = config_backup::Entity::delete_many()
+            .filter(config_backup::Column::Id.is_in(expired))
+            .exec(&self.db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))?;
+        Ok(res.rows_affected)
+    }
+
+    /// Newest first.
+    pub async fn list(&self, opts: Pagination) -> Result<Page<BackupSummary>, ServiceError> {
+        let (created_at, id) = (config_backup::Column::CreatedAt, config_backup::Column::Id);
+        let page = crate::db::fetch_keyset_page(&self.db, config_backup::Entity::find(), created_at, id, opts, |m| {
+            crate::db::anchor_of(&m.created_at, m.id)
+        })
+        .await?;
+        Ok(page.map(BackupSummary::from))
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<ConfigSnapshot>, ServiceError> {
+        let found = config_backup::Entity::find_by_id(id).one(&self.db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        found
+            .map(|m| serde_json::from_str(&m.snapshot).map_err(|e| ServiceError::Validation(format!("corrupt backup {}: {}", id, e))))
+            .transpose()
+    }
+
+    /// Restore a snapshot: rows missing from the database are re-created and existing rows
+    /// are overwritten with the snapshot values. Rows created after the snapshot are kept.
+    pub async fn restore(&self, id: Uuid) -> Result<RestoreReport, ServiceError> {
+        let snap = self.get(id).await?.ok_or_else(|| ServiceError::not_found("config_backup"))?;
+        let dbe = |e: DbErr| ServiceError::Db(e.to_string());
+        let txn = self.db.begin().await.map_err(dbe)?;
+        let pre = self.insert_snapshot(&txn, "pre_restore").await?;
+        // 按外键依赖顺序写入
+        let report = RestoreReport {
+            backup_id: id,
+            pre_restore_backup_id: pre.id,
+            tenants: upsert_all::<tenant::Entity, _>(&txn, snap.tenants).await.map_err(dbe)?,
+            upstreams: upsert_all::<upstream::Entity, _>(&txn, snap.upstreams).await.map_err(dbe)?,
+            rate_limits: upsert_all::<ratelimit::Entity, _>(&txn, snap.rate_limits).await.map_err(dbe)?,
+            routes: upsert_all::<route::Entity, _>(&txn, snap.routes).await.map_err(dbe)?,
+            proxy_apis: upsert_all::<proxy_api::Entity, _>(&txn, snap.proxy_apis).await.map_err(dbe)?,
+        };
+        txn.commit().await.map_err(dbe)?;
+        if let Err(e) = self.prune_logged().await {
+            warn!(error = %e, "config_backup_prune_failed");
+        }
+
+        if let Some(bus) = &self.events {
+            bus.publish(ConfigEvent::CacheFlushed);
+        }
+        info!(backup_id = %id, ?report, "config_backup_restored");
+        Ok(report)
+    }
+
+    /// Take a `scheduled` snapshot every `interval`.
+    pub fn spawn_scheduler(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // 第一次 tick 立即返回，跳过以免启动时重复快照
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Some(Err(e)) = run_exclusive(this.lock.as_deref(), "config_backup", interval, this.snapshot("scheduled")).await {
+                    warn!(error = %e, "config_backup_scheduled_failed");
+                }
+            }
+        })
+    }
+}
+
+/// A configuration change in progress, opened by [`ConfigBackupService::pre_apply`].
+/// Dropping it without [`commit`](PreApply::commit) rolls back both the change and its snapshot.
+pub struct PreApply<'a> {
+    backups: &'a ConfigBackupService,
+    txn: DatabaseTransaction,
+    entity: Uuid,
+    snapshotted: bool,
+}
+
+impl PreApply<'_> {
+    /// Connection the change must be written through.
+    pub fn txn(&self) -> &DatabaseTransaction {
+        &self.txn
+    }
+
+    /// Commit the change with its snapshot, then apply retention.
+    pub async fn commit(self) -> Result<(), ServiceError> {
+        self.txn.commit().await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        if self.snapshotted {
+            self.backups.record_pre_apply(self.entity);
+            // 变更已提交，清理失败不应让调用方看到错误
+            if let Err(e) = self.backups.prune_logged().await {
+                warn!(error = %e, "config_backup_prune_failed");
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn capture_in<C: ConnectionTrait>(db: &C) -> Result<ConfigSnapshot, ServiceError> {
+    let dbe = |e: DbErr| ServiceError::Db(e.to_string());
+    Ok(ConfigSnapshot {
+        tenants: tenant::Entity::find().all(db).await.map_err(dbe)?,
+        upstreams: upstream::Entity::find().all(db).await.map_err(dbe)?,
+        rate_limits: ratelimit::Entity::find().all(db).await.map_err(dbe)?,
+        routes: route::Entity::find().all(db).await.map_err(dbe)?,
+        proxy_apis: proxy_api::Entity::find().all(db).await.map_err(dbe)?,
+    })
+}
+
+/// INSERT ... ON CONFLICT (pk) DO UPDATE SET <all other columns>.
+async fn upsert_all<E, C>(db: &C, rows: Vec<E::Model>) -> Result<usize, DbErr>
+where
+    E: EntityTrait,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send,
+    C: ConnectionTrait,
+{
+    let total = rows.len();
+    let pk: Vec<E::Column> = E::PrimaryKey::iter().map(|k| k.into_column()).collect();
+    let updates: Vec<E::Column> = E::Column::iter()
+        .filter(|c| !pk.iter().any(|k| k.as_str() == c.as_str()))
+        .collect();
+    let mut rows = rows.into_iter().peekable();
+    while rows.peek().is_some() {
+        let chunk: Vec<E::ActiveModel> = rows.by_ref().take(RESTORE_CHUNK).map(|m| m.into_active_model().reset_all()).collect();
+        E::insert_many(chunk)
+            .on_conflict(OnConflict::columns(pk.clone()).update_columns(updates.clone()).to_owned())
+            .exec_without_returning(db)
+            .await?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::get_db;
+
+    #[test]
+    fn snapshot_roundtrip_and_count() {
+        let now = Utc::now().fixed_offset();
+        let t = tenant::Model { id: Uuid::new_v4(), name: "acme".into(), created_at: now, suspended: false, suspended_reason: None, email_verification: "off".into(), invite_only: false };
+        let tid = t.id;
+        let snap = ConfigSnapshot { tenants: vec![t], ..Default::default() };
+        assert_eq!(snap.item_count(), 1);
+        assert_eq!(snap.for_tenant(tid).item_count(), 1);
+        assert_eq!(snap.for_tenant(Uuid::new_v4()).item_count(), 0);
+        let json = serde_json::to_string(&snap).unwrap();
+        assert_eq!(serde_json::from_str::<ConfigSnapshot>(&json).unwrap(), snap);
+    }
+
+    #[tokio::test]
+    async fn restore_recovers_deleted_rows() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let svc = ConfigBackupService::new(db.clone(), 1000, Duration::from_secs(60));
+
+        let up = upstream::create(&db, &format!("backup_up_{}", Uuid::new_v4()), "https://api.example.com").await?;
+        let backup = svc.snapshot("manual").await?;
+        upstream::Entity::delete_by_id(up.id).exec(&db).await?;
+        assert!(upstream::Entity::find_by_id(up.id).one(&db).await?.is_none());
+
+        let report = svc.restore(backup.id).await?;
+        assert!(report.upstreams >= 1);
+        let restored = upstream::Entity::find_by_id(up.id).one(&db).await?.expect("restored");
+        assert_eq!(restored.name, up.name);
+
+        upstream::Entity::delete_by_id(up.id).exec(&db).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_cursor_resumes_after_newer_snapshots() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let svc = ConfigBackupService::new(db.clone(), 1000, Duration::from_secs(60));
+        for _ in 0..3 {
+            svc.snapshot("manual").await?;
+        }
+
+        let first = svc.list(Pagination::new(1, 2)).await?;
+        assert_eq!((first.items.len(), first.total, first.has_more), (2, 3, true));
+        // 翻页期间新增的快照不应让第一页的行在第二页重复出现
+        svc.snapshot("manual").await?;
+        let second = svc.list(Pagination::from_cursor(first.next_cursor.as_deref().expect("next cursor"), 100)?).await?;
+        assert_eq!(second.items.len(), 1);
+        assert!(!first.items.iter().any(|a| a.id == second.items[0].id));
+        assert!(!second.has_more);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pre_apply_burst_keeps_the_last_scheduled_snapshot() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        use sea_orm::{ColumnTrait, PaginatorTrait, QueryFilter};
+        let (db, _schema) = get_db().await?;
+        let svc = ConfigBackupService::new(db.clone(), 2, Duration::ZERO);
+        let scheduled = svc.snapshot("scheduled").await?;
+        for _ in 0..5 {
+            svc.pre_apply(Uuid::new_v4()).await?.commit().await?;
+        }
+
+        assert!(config_backup::Entity::find_by_id(scheduled.id).one(&db).await?.is_some());
+        let pre_apply = config_backup::Entity::find().filter(config_backup::Column::Reason.eq("pre_apply")).count(&db).await?;
+        assert_eq!(pre_apply, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pre_apply_snapshots_per_entity_with_the_change() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        use sea_orm::{ColumnTrait, PaginatorTrait, QueryFilter};
+        let (db, _schema) = get_db().await?;
+        let svc = ConfigBackupService::new(db.clone(), 1000, Duration::from_secs(60));
+        let count = || config_backup::Entity::find().filter(config_backup::Column::Reason.eq("pre_apply")).count(&db);
+        let before = count().await?;
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // 未提交的变更连同快照一起回滚，也不计入节流
+        drop(svc.pre_apply(a).await?);
+        assert_eq!(count().await?, before);
+
+        svc.pre_apply(a).await?.commit().await?;
+        svc.pre_apply(a).await?.commit().await?;
+        svc.pre_apply(b).await?.commit().await?;
+        assert_eq!(count().await?, before + 2);
+        Ok(())
+    }
+}
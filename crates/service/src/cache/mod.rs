@@ -0,0 +1,11 @@
+//! Read-path caching for small, rarely-changing control-plane tables
+//!
+//! `VersionedCache` is the generic building block (version + optional TTL / size bound);
+//! `ReadCache` fronts proxy_api / route / upstream / ratelimit / tenant reads and API key
+//! hash lookups, and is invalidated via `events::EventBus`.
+
+pub mod versioned;
+pub mod read_cache;
+
+pub use read_cache::{ReadCache, ReadCacheOptions};
+pub use versioned::{CacheStats, VersionedCache};
@@ -0,0 +1,231 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sea_orm::{DatabaseConnection, EntityTrait};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use models::{apikey, proxy_api, route, tenant, upstream};
+
+use crate::cache::versioned::{CacheStats, VersionedCache};
+use crate::db::{apikey_service, route_service, tenant_service, upstream_service};
+use crate::errors::ServiceError;
+use crate::events::{ConfigEvent, EventBus};
+
+/// Bounds applied to every cache in [`ReadCache`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReadCacheOptions {
+    /// Entries older than this are reloaded; `None` = only event-driven invalidation.
+    pub ttl: Option<Duration>,
+    /// Per-cache entry limit; `None` = unbounded.
+    pub max_entries: Option<usize>,
+}
+
+impl Default for ReadCacheOptions {
+    fn default() -> Self {
+        Self { ttl: Some(Duration::from_secs(300)), max_entries: Some(10_000) }
+    }
+}
+
+/// Cache fronting control-plane reads of proxy_api / route / upstream / tenant
+/// and data-plane API key lookups by hash.
+///
+/// Mutations invalidate in two ways: the writer calls [`ReadCache::apply`] before
+/// responding (read-your-writes in this process), and publishes the event so
+/// [`ReadCache::spawn_invalidator`] subscribers elsewhere drop their copies too.
+pub struct ReadCache {
+    pub proxy_api_lists: VersionedCache<Option<Uuid>, Vec<proxy_api::Model>>,
+    pub proxy_api_by_id: VersionedCache<Uuid, Option<proxy_api::Model>>,
+    pub upstream_by_id: VersionedCache<Uuid, Option<upstream::Model>>,
+    pub tenant_by_id: VersionedCache<Uuid, Option<tenant::Model>>,
+    pub route_by_id: VersionedCache<Uuid, Option<route::Model>>,
+    pub apikey_by_hash: VersionedCache<String, Option<apikey::Model>>,
+}
+
+fn bounded<K, V>(name: &'static str, opts: ReadCacheOptions) -> VersionedCache<K, V>
+where
+    K: Eq + std::hash::Hash + Clone,
+    V: Clone,
+{
+    let mut cache = VersionedCache::new(name);
+    if let Some(ttl) = opts.ttl {
+        cache = cache.with_ttl(ttl);
+    }
+    if let Some(max) = opts.max_entries {
+        cache = cache.with_max_entries(max);
+    }
+    cache
+}
+
+impl Default for ReadCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadCache {
+    pub fn new() -> Self {
+        Self::with_options(ReadCacheOptions::default())
+    }
+
+    pub fn with_options(opts: ReadCacheOptions) -> Self {
+        Self {
+            proxy_api_lists: bounded("proxy_api_lists", opts),
+            proxy_api_by_id: bounded("proxy_api_by_id", opts),
+            upstream_by_id: bounded("upstream_by_id", opts),
+            tenant_by_id: bounded("tenant_by_id", opts),
+            route_by_id: bounded("route_by_id", opts),
+            apikey_by_hash: bounded("apikey_by_hash", opts),
+        }
+    }
+
+    /// Cached `upstream_service::get_upstream`.
+    pub async fn get_upstream(&self, db: &DatabaseConnection, id: Uuid) -> Result<Option<upstream::Model>, ServiceError> {
+        self.upstream_by_id.get_or_load(id, upstream_service::get_upstream(db, id)).await
+    }
+
+    /// Load every upstream into the cache; returns how many were loaded.
+    pub async fn warm_upstreams(&self, db: &DatabaseConnection) -> Result<usize, ServiceError> {
+        let version = self.upstream_by_id.version();
+        let all = upstream::Entity::find().all(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        let count = all.len();
+        for u in all {
+            self.upstream_by_id.insert(u.id, Some(u), version).await;
+        }
+        Ok(count)
+    }
+
+    /// Cached `tenant_service::get_tenant` (suspension checks on the data path).
+    pub async fn get_tenant(&self, db: &DatabaseConnection, id: Uuid) -> Result<Option<tenant::Model>, ServiceError> {
+        self.tenant_by_id.get_or_load(id, tenant_service::get_tenant(db, id)).await
+    }
+
+    /// Cached `route_service::get_route`.
+    pub async fn get_route(&self, db: &DatabaseConnection, id: Uuid) -> Result<Option<route::Model>, ServiceError> {
+        self.route_by_id.get_or_load(id, route_service::get_route(db, id)).await
+    }
+
+    /// Cached `apikey_service::find_api_key_by_hash`; unknown hashes are cached as `None` too.
+    pub async fn find_api_key(&self, db: &DatabaseConnection, key_hash: &str) -> Result<Option<apikey::Model>, ServiceError> {
+        self.apikey_by_hash.get_or_load(key_hash.to_string(), apikey_service::find_api_key_by_hash(db, key_hash)).await
+    }
+
+    /// Invalidate locally, then publish so other subscribers follow; returns the revision.
+    pub async fn apply(&self, bus: &EventBus, event: ConfigEvent) -> i64 {
+        self.handle_event(&event).await;
+        bus.publish(event)
+    }
+
+    /// Apply a change event to the affected caches.
+    pub async fn handle_event(&self, event: &ConfigEvent) {
+        debug!(?event, "read_cache_event");
+        match event {
+            ConfigEvent::ProxyApiChanged { .. } => {
+                self.proxy_api_lists.invalidate().await;
+                self.proxy_api_by_id.invalidate().await;
+            }
+            ConfigEvent::UpstreamChanged { .. } => {
+                self.upstream_by_id.invalidate().await;
+            }
+            // rate limits are read by the gateway, not through this cache
+            ConfigEvent::RateLimitChanged { .. } => {}
+            ConfigEvent::TenantChanged { .. } => {
+                self.tenant_by_id.invalidate().await;
+            }
+            ConfigEvent::RouteChanged { .. } | ConfigEvent::RouteCutover { .. } => {
+                self.route_by_id.invalidate().await;
+            }
+            ConfigEvent::ApiKeyChanged { .. } => {
+                self.apikey_by_hash.invalidate().await;
+            }
+            ConfigEvent::CacheFlushed => self.flush().await,
+        }
+    }
+
+    /// Drop every cached entry.
+    pub async fn flush(&self) {
+        self.proxy_api_lists.invalidate().await;
+        self.proxy_api_by_id.invalidate().await;
+        self.upstream_by_id.invalidate().await;
+        self.tenant_by_id.invalidate().await;
+        self.route_by_id.invalidate().await;
+        self.apikey_by_hash.invalidate().await;
+        info!("read_cache_flushed");
+    }
+
+    pub async fn stats(&self) -> Vec<CacheStats> {
+        vec![
+            self.proxy_api_lists.stats().await,
+            self.proxy_api_by_id.stats().await,
+            self.upstream_by_id.stats().await,
+            self.tenant_by_id.stats().await,
+            self.route_by_id.stats().await,
+            self.apikey_by_hash.stats().await,
+        ]
+    }
+
+    /// Subscribe to the bus and invalidate on every change event.
+    /// On receiver lag everything is flushed, since events may have been missed.
+    pub fn spawn_invalidator(self: &Arc<Self>, bus: &EventBus) -> JoinHandle<()> {
+        let cache = Arc::clone(self);
+        let mut rx = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => cache.handle_event(&msg.event).await,
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(skipped = n, "read_cache_invalidator_lagged");
+                        cache.flush().await;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn invalidator_reacts_to_bus_events() {
+        let cache = Arc::new(ReadCache::new());
+        let bus = EventBus::new(16);
+        let _handle = cache.spawn_invalidator(&bus);
+
+        let id = Uuid::new_v4();
+        cache.tenant_by_id.insert(id, None, cache.tenant_by_id.version()).await;
+        cache.upstream_by_id.insert(id, None, cache.upstream_by_id.version()).await;
+
+        bus.publish(ConfigEvent::TenantChanged { id });
+        for _ in 0..50 {
+            if cache.tenant_by_id.version() > 0 { break; }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(cache.tenant_by_id.version(), 1);
+        // unrelated caches are untouched
+        assert_eq!(cache.upstream_by_id.version(), 0);
+        assert!(cache.upstream_by_id.get(&id).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn apply_invalidates_before_publishing() {
+        let cache = ReadCache::new();
+        let bus = EventBus::new(16);
+        let mut rx = bus.subscribe();
+        let id = Uuid::new_v4();
+        cache.route_by_id.insert(id, None, cache.route_by_id.version()).await;
+        cache.apikey_by_hash.insert("h".repeat(12), None, cache.apikey_by_hash.version()).await;
+
+        let rev = cache.apply(&bus, ConfigEvent::RouteCutover { id, from: Uuid::new_v4(), to: Uuid::new_v4() }).await;
+        assert!(cache.route_by_id.get(&id).await.is_none());
+        assert!(cache.apikey_by_hash.get(&"h".repeat(12)).await.is_some());
+        assert_eq!(rx.recv().await.unwrap().revision, rev);
+
+        cache.apply(&bus, ConfigEvent::ApiKeyChanged { id }).await;
+        assert!(cache.apikey_by_hash.get(&"h".repeat(12)).await.is_none());
+    }
+}
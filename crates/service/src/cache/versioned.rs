@@ -0,0 +1,240 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+static CACHE_HITS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "api_proxy_read_cache_hits_total",
+        "Control-plane read cache hits",
+        &["cache"]
+    )
+    .expect("register read_cache_hits_total")
+});
+
+static CACHE_MISSES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "api_proxy_read_cache_misses_total",
+        "Control-plane read cache misses",
+        &["cache"]
+    )
+    .expect("register read_cache_misses_total")
+});
+
+/// Force registration (see `observability::register_metrics`).
+pub(crate) fn register_metrics() {
+    Lazy::force(&CACHE_HITS_TOTAL);
+    Lazy::force(&CACHE_MISSES_TOTAL);
+}
+
+/// Point-in-time statistics for one cache.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub version: u64,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+/// Versioned in-memory cache.
+///
+/// Every entry is tagged with the cache version observed before loading.
+/// `invalidate()` bumps the version, so a load racing with an invalidation
+/// can never re-populate stale data.
+///
+/// Optional bounds: entries older than `ttl` are treated as misses (a safety net
+/// for changes made outside this process), and at most `max_entries` are kept,
+/// evicting the oldest first.
+pub struct VersionedCache<K, V> {
+    name: &'static str,
+    version: AtomicU64,
+    entries: RwLock<HashMap<K, Entry<V>>>,
+    ttl: Option<Duration>,
+    max_entries: Option<usize>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct Entry<V> {
+    version: u64,
+    inserted: Instant,
+    value: V,
+}
+
+impl<K, V> VersionedCache<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            version: AtomicU64::new(0),
+            entries: RwLock::new(HashMap::new()),
+            ttl: None,
+            max_entries: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Expire entries `ttl` after they were loaded.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Keep at most `max` entries (minimum 1).
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max.max(1));
+        self
+    }
+
+    fn expired(&self, inserted: Instant) -> bool {
+        self.ttl.is_some_and(|ttl| inserted.elapsed() >= ttl)
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Return a cached value if present and current.
+    pub async fn get(&self, key: &K) -> Option<V> {
+        let current = self.version();
+        let map = self.entries.read().await;
+        match map.get(key) {
+            Some(e) if e.version == current && !self.expired(e.inserted) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                CACHE_HITS_TOTAL.with_label_values(&[self.name]).inc();
+                Some(e.value.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                CACHE_MISSES_TOTAL.with_label_values(&[self.name]).inc();
+                None
+            }
+        }
+    }
+
+    /// Store a value loaded while the cache was at `version`; ignored if stale.
+    pub async fn insert(&self, key: K, value: V, version: u64) {
+        let mut map = self.entries.write().await;
+        if version != self.version() {
+            return;
+        }
+        if let Some(max) = self.max_entries {
+            if map.len() >= max && !map.contains_key(&key) {
+                map.retain(|_, e| !self.expired(e.inserted));
+            }
+            while map.len() >= max && !map.contains_key(&key) {
+                let Some(oldest) = map.iter().min_by_key(|(_, e)| e.inserted).map(|(k, _)| k.clone()) else { break };
+                map.remove(&oldest);
+            }
+        }
+        map.insert(key, Entry { version, inserted: Instant::now(), value });
+    }
+
+    /// Read through the cache, calling `load` on miss. Errors are not cached.
+    pub async fn get_or_load<E, Fut>(&self, key: K, load: Fut) -> Result<V, E>
+    where
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(v) = self.get(&key).await {
+            return Ok(v);
+        }
+        let version = self.version();
+        let value = load.await?;
+        self.insert(key, value.clone(), version).await;
+        Ok(value)
+    }
+
+    /// Drop all entries and advance the version.
+    pub async fn invalidate(&self) {
+        let mut map = self.entries.write().await;
+        self.version.fetch_add(1, Ordering::AcqRel);
+        map.clear();
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        CacheStats {
+            name: self.name,
+            version: self.version(),
+            entries: self.entries.read().await.len(),
+            hits,
+            misses,
+            hit_rate: if total == 0 { 0.0 } else { hits as f64 / total as f64 },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hit_after_load_and_miss_after_invalidate() {
+        let cache: VersionedCache<u32, String> = VersionedCache::new("test_basic");
+        let v = cache.get_or_load(1, async { Ok::<_, ()>("a".to_string()) }).await.unwrap();
+        assert_eq!(v, "a");
+        assert_eq!(cache.get(&1).await.as_deref(), Some("a"));
+
+        cache.invalidate().await;
+        assert!(cache.get(&1).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.version, 1);
+        assert_eq!(stats.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn stale_insert_is_ignored() {
+        let cache: VersionedCache<u32, u32> = VersionedCache::new("test_stale");
+        let seen = cache.version();
+        cache.invalidate().await;
+        cache.insert(1, 42, seen).await;
+        assert!(cache.get(&1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let cache: VersionedCache<u32, u32> = VersionedCache::new("test_err");
+        let r = cache.get_or_load(1, async { Err::<u32, &str>("boom") }).await;
+        assert!(r.is_err());
+        let r = cache.get_or_load(1, async { Ok::<u32, &str>(7) }).await;
+        assert_eq!(r, Ok(7));
+    }
+
+    #[tokio::test]
+    async fn ttl_and_capacity_bound_entries() {
+        let cache: VersionedCache<u32, u32> = VersionedCache::new("test_ttl").with_ttl(Duration::from_millis(20));
+        cache.insert(1, 1, cache.version()).await;
+        assert_eq!(cache.get(&1).await, Some(1));
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.get(&1).await.is_none());
+
+        let cache: VersionedCache<u32, u32> = VersionedCache::new("test_cap").with_max_entries(2);
+        for k in 1..=3 {
+            cache.insert(k, k, cache.version()).await;
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        assert_eq!(cache.stats().await.entries, 2);
+        // oldest evicted first
+        assert!(cache.get(&1).await.is_none());
+        assert_eq!(cache.get(&3).await, Some(3));
+    }
+}
@@ -0,0 +1,284 @@
+//! Sampled request/response body capture into `request_payload`, configured per route in `route_capture`.
+//! - Off unless `CaptureOptions::enabled` (config.toml `[capture]`) and the route has a capture row.
+//! - Bodies are redacted first and truncated after, so a cut never exposes a value redaction would mask.
+//! - Settings are read from an in-memory snapshot; writes on this replica refresh it immediately,
+//!   other replicas pick changes up within `snapshot_ttl`.
+//! - The logging pipeline calls [`CaptureService::capture`] after it has written the `request_log` row.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use rand::Rng;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder, Set};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::time::{system_clock, SharedClock};
+use models::{request_log, request_payload, route_capture};
+
+use crate::db::{request_log_service, route_service};
+use crate::errors::ServiceError;
+use crate::replay;
+
+#[derive(Debug, Clone)]
+pub struct CaptureOptions {
+    pub enabled: bool,
+    /// Upper bound for a route's `max_bytes`.
+    pub max_bytes_limit: u32,
+    /// `max_bytes` when a route does not set one.
+    pub default_max_bytes: u32,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self { enabled: false, max_bytes_limit: 1024 * 1024, default_max_bytes: 16 * 1024 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetCaptureInput {
+    pub sample_percent: i16,
+    pub max_bytes: Option<i32>,
+    #[serde(default = "default_true")]
+    pub capture_response: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Upstream response half of an exchange.
+#[derive(Debug, Clone, Copy)]
+pub struct CapturedResponse<'a> {
+    pub status: u16,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+/// One proxied request as seen by the logging pipeline.
+#[derive(Debug, Clone, Copy)]
+pub struct Exchange<'a> {
+    pub method: &'a str,
+    /// Path and query as received.
+    pub uri: &'a str,
+    pub request_headers: &'a HeaderMap,
+    pub request_body: &'a [u8],
+    /// `None` when the upstream could not be reached.
+    pub response: Option<CapturedResponse<'a>>,
+}
+
+/// `roll` is uniform in 0..100.
+pub fn sampled(sample_percent: i16, roll: u8) -> bool {
+    i16::from(roll) < sample_percent.clamp(0, 100)
+}
+
+fn truncate(mut body: Vec<u8>, max_bytes: usize) -> (Vec<u8>, bool) {
+    let truncated = body.len() > max_bytes;
+    body.truncate(max_bytes);
+    (body, truncated)
+}
+
+fn headers_json(headers: &HeaderMap) -> String {
+    serde_json::to_string(&replay::header_snapshot(headers)).unwrap_or_else(|_| "{}".into())
+}
+
+/// Redact, then truncate each body to `max_bytes`.
+pub fn build_payload(request_log_id: i64, settings: &route_capture::Model, exchange: &Exchange<'_>, now: chrono::DateTime<chrono::FixedOffset>) -> request_payload::Model {
+    let max_bytes = settings.max_bytes.max(0) as usize;
+    let (request_body, request_truncated) = truncate(replay::body_snapshot(exchange.request_body), max_bytes);
+    let response = exchange.response.filter(|_| settings.capture_response);
+    let (response_body, response_truncated) = match response {
+        Some(r) => {
+            let (body, truncated) = truncate(replay::body_snapshot(r.body), max_bytes);
+            (Some(body), truncated)
+        }
+        None => (None, false),
+    };
+    request_payload::Model {
+        request_log_id,
+        method: exchange.method.to_string(),
+        uri: common::redaction::global().redact_uri(exchange.uri).into_owned(),
+        request_headers: headers_json(exchange.request_headers),
+        request_body: Some(request_body),
+        request_truncated,
+        response_status: response.map(|r| i32::from(r.status)),
+        response_headers: response.map(|r| headers_json(r.headers)),
+        response_body,
+        response_truncated,
+        created_at: now,
+    }
+}
+
+fn dbe(e: DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+type Snapshot = Arc<HashMap<Uuid, route_capture::Model>>;
+
+pub struct CaptureService {
+    db: DatabaseConnection,
+    opts: CaptureOptions,
+    clock: SharedClock,
+    snapshot_ttl: Duration,
+    snapshot: RwLock<Option<(Instant, Snapshot)>>,
+}
+
+impl CaptureService {
+    pub fn new(db: DatabaseConnection, opts: CaptureOptions) -> Self {
+        Self { db, opts, clock: system_clock(), snapshot_ttl: Duration::from_secs(30), snapshot: RwLock::new(None) }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_snapshot_ttl(mut self, ttl: Duration) -> Self {
+        self.snapshot_ttl = ttl;
+        self
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.opts.enabled
+    }
+
+    pub async fn list(&self) -> Result<Vec<route_capture::Model>, ServiceError> {
+        route_capture::Entity::find().order_by_asc(route_capture::Column::UpdatedAt).all(&self.db).await.map_err(dbe)
+    }
+
+    pub async fn get(&self, route_id: Uuid) -> Result<route_capture::Model, ServiceError> {
+        route_capture::Entity::find_by_id(route_id).one(&self.db).await.map_err(dbe)?.ok_or_else(|| ServiceError::not_found("route capture"))
+    }
+
+    /// Create or replace the route's capture settings.
+    pub async fn set(&self, route_id: Uuid, input: SetCaptureInput) -> Result<route_capture::Model, ServiceError> {
+        if !(0..=100).contains(&input.sample_percent) {
+            return Err(ServiceError::Validation("sample_percent must be between 0 and 100".into()));
+        }
+        let max_bytes = input.max_bytes.unwrap_or(self.opts.default_max_bytes as i32);
+        if max_bytes < 1 || max_bytes as i64 > i64::from(self.opts.max_bytes_limit) {
+            return Err(ServiceError::Validation(format!("max_bytes must be between 1 and {}", self.opts.max_bytes_limit)));
+        }
+        route_service::get_route(&self.db, route_id).await?.ok_or_else(|| ServiceError::not_found("route"))?;
+        let am = route_capture::ActiveModel {
+            route_id: Set(route_id),
+            sample_percent: Set(input.sample_percent),
+            max_bytes: Set(max_bytes),
+            capture_response: Set(input.capture_response),
+            updated_at: Set(self.clock.now_fixed()),
+        };
+        route_capture::Entity::insert(am)
+            .on_conflict(
+                OnConflict::column(route_capture::Column::RouteId)
+                    .update_columns([
+                        route_capture::Column::SamplePercent,
+                        route_capture::Column::MaxBytes,
+                        route_capture::Column::CaptureResponse,
+                        route_capture::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .map_err(dbe)?;
+        let saved = self.get(route_id).await?;
+        info!(%route_id, sample_percent = saved.sample_percent, max_bytes = saved.max_bytes, capture_response = saved.capture_response, "route_capture_set");
+        self.invalidate().await;
+        Ok(saved)
+    }
+
+    pub async fn remove(&self, route_id: Uuid) -> Result<(), ServiceError> {
+        let res = route_capture::Entity::delete_by_id(route_id).exec(&self.db).await.map_err(dbe)?;
+        if res.rows_affected == 0 {
+            return Err(ServiceError::not_found("route capture"));
+        }
+        info!(%route_id, "route_capture_removed");
+        self.invalidate().await;
+        Ok(())
+    }
+
+    async fn invalidate(&self) {
+        *self.snapshot.write().await = None;
+    }
+
+    async fn settings(&self) -> Result<Snapshot, ServiceError> {
+        if let Some((at, snap)) = self.snapshot.read().await.as_ref() {
+            if at.elapsed() < self.snapshot_ttl {
+                return Ok(Arc::clone(snap));
+            }
+        }
+        let rows = route_capture::Entity::find().all(&self.db).await.map_err(dbe)?;
+        let snap: Snapshot = Arc::new(rows.into_iter().map(|r| (r.route_id, r)).collect());
+        *self.snapshot.write().await = Some((Instant::now(), Arc::clone(&snap)));
+        Ok(snap)
+    }
+
+    /// Sample the exchange by the route's settings and store it for `log`; returns whether it was stored.
+    pub async fn capture(&self, log: &request_log::Model, exchange: &Exchange<'_>) -> Result<bool, ServiceError> {
+        if !self.opts.enabled {
+            return Ok(false);
+        }
+        let snap = self.settings().await?;
+        let Some(settings) = snap.get(&log.route_id) else { return Ok(false) };
+        if !sampled(settings.sample_percent, rand::thread_rng().gen_range(0..100)) {
+            return Ok(false);
+        }
+        let payload = build_payload(log.id, settings, exchange, self.clock.now_fixed());
+        if payload.request_truncated || payload.response_truncated {
+            warn!(request_log_id = log.id, route_id = %log.route_id, max_bytes = settings.max_bytes, "captured body truncated");
+        }
+        request_log_service::save_payload(&self.db, payload).await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(max_bytes: i32, capture_response: bool) -> route_capture::Model {
+        route_capture::Model { route_id: Uuid::nil(), sample_percent: 100, max_bytes, capture_response, updated_at: chrono::Utc::now().into() }
+    }
+
+    #[test]
+    fn sampling_respects_percent_bounds() {
+        assert!(!sampled(0, 0));
+        assert!(sampled(1, 0) && !sampled(1, 1));
+        assert!((0..100).all(|roll| sampled(100, roll)));
+        assert_eq!((0..100).filter(|&roll| sampled(25, roll)).count(), 25);
+        assert!(!sampled(-5, 0) && sampled(250, 99));
+    }
+
+    #[test]
+    fn payload_is_redacted_before_truncation() {
+        let mut req_headers = HeaderMap::new();
+        req_headers.insert("authorization", "Bearer secret".parse().unwrap());
+        req_headers.insert("content-type", "application/json".parse().unwrap());
+        let resp_headers = HeaderMap::new();
+        let exchange = Exchange {
+            method: "POST",
+            uri: "/orders?token=abc&page=2",
+            request_headers: &req_headers,
+            request_body: br#"{"password":"hunter2","item":"book"}"#,
+            response: Some(CapturedResponse { status: 201, headers: &resp_headers, body: b"created" }),
+        };
+        let p = build_payload(7, &settings(1024, true), &exchange, chrono::Utc::now().into());
+        assert_eq!(p.uri, "/orders?token=[REDACTED]&page=2");
+        let headers = p.request_header_map();
+        assert_eq!((headers["authorization"].as_str(), headers["content-type"].as_str()), ("[REDACTED]", "application/json"));
+        let body = String::from_utf8(p.request_body.clone().unwrap()).unwrap();
+        assert!(!body.contains("hunter2") && body.contains("book"));
+        assert_eq!((p.response_status, p.response_body.as_deref()), (Some(201), Some(&b"created"[..])));
+
+        // the cut happens after redaction, so even a prefix never contains the secret
+        let p = build_payload(7, &settings(16, false), &exchange, chrono::Utc::now().into());
+        assert!(p.request_truncated && p.request_body.as_ref().unwrap().len() == 16);
+        assert!(!String::from_utf8_lossy(p.request_body.as_ref().unwrap()).contains("hunter2"));
+        assert_eq!((p.response_status, p.response_body, p.response_truncated), (None, None, false));
+    }
+}
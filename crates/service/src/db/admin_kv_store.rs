@@ -0,0 +1,112 @@
+//! Database-backed admin key store (`admin_api_key` table); shared by all server replicas.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::pagination::{Page, Pagination};
+use common::time::{system_clock, SharedClock};
+use models::admin_api_key::{self, Entity as AdminApiKeyEntity};
+use sea_orm::{sea_query::OnConflict, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set};
+use tracing::error;
+
+use crate::admin::kv_store::AdminKvStore;
+use crate::errors::ServiceError;
+use crate::storage::health::{StoreHealth, WriteTracker};
+
+const STORE: &str = "admin_api_key";
+
+pub struct DbApiKeysStore {
+    db: DatabaseConnection,
+    clock: SharedClock,
+    writes: WriteTracker,
+}
+
+impl DbApiKeysStore {
+    pub fn new(db: DatabaseConnection) -> Arc<Self> {
+        Arc::new(Self { db, clock: system_clock(), writes: WriteTracker::default() })
+    }
+}
+
+#[async_trait]
+impl AdminKvStore for DbApiKeysStore {
+    async fn list(&self) -> Vec<(String, String)> {
+        match AdminApiKeyEntity::find().all(&self.db).await {
+            Ok(rows) => rows.into_iter().map(|r| (r.user_name, r.api_key)).collect(),
+            Err(e) => {
+                error!(store = STORE, error = %e, "list admin api keys failed");
+                Vec::new()
+            }
+        }
+    }
+
+    async fn list_page(&self, opts: Pagination) -> Result<Page<(String, String)>, ServiceError> {
+        let select = AdminApiKeyEntity::find().order_by_asc(admin_api_key::Column::UserName);
+        Ok(crate::db::fetch_page(&self.db, select, opts).await?.map(|r| (r.user_name, r.api_key)))
+    }
+
+    async fn set(&self, user: String, api_key: String) -> Result<(), ServiceError> {
+        let now = self.clock.now();
+        let row = admin_api_key::ActiveModel {
+            user_name: Set(user),
+            api_key: Set(api_key),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        };
+        let res = AdminApiKeyEntity::insert(row)
+            .on_conflict(
+                OnConflict::column(admin_api_key::Column::UserName)
+                    .update_columns([admin_api_key::Column::ApiKey, admin_api_key::Column::UpdatedAt])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await;
+        self.writes.record(STORE, res).map(|_| ())
+    }
+
+    async fn delete(&self, user: &str) -> Result<bool, ServiceError> {
+        let res = AdminApiKeyEntity::delete_by_id(user.to_string()).exec(&self.db).await;
+        self.writes.record(STORE, res).map(|r| r.rows_affected > 0)
+    }
+
+    /// 查询失败时视为不存在（拒绝访问）
+    async fn contains_value(&self, value: &str) -> bool {
+        match AdminApiKeyEntity::find().filter(admin_api_key::Column::ApiKey.eq(value)).count(&self.db).await {
+            Ok(n) => n > 0,
+            Err(e) => {
+                error!(store = STORE, error = %e, "admin api key lookup failed");
+                false
+            }
+        }
+    }
+
+    async fn health(&self) -> StoreHealth {
+        let entries = AdminApiKeyEntity::find().count(&self.db).await.ok().map(|n| n as usize);
+        self.writes.health(STORE, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::get_db;
+
+    #[tokio::test]
+    async fn db_admin_kv_store_crud() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let store = DbApiKeysStore::new(db);
+        let user = format!("svc_admin_{}", uuid::Uuid::new_v4());
+        let key = format!("key_{}", uuid::Uuid::new_v4());
+
+        store.set(user.clone(), "old".into()).await?;
+        store.set(user.clone(), key.clone()).await?;
+        assert!(store.contains_value(&key).await);
+        assert!(store.list().await.contains(&(user.clone(), key.clone())));
+
+        assert!(store.delete(&user).await?);
+        assert!(!store.delete(&user).await?);
+        assert!(!store.contains_value(&key).await);
+        assert!(store.health().await.writable);
+        Ok(())
+    }
+}
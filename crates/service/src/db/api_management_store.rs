@@ -0,0 +1,156 @@
+//! Database-backed API management store (`managed_api` table); shared by all server replicas.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::pagination::{Page, Pagination};
+use common::time::{system_clock, SharedClock};
+use models::managed_api::{self, Entity as ManagedApiEntity};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryOrder, Set};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::admin::api_mgmt_store::{ApiManagementStore, ApiRecord, ApiRecordInput, AuthInfo};
+use crate::errors::ServiceError;
+use crate::storage::health::{StoreHealth, WriteTracker};
+
+const STORE: &str = "managed_api";
+
+pub struct DbApiStore {
+    db: DatabaseConnection,
+    clock: SharedClock,
+    writes: WriteTracker,
+}
+
+impl DbApiStore {
+    pub fn new(db: DatabaseConnection) -> Arc<Self> {
+        Self::new_with_clock(db, system_clock())
+    }
+
+    /// 指定时间源初始化（测试中可注入 MockClock）
+    pub fn new_with_clock(db: DatabaseConnection, clock: SharedClock) -> Arc<Self> {
+        Arc::new(Self { db, clock, writes: WriteTracker::default() })
+    }
+}
+
+impl DbApiStore {
+    /// 按原 id 与创建时间写入（从文件存储导入时使用）
+    pub async fn insert_existing(&self, rec: ApiRecord) -> Result<(), ServiceError> {
+        let row = managed_api::ActiveModel {
+            id: Set(rec.id),
+            endpoint_url: Set(rec.endpoint_url),
+            method: Set(rec.method),
+            forward_target: Set(rec.forward_target),
+            require_api_key: Set(rec.auth.require_api_key),
+            created_at: Set(rec.created_at.into()),
+        };
+        let res = row.insert(&self.db).await;
+        self.writes.record(STORE, res).map(|_| ())
+    }
+}
+
+fn to_record(m: managed_api::Model) -> ApiRecord {
+    ApiRecord {
+        id: m.id,
+        endpoint_url: m.endpoint_url,
+        method: m.method,
+        forward_target: m.forward_target,
+        auth: AuthInfo { require_api_key: m.require_api_key },
+        created_at: m.created_at.into(),
+    }
+}
+
+#[async_trait]
+impl ApiManagementStore for DbApiStore {
+    async fn list(&self) -> Vec<ApiRecord> {
+        match ManagedApiEntity::find().order_by_asc(managed_api::Column::CreatedAt).all(&self.db).await {
+            Ok(rows) => rows.into_iter().map(to_record).collect(),
+            Err(e) => {
+                error!(store = STORE, error = %e, "list managed apis failed");
+                Vec::new()
+            }
+        }
+    }
+
+    async fn list_page(&self, opts: Pagination) -> Result<Page<ApiRecord>, ServiceError> {
+        let select = ManagedApiEntity::find().order_by_asc(managed_api::Column::CreatedAt).order_by_asc(managed_api::Column::Id);
+        Ok(crate::db::fetch_page(&self.db, select, opts).await?.map(to_record))
+    }
+
+    async fn get(&self, id: Uuid) -> Option<ApiRecord> {
+        match ManagedApiEntity::find_by_id(id).one(&self.db).await {
+            Ok(row) => row.map(to_record),
+            Err(e) => {
+                error!(store = STORE, error = %e, %id, "get managed api failed");
+                None
+            }
+        }
+    }
+
+    async fn create(&self, input: ApiRecordInput) -> Result<ApiRecord, ServiceError> {
+        input.validate()?;
+        let row = managed_api::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            endpoint_url: Set(input.endpoint_url),
+            method: Set(input.method.to_ascii_uppercase()),
+            forward_target: Set(input.forward_target),
+            require_api_key: Set(input.auth.require_api_key),
+            created_at: Set(self.clock.now().into()),
+        };
+        let res = row.insert(&self.db).await;
+        self.writes.record(STORE, res).map(to_record)
+    }
+
+    async fn update(&self, id: Uuid, input: ApiRecordInput) -> Result<ApiRecord, ServiceError> {
+        input.validate()?;
+        let found = ManagedApiEntity::find_by_id(id).one(&self.db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+        let mut row: managed_api::ActiveModel = found.ok_or_else(|| ServiceError::not_found("api"))?.into();
+        row.endpoint_url = Set(input.endpoint_url);
+        row.method = Set(input.method.to_ascii_uppercase());
+        row.forward_target = Set(input.forward_target);
+        row.require_api_key = Set(input.auth.require_api_key);
+        let res = row.update(&self.db).await;
+        self.writes.record(STORE, res).map(to_record)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, ServiceError> {
+        let res = ManagedApiEntity::delete_by_id(id).exec(&self.db).await;
+        self.writes.record(STORE, res).map(|r| r.rows_affected > 0)
+    }
+
+    async fn health(&self) -> StoreHealth {
+        let entries = ManagedApiEntity::find().count(&self.db).await.ok().map(|n| n as usize);
+        self.writes.health(STORE, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::get_db;
+
+    #[tokio::test]
+    async fn db_api_store_crud_and_validation() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let store = DbApiStore::new(db);
+        let input = ApiRecordInput {
+            endpoint_url: "/db/orders".into(),
+            method: "get".into(),
+            forward_target: "http://127.0.0.1:9099".into(),
+            auth: AuthInfo { require_api_key: true },
+        };
+        let created = store.create(input.clone()).await?;
+        assert_eq!(created.method, "GET");
+        assert_eq!(store.get(created.id).await, Some(created.clone()));
+
+        let updated = store.update(created.id, ApiRecordInput { method: "POST".into(), ..input.clone() }).await?;
+        assert_eq!((updated.method.as_str(), updated.created_at), ("POST", created.created_at));
+        assert!(matches!(store.update(Uuid::new_v4(), input.clone()).await, Err(ServiceError::NotFound(_))));
+        assert!(matches!(store.create(ApiRecordInput { endpoint_url: "orders".into(), ..input }).await, Err(ServiceError::Validation(_))));
+
+        assert!(store.delete(created.id).await?);
+        assert!(store.get(created.id).await.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use argon2::{password_hash::{PasswordHasher, SaltString}, Argon2};
+use common::pagination::{Pagination, DEFAULT_MAX_PER_PAGE};
+use rand::rngs::OsRng;
+use uuid::Uuid;
+use sea_orm::{DatabaseConnection, EntityTrait};
+use models::apikey;
+use crate::{errors::ServiceError};
+
+/// Salted argon2 hash stored in `api_key.key_hash`; the plaintext key is shown to the user once.
+pub fn hash_api_key(key: &str) -> Result<String, ServiceError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(key.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| ServiceError::Validation(e.to_string()))
+}
+
+/// Create API key for a user.
+pub async fn create_api_key(db: &DatabaseConnection, user_id: Uuid, key_hash: &str) -> Result<apikey::Model, ServiceError> {
+    Ok(apikey::create(db, user_id, key_hash).await?)
+}
+
+/// Get API key by id.
+pub async fn get_api_key(db: &DatabaseConnection, id: Uuid) -> Result<Option<apikey::Model>, ServiceError> {
+    apikey::Entity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// Look up an API key by its hash (data-plane key check).
+pub async fn find_api_key_by_hash(db: &DatabaseConnection, key_hash: &str) -> Result<Option<apikey::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    apikey::Entity::find()
+        .filter(apikey::Column::KeyHash.eq(key_hash))
+        .one(db)
+        .await
+        .map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// Delete API key.
+pub async fn delete_api_key(db: &DatabaseConnection, id: Uuid) -> Result<(), ServiceError> {
+    apikey::Entity::delete_by_id(id).exec(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(())
+}
+
+/// Set or clear the expiry of an API key.
+pub async fn set_api_key_expiry(db: &DatabaseConnection, id: Uuid, expires_at: Option<chrono::DateTime<chrono::Utc>>) -> Result<apikey::Model, ServiceError> {
+    use sea_orm::{ActiveModelTrait, IntoActiveModel, Set};
+    let key = get_api_key(db, id).await?.ok_or_else(|| ServiceError::not_found("api_key"))?;
+    let mut am = key.into_active_model();
+    am.expires_at = Set(expires_at.map(|t| t.fixed_offset()));
+    am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// Move `last_used_at` forward to `at`; never moves it back, so out-of-order flushes are harmless.
+/// Returns whether the key was updated.
+pub async fn touch_api_key_last_used(db: &DatabaseConnection, id: Uuid, at: chrono::DateTime<chrono::Utc>) -> Result<bool, ServiceError> {
+    use sea_orm::{sea_query::{Condition, Expr}, ColumnTrait, QueryFilter};
+    let at = at.fixed_offset();
+    let res = apikey::Entity::update_many()
+        .col_expr(apikey::Column::LastUsedAt, Expr::value(at))
+        .filter(apikey::Column::Id.eq(id))
+        .filter(Condition::any().add(apikey::Column::LastUsedAt.is_null()).add(apikey::Column::LastUsedAt.lt(at)))
+        .exec(db)
+        .await
+        .map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(res.rows_affected > 0)
+}
+
+/// List API keys for user.
+pub async fn list_api_keys_by_user(db: &DatabaseConnection, user_id: Uuid) -> Result<Vec<apikey::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let keys = apikey::Entity::find().filter(apikey::Column::UserId.eq(user_id))
+        .all(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(keys)
+}
+
+/// List API keys by user with pagination.
+pub async fn list_api_keys_by_user_paginated(db: &DatabaseConnection, user_id: Uuid, opts: Pagination) -> Result<Vec<apikey::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
+    let (page_idx, per_page) = opts.normalize(DEFAULT_MAX_PER_PAGE);
+    let rows = apikey::Entity::find()
+        .filter(apikey::Column::UserId.eq(user_id))
+        .paginate(db, per_page)
+        .fetch_page(page_idx)
+        .await
+        .map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::{tenant, user};
+    use crate::test_support::get_db;
+
+    #[tokio::test]
+    async fn apikey_crud_service() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+
+        let t = tenant::create(&db, &format!("svc_apikey_tenant_{}", Uuid::new_v4())).await?;
+        let u = user::create(&db, t.id, &format!("svc_{}@example.com", Uuid::new_v4()), "User").await?;
+
+        let key = create_api_key(&db, u.id, "0123456789abcd").await?;
+        let got = get_api_key(&db, key.id).await?.unwrap();
+        assert_eq!(got.id, key.id);
+        assert!(find_api_key_by_hash(&db, "0123456789abcd").await?.is_some());
+
+        let used = chrono::Utc::now();
+        assert!(touch_api_key_last_used(&db, key.id, used).await?);
+        assert!(!touch_api_key_last_used(&db, key.id, used - chrono::Duration::minutes(5)).await?);
+        assert_eq!(get_api_key(&db, key.id).await?.unwrap().last_used_at.map(|t| t.timestamp()), Some(used.timestamp()));
+
+        let listed = list_api_keys_by_user(&db, u.id).await?;
+        assert!(listed.iter().any(|k| k.id == key.id));
+
+        delete_api_key(&db, key.id).await?;
+        let after = get_api_key(&db, key.id).await?;
+        assert!(after.is_none());
+
+        user::hard_delete(&db, u.id).await?;
+        tenant::Entity::delete_by_id(t.id).exec(&db).await?;
+        Ok(())
+    }
+}
\ No newline at end of file
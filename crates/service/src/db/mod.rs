@@ -2,6 +2,74 @@ pub mod tenant_service;
 pub mod user_service;
 pub mod upstream_service;
 pub mod route_service;
+pub mod apikey_service;
 pub mod request_log_service;
 pub mod ratelimit_service;
-pub mod proxy_api_service;
\ No newline at end of file
+pub mod proxy_api_service;
+pub mod admin_kv_store;
+pub mod api_management_store;
+use common::pagination::{ensure_in_range, Anchor, Page, Pagination, PaginationError};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select};
+use uuid::Uuid;
+
+use crate::errors::ServiceError;
+
+/// Fetch one page of `select`: LIMIT/OFFSET plus a COUNT for totals and out-of-range detection.
+/// The `per_page` upper bound is enforced where the request is parsed (`pagination.max_per_page`).
+pub(crate) async fn fetch_page<E>(db: &DatabaseConnection, select: Select<E>, opts: Pagination) -> Result<Page<E::Model>, ServiceError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let (page_idx, per_page) = opts.validate(u32::MAX)?;
+    let paginator = select.paginate(db, per_page);
+    let total = paginator.num_items().await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    ensure_in_range(opts, total)?;
+    let items = paginator.fetch_page(page_idx).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(Page::new(items, opts, total))
+}
+
+/// Keyset variant of [`fetch_page`] for lists ordered newest first by `(created_at, id)`.
+/// A cursor anchor resumes strictly after the last row already returned, so inserts at
+/// the head do not shift later pages; without one the page number is used as an offset.
+pub(crate) async fn fetch_keyset_page<E>(
+    db: &DatabaseConnection,
+    select: Select<E>,
+    created_at: E::Column,
+    id: E::Column,
+    opts: Pagination,
+    anchor: impl Fn(&E::Model) -> Anchor,
+) -> Result<Page<E::Model>, ServiceError>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let (page_idx, per_page) = opts.validate(u32::MAX)?;
+    let total = select.clone().count(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    let mut select = select.order_by_desc(created_at).order_by_desc(id);
+    match opts.after {
+        Some(after) => {
+            let at: DateTimeWithTimeZone = chrono::DateTime::from_timestamp_micros(after.at_micros)
+                .ok_or_else(|| PaginationError::InvalidCursor(after.at_micros.to_string()))?
+                .fixed_offset();
+            let last_id = Uuid::from_u128(after.id);
+            select = select.filter(
+                Condition::any()
+                    .add(created_at.lt(at))
+                    .add(Condition::all().add(created_at.eq(at)).add(id.lt(last_id))),
+            );
+        }
+        None => {
+            ensure_in_range(opts, total)?;
+            select = select.offset(page_idx * per_page);
+        }
+    }
+    let items = select.limit(per_page + 1).all(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(Page::keyset(items, opts, total, anchor))
+}
+
+/// Keyset anchor of a row: `created_at` in microseconds plus the id as tie-breaker.
+pub(crate) fn anchor_of(created_at: &DateTimeWithTimeZone, id: Uuid) -> Anchor {
+    Anchor { at_micros: created_at.timestamp_micros(), id: id.as_u128() }
+}
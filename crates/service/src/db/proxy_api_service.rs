@@ -1,8 +1,11 @@
-use sea_orm::{DatabaseConnection, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait};
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, ActiveModelTrait, Set, QueryFilter, QueryOrder, ColumnTrait};
 use uuid::Uuid;
 use chrono::Utc;
+use common::pagination::{Page, Pagination};
 use models::proxy_api::{self, Entity as ProxyApiEntity};
 use crate::errors::ServiceError;
+use crate::proxy_api::maintenance::MaintenanceWindow;
+use crate::proxy_api::mock::MockResponse;
 
 /// List proxy APIs, optionally filtered by tenant.
 pub async fn list_proxy_apis(db: &DatabaseConnection, tenant_id: Option<Uuid>) -> Result<Vec<proxy_api::Model>, ServiceError> {
@@ -12,6 +15,13 @@ pub async fn list_proxy_apis(db: &DatabaseConnection, tenant_id: Option<Uuid>) -
     Ok(rows)
 }
 
+/// List proxy APIs with pagination, oldest first.
+pub async fn list_proxy_apis_paginated(db: &DatabaseConnection, tenant_id: Option<Uuid>, opts: Pagination) -> Result<Page<proxy_api::Model>, ServiceError> {
+    let mut finder = ProxyApiEntity::find().order_by_asc(proxy_api::Column::CreatedAt).order_by_asc(proxy_api::Column::Id);
+    if let Some(tid) = tenant_id { finder = finder.filter(proxy_api::Column::TenantId.eq(tid)); }
+    crate::db::fetch_page(db, finder, opts).await
+}
+
 /// Create a proxy API after validation.
 pub async fn create_proxy_api(
     db: &DatabaseConnection,
@@ -33,8 +43,8 @@ pub async fn get_proxy_api(db: &DatabaseConnection, id: Uuid) -> Result<Option<p
 }
 
 /// Update a proxy API with optional fields and validations.
-pub async fn update_proxy_api(
-    db: &DatabaseConnection,
+pub async fn update_proxy_api<C: ConnectionTrait>(
+    db: &C,
     id: Uuid,
     endpoint_url: Option<&str>,
     method: Option<&str>,
@@ -55,8 +65,53 @@ pub async fn update_proxy_api(
     Ok(updated)
 }
 
+/// Enable/disable mock mode; `mock` replaces the stored response when given.
+pub async fn set_proxy_api_mock<C: ConnectionTrait>(db: &C, id: Uuid, enabled: bool, mock: Option<MockResponse>) -> Result<proxy_api::Model, ServiceError> {
+    let existing = ProxyApiEntity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("proxy_api"))?;
+    if enabled && mock.is_none() && existing.mock_status.is_none() {
+        return Err(ServiceError::Validation("mock response required to enable mock mode".into()));
+    }
+    let mut am: proxy_api::ActiveModel = existing.into();
+    if let Some(m) = mock {
+        m.validate()?;
+        am.mock_status = Set(Some(m.status as i32));
+        am.mock_headers = Set(Some(serde_json::to_string(&m.headers).map_err(|e| ServiceError::Validation(e.to_string()))?));
+        am.mock_body = Set(Some(m.body));
+    }
+    am.mock_enabled = Set(enabled);
+    am.updated_at = Set(Utc::now().into());
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
+/// Replace the maintenance window and payload of a proxy API.
+pub async fn set_proxy_api_maintenance<C: ConnectionTrait>(db: &C, id: Uuid, window: MaintenanceWindow) -> Result<proxy_api::Model, ServiceError> {
+    window.validate()?;
+    let mut am: proxy_api::ActiveModel = ProxyApiEntity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("proxy_api"))?
+        .into();
+    am.maintenance_start = Set(window.start.map(Into::into));
+    am.maintenance_end = Set(window.end.map(Into::into));
+    am.maintenance_message = Set(window.message);
+    am.updated_at = Set(Utc::now().into());
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
+/// Set or clear the gRPC method a proxy API is transcoded to.
+pub async fn set_proxy_api_grpc_method<C: ConnectionTrait>(db: &C, id: Uuid, grpc_method: Option<String>) -> Result<proxy_api::Model, ServiceError> {
+    let mut am: proxy_api::ActiveModel = ProxyApiEntity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("proxy_api"))?
+        .into();
+    am.grpc_method = Set(grpc_method);
+    am.updated_at = Set(Utc::now().into());
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
 /// Delete a proxy API; returns true if deleted.
-pub async fn delete_proxy_api(db: &DatabaseConnection, id: Uuid) -> Result<bool, ServiceError> {
+pub async fn delete_proxy_api<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, ServiceError> {
     let res = ProxyApiEntity::delete_by_id(id).exec(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
     Ok(res.rows_affected > 0)
 }
@@ -70,7 +125,7 @@ mod tests {
     #[tokio::test]
     async fn proxy_api_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let t = tenant::create(&db, &format!("svc_proxy_tenant_{}", Uuid::new_v4())).await?;
 
@@ -85,6 +140,20 @@ mod tests {
         assert!(updated.require_api_key);
         assert!(!updated.enabled);
 
+        assert!(set_proxy_api_mock(&db, a.id, true, None).await.is_err());
+        let mock = MockResponse { status: 503, headers: Default::default(), body: "down for {{method}}".into() };
+        let mocked = set_proxy_api_mock(&db, a.id, true, Some(mock.clone())).await?;
+        assert_eq!(MockResponse::from_model(&mocked), Some(mock));
+        let unmocked = set_proxy_api_mock(&db, a.id, false, None).await?;
+        assert!(MockResponse::from_model(&unmocked).is_none());
+        assert_eq!(unmocked.mock_status, Some(503));
+
+        let start = Utc::now();
+        let window = MaintenanceWindow { start: Some(start), end: Some(start + chrono::Duration::hours(1)), message: Some("back soon".into()) };
+        let m = set_proxy_api_maintenance(&db, a.id, window).await?;
+        assert_eq!(m.maintenance_message.as_deref(), Some("back soon"));
+        assert!(set_proxy_api_maintenance(&db, a.id, MaintenanceWindow { start: Some(start), end: Some(start), message: None }).await.is_err());
+
         let list_all = list_proxy_apis(&db, None).await?;
         assert!(!list_all.is_empty());
         let list_tenant = list_proxy_apis(&db, Some(t.id)).await?;
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 use sea_orm::{DatabaseConnection, ActiveModelTrait, EntityTrait, Set};
 use chrono::Utc;
 use models::ratelimit;
 use crate::{errors::ServiceError};
-use common::pagination::Pagination;
+use common::pagination::{Page, Pagination};
+use common::rate_limit::Algorithm;
 
 /// Create a rate limit.
 pub async fn create_rate_limit(db: &DatabaseConnection, tenant_id: Option<Uuid>, requests_per_minute: i32, burst: i32) -> Result<ratelimit::Model, ServiceError> {
@@ -13,6 +16,8 @@ pub async fn create_rate_limit(db: &DatabaseConnection, tenant_id: Option<Uuid>,
         requests_per_minute: Set(requests_per_minute),
         burst: Set(burst),
         created_at: Set(Utc::now().into()),
+        spike_arrest_interval_ms: Set(None),
+        algorithm: Set(Algorithm::TokenBucket.as_str().to_string()),
     };
     Ok(am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
@@ -22,8 +27,9 @@ pub async fn get_rate_limit(db: &DatabaseConnection, id: Uuid) -> Result<Option<
     Ok(ratelimit::Entity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
 
-/// Update rate limit.
-pub async fn update_rate_limit(db: &DatabaseConnection, id: Uuid, requests_per_minute: Option<i32>, burst: Option<i32>, tenant_id: Option<Option<Uuid>>) -> Result<ratelimit::Model, ServiceError> {
+/// Update rate limit. `spike_arrest_interval_ms`: `Some(None)` disables spike arrest.
+/// `algorithm` switches between the token bucket and the strict per-minute window algorithms.
+pub async fn update_rate_limit(db: &DatabaseConnection, id: Uuid, requests_per_minute: Option<i32>, burst: Option<i32>, tenant_id: Option<Option<Uuid>>, spike_arrest_interval_ms: Option<Option<i32>>, algorithm: Option<Algorithm>) -> Result<ratelimit::Model, ServiceError> {
     let mut am: ratelimit::ActiveModel = ratelimit::Entity::find_by_id(id)
         .one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
         .ok_or_else(|| ServiceError::not_found("rate_limit"))?
@@ -37,6 +43,11 @@ pub async fn update_rate_limit(db: &DatabaseConnection, id: Uuid, requests_per_m
         am.burst = Set(b);
     }
     if let Some(t) = tenant_id { am.tenant_id = Set(t); }
+    if let Some(s) = spike_arrest_interval_ms {
+        if matches!(s, Some(ms) if ms <= 0) { return Err(ServiceError::Validation("spike_arrest_interval_ms must be > 0".into())); }
+        am.spike_arrest_interval_ms = Set(s);
+    }
+    if let Some(a) = algorithm { am.algorithm = Set(a.as_str().to_string()); }
     Ok(am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
 
@@ -47,16 +58,53 @@ pub async fn delete_rate_limit(db: &DatabaseConnection, id: Uuid) -> Result<(),
 }
 
 /// List rate limits by tenant with pagination.
-pub async fn list_rate_limits_by_tenant_paginated(db: &DatabaseConnection, tenant_id: Uuid, opts: Pagination) -> Result<Vec<ratelimit::Model>, ServiceError> {
-    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
-    let (page_idx, per_page) = opts.normalize();
-    let rows = ratelimit::Entity::find()
-        .filter(ratelimit::Column::TenantId.eq(tenant_id))
-        .paginate(db, per_page)
-        .fetch_page(page_idx)
-        .await
-        .map_err(|e| ServiceError::Db(e.to_string()))?;
-    Ok(rows)
+pub async fn list_rate_limits_by_tenant_paginated(db: &DatabaseConnection, tenant_id: Uuid, opts: Pagination) -> Result<Page<ratelimit::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let select = ratelimit::Entity::find().filter(ratelimit::Column::TenantId.eq(tenant_id));
+    crate::db::fetch_page(db, select, opts).await
+}
+
+/// Spike arrest interval per tenant, as enforced by the gateway. A tenant with several records gets the longest interval.
+pub async fn spike_arrest_intervals_by_tenant(db: &DatabaseConnection) -> Result<HashMap<Uuid, Duration>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let records = ratelimit::Entity::find()
+        .filter(ratelimit::Column::TenantId.is_not_null())
+        .filter(ratelimit::Column::SpikeArrestIntervalMs.is_not_null())
+        .all(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    let mut intervals: HashMap<Uuid, Duration> = HashMap::new();
+    for r in records {
+        if let (Some(tenant_id), Some(interval)) = (r.tenant_id, r.spike_arrest_interval()) {
+            let entry = intervals.entry(tenant_id).or_insert(interval);
+            *entry = (*entry).max(interval);
+        }
+    }
+    Ok(intervals)
+}
+
+/// Connect to `database_url` and pass [`spike_arrest_intervals_by_tenant`] to `apply` every `interval` until the future is dropped.
+/// A failed refresh keeps the previous intervals.
+pub async fn sync_spike_arrest_intervals(database_url: &str, interval: Duration, apply: impl Fn(HashMap<Uuid, Duration>)) {
+    let db = loop {
+        match sea_orm::Database::connect(database_url).await {
+            Ok(db) => break db,
+            Err(e) => {
+                tracing::warn!(err = %e, "rate limit records: database unavailable, retrying");
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+        }
+    };
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        match spike_arrest_intervals_by_tenant(&db).await {
+            Ok(intervals) => {
+                tracing::debug!(tenants = intervals.len(), "tenant spike arrest intervals refreshed");
+                apply(intervals);
+            }
+            Err(e) => tracing::warn!(err = %e, "failed to load rate limit records"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -68,16 +116,27 @@ mod tests {
     #[tokio::test]
     async fn ratelimit_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let t = tenant::create(&db, &format!("svc_rl_tenant_{}", Uuid::new_v4())).await?;
         let rl = create_rate_limit(&db, Some(t.id), 60, 10).await?;
         let found = get_rate_limit(&db, rl.id).await?.unwrap();
         assert_eq!(found.requests_per_minute, 60);
 
-        let updated = update_rate_limit(&db, rl.id, Some(120), Some(20), Some(Some(t.id))).await?;
+        assert_eq!(found.algorithm_kind(), Algorithm::TokenBucket);
+
+        let updated = update_rate_limit(&db, rl.id, Some(120), Some(20), Some(Some(t.id)), Some(Some(10)), Some(Algorithm::SlidingWindow)).await?;
         assert_eq!(updated.requests_per_minute, 120);
         assert_eq!(updated.burst, 20);
+        assert_eq!(updated.spike_arrest_interval_ms, Some(10));
+        assert_eq!(updated.algorithm, "sliding_window");
+        assert!(update_rate_limit(&db, rl.id, None, None, None, Some(Some(0)), None).await.is_err());
+
+        let stricter = create_rate_limit(&db, Some(t.id), 60, 10).await?;
+        update_rate_limit(&db, stricter.id, None, None, None, Some(Some(25)), None).await?;
+        let intervals = spike_arrest_intervals_by_tenant(&db).await?;
+        assert_eq!(intervals.get(&t.id), Some(&Duration::from_millis(25)));
+        delete_rate_limit(&db, stricter.id).await?;
 
         delete_rate_limit(&db, rl.id).await?;
         let after = get_rate_limit(&db, rl.id).await?;
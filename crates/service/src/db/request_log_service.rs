@@ -3,9 +3,9 @@ use sea_orm::{DatabaseConnection, ActiveModelTrait, EntityTrait, Set};
 use chrono::Utc;
 use models::request_log;
 use crate::{errors::ServiceError};
-use common::pagination::Pagination;
+use common::pagination::{Page, Pagination};
 
-/// Create a request log entry.
+/// Create a request log entry. `error_message` is redacted before it is stored.
 pub async fn create_request_log(
     db: &DatabaseConnection,
     route_id: Uuid,
@@ -15,6 +15,9 @@ pub async fn create_request_log(
     success: bool,
     error_message: Option<String>,
     client_ip: Option<String>,
+    config_revision: Option<i64>,
+    country: Option<String>,
+    asn: Option<i64>,
 ) -> Result<request_log::Model, ServiceError> {
     let am = request_log::ActiveModel {
         id: Set(0), // auto-increment by DB
@@ -23,9 +26,12 @@ pub async fn create_request_log(
         status_code: Set(status_code),
         latency_ms: Set(latency_ms),
         success: Set(success),
-        error_message: Set(error_message),
+        error_message: Set(error_message.map(|m| common::redaction::global().redact_text(&m))),
         client_ip: Set(client_ip),
         timestamp: Set(Utc::now().into()),
+        config_revision: Set(config_revision),
+        country: Set(country),
+        asn: Set(asn),
     };
     Ok(am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
@@ -42,16 +48,51 @@ pub async fn delete_request_log(db: &DatabaseConnection, id: i64) -> Result<(),
 }
 
 /// List logs by route with pagination.
-pub async fn list_logs_by_route_paginated(db: &DatabaseConnection, route_id: Uuid, opts: Pagination) -> Result<Vec<request_log::Model>, ServiceError> {
-    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
-    let (page_idx, per_page) = opts.normalize();
-    let rows = request_log::Entity::find()
-        .filter(request_log::Column::RouteId.eq(route_id))
-        .paginate(db, per_page)
-        .fetch_page(page_idx)
-        .await
-        .map_err(|e| ServiceError::Db(e.to_string()))?;
-    Ok(rows)
+pub async fn list_logs_by_route_paginated(db: &DatabaseConnection, route_id: Uuid, opts: Pagination) -> Result<Page<request_log::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let select = request_log::Entity::find().filter(request_log::Column::RouteId.eq(route_id));
+    crate::db::fetch_page(db, select, opts).await
+}
+
+/// `GET /admin/request-logs` 的过滤条件；tenant_id 通过 route 关联过滤
+#[derive(Debug, Clone, Default)]
+pub struct RequestLogFilter {
+    pub tenant_id: Option<Uuid>,
+    pub route_id: Option<Uuid>,
+    /// 只返回 id 大于该值的记录，供轮询增量拉取
+    pub after_id: Option<i64>,
+}
+
+/// 按 id 升序返回最多 `limit` 条；未指定 after_id 时返回最近的 `limit` 条
+pub async fn list_recent(db: &DatabaseConnection, filter: &RequestLogFilter, limit: u64) -> Result<Vec<request_log::Model>, ServiceError> {
+    use models::route;
+    use sea_orm::{ColumnTrait, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait};
+    let mut select = request_log::Entity::find();
+    if let Some(tenant_id) = filter.tenant_id {
+        select = select.join(JoinType::InnerJoin, request_log::Relation::Route.def()).filter(route::Column::TenantId.eq(tenant_id));
+    }
+    if let Some(route_id) = filter.route_id {
+        select = select.filter(request_log::Column::RouteId.eq(route_id));
+    }
+    let rows = match filter.after_id {
+        Some(after) => select.filter(request_log::Column::Id.gt(after)).order_by_asc(request_log::Column::Id).limit(limit).all(db).await,
+        None => select.order_by_desc(request_log::Column::Id).limit(limit).all(db).await.map(|mut rows| {
+            rows.reverse();
+            rows
+        }),
+    };
+    rows.map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// 请求日志对应的捕获内容；未开启捕获时为 None
+pub async fn get_payload(db: &DatabaseConnection, request_log_id: i64) -> Result<Option<models::request_payload::Model>, ServiceError> {
+    models::request_payload::Entity::find_by_id(request_log_id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// 保存捕获内容；调用方负责脱敏与截断（见 `crate::replay::{header_snapshot, body_snapshot}`）
+pub async fn save_payload(db: &DatabaseConnection, payload: models::request_payload::Model) -> Result<models::request_payload::Model, ServiceError> {
+    let am: models::request_payload::ActiveModel = payload.into();
+    am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))
 }
 
 #[cfg(test)]
@@ -63,7 +104,7 @@ mod tests {
     #[tokio::test]
     async fn request_log_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let t = tenant::create(&db, &format!("svc_rl_tenant_{}", Uuid::new_v4())).await?;
         let up = upstream::create(&db, &format!("svc_up_{}", Uuid::new_v4()), "https://api.example.com").await?;
@@ -78,19 +119,55 @@ mod tests {
             circuit_breaker_threshold: Set(5),
             rate_limit_id: Set(None),
             created_at: Set(Utc::now().into()),
+            blue_upstream_id: Set(None),
+            green_upstream_id: Set(None),
+            active_color: Set(None),
+            previous_upstream_id: Set(None),
         }.insert(&db).await?;
 
-        let log = create_request_log(&db, r.id, None, 200, 123, true, None, Some("127.0.0.1".into())).await?;
+        let log = create_request_log(&db, r.id, None, 200, 123, true, None, Some("127.0.0.1".into()), Some(3), Some("DE".into()), Some(3320)).await?;
         let got = get_request_log(&db, log.id).await?.unwrap();
         assert_eq!(got.status_code, 200);
+        let failed = create_request_log(&db, r.id, None, 502, 5, false, Some("GET /svc?token=abc failed".into()), None, None, None, None).await?;
+        assert_eq!(failed.error_message.as_deref(), Some("GET /svc?token=[REDACTED] failed"));
+        delete_request_log(&db, failed.id).await?;
+        assert_eq!(got.config_revision, Some(3));
+        assert_eq!((got.country.as_deref(), got.asn), (Some("DE"), Some(3320)));
 
         // pagination
-        let page1 = list_logs_by_route_paginated(&db, r.id, Pagination { page: 1, per_page: 10 }).await?;
-        assert!(!page1.is_empty());
+        let page1 = list_logs_by_route_paginated(&db, r.id, Pagination::new(1, 10)).await?;
+        assert!(!page1.items.is_empty());
+
+        let by_tenant = RequestLogFilter { tenant_id: Some(t.id), ..Default::default() };
+        let recent = list_recent(&db, &by_tenant, 10).await?;
+        assert_eq!(recent.iter().map(|l| l.id).collect::<Vec<_>>(), vec![log.id]);
+        let newer = RequestLogFilter { after_id: Some(log.id), ..by_tenant };
+        assert!(list_recent(&db, &newer, 10).await?.is_empty());
+
+        // captured payload is removed together with its log
+        assert!(get_payload(&db, log.id).await?.is_none());
+        let payload = models::request_payload::Model {
+            request_log_id: log.id,
+            method: "POST".into(),
+            uri: "/svc?x=1".into(),
+            request_headers: r#"{"content-type":"application/json"}"#.into(),
+            request_body: Some(b"{}".to_vec()),
+            request_truncated: false,
+            response_status: Some(200),
+            response_headers: None,
+            response_body: Some(b"ok".to_vec()),
+            response_truncated: false,
+            created_at: Utc::now().into(),
+        };
+        save_payload(&db, payload).await?;
+        let stored = get_payload(&db, log.id).await?.unwrap();
+        assert_eq!(stored.request_header_map()["content-type"], "application/json");
+        assert_eq!(stored.response_body.as_deref(), Some(&b"ok"[..]));
 
         delete_request_log(&db, log.id).await?;
         let after = get_request_log(&db, log.id).await?;
         assert!(after.is_none());
+        assert!(get_payload(&db, log.id).await?.is_none());
 
         route::Entity::delete_by_id(r.id).exec(&db).await?;
         upstream::Entity::delete_by_id(up.id).exec(&db).await?;
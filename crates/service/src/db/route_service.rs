@@ -1,9 +1,10 @@
 use uuid::Uuid;
 use chrono::Utc;
-use sea_orm::{DatabaseConnection, ActiveModelTrait, EntityTrait, Set};
+use sea_orm::{ConnectionTrait, DatabaseConnection, ActiveModelTrait, EntityTrait, QuerySelect, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
 use models::route;
 use crate::{errors::ServiceError};
-use common::pagination::Pagination;
+use common::pagination::{Page, Pagination};
 
 /// Create a route.
 pub async fn create_route(
@@ -37,19 +38,23 @@ pub async fn create_route(
         circuit_breaker_threshold: Set(circuit_breaker_threshold),
         rate_limit_id: Set(rate_limit_id),
         created_at: Set(Utc::now().into()),
+        blue_upstream_id: Set(None),
+        green_upstream_id: Set(None),
+        active_color: Set(None),
+        previous_upstream_id: Set(None),
     };
     let model = am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
     Ok(model)
 }
 
 /// Get route by id.
-pub async fn get_route(db: &DatabaseConnection, id: Uuid) -> Result<Option<route::Model>, ServiceError> {
+pub async fn get_route<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<Option<route::Model>, ServiceError> {
     Ok(route::Entity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
 
 /// Update route.
-pub async fn update_route(
-    db: &DatabaseConnection,
+pub async fn update_route<C: ConnectionTrait>(
+    db: &C,
     id: Uuid,
     method: Option<&str>,
     path: Option<&str>,
@@ -81,22 +86,113 @@ pub async fn update_route(
 }
 
 /// Delete route.
-pub async fn delete_route(db: &DatabaseConnection, id: Uuid) -> Result<(), ServiceError> {
+pub async fn delete_route<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<(), ServiceError> {
     route::Entity::delete_by_id(id).exec(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
     Ok(())
 }
 
+/// 蓝绿部署的一侧
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeploymentColor { Blue, Green }
+
+impl DeploymentColor {
+    pub fn as_str(&self) -> &'static str {
+        match self { DeploymentColor::Blue => "blue", DeploymentColor::Green => "green" }
+    }
+
+    pub fn other(&self) -> Self {
+        match self { DeploymentColor::Blue => DeploymentColor::Green, DeploymentColor::Green => DeploymentColor::Blue }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s { "blue" => Some(DeploymentColor::Blue), "green" => Some(DeploymentColor::Green), _ => None }
+    }
+
+    fn slot(&self, r: &route::Model) -> Option<Uuid> {
+        match self { DeploymentColor::Blue => r.blue_upstream_id, DeploymentColor::Green => r.green_upstream_id }
+    }
+
+    /// upstream 命中哪一侧
+    fn of(r: &route::Model, upstream_id: Uuid) -> Option<Self> {
+        [DeploymentColor::Blue, DeploymentColor::Green].into_iter().find(|c| c.slot(r) == Some(upstream_id))
+    }
+}
+
+/// Result of a cutover or rollback.
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteCutover {
+    pub route: route::Model,
+    pub from_upstream_id: Uuid,
+    pub to_upstream_id: Uuid,
+}
+
+/// Store the blue/green targets of a route. Traffic is not moved; `active_color`
+/// reflects whichever slot the current upstream already matches.
+pub async fn set_route_blue_green<C: ConnectionTrait>(db: &C, id: Uuid, blue: Uuid, green: Uuid) -> Result<route::Model, ServiceError> {
+    if blue == green {
+        return Err(ServiceError::Validation("blue and green upstreams must differ".into()));
+    }
+    let current = get_route(db, id).await?.ok_or_else(|| ServiceError::not_found("route"))?;
+    let mut am: route::ActiveModel = current.clone().into();
+    am.blue_upstream_id = Set(Some(blue));
+    am.green_upstream_id = Set(Some(green));
+    let probe = route::Model { blue_upstream_id: Some(blue), green_upstream_id: Some(green), ..current.clone() };
+    am.active_color = Set(DeploymentColor::of(&probe, current.upstream_id).map(|c| c.as_str().to_string()));
+    am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))
+}
+
+/// Atomically switch a route to `target` (default: the inactive side). The row is locked
+/// for the duration of the switch, and the replaced upstream is kept for rollback.
+pub async fn cutover_route<C: ConnectionTrait + TransactionTrait>(db: &C, id: Uuid, target: Option<DeploymentColor>) -> Result<RouteCutover, ServiceError> {
+    let dbe = |e: sea_orm::DbErr| ServiceError::Db(e.to_string());
+    let txn = db.begin().await.map_err(dbe)?;
+    let current = route::Entity::find_by_id(id).lock_exclusive().one(&txn).await.map_err(dbe)?
+        .ok_or_else(|| ServiceError::not_found("route"))?;
+    if current.blue_upstream_id.is_none() || current.green_upstream_id.is_none() {
+        return Err(ServiceError::Validation("route has no blue/green upstreams configured".into()));
+    }
+    let active = current.active_color.as_deref().and_then(DeploymentColor::parse);
+    let target = target.or(active.map(|c| c.other()))
+        .ok_or_else(|| ServiceError::Validation("route matches neither slot; specify the target color".into()))?;
+    let to = target.slot(&current).unwrap_or(current.upstream_id);
+    if to == current.upstream_id {
+        return Err(ServiceError::Validation(format!("route already serves the {} upstream", target.as_str())));
+    }
+    let from = current.upstream_id;
+    let mut am: route::ActiveModel = current.into();
+    am.upstream_id = Set(to);
+    am.previous_upstream_id = Set(Some(from));
+    am.active_color = Set(Some(target.as_str().to_string()));
+    let updated = am.update(&txn).await.map_err(dbe)?;
+    txn.commit().await.map_err(dbe)?;
+    Ok(RouteCutover { route: updated, from_upstream_id: from, to_upstream_id: to })
+}
+
+/// Swap back to the upstream recorded by the last cutover.
+pub async fn rollback_route<C: ConnectionTrait + TransactionTrait>(db: &C, id: Uuid) -> Result<RouteCutover, ServiceError> {
+    let dbe = |e: sea_orm::DbErr| ServiceError::Db(e.to_string());
+    let txn = db.begin().await.map_err(dbe)?;
+    let current = route::Entity::find_by_id(id).lock_exclusive().one(&txn).await.map_err(dbe)?
+        .ok_or_else(|| ServiceError::not_found("route"))?;
+    let to = current.previous_upstream_id
+        .ok_or_else(|| ServiceError::Validation("route has no previous upstream to roll back to".into()))?;
+    let from = current.upstream_id;
+    let color = DeploymentColor::of(&current, to).map(|c| c.as_str().to_string());
+    let mut am: route::ActiveModel = current.into();
+    am.upstream_id = Set(to);
+    am.previous_upstream_id = Set(Some(from));
+    am.active_color = Set(color);
+    let updated = am.update(&txn).await.map_err(dbe)?;
+    txn.commit().await.map_err(dbe)?;
+    Ok(RouteCutover { route: updated, from_upstream_id: from, to_upstream_id: to })
+}
+
 /// List routes for a tenant with pagination.
-pub async fn list_routes_by_tenant_paginated(db: &DatabaseConnection, tenant_id: Uuid, opts: Pagination) -> Result<Vec<route::Model>, ServiceError> {
-    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
-    let (page_idx, per_page) = opts.normalize();
-    let rows = route::Entity::find()
-        .filter(route::Column::TenantId.eq(tenant_id))
-        .paginate(db, per_page)
-        .fetch_page(page_idx)
-        .await
-        .map_err(|e| ServiceError::Db(e.to_string()))?;
-    Ok(rows)
+pub async fn list_routes_by_tenant_paginated(db: &DatabaseConnection, tenant_id: Uuid, opts: Pagination) -> Result<Page<route::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let select = route::Entity::find().filter(route::Column::TenantId.eq(tenant_id));
+    crate::db::fetch_page(db, select, opts).await
 }
 
 #[cfg(test)]
@@ -108,7 +204,7 @@ mod tests {
     #[tokio::test]
     async fn route_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let t = tenant::create(&db, &format!("svc_route_tenant_{}", Uuid::new_v4())).await?;
         let up = upstream::create(&db, &format!("svc_up_{}", Uuid::new_v4()), "https://api.example.com").await?;
@@ -122,10 +218,27 @@ mod tests {
         assert_eq!(updated.path, "/svc2");
 
         // pagination
-        let page1 = list_routes_by_tenant_paginated(&db, t.id, Pagination { page: 1, per_page: 10 }).await?;
-        assert!(!page1.is_empty());
+        let page1 = list_routes_by_tenant_paginated(&db, t.id, Pagination::new(1, 10)).await?;
+        assert!(!page1.items.is_empty());
+        let beyond = list_routes_by_tenant_paginated(&db, t.id, Pagination::new(99, 10)).await;
+        assert!(matches!(beyond, Err(ServiceError::Pagination(_))));
+
+        // 蓝绿切换与回滚
+        let green = upstream::create(&db, &format!("svc_up_green_{}", Uuid::new_v4()), "https://green.example.com").await?;
+        assert!(cutover_route(&db, r.id, None).await.is_err());
+        let bg = set_route_blue_green(&db, r.id, up.id, green.id).await?;
+        assert_eq!(bg.active_color.as_deref(), Some("blue"));
+        let c = cutover_route(&db, r.id, None).await?;
+        assert_eq!((c.from_upstream_id, c.to_upstream_id), (up.id, green.id));
+        assert_eq!(c.route.active_color.as_deref(), Some("green"));
+        assert!(cutover_route(&db, r.id, Some(DeploymentColor::Green)).await.is_err());
+        let back = rollback_route(&db, r.id).await?;
+        assert_eq!(back.route.upstream_id, up.id);
+        assert_eq!(back.route.previous_upstream_id, Some(green.id));
+        assert_eq!(back.route.active_color.as_deref(), Some("blue"));
 
         delete_route(&db, r.id).await?;
+        upstream::Entity::delete_by_id(green.id).exec(&db).await?;
         let after = get_route(&db, r.id).await?;
         assert!(after.is_none());
 
@@ -1,7 +1,8 @@
 use uuid::Uuid;
-use sea_orm::{DatabaseConnection, EntityTrait, ActiveModelTrait, Set};
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, ActiveModelTrait, Set};
 
 use models::tenant;
+use common::pagination::{Page, Pagination};
 use crate::errors::ServiceError;
 
 /// Create a tenant.
@@ -15,8 +16,15 @@ pub async fn get_tenant(db: &DatabaseConnection, id: Uuid) -> Result<Option<tena
     Ok(tenant::Entity::find_by_id(id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?)
 }
 
+/// List tenants ordered by name with pagination.
+pub async fn list_tenants_paginated(db: &DatabaseConnection, opts: Pagination) -> Result<Page<tenant::Model>, ServiceError> {
+    use sea_orm::QueryOrder;
+    let select = tenant::Entity::find().order_by_asc(tenant::Column::Name).order_by_asc(tenant::Column::Id);
+    crate::db::fetch_page(db, select, opts).await
+}
+
 /// Update tenant name.
-pub async fn update_tenant_name(db: &DatabaseConnection, id: Uuid, name: &str) -> Result<tenant::Model, ServiceError> {
+pub async fn update_tenant_name<C: ConnectionTrait>(db: &C, id: Uuid, name: &str) -> Result<tenant::Model, ServiceError> {
     tenant::validate_name(name)?;
     let mut am: tenant::ActiveModel = tenant::Entity::find_by_id(id)
         .one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
@@ -28,8 +36,43 @@ pub async fn update_tenant_name(db: &DatabaseConnection, id: Uuid, name: &str) -
     Ok(updated)
 }
 
+/// Kill switch: suspend or resume all traffic of a tenant.
+pub async fn set_tenant_suspended<C: ConnectionTrait>(db: &C, id: Uuid, suspended: bool, reason: Option<String>) -> Result<tenant::Model, ServiceError> {
+    let mut am: tenant::ActiveModel = tenant::Entity::find_by_id(id)
+        .one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("tenant"))?
+        .into();
+    am.suspended = Set(suspended);
+    am.suspended_reason = Set(if suspended { reason } else { None });
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
+/// Set the email verification policy of a tenant (`off` / `optional` / `required`).
+pub async fn set_tenant_email_verification<C: ConnectionTrait>(db: &C, id: Uuid, policy: &str) -> Result<tenant::Model, ServiceError> {
+    tenant::validate_email_verification(policy)?;
+    let mut am: tenant::ActiveModel = tenant::Entity::find_by_id(id)
+        .one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("tenant"))?
+        .into();
+    am.email_verification = Set(policy.to_string());
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
+/// Close (`true`) or reopen self-registration; invite-only tenants onboard users through invitations.
+pub async fn set_tenant_invite_only<C: ConnectionTrait>(db: &C, id: Uuid, invite_only: bool) -> Result<tenant::Model, ServiceError> {
+    let mut am: tenant::ActiveModel = tenant::Entity::find_by_id(id)
+        .one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?
+        .ok_or_else(|| ServiceError::not_found("tenant"))?
+        .into();
+    am.invite_only = Set(invite_only);
+    let updated = am.update(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
+    Ok(updated)
+}
+
 /// Hard delete tenant.
-pub async fn delete_tenant(db: &DatabaseConnection, id: Uuid) -> Result<(), ServiceError> {
+pub async fn delete_tenant<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<(), ServiceError> {
     tenant::Entity::delete_by_id(id).exec(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
     Ok(())
 }
@@ -42,7 +85,7 @@ mod tests {
     #[tokio::test]
     async fn tenant_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let name = format!("svc_tenant_{}", Uuid::new_v4());
         let t = create_tenant(&db, &name).await?;
@@ -50,10 +93,24 @@ mod tests {
 
         let found = get_tenant(&db, t.id).await?.unwrap();
         assert_eq!(found.id, t.id);
+        let page = list_tenants_paginated(&db, Pagination::new(1, 100)).await?;
+        assert!(page.total >= 1);
 
         let updated = update_tenant_name(&db, t.id, "new_name").await?;
         assert_eq!(updated.name, "new_name");
 
+        let suspended = set_tenant_suspended(&db, t.id, true, Some("billing".into())).await?;
+        assert!(suspended.suspended);
+        assert_eq!(suspended.suspended_reason.as_deref(), Some("billing"));
+        let resumed = set_tenant_suspended(&db, t.id, false, Some("ignored".into())).await?;
+        assert!(!resumed.suspended && resumed.suspended_reason.is_none());
+
+        assert_eq!(t.email_verification, tenant::EMAIL_VERIFICATION_OFF);
+        let required = set_tenant_email_verification(&db, t.id, tenant::EMAIL_VERIFICATION_REQUIRED).await?;
+        assert_eq!(required.email_verification, "required");
+        assert!(matches!(set_tenant_email_verification(&db, t.id, "sometimes").await, Err(ServiceError::Model(_))));
+        assert!(set_tenant_invite_only(&db, t.id, true).await?.invite_only);
+
         delete_tenant(&db, t.id).await?;
         let after = get_tenant(&db, t.id).await?;
         assert!(after.is_none());
@@ -3,7 +3,7 @@ use chrono::Utc;
 use sea_orm::{DatabaseConnection, EntityTrait, ActiveModelTrait, Set};
 use models::upstream;
 use crate::{errors::ServiceError};
-use common::pagination::Pagination;
+use common::pagination::{Page, Pagination};
 
 /// Create an upstream.
 pub async fn create_upstream(db: &DatabaseConnection, name: &str, base_url: &str) -> Result<upstream::Model, ServiceError> {
@@ -37,17 +37,11 @@ pub async fn delete_upstream(db: &DatabaseConnection, id: Uuid) -> Result<(), Se
 }
 
 /// List upstreams with optional active filter and pagination.
-pub async fn list_upstreams_paginated(db: &DatabaseConnection, active: Option<bool>, opts: Pagination) -> Result<Vec<upstream::Model>, ServiceError> {
-    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
-    let (page_idx, per_page) = opts.normalize();
+pub async fn list_upstreams_paginated(db: &DatabaseConnection, active: Option<bool>, opts: Pagination) -> Result<Page<upstream::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
     let mut select = upstream::Entity::find();
     if let Some(a) = active { select = select.filter(upstream::Column::Active.eq(a)); }
-    let rows = select
-        .paginate(db, per_page)
-        .fetch_page(page_idx)
-        .await
-        .map_err(|e| ServiceError::Db(e.to_string()))?;
-    Ok(rows)
+    crate::db::fetch_page(db, select, opts).await
 }
 
 #[cfg(test)]
@@ -58,7 +52,7 @@ mod tests {
     #[tokio::test]
     async fn upstream_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let up = create_upstream(&db, &format!("svc_up_{}", Uuid::new_v4()), "https://api.example.com").await?;
         let found = get_upstream(&db, up.id).await?.unwrap();
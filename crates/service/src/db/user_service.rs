@@ -4,7 +4,7 @@ use sea_orm::{DatabaseConnection, ActiveModelTrait, EntityTrait, Set};
 
 use models::user;
 use crate::{errors::ServiceError};
-use common::pagination::Pagination;
+use common::pagination::{Page, Pagination};
 
 /// Create a new user under a tenant.
 pub async fn create_user(db: &DatabaseConnection, tenant_id: Uuid, email: &str, name: &str) -> Result<user::Model, ServiceError> {
@@ -56,17 +56,10 @@ pub async fn list_users_by_tenant_paginated(
     db: &DatabaseConnection,
     tenant_id: Uuid,
     opts: Pagination,
-) -> Result<Vec<user::Model>, ServiceError> {
-    use sea_orm::{QueryFilter, ColumnTrait, PaginatorTrait};
-    let (page_idx, per_page) = opts.normalize();
-    // SeaORM's paginate uses 0-based page index internally via fetch_page
-    let users = user::Entity::find()
-        .filter(user::Column::TenantId.eq(tenant_id))
-        .paginate(db, per_page)
-        .fetch_page(page_idx)
-        .await
-        .map_err(|e| ServiceError::Db(e.to_string()))?;
-    Ok(users)
+) -> Result<Page<user::Model>, ServiceError> {
+    use sea_orm::{QueryFilter, ColumnTrait};
+    let select = user::Entity::find().filter(user::Column::TenantId.eq(tenant_id));
+    crate::db::fetch_page(db, select, opts).await
 }
 
 #[cfg(test)]
@@ -78,7 +71,7 @@ mod tests {
     #[tokio::test]
     async fn user_crud_service() -> Result<(), anyhow::Error> {
         if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
-        let db = get_db().await?;
+        let (db, _schema) = get_db().await?;
 
         let tname = format!("svc_tenant_{}", Uuid::new_v4());
         let t = tenant::create(&db, &tname).await?;
@@ -105,9 +98,11 @@ mod tests {
         // pagination test
         let u2 = user::create(&db, t.id, &format!("svc_{}@example.com", Uuid::new_v4()), "User2").await?;
         let u3 = user::create(&db, t.id, &format!("svc_{}@example.com", Uuid::new_v4()), "User3").await?;
-        let page = Pagination { page: 1, per_page: 2 };
+        let page = Pagination::new(1, 2);
         let page1 = list_users_by_tenant_paginated(&db, t.id, page).await?;
-        assert!(page1.len() <= 2 && page1.len() >= 2);
+        assert_eq!(page1.items.len(), 2);
+        assert!(page1.total >= 3);
+        assert!(page1.has_more);
 
         user::hard_delete(&db, u2.id).await?;
         user::hard_delete(&db, u3.id).await?;
@@ -0,0 +1,72 @@
+//! Re-encryption of encrypted columns after a master key rotation (or after enabling encryption).
+//! Rows not sealed with the current primary key — plaintext or an older key id — are decrypted
+//! with whichever key they name and written back sealed with the primary key.
+//! Signing keys still in the legacy `v1:{base64}` format are converted to the current format on the way.
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QuerySelect};
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use common::crypto::MasterKeys;
+use models::encrypted::EncryptedString;
+use models::{jwt_signing_key, webhook};
+
+use crate::errors::ServiceError;
+use crate::jobs::JobContext;
+
+fn dbe(e: DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+/// Rows rewritten per encrypted column.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReencryptReport {
+    pub key_id: String,
+    pub webhook_secret: u64,
+    pub jwt_signing_key_secret: u64,
+}
+
+/// Rewrite every encrypted column value not sealed with the installed primary key.
+pub async fn reencrypt_all(db: &DatabaseConnection, ctx: Option<&JobContext>) -> Result<ReencryptReport, ServiceError> {
+    let keys = common::crypto::installed().ok_or_else(|| ServiceError::Crypto("no master key installed".into()))?;
+    let plaintext = |stored: String| -> Result<String, ServiceError> { Ok(EncryptedString::from_stored(stored)?.into_inner()) };
+    let webhook_secret = rewrite::<webhook::Entity>(db, &keys, webhook::Column::Id, webhook::Column::Secret, plaintext, ctx).await?;
+    let jwt_signing_key_secret =
+        rewrite::<jwt_signing_key::Entity>(db, &keys, jwt_signing_key::Column::Id, jwt_signing_key::Column::Secret, crate::jwt_keys::column_plaintext, ctx).await?;
+    let report = ReencryptReport { key_id: keys.primary_id().to_string(), webhook_secret, jwt_signing_key_secret };
+    info!(key_id = %report.key_id, webhook_secret, jwt_signing_key_secret, "columns_reencrypted");
+    Ok(report)
+}
+
+/// One encrypted column of a table keyed by a uuid `id`; `plaintext` turns a stored value into the column plaintext.
+/// Returns the number of rows rewritten.
+async fn rewrite<E: EntityTrait>(
+    db: &DatabaseConnection,
+    keys: &MasterKeys,
+    id: E::Column,
+    column: E::Column,
+    plaintext: impl Fn(String) -> Result<String, ServiceError>,
+    ctx: Option<&JobContext>,
+) -> Result<u64, ServiceError> {
+    // 只取原始存储值判断是否需要重写，不整表解密
+    let rows: Vec<(Uuid, String)> = E::find().select_only().column(id).column(column).into_tuple().all(db).await.map_err(dbe)?;
+    let stale: Vec<_> = rows.into_iter().filter(|(_, stored)| !keys.is_current(stored)).collect();
+    if let Some(ctx) = ctx {
+        ctx.add_total(stale.len() as u64);
+    }
+    let mut rewritten = 0;
+    for (row_id, stored) in stale {
+        if ctx.is_some_and(|c| c.is_cancelled()) {
+            break;
+        }
+        let sealed = EncryptedString::new(plaintext(stored)?)?;
+        E::update_many().col_expr(column, Expr::value(sealed)).filter(id.eq(row_id)).exec(db).await.map_err(dbe)?;
+        rewritten += 1;
+        if let Some(ctx) = ctx {
+            ctx.advance(1);
+        }
+    }
+    Ok(rewritten)
+}
@@ -7,10 +7,21 @@ pub enum ServiceError {
     Validation(String),
     #[error("not found: {0}")]
     NotFound(String),
+    /// Unique constraint hit (name / email already taken)
+    #[error("conflict: {0}")]
+    Conflict(String),
     #[error("database error: {0}")]
     Db(String),
     #[error("model error: {0}")]
     Model(#[from] models::errors::ModelError),
+    #[error("pagination error: {0}")]
+    Pagination(#[from] common::pagination::PaginationError),
+    #[error("crypto error: {0}")]
+    Crypto(String),
+}
+
+impl From<common::crypto::CryptoError> for ServiceError {
+    fn from(e: common::crypto::CryptoError) -> Self { Self::Crypto(e.to_string()) }
 }
 
 impl ServiceError {
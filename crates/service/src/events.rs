@@ -0,0 +1,116 @@
+//! In-process event bus for control-plane change notifications.
+//! - Writers publish after a successful mutation.
+//! - Subscribers (caches, gateway refreshers) react asynchronously.
+//! - Every published event gets a monotonically increasing revision id; the data plane
+//!   stamps the active revision into logs so rejections can be traced to the causing change.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Control-plane change events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigEvent {
+    ProxyApiChanged { id: Uuid },
+    UpstreamChanged { id: Uuid },
+    RateLimitChanged { id: Uuid },
+    TenantChanged { id: Uuid },
+    /// Route created, updated or deleted (including blue/green configuration).
+    RouteChanged { id: Uuid },
+    /// API key created, revoked or its expiry changed.
+    ApiKeyChanged { id: Uuid },
+    /// Route target switched (blue/green cutover or rollback).
+    RouteCutover { id: Uuid, from: Uuid, to: Uuid },
+    /// Manual flush of all cached reads.
+    CacheFlushed,
+}
+
+impl ConfigEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConfigEvent::ProxyApiChanged { .. } => "proxy_api",
+            ConfigEvent::UpstreamChanged { .. } => "upstream",
+            ConfigEvent::RateLimitChanged { .. } => "rate_limit",
+            ConfigEvent::TenantChanged { .. } => "tenant",
+            ConfigEvent::RouteChanged { .. } => "route",
+            ConfigEvent::ApiKeyChanged { .. } => "api_key",
+            ConfigEvent::RouteCutover { .. } => "route_cutover",
+            ConfigEvent::CacheFlushed => "cache_flush",
+        }
+    }
+
+    /// The entity the change applies to, if any.
+    pub fn entity_id(&self) -> Option<Uuid> {
+        match self {
+            ConfigEvent::ProxyApiChanged { id }
+            | ConfigEvent::UpstreamChanged { id }
+            | ConfigEvent::RateLimitChanged { id }
+            | ConfigEvent::TenantChanged { id }
+            | ConfigEvent::RouteChanged { id }
+            | ConfigEvent::ApiKeyChanged { id }
+            | ConfigEvent::RouteCutover { id, .. } => Some(*id),
+            ConfigEvent::CacheFlushed => None,
+        }
+    }
+}
+
+/// An event together with the revision it created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionedEvent {
+    pub revision: i64,
+    pub event: ConfigEvent,
+}
+
+/// Cloneable handle over a broadcast channel.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<RevisionedEvent>,
+    revision: Arc<AtomicI64>,
+    /// entity id -> last revision that touched it
+    last_by_entity: Arc<Mutex<HashMap<Uuid, i64>>>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self { tx, revision: Arc::new(AtomicI64::new(0)), last_by_entity: Arc::default() }
+    }
+
+    /// Publish an event and return its revision; having no subscribers is not an error.
+    pub fn publish(&self, event: ConfigEvent) -> i64 {
+        let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(id) = event.entity_id() {
+            self.last_by_entity.lock().expect("revision map").insert(id, revision);
+        }
+        let _ = self.tx.send(RevisionedEvent { revision, event });
+        revision
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RevisionedEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Latest published revision (0 before any change).
+    pub fn current_revision(&self) -> i64 {
+        self.revision.load(Ordering::SeqCst)
+    }
+
+    /// Last revision that changed `entity` since startup.
+    pub fn revision_of(&self, entity: Uuid) -> Option<i64> {
+        self.last_by_entity.lock().expect("revision map").get(&entity).copied()
+    }
+
+    /// Continue numbering after revisions persisted by a previous process.
+    pub fn resume_from(&self, revision: i64) {
+        self.revision.fetch_max(revision, Ordering::SeqCst);
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
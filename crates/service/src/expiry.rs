@@ -0,0 +1,311 @@
+//! Expiry notifications for credentials.
+//! - Scans API keys (`api_key.expires_at`), configured JWT signing keys and PEM certificates.
+//! - An item is reported once it is within its lead time (per tenant for API keys) or already expired.
+//! - The scheduler notifies through the webhook subsystem: API keys go to the owning tenant,
+//!   certificates and signing keys to the operator tenant. Each item/stage is notified once per process.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, JoinType, QueryFilter, QuerySelect, RelationTrait};
+use serde::Serialize;
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::lock::{run_exclusive, DistributedLock};
+use common::time::{system_clock, SharedClock};
+use models::apikey;
+
+use crate::errors::ServiceError;
+use crate::webhooks::{WebhookEvent, WebhookService};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    ApiKey,
+    JwtSigningKey,
+    TlsCertificate,
+}
+
+impl CredentialKind {
+    fn event(&self) -> WebhookEvent {
+        match self {
+            CredentialKind::ApiKey => WebhookEvent::KeyExpiring,
+            CredentialKind::JwtSigningKey => WebhookEvent::SigningKeyExpiring,
+            CredentialKind::TlsCertificate => WebhookEvent::CertificateExpiring,
+        }
+    }
+}
+
+/// One credential within its notification window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpiringItem {
+    pub kind: CredentialKind,
+    /// API key id, JWT `kid` or certificate path
+    pub id: String,
+    pub tenant_id: Option<Uuid>,
+    /// Owner email for API keys
+    pub owner: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    /// Negative once expired
+    pub days_left: i64,
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExpiryOptions {
+    pub interval: Duration,
+    pub lead_days: u32,
+    pub tenant_lead_days: HashMap<Uuid, u32>,
+    pub certificates: Vec<String>,
+    /// (kid, expires_at)
+    pub jwt_signing_keys: Vec<(String, DateTime<Utc>)>,
+    pub operator_tenant_id: Option<Uuid>,
+}
+
+impl Default for ExpiryOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(3600),
+            lead_days: 14,
+            tenant_lead_days: HashMap::new(),
+            certificates: Vec::new(),
+            jwt_signing_keys: Vec::new(),
+            operator_tenant_id: None,
+        }
+    }
+}
+
+impl ExpiryOptions {
+    fn lead_for(&self, tenant_id: Option<Uuid>) -> u32 {
+        tenant_id.and_then(|t| self.tenant_lead_days.get(&t).copied()).unwrap_or(self.lead_days)
+    }
+}
+
+/// `Some(item)` when `expires_at` is within `lead_days` of `now` (or past).
+fn within(kind: CredentialKind, id: String, tenant_id: Option<Uuid>, owner: Option<String>, expires_at: DateTime<Utc>, now: DateTime<Utc>, lead_days: u32) -> Option<ExpiringItem> {
+    if expires_at - now > chrono::Duration::days(lead_days as i64) {
+        return None;
+    }
+    let days_left = (expires_at - now).num_days();
+    Some(ExpiringItem { kind, id, tenant_id, owner, expires_at, days_left, expired: expires_at <= now })
+}
+
+/// `notAfter` of the first certificate in a PEM file.
+pub fn certificate_not_after(pem_text: &[u8]) -> Result<DateTime<Utc>, String> {
+    let blocks = pem::parse_many(pem_text).map_err(|e| format!("invalid PEM: {}", e))?;
+    let cert = blocks.iter().find(|b| b.tag() == "CERTIFICATE").ok_or("no CERTIFICATE block")?;
+    der::not_after(cert.contents())
+}
+
+/// Minimal DER walk to `TBSCertificate.validity.notAfter`.
+mod der {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+
+    /// (tag, content) of the TLV at the start of `buf`, plus the remainder.
+    fn tlv(buf: &[u8]) -> Result<(u8, &[u8], &[u8]), String> {
+        let (&tag, rest) = buf.split_first().ok_or("truncated DER")?;
+        let (&first, rest) = rest.split_first().ok_or("truncated DER")?;
+        let (len, rest) = if first < 0x80 {
+            (first as usize, rest)
+        } else {
+            let n = (first & 0x7f) as usize;
+            if n == 0 || n > 4 || rest.len() < n {
+                return Err("unsupported DER length".into());
+            }
+            (rest[..n].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize), &rest[n..])
+        };
+        if rest.len() < len {
+            return Err("truncated DER".into());
+        }
+        Ok((tag, &rest[..len], &rest[len..]))
+    }
+
+    pub fn not_after(cert: &[u8]) -> Result<DateTime<Utc>, String> {
+        let (_, cert, _) = tlv(cert)?;
+        let (_, tbs, _) = tlv(cert)?;
+        let (tag, _, mut rest) = tlv(tbs)?;
+        // [0] version 可选；存在时再跳过 serialNumber
+        if tag == 0xa0 {
+            rest = tlv(rest)?.2;
+        }
+        // signature, issuer
+        rest = tlv(rest)?.2;
+        rest = tlv(rest)?.2;
+        let (_, validity, _) = tlv(rest)?;
+        let (_, _, validity) = tlv(validity)?;
+        let (tag, time, _) = tlv(validity)?;
+        let text = std::str::from_utf8(time).map_err(|_| "invalid time encoding")?;
+        let parsed = match tag {
+            // UTCTime: YYMMDDHHMMSSZ，YY >= 50 表示 19YY
+            0x17 => {
+                let yy: u32 = text.get(..2).and_then(|y| y.parse().ok()).ok_or("invalid UTCTime")?;
+                let century = if yy >= 50 { "19" } else { "20" };
+                NaiveDateTime::parse_from_str(&format!("{}{}", century, text), "%Y%m%d%H%M%SZ")
+            }
+            0x18 => NaiveDateTime::parse_from_str(text, "%Y%m%d%H%M%SZ"),
+            _ => return Err("unexpected validity time type".into()),
+        };
+        parsed.map(|t| t.and_utc()).map_err(|e| format!("invalid validity time {}: {}", text, e))
+    }
+}
+
+pub struct ExpiryScanner {
+    db: DatabaseConnection,
+    webhooks: Arc<WebhookService>,
+    opts: ExpiryOptions,
+    /// "kind:id:stage" already notified
+    notified: Mutex<HashSet<String>>,
+    lock: Option<Arc<dyn DistributedLock>>,
+    clock: SharedClock,
+}
+
+impl ExpiryScanner {
+    pub fn new(db: DatabaseConnection, webhooks: Arc<WebhookService>, opts: ExpiryOptions) -> Self {
+        Self { db, webhooks, opts, notified: Mutex::new(HashSet::new()), lock: None, clock: system_clock() }
+    }
+
+    /// Run the scheduled scan on one replica at a time.
+    pub fn with_lock(mut self, lock: Arc<dyn DistributedLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Items within their lead time; `lead_override` replaces every configured lead time.
+    pub async fn scan(&self, now: DateTime<Utc>, lead_override: Option<u32>) -> Result<Vec<ExpiringItem>, ServiceError> {
+        let max_lead = lead_override.unwrap_or_else(|| self.opts.tenant_lead_days.values().copied().chain([self.opts.lead_days]).max().unwrap_or(0));
+        let horizon = now + chrono::Duration::days(max_lead as i64);
+        let keys: Vec<(Uuid, DateTime<chrono::FixedOffset>, Uuid, String)> = apikey::Entity::find()
+            .select_only()
+            .column(apikey::Column::Id)
+            .column(apikey::Column::ExpiresAt)
+            .column(models::user::Column::TenantId)
+            .column(models::user::Column::Email)
+            .join(JoinType::InnerJoin, apikey::Relation::User.def())
+            .filter(apikey::Column::Status.eq("active"))
+            .filter(apikey::Column::ExpiresAt.lte(horizon))
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))?;
+
+        let mut items = Vec::new();
+        for (id, expires_at, tenant_id, email) in keys {
+            let lead = lead_override.unwrap_or_else(|| self.opts.lead_for(Some(tenant_id)));
+            items.extend(within(CredentialKind::ApiKey, id.to_string(), Some(tenant_id), Some(email), expires_at.with_timezone(&Utc), now, lead));
+        }
+        let lead = lead_override.unwrap_or(self.opts.lead_days);
+        for (kid, expires_at) in &self.opts.jwt_signing_keys {
+            items.extend(within(CredentialKind::JwtSigningKey, kid.clone(), None, None, *expires_at, now, lead));
+        }
+        for path in &self.opts.certificates {
+            match std::fs::read(path).map_err(|e| e.to_string()).and_then(|pem| certificate_not_after(&pem)) {
+                Ok(not_after) => items.extend(within(CredentialKind::TlsCertificate, path.clone(), None, None, not_after, now, lead)),
+                Err(e) => warn!(path = %path, error = %e, "expiry_certificate_unreadable"),
+            }
+        }
+        items.sort_by_key(|i| i.expires_at);
+        Ok(items)
+    }
+
+    /// Scan and notify items not notified before; returns the number of notifications sent.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<usize, ServiceError> {
+        let items = self.scan(now, None).await?;
+        let mut sent = 0;
+        for item in items {
+            let stage = if item.expired { "expired" } else { "expiring" };
+            let key = format!("{:?}:{}:{}", item.kind, item.id, stage);
+            if self.notified.lock().unwrap_or_else(|e| e.into_inner()).contains(&key) {
+                continue;
+            }
+            warn!(kind = ?item.kind, id = %item.id, tenant_id = ?item.tenant_id, expires_at = %item.expires_at, days_left = item.days_left, "credential_expiring");
+            let Some(tenant_id) = item.tenant_id.or(self.opts.operator_tenant_id) else {
+                self.notified.lock().unwrap_or_else(|e| e.into_inner()).insert(key);
+                continue;
+            };
+            let data = json!({
+                "kind": item.kind,
+                "id": item.id,
+                "owner": item.owner,
+                "expires_at": item.expires_at,
+                "days_left": item.days_left,
+                "expired": item.expired,
+            });
+            match self.webhooks.emit(tenant_id, item.kind.event(), data).await {
+                Ok(_) => {
+                    self.notified.lock().unwrap_or_else(|e| e.into_inner()).insert(key);
+                    sent += 1;
+                }
+                Err(e) => warn!(id = %item.id, error = %e, "expiry_notification_failed"),
+            }
+        }
+        if sent > 0 {
+            info!(sent, "expiry_notifications_queued");
+        }
+        Ok(sent)
+    }
+
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.opts.interval);
+            loop {
+                ticker.tick().await;
+                if let Some(Err(e)) = run_exclusive(this.lock.as_deref(), "expiry_scan", this.opts.interval, this.run_once(this.clock.now())).await {
+                    warn!(error = %e, "expiry_scan_failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CERT: &str = "-----BEGIN CERTIFICATE-----
+MIIBeDCCAR+gAwIBAgIUagDTke6cwPdoNUnt1eGMfWHwLXYwCgYIKoZIzj0EAwIw
+EjEQMA4GA1UEAwwHZ3cudGVzdDAeFw0yNjEwMTYxNTQwMjdaFw0yNjExMTUxNTQw
+MjdaMBIxEDAOBgNVBAMMB2d3LnRlc3QwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNC
+AAQ/0dXM5O6QYQpOHR7E5ja9LGYyF9hkhaulElzPh+SIlm77LtbwM72jFKIVvFzX
+tjSr+G8HvZW6rbv4ILfP1q9Mo1MwUTAdBgNVHQ4EFgQUlYb4KNYksBCI9ttLfyYG
+DU52qf8wHwYDVR0jBBgwFoAUlYb4KNYksBCI9ttLfyYGDU52qf8wDwYDVR0TAQH/
+BAUwAwEB/zAKBggqhkjOPQQDAgNHADBEAiBmMWjkCqJ9K+5DAxs537CC6QEmJkne
+uf17ujak6O9dLAIgWfZ6j2ZM7u4A8CUG+z+hvfnGgC0PbKlq368ki/wRhEI=
+-----END CERTIFICATE-----
+";
+
+    #[test]
+    fn reads_certificate_not_after() {
+        let not_after = certificate_not_after(CERT.as_bytes()).unwrap();
+        assert_eq!(not_after.to_rfc3339(), "2026-11-15T15:40:27+00:00");
+        assert!(certificate_not_after(b"-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n").is_err());
+        assert!(certificate_not_after(b"not pem").is_err());
+    }
+
+    #[test]
+    fn lead_time_window_and_tenant_override() {
+        let now = DateTime::parse_from_rfc3339("2026-11-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |days: i64| now + chrono::Duration::days(days);
+        assert!(within(CredentialKind::ApiKey, "k".into(), None, None, at(20), now, 14).is_none());
+        let soon = within(CredentialKind::ApiKey, "k".into(), None, None, at(10), now, 14).unwrap();
+        assert_eq!((soon.days_left, soon.expired), (10, false));
+        let gone = within(CredentialKind::ApiKey, "k".into(), None, None, at(-2), now, 14).unwrap();
+        assert_eq!((gone.days_left, gone.expired), (-2, true));
+
+        let t = Uuid::new_v4();
+        let opts = ExpiryOptions { tenant_lead_days: HashMap::from([(t, 30)]), ..Default::default() };
+        assert_eq!(opts.lead_for(Some(t)), 30);
+        assert_eq!(opts.lead_for(Some(Uuid::new_v4())), 14);
+        assert_eq!(opts.lead_for(None), 14);
+    }
+}
@@ -0,0 +1,303 @@
+//! Feature flags stored in `feature_flag` / `feature_flag_override`.
+//! - Evaluation precedence (see `common::feature_flags`): tenant override, percentage rollout, default.
+//! - Evaluations read an in-memory snapshot of all flags; writes on this replica refresh it immediately,
+//!   other replicas pick changes up within `snapshot_ttl`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::feature_flags::{Evaluation, FlagRule, FlagSet};
+use common::time::{system_clock, SharedClock};
+use models::{feature_flag, feature_flag_override};
+
+use crate::errors::ServiceError;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateFlagInput {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default_enabled: bool,
+    #[serde(default)]
+    pub rollout_percent: i16,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateFlagInput {
+    pub description: Option<String>,
+    pub default_enabled: Option<bool>,
+    pub rollout_percent: Option<i16>,
+}
+
+/// A flag with its tenant overrides.
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagDetail {
+    #[serde(flatten)]
+    pub flag: feature_flag::Model,
+    pub overrides: Vec<feature_flag_override::Model>,
+}
+
+fn validate_name(name: &str) -> Result<(), ServiceError> {
+    let ok = !name.is_empty()
+        && name.len() <= 128
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'));
+    if ok {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation("name must be 1-128 characters of [a-z0-9_.-]".into()))
+    }
+}
+
+fn validate_percent(p: i16) -> Result<(), ServiceError> {
+    if (0..=100).contains(&p) {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation("rollout_percent must be between 0 and 100".into()))
+    }
+}
+
+fn dbe(e: DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+/// Rows -> evaluation rules keyed by flag name.
+fn to_flag_set(flags: Vec<feature_flag::Model>, overrides: Vec<feature_flag_override::Model>) -> FlagSet {
+    let rules: Vec<FlagRule> = flags
+        .into_iter()
+        .map(|f| FlagRule {
+            overrides: overrides.iter().filter(|o| o.flag_id == f.id).map(|o| (o.tenant_id.to_string(), o.enabled)).collect(),
+            name: f.name,
+            default_enabled: f.default_enabled,
+            rollout_percent: f.rollout_percent.clamp(0, 100) as u8,
+        })
+        .collect();
+    FlagSet::from(rules)
+}
+
+pub struct FeatureFlagService {
+    db: DatabaseConnection,
+    clock: SharedClock,
+    snapshot_ttl: Duration,
+    snapshot: RwLock<Option<(Instant, Arc<FlagSet>)>>,
+}
+
+impl FeatureFlagService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, clock: system_clock(), snapshot_ttl: Duration::from_secs(30), snapshot: RwLock::new(None) }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_snapshot_ttl(mut self, ttl: Duration) -> Self {
+        self.snapshot_ttl = ttl;
+        self
+    }
+
+    pub async fn list(&self) -> Result<Vec<FlagDetail>, ServiceError> {
+        let flags = feature_flag::Entity::find().order_by_asc(feature_flag::Column::Name).all(&self.db).await.map_err(dbe)?;
+        let overrides = feature_flag_override::Entity::find().order_by_asc(feature_flag_override::Column::UpdatedAt).all(&self.db).await.map_err(dbe)?;
+        Ok(flags
+            .into_iter()
+            .map(|flag| {
+                let overrides = overrides.iter().filter(|o| o.flag_id == flag.id).cloned().collect();
+                FlagDetail { flag, overrides }
+            })
+            .collect())
+    }
+
+    async fn find(&self, name: &str) -> Result<feature_flag::Model, ServiceError> {
+        feature_flag::Entity::find()
+            .filter(feature_flag::Column::Name.eq(name))
+            .one(&self.db)
+            .await
+            .map_err(dbe)?
+            .ok_or_else(|| ServiceError::not_found("feature flag"))
+    }
+
+    pub async fn get(&self, name: &str) -> Result<FlagDetail, ServiceError> {
+        let flag = self.find(name).await?;
+        let overrides = feature_flag_override::Entity::find()
+            .filter(feature_flag_override::Column::FlagId.eq(flag.id))
+            .all(&self.db)
+            .await
+            .map_err(dbe)?;
+        Ok(FlagDetail { flag, overrides })
+    }
+
+    pub async fn create(&self, input: CreateFlagInput) -> Result<feature_flag::Model, ServiceError> {
+        validate_name(&input.name)?;
+        validate_percent(input.rollout_percent)?;
+        match self.find(&input.name).await {
+            Ok(_) => return Err(ServiceError::Validation(format!("feature flag {} already exists", input.name))),
+            Err(ServiceError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        let now = self.clock.now_fixed();
+        let am = feature_flag::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(input.name),
+            description: Set(input.description),
+            default_enabled: Set(input.default_enabled),
+            rollout_percent: Set(input.rollout_percent),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+        let saved = am.insert(&self.db).await.map_err(dbe)?;
+        info!(flag = %saved.name, default_enabled = saved.default_enabled, rollout_percent = saved.rollout_percent, "feature_flag_created");
+        self.invalidate().await;
+        Ok(saved)
+    }
+
+    pub async fn update(&self, name: &str, input: UpdateFlagInput) -> Result<feature_flag::Model, ServiceError> {
+        let mut am = self.find(name).await?.into_active_model();
+        if let Some(description) = input.description {
+            am.description = Set(Some(description));
+        }
+        if let Some(enabled) = input.default_enabled {
+            am.default_enabled = Set(enabled);
+        }
+        if let Some(p) = input.rollout_percent {
+            validate_percent(p)?;
+            am.rollout_percent = Set(p);
+        }
+        am.updated_at = Set(self.clock.now_fixed());
+        let saved = am.update(&self.db).await.map_err(dbe)?;
+        info!(flag = %saved.name, default_enabled = saved.default_enabled, rollout_percent = saved.rollout_percent, "feature_flag_updated");
+        self.invalidate().await;
+        Ok(saved)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<(), ServiceError> {
+        let flag = self.find(name).await?;
+        feature_flag::Entity::delete_by_id(flag.id).exec(&self.db).await.map_err(dbe)?;
+        info!(flag = %name, "feature_flag_deleted");
+        self.invalidate().await;
+        Ok(())
+    }
+
+    /// Force the flag on or off for one tenant (replaces an existing override).
+    pub async fn set_override(&self, name: &str, tenant_id: Uuid, enabled: bool) -> Result<feature_flag_override::Model, ServiceError> {
+        let flag = self.find(name).await?;
+        let am = feature_flag_override::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            flag_id: Set(flag.id),
+            tenant_id: Set(tenant_id),
+            enabled: Set(enabled),
+            updated_at: Set(self.clock.now_fixed()),
+        };
+        feature_flag_override::Entity::insert(am)
+            .on_conflict(
+                OnConflict::columns([feature_flag_override::Column::FlagId, feature_flag_override::Column::TenantId])
+                    .update_columns([feature_flag_override::Column::Enabled, feature_flag_override::Column::UpdatedAt])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .map_err(dbe)?;
+        info!(flag = %name, tenant_id = %tenant_id, enabled, "feature_flag_override_set");
+        self.invalidate().await;
+        feature_flag_override::Entity::find()
+            .filter(feature_flag_override::Column::FlagId.eq(flag.id))
+            .filter(feature_flag_override::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await
+            .map_err(dbe)?
+            .ok_or_else(|| ServiceError::not_found("feature flag override"))
+    }
+
+    pub async fn remove_override(&self, name: &str, tenant_id: Uuid) -> Result<(), ServiceError> {
+        let flag = self.find(name).await?;
+        let res = feature_flag_override::Entity::delete_many()
+            .filter(feature_flag_override::Column::FlagId.eq(flag.id))
+            .filter(feature_flag_override::Column::TenantId.eq(tenant_id))
+            .exec(&self.db)
+            .await
+            .map_err(dbe)?;
+        if res.rows_affected == 0 {
+            return Err(ServiceError::not_found("feature flag override"));
+        }
+        info!(flag = %name, tenant_id = %tenant_id, "feature_flag_override_removed");
+        self.invalidate().await;
+        Ok(())
+    }
+
+    /// All flags as evaluation rules (cached for `snapshot_ttl`); the gateway's `feature_flags` uses the same shape.
+    pub async fn snapshot(&self) -> Result<Arc<FlagSet>, ServiceError> {
+        if let Some((loaded, set)) = self.snapshot.read().await.as_ref() {
+            if loaded.elapsed() < self.snapshot_ttl {
+                return Ok(Arc::clone(set));
+            }
+        }
+        let flags = feature_flag::Entity::find().all(&self.db).await.map_err(dbe)?;
+        let overrides = feature_flag_override::Entity::find().all(&self.db).await.map_err(dbe)?;
+        let set = Arc::new(to_flag_set(flags, overrides));
+        *self.snapshot.write().await = Some((Instant::now(), Arc::clone(&set)));
+        Ok(set)
+    }
+
+    /// Evaluate one flag for a tenant (or no tenant: default only).
+    pub async fn evaluate(&self, name: &str, tenant_id: Option<Uuid>) -> Result<Evaluation, ServiceError> {
+        let tenant = tenant_id.map(|t| t.to_string());
+        Ok(self.snapshot().await?.evaluate(name, tenant.as_deref()))
+    }
+
+    /// Gate for code paths: unknown flags and load failures return `fallback` so a flag outage keeps current behavior.
+    pub async fn is_enabled(&self, name: &str, tenant_id: Uuid, fallback: bool) -> bool {
+        match self.snapshot().await {
+            Ok(set) => set.enabled_or(name, Some(&tenant_id.to_string()), fallback),
+            Err(e) => {
+                warn!(flag = name, err = %e, "load feature flags failed; using fallback");
+                fallback
+            }
+        }
+    }
+
+    async fn invalidate(&self) {
+        *self.snapshot.write().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn rows_become_rules() {
+        let now = Utc::now().fixed_offset();
+        let flag = feature_flag::Model {
+            id: Uuid::new_v4(),
+            name: "new_limiter".into(),
+            description: None,
+            default_enabled: false,
+            rollout_percent: 0,
+            created_at: now,
+            updated_at: now,
+        };
+        let tenant = Uuid::new_v4();
+        let ov = feature_flag_override::Model { id: Uuid::new_v4(), flag_id: flag.id, tenant_id: tenant, enabled: true, updated_at: now };
+        let set = to_flag_set(vec![flag], vec![ov]);
+        assert!(set.enabled_or("new_limiter", Some(&tenant.to_string()), false));
+        assert!(!set.enabled_or("new_limiter", Some(&Uuid::new_v4().to_string()), true));
+    }
+
+    #[test]
+    fn input_validation() {
+        assert!(validate_name("gateway.bandit_selection").is_ok());
+        assert!(validate_name("Bad Name").is_err());
+        assert!(validate_name("").is_err());
+        assert!(validate_percent(100).is_ok());
+        assert!(validate_percent(101).is_err());
+        assert!(validate_percent(-1).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use common::pagination::{Page, Pagination};
 use crate::errors::ServiceError;
 use crate::storage::json_map_store::JsonMapStore;
 use crate::admin::kv_store::AdminKvStore;
@@ -22,6 +23,13 @@ impl ApiKeysStore {
         self.store.list().await
     }
 
+    /// One page of entries ordered by user (the whole map is held in memory).
+    pub async fn list_page(&self, opts: Pagination) -> Result<Page<(String, String)>, ServiceError> {
+        let mut all = self.store.list().await;
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(Page::from_vec(all, opts)?)
+    }
+
     /// Upsert the API key for a user and persist.
     pub async fn set(&self, user: String, api_key: String) -> Result<(), ServiceError> {
         self.store.insert(user, api_key).await
@@ -41,9 +49,11 @@ impl ApiKeysStore {
 #[async_trait::async_trait]
 impl AdminKvStore for ApiKeysStore {
     async fn list(&self) -> Vec<(String, String)> { self.list().await }
+    async fn list_page(&self, opts: Pagination) -> Result<Page<(String, String)>, ServiceError> { self.list_page(opts).await }
     async fn set(&self, user: String, api_key: String) -> Result<(), ServiceError> { self.set(user, api_key).await }
     async fn delete(&self, user: &str) -> Result<bool, ServiceError> { self.delete(user).await }
     async fn contains_value(&self, value: &str) -> bool { self.contains_value(value).await }
+    async fn health(&self) -> crate::storage::health::StoreHealth { self.store.health().await }
 }
 
 #[cfg(test)]
@@ -1,72 +1,30 @@
 use std::sync::Arc;
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use common::pagination::{Page, Pagination};
+use common::time::{system_clock, SharedClock};
 use uuid::Uuid;
 use crate::errors::ServiceError;
 use crate::storage::json_map_store::JsonMapStore;
 use crate::admin::api_mgmt_store::ApiManagementStore;
 
-/// 认证信息定义：目前支持是否需要 API Key，后续可扩展为更多类型
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct AuthInfo {
-    pub require_api_key: bool,
-}
-
-/// API 记录结构：用于描述被代理/转发的 API
-/// - endpoint_url: 例如 `/api/v1/orders`
-/// - method: `GET|POST|PUT|DELETE|PATCH|HEAD|OPTIONS`
-/// - forward_target: 例如 `https://upstream.example.com`
-/// - auth: 认证要求，目前仅包含是否需要 API Key
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub struct ApiRecord {
-    pub id: Uuid,
-    pub endpoint_url: String,
-    pub method: String,
-    pub forward_target: String,
-    pub auth: AuthInfo,
-    pub created_at: DateTime<Utc>,
-}
-
-/// 创建/更新输入模型：不包含 id/created_at，由服务端生成
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
-pub struct ApiRecordInput {
-    pub endpoint_url: String,
-    pub method: String,
-    pub forward_target: String,
-    pub auth: AuthInfo,
-}
-
-impl ApiRecordInput {
-    /// 统一校验：方法、路径、目标地址
-    pub fn validate(&self) -> Result<(), ServiceError> {
-        let method_up = self.method.to_ascii_uppercase();
-        let valid_methods = [
-            "GET","POST","PUT","DELETE","PATCH","HEAD","OPTIONS"
-        ];
-        if !valid_methods.contains(&method_up.as_str()) {
-            return Err(ServiceError::Validation("invalid HTTP method".into()));
-        }
-        if !(self.endpoint_url.starts_with('/')) {
-            return Err(ServiceError::Validation("endpoint_url must start with '/'".into()));
-        }
-        if !(self.forward_target.starts_with("http://") || self.forward_target.starts_with("https://")) {
-            return Err(ServiceError::Validation("forward_target must start with http(s)".into()));
-        }
-        Ok(())
-    }
-}
+pub use crate::admin::api_mgmt_store::{ApiRecord, ApiRecordInput, AuthInfo};
 
 /// 文件存储：以 JSON 文件持久化 API 列表
 #[derive(Clone)]
 pub struct ApiStore {
     store: Arc<JsonMapStore<Uuid, ApiRecord>>,
+    clock: SharedClock,
 }
 
 impl ApiStore {
     /// 初始化存储，若文件不存在则创建空文件
     pub async fn new<P: Into<std::path::PathBuf>>(path: P) -> Result<Arc<Self>, ServiceError> {
+        Self::new_with_clock(path, system_clock()).await
+    }
+
+    /// 指定时间源初始化（测试中可注入 MockClock）
+    pub async fn new_with_clock<P: Into<std::path::PathBuf>>(path: P, clock: SharedClock) -> Result<Arc<Self>, ServiceError> {
         let store = JsonMapStore::<Uuid, ApiRecord>::new(path).await?;
-        Ok(Arc::new(Self { store }))
+        Ok(Arc::new(Self { store, clock }))
     }
 
     /// 列出全部 API
@@ -79,6 +37,13 @@ impl ApiStore {
             .collect()
     }
 
+    /// 分页列出，按创建时间排序（整表已在内存中）
+    pub async fn list_page(&self, opts: Pagination) -> Result<Page<ApiRecord>, ServiceError> {
+        let mut all = self.list().await;
+        all.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(Page::from_vec(all, opts)?)
+    }
+
     /// 根据 id 获取
     pub async fn get(&self, id: Uuid) -> Option<ApiRecord> {
         self.store.get(&id).await
@@ -93,7 +58,7 @@ impl ApiStore {
             method: input.method.to_ascii_uppercase(),
             forward_target: input.forward_target,
             auth: input.auth,
-            created_at: Utc::now(),
+            created_at: self.clock.now(),
         };
         self.store.insert(rec.id, rec.clone()).await?;
         Ok(rec)
@@ -126,10 +91,12 @@ impl ApiStore {
 #[async_trait::async_trait]
 impl ApiManagementStore for ApiStore {
     async fn list(&self) -> Vec<ApiRecord> { self.list().await }
+    async fn list_page(&self, opts: Pagination) -> Result<Page<ApiRecord>, ServiceError> { self.list_page(opts).await }
     async fn get(&self, id: Uuid) -> Option<ApiRecord> { self.get(id).await }
     async fn create(&self, input: ApiRecordInput) -> Result<ApiRecord, ServiceError> { self.create(input).await }
     async fn update(&self, id: Uuid, input: ApiRecordInput) -> Result<ApiRecord, ServiceError> { self.update(id, input).await }
     async fn delete(&self, id: Uuid) -> Result<bool, ServiceError> { self.delete(id).await }
+    async fn health(&self) -> crate::storage::health::StoreHealth { self.store.health().await }
 }
 
 #[cfg(test)]
@@ -149,7 +116,7 @@ mod tests {
         let input = ApiRecordInput {
             endpoint_url: "/admin/posts".into(),
             method: "get".into(),
-            forward_target: "https://jsonplaceholder.typicode.com".into(),
+            forward_target: "http://127.0.0.1:9099".into(),
             auth: AuthInfo { require_api_key: true },
         };
         let created = store.create(input.clone()).await.expect("create ok");
@@ -200,4 +167,28 @@ mod tests {
         };
         assert!(matches!(store.create(bad3).await, Err(ServiceError::Validation(_))));
     }
+
+    #[tokio::test]
+    async fn api_store_uses_injected_clock() {
+        use chrono::{TimeZone, Utc};
+        use common::time::MockClock;
+
+        let fixed = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let store = ApiStore::new_with_clock("data/test_apis_clock.json", Arc::new(MockClock::new(fixed)))
+            .await
+            .expect("store init");
+        let created = store
+            .create(ApiRecordInput {
+                endpoint_url: "/clock".into(),
+                method: "GET".into(),
+                forward_target: "https://example.com".into(),
+                auth: AuthInfo { require_api_key: false },
+            })
+            .await
+            .expect("create ok");
+        assert_eq!(created.created_at, fixed);
+        let json = serde_json::to_value(&created).unwrap();
+        assert_eq!(json["created_at"], "2024-01-02T03:04:05Z");
+        store.delete(created.id).await.expect("delete ok");
+    }
 }
\ No newline at end of file
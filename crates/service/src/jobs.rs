@@ -0,0 +1,344 @@
+//! Async jobs for long-running admin operations (bulk import, tenant export, batch revocation).
+//! - `submit` returns immediately with a job id; callers answer `202 Accepted`.
+//! - At most `max_concurrent` jobs run at once; beyond `max_pending` unfinished jobs new
+//!   submissions are rejected so callers can back off (429 + Retry-After).
+//! - Cancellation is cooperative: jobs check `JobContext::is_cancelled` between items and
+//!   keep whatever they already applied.
+//! - Finished jobs are kept in memory (newest `retention`) for progress/result polling.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::errors::ServiceError;
+
+/// Admin jobs by kind and final status (`rejected` when the queue is full).
+pub static ADMIN_JOBS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        crate::observability::metric_names::ADMIN_JOBS_TOTAL,
+        "Admin background jobs by kind and final status",
+        &["kind", "status"]
+    )
+    .expect("register admin_jobs_total")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct JobProgress {
+    pub done: u64,
+    /// 0 until the job knows how many items it will process
+    pub total: u64,
+}
+
+/// Job snapshot returned by the jobs API.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: Uuid,
+    pub kind: String,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    /// Job output; also set for cancelled jobs when they report partial results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub cancel_requested: bool,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+struct JobEntry {
+    info: Mutex<JobInfo>,
+    cancel: AtomicBool,
+}
+
+impl JobEntry {
+    fn snapshot(&self) -> JobInfo {
+        self.info.lock().expect("job lock").clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut JobInfo)) {
+        f(&mut self.info.lock().expect("job lock"))
+    }
+}
+
+/// Handed to the job body for progress reporting and cancellation checks.
+#[derive(Clone)]
+pub struct JobContext {
+    entry: Arc<JobEntry>,
+}
+
+impl JobContext {
+    pub fn id(&self) -> Uuid {
+        self.entry.snapshot().id
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.entry.update(|i| i.progress.total = total);
+    }
+
+    /// For jobs that discover their work in stages.
+    pub fn add_total(&self, n: u64) {
+        self.entry.update(|i| i.progress.total += n);
+    }
+
+    pub fn advance(&self, n: u64) {
+        self.entry.update(|i| i.progress.done += n);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.entry.cancel.load(Ordering::SeqCst)
+    }
+}
+
+/// Rejected submission: too many unfinished jobs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueFull {
+    pub pending: usize,
+}
+
+impl std::fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} admin jobs pending; retry later", self.pending)
+    }
+}
+
+pub struct JobManager {
+    jobs: Mutex<HashMap<Uuid, Arc<JobEntry>>>,
+    slots: Arc<Semaphore>,
+    max_pending: usize,
+    retention: usize,
+}
+
+impl JobManager {
+    pub fn new(max_concurrent: usize, max_pending: usize, retention: usize) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            max_pending: max_pending.max(1),
+            retention,
+        }
+    }
+
+    /// Queue `body` and return its initial snapshot. The body's `Ok` value becomes the job
+    /// result; if cancellation was requested the job ends as `cancelled` with that partial result.
+    pub fn submit<F, Fut>(&self, kind: &str, body: F) -> Result<JobInfo, QueueFull>
+    where
+        F: FnOnce(JobContext) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Value, ServiceError>> + Send + 'static,
+    {
+        let entry = {
+            let mut jobs = self.jobs.lock().expect("jobs lock");
+            let pending = jobs.values().filter(|e| !e.snapshot().status.is_finished()).count();
+            if pending >= self.max_pending {
+                ADMIN_JOBS_TOTAL.with_label_values(&[kind, "rejected"]).inc();
+                warn!(kind, pending, "admin_job_rejected");
+                return Err(QueueFull { pending });
+            }
+            prune(&mut jobs, self.retention);
+            let info = JobInfo {
+                id: Uuid::new_v4(),
+                kind: kind.to_string(),
+                status: JobStatus::Queued,
+                progress: JobProgress::default(),
+                result: None,
+                error: None,
+                cancel_requested: false,
+                created_at: Utc::now(),
+                started_at: None,
+                finished_at: None,
+            };
+            let entry = Arc::new(JobEntry { info: Mutex::new(info), cancel: AtomicBool::new(false) });
+            jobs.insert(entry.snapshot().id, Arc::clone(&entry));
+            entry
+        };
+        let snapshot = entry.snapshot();
+        info!(job_id = %snapshot.id, kind, "admin_job_queued");
+
+        let slots = Arc::clone(&self.slots);
+        tokio::spawn(async move {
+            let _permit = slots.acquire_owned().await.expect("job semaphore closed");
+            if entry.cancel.load(Ordering::SeqCst) {
+                finish(&entry, JobStatus::Cancelled, None, None);
+                return;
+            }
+            entry.update(|i| {
+                i.status = JobStatus::Running;
+                i.started_at = Some(Utc::now());
+            });
+            let ctx = JobContext { entry: Arc::clone(&entry) };
+            // 任务体 panic 时也要落到 failed，而不是永远停在 running
+            let outcome = tokio::spawn(body(ctx)).await;
+            let cancelled = entry.cancel.load(Ordering::SeqCst);
+            match outcome {
+                Ok(Ok(v)) if cancelled => finish(&entry, JobStatus::Cancelled, Some(v), None),
+                Ok(Ok(v)) => finish(&entry, JobStatus::Succeeded, Some(v), None),
+                Ok(Err(e)) => finish(&entry, JobStatus::Failed, None, Some(e.to_string())),
+                Err(e) => finish(&entry, JobStatus::Failed, None, Some(format!("job panicked: {}", e))),
+            }
+        });
+        Ok(snapshot)
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<JobInfo> {
+        self.jobs.lock().expect("jobs lock").get(&id).map(|e| e.snapshot())
+    }
+
+    /// Newest first.
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut all: Vec<JobInfo> = self.jobs.lock().expect("jobs lock").values().map(|e| e.snapshot()).collect();
+        all.sort_by_key(|j| std::cmp::Reverse(j.created_at));
+        all
+    }
+
+    /// Request cancellation; finished jobs are returned unchanged.
+    pub fn cancel(&self, id: Uuid) -> Option<JobInfo> {
+        let entry = self.jobs.lock().expect("jobs lock").get(&id).cloned()?;
+        if !entry.snapshot().status.is_finished() {
+            entry.cancel.store(true, Ordering::SeqCst);
+            entry.update(|i| i.cancel_requested = true);
+            info!(job_id = %id, "admin_job_cancel_requested");
+        }
+        Some(entry.snapshot())
+    }
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new(2, 16, 100)
+    }
+}
+
+fn finish(entry: &JobEntry, status: JobStatus, result: Option<Value>, error: Option<String>) {
+    entry.update(|i| {
+        i.status = status;
+        i.result = result;
+        i.error = error;
+        i.finished_at = Some(Utc::now());
+        ADMIN_JOBS_TOTAL.with_label_values(&[&i.kind, status.as_str()]).inc();
+        info!(job_id = %i.id, kind = %i.kind, status = status.as_str(), done = i.progress.done, total = i.progress.total, "admin_job_finished");
+    });
+}
+
+/// Keep the newest `retention` finished jobs.
+fn prune(jobs: &mut HashMap<Uuid, Arc<JobEntry>>, retention: usize) {
+    let mut finished: Vec<(DateTime<Utc>, Uuid)> = jobs
+        .values()
+        .map(|e| e.snapshot())
+        .filter(|i| i.status.is_finished())
+        .map(|i| (i.finished_at.unwrap_or(i.created_at), i.id))
+        .collect();
+    if finished.len() <= retention {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - retention) {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_finished(m: &JobManager, id: Uuid) -> JobInfo {
+        for _ in 0..200 {
+            let info = m.get(id).unwrap();
+            if info.status.is_finished() {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn job_reports_progress_and_result() {
+        let m = JobManager::new(1, 4, 10);
+        let job = m
+            .submit("count", |ctx| async move {
+                ctx.set_total(3);
+                for _ in 0..3 {
+                    ctx.advance(1);
+                }
+                Ok(serde_json::json!({ "counted": 3 }))
+            })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        let done = wait_finished(&m, job.id).await;
+        assert_eq!(done.status, JobStatus::Succeeded);
+        assert_eq!(done.progress, JobProgress { done: 3, total: 3 });
+        assert_eq!(done.result.unwrap()["counted"], 3);
+
+        let failed = m.submit("fail", |_| async { Err(ServiceError::Validation("bad input".into())) }).unwrap();
+        let failed = wait_finished(&m, failed.id).await;
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert!(failed.error.unwrap().contains("bad input"));
+    }
+
+    #[tokio::test]
+    async fn cancellation_and_backpressure() {
+        let m = JobManager::new(1, 2, 10);
+        let running = m
+            .submit("slow", |ctx| async move {
+                let mut done = 0;
+                while !ctx.is_cancelled() {
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                    done += 1;
+                }
+                Ok(serde_json::json!({ "done": done }))
+            })
+            .unwrap();
+        // 单并发：第二个任务排队
+        let queued = m.submit("slow", |_| async { Ok(Value::Null) }).unwrap();
+        assert_eq!(m.submit("slow", |_| async { Ok(Value::Null) }).unwrap_err(), QueueFull { pending: 2 });
+
+        while m.get(running.id).unwrap().status != JobStatus::Running {
+            tokio::task::yield_now().await;
+        }
+        m.cancel(queued.id).unwrap();
+        m.cancel(running.id).unwrap();
+        let running = wait_finished(&m, running.id).await;
+        assert_eq!(running.status, JobStatus::Cancelled);
+        assert!(running.result.is_some());
+        assert_eq!(wait_finished(&m, queued.id).await.status, JobStatus::Cancelled);
+        assert!(m.submit("slow", |_| async { Ok(Value::Null) }).is_ok());
+    }
+}
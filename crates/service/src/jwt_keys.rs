@@ -0,0 +1,183 @@
+//! Login token signing keys stored in `jwt_signing_key`.
+//! - Key material is stored in an encrypted column (`models::encrypted::EncryptedString`) as base64; rows written
+//!   before column encryption keep the legacy `v1:{base64}` format and are opened with the legacy key.
+//! - `rotate` makes a new key active; the previous one keeps verifying for `grace` (tokens it signed are still live).
+//! - The in-memory `KeyRing` is rebuilt after every change on this replica and by `spawn_refresh` for the others.
+//! - Tokens without `kid` stop verifying `grace` after the first key was created.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set, TransactionTrait};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::time::{system_clock, SharedClock};
+use models::encrypted::EncryptedString;
+use models::jwt_signing_key;
+
+use crate::auth::keys::{KeyAlgorithm, KeyMaterial, KeyRing};
+use crate::errors::ServiceError;
+
+fn dbe(e: DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+/// Raw key material of a decrypted column value.
+fn material_of(value: &str) -> Result<Vec<u8>, ServiceError> {
+    if common::crypto::is_legacy_sealed(value) {
+        let keys = common::crypto::installed().ok_or(common::crypto::CryptoError::NoMasterKey)?;
+        return Ok(keys.open_legacy(value)?);
+    }
+    STANDARD.decode(value).map_err(|_| ServiceError::Crypto("key material is not base64".into()))
+}
+
+/// Column plaintext for a stored value, converting the legacy format to base64 material (re-encryption job).
+pub(crate) fn column_plaintext(stored: String) -> Result<String, ServiceError> {
+    if common::crypto::is_legacy_sealed(&stored) {
+        return Ok(STANDARD.encode(material_of(&stored)?));
+    }
+    Ok(EncryptedString::from_stored(stored)?.into_inner())
+}
+
+pub struct JwtKeyService {
+    db: DatabaseConnection,
+    clock: SharedClock,
+    grace: Duration,
+    ring: ArcSwap<KeyRing>,
+}
+
+impl JwtKeyService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db, clock: system_clock(), grace: Duration::from_secs(12 * 3600), ring: ArcSwap::from_pointee(KeyRing::default()) }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// How long a superseded key keeps verifying; should cover the token TTL.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Keys currently loaded (empty until the first `reload`).
+    pub fn current(&self) -> Arc<KeyRing> {
+        self.ring.load_full()
+    }
+
+    /// All keys, newest first; key material is never serialized.
+    pub async fn list(&self) -> Result<Vec<jwt_signing_key::Model>, ServiceError> {
+        jwt_signing_key::Entity::find().order_by_desc(jwt_signing_key::Column::CreatedAt).all(&self.db).await.map_err(dbe)
+    }
+
+    /// Rebuild the key ring from keys still inside their verification window.
+    pub async fn reload(&self) -> Result<Arc<KeyRing>, ServiceError> {
+        let now = self.clock.now_fixed();
+        let rows = jwt_signing_key::Entity::find()
+            .filter(Condition::any().add(jwt_signing_key::Column::VerifyUntil.is_null()).add(jwt_signing_key::Column::VerifyUntil.gt(now)))
+            .all(&self.db)
+            .await
+            .map_err(dbe)?;
+        let mut ring = KeyRing::default();
+        for row in rows {
+            let Some(algorithm) = KeyAlgorithm::parse(&row.algorithm) else {
+                warn!(kid = %row.kid, algorithm = %row.algorithm, "unsupported jwt key algorithm; skipped");
+                continue;
+            };
+            let secret = match material_of(row.secret.as_str()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!(kid = %row.kid, err = %e, "jwt key material cannot be read; skipped");
+                    continue;
+                }
+            };
+            let material = KeyMaterial { algorithm, secret };
+            let until = row.verify_until.map(|t| t.with_timezone(&chrono::Utc));
+            if let Err(e) = ring.insert(&row.kid, &material, row.active, until) {
+                warn!(kid = %row.kid, err = %e, "invalid jwt key material; skipped");
+            }
+        }
+        // Tokens without kid verify with jwt_secret only until grace after the first key existed.
+        let first = jwt_signing_key::Entity::find().order_by_asc(jwt_signing_key::Column::CreatedAt).one(&self.db).await.map_err(dbe)?;
+        let grace = chrono::Duration::from_std(self.grace).unwrap_or_else(|_| chrono::Duration::hours(12));
+        ring.set_legacy_until(first.map(|k| k.created_at.with_timezone(&chrono::Utc) + grace));
+        let ring = Arc::new(ring);
+        self.ring.store(Arc::clone(&ring));
+        Ok(ring)
+    }
+
+    /// Generate and activate a new key; the previous active key verifies until now + grace.
+    pub async fn rotate(&self, algorithm: KeyAlgorithm) -> Result<jwt_signing_key::Model, ServiceError> {
+        let material = KeyMaterial::generate(algorithm).map_err(|e| ServiceError::Crypto(e.to_string()))?;
+        let public_key = material.public_key().map_err(|e| ServiceError::Crypto(e.to_string()))?;
+        let now = self.clock.now_fixed();
+        let grace = chrono::Duration::from_std(self.grace).unwrap_or_else(|_| chrono::Duration::hours(12));
+        let kid = format!("{}-{}", now.format("%Y%m%d%H%M%S"), &Uuid::new_v4().simple().to_string()[..8]);
+
+        let txn = self.db.begin().await.map_err(dbe)?;
+        let previous = jwt_signing_key::Entity::find().filter(jwt_signing_key::Column::Active.eq(true)).all(&txn).await.map_err(dbe)?;
+        for key in previous {
+            let mut am = key.into_active_model();
+            am.active = Set(false);
+            am.verify_until = Set(Some(now + grace));
+            am.update(&txn).await.map_err(dbe)?;
+        }
+        let saved = jwt_signing_key::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            kid: Set(kid),
+            algorithm: Set(algorithm.as_str().to_string()),
+            secret: Set(EncryptedString::new(STANDARD.encode(&material.secret))?),
+            public_key: Set(public_key),
+            active: Set(true),
+            created_at: Set(now),
+            verify_until: Set(None),
+        }
+        .insert(&txn)
+        .await
+        .map_err(dbe)?;
+        txn.commit().await.map_err(dbe)?;
+        info!(kid = %saved.kid, algorithm = %saved.algorithm, grace_secs = self.grace.as_secs(), "jwt_key_rotated");
+        self.reload().await?;
+        Ok(saved)
+    }
+
+    /// Stop accepting tokens signed with `kid` immediately; the active key must be rotated out first.
+    pub async fn retire(&self, kid: &str) -> Result<(), ServiceError> {
+        let key = jwt_signing_key::Entity::find()
+            .filter(jwt_signing_key::Column::Kid.eq(kid))
+            .one(&self.db)
+            .await
+            .map_err(dbe)?
+            .ok_or_else(|| ServiceError::not_found("jwt key"))?;
+        if key.active {
+            return Err(ServiceError::Validation("cannot retire the active key; rotate first".into()));
+        }
+        let mut am = key.into_active_model();
+        am.verify_until = Set(Some(self.clock.now_fixed()));
+        am.update(&self.db).await.map_err(dbe)?;
+        info!(kid = %kid, "jwt_key_retired");
+        self.reload().await?;
+        Ok(())
+    }
+
+    /// Periodic reload so rotations on other replicas take effect.
+    pub fn spawn_refresh(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = this.reload().await {
+                    warn!(err = %e, "jwt_key_reload_failed");
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,111 @@
+//! Asynchronous `api_key.last_used_at` tracking for the data plane.
+//! - `record` only touches an in-memory map, so the request path never waits on the database;
+//! - a background loop flushes the latest use per key every interval, at most one write per key;
+//! - `last_used_at` only moves forward, so several gateway instances flushing the same key is fine.
+//!   Uses recorded since the last flush are lost if the process exits.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{Database, DatabaseConnection};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::db::apikey_service;
+
+/// Delay before reconnecting after the database could not be reached
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub struct ApiKeyActivity {
+    pending: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl ApiKeyActivity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note a use of `key_id` at `at`; keeps the latest use per key.
+    pub fn record(&self, key_id: Uuid, at: DateTime<Utc>) {
+        let mut pending = self.pending.lock().expect("key activity lock");
+        let last = pending.entry(key_id).or_insert(at);
+        if *last < at {
+            *last = at;
+        }
+    }
+
+    /// [`Self::record`] at the current time
+    pub fn record_now(&self, key_id: Uuid) {
+        self.record(key_id, Utc::now());
+    }
+
+    /// Number of keys waiting to be flushed
+    pub fn pending(&self) -> usize {
+        self.pending.lock().expect("key activity lock").len()
+    }
+
+    fn take(&self) -> HashMap<Uuid, DateTime<Utc>> {
+        std::mem::take(&mut *self.pending.lock().expect("key activity lock"))
+    }
+
+    /// Write pending uses to `api_key.last_used_at`; returns the number of keys updated.
+    /// Failed writes are put back and retried on the next flush.
+    pub async fn flush(&self, db: &DatabaseConnection) -> usize {
+        let mut updated = 0;
+        for (key_id, at) in self.take() {
+            match apikey_service::touch_api_key_last_used(db, key_id, at).await {
+                Ok(touched) => updated += touched as usize,
+                Err(e) => {
+                    warn!(api_key_id = %key_id, err = %e, "failed to update api key last_used_at");
+                    self.record(key_id, at);
+                }
+            }
+        }
+        updated
+    }
+
+    /// Connect to `database_url` and flush every `interval` until the future is dropped.
+    pub async fn run(&self, database_url: &str, interval: Duration) {
+        let db = loop {
+            match Database::connect(database_url).await {
+                Ok(db) => break db,
+                Err(e) => {
+                    warn!(err = %e, "key activity: database unavailable, retrying");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        };
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let updated = self.flush(&db).await;
+            if updated > 0 {
+                debug!(updated, "api key last_used_at flushed");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_use_per_key() {
+        let activity = ApiKeyActivity::new();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let t = Utc::now();
+        activity.record(a, t);
+        activity.record(a, t - chrono::Duration::seconds(5));
+        activity.record(b, t);
+        activity.record(b, t + chrono::Duration::seconds(5));
+        let taken = activity.take();
+        assert_eq!(taken[&a], t);
+        assert_eq!(taken[&b], t + chrono::Duration::seconds(5));
+        assert_eq!(activity.pending(), 0);
+    }
+}
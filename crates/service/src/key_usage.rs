@@ -0,0 +1,244 @@
+//! Per-API-key usage for `GET /admin/api-keys/{id}/usage`, so key owners can self-diagnose integrations.
+//! - Computed from `request_log` filtered by `api_key_id`; `metrics_rollup` is per route only, so it
+//!   cannot answer per-key questions. This is the Postgres query; the ClickHouse backend has its own in
+//!   [`crate::request_log_repo::clickhouse`].
+//! - Ranges are capped at [`MAX_RANGE`] to keep the scan bounded; `request_log (api_key_id, timestamp)`
+//!   is indexed for it.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::errors::ServiceError;
+use crate::stats::WindowStats;
+
+/// Longest range one query may cover.
+pub const MAX_RANGE: chrono::Duration = chrono::Duration::days(31);
+pub const MAX_TOP_ROUTES: u32 = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyUsageQuery {
+    pub api_key_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub top: u32,
+}
+
+impl KeyUsageQuery {
+    pub fn validate(&self) -> Result<(), ServiceError> {
+        if self.to <= self.from {
+            return Err(ServiceError::Validation("to must be after from".into()));
+        }
+        if self.to - self.from > MAX_RANGE {
+            return Err(ServiceError::Validation(format!("range must not exceed {} days", MAX_RANGE.num_days())));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, FromQueryResult)]
+pub struct LatencyStats {
+    pub avg_ms: Option<f64>,
+    pub p50_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub max_ms: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct StatusCount {
+    pub status_code: i32,
+    pub requests: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct KeyRouteUsage {
+    pub route_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub p95_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyUsage {
+    pub api_key_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    #[serde(flatten)]
+    pub totals: WindowStats,
+    pub latency: LatencyStats,
+    /// Most frequent first
+    pub status_codes: Vec<StatusCount>,
+    /// By request count
+    pub top_routes: Vec<KeyRouteUsage>,
+    pub last_request_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TotalsRow {
+    requests: i64,
+    errors: i64,
+    rate_limited: i64,
+    last_request_at: Option<DateTime<chrono::FixedOffset>>,
+}
+
+fn dbe(e: sea_orm::DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+fn stmt(sql: &str, values: Vec<sea_orm::Value>) -> Statement {
+    Statement::from_sql_and_values(DbBackend::Postgres, sql, values)
+}
+
+/// Validate `q` and make sure the key exists; keys live in Postgres whatever the request log backend.
+pub(crate) async fn check_query(db: &DatabaseConnection, q: &KeyUsageQuery) -> Result<(), ServiceError> {
+    q.validate()?;
+    if models::apikey::Entity::find_by_id(q.api_key_id).one(db).await.map_err(dbe)?.is_none() {
+        return Err(ServiceError::not_found("api key"));
+    }
+    Ok(())
+}
+
+/// Usage of one key in `[from, to)`; `NotFound` when the key does not exist.
+pub async fn usage(db: &DatabaseConnection, q: &KeyUsageQuery) -> Result<KeyUsage, ServiceError> {
+    check_query(db, q).await?;
+    let range = || -> Vec<sea_orm::Value> { vec![q.api_key_id.into(), q.from.fixed_offset().into(), q.to.fixed_offset().into()] };
+    const WHERE: &str = "WHERE l.api_key_id = $1 AND l.timestamp >= $2 AND l.timestamp < $3";
+
+    let totals = TotalsRow::find_by_statement(stmt(
+        &format!(
+            "SELECT count(*)::bigint AS requests, \
+                    count(*) FILTER (WHERE NOT l.success)::bigint AS errors, \
+                    count(*) FILTER (WHERE l.status_code = 429)::bigint AS rate_limited, \
+                    max(l.timestamp) AS last_request_at \
+             FROM request_log l {}",
+            WHERE
+        ),
+        range(),
+    ))
+    .one(db)
+    .await
+    .map_err(dbe)?;
+
+    let latency = LatencyStats::find_by_statement(stmt(
+        &format!(
+            "SELECT avg(l.latency_ms)::double precision AS avg_ms, \
+                    percentile_cont(0.5) WITHIN GROUP (ORDER BY l.latency_ms) AS p50_ms, \
+                    percentile_cont(0.95) WITHIN GROUP (ORDER BY l.latency_ms) AS p95_ms, \
+                    percentile_cont(0.99) WITHIN GROUP (ORDER BY l.latency_ms) AS p99_ms, \
+                    max(l.latency_ms) AS max_ms \
+             FROM request_log l {}",
+            WHERE
+        ),
+        range(),
+    ))
+    .one(db)
+    .await
+    .map_err(dbe)?
+    .unwrap_or_default();
+
+    let status_codes = StatusCount::find_by_statement(stmt(
+        &format!("SELECT l.status_code, count(*)::bigint AS requests FROM request_log l {} GROUP BY l.status_code ORDER BY requests DESC, l.status_code", WHERE),
+        range(),
+    ))
+    .all(db)
+    .await
+    .map_err(dbe)?;
+
+    let mut values = range();
+    values.push((q.top.clamp(1, MAX_TOP_ROUTES) as i64).into());
+    let top_routes = KeyRouteUsage::find_by_statement(stmt(
+        &format!(
+            "SELECT r.id AS route_id, r.method, r.path, \
+                    count(*)::bigint AS requests, \
+                    count(*) FILTER (WHERE NOT l.success)::bigint AS errors, \
+                    percentile_cont(0.95) WITHIN GROUP (ORDER BY l.latency_ms) AS p95_ms \
+             FROM request_log l JOIN route r ON r.id = l.route_id {} \
+             GROUP BY r.id, r.method, r.path \
+             ORDER BY requests DESC LIMIT $4",
+            WHERE
+        ),
+        values,
+    ))
+    .all(db)
+    .await
+    .map_err(dbe)?;
+
+    let (totals, last_request_at) = match totals {
+        Some(t) => (WindowStats::new(t.requests, t.errors, t.rate_limited), t.last_request_at.map(|t| t.with_timezone(&Utc))),
+        None => (WindowStats::default(), None),
+    };
+    Ok(KeyUsage { api_key_id: q.api_key_id, from: q.from, to: q.to, totals, latency, status_codes, top_routes, last_request_at })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::request_log_service::create_request_log;
+    use crate::test_support::get_db;
+    use models::{apikey, route, tenant, upstream, user};
+    use sea_orm::{ActiveModelTrait, Set};
+
+    #[test]
+    fn validate_range() {
+        let now = Utc::now();
+        let q = |from, to| KeyUsageQuery { api_key_id: Uuid::nil(), from, to, top: 10 };
+        assert!(q(now - chrono::Duration::hours(1), now).validate().is_ok());
+        assert!(q(now, now).validate().is_err());
+        assert!(q(now - chrono::Duration::days(32), now).validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn usage_for_key() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+
+        let t = tenant::create(&db, &format!("usage_tenant_{}", Uuid::new_v4())).await?;
+        let u = user::create(&db, t.id, &format!("usage_{}@example.com", Uuid::new_v4().simple()), "Usage").await?;
+        let key = apikey::create(&db, u.id, &format!("usage_key_hash_{}", Uuid::new_v4().simple())).await?;
+        let other = apikey::create(&db, u.id, &format!("usage_key_hash_{}", Uuid::new_v4().simple())).await?;
+        let up = upstream::create(&db, &format!("usage_up_{}", Uuid::new_v4()), "https://api.example.com").await?;
+        let mut routes = Vec::new();
+        for path in ["/orders", "/users"] {
+            routes.push(route::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                tenant_id: Set(t.id),
+                method: Set("GET".into()),
+                path: Set(path.into()),
+                upstream_id: Set(up.id),
+                timeout_ms: Set(1000),
+                retry_max_attempts: Set(2),
+                circuit_breaker_threshold: Set(5),
+                rate_limit_id: Set(None),
+                created_at: Set(Utc::now().into()),
+                blue_upstream_id: Set(None),
+                green_upstream_id: Set(None),
+                active_color: Set(None),
+                previous_upstream_id: Set(None),
+            }.insert(&db).await?);
+        }
+        for (route, status, latency) in [(0, 200, 10), (0, 200, 20), (0, 502, 300), (1, 429, 1)] {
+            create_request_log(&db, routes[route].id, Some(key.id), status, latency, status < 400, None, None, None, None, None).await?;
+        }
+        create_request_log(&db, routes[1].id, Some(other.id), 200, 5, true, None, None, None, None, None).await?;
+
+        let now = Utc::now();
+        let q = KeyUsageQuery { api_key_id: key.id, from: now - chrono::Duration::hours(1), to: now + chrono::Duration::minutes(1), top: 10 };
+        let usage = usage(&db, &q).await?;
+        assert_eq!((usage.totals.requests, usage.totals.errors, usage.totals.rate_limited), (4, 2, 1));
+        assert_eq!(usage.totals.error_rate, 0.5);
+        assert_eq!(usage.latency.max_ms, Some(300));
+        assert_eq!(usage.status_codes[0], StatusCount { status_code: 200, requests: 2 });
+        assert_eq!(usage.top_routes.iter().map(|r| (r.path.as_str(), r.requests, r.errors)).collect::<Vec<_>>(), vec![("/orders", 3, 1), ("/users", 1, 1)]);
+        assert!(usage.last_request_at.is_some());
+
+        let missing = KeyUsageQuery { api_key_id: Uuid::new_v4(), ..q.clone() };
+        assert!(matches!(super::usage(&db, &missing).await, Err(ServiceError::NotFound(_))));
+        let earlier = KeyUsageQuery { to: now - chrono::Duration::minutes(30), ..q };
+        assert_eq!(super::usage(&db, &earlier).await?.totals.requests, 0);
+        Ok(())
+    }
+}
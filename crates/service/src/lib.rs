@@ -14,3 +14,28 @@ pub mod db;
 pub mod file;
 pub mod admin;
 pub mod proxy_api;
+pub mod events;
+pub mod cache;
+pub mod backup;
+pub mod seed;
+pub mod signup;
+pub mod observability;
+pub mod revisions;
+pub mod jobs;
+pub mod anomaly;
+pub mod webhooks;
+pub mod expiry;
+pub mod stats;
+pub mod key_usage;
+pub mod key_activity;
+pub mod reports;
+pub mod scheduled_reports;
+pub mod metrics_rollup;
+pub mod feature_flags;
+pub mod jwt_keys;
+pub mod encryption;
+pub mod request_log_stream;
+pub mod log_export;
+pub mod request_log_repo;
+pub mod replay;
+pub mod capture;
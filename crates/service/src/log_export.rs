@@ -0,0 +1,498 @@
+//! Export of the live request log (see [`crate::request_log_stream`]) to external sinks, so analytics
+//! consumers read gateway traffic without querying Postgres.
+//! - The exporter subscribes to the stream, batches entries by `batch_size` / `flush_interval` and hands
+//!   each batch to every sink's own queue; a slow or failing sink drops batches once its queue is full
+//!   instead of holding back the others.
+//! - Sinks: generic HTTP bulk endpoint (NDJSON or JSON array), Kafka through a Kafka REST Proxy
+//!   (`POST /topics/{topic}`, keyed by tenant), and S3/MinIO objects of JSON lines signed with SigV4.
+//! - Delivery is at most once: a batch is retried `max_retries` times with doubling backoff, then dropped.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::time::{system_clock, SharedClock};
+
+use crate::request_log_stream::{LiveRequestLog, RequestLogStream};
+
+pub static LOG_EXPORT_RECORDS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "api_proxy_log_export_records_total",
+        "Request log entries handled per export sink by result (exported/failed/dropped)",
+        &["sink", "result"]
+    )
+    .expect("register log_export_records_total")
+});
+
+type HmacSha256 = Hmac<Sha256>;
+pub type Batch = Arc<Vec<Arc<LiveRequestLog>>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError {
+    #[error("request failed: {0}")]
+    Request(String),
+    #[error("sink responded {0}")]
+    Status(u16),
+    #[error("encode failed: {0}")]
+    Encode(String),
+}
+
+impl From<reqwest::Error> for SinkError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e.to_string())
+    }
+}
+
+/// Destination for batches of request log entries.
+#[async_trait]
+pub trait LogSink: Send + Sync {
+    /// Metric label and log field.
+    fn name(&self) -> &str;
+    async fn send(&self, batch: &[Arc<LiveRequestLog>]) -> Result<(), SinkError>;
+}
+
+fn check_status(resp: reqwest::Response) -> Result<(), SinkError> {
+    let status = resp.status();
+    if status.is_success() { Ok(()) } else { Err(SinkError::Status(status.as_u16())) }
+}
+
+/// One JSON document per line, newline terminated.
+pub fn to_json_lines(batch: &[Arc<LiveRequestLog>]) -> Result<Vec<u8>, SinkError> {
+    let mut out = Vec::with_capacity(batch.len() * 256);
+    for entry in batch {
+        serde_json::to_writer(&mut out, entry.as_ref()).map_err(|e| SinkError::Encode(e.to_string()))?;
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpFormat {
+    /// `application/x-ndjson`
+    #[default]
+    Ndjson,
+    /// One `application/json` array per batch
+    JsonArray,
+}
+
+/// Generic HTTP bulk endpoint: one POST per batch.
+pub struct HttpSink {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+    headers: BTreeMap<String, String>,
+    format: HttpFormat,
+}
+
+impl HttpSink {
+    pub fn new(name: impl Into<String>, client: reqwest::Client, url: impl Into<String>, headers: BTreeMap<String, String>, format: HttpFormat) -> Self {
+        Self { name: name.into(), client, url: url.into(), headers, format }
+    }
+}
+
+#[async_trait]
+impl LogSink for HttpSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, batch: &[Arc<LiveRequestLog>]) -> Result<(), SinkError> {
+        let (content_type, body) = match self.format {
+            HttpFormat::Ndjson => ("application/x-ndjson", to_json_lines(batch)?),
+            HttpFormat::JsonArray => ("application/json", serde_json::to_vec(&batch.iter().map(|e| e.as_ref()).collect::<Vec<_>>()).map_err(|e| SinkError::Encode(e.to_string()))?),
+        };
+        let mut req = self.client.post(&self.url).header(reqwest::header::CONTENT_TYPE, content_type).body(body);
+        for (k, v) in &self.headers {
+            req = req.header(k, v);
+        }
+        check_status(req.send().await?)
+    }
+}
+
+/// Kafka topic through a Kafka REST Proxy (v2 JSON embedded format); records are keyed by tenant so
+/// one tenant's entries stay ordered within a partition.
+pub struct KafkaRestSink {
+    name: String,
+    client: reqwest::Client,
+    endpoint: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl KafkaRestSink {
+    pub fn new(name: impl Into<String>, client: reqwest::Client, rest_url: &str, topic: &str, headers: BTreeMap<String, String>) -> Self {
+        let endpoint = format!("{}/topics/{}", rest_url.trim_end_matches('/'), topic);
+        Self { name: name.into(), client, endpoint, headers }
+    }
+}
+
+#[async_trait]
+impl LogSink for KafkaRestSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, batch: &[Arc<LiveRequestLog>]) -> Result<(), SinkError> {
+        let records: Vec<_> = batch.iter().map(|e| json!({ "key": e.tenant_id, "value": e.as_ref() })).collect();
+        let mut req = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/vnd.kafka.json.v2+json")
+            .json(&json!({ "records": records }));
+        for (k, v) in &self.headers {
+            req = req.header(k, v);
+        }
+        check_status(req.send().await?)
+    }
+}
+
+/// S3 / MinIO bucket and credentials.
+#[derive(Debug, Clone)]
+pub struct S3Target {
+    /// e.g. `https://s3.eu-west-1.amazonaws.com` or `http://minio:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    /// Key prefix, e.g. `gateway-logs/`
+    pub prefix: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// `{endpoint}/{bucket}/{key}` (MinIO) instead of `{bucket}.{host}/{key}`
+    pub path_style: bool,
+}
+
+/// One `.jsonl` object per batch under `{prefix}dt=YYYY-MM-DD/hour=HH/`, partitioned by the first
+/// entry's timestamp so query engines can prune by date.
+pub struct S3Sink {
+    name: String,
+    client: reqwest::Client,
+    target: S3Target,
+    /// Source of the SigV4 request time.
+    clock: SharedClock,
+}
+
+impl S3Sink {
+    pub fn new(name: impl Into<String>, client: reqwest::Client, target: S3Target) -> Self {
+        Self { name: name.into(), client, target, clock: system_clock() }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn object_key(&self, at: DateTime<Utc>) -> String {
+        format!("{}dt={}/hour={}/{}-{}.jsonl", self.target.prefix, at.format("%Y-%m-%d"), at.format("%H"), at.format("%Y%m%dT%H%M%S"), Uuid::new_v4().simple())
+    }
+
+    /// `(url, host, canonical path)`
+    fn location(&self, key: &str) -> (String, String, String) {
+        let t = &self.target;
+        let (scheme, host) = t.endpoint.trim_end_matches('/').split_once("://").unwrap_or(("https", t.endpoint.as_str()));
+        if t.path_style {
+            let path = format!("/{}/{}", t.bucket, uri_encode_path(key));
+            (format!("{}://{}{}", scheme, host, path), host.to_string(), path)
+        } else {
+            let host = format!("{}.{}", t.bucket, host);
+            let path = format!("/{}", uri_encode_path(key));
+            (format!("{}://{}{}", scheme, host, path), host, path)
+        }
+    }
+}
+
+#[async_trait]
+impl LogSink for S3Sink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, batch: &[Arc<LiveRequestLog>]) -> Result<(), SinkError> {
+        let Some(first) = batch.first() else { return Ok(()) };
+        let body = to_json_lines(batch)?;
+        let key = self.object_key(first.timestamp);
+        let (url, host, path) = self.location(&key);
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = self.clock.now();
+        let auth = sigv4_authorization(&self.target, "PUT", &host, &path, &payload_hash, now);
+        let resp = self
+            .client
+            .put(url)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("x-amz-content-sha256", payload_hash)
+            .header(reqwest::header::AUTHORIZATION, auth)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(body)
+            .send()
+            .await?;
+        check_status(resp)
+    }
+}
+
+/// RFC 3986 encoding of an object key, keeping `/` separators.
+fn uri_encode_path(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 signing key for `{date}/{region}/{service}/aws4_request`.
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date);
+    let k = hmac_sha256(&k, region);
+    let k = hmac_sha256(&k, service);
+    hmac_sha256(&k, "aws4_request")
+}
+
+/// `Authorization` header for a request without query string, signing `host`, `x-amz-content-sha256`
+/// and `x-amz-date`.
+fn sigv4_authorization(t: &S3Target, method: &str, host: &str, path: &str, payload_hash: &str, now: DateTime<Utc>) -> String {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request =
+        format!("{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}", method, path, host, payload_hash, amz_date, signed_headers, payload_hash);
+    let scope = format!("{}/{}/s3/aws4_request", date, t.region);
+    let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes())));
+    let signature = hex::encode(hmac_sha256(&signing_key(&t.secret_access_key, &date, &t.region, "s3"), &string_to_sign));
+    format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}", t.access_key_id, scope, signed_headers, signature)
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+    /// Batches queued per sink before new ones are dropped
+    pub max_pending_batches: usize,
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            flush_interval: Duration::from_secs(5),
+            max_pending_batches: 16,
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+pub struct LogExporter {
+    sinks: Vec<Arc<dyn LogSink>>,
+    opts: ExportOptions,
+}
+
+impl LogExporter {
+    pub fn new(sinks: Vec<Arc<dyn LogSink>>, opts: ExportOptions) -> Self {
+        Self { sinks, opts }
+    }
+
+    /// Subscribe to `stream` and start one delivery task per sink plus the batching task.
+    pub fn spawn(self, stream: &RequestLogStream) -> JoinHandle<()> {
+        let mut rx = stream.subscribe();
+        let opts = self.opts.clone();
+        let queues: Vec<(String, mpsc::Sender<Batch>)> = self
+            .sinks
+            .into_iter()
+            .map(|sink| {
+                let (tx, rx) = mpsc::channel(opts.max_pending_batches.max(1));
+                let name = sink.name().to_string();
+                spawn_sink(sink, rx, opts.clone());
+                (name, tx)
+            })
+            .collect();
+        info!(sinks = ?queues.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), batch_size = opts.batch_size, "log export started");
+        tokio::spawn(async move {
+            let batch_size = opts.batch_size.max(1);
+            let mut buf: Vec<Arc<LiveRequestLog>> = Vec::with_capacity(batch_size);
+            let mut ticker = tokio::time::interval(opts.flush_interval.max(Duration::from_millis(100)));
+            loop {
+                tokio::select! {
+                    msg = rx.recv() => match msg {
+                        Ok(entry) => {
+                            buf.push(entry);
+                            if buf.len() >= batch_size {
+                                dispatch(&queues, &mut buf);
+                            }
+                        }
+                        Err(RecvError::Lagged(n)) => {
+                            warn!(skipped = n, "log_export_lagged");
+                            for (name, _) in &queues {
+                                LOG_EXPORT_RECORDS_TOTAL.with_label_values(&[name.as_str(), "dropped"]).inc_by(n);
+                            }
+                        }
+                        Err(RecvError::Closed) => {
+                            dispatch(&queues, &mut buf);
+                            break;
+                        }
+                    },
+                    _ = ticker.tick() => dispatch(&queues, &mut buf),
+                }
+            }
+        })
+    }
+}
+
+/// Hand the buffered entries to every sink queue; full queues drop the batch.
+fn dispatch(queues: &[(String, mpsc::Sender<Batch>)], buf: &mut Vec<Arc<LiveRequestLog>>) {
+    if buf.is_empty() {
+        return;
+    }
+    let batch: Batch = Arc::new(std::mem::take(buf));
+    for (name, tx) in queues {
+        if tx.try_send(Arc::clone(&batch)).is_err() {
+            warn!(sink = %name, records = batch.len(), "log_export_queue_full");
+            LOG_EXPORT_RECORDS_TOTAL.with_label_values(&[name.as_str(), "dropped"]).inc_by(batch.len() as u64);
+        }
+    }
+}
+
+fn spawn_sink(sink: Arc<dyn LogSink>, mut rx: mpsc::Receiver<Batch>, opts: ExportOptions) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(batch) = rx.recv().await {
+            let result = send_with_retry(sink.as_ref(), &batch, &opts).await;
+            let outcome = if result.is_ok() { "exported" } else { "failed" };
+            LOG_EXPORT_RECORDS_TOTAL.with_label_values(&[sink.name(), outcome]).inc_by(batch.len() as u64);
+        }
+    })
+}
+
+async fn send_with_retry(sink: &dyn LogSink, batch: &[Arc<LiveRequestLog>], opts: &ExportOptions) -> Result<(), SinkError> {
+    let mut attempt = 0;
+    loop {
+        match sink.send(batch).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= opts.max_retries => {
+                warn!(sink = sink.name(), records = batch.len(), attempts = attempt + 1, error = %e, "log_export_batch_failed");
+                return Err(e);
+            }
+            Err(e) => {
+                let delay = opts.initial_backoff.saturating_mul(2u32.saturating_pow(attempt));
+                warn!(sink = sink.name(), attempt = attempt + 1, retry_in_ms = delay.as_millis() as u64, error = %e, "log_export_batch_retry");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn entry(path: &str) -> LiveRequestLog {
+        LiveRequestLog {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            tenant_id: Uuid::nil(),
+            proxy_api_id: Uuid::nil(),
+            method: "GET".into(),
+            path: path.into(),
+            status: 200,
+            latency_ms: 4,
+            client_ip: None,
+            outcome: "forwarded".into(),
+            error: None,
+            config_revision: 1,
+        }
+    }
+
+    #[derive(Default)]
+    struct Recording {
+        batches: Mutex<Vec<Vec<String>>>,
+        failures_left: Mutex<u32>,
+    }
+
+    #[async_trait]
+    impl LogSink for Recording {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn send(&self, batch: &[Arc<LiveRequestLog>]) -> Result<(), SinkError> {
+            let mut left = self.failures_left.lock().unwrap();
+            if *left > 0 {
+                *left -= 1;
+                return Err(SinkError::Status(503));
+            }
+            self.batches.lock().unwrap().push(batch.iter().map(|e| e.path.clone()).collect());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines_and_object_keys() {
+        let lines = to_json_lines(&[Arc::new(entry("/a")), Arc::new(entry("/b"))]).unwrap();
+        let text = String::from_utf8(lines).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.ends_with('\n'));
+        assert_eq!(serde_json::from_str::<LiveRequestLog>(text.lines().next().unwrap()).unwrap(), entry("/a"));
+
+        let target = S3Target {
+            endpoint: "http://minio:9000".into(),
+            bucket: "logs".into(),
+            prefix: "gw/".into(),
+            region: "us-east-1".into(),
+            access_key_id: "AKID".into(),
+            secret_access_key: "secret".into(),
+            path_style: true,
+        };
+        let sink = S3Sink::new("s3", reqwest::Client::new(), target.clone());
+        let key = sink.object_key(entry("/a").timestamp);
+        assert!(key.starts_with("gw/dt=2023-11-14/hour=22/20231114T221320-"), "{}", key);
+        let (url, host, path) = sink.location("gw/a b.jsonl");
+        assert_eq!((url.as_str(), host.as_str(), path.as_str()), ("http://minio:9000/logs/gw/a%20b.jsonl", "minio:9000", "/logs/gw/a%20b.jsonl"));
+        let vhost = S3Sink::new("s3", reqwest::Client::new(), S3Target { endpoint: "https://s3.amazonaws.com".into(), path_style: false, ..target });
+        assert_eq!(vhost.location("k").0, "https://logs.s3.amazonaws.com/k");
+    }
+
+    #[test]
+    fn sigv4_signing_key_matches_aws_example() {
+        // https://docs.aws.amazon.com/general/latest/gr/signature-v4-examples.html
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex::encode(key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[tokio::test]
+    async fn batches_by_size_and_retries_failed_sends() {
+        let stream = RequestLogStream::new(64);
+        let sink = Arc::new(Recording { failures_left: Mutex::new(1), ..Default::default() });
+        let opts = ExportOptions { batch_size: 2, flush_interval: Duration::from_secs(3600), initial_backoff: Duration::from_millis(1), ..Default::default() };
+        LogExporter::new(vec![sink.clone() as Arc<dyn LogSink>], opts).spawn(&stream);
+        for p in ["/a", "/b", "/c", "/d"] {
+            stream.publish(entry(p));
+        }
+        for _ in 0..100 {
+            if sink.batches.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*sink.batches.lock().unwrap(), vec![vec!["/a", "/b"], vec!["/c", "/d"]]);
+    }
+}
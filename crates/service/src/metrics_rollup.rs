@@ -0,0 +1,328 @@
+//! Per-minute metrics rollups for charts without Prometheus.
+//! - The aggregator upserts `metrics_rollup` rows for completed minutes from `request_log`; each run
+//!   re-aggregates the last `lookback` minutes so late log writes are picked up.
+//! - The same run upserts `metrics_rollup_dimension` rows per API key and per client IP, which
+//!   `GET /admin/reports/top` ranks.
+//! - Rows older than `retention` are deleted.
+//! - Queries re-bucket minutes into `step` seconds. Combining minutes cannot reproduce exact
+//!   percentiles: p50 is the request-weighted mean of the minute p50s, p95/p99 take the maximum
+//!   (an upper bound).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, DurationRound, Utc};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryOrder, Statement};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::lock::{run_exclusive, DistributedLock};
+use common::time::{system_clock, SharedClock};
+use models::{metrics_rollup, metrics_rollup_dimension};
+
+use crate::errors::ServiceError;
+
+/// Upper bound on points per query.
+pub const MAX_POINTS: i64 = 10_000;
+/// A run never re-scans more than this much history.
+const MAX_CATCH_UP: chrono::Duration = chrono::Duration::days(1);
+
+#[derive(Debug, Clone)]
+pub struct RollupOptions {
+    pub interval: Duration,
+    pub lookback: Duration,
+    pub retention: Duration,
+}
+
+impl Default for RollupOptions {
+    fn default() -> Self {
+        Self { interval: Duration::from_secs(60), lookback: Duration::from_secs(300), retention: Duration::from_secs(30 * 86_400) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsQuery {
+    pub route_id: Option<Uuid>,
+    pub tenant_id: Option<Uuid>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Seconds, multiple of 60
+    pub step: i64,
+}
+
+impl MetricsQuery {
+    pub fn validate(&self) -> Result<(), ServiceError> {
+        if self.to <= self.from {
+            return Err(ServiceError::Validation("to must be after from".into()));
+        }
+        if self.step < 60 || self.step % 60 != 0 {
+            return Err(ServiceError::Validation("step must be a positive multiple of 60 seconds".into()));
+        }
+        let points = (self.to - self.from).num_seconds() / self.step;
+        if points > MAX_POINTS {
+            return Err(ServiceError::Validation(format!("query spans {} points; at most {} allowed (increase step)", points, MAX_POINTS)));
+        }
+        Ok(())
+    }
+
+    /// `from` aligned down to a multiple of `step` since the epoch.
+    fn aligned_from(&self) -> DateTime<Utc> {
+        let secs = self.from.timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(self.step), 0).unwrap_or(self.from)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricsPoint {
+    pub ts: DateTime<Utc>,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_avg_ms: f64,
+    pub latency_max_ms: i32,
+    pub latency_p50_ms: f64,
+    pub latency_p95_ms: f64,
+    pub latency_p99_ms: f64,
+}
+
+impl MetricsPoint {
+    fn empty(ts: DateTime<Utc>) -> Self {
+        Self { ts, requests: 0, errors: 0, error_rate: 0.0, latency_avg_ms: 0.0, latency_max_ms: 0, latency_p50_ms: 0.0, latency_p95_ms: 0.0, latency_p99_ms: 0.0 }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PointRow {
+    ts: DateTime<chrono::FixedOffset>,
+    requests: i64,
+    errors: i64,
+    latency_sum_ms: i64,
+    latency_max_ms: i32,
+    latency_p50_ms: Option<f64>,
+    latency_p95_ms: f64,
+    latency_p99_ms: f64,
+}
+
+impl From<PointRow> for MetricsPoint {
+    fn from(r: PointRow) -> Self {
+        let per_request = |v: i64| if r.requests > 0 { v as f64 / r.requests as f64 } else { 0.0 };
+        Self {
+            ts: r.ts.with_timezone(&Utc),
+            requests: r.requests,
+            errors: r.errors,
+            error_rate: per_request(r.errors),
+            latency_avg_ms: per_request(r.latency_sum_ms),
+            latency_max_ms: r.latency_max_ms,
+            latency_p50_ms: r.latency_p50_ms.unwrap_or(0.0),
+            latency_p95_ms: r.latency_p95_ms,
+            latency_p99_ms: r.latency_p99_ms,
+        }
+    }
+}
+
+/// One point per step between `from` and `to`; steps without traffic are zero.
+fn fill_gaps(points: Vec<MetricsPoint>, q: &MetricsQuery) -> Vec<MetricsPoint> {
+    let mut out = Vec::new();
+    let mut found = points.into_iter().peekable();
+    let mut ts = q.aligned_from();
+    while ts < q.to {
+        match found.peek() {
+            Some(p) if p.ts == ts => out.extend(found.next()),
+            _ => out.push(MetricsPoint::empty(ts)),
+        }
+        ts += chrono::Duration::seconds(q.step);
+    }
+    out
+}
+
+fn dbe(e: sea_orm::DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+fn minute(t: DateTime<Utc>) -> DateTime<Utc> {
+    t.duration_trunc(chrono::Duration::minutes(1)).unwrap_or(t)
+}
+
+/// Upsert rollups for minutes in `[from, to)`; returns rows written.
+pub async fn roll_up(db: &DatabaseConnection, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<u64, ServiceError> {
+    let sql = "INSERT INTO metrics_rollup \
+            (bucket, route_id, tenant_id, requests, errors, latency_sum_ms, latency_max_ms, latency_p50_ms, latency_p95_ms, latency_p99_ms) \
+        SELECT date_trunc('minute', l.timestamp) AS bucket, l.route_id, r.tenant_id, \
+               count(*), count(*) FILTER (WHERE NOT l.success), coalesce(sum(l.latency_ms), 0), coalesce(max(l.latency_ms), 0), \
+               percentile_cont(0.5) WITHIN GROUP (ORDER BY l.latency_ms), \
+               percentile_cont(0.95) WITHIN GROUP (ORDER BY l.latency_ms), \
+               percentile_cont(0.99) WITHIN GROUP (ORDER BY l.latency_ms) \
+        FROM request_log l JOIN route r ON r.id = l.route_id \
+        WHERE l.timestamp >= $1 AND l.timestamp < $2 \
+        GROUP BY 1, l.route_id, r.tenant_id \
+        ON CONFLICT (bucket, route_id) DO UPDATE SET \
+            tenant_id = EXCLUDED.tenant_id, requests = EXCLUDED.requests, errors = EXCLUDED.errors, \
+            latency_sum_ms = EXCLUDED.latency_sum_ms, latency_max_ms = EXCLUDED.latency_max_ms, \
+            latency_p50_ms = EXCLUDED.latency_p50_ms, latency_p95_ms = EXCLUDED.latency_p95_ms, latency_p99_ms = EXCLUDED.latency_p99_ms";
+    let res = db
+        .execute(Statement::from_sql_and_values(DbBackend::Postgres, sql, vec![from.fixed_offset().into(), to.fixed_offset().into()]))
+        .await
+        .map_err(dbe)?;
+    let mut written = res.rows_affected();
+    for (dimension, column) in [(metrics_rollup_dimension::DIMENSION_API_KEY, "l.api_key_id::text"), (metrics_rollup_dimension::DIMENSION_CLIENT_IP, "l.client_ip")] {
+        let sql = format!(
+            "INSERT INTO metrics_rollup_dimension (bucket, dimension, key, requests, errors, latency_max_ms, latency_p99_ms) \
+             SELECT date_trunc('minute', l.timestamp) AS bucket, $3, {column}, \
+                    count(*), count(*) FILTER (WHERE NOT l.success), coalesce(max(l.latency_ms), 0), \
+                    percentile_cont(0.99) WITHIN GROUP (ORDER BY l.latency_ms) \
+             FROM request_log l \
+             WHERE l.timestamp >= $1 AND l.timestamp < $2 AND {column} IS NOT NULL \
+             GROUP BY 1, {column} \
+             ON CONFLICT (bucket, dimension, key) DO UPDATE SET \
+                 requests = EXCLUDED.requests, errors = EXCLUDED.errors, \
+                 latency_max_ms = EXCLUDED.latency_max_ms, latency_p99_ms = EXCLUDED.latency_p99_ms"
+        );
+        let values = vec![from.fixed_offset().into(), to.fixed_offset().into(), dimension.into()];
+        written += db.execute(Statement::from_sql_and_values(DbBackend::Postgres, sql, values)).await.map_err(dbe)?.rows_affected();
+    }
+    Ok(written)
+}
+
+/// Delete rollups older than `before`.
+pub async fn prune(db: &DatabaseConnection, before: DateTime<Utc>) -> Result<u64, ServiceError> {
+    use sea_orm::{ColumnTrait, QueryFilter};
+    let res = metrics_rollup::Entity::delete_many()
+        .filter(metrics_rollup::Column::Bucket.lt(before.fixed_offset()))
+        .exec(db)
+        .await
+        .map_err(dbe)?;
+    let dims = metrics_rollup_dimension::Entity::delete_many()
+        .filter(metrics_rollup_dimension::Column::Bucket.lt(before.fixed_offset()))
+        .exec(db)
+        .await
+        .map_err(dbe)?;
+    Ok(res.rows_affected + dims.rows_affected)
+}
+
+/// Series for a route, a tenant or all traffic.
+pub async fn query(db: &DatabaseConnection, q: &MetricsQuery) -> Result<Vec<MetricsPoint>, ServiceError> {
+    q.validate()?;
+    let mut sql = String::from(
+        "SELECT to_timestamp(floor(extract(epoch FROM bucket) / $3) * $3) AS ts, \
+                sum(requests)::bigint AS requests, sum(errors)::bigint AS errors, \
+                sum(latency_sum_ms)::bigint AS latency_sum_ms, max(latency_max_ms) AS latency_max_ms, \
+                sum(latency_p50_ms * requests) / nullif(sum(requests), 0) AS latency_p50_ms, \
+                max(latency_p95_ms) AS latency_p95_ms, max(latency_p99_ms) AS latency_p99_ms \
+         FROM metrics_rollup WHERE bucket >= $1 AND bucket < $2",
+    );
+    let mut values: Vec<sea_orm::Value> = vec![q.aligned_from().fixed_offset().into(), q.to.fixed_offset().into(), (q.step as f64).into()];
+    if let Some(route_id) = q.route_id {
+        values.push(route_id.into());
+        sql.push_str(&format!(" AND route_id = ${}", values.len()));
+    }
+    if let Some(tenant_id) = q.tenant_id {
+        values.push(tenant_id.into());
+        sql.push_str(&format!(" AND tenant_id = ${}", values.len()));
+    }
+    sql.push_str(" GROUP BY 1 ORDER BY 1");
+    let rows = PointRow::find_by_statement(Statement::from_sql_and_values(DbBackend::Postgres, sql, values))
+        .all(db)
+        .await
+        .map_err(dbe)?;
+    Ok(fill_gaps(rows.into_iter().map(MetricsPoint::from).collect(), q))
+}
+
+pub struct MetricsRollupService {
+    db: DatabaseConnection,
+    opts: RollupOptions,
+    lock: Option<Arc<dyn DistributedLock>>,
+    clock: SharedClock,
+}
+
+impl MetricsRollupService {
+    pub fn new(db: DatabaseConnection, opts: RollupOptions) -> Self {
+        Self { db, opts, lock: None, clock: system_clock() }
+    }
+
+    /// Run the scheduled rollup on one replica at a time.
+    pub fn with_lock(mut self, lock: Arc<dyn DistributedLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Aggregate completed minutes since the last rollup (at least `lookback`), then apply retention.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<u64, ServiceError> {
+        let to = minute(now);
+        let lookback = chrono::Duration::from_std(self.opts.lookback).unwrap_or_default();
+        let latest = metrics_rollup::Entity::find()
+            .order_by_desc(metrics_rollup::Column::Bucket)
+            .one(&self.db)
+            .await
+            .map_err(dbe)?
+            .map(|m| m.bucket.with_timezone(&Utc));
+        let from = latest.map_or(to - lookback, |l| l.min(to - lookback)).max(to - MAX_CATCH_UP);
+        let written = roll_up(&self.db, from, to).await?;
+        let retention = chrono::Duration::from_std(self.opts.retention).unwrap_or(MAX_CATCH_UP);
+        let pruned = prune(&self.db, to - retention).await?;
+        if pruned > 0 {
+            info!(pruned, "metrics_rollup_pruned");
+        }
+        Ok(written)
+    }
+
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.opts.interval);
+            loop {
+                ticker.tick().await;
+                if let Some(Err(e)) = run_exclusive(this.lock.as_deref(), "metrics_rollup", this.opts.interval, this.run_once(this.clock.now())).await {
+                    warn!(error = %e, "metrics_rollup_failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn q(from: &str, to: &str, step: i64) -> MetricsQuery {
+        MetricsQuery { route_id: None, tenant_id: None, from: at(from), to: at(to), step }
+    }
+
+    #[test]
+    fn validates_range_and_step() {
+        assert!(q("2026-01-01T00:00:00Z", "2026-01-01T01:00:00Z", 60).validate().is_ok());
+        assert!(q("2026-01-01T01:00:00Z", "2026-01-01T00:00:00Z", 60).validate().is_err());
+        assert!(q("2026-01-01T00:00:00Z", "2026-01-01T01:00:00Z", 90).validate().is_err());
+        assert!(q("2026-01-01T00:00:00Z", "2026-03-01T00:00:00Z", 60).validate().unwrap_err().to_string().contains("increase step"));
+    }
+
+    #[test]
+    fn fills_missing_steps_with_zero_points() {
+        let query = q("2026-01-01T00:01:30Z", "2026-01-01T00:10:00Z", 300);
+        let row = PointRow {
+            ts: at("2026-01-01T00:05:00Z").fixed_offset(),
+            requests: 4,
+            errors: 1,
+            latency_sum_ms: 200,
+            latency_max_ms: 90,
+            latency_p50_ms: Some(40.0),
+            latency_p95_ms: 88.0,
+            latency_p99_ms: 90.0,
+        };
+        let points = fill_gaps(vec![row.into()], &query);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0], MetricsPoint::empty(at("2026-01-01T00:00:00Z")));
+        assert_eq!((points[1].requests, points[1].error_rate, points[1].latency_avg_ms), (4, 0.25, 50.0));
+        assert_eq!(minute(at("2026-01-01T00:05:59Z")), at("2026-01-01T00:05:00Z"));
+    }
+}
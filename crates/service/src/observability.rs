@@ -0,0 +1,359 @@
+//! Monitoring bundle: Prometheus alert rules + Grafana dashboard generated from the
+//! configured upstreams / routes and SLO targets, so dashboards never drift from config.
+//!
+//! Metric names live here and are used by the gateway when registering, so the
+//! generated queries always reference series that actually exist.
+
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use models::{proxy_api, route, upstream};
+
+use crate::errors::ServiceError;
+
+pub mod metric_names {
+    pub const REQUESTS_TOTAL: &str = "api_proxy_requests_total";
+    pub const UPSTREAM_ERRORS_TOTAL: &str = "api_proxy_upstream_errors_total";
+    pub const REQUEST_DURATION_SECONDS: &str = "api_proxy_request_duration_seconds";
+    pub const ROUTE_REQUEST_DURATION_SECONDS: &str = "api_proxy_route_request_duration_seconds";
+    pub const RATE_LIMITED_TOTAL: &str = "api_proxy_rate_limited_total";
+    pub const CIRCUIT_BREAKER_OPEN_TOTAL: &str = "api_proxy_circuit_breaker_open_total";
+    pub const CIRCUIT_BREAKER_STATE: &str = "api_proxy_circuit_breaker_state";
+    pub const CIRCUIT_BREAKER_TRANSITIONS_TOTAL: &str = "api_proxy_circuit_breaker_transitions_total";
+    pub const CIRCUIT_BREAKER_REJECTED_TOTAL: &str = "api_proxy_circuit_breaker_rejected_total";
+    pub const UPSTREAM_EJECTED: &str = "api_proxy_upstream_ejected";
+    pub const BULKHEAD_IN_FLIGHT: &str = "api_proxy_bulkhead_in_flight";
+    pub const BULKHEAD_REJECTED_TOTAL: &str = "api_proxy_bulkhead_rejected_total";
+    pub const LOAD_SHED_TOTAL: &str = "api_proxy_load_shed_total";
+    pub const IN_FLIGHT_REQUESTS: &str = "api_proxy_in_flight_requests";
+    pub const BLOCKED_REQUESTS_TOTAL: &str = "api_proxy_blocked_requests_total";
+    pub const ADMIN_JOBS_TOTAL: &str = "api_proxy_admin_jobs_total";
+}
+
+use metric_names as m;
+
+/// Register the server-side Prometheus metrics up front, so `/metrics` exposes every
+/// series from the first scrape and a duplicate registration fails at startup.
+pub fn register_metrics() {
+    use once_cell::sync::Lazy;
+    Lazy::force(&crate::proxy_api::maintenance::BLOCKED_REQUESTS_TOTAL);
+    Lazy::force(&crate::jobs::ADMIN_JOBS_TOTAL);
+    Lazy::force(&crate::webhooks::WEBHOOK_DELIVERIES_TOTAL);
+    Lazy::force(&crate::log_export::LOG_EXPORT_RECORDS_TOTAL);
+    Lazy::force(&crate::anomaly::ANOMALIES_DETECTED_TOTAL);
+    Lazy::force(&crate::storage::health::STORE_ENTRIES);
+    Lazy::force(&crate::storage::health::STORE_LAST_SAVE);
+    Lazy::force(&crate::storage::health::STORE_SAVE_ERRORS);
+    Lazy::force(&crate::storage::health::STORE_WRITABLE);
+    crate::cache::versioned::register_metrics();
+}
+
+/// SLO 目标（来自 config.toml [slo]）
+#[derive(Debug, Clone, Serialize)]
+pub struct SloTargets {
+    /// 可用性目标，如 0.999
+    pub availability: f64,
+    /// p99 延迟目标（毫秒）
+    pub latency_p99_ms: u64,
+    /// 告警持续时间，如 "5m"
+    pub alert_for: String,
+    /// 被限流请求占比超过该值时告警
+    pub max_rate_limited_ratio: f64,
+}
+
+impl SloTargets {
+    /// 允许的错误比例（1 - availability），去掉浮点尾差
+    pub fn error_budget(&self) -> f64 {
+        ((1.0 - self.availability) * 1e6).round() / 1e6
+    }
+}
+
+impl Default for SloTargets {
+    fn default() -> Self {
+        Self { availability: 0.999, latency_p99_ms: 500, alert_for: "5m".into(), max_rate_limited_ratio: 0.05 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ObservabilityBundle {
+    pub generated_at: DateTime<Utc>,
+    pub slo: SloTargets,
+    /// Prometheus rule file (`groups:`)；JSON 同时也是合法的 YAML，可直接保存为 rules 文件
+    pub prometheus_rules: Value,
+    /// Grafana dashboard model，可通过 Import 导入
+    pub grafana_dashboard: Value,
+}
+
+/// Load current config and build the bundle.
+pub async fn load_bundle(db: &DatabaseConnection, slo: &SloTargets) -> Result<ObservabilityBundle, ServiceError> {
+    let dbe = |e: sea_orm::DbErr| ServiceError::Db(e.to_string());
+    let upstreams = upstream::Entity::find().all(db).await.map_err(dbe)?;
+    let routes = route::Entity::find().all(db).await.map_err(dbe)?;
+    let proxy_apis = proxy_api::Entity::find().all(db).await.map_err(dbe)?;
+    Ok(build_bundle(&upstreams, &routes, &proxy_apis, slo, Utc::now()))
+}
+
+/// 网关 `upstream` 标签取值：base_url 的 host:port（缺省端口按 scheme 补全）
+pub fn upstream_label(base_url: &str) -> Option<String> {
+    let (scheme, rest) = base_url.split_once("://")?;
+    let authority = rest.split(['/', '?']).next().filter(|a| !a.is_empty())?;
+    if authority.contains(':') {
+        return Some(authority.to_string());
+    }
+    let port = if scheme.eq_ignore_ascii_case("https") { 443 } else { 80 };
+    Some(format!("{}:{}", authority, port))
+}
+
+/// PromQL 字符串字面量转义
+fn quote(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn build_bundle(
+    upstreams: &[upstream::Model],
+    routes: &[route::Model],
+    proxy_apis: &[proxy_api::Model],
+    slo: &SloTargets,
+    now: DateTime<Utc>,
+) -> ObservabilityBundle {
+    let mut active: Vec<(&upstream::Model, String)> = upstreams
+        .iter()
+        .filter(|u| u.active)
+        .filter_map(|u| upstream_label(&u.base_url).map(|l| (u, l)))
+        .collect();
+    active.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+
+    ObservabilityBundle {
+        generated_at: now,
+        slo: slo.clone(),
+        prometheus_rules: alert_rules(&active, routes, slo),
+        grafana_dashboard: dashboard(&active, routes, proxy_apis, slo, now),
+    }
+}
+
+fn error_ratio() -> String {
+    format!("sum(rate({}[5m])) / clamp_min(sum(rate({}[5m])), 1e-9)", m::UPSTREAM_ERRORS_TOTAL, m::REQUESTS_TOTAL)
+}
+
+fn p99() -> String {
+    format!("histogram_quantile(0.99, sum by (le) (rate({}_bucket[5m])))", m::REQUEST_DURATION_SECONDS)
+}
+
+fn alert_rules(upstreams: &[(&upstream::Model, String)], routes: &[route::Model], slo: &SloTargets) -> Value {
+    let budget = slo.error_budget();
+    let mut gateway = vec![
+        json!({
+            "alert": "ApiProxyErrorBudgetBurn",
+            "expr": format!("({}) > {}", error_ratio(), budget),
+            "for": slo.alert_for,
+            "labels": { "severity": "critical" },
+            "annotations": {
+                "summary": format!("Upstream error ratio above SLO budget ({:.3}%)", budget * 100.0),
+                "description": format!("Availability target {} is being missed.", slo.availability),
+            },
+        }),
+        json!({
+            "alert": "ApiProxyLatencyP99High",
+            "expr": format!("{} > {}", p99(), slo.latency_p99_ms as f64 / 1000.0),
+            "for": slo.alert_for,
+            "labels": { "severity": "warning" },
+            "annotations": { "summary": format!("p99 latency above {}ms", slo.latency_p99_ms) },
+        }),
+        json!({
+            "alert": "ApiProxyRateLimitedHigh",
+            "expr": format!("sum(rate({}[5m])) / clamp_min(sum(rate({}[5m])), 1e-9) > {}", m::RATE_LIMITED_TOTAL, m::REQUESTS_TOTAL, slo.max_rate_limited_ratio),
+            "for": slo.alert_for,
+            "labels": { "severity": "warning" },
+            "annotations": { "summary": format!("More than {:.1}% of requests are rate limited", slo.max_rate_limited_ratio * 100.0) },
+        }),
+        json!({
+            "alert": "ApiProxyCircuitBreakerOpen",
+            "expr": format!("sum(rate({}[5m])) > 0", m::CIRCUIT_BREAKER_OPEN_TOTAL),
+            "for": slo.alert_for,
+            "labels": { "severity": "warning" },
+            "annotations": { "summary": "Circuit breaker is rejecting requests" },
+        }),
+    ];
+    // 最短的路由超时：p99 接近它时请求会开始超时
+    if let Some(min_timeout) = routes.iter().map(|r| r.timeout_ms).filter(|t| *t > 0).min() {
+        gateway.push(json!({
+            "alert": "ApiProxyLatencyNearRouteTimeout",
+            "expr": format!("{} > {}", p99(), min_timeout as f64 * 0.8 / 1000.0),
+            "for": slo.alert_for,
+            "labels": { "severity": "warning" },
+            "annotations": { "summary": format!("p99 latency above 80% of the shortest route timeout ({}ms)", min_timeout) },
+        }));
+    }
+
+    let upstream_rules: Vec<Value> = upstreams
+        .iter()
+        .flat_map(|(u, label)| {
+            let l = quote(label);
+            [
+                json!({
+                    "alert": "ApiProxyUpstreamEjected",
+                    "expr": format!("{}{{upstream=\"{}\"}} == 1", m::UPSTREAM_EJECTED, l),
+                    "for": "1m",
+                    "labels": { "severity": "critical", "upstream": label, "upstream_name": u.name },
+                    "annotations": { "summary": format!("Upstream {} ({}) ejected by outlier detection", u.name, label) },
+                }),
+                json!({
+                    "alert": "ApiProxyUpstreamBulkheadRejecting",
+                    "expr": format!("sum(rate({}{{upstream=\"{}\"}}[5m])) > 0", m::BULKHEAD_REJECTED_TOTAL, l),
+                    "for": slo.alert_for,
+                    "labels": { "severity": "warning", "upstream": label, "upstream_name": u.name },
+                    "annotations": { "summary": format!("Bulkhead rejecting requests to {} ({})", u.name, label) },
+                }),
+            ]
+        })
+        .collect();
+
+    json!({
+        "groups": [
+            { "name": "api_proxy_slo", "rules": gateway },
+            { "name": "api_proxy_upstreams", "rules": upstream_rules },
+        ]
+    })
+}
+
+fn panel(id: u32, title: &str, exprs: &[(String, &str)], unit: &str, grid: (u32, u32, u32, u32)) -> Value {
+    let targets: Vec<Value> = exprs
+        .iter()
+        .enumerate()
+        .map(|(i, (expr, legend))| json!({ "refId": ((b'A' + i as u8) as char).to_string(), "expr": expr, "legendFormat": legend }))
+        .collect();
+    json!({
+        "id": id,
+        "type": "timeseries",
+        "title": title,
+        "datasource": { "type": "prometheus", "uid": "${datasource}" },
+        "fieldConfig": { "defaults": { "unit": unit }, "overrides": [] },
+        "gridPos": { "x": grid.0, "y": grid.1, "w": grid.2, "h": grid.3 },
+        "targets": targets,
+    })
+}
+
+fn dashboard(
+    upstreams: &[(&upstream::Model, String)],
+    routes: &[route::Model],
+    proxy_apis: &[proxy_api::Model],
+    slo: &SloTargets,
+    now: DateTime<Utc>,
+) -> Value {
+    let mut panels = vec![
+        panel(1, "Request rate", &[(format!("sum(rate({}[1m]))", m::REQUESTS_TOTAL), "rps")], "reqps", (0, 0, 12, 8)),
+        panel(
+            2,
+            "Error ratio vs SLO budget",
+            &[(error_ratio(), "error ratio"), (format!("vector({})", slo.error_budget()), "budget")],
+            "percentunit",
+            (12, 0, 12, 8),
+        ),
+        panel(
+            3,
+            "Latency p99 vs SLO",
+            &[(p99(), "p99"), (format!("vector({})", slo.latency_p99_ms as f64 / 1000.0), "target")],
+            "s",
+            (0, 8, 12, 8),
+        ),
+        panel(
+            4,
+            "Rejections",
+            &[
+                (format!("sum(rate({}[1m]))", m::RATE_LIMITED_TOTAL), "rate limited"),
+                (format!("sum(rate({}[1m]))", m::CIRCUIT_BREAKER_OPEN_TOTAL), "circuit open"),
+                (format!("sum by (priority) (rate({}[1m]))", m::LOAD_SHED_TOTAL), "shed {{priority}}"),
+                (format!("sum by (reason) (rate({}[1m]))", m::BLOCKED_REQUESTS_TOTAL), "blocked {{reason}}"),
+            ],
+            "reqps",
+            (12, 8, 12, 8),
+        ),
+    ];
+
+    let labels: Vec<String> = upstreams.iter().map(|(_, l)| quote(l)).collect();
+    if !labels.is_empty() {
+        // 仅展示当前配置中的上游
+        let selector = format!("upstream=~\"{}\"", labels.iter().map(|l| l.replace('.', "\\\\.")).collect::<Vec<_>>().join("|"));
+        panels.push(panel(
+            5,
+            "Upstream in-flight",
+            &[(format!("sum by (upstream) ({}{{{}}})", m::BULKHEAD_IN_FLIGHT, selector), "{{upstream}}")],
+            "short",
+            (0, 16, 12, 8),
+        ));
+        panels.push(panel(
+            6,
+            "Upstream ejected",
+            &[(format!("max by (upstream) ({}{{{}}})", m::UPSTREAM_EJECTED, selector), "{{upstream}}")],
+            "short",
+            (12, 16, 12, 8),
+        ));
+    }
+
+    let mut md = String::from("| upstream | label | active routes |\n|---|---|---|\n");
+    for (u, label) in upstreams {
+        let n = routes.iter().filter(|r| r.upstream_id == u.id).count();
+        md.push_str(&format!("| {} | `{}` | {} |\n", u.name, label, n));
+    }
+    md.push_str(&format!("\n{} routes, {} proxy APIs ({} enabled).", routes.len(), proxy_apis.len(), proxy_apis.iter().filter(|a| a.enabled).count()));
+    panels.push(json!({
+        "id": 7,
+        "type": "text",
+        "title": "Configured upstreams",
+        "gridPos": { "x": 0, "y": 24, "w": 24, "h": 6 },
+        "options": { "mode": "markdown", "content": md },
+    }));
+
+    json!({
+        "uid": "api-proxy-gateway",
+        "title": "API Proxy Gateway",
+        "tags": ["api-proxy", "generated"],
+        "timezone": "browser",
+        "schemaVersion": 39,
+        "version": 1,
+        "refresh": "30s",
+        "time": { "from": "now-6h", "to": "now" },
+        "description": format!("Generated {} from gateway config", now.to_rfc3339()),
+        "templating": { "list": [{ "name": "datasource", "type": "datasource", "query": "prometheus" }] },
+        "panels": panels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn upstream(name: &str, base_url: &str, active: bool) -> upstream::Model {
+        let now = Utc::now().into();
+        upstream::Model { id: Uuid::new_v4(), name: name.into(), base_url: base_url.into(), health_url: None, active, created_at: now, updated_at: now }
+    }
+
+    #[test]
+    fn upstream_label_matches_gateway_addr() {
+        assert_eq!(upstream_label("http://127.0.0.1:9099/posts").as_deref(), Some("127.0.0.1:9099"));
+        assert_eq!(upstream_label("https://api.example.com").as_deref(), Some("api.example.com:443"));
+        assert_eq!(upstream_label("not a url"), None);
+    }
+
+    #[test]
+    fn bundle_tracks_configured_upstreams_and_slo() {
+        let ups = vec![upstream("echo", "http://127.0.0.1:9099", true), upstream("old", "http://10.0.0.1:80", false)];
+        let slo = SloTargets { availability: 0.99, latency_p99_ms: 250, ..SloTargets::default() };
+        let b = build_bundle(&ups, &[], &[], &slo, Utc::now());
+
+        let rules = b.prometheus_rules.to_string();
+        assert!(rules.contains("api_proxy_upstream_ejected{upstream=\\\"127.0.0.1:9099\\\"}"));
+        assert!(!rules.contains("10.0.0.1"));
+        assert!(rules.contains("> 0.25"));
+        assert!(!rules.contains("ApiProxyLatencyNearRouteTimeout"));
+        let slo_rules = &b.prometheus_rules["groups"][0]["rules"];
+        assert!(slo_rules[0]["expr"].as_str().unwrap().ends_with("> 0.01"));
+
+        let panels = b.grafana_dashboard["panels"].as_array().unwrap();
+        assert_eq!(panels.len(), 7);
+        assert!(b.grafana_dashboard.to_string().contains("127\\\\\\\\.0\\\\\\\\.0\\\\\\\\.1:9099"));
+    }
+}
@@ -0,0 +1,227 @@
+//! gRPC-JSON transcoding for proxy APIs backed by gRPC services.
+//!
+//! A proxy API with `grpc_method = "package.Service/Method"` maps its HTTP method + path to
+//! that RPC. The JSON body (plus query parameters for fields not in the body) is encoded as
+//! the method's input message using a compiled descriptor set (`protoc --descriptor_set_out
+//! --include_imports`); the unary response is decoded back to JSON and the gRPC status is
+//! mapped to an HTTP status. Streaming methods are not supported.
+
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor};
+use serde_json::{Map, Value};
+
+use crate::errors::ServiceError;
+
+/// gRPC message frame header: 1 byte compression flag + 4 byte big-endian length.
+const FRAME_HEADER_LEN: usize = 5;
+
+/// Descriptor pool used to encode/decode messages of transcoded methods.
+#[derive(Clone)]
+pub struct GrpcTranscoder {
+    pool: DescriptorPool,
+}
+
+impl GrpcTranscoder {
+    /// Load one or more `FileDescriptorSet` files.
+    pub fn from_files(paths: &[String]) -> Result<Self, ServiceError> {
+        let mut pool = DescriptorPool::new();
+        for path in paths {
+            let bytes = std::fs::read(path).map_err(|e| ServiceError::Validation(format!("read descriptor set {}: {}", path, e)))?;
+            pool.decode_file_descriptor_set(bytes.as_slice())
+                .map_err(|e| ServiceError::Validation(format!("invalid descriptor set {}: {}", path, e)))?;
+        }
+        Ok(Self { pool })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ServiceError> {
+        let pool = DescriptorPool::decode(bytes).map_err(|e| ServiceError::Validation(format!("invalid descriptor set: {}", e)))?;
+        Ok(Self { pool })
+    }
+
+    /// Look up `package.Service/Method`; only unary methods can be transcoded.
+    pub fn method(&self, full_name: &str) -> Result<MethodDescriptor, ServiceError> {
+        let (service, method) = full_name
+            .trim_start_matches('/')
+            .split_once('/')
+            .ok_or_else(|| ServiceError::Validation(format!("grpc_method must be package.Service/Method, got {}", full_name)))?;
+        let svc = self
+            .pool
+            .get_service_by_name(service)
+            .ok_or_else(|| ServiceError::Validation(format!("unknown gRPC service {}", service)))?;
+        let m = svc
+            .methods()
+            .find(|m| m.name() == method)
+            .ok_or_else(|| ServiceError::Validation(format!("unknown method {} on {}", method, service)))?;
+        if m.is_client_streaming() || m.is_server_streaming() {
+            return Err(ServiceError::Validation(format!("{} is a streaming method; only unary methods can be transcoded", full_name)));
+        }
+        Ok(m)
+    }
+
+    /// JSON body + path parameters + query parameters -> framed request message.
+    pub fn encode_request(
+        &self,
+        method: &MethodDescriptor,
+        body: &[u8],
+        path_params: &[(String, String)],
+        query: Option<&str>,
+    ) -> Result<Vec<u8>, ServiceError> {
+        let mut fields = if body.iter().all(u8::is_ascii_whitespace) {
+            Map::new()
+        } else {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(Value::Object(m)) => m,
+                Ok(_) => return Err(ServiceError::Validation("request body must be a JSON object".into())),
+                Err(e) => return Err(ServiceError::Validation(format!("invalid JSON body: {}", e))),
+            }
+        };
+        // 路径参数、查询参数只补充 body 中没有的字段；通配段不参与
+        for (k, v) in path_params.iter().filter(|(k, _)| k != "*") {
+            fields.entry(k.clone()).or_insert_with(|| query_value(v));
+        }
+        for (k, v) in query.into_iter().flat_map(|q| q.split('&')).filter_map(|kv| kv.split_once('=')) {
+            fields.entry(k.to_string()).or_insert_with(|| query_value(v));
+        }
+        let msg = DynamicMessage::deserialize(method.input(), Value::Object(fields))
+            .map_err(|e| ServiceError::Validation(format!("request does not match {}: {}", method.input().full_name(), e)))?;
+        Ok(frame(&msg.encode_to_vec()))
+    }
+
+    /// Framed response body -> JSON of the output message.
+    pub fn decode_response(&self, method: &MethodDescriptor, body: &[u8]) -> Result<Value, ServiceError> {
+        let payload = unframe(body)?;
+        let msg = DynamicMessage::decode(method.output(), payload)
+            .map_err(|e| ServiceError::Validation(format!("invalid {} message: {}", method.output().full_name(), e)))?;
+        serde_json::to_value(&msg).map_err(|e| ServiceError::Validation(e.to_string()))
+    }
+}
+
+/// HTTP/2 path of a method: `/package.Service/Method`.
+pub fn grpc_path(full_name: &str) -> String {
+    format!("/{}", full_name.trim_start_matches('/'))
+}
+
+/// Query values are strings; numbers and booleans are passed as JSON literals.
+fn query_value(raw: &str) -> Value {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(v @ (Value::Number(_) | Value::Bool(_))) => v,
+        _ => Value::String(raw.replace('+', " ")),
+    }
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    out.push(0);
+    out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+fn unframe(body: &[u8]) -> Result<&[u8], ServiceError> {
+    if body.len() < FRAME_HEADER_LEN {
+        return Err(ServiceError::Validation("empty gRPC response".into()));
+    }
+    if body[0] != 0 {
+        return Err(ServiceError::Validation("compressed gRPC responses are not supported".into()));
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    body.get(FRAME_HEADER_LEN..FRAME_HEADER_LEN + len)
+        .ok_or_else(|| ServiceError::Validation("truncated gRPC response".into()))
+}
+
+/// gRPC status code -> HTTP status (google.rpc.Code mapping).
+pub fn http_status(grpc_code: i32) -> u16 {
+    match grpc_code {
+        0 => 200,
+        1 => 499,
+        3 | 9 | 11 => 400,
+        4 => 504,
+        5 => 404,
+        6 | 10 => 409,
+        7 => 403,
+        8 => 429,
+        12 => 501,
+        14 => 503,
+        16 => 401,
+        _ => 500,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{
+        field_descriptor_proto::{Label, Type},
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+        ServiceDescriptorProto,
+    };
+
+    fn field(name: &str, number: i32, ty: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.into()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(ty as i32),
+            json_name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    fn transcoder() -> GrpcTranscoder {
+        let file = FileDescriptorProto {
+            name: Some("greeter.proto".into()),
+            package: Some("demo".into()),
+            syntax: Some("proto3".into()),
+            message_type: vec![
+                DescriptorProto { name: Some("HelloRequest".into()), field: vec![field("name", 1, Type::String), field("times", 2, Type::Int32)], ..Default::default() },
+                DescriptorProto { name: Some("HelloReply".into()), field: vec![field("message", 1, Type::String)], ..Default::default() },
+            ],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Greeter".into()),
+                method: vec![
+                    MethodDescriptorProto { name: Some("SayHello".into()), input_type: Some(".demo.HelloRequest".into()), output_type: Some(".demo.HelloReply".into()), ..Default::default() },
+                    MethodDescriptorProto { name: Some("Stream".into()), input_type: Some(".demo.HelloRequest".into()), output_type: Some(".demo.HelloReply".into()), server_streaming: Some(true), ..Default::default() },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        GrpcTranscoder::from_bytes(&FileDescriptorSet { file: vec![file] }.encode_to_vec()).unwrap()
+    }
+
+    #[test]
+    fn request_and_response_roundtrip() {
+        let t = transcoder();
+        let m = t.method("demo.Greeter/SayHello").unwrap();
+        let framed = t.encode_request(&m, br#"{"name":"ada"}"#, &[], Some("times=3&name=ignored")).unwrap();
+        assert_eq!(framed[0], 0);
+        let req = DynamicMessage::decode(m.input(), &framed[FRAME_HEADER_LEN..]).unwrap();
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json, serde_json::json!({ "name": "ada", "times": 3 }));
+
+        let mut reply = DynamicMessage::new(m.output());
+        reply.set_field_by_name("message", prost_reflect::Value::String("hi ada".into()));
+        let out = t.decode_response(&m, &frame(&reply.encode_to_vec())).unwrap();
+        assert_eq!(out["message"], "hi ada");
+        assert_eq!(grpc_path("demo.Greeter/SayHello"), "/demo.Greeter/SayHello");
+
+        // 路径参数 {name} 可替代 body 字段
+        let framed = t.encode_request(&m, b"", &[("name".into(), "bob".into()), ("*".into(), "x".into())], None).unwrap();
+        let req = DynamicMessage::decode(m.input(), &framed[FRAME_HEADER_LEN..]).unwrap();
+        assert_eq!(serde_json::to_value(&req).unwrap(), serde_json::json!({ "name": "bob" }));
+    }
+
+    #[test]
+    fn rejects_unknown_streaming_and_bad_input() {
+        let t = transcoder();
+        assert!(t.method("demo.Greeter/Nope").is_err());
+        assert!(t.method("demo.Greeter").is_err());
+        assert!(t.method("demo.Greeter/Stream").is_err());
+        let m = t.method("demo.Greeter/SayHello").unwrap();
+        assert!(t.encode_request(&m, b"[1]", &[], None).is_err());
+        assert!(t.encode_request(&m, br#"{"unknown_field":1}"#, &[], None).is_err());
+        assert!(t.decode_response(&m, &[0, 0, 0, 0, 9, 1]).is_err());
+        assert_eq!(http_status(5), 404);
+        assert_eq!(http_status(14), 503);
+    }
+}
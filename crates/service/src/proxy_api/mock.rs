@@ -0,0 +1,102 @@
+//! Mock mode for proxy APIs: a static response (status/headers/body) with simple templating.
+//!
+//! Templates use `{{name}}` placeholders:
+//! `{{method}}`, `{{path}}`, `{{query}}`, `{{now}}` (RFC 3339), `{{query.<key>}}`, `{{header.<name>}}`, `{{path.<param>}}`.
+//! Unknown placeholders render as an empty string.
+
+use std::collections::BTreeMap;
+
+use axum::http::{HeaderName, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ServiceError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn validate(&self) -> Result<(), ServiceError> {
+        StatusCode::from_u16(self.status)
+            .ok()
+            .filter(|s| (200..=599).contains(&s.as_u16()))
+            .ok_or_else(|| ServiceError::Validation(format!("invalid mock status {}", self.status)))?;
+        for (name, value) in &self.headers {
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| ServiceError::Validation(format!("invalid mock header name '{}'", name)))?;
+            HeaderValue::from_str(value).map_err(|_| ServiceError::Validation(format!("invalid mock header value for '{}'", name)))?;
+        }
+        Ok(())
+    }
+
+    /// 从 proxy_api 记录读取 mock 配置（未开启时返回 None）
+    pub fn from_model(m: &models::proxy_api::Model) -> Option<Self> {
+        if !m.mock_enabled {
+            return None;
+        }
+        Some(Self {
+            status: m.mock_status.and_then(|s| u16::try_from(s).ok()).unwrap_or(200),
+            headers: m.mock_headers.as_deref().and_then(|h| serde_json::from_str(h).ok()).unwrap_or_default(),
+            body: m.mock_body.clone().unwrap_or_default(),
+        })
+    }
+
+    /// 渲染 headers 与 body 中的模板
+    pub fn render(&self, lookup: impl Fn(&str) -> Option<String>) -> MockResponse {
+        MockResponse {
+            status: self.status,
+            headers: self.headers.iter().map(|(k, v)| (k.clone(), render_template(v, &lookup))).collect(),
+            body: render_template(&self.body, &lookup),
+        }
+    }
+}
+
+pub fn render_template(tpl: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(tpl.len());
+    let mut rest = tpl;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                out.push_str(&lookup(after[..end].trim()).unwrap_or_default());
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        let lookup = |k: &str| match k {
+            "method" => Some("GET".to_string()),
+            "query.id" => Some("42".to_string()),
+            _ => None,
+        };
+        assert_eq!(render_template("{{method}} #{{ query.id }} {{missing}}!", lookup), "GET #42 !");
+        assert_eq!(render_template("no vars", lookup), "no vars");
+        assert_eq!(render_template("broken {{method", lookup), "broken {{method");
+    }
+
+    #[test]
+    fn validates_status_and_headers() {
+        let ok = MockResponse { status: 503, headers: BTreeMap::from([("retry-after".into(), "60".into())]), body: String::new() };
+        assert!(ok.validate().is_ok());
+        assert!(MockResponse { status: 99, ..ok.clone() }.validate().is_err());
+        assert!(MockResponse { headers: BTreeMap::from([("bad header".into(), "x".into())]), ..ok }.validate().is_err());
+    }
+}
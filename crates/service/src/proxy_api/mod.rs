@@ -1,2 +1,7 @@
 pub mod repository;
-pub mod service;
\ No newline at end of file
+pub mod service;
+pub mod mock;
+pub mod maintenance;pub mod grpc;
+pub mod rewrite;
+pub mod openapi;
+
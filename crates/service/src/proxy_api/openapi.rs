@@ -0,0 +1,156 @@
+//! 按租户已启用的 proxy API 生成数据面 OpenAPI 3.0 文档（admin 接口文档见 server 的 utoipa ApiDoc）。
+//! - endpoint_url 中的 `{name}` 生成 path 参数，末尾 `*` 记为 `{wildcard}`；
+//! - 不暴露 forward_target 等上游信息；
+//! - `/api/*` 统一经过 API key 中间件，每个操作都声明 `X-API-Key`（或 query `api_key`）。
+
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+/// 通配段在文档中的参数名
+const WILDCARD_PARAM: &str = "wildcard";
+
+/// `/orders/{id}/items/*` -> (`/orders/{id}/items/{wildcard}`, [`id`, `wildcard`])
+fn openapi_path(endpoint_url: &str) -> (String, Vec<String>) {
+    let mut params = Vec::new();
+    let segments: Vec<String> = endpoint_url
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|seg| {
+            if seg == "*" {
+                params.push(WILDCARD_PARAM.to_string());
+                format!("{{{}}}", WILDCARD_PARAM)
+            } else {
+                if let Some(name) = seg.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                    params.push(name.to_string());
+                }
+                seg.to_string()
+            }
+        })
+        .collect();
+    (format!("/{}", segments.join("/")), params)
+}
+
+fn operation(api: &models::proxy_api::Model, params: &[String]) -> Value {
+    let parameters: Vec<Value> = params
+        .iter()
+        .map(|name| {
+            let mut p = json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } });
+            if name == WILDCARD_PARAM {
+                p["description"] = json!("remaining path segments (may contain '/')");
+            }
+            p
+        })
+        .collect();
+    let mut responses = json!({
+        "default": { "description": "Upstream response" },
+        "401": { "description": "Missing or invalid API key" },
+        "429": { "description": "Rate limited" },
+    });
+    if api.maintenance_start.is_some() || api.maintenance_end.is_some() {
+        responses["503"] = json!({ "description": "Under maintenance" });
+    }
+    let mut op = json!({
+        "operationId": format!("{}_{}", api.method.to_ascii_lowercase(), api.id.simple()),
+        "summary": format!("{} {}", api.method, api.endpoint_url),
+        "description": "Also matches sub-paths of the endpoint; the remainder is appended to the upstream URL.",
+        "parameters": parameters,
+        "security": [{ "ApiKeyHeader": [] }, { "ApiKeyQuery": [] }],
+        "responses": responses,
+        "x-proxy-api-id": api.id,
+    });
+    if api.mock_enabled {
+        op["x-mock"] = json!(true);
+    }
+    if let Some(method) = &api.grpc_method {
+        op["x-grpc-method"] = json!(method);
+        op["requestBody"] = json!({ "content": { "application/json": { "schema": { "type": "object" } } } });
+    }
+    op
+}
+
+/// 只收录 `enabled` 的 API；同一路径的多个方法合并到一个 path item。
+/// `info.version` 取最近一次修改时间，便于客户端判断文档是否变化
+pub fn document(tenant_id: Uuid, apis: &[models::proxy_api::Model]) -> Value {
+    let mut enabled: Vec<&models::proxy_api::Model> = apis.iter().filter(|a| a.enabled && a.tenant_id == tenant_id).collect();
+    enabled.sort_by(|a, b| (&a.endpoint_url, &a.method).cmp(&(&b.endpoint_url, &b.method)));
+
+    let mut paths = Map::new();
+    for api in &enabled {
+        let (path, params) = openapi_path(&api.endpoint_url);
+        let item = paths.entry(path).or_insert_with(|| json!({}));
+        item[api.method.to_ascii_lowercase()] = operation(api, &params);
+    }
+    let version = enabled.iter().map(|a| a.updated_at).max().map(|t| t.to_rfc3339()).unwrap_or_else(|| "0".to_string());
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": format!("Proxy APIs for tenant {}", tenant_id),
+            "version": version,
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "ApiKeyHeader": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+                "ApiKeyQuery": { "type": "apiKey", "in": "query", "name": "api_key" },
+            }
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(tenant_id: Uuid, endpoint: &str, method: &str, enabled: bool) -> models::proxy_api::Model {
+        let now = chrono::Utc::now().into();
+        models::proxy_api::Model {
+            id: Uuid::new_v4(),
+            tenant_id,
+            endpoint_url: endpoint.into(),
+            method: method.into(),
+            forward_target: "http://internal-orders:8080".into(),
+            require_api_key: true,
+            enabled,
+            created_at: now,
+            updated_at: now,
+            mock_enabled: false,
+            mock_status: None,
+            mock_headers: None,
+            mock_body: None,
+            maintenance_start: None,
+            maintenance_end: None,
+            maintenance_message: None,
+            grpc_method: None,
+        }
+    }
+
+    #[test]
+    fn documents_enabled_apis_of_the_tenant() {
+        let tenant = Uuid::new_v4();
+        let apis = vec![
+            api(tenant, "/api/orders/{id}/items/*", "GET", true),
+            api(tenant, "/api/orders/{id}/items/*", "POST", true),
+            api(tenant, "/api/legacy", "GET", false),
+            api(Uuid::new_v4(), "/api/other", "GET", true),
+        ];
+        let doc = document(tenant, &apis);
+        let paths = doc["paths"].as_object().unwrap();
+        assert_eq!(paths.keys().collect::<Vec<_>>(), vec!["/api/orders/{id}/items/{wildcard}"]);
+
+        let item = &paths["/api/orders/{id}/items/{wildcard}"];
+        assert!(item["get"].is_object() && item["post"].is_object());
+        let names: Vec<&str> = item["get"]["parameters"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["id", "wildcard"]);
+        assert_eq!(item["get"]["security"][0], json!({ "ApiKeyHeader": [] }));
+        assert!(!doc.to_string().contains("internal-orders"));
+    }
+
+    #[test]
+    fn empty_tenant_yields_valid_skeleton() {
+        let doc = document(Uuid::new_v4(), &[]);
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["info"]["version"], "0");
+        assert!(doc["paths"].as_object().unwrap().is_empty());
+    }
+}
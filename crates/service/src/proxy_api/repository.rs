@@ -1,16 +1,26 @@
 use async_trait::async_trait;
+use common::pagination::{Page, Pagination};
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
 use crate::errors::ServiceError;
+use crate::proxy_api::maintenance::MaintenanceWindow;
+use crate::proxy_api::mock::MockResponse;
 
 #[async_trait]
 pub trait ProxyApiRepository: Send + Sync {
     async fn list(&self, tenant_id: Option<Uuid>) -> Result<Vec<models::proxy_api::Model>, ServiceError>;
+    async fn list_page(&self, tenant_id: Option<Uuid>, opts: Pagination) -> Result<Page<models::proxy_api::Model>, ServiceError>;
     async fn create(&self, tenant_id: Uuid, endpoint_url: &str, method: &str, forward_target: &str, require_api_key: bool) -> Result<models::proxy_api::Model, ServiceError>;
     async fn get(&self, id: Uuid) -> Result<Option<models::proxy_api::Model>, ServiceError>;
     async fn update(&self, id: Uuid, endpoint_url: Option<&str>, method: Option<&str>, forward_target: Option<&str>, require_api_key: Option<bool>, enabled: Option<bool>) -> Result<models::proxy_api::Model, ServiceError>;
     async fn delete(&self, id: Uuid) -> Result<bool, ServiceError>;
+    /// 开启/关闭 mock 模式；`mock` 为 None 时保留已存的响应配置
+    async fn set_mock(&self, id: Uuid, enabled: bool, mock: Option<MockResponse>) -> Result<models::proxy_api::Model, ServiceError>;
+    /// 设置维护窗口与停用/维护时的 503 payload
+    async fn set_maintenance(&self, id: Uuid, window: MaintenanceWindow) -> Result<models::proxy_api::Model, ServiceError>;
+    /// 设置 gRPC 转码目标方法（None 关闭转码）
+    async fn set_grpc_method(&self, id: Uuid, grpc_method: Option<String>) -> Result<models::proxy_api::Model, ServiceError>;
 }
 
 /// SeaORM-backed repository implementation.
@@ -24,6 +34,10 @@ impl ProxyApiRepository for SeaOrmProxyApiRepository {
         crate::db::proxy_api_service::list_proxy_apis(&self.db, tenant_id).await
     }
 
+    async fn list_page(&self, tenant_id: Option<Uuid>, opts: Pagination) -> Result<Page<models::proxy_api::Model>, ServiceError> {
+        crate::db::proxy_api_service::list_proxy_apis_paginated(&self.db, tenant_id, opts).await
+    }
+
     async fn create(&self, tenant_id: Uuid, endpoint_url: &str, method: &str, forward_target: &str, require_api_key: bool) -> Result<models::proxy_api::Model, ServiceError> {
         crate::db::proxy_api_service::create_proxy_api(&self.db, tenant_id, endpoint_url, method, forward_target, require_api_key).await
     }
@@ -39,4 +53,16 @@ impl ProxyApiRepository for SeaOrmProxyApiRepository {
     async fn delete(&self, id: Uuid) -> Result<bool, ServiceError> {
         crate::db::proxy_api_service::delete_proxy_api(&self.db, id).await
     }
+
+    async fn set_mock(&self, id: Uuid, enabled: bool, mock: Option<MockResponse>) -> Result<models::proxy_api::Model, ServiceError> {
+        crate::db::proxy_api_service::set_proxy_api_mock(&self.db, id, enabled, mock).await
+    }
+
+    async fn set_maintenance(&self, id: Uuid, window: MaintenanceWindow) -> Result<models::proxy_api::Model, ServiceError> {
+        crate::db::proxy_api_service::set_proxy_api_maintenance(&self.db, id, window).await
+    }
+
+    async fn set_grpc_method(&self, id: Uuid, grpc_method: Option<String>) -> Result<models::proxy_api::Model, ServiceError> {
+        crate::db::proxy_api_service::set_proxy_api_grpc_method(&self.db, id, grpc_method).await
+    }
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+//! 路径参数：endpoint_url 中 `{name}` 与末尾 `*` 捕获的值可用于
+//! - forward_target 模板，如 `/orders/{id}/items/*` → `http://orders/v2/{id}/lines/{*}`；
+//! - 上游请求头 `X-Path-{Name}`（`{id}` → `X-Path-Id`，`{order_id}` → `X-Path-Order-Id`）；
+//! - 日志字段 `path_params`。
+
+use common::routing::pattern_params;
+
+use crate::errors::ServiceError;
+
+/// (参数名, 值)，通配段名为 `*`
+pub type PathParams = Vec<(String, String)>;
+
+/// 模板中的 `{name}` 占位符
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else { break };
+        out.push(&rest[start + 1..start + 1 + len]);
+        rest = &rest[start + 2 + len..];
+    }
+    out
+}
+
+/// endpoint_url 必须是合法模式，forward_target 只能引用其中的参数
+pub fn validate(endpoint_url: &str, forward_target: &str) -> Result<(), ServiceError> {
+    let params = pattern_params(endpoint_url).map_err(|e| ServiceError::Validation(e.to_string()))?;
+    match placeholders(forward_target).into_iter().find(|p| !params.iter().any(|n| n == p)) {
+        Some(unknown) => Err(ServiceError::Validation(format!("forward_target references unknown path parameter {{{}}}", unknown))),
+        None => Ok(()),
+    }
+}
+
+/// 渲染转发地址：替换占位符后拼接前缀匹配剩余的路径；未在模板中引用的通配值同样拼接在后
+pub fn render_target(template: &str, params: &[(String, String)], rest: &str) -> String {
+    let mut out = template.to_string();
+    for name in placeholders(template) {
+        let value = params.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str()).unwrap_or_default();
+        out = out.replacen(&format!("{{{}}}", name), value, 1);
+    }
+    let mut out = out.trim_end_matches('/').to_string();
+    out.push_str(rest);
+    if let Some((_, wildcard)) = params.iter().find(|(n, _)| n == "*") {
+        if !wildcard.is_empty() && !template.contains("{*}") {
+            out.push('/');
+            out.push_str(wildcard);
+        }
+    }
+    out
+}
+
+/// 转发给上游的请求头名；通配段与非字母数字名称不下发
+pub fn header_name(param: &str) -> Option<String> {
+    if param.is_empty() || !param.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return None;
+    }
+    let words: Vec<String> = param
+        .split(['_', '-'])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map(|c| c.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()).unwrap_or_default()
+        })
+        .collect();
+    Some(format!("X-Path-{}", words.join("-")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> PathParams {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn renders_templates_rest_and_wildcards() {
+        let p = params(&[("id", "42"), ("*", "a/b")]);
+        assert_eq!(render_target("http://orders/v2/{id}/lines/{*}", &p, ""), "http://orders/v2/42/lines/a/b");
+        assert_eq!(render_target("http://orders/v2/{id}", &p, ""), "http://orders/v2/42/a/b");
+        assert_eq!(render_target("http://echo/posts/", &[], "/1"), "http://echo/posts/1");
+        assert_eq!(render_target("http://orders/{id}", &params(&[("id", "7")]), "/items"), "http://orders/7/items");
+    }
+
+    #[test]
+    fn validates_and_names_headers() {
+        assert!(validate("/orders/{id}/items/*", "http://orders/{id}/{*}").is_ok());
+        assert!(validate("/orders/{id}", "http://orders/{oid}").unwrap_err().to_string().contains("{oid}"));
+        assert!(validate("/orders/*/x", "http://orders").is_err());
+        assert_eq!(header_name("id").as_deref(), Some("X-Path-Id"));
+        assert_eq!(header_name("order_id").as_deref(), Some("X-Path-Order-Id"));
+        assert_eq!(header_name("*"), None);
+    }
+}
@@ -1,21 +1,128 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use arc_swap::ArcSwapOption;
 use uuid::Uuid;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
+use common::pagination::{Page, Pagination};
+use common::routing::Router;
+use common::time::{system_clock, SharedClock};
+
+use crate::backup::ConfigBackupService;
+use crate::cache::ReadCache;
+use crate::db::proxy_api_service;
 use crate::errors::ServiceError;
+use crate::events::{ConfigEvent, EventBus};
 use crate::proxy_api::repository::ProxyApiRepository;
+use crate::proxy_api::rewrite::{self, PathParams};
 
 /// Application service encapsulating proxy API business rules.
 /// Handles validations and tenant existence policy at the service layer.
 pub struct ProxyApiService<R: ProxyApiRepository> {
     repo: Arc<R>,
+    cache: Option<Arc<ReadCache>>,
+    events: Option<EventBus>,
+    backups: Option<Arc<ConfigBackupService>>,
+    clock: SharedClock,
+    /// 编译后的路由表，路由代次变化时重建
+    routes: ArcSwapOption<CompiledRoutes>,
+    /// 无缓存时由本实例的写操作推进的路由代次
+    generation: AtomicU64,
+}
+
+/// 请求命中的 proxy API、渲染后的转发地址与捕获的路径参数
+#[derive(Debug, Clone)]
+pub struct ResolvedRoute {
+    pub api: models::proxy_api::Model,
+    pub target: String,
+    pub params: PathParams,
+}
+
+/// 某一代 proxy API 列表编译出的路由表
+pub struct CompiledRoutes {
+    generation: u64,
+    router: Router<models::proxy_api::Model>,
+}
+
+impl CompiledRoutes {
+    pub fn build(generation: u64, apis: &[models::proxy_api::Model]) -> Self {
+        Self { generation, router: build_router(apis) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.router.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.router.is_empty()
+    }
+
+    /// 见 [`resolve_target`]
+    pub fn resolve(&self, method: &str, path: &str) -> Option<ResolvedRoute> {
+        let m = self.router.at(method, path)?;
+        let params: PathParams = m.params.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect();
+        Some(ResolvedRoute {
+            api: m.value.clone(),
+            target: rewrite::render_target(&m.value.forward_target, &params, m.rest),
+            params,
+        })
+    }
 }
 
 impl<R: ProxyApiRepository> ProxyApiService<R> {
-    pub fn new(repo: Arc<R>) -> Self { Self { repo } }
+    pub fn new(repo: Arc<R>) -> Self {
+        Self {
+            repo,
+            cache: None,
+            events: None,
+            backups: None,
+            clock: system_clock(),
+            routes: ArcSwapOption::empty(),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Serve reads from `cache`; writes publish change events on `events`.
+    pub fn with_cache(mut self, cache: Arc<ReadCache>, events: EventBus) -> Self {
+        self.cache = Some(cache);
+        self.events = Some(events);
+        self
+    }
+
+    /// Snapshot the configuration before updates/deletes. The snapshot and the change share
+    /// one transaction, so writes then go to the database directly instead of through `repo`.
+    pub fn with_backups(mut self, backups: Arc<ConfigBackupService>) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 本地缓存立即失效（写后读一致），再广播给其他订阅者
+    async fn publish_changed(&self, id: Uuid) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        let event = ConfigEvent::ProxyApiChanged { id };
+        match (&self.cache, &self.events) {
+            (Some(cache), Some(bus)) => { cache.apply(bus, event).await; }
+            (None, Some(bus)) => { bus.publish(event); }
+            _ => {}
+        }
+    }
 
     pub async fn list(&self, tenant_id: Option<Uuid>) -> Result<Vec<models::proxy_api::Model>, ServiceError> {
-        self.repo.list(tenant_id).await
+        match &self.cache {
+            Some(cache) => cache.proxy_api_lists.get_or_load(tenant_id, self.repo.list(tenant_id)).await,
+            None => self.repo.list(tenant_id).await,
+        }
+    }
+
+    /// Paginated list with totals, paged in the query (not through the list cache);
+    /// rejects invalid or out-of-range pages.
+    pub async fn list_page(&self, tenant_id: Option<Uuid>, opts: Pagination) -> Result<Page<models::proxy_api::Model>, ServiceError> {
+        self.repo.list_page(tenant_id, opts).await
     }
 
     /// Create with policy: auto-create tenant if missing.
@@ -29,18 +136,27 @@ impl<R: ProxyApiRepository> ProxyApiService<R> {
         require_api_key: bool,
         db: &sea_orm::DatabaseConnection,
     ) -> Result<models::proxy_api::Model, ServiceError> {
+        rewrite::validate(endpoint_url, forward_target)?;
+        check_egress(forward_target).await?;
         // Ensure tenant exists; create if not.
         use sea_orm::{EntityTrait, ActiveModelTrait, Set};
         let maybe = models::tenant::Entity::find_by_id(tenant_id).one(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
         if maybe.is_none() {
-            let am = models::tenant::ActiveModel { id: Set(tenant_id), name: Set(format!("auto-tenant-{}", tenant_id)), created_at: Set(chrono::Utc::now().into()) };
+            let am = models::tenant::ActiveModel { id: Set(tenant_id), name: Set(format!("auto-tenant-{}", tenant_id)), created_at: Set(self.clock.now_fixed()), suspended: Set(false), suspended_reason: Set(None), email_verification: Set(models::tenant::EMAIL_VERIFICATION_OFF.into()), invite_only: Set(false) };
             am.insert(db).await.map_err(|e| ServiceError::Db(e.to_string()))?;
             info!(tenant_id = %tenant_id, "auto_created_tenant_for_proxy_api");
         }
-        self.repo.create(tenant_id, endpoint_url, method, forward_target, require_api_key).await
+        let created = self.repo.create(tenant_id, endpoint_url, method, forward_target, require_api_key).await?;
+        self.publish_changed(created.id).await;
+        Ok(created)
     }
 
-    pub async fn get(&self, id: Uuid) -> Result<Option<models::proxy_api::Model>, ServiceError> { self.repo.get(id).await }
+    pub async fn get(&self, id: Uuid) -> Result<Option<models::proxy_api::Model>, ServiceError> {
+        match &self.cache {
+            Some(cache) => cache.proxy_api_by_id.get_or_load(id, self.repo.get(id)).await,
+            None => self.repo.get(id).await,
+        }
+    }
 
     pub async fn update(
         &self,
@@ -51,8 +167,196 @@ impl<R: ProxyApiRepository> ProxyApiService<R> {
         require_api_key: Option<bool>,
         enabled: Option<bool>,
     ) -> Result<models::proxy_api::Model, ServiceError> {
-        self.repo.update(id, endpoint_url, method, forward_target, require_api_key, enabled).await
+        if endpoint_url.is_some() || forward_target.is_some() {
+            let current = self.repo.get(id).await?.ok_or_else(|| ServiceError::not_found("proxy_api"))?;
+            rewrite::validate(endpoint_url.unwrap_or(&current.endpoint_url), forward_target.unwrap_or(&current.forward_target))?;
+        }
+        if let Some(target) = forward_target {
+            check_egress(target).await?;
+        }
+        let updated = match &self.backups {
+            Some(b) => {
+                let change = b.pre_apply(id).await?;
+                let updated = proxy_api_service::update_proxy_api(change.txn(), id, endpoint_url, method, forward_target, require_api_key, enabled).await?;
+                change.commit().await?;
+                updated
+            }
+            None => self.repo.update(id, endpoint_url, method, forward_target, require_api_key, enabled).await?,
+        };
+        self.publish_changed(id).await;
+        Ok(updated)
     }
 
-    pub async fn delete(&self, id: Uuid) -> Result<bool, ServiceError> { self.repo.delete(id).await }
+    pub async fn delete(&self, id: Uuid) -> Result<bool, ServiceError> {
+        let deleted = match &self.backups {
+            Some(b) => {
+                let change = b.pre_apply(id).await?;
+                let deleted = proxy_api_service::delete_proxy_api(change.txn(), id).await?;
+                change.commit().await?;
+                deleted
+            }
+            None => self.repo.delete(id).await?,
+        };
+        if deleted { self.publish_changed(id).await; }
+        Ok(deleted)
+    }
+
+    pub async fn set_mock(&self, id: Uuid, enabled: bool, mock: Option<crate::proxy_api::mock::MockResponse>) -> Result<models::proxy_api::Model, ServiceError> {
+        let updated = match &self.backups {
+            Some(b) => {
+                let change = b.pre_apply(id).await?;
+                let updated = proxy_api_service::set_proxy_api_mock(change.txn(), id, enabled, mock).await?;
+                change.commit().await?;
+                updated
+            }
+            None => self.repo.set_mock(id, enabled, mock).await?,
+        };
+        info!(id = %id, enabled, "proxy_api_mock_updated");
+        self.publish_changed(id).await;
+        Ok(updated)
+    }
+
+    pub async fn set_maintenance(&self, id: Uuid, window: crate::proxy_api::maintenance::MaintenanceWindow) -> Result<models::proxy_api::Model, ServiceError> {
+        let updated = match &self.backups {
+            Some(b) => {
+                let change = b.pre_apply(id).await?;
+                let updated = proxy_api_service::set_proxy_api_maintenance(change.txn(), id, window).await?;
+                change.commit().await?;
+                updated
+            }
+            None => self.repo.set_maintenance(id, window).await?,
+        };
+        info!(id = %id, start = ?updated.maintenance_start, end = ?updated.maintenance_end, "proxy_api_maintenance_updated");
+        self.publish_changed(id).await;
+        Ok(updated)
+    }
+
+    /// Map the proxy API to a gRPC method (None turns transcoding off).
+    pub async fn set_grpc_method(&self, id: Uuid, grpc_method: Option<String>) -> Result<models::proxy_api::Model, ServiceError> {
+        let updated = match &self.backups {
+            Some(b) => {
+                let change = b.pre_apply(id).await?;
+                let updated = proxy_api_service::set_proxy_api_grpc_method(change.txn(), id, grpc_method).await?;
+                change.commit().await?;
+                updated
+            }
+            None => self.repo.set_grpc_method(id, grpc_method).await?,
+        };
+        info!(id = %id, grpc_method = ?updated.grpc_method, "proxy_api_grpc_method_updated");
+        self.publish_changed(id).await;
+        Ok(updated)
+    }
+
+    /// Find the proxy API serving `method path` and the URL to forward to.
+    /// Disabled APIs still match so the caller can answer 503 instead of 404.
+    pub async fn resolve(&self, method: &str, path: &str) -> Result<Option<ResolvedRoute>, ServiceError> {
+        Ok(self.routes().await?.resolve(method, path))
+    }
+
+    /// 当前路由表；proxy API 变更（缓存失效或本实例写入）后首次访问时重建
+    pub async fn routes(&self) -> Result<Arc<CompiledRoutes>, ServiceError> {
+        let generation = self.route_generation();
+        if let Some(routes) = self.routes.load_full().filter(|r| r.generation == generation) {
+            return Ok(routes);
+        }
+        let all = self.list(None).await?;
+        let routes = Arc::new(CompiledRoutes::build(generation, &all));
+        info!(generation, routes = routes.len(), "proxy_api_routes_rebuilt");
+        self.routes.store(Some(routes.clone()));
+        Ok(routes)
+    }
+
+    fn route_generation(&self) -> u64 {
+        match &self.cache {
+            Some(cache) => cache.proxy_api_lists.version(),
+            None => self.generation.load(Ordering::Acquire),
+        }
+    }
+}
+
+/// forward_target 的主机在出站 allowlist 内，且解析结果不落在被拒绝的私有/保留网段
+async fn check_egress(forward_target: &str) -> Result<(), ServiceError> {
+    common::egress::global()
+        .check_url_resolved(forward_target)
+        .await
+        .map_err(|e| ServiceError::Validation(format!("forward_target: {}", e)))
+}
+
+/// endpoint_url 作为前缀路由编译（支持 `{param}` 与末尾 `*`），无法编译的条目跳过
+fn build_router(apis: &[models::proxy_api::Model]) -> Router<models::proxy_api::Model> {
+    let mut router = Router::new();
+    for api in apis {
+        if let Err(e) = router.insert(&api.method, &api.endpoint_url, api.clone(), true) {
+            warn!(id = %api.id, endpoint_url = %api.endpoint_url, err = %e, "proxy_api_route_skipped");
+        }
+    }
+    router
+}
+
+/// 精确匹配 endpoint_url，或以 `endpoint_url/` 为前缀（剩余部分拼接到 forward_target）；
+/// 多个命中时取最具体的 endpoint_url。停用的 API 同样参与匹配（由调用方返回 503）。
+/// endpoint_url 中的 `{name}` / `*` 捕获值可在 forward_target 中以 `{name}` / `{*}` 引用。
+pub fn resolve_target(apis: &[models::proxy_api::Model], method: &str, path: &str) -> Option<ResolvedRoute> {
+    CompiledRoutes::build(0, apis).resolve(method, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(endpoint: &str, method: &str, target: &str, enabled: bool) -> models::proxy_api::Model {
+        let now = chrono::Utc::now().into();
+        models::proxy_api::Model {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            endpoint_url: endpoint.into(),
+            method: method.into(),
+            forward_target: target.into(),
+            require_api_key: false,
+            enabled,
+            created_at: now,
+            updated_at: now,
+            mock_enabled: false,
+            mock_status: None,
+            mock_headers: None,
+            mock_body: None,
+            maintenance_start: None,
+            maintenance_end: None,
+            maintenance_message: None,
+            grpc_method: None,
+        }
+    }
+
+    #[test]
+    fn resolve_target_prefers_longest_match() {
+        let apis = vec![
+            api("/api/posts", "GET", "http://echo/posts", true),
+            api("/api/posts/special", "GET", "http://special/", true),
+            api("/api/off", "GET", "http://off", false),
+        ];
+        assert_eq!(resolve_target(&apis, "GET", "/api/posts").unwrap().target, "http://echo/posts");
+        assert_eq!(resolve_target(&apis, "get", "/api/posts/1").unwrap().target, "http://echo/posts/1");
+        assert_eq!(resolve_target(&apis, "GET", "/api/posts/special/x").unwrap().target, "http://special/x");
+        assert!(resolve_target(&apis, "GET", "/api/postsx").is_none());
+        assert!(resolve_target(&apis, "POST", "/api/posts").is_none());
+        assert!(!resolve_target(&apis, "GET", "/api/off").unwrap().api.enabled);
+    }
+
+    #[test]
+    fn compiled_routes_match_path_params() {
+        let apis = vec![
+            api("/api/orders/{id}", "GET", "http://orders/", true),
+            api("/api/orders/recent", "GET", "http://recent", true),
+        ];
+        let routes = CompiledRoutes::build(1, &apis);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes.resolve("GET", "/api/orders/7/items").unwrap().target, "http://orders/items");
+        assert_eq!(routes.resolve("GET", "/api/orders/recent").unwrap().target, "http://recent");
+        assert!(routes.resolve("GET", "/api/orders").is_none());
+
+        let templated = vec![api("/api/orders/{id}/items/*", "GET", "http://orders/v2/{id}/lines/{*}", true)];
+        let r = resolve_target(&templated, "GET", "/api/orders/9/items/3/detail").unwrap();
+        assert_eq!(r.target, "http://orders/v2/9/lines/3/detail");
+        assert_eq!(r.params, vec![("id".to_string(), "9".to_string()), ("*".to_string(), "3/detail".to_string())]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,224 @@
+//! Request replay: re-issue a captured request (`request_payload`) and diff the result against the
+//! response recorded at capture time.
+//! - Both sides go through the same redaction before comparing, so masked values never show up as changes.
+//! - Headers that differ on every response (Date, Content-Length, ...) are ignored.
+//! - JSON bodies are compared field by field; other bodies report the first differing byte offset.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use axum::http::HeaderMap;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Response headers that change between otherwise identical responses.
+pub const VOLATILE_HEADERS: &[&str] =
+    &["date", "age", "expires", "connection", "keep-alive", "transfer-encoding", "content-length", "x-request-id", "server-timing"];
+
+/// Request headers not re-sent on replay: hop-by-hop headers and ones the HTTP client recomputes.
+pub const SKIPPED_REQUEST_HEADERS: &[&str] =
+    &["host", "connection", "keep-alive", "proxy-connection", "transfer-encoding", "te", "upgrade", "content-length"];
+
+/// At most this many body field changes are reported.
+pub const MAX_BODY_CHANGES: usize = 100;
+
+/// Header map as stored in `request_payload`: lower-case names, values redacted, repeated headers joined with ", ".
+pub fn header_snapshot(headers: &HeaderMap) -> BTreeMap<String, String> {
+    let redaction = common::redaction::global();
+    let mut out: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in headers {
+        let value = String::from_utf8_lossy(value.as_bytes());
+        let value = redaction.header_value(name.as_str(), &value).to_string();
+        out.entry(name.as_str().to_string()).and_modify(|v| *v = format!("{}, {}", v, value)).or_insert(value);
+    }
+    out
+}
+
+/// Body as stored in `request_payload`: UTF-8 bodies are redacted, binary bodies kept as-is.
+pub fn body_snapshot(body: &[u8]) -> Vec<u8> {
+    match std::str::from_utf8(body) {
+        Ok(text) => common::redaction::global().redact_body(text).into_bytes(),
+        Err(_) => body.to_vec(),
+    }
+}
+
+/// One side of the comparison.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+    /// Body was cut at the capture limit; only the captured prefix is compared.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatusChange {
+    pub recorded: u16,
+    pub replayed: u16,
+}
+
+/// `None` on one side means the header is absent there.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HeaderChange {
+    pub name: String,
+    pub recorded: Option<String>,
+    pub replayed: Option<String>,
+}
+
+/// A changed JSON field; `path` uses `$.a.b[0]` notation, `None` means the field is absent on that side.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    pub recorded: Option<Value>,
+    pub replayed: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BodyDiff {
+    pub recorded_bytes: usize,
+    pub replayed_bytes: usize,
+    pub recorded_truncated: bool,
+    /// Field-level changes when both bodies are JSON.
+    pub changes: Vec<FieldChange>,
+    /// Byte offset of the first difference for non-JSON bodies.
+    pub first_difference_at: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReplayDiff {
+    pub identical: bool,
+    pub status: Option<StatusChange>,
+    pub headers: Vec<HeaderChange>,
+    pub body: Option<BodyDiff>,
+}
+
+/// Compare the recorded response with the replayed one; `ignore_headers` extends [`VOLATILE_HEADERS`].
+pub fn diff(recorded: &Snapshot, replayed: &Snapshot, ignore_headers: &[String]) -> ReplayDiff {
+    let status = (recorded.status != replayed.status).then_some(StatusChange { recorded: recorded.status, replayed: replayed.status });
+
+    let ignored = |name: &str| VOLATILE_HEADERS.contains(&name) || ignore_headers.iter().any(|h| h.eq_ignore_ascii_case(name));
+    let names: BTreeSet<&String> = recorded.headers.keys().chain(replayed.headers.keys()).collect();
+    let headers: Vec<HeaderChange> = names
+        .into_iter()
+        .filter(|name| !ignored(name))
+        .filter_map(|name| {
+            let (before, after) = (recorded.headers.get(name), replayed.headers.get(name));
+            (before != after).then(|| HeaderChange { name: name.clone(), recorded: before.cloned(), replayed: after.cloned() })
+        })
+        .collect();
+
+    let body = body_diff(recorded, &replayed.body);
+    ReplayDiff { identical: status.is_none() && headers.is_empty() && body.is_none(), status, headers, body }
+}
+
+fn body_diff(recorded: &Snapshot, replayed: &[u8]) -> Option<BodyDiff> {
+    let compared = if recorded.truncated { &replayed[..replayed.len().min(recorded.body.len())] } else { replayed };
+    if recorded.body == compared {
+        return None;
+    }
+    let mut out = BodyDiff {
+        recorded_bytes: recorded.body.len(),
+        replayed_bytes: replayed.len(),
+        recorded_truncated: recorded.truncated,
+        changes: Vec::new(),
+        first_difference_at: None,
+    };
+    let parsed = (!recorded.truncated)
+        .then(|| Some((serde_json::from_slice::<Value>(&recorded.body).ok()?, serde_json::from_slice::<Value>(replayed).ok()?)))
+        .flatten();
+    match parsed {
+        Some((before, after)) => {
+            json_changes("$".to_string(), Some(&before), Some(&after), &mut out.changes);
+            // 仅格式不同（如键顺序、空白）的 JSON 视为相同
+            if out.changes.is_empty() {
+                return None;
+            }
+        }
+        None => {
+            let common_len = recorded.body.len().min(compared.len());
+            out.first_difference_at = Some((0..common_len).find(|&i| recorded.body[i] != compared[i]).unwrap_or(common_len));
+        }
+    }
+    Some(out)
+}
+
+fn json_changes(path: String, before: Option<&Value>, after: Option<&Value>, out: &mut Vec<FieldChange>) {
+    if out.len() >= MAX_BODY_CHANGES || before == after {
+        return;
+    }
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                json_changes(format!("{}.{}", path, key), a.get(key), b.get(key), out);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                json_changes(format!("{}[{}]", path, i), a.get(i), b.get(i), out);
+            }
+        }
+        _ => out.push(FieldChange { path, recorded: before.cloned(), replayed: after.cloned() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot(status: u16, headers: &[(&str, &str)], body: &str) -> Snapshot {
+        Snapshot {
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.as_bytes().to_vec(),
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn identical_responses_ignore_volatile_headers_and_json_formatting() {
+        let recorded = snapshot(200, &[("content-type", "application/json"), ("date", "Mon")], r#"{"a":1,"b":[1,2]}"#);
+        let replayed = snapshot(200, &[("content-type", "application/json"), ("date", "Tue")], r#"{ "b": [1, 2], "a": 1 }"#);
+        assert!(diff(&recorded, &replayed, &[]).identical);
+    }
+
+    #[test]
+    fn reports_status_header_and_json_field_changes() {
+        let recorded = snapshot(200, &[("content-type", "application/json"), ("x-version", "1"), ("etag", "\"a\"")], r#"{"user":{"id":1,"name":"a"},"tags":["x"]}"#);
+        let replayed = snapshot(500, &[("content-type", "application/json"), ("x-cache", "MISS"), ("etag", "\"b\"")], r#"{"user":{"id":1,"name":"b"},"tags":["x","y"]}"#);
+        let d = diff(&recorded, &replayed, &["ETag".to_string()]);
+        assert!(!d.identical);
+        assert_eq!(d.status, Some(StatusChange { recorded: 200, replayed: 500 }));
+        let names: Vec<&str> = d.headers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["x-cache", "x-version"]);
+        assert_eq!(d.headers[1].replayed, None);
+        let body = d.body.unwrap();
+        let paths: Vec<&str> = body.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.tags[1]", "$.user.name"]);
+        assert_eq!(body.changes[0], FieldChange { path: "$.tags[1]".into(), recorded: None, replayed: Some(json!("y")) });
+    }
+
+    #[test]
+    fn non_json_and_truncated_bodies_compare_bytes() {
+        let d = diff(&snapshot(200, &[], "hello world"), &snapshot(200, &[], "hello there"), &[]);
+        assert_eq!(d.body.unwrap().first_difference_at, Some(6));
+
+        let mut recorded = snapshot(200, &[], "hello");
+        recorded.truncated = true;
+        assert!(diff(&recorded, &snapshot(200, &[], "hello world"), &[]).identical);
+        let d = diff(&recorded, &snapshot(200, &[], "help"), &[]);
+        assert_eq!(d.body.map(|b| (b.first_difference_at, b.recorded_truncated)), Some((Some(3), true)));
+    }
+
+    #[test]
+    fn snapshots_redact_sensitive_headers_and_join_repeated_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        headers.append("accept", "text/html".parse().unwrap());
+        headers.append("accept", "application/json".parse().unwrap());
+        let snap = header_snapshot(&headers);
+        assert_ne!(snap["authorization"], "Bearer secret");
+        assert_eq!(snap["accept"], "text/html, application/json");
+    }
+}
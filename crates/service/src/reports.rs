@@ -0,0 +1,279 @@
+//! Top-N reports for `GET /admin/reports/top`, for capacity planning and abuse investigation.
+//! - Route and tenant rankings read `metrics_rollup`; API key and client IP rankings read
+//!   `metrics_rollup_dimension`. Both are written by [`crate::metrics_rollup`], so the newest
+//!   minute may still be missing and ranges older than the rollup retention return nothing.
+//! - `latency_p99` ranks by the highest per-minute p99 in the range (an upper bound, as in
+//!   `/admin/metrics/query`).
+//! - Totals cover all traffic in the range, so `share` is each entry's fraction of all requests.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use models::{metrics_rollup_dimension, route, tenant};
+
+use crate::errors::ServiceError;
+
+/// Longest range one report may cover.
+pub const MAX_RANGE: chrono::Duration = chrono::Duration::days(31);
+pub const MAX_LIMIT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Route,
+    Tenant,
+    ApiKey,
+    ClientIp,
+}
+
+impl Dimension {
+    pub fn parse(s: &str) -> Result<Self, ServiceError> {
+        match s {
+            "route" => Ok(Self::Route),
+            "tenant" => Ok(Self::Tenant),
+            "api_key" => Ok(Self::ApiKey),
+            "client_ip" => Ok(Self::ClientIp),
+            other => Err(ServiceError::Validation(format!("unknown dimension {}; expected route, tenant, api_key or client_ip", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    Requests,
+    Errors,
+    LatencyP99,
+}
+
+impl Metric {
+    pub fn parse(s: &str) -> Result<Self, ServiceError> {
+        match s {
+            "requests" => Ok(Self::Requests),
+            "errors" => Ok(Self::Errors),
+            "latency_p99" => Ok(Self::LatencyP99),
+            other => Err(ServiceError::Validation(format!("unknown metric {}; expected requests, errors or latency_p99", other))),
+        }
+    }
+
+    /// Result column ranked on; a fixed identifier, never user input.
+    fn column(self) -> &'static str {
+        match self {
+            Self::Requests => "requests",
+            Self::Errors => "errors",
+            Self::LatencyP99 => "latency_p99_ms",
+        }
+    }
+}
+
+/// `30m`, `24h`, `7d`; a bare number is seconds.
+pub fn parse_range(s: &str) -> Result<chrono::Duration, ServiceError> {
+    let s = s.trim();
+    let invalid = || ServiceError::Validation(format!("invalid range {:?}; use e.g. 30m, 24h or 7d", s));
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let n: i64 = num.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "" | "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86_400,
+        _ => return Err(invalid()),
+    };
+    chrono::Duration::try_seconds(secs).filter(|d| *d > chrono::Duration::zero()).ok_or_else(invalid)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopQuery {
+    pub dimension: Dimension,
+    pub metric: Metric,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub limit: u32,
+}
+
+impl TopQuery {
+    pub fn validate(&self) -> Result<(), ServiceError> {
+        if self.to <= self.from {
+            return Err(ServiceError::Validation("range must be positive".into()));
+        }
+        if self.to - self.from > MAX_RANGE {
+            return Err(ServiceError::Validation(format!("range must not exceed {} days", MAX_RANGE.num_days())));
+        }
+        if self.limit == 0 || self.limit > MAX_LIMIT {
+            return Err(ServiceError::Validation(format!("limit must be between 1 and {}", MAX_LIMIT)));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportTotals {
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopEntry {
+    pub rank: u32,
+    /// Route / tenant / API key id, or the client IP
+    pub key: String,
+    /// `GET /orders` for routes, the tenant name for tenants
+    pub label: Option<String>,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_p99_ms: f64,
+    /// Fraction of all requests in the range
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopReport {
+    pub dimension: Dimension,
+    pub metric: Metric,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub totals: ReportTotals,
+    pub items: Vec<TopEntry>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RankRow {
+    key: String,
+    requests: i64,
+    errors: i64,
+    latency_p99_ms: Option<f64>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TotalsRow {
+    requests: i64,
+    errors: i64,
+}
+
+fn dbe(e: sea_orm::DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+fn ratio(part: i64, whole: i64) -> f64 {
+    if whole > 0 { part as f64 / whole as f64 } else { 0.0 }
+}
+
+/// Rank SQL for a dimension; `$1`/`$2` bound the range, `$3` is the limit.
+fn rank_sql(dimension: Dimension, metric: Metric) -> String {
+    let (key, source) = match dimension {
+        Dimension::Route => ("route_id::text", "metrics_rollup WHERE"),
+        Dimension::Tenant => ("tenant_id::text", "metrics_rollup WHERE"),
+        Dimension::ApiKey | Dimension::ClientIp => ("key", "metrics_rollup_dimension WHERE dimension = $4 AND"),
+    };
+    format!(
+        "SELECT {key} AS key, sum(requests)::bigint AS requests, sum(errors)::bigint AS errors, max(latency_p99_ms) AS latency_p99_ms \
+         FROM {source} bucket >= $1 AND bucket < $2 \
+         GROUP BY 1 ORDER BY {order} DESC NULLS LAST, requests DESC, key LIMIT $3",
+        order = metric.column()
+    )
+}
+
+/// Route and tenant labels; keys that are not (or no longer) known stay unlabeled.
+async fn labels(db: &DatabaseConnection, dimension: Dimension, keys: &[String]) -> Result<HashMap<String, String>, ServiceError> {
+    let ids: Vec<Uuid> = keys.iter().filter_map(|k| Uuid::parse_str(k).ok()).collect();
+    let labels = match dimension {
+        Dimension::Route => route::Entity::find()
+            .filter(route::Column::Id.is_in(ids))
+            .all(db)
+            .await
+            .map_err(dbe)?
+            .into_iter()
+            .map(|r| (r.id.to_string(), format!("{} {}", r.method, r.path)))
+            .collect(),
+        Dimension::Tenant => tenant::Entity::find().filter(tenant::Column::Id.is_in(ids)).all(db).await.map_err(dbe)?.into_iter().map(|t| (t.id.to_string(), t.name)).collect(),
+        Dimension::ApiKey | Dimension::ClientIp => HashMap::new(),
+    };
+    Ok(labels)
+}
+
+pub async fn top(db: &DatabaseConnection, q: &TopQuery) -> Result<TopReport, ServiceError> {
+    q.validate()?;
+    let range = || -> Vec<sea_orm::Value> { vec![q.from.fixed_offset().into(), q.to.fixed_offset().into()] };
+
+    let totals = TotalsRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT coalesce(sum(requests), 0)::bigint AS requests, coalesce(sum(errors), 0)::bigint AS errors FROM metrics_rollup WHERE bucket >= $1 AND bucket < $2",
+        range(),
+    ))
+    .one(db)
+    .await
+    .map_err(dbe)?
+    .map(|t| ReportTotals { requests: t.requests, errors: t.errors, error_rate: ratio(t.errors, t.requests) })
+    .unwrap_or_default();
+
+    let mut values = range();
+    values.push((q.limit as i64).into());
+    match q.dimension {
+        Dimension::ApiKey => values.push(metrics_rollup_dimension::DIMENSION_API_KEY.into()),
+        Dimension::ClientIp => values.push(metrics_rollup_dimension::DIMENSION_CLIENT_IP.into()),
+        Dimension::Route | Dimension::Tenant => {}
+    }
+    let rows = RankRow::find_by_statement(Statement::from_sql_and_values(DbBackend::Postgres, rank_sql(q.dimension, q.metric), values))
+        .all(db)
+        .await
+        .map_err(dbe)?;
+
+    let keys: Vec<String> = rows.iter().map(|r| r.key.clone()).collect();
+    let labels = labels(db, q.dimension, &keys).await?;
+    let items = rows
+        .into_iter()
+        .zip(1..)
+        .map(|(r, rank)| TopEntry {
+            rank,
+            label: labels.get(&r.key).cloned(),
+            error_rate: ratio(r.errors, r.requests),
+            share: ratio(r.requests, totals.requests),
+            latency_p99_ms: r.latency_p99_ms.unwrap_or(0.0),
+            key: r.key,
+            requests: r.requests,
+            errors: r.errors,
+        })
+        .collect();
+    Ok(TopReport { dimension: q.dimension, metric: q.metric, from: q.from, to: q.to, totals, items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ranges_dimensions_and_metrics() {
+        assert_eq!(parse_range("24h").unwrap(), chrono::Duration::hours(24));
+        assert_eq!(parse_range("30m").unwrap(), chrono::Duration::minutes(30));
+        assert_eq!(parse_range("7d").unwrap(), chrono::Duration::days(7));
+        assert_eq!(parse_range("90").unwrap(), chrono::Duration::seconds(90));
+        for bad in ["", "h", "0h", "-1h", "2w", "1.5h"] {
+            assert!(parse_range(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(Dimension::parse("client_ip").unwrap(), Dimension::ClientIp);
+        assert!(Dimension::parse("country").is_err());
+        assert_eq!(Metric::parse("latency_p99").unwrap(), Metric::LatencyP99);
+        assert!(Metric::parse("p99").is_err());
+
+        let now = Utc::now();
+        let q = |from| TopQuery { dimension: Dimension::Route, metric: Metric::Requests, from, to: now, limit: 10 };
+        assert!(q(now - chrono::Duration::days(1)).validate().is_ok());
+        assert!(q(now - chrono::Duration::days(32)).validate().is_err());
+        let over = TopQuery { limit: MAX_LIMIT + 1, ..q(now - chrono::Duration::hours(1)) };
+        assert!(over.validate().is_err());
+    }
+
+    #[test]
+    fn rank_sql_orders_by_metric() {
+        let sql = rank_sql(Dimension::ClientIp, Metric::LatencyP99);
+        assert!(sql.contains("FROM metrics_rollup_dimension WHERE dimension = $4 AND bucket >= $1"));
+        assert!(sql.contains("ORDER BY latency_p99_ms DESC"));
+        assert!(rank_sql(Dimension::Tenant, Metric::Errors).starts_with("SELECT tenant_id::text AS key"));
+    }
+}
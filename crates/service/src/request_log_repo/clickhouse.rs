@@ -0,0 +1,432 @@
+//! ClickHouse request log repository over the HTTP interface (port 8123).
+//! - Rows go to a MergeTree table partitioned by day and expired by `TTL timestamp + ttl_days`;
+//!   inserts use `async_insert` so ClickHouse batches small writes server side.
+//! - ClickHouse has no sequences: ids are `unix_ms << 16 | counter`, increasing per process and
+//!   usable for `after_id` polling; the counter starts at a random value so replicas rarely collide.
+//! - Query values are bound as `{name:Type}` parameters, never spliced into SQL. Tenant filters and
+//!   route paths are resolved against Postgres, where routes live.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use common::http_client::{HttpClientConfig, HttpClientFactory};
+use models::{request_log, route};
+
+use super::{NewRequestLog, RequestLogRepository};
+use crate::db::request_log_service::RequestLogFilter;
+use crate::errors::ServiceError;
+use crate::key_usage::{self, KeyRouteUsage, KeyUsage, KeyUsageQuery, LatencyStats, StatusCount, MAX_TOP_ROUTES};
+use crate::stats::WindowStats;
+
+#[derive(Debug, Clone)]
+pub struct ClickHouseOptions {
+    /// e.g. `http://clickhouse:8123`
+    pub url: String,
+    pub database: String,
+    /// Plain identifier, validated by the config loader
+    pub table: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub ttl_days: u32,
+    pub async_insert: bool,
+    /// Wait until the async insert is flushed, so insert errors reach the caller
+    pub wait_for_async_insert: bool,
+    pub timeout: Duration,
+}
+
+impl Default for ClickHouseOptions {
+    fn default() -> Self {
+        Self {
+            url: "http://127.0.0.1:8123".into(),
+            database: "default".into(),
+            table: "request_log".into(),
+            user: None,
+            password: None,
+            ttl_days: 30,
+            async_insert: true,
+            wait_for_async_insert: true,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Time-ordered ids: `unix_ms << 16 | counter`.
+struct IdGenerator {
+    counter: AtomicU64,
+}
+
+impl IdGenerator {
+    fn new() -> Self {
+        Self { counter: AtomicU64::new(rand::thread_rng().gen()) }
+    }
+
+    fn next(&self, at: DateTime<Utc>) -> i64 {
+        (at.timestamp_millis() << 16) | (self.counter.fetch_add(1, Ordering::Relaxed) & 0xFFFF) as i64
+    }
+}
+
+/// One row as written to and read from ClickHouse (`JSONEachRow`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Row {
+    id: i64,
+    route_id: Uuid,
+    api_key_id: Option<Uuid>,
+    status_code: i32,
+    latency_ms: i32,
+    success: bool,
+    error_message: Option<String>,
+    client_ip: Option<String>,
+    #[serde(with = "ch_datetime")]
+    timestamp: DateTime<Utc>,
+    config_revision: Option<i64>,
+    country: Option<String>,
+    asn: Option<i64>,
+}
+
+impl From<Row> for request_log::Model {
+    fn from(r: Row) -> Self {
+        Self {
+            id: r.id,
+            route_id: r.route_id,
+            api_key_id: r.api_key_id,
+            status_code: r.status_code,
+            latency_ms: r.latency_ms,
+            success: r.success,
+            error_message: r.error_message,
+            client_ip: r.client_ip,
+            timestamp: r.timestamp.fixed_offset(),
+            config_revision: r.config_revision,
+            country: r.country,
+            asn: r.asn,
+        }
+    }
+}
+
+/// `DateTime64(3, 'UTC')` in the default text format, `2026-10-16 08:30:00.123`.
+mod ch_datetime {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+    pub fn serialize<S: Serializer>(t: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&t.format(FORMAT).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+        let s = String::deserialize(d)?;
+        NaiveDateTime::parse_from_str(&s, FORMAT).map(|t| t.and_utc()).map_err(serde::de::Error::custom)
+    }
+
+    pub fn param(t: &DateTime<Utc>) -> String {
+        t.format(FORMAT).to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TotalsRow {
+    requests: i64,
+    errors: i64,
+    rate_limited: i64,
+    last_request_at: Option<String>,
+    avg_ms: Option<f64>,
+    p50_ms: Option<f64>,
+    p95_ms: Option<f64>,
+    p99_ms: Option<f64>,
+    max_ms: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusRow {
+    status_code: i32,
+    requests: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RouteRow {
+    route_id: Uuid,
+    requests: i64,
+    errors: i64,
+    p95_ms: Option<f64>,
+}
+
+fn che(e: impl std::fmt::Display) -> ServiceError {
+    ServiceError::Db(format!("clickhouse: {}", e))
+}
+
+pub struct ClickHouseRequestLogRepository {
+    /// Routes and API keys
+    db: DatabaseConnection,
+    client: reqwest::Client,
+    opts: ClickHouseOptions,
+    ids: IdGenerator,
+}
+
+impl ClickHouseRequestLogRepository {
+    pub fn new(db: DatabaseConnection, opts: ClickHouseOptions) -> Result<Self, ServiceError> {
+        let client = HttpClientFactory::new(HttpClientConfig { timeout_ms: opts.timeout.as_millis() as u64, ..Default::default() })
+            .with_egress(common::egress::global())
+            .build()
+            .map_err(che)?;
+        Ok(Self { db, client, opts, ids: IdGenerator::new() })
+    }
+
+    /// Create the table when missing and apply the configured TTL (existing parts are not rewritten).
+    pub async fn ensure_schema(&self) -> Result<(), ServiceError> {
+        let t = &self.opts.table;
+        let ttl = format!("toDateTime(timestamp) + INTERVAL {} DAY", self.opts.ttl_days.max(1));
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {t} (\
+                id Int64, route_id UUID, api_key_id Nullable(UUID), status_code Int32, latency_ms Int32, success Bool, \
+                error_message Nullable(String), client_ip Nullable(String), timestamp DateTime64(3, 'UTC'), \
+                config_revision Nullable(Int64), country LowCardinality(Nullable(String)), asn Nullable(Int64), \
+                INDEX idx_id id TYPE minmax GRANULARITY 4, \
+                INDEX idx_api_key api_key_id TYPE bloom_filter GRANULARITY 4\
+            ) ENGINE = MergeTree PARTITION BY toYYYYMMDD(timestamp) ORDER BY (timestamp, id) TTL {ttl}"
+        );
+        self.execute(&create, &[]).await?;
+        self.execute(&format!("ALTER TABLE {t} MODIFY TTL {ttl}"), &[("materialize_ttl_after_modify", "0".into())]).await?;
+        info!(table = %t, ttl_days = self.opts.ttl_days, "clickhouse request log table ready");
+        Ok(())
+    }
+
+    fn request(&self, params: &[(&str, String)]) -> reqwest::RequestBuilder {
+        let mut req = self.client.post(&self.opts.url).query(&[("database", self.opts.database.as_str())]).query(params);
+        if let Some(user) = &self.opts.user {
+            req = req.header("X-ClickHouse-User", user);
+        }
+        if let Some(password) = &self.opts.password {
+            req = req.header("X-ClickHouse-Key", password);
+        }
+        req
+    }
+
+    async fn send(req: reqwest::RequestBuilder) -> Result<String, ServiceError> {
+        let resp = req.send().await.map_err(che)?;
+        let status = resp.status();
+        let text = resp.text().await.map_err(che)?;
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(che(format!("{} {}", status.as_u16(), text.trim())))
+        }
+    }
+
+    /// Statement without result rows; `settings` are passed as URL parameters.
+    async fn execute(&self, sql: &str, settings: &[(&str, String)]) -> Result<(), ServiceError> {
+        Self::send(self.request(settings).body(sql.to_string())).await.map(|_| ())
+    }
+
+    /// `SELECT` with `{name:Type}` placeholders bound from `params`.
+    async fn query<T: DeserializeOwned>(&self, sql: &str, params: &[(&str, String)]) -> Result<Vec<T>, ServiceError> {
+        let mut url_params: Vec<(String, String)> = params.iter().map(|(k, v)| (format!("param_{}", k), v.clone())).collect();
+        url_params.push(("output_format_json_quote_64bit_integers".into(), "0".into()));
+        let body = Self::send(self.request(&[]).query(&url_params).body(format!("{} FORMAT JSONEachRow", sql))).await?;
+        body.lines().filter(|l| !l.trim().is_empty()).map(|l| serde_json::from_str(l).map_err(che)).collect()
+    }
+
+    /// Route ids of a tenant, from Postgres.
+    async fn tenant_routes(&self, tenant_id: Uuid) -> Result<Vec<Uuid>, ServiceError> {
+        route::Entity::find()
+            .select_only()
+            .column(route::Column::Id)
+            .filter(route::Column::TenantId.eq(tenant_id))
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))
+    }
+}
+
+/// ClickHouse text form of `Array(UUID)`.
+fn uuid_array(ids: &[Uuid]) -> String {
+    format!("[{}]", ids.iter().map(|id| format!("'{}'", id)).collect::<Vec<_>>().join(","))
+}
+
+#[async_trait]
+impl RequestLogRepository for ClickHouseRequestLogRepository {
+    fn backend(&self) -> &'static str {
+        "clickhouse"
+    }
+
+    async fn insert(&self, log: NewRequestLog) -> Result<request_log::Model, ServiceError> {
+        let now = Utc::now();
+        let row = Row {
+            id: self.ids.next(now),
+            route_id: log.route_id,
+            api_key_id: log.api_key_id,
+            status_code: log.status_code,
+            latency_ms: log.latency_ms,
+            success: log.success,
+            error_message: log.error_message.map(|m| common::redaction::global().redact_text(&m)),
+            client_ip: log.client_ip,
+            timestamp: now,
+            config_revision: log.config_revision,
+            country: log.country,
+            asn: log.asn,
+        };
+        let mut settings = vec![("query", format!("INSERT INTO {} FORMAT JSONEachRow", self.opts.table))];
+        if self.opts.async_insert {
+            settings.push(("async_insert", "1".into()));
+            settings.push(("wait_for_async_insert", if self.opts.wait_for_async_insert { "1" } else { "0" }.into()));
+        }
+        let body = serde_json::to_string(&row).map_err(che)?;
+        Self::send(self.request(&settings).body(body)).await?;
+        Ok(row.into())
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<request_log::Model>, ServiceError> {
+        let sql = format!("SELECT * FROM {} WHERE id = {{id:Int64}} LIMIT 1", self.opts.table);
+        let rows: Vec<Row> = self.query(&sql, &[("id", id.to_string())]).await?;
+        Ok(rows.into_iter().next().map(Into::into))
+    }
+
+    async fn list_recent(&self, filter: &RequestLogFilter, limit: u64) -> Result<Vec<request_log::Model>, ServiceError> {
+        let mut conditions = vec!["1".to_string()];
+        let mut params = vec![("limit", limit.to_string())];
+        if let Some(tenant_id) = filter.tenant_id {
+            let routes = self.tenant_routes(tenant_id).await?;
+            if routes.is_empty() {
+                return Ok(Vec::new());
+            }
+            conditions.push("has({routes:Array(UUID)}, route_id)".into());
+            params.push(("routes", uuid_array(&routes)));
+        }
+        if let Some(route_id) = filter.route_id {
+            conditions.push("route_id = {route:UUID}".into());
+            params.push(("route", route_id.to_string()));
+        }
+        let order = match filter.after_id {
+            Some(after) => {
+                conditions.push("id > {after:Int64}".into());
+                params.push(("after", after.to_string()));
+                "ASC"
+            }
+            None => "DESC",
+        };
+        let sql = format!("SELECT * FROM {} WHERE {} ORDER BY id {} LIMIT {{limit:UInt64}}", self.opts.table, conditions.join(" AND "), order);
+        let mut rows: Vec<request_log::Model> = self.query::<Row>(&sql, &params).await?.into_iter().map(Into::into).collect();
+        if filter.after_id.is_none() {
+            rows.reverse();
+        }
+        Ok(rows)
+    }
+
+    async fn key_usage(&self, q: &KeyUsageQuery) -> Result<KeyUsage, ServiceError> {
+        key_usage::check_query(&self.db, q).await?;
+        let table = &self.opts.table;
+        const WHERE: &str = "WHERE api_key_id = {key:UUID} AND timestamp >= {from:DateTime64(3, 'UTC')} AND timestamp < {to:DateTime64(3, 'UTC')}";
+        let params = [("key", q.api_key_id.to_string()), ("from", ch_datetime::param(&q.from)), ("to", ch_datetime::param(&q.to))];
+
+        let totals: Option<TotalsRow> = self
+            .query(
+                &format!(
+                    "SELECT count() AS requests, countIf(NOT success) AS errors, countIf(status_code = 429) AS rate_limited, \
+                            toString(maxOrNull(timestamp)) AS last_request_at, avgOrNull(latency_ms) AS avg_ms, \
+                            quantileOrNull(0.5)(latency_ms) AS p50_ms, quantileOrNull(0.95)(latency_ms) AS p95_ms, \
+                            quantileOrNull(0.99)(latency_ms) AS p99_ms, maxOrNull(latency_ms) AS max_ms \
+                     FROM {} {}",
+                    table, WHERE
+                ),
+                &params,
+            )
+            .await?
+            .into_iter()
+            .next();
+
+        let status_codes: Vec<StatusCount> = self
+            .query::<StatusRow>(
+                &format!("SELECT status_code, count() AS requests FROM {} {} GROUP BY status_code ORDER BY requests DESC, status_code", table, WHERE),
+                &params,
+            )
+            .await?
+            .into_iter()
+            .map(|r| StatusCount { status_code: r.status_code, requests: r.requests })
+            .collect();
+
+        let mut route_params = params.to_vec();
+        route_params.push(("top", q.top.clamp(1, MAX_TOP_ROUTES).to_string()));
+        let by_route: Vec<RouteRow> = self
+            .query(
+                &format!(
+                    "SELECT route_id, count() AS requests, countIf(NOT success) AS errors, quantileOrNull(0.95)(latency_ms) AS p95_ms \
+                     FROM {} {} GROUP BY route_id ORDER BY requests DESC LIMIT {{top:UInt32}}",
+                    table, WHERE
+                ),
+                &route_params,
+            )
+            .await?;
+        let ids: Vec<Uuid> = by_route.iter().map(|r| r.route_id).collect();
+        let routes: HashMap<Uuid, route::Model> =
+            route::Entity::find().filter(route::Column::Id.is_in(ids)).all(&self.db).await.map_err(|e| ServiceError::Db(e.to_string()))?.into_iter().map(|r| (r.id, r)).collect();
+        // 已删除的路由不再出现在结果中，与 Postgres 后端的 JOIN 一致
+        let top_routes = by_route
+            .into_iter()
+            .filter_map(|r| {
+                let route = routes.get(&r.route_id)?;
+                Some(KeyRouteUsage { route_id: r.route_id, method: route.method.clone(), path: route.path.clone(), requests: r.requests, errors: r.errors, p95_ms: r.p95_ms })
+            })
+            .collect();
+
+        let (totals, latency, last_request_at) = match totals {
+            Some(t) if t.requests > 0 => (
+                WindowStats::new(t.requests, t.errors, t.rate_limited),
+                LatencyStats { avg_ms: t.avg_ms, p50_ms: t.p50_ms, p95_ms: t.p95_ms, p99_ms: t.p99_ms, max_ms: t.max_ms },
+                t.last_request_at.and_then(|s| chrono::NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S%.3f").ok()).map(|t| t.and_utc()),
+            ),
+            _ => (WindowStats::default(), LatencyStats::default(), None),
+        };
+        Ok(KeyUsage { api_key_id: q.api_key_id, from: q.from, to: q.to, totals, latency, status_codes, top_routes, last_request_at })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_json_each_row() {
+        let at = DateTime::from_timestamp_millis(1_700_000_000_123).unwrap();
+        let row = Row {
+            id: 42,
+            route_id: Uuid::nil(),
+            api_key_id: None,
+            status_code: 502,
+            latency_ms: 17,
+            success: false,
+            error_message: Some("bad gateway".into()),
+            client_ip: Some("10.0.0.1".into()),
+            timestamp: at,
+            config_revision: Some(3),
+            country: None,
+            asn: None,
+        };
+        let line = serde_json::to_string(&row).unwrap();
+        assert!(line.contains(r#""timestamp":"2023-11-14 22:13:20.123""#), "{}", line);
+        assert_eq!(serde_json::from_str::<Row>(&line).unwrap(), row);
+        let model: request_log::Model = row.into();
+        assert_eq!((model.id, model.timestamp.timestamp_millis()), (42, 1_700_000_000_123));
+    }
+
+    #[test]
+    fn ids_increase_with_time() {
+        let ids = IdGenerator { counter: AtomicU64::new(0xFFFE) };
+        let at = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let (a, b) = (ids.next(at), ids.next(at));
+        assert_eq!(a >> 16, at.timestamp_millis());
+        assert_ne!(a, b);
+        // 计数器回绕后，下一毫秒的 id 仍然更大
+        assert!(ids.next(at + chrono::Duration::milliseconds(1)) > a.max(b));
+        assert_eq!(uuid_array(&[Uuid::nil()]), "['00000000-0000-0000-0000-000000000000']");
+    }
+}
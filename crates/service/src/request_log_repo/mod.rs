@@ -0,0 +1,86 @@
+//! Request log storage behind one interface, selected by config.toml `[request_log] backend`.
+//! - `postgres` (default): the `request_log` table, see [`crate::db::request_log_service`].
+//! - `clickhouse`: a MergeTree table written with async inserts and expired by TTL, for traffic
+//!   Postgres cannot hold; see [`clickhouse`].
+//! Routes, API keys and captured payloads stay in Postgres with either backend.
+
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use models::request_log;
+
+use crate::db::request_log_service::{self, RequestLogFilter};
+use crate::errors::ServiceError;
+use crate::key_usage::{KeyUsage, KeyUsageQuery};
+
+pub mod clickhouse;
+
+/// Fields of a request log entry before it is stored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewRequestLog {
+    pub route_id: Uuid,
+    pub api_key_id: Option<Uuid>,
+    pub status_code: i32,
+    pub latency_ms: i32,
+    pub success: bool,
+    /// Redacted by the repository before it is stored
+    pub error_message: Option<String>,
+    pub client_ip: Option<String>,
+    pub config_revision: Option<i64>,
+    pub country: Option<String>,
+    pub asn: Option<i64>,
+}
+
+#[async_trait]
+pub trait RequestLogRepository: Send + Sync {
+    /// `postgres` / `clickhouse`
+    fn backend(&self) -> &'static str;
+    async fn insert(&self, log: NewRequestLog) -> Result<request_log::Model, ServiceError>;
+    async fn get(&self, id: i64) -> Result<Option<request_log::Model>, ServiceError>;
+    /// Up to `limit` entries in ascending id order, see [`RequestLogFilter`]
+    async fn list_recent(&self, filter: &RequestLogFilter, limit: u64) -> Result<Vec<request_log::Model>, ServiceError>;
+    /// `GET /admin/api-keys/{id}/usage`
+    async fn key_usage(&self, q: &KeyUsageQuery) -> Result<KeyUsage, ServiceError>;
+}
+
+/// SeaORM-backed repository implementation.
+pub struct SeaOrmRequestLogRepository {
+    pub db: DatabaseConnection,
+}
+
+#[async_trait]
+impl RequestLogRepository for SeaOrmRequestLogRepository {
+    fn backend(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn insert(&self, log: NewRequestLog) -> Result<request_log::Model, ServiceError> {
+        request_log_service::create_request_log(
+            &self.db,
+            log.route_id,
+            log.api_key_id,
+            log.status_code,
+            log.latency_ms,
+            log.success,
+            log.error_message,
+            log.client_ip,
+            log.config_revision,
+            log.country,
+            log.asn,
+        )
+        .await
+    }
+
+    async fn get(&self, id: i64) -> Result<Option<request_log::Model>, ServiceError> {
+        request_log_service::get_request_log(&self.db, id).await
+    }
+
+    async fn list_recent(&self, filter: &RequestLogFilter, limit: u64) -> Result<Vec<request_log::Model>, ServiceError> {
+        request_log_service::list_recent(&self.db, filter, limit).await
+    }
+
+    async fn key_usage(&self, q: &KeyUsageQuery) -> Result<KeyUsage, ServiceError> {
+        crate::key_usage::usage(&self.db, q).await
+    }
+}
@@ -0,0 +1,134 @@
+//! Live request log feed for `GET /admin/request-logs/stream`.
+//! - The data plane publishes one entry per completed `/api/*` request, before any persistence.
+//! - Subscribers filter entries themselves; a slow subscriber skips entries (reported as lagged)
+//!   rather than slowing down request handling.
+//! - Publishing with no subscribers is a cheap no-op.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// One completed request as seen by the data plane.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveRequestLog {
+    pub timestamp: DateTime<Utc>,
+    pub tenant_id: Uuid,
+    pub proxy_api_id: Uuid,
+    pub method: String,
+    /// Request path with sensitive query parameters redacted.
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub client_ip: Option<String>,
+    /// How the request was handled: forwarded / grpc / mock / blocked / error.
+    pub outcome: String,
+    pub error: Option<String>,
+    pub config_revision: i64,
+}
+
+/// Subscriber-side filter; `None` fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LiveLogFilter {
+    pub tenant_id: Option<Uuid>,
+    pub proxy_api_id: Option<Uuid>,
+    /// Only entries whose path starts with this prefix.
+    pub path_prefix: Option<String>,
+    /// Only entries with status >= this value (e.g. 400 for errors).
+    pub min_status: Option<u16>,
+}
+
+impl LiveLogFilter {
+    pub fn matches(&self, entry: &LiveRequestLog) -> bool {
+        self.tenant_id.is_none_or(|t| t == entry.tenant_id)
+            && self.proxy_api_id.is_none_or(|a| a == entry.proxy_api_id)
+            && self.path_prefix.as_deref().is_none_or(|p| entry.path.starts_with(p))
+            && self.min_status.is_none_or(|s| entry.status >= s)
+    }
+}
+
+/// Cloneable handle over a broadcast channel of live entries.
+#[derive(Clone)]
+pub struct RequestLogStream {
+    tx: broadcast::Sender<Arc<LiveRequestLog>>,
+}
+
+impl RequestLogStream {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self { tx }
+    }
+
+    pub fn publish(&self, entry: LiveRequestLog) {
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(Arc::new(entry));
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveRequestLog>> {
+        self.tx.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for RequestLogStream {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tenant_id: Uuid, path: &str, status: u16) -> LiveRequestLog {
+        LiveRequestLog {
+            timestamp: Utc::now(),
+            tenant_id,
+            proxy_api_id: Uuid::new_v4(),
+            method: "GET".into(),
+            path: path.into(),
+            status,
+            latency_ms: 3,
+            client_ip: None,
+            outcome: "forwarded".into(),
+            error: None,
+            config_revision: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_entries_and_filter_them() {
+        let stream = RequestLogStream::new(8);
+        let tenant = Uuid::new_v4();
+        // no subscribers: dropped silently
+        stream.publish(entry(tenant, "/api/early", 200));
+
+        let mut rx = stream.subscribe();
+        stream.publish(entry(tenant, "/api/orders/1", 502));
+        let got = rx.recv().await.unwrap();
+        assert_eq!(got.path, "/api/orders/1");
+
+        let filter = LiveLogFilter { tenant_id: Some(tenant), path_prefix: Some("/api/orders".into()), min_status: Some(500), ..Default::default() };
+        assert!(filter.matches(&got));
+        assert!(!filter.matches(&entry(tenant, "/api/orders/1", 200)));
+        assert!(!filter.matches(&entry(tenant, "/api/users", 503)));
+        assert!(!filter.matches(&entry(Uuid::new_v4(), "/api/orders/1", 502)));
+    }
+
+    #[tokio::test]
+    async fn slow_subscribers_lag_instead_of_blocking() {
+        let stream = RequestLogStream::new(2);
+        let mut rx = stream.subscribe();
+        for i in 0..5 {
+            stream.publish(entry(Uuid::nil(), &format!("/api/{}", i), 200));
+        }
+        assert!(matches!(rx.recv().await, Err(broadcast::error::RecvError::Lagged(3))));
+        assert_eq!(rx.recv().await.unwrap().path, "/api/3");
+    }
+}
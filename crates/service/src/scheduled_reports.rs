@@ -0,0 +1,466 @@
+//! Scheduled traffic reports per tenant.
+//! - Daily (previous UTC day) and weekly (previous Monday-to-Monday UTC week) summaries: totals, top
+//!   endpoints and rate limit usage, computed from `metrics_rollup`.
+//! - Rendered to JSON and HTML and delivered as the `report.generated` webhook event to tenants
+//!   subscribed to it; tenants without a subscription are skipped.
+//! - `scheduled_report_run` records each (tenant, period, start) before delivery, so a report is sent
+//!   once across restarts and replicas. Periods missed while the scheduler was down are not backfilled.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, DurationRound, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter, Set, Statement};
+use serde::Serialize;
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::lock::{run_exclusive, DistributedLock};
+use common::time::{system_clock, SharedClock};
+use models::{ratelimit, route, scheduled_report_run, tenant};
+
+use crate::errors::ServiceError;
+use crate::webhooks::{WebhookEvent, WebhookService};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => scheduled_report_run::PERIOD_DAILY,
+            ReportPeriod::Weekly => scheduled_report_run::PERIOD_WEEKLY,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [ReportPeriod::Daily, ReportPeriod::Weekly].into_iter().find(|p| p.as_str() == s)
+    }
+
+    /// `[from, to)` of the last period completed at `now`.
+    pub fn last_completed(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let today = now.duration_trunc(chrono::Duration::days(1)).unwrap_or(now);
+        match self {
+            ReportPeriod::Daily => (today - chrono::Duration::days(1), today),
+            ReportPeriod::Weekly => {
+                let monday = today - chrono::Duration::days(now.weekday().num_days_from_monday() as i64);
+                (monday - chrono::Duration::weeks(1), monday)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduleOptions {
+    pub interval: Duration,
+    pub daily: bool,
+    pub weekly: bool,
+    /// UTC hour after which the previous period is reported
+    pub send_hour: u32,
+    pub top_endpoints: u32,
+}
+
+impl Default for ScheduleOptions {
+    fn default() -> Self {
+        Self { interval: Duration::from_secs(300), daily: true, weekly: true, send_hour: 1, top_endpoints: 10 }
+    }
+}
+
+impl ScheduleOptions {
+    /// Enabled periods whose report is due at `now`, with their bounds.
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<(ReportPeriod, DateTime<Utc>, DateTime<Utc>)> {
+        let enabled = [(ReportPeriod::Daily, self.daily), (ReportPeriod::Weekly, self.weekly)];
+        enabled
+            .into_iter()
+            .filter(|(_, on)| *on)
+            .map(|(p, _)| {
+                let (from, to) = p.last_completed(now);
+                (p, from, to)
+            })
+            .filter(|(_, _, to)| now >= *to + chrono::Duration::hours(self.send_hour as i64))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportTotals {
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_avg_ms: f64,
+    /// Highest minute p99 in the period (an upper bound)
+    pub latency_p99_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EndpointSummary {
+    pub route_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    pub latency_p99_ms: f64,
+}
+
+/// Peak per-minute traffic of a rate limited route against its limit.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaUsage {
+    pub route_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub requests_per_minute: i32,
+    pub peak_requests_per_minute: i64,
+    pub peak_utilization: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TenantReport {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    pub period: ReportPeriod,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub totals: ReportTotals,
+    pub top_endpoints: Vec<EndpointSummary>,
+    /// Highest utilization first
+    pub quota: Vec<QuotaUsage>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct RouteRow {
+    route_id: Uuid,
+    requests: i64,
+    errors: i64,
+    latency_sum_ms: i64,
+    latency_p99_ms: Option<f64>,
+    peak_requests: i64,
+}
+
+fn dbe(e: sea_orm::DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+fn ratio(part: i64, whole: i64) -> f64 {
+    if whole > 0 { part as f64 / whole as f64 } else { 0.0 }
+}
+
+/// Summary of one tenant's traffic in `[from, to)`.
+pub async fn generate(db: &DatabaseConnection, tenant_id: Uuid, period: ReportPeriod, from: DateTime<Utc>, to: DateTime<Utc>, top: u32) -> Result<TenantReport, ServiceError> {
+    let tenant = tenant::Entity::find_by_id(tenant_id).one(db).await.map_err(dbe)?.ok_or_else(|| ServiceError::not_found("tenant"))?;
+    let rows = RouteRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT route_id, sum(requests)::bigint AS requests, sum(errors)::bigint AS errors, sum(latency_sum_ms)::bigint AS latency_sum_ms, \
+                max(latency_p99_ms) AS latency_p99_ms, max(requests) AS peak_requests \
+         FROM metrics_rollup WHERE tenant_id = $1 AND bucket >= $2 AND bucket < $3 \
+         GROUP BY route_id ORDER BY requests DESC, route_id",
+        vec![tenant_id.into(), from.fixed_offset().into(), to.fixed_offset().into()],
+    ))
+    .all(db)
+    .await
+    .map_err(dbe)?;
+
+    let routes: HashMap<Uuid, route::Model> =
+        route::Entity::find().filter(route::Column::TenantId.eq(tenant_id)).all(db).await.map_err(dbe)?.into_iter().map(|r| (r.id, r)).collect();
+    let limit_ids: Vec<Uuid> = routes.values().filter_map(|r| r.rate_limit_id).collect();
+    let limits: HashMap<Uuid, i32> =
+        ratelimit::Entity::find().filter(ratelimit::Column::Id.is_in(limit_ids)).all(db).await.map_err(dbe)?.into_iter().map(|l| (l.id, l.requests_per_minute)).collect();
+
+    let requests: i64 = rows.iter().map(|r| r.requests).sum();
+    let errors: i64 = rows.iter().map(|r| r.errors).sum();
+    let totals = ReportTotals {
+        requests,
+        errors,
+        error_rate: ratio(errors, requests),
+        latency_avg_ms: ratio(rows.iter().map(|r| r.latency_sum_ms).sum(), requests),
+        latency_p99_ms: rows.iter().filter_map(|r| r.latency_p99_ms).fold(0.0, f64::max),
+    };
+    // 已删除的路由没有 method/path，仍计入合计
+    let label = |id: &Uuid| routes.get(id).map(|r| (r.method.clone(), r.path.clone())).unwrap_or_else(|| ("-".into(), "(deleted route)".into()));
+    let top_endpoints = rows
+        .iter()
+        .take(top as usize)
+        .map(|r| {
+            let (method, path) = label(&r.route_id);
+            EndpointSummary {
+                route_id: r.route_id,
+                method,
+                path,
+                requests: r.requests,
+                errors: r.errors,
+                error_rate: ratio(r.errors, r.requests),
+                latency_p99_ms: r.latency_p99_ms.unwrap_or(0.0),
+            }
+        })
+        .collect();
+    let mut quota: Vec<QuotaUsage> = rows
+        .iter()
+        .filter_map(|r| {
+            let limit = *limits.get(&routes.get(&r.route_id)?.rate_limit_id?)?;
+            let (method, path) = label(&r.route_id);
+            Some(QuotaUsage {
+                route_id: r.route_id,
+                method,
+                path,
+                requests_per_minute: limit,
+                peak_requests_per_minute: r.peak_requests,
+                peak_utilization: ratio(r.peak_requests, limit as i64),
+            })
+        })
+        .collect();
+    quota.sort_by(|a, b| b.peak_utilization.total_cmp(&a.peak_utilization));
+
+    Ok(TenantReport { tenant_id, tenant_name: tenant.name, period, from, to, totals, top_endpoints, quota })
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn percent(v: f64) -> String {
+    format!("{:.2}%", v * 100.0)
+}
+
+/// Self-contained HTML rendering for email clients and browsers.
+pub fn render_html(r: &TenantReport) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{period} report: {name}</title></head>\n<body style=\"font-family:sans-serif\">\n\
+         <h1>{period} traffic report: {name}</h1>\n<p>{from} &ndash; {to} (UTC)</p>\n\
+         <table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n\
+         <tr><th>Requests</th><th>Errors</th><th>Error rate</th><th>Avg latency</th><th>p99 latency</th></tr>\n\
+         <tr><td>{requests}</td><td>{errors}</td><td>{error_rate}</td><td>{avg:.1} ms</td><td>{p99:.1} ms</td></tr>\n</table>\n",
+        period = match r.period {
+            ReportPeriod::Daily => "Daily",
+            ReportPeriod::Weekly => "Weekly",
+        },
+        name = escape(&r.tenant_name),
+        from = r.from.format("%Y-%m-%d %H:%M"),
+        to = r.to.format("%Y-%m-%d %H:%M"),
+        requests = r.totals.requests,
+        errors = r.totals.errors,
+        error_rate = percent(r.totals.error_rate),
+        avg = r.totals.latency_avg_ms,
+        p99 = r.totals.latency_p99_ms,
+    );
+    html.push_str("<h2>Top endpoints</h2>\n");
+    if r.top_endpoints.is_empty() {
+        html.push_str("<p>No traffic in this period.</p>\n");
+    } else {
+        html.push_str("<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n<tr><th>Endpoint</th><th>Requests</th><th>Errors</th><th>Error rate</th><th>p99 latency</th></tr>\n");
+        for e in &r.top_endpoints {
+            html.push_str(&format!(
+                "<tr><td>{} {}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1} ms</td></tr>\n",
+                escape(&e.method),
+                escape(&e.path),
+                e.requests,
+                e.errors,
+                percent(e.error_rate),
+                e.latency_p99_ms
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    if !r.quota.is_empty() {
+        html.push_str("<h2>Rate limit usage</h2>\n<table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\n<tr><th>Endpoint</th><th>Limit (req/min)</th><th>Peak (req/min)</th><th>Peak utilization</th></tr>\n");
+        for q in &r.quota {
+            html.push_str(&format!(
+                "<tr><td>{} {}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&q.method),
+                escape(&q.path),
+                q.requests_per_minute,
+                q.peak_requests_per_minute,
+                percent(q.peak_utilization)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+pub struct ReportScheduler {
+    db: DatabaseConnection,
+    webhooks: Arc<WebhookService>,
+    opts: ScheduleOptions,
+    lock: Option<Arc<dyn DistributedLock>>,
+    clock: SharedClock,
+}
+
+impl ReportScheduler {
+    pub fn new(db: DatabaseConnection, webhooks: Arc<WebhookService>, opts: ScheduleOptions) -> Self {
+        Self { db, webhooks, opts, lock: None, clock: system_clock() }
+    }
+
+    /// Run the scheduled check on one replica at a time.
+    pub fn with_lock(mut self, lock: Arc<dyn DistributedLock>) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record the run; `false` when the report was already generated.
+    async fn claim(&self, tenant_id: Uuid, period: ReportPeriod, from: DateTime<Utc>, now: DateTime<Utc>) -> Result<bool, ServiceError> {
+        let am = scheduled_report_run::ActiveModel {
+            tenant_id: Set(tenant_id),
+            period: Set(period.as_str().to_string()),
+            period_start: Set(from.fixed_offset()),
+            created_at: Set(now.fixed_offset()),
+        };
+        let inserted = scheduled_report_run::Entity::insert(am)
+            .on_conflict(
+                OnConflict::columns([scheduled_report_run::Column::TenantId, scheduled_report_run::Column::Period, scheduled_report_run::Column::PeriodStart])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await
+            .map_err(dbe)?;
+        Ok(inserted > 0)
+    }
+
+    async fn release(&self, tenant_id: Uuid, period: ReportPeriod, from: DateTime<Utc>) {
+        let res = scheduled_report_run::Entity::delete_many()
+            .filter(scheduled_report_run::Column::TenantId.eq(tenant_id))
+            .filter(scheduled_report_run::Column::Period.eq(period.as_str()))
+            .filter(scheduled_report_run::Column::PeriodStart.eq(from.fixed_offset()))
+            .exec(&self.db)
+            .await;
+        if let Err(e) = res {
+            warn!(tenant_id = %tenant_id, period = period.as_str(), error = %e, "scheduled_report_release_failed");
+        }
+    }
+
+    async fn send(&self, tenant_id: Uuid, period: ReportPeriod, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<usize, ServiceError> {
+        let report = generate(&self.db, tenant_id, period, from, to, self.opts.top_endpoints).await?;
+        let data = json!({
+            "period": period,
+            "from": from,
+            "to": to,
+            "html": render_html(&report),
+            "report": report,
+        });
+        self.webhooks.emit(tenant_id, WebhookEvent::ReportGenerated, data).await
+    }
+
+    /// Generate and queue every due report not sent before; returns the number of reports sent.
+    pub async fn run_once(&self, now: DateTime<Utc>) -> Result<usize, ServiceError> {
+        let due = self.opts.due(now);
+        if due.is_empty() {
+            return Ok(0);
+        }
+        let tenants = self.webhooks.subscribed_tenants(WebhookEvent::ReportGenerated).await?;
+        let mut sent = 0;
+        for (period, from, to) in due {
+            for &tenant_id in &tenants {
+                if !self.claim(tenant_id, period, from, now).await? {
+                    continue;
+                }
+                match self.send(tenant_id, period, from, to).await {
+                    Ok(_) => sent += 1,
+                    Err(e) => {
+                        warn!(tenant_id = %tenant_id, period = period.as_str(), error = %e, "scheduled_report_failed");
+                        self.release(tenant_id, period, from).await;
+                    }
+                }
+            }
+        }
+        if sent > 0 {
+            info!(sent, "scheduled_reports_queued");
+        }
+        Ok(sent)
+    }
+
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.opts.interval);
+            loop {
+                ticker.tick().await;
+                if let Some(Err(e)) = run_exclusive(this.lock.as_deref(), "scheduled_reports", this.opts.interval, this.run_once(this.clock.now())).await {
+                    warn!(error = %e, "scheduled_reports_failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn periods_and_due_reports() {
+        // 2026-10-14 is a Wednesday
+        let now = at("2026-10-14T05:30:00Z");
+        assert_eq!(ReportPeriod::Daily.last_completed(now), (at("2026-10-13T00:00:00Z"), at("2026-10-14T00:00:00Z")));
+        assert_eq!(ReportPeriod::Weekly.last_completed(now), (at("2026-10-05T00:00:00Z"), at("2026-10-12T00:00:00Z")));
+        assert_eq!(ReportPeriod::Weekly.last_completed(at("2026-10-12T00:00:00Z")).1, at("2026-10-12T00:00:00Z"));
+
+        let opts = ScheduleOptions { send_hour: 6, ..Default::default() };
+        let due: Vec<ReportPeriod> = opts.due(now).into_iter().map(|(p, _, _)| p).collect();
+        assert_eq!(due, vec![ReportPeriod::Weekly]);
+        let due: Vec<ReportPeriod> = opts.due(at("2026-10-14T06:00:00Z")).into_iter().map(|(p, _, _)| p).collect();
+        assert_eq!(due, vec![ReportPeriod::Daily, ReportPeriod::Weekly]);
+        // 周一发送时刻之前，上周报表尚未到期
+        let monday: Vec<ReportPeriod> = opts.due(at("2026-10-12T03:00:00Z")).into_iter().map(|(p, _, _)| p).collect();
+        assert!(monday.is_empty());
+        let daily_only = ScheduleOptions { weekly: false, send_hour: 0, ..Default::default() };
+        assert_eq!(daily_only.due(now).len(), 1);
+        assert_eq!(ReportPeriod::parse("weekly"), Some(ReportPeriod::Weekly));
+        assert_eq!(ReportPeriod::parse("monthly"), None);
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let report = TenantReport {
+            tenant_id: Uuid::nil(),
+            tenant_name: "<acme & co>".into(),
+            period: ReportPeriod::Daily,
+            from: at("2026-10-13T00:00:00Z"),
+            to: at("2026-10-14T00:00:00Z"),
+            totals: ReportTotals { requests: 200, errors: 3, error_rate: 0.015, latency_avg_ms: 12.5, latency_p99_ms: 80.0 },
+            top_endpoints: vec![EndpointSummary {
+                route_id: Uuid::nil(),
+                method: "GET".into(),
+                path: "/orders/<id>".into(),
+                requests: 200,
+                errors: 3,
+                error_rate: 0.015,
+                latency_p99_ms: 80.0,
+            }],
+            quota: Vec::new(),
+        };
+        let html = render_html(&report);
+        assert!(html.contains("Daily traffic report: &lt;acme &amp; co&gt;"));
+        assert!(html.contains("GET /orders/&lt;id&gt;"));
+        assert!(html.contains("<td>1.50%</td>"));
+        assert!(!html.contains("Rate limit usage"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Development seed data: one call creates a demo tenant, users with known passwords,
+//! upstreams pointing at the local echo server, routes, API keys and synthetic request logs.
+//! Dev-mode only — never wire this into a production startup path.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde::Serialize;
+use tracing::info;
+use uuid::Uuid;
+
+use models::{request_log, tenant};
+
+use crate::auth::{domain::RegisterInput, repo::seaorm::SeaOrmAuthRepository, service::{AuthConfig, AuthService}};
+use crate::db::{apikey_service::hash_api_key, ratelimit_service, route_service, upstream_service};
+use crate::errors::ServiceError;
+
+/// 所有演示用户共用的密码
+pub const DEMO_PASSWORD: &str = "demo-password-123";
+/// 默认指向内置 echo 上游
+pub const DEFAULT_ECHO_BASE_URL: &str = "http://127.0.0.1:9099";
+
+#[derive(Debug, Clone)]
+pub struct SeedOptions {
+    pub tenant_name: String,
+    pub echo_base_url: String,
+    pub users: usize,
+    pub request_logs: usize,
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        Self {
+            tenant_name: "demo".into(),
+            echo_base_url: DEFAULT_ECHO_BASE_URL.into(),
+            users: 2,
+            request_logs: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeededUser {
+    pub id: Uuid,
+    pub email: String,
+    pub password: String,
+    /// 明文 key，仅在此处返回一次（数据库中只存哈希）
+    pub api_key: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SeedReport {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    pub users: Vec<SeededUser>,
+    pub upstream_ids: Vec<Uuid>,
+    pub route_ids: Vec<Uuid>,
+    pub rate_limit_id: Uuid,
+    /// 控制面 `/api/*` 演示路由
+    pub proxy_api_ids: Vec<Uuid>,
+    pub request_logs: usize,
+}
+
+/// 演示路由：(method, path, upstream 序号)
+const DEMO_ROUTES: &[(&str, &str, usize)] = &[
+    ("GET", "/demo/echo", 0),
+    ("POST", "/demo/echo", 0),
+    ("GET", "/demo/slow", 1),
+];
+
+/// Create the demo data set. Fails with a validation error if the tenant already exists,
+/// so running the seed twice never duplicates data.
+pub async fn seed_demo(db: &DatabaseConnection, opts: &SeedOptions) -> Result<SeedReport, ServiceError> {
+    let exists = tenant::Entity::find()
+        .filter(tenant::Column::Name.eq(opts.tenant_name.clone()))
+        .one(db)
+        .await
+        .map_err(|e| ServiceError::Db(e.to_string()))?;
+    if exists.is_some() {
+        return Err(ServiceError::Validation(format!("tenant '{}' already exists; seed data is created only once", opts.tenant_name)));
+    }
+
+    let t = tenant::create(db, &opts.tenant_name).await?;
+
+    let auth = AuthService::new(
+        Arc::new(SeaOrmAuthRepository { db: db.clone() }),
+        AuthConfig { jwt_secret: None, password_algorithm: "argon2".into() },
+    );
+    let mut users = Vec::with_capacity(opts.users);
+    for i in 0..opts.users {
+        let (email, name) = if i == 0 {
+            (format!("admin@{}.local", opts.tenant_name), "Demo Admin".to_string())
+        } else {
+            (format!("dev{}@{}.local", i, opts.tenant_name), format!("Demo Developer {}", i))
+        };
+        let u = auth
+            .register(RegisterInput { tenant_id: t.id, email, name, password: DEMO_PASSWORD.into() })
+            .await
+            .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        // 首个用户可管理邀请与成员
+        if i == 0 {
+            models::user::set_role(db, u.id, models::user::ROLE_ADMIN).await?;
+        }
+        let api_key = generate_api_key();
+        models::apikey::create(db, u.id, &hash_api_key(&api_key)?).await?;
+        users.push(SeededUser { id: u.id, email: u.email, password: DEMO_PASSWORD.into(), api_key });
+    }
+
+    let base = opts.echo_base_url.trim_end_matches('/');
+    let echo = upstream_service::create_upstream(db, &format!("{}-echo", opts.tenant_name), base).await?;
+    let slow = upstream_service::create_upstream(db, &format!("{}-echo-slow", opts.tenant_name), &format!("{}/?latency_ms=250", base)).await?;
+    let upstreams = [echo.id, slow.id];
+
+    let rl = ratelimit_service::create_rate_limit(db, Some(t.id), 600, 50).await?;
+    let mut route_ids = Vec::with_capacity(DEMO_ROUTES.len());
+    for (method, path, upstream) in DEMO_ROUTES {
+        let r = route_service::create_route(db, t.id, method, path, upstreams[*upstream], 5000, 2, 5, Some(rl.id)).await?;
+        route_ids.push(r.id);
+    }
+    let posts = models::proxy_api::create(db, t.id, "/api/posts", "GET", &format!("{}/posts", base), true).await?;
+
+    let now: DateTime<FixedOffset> = Utc::now().into();
+    let logs: Vec<request_log::ActiveModel> = (0..opts.request_logs)
+        .map(|i| {
+            let (status, latency_ms, error) = synthetic_outcome(i);
+            request_log::ActiveModel {
+                route_id: Set(route_ids[i % route_ids.len()]),
+                api_key_id: Set(None),
+                status_code: Set(status),
+                latency_ms: Set(latency_ms),
+                success: Set(status < 400),
+                error_message: Set(error.map(str::to_string)),
+                client_ip: Set(Some(format!("10.0.0.{}", i % 254 + 1))),
+                // 均匀分布在过去 24 小时内
+                timestamp: Set(now - Duration::seconds((i as i64 * 86_400) / opts.request_logs.max(1) as i64)),
+                ..Default::default()
+            }
+        })
+        .collect();
+    for chunk in logs.chunks(500) {
+        request_log::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await
+            .map_err(|e| ServiceError::Db(e.to_string()))?;
+    }
+
+    info!(tenant_id = %t.id, users = users.len(), routes = route_ids.len(), request_logs = logs.len(), "seeded demo data");
+    Ok(SeedReport {
+        tenant_id: t.id,
+        tenant_name: t.name,
+        users,
+        upstream_ids: upstreams.to_vec(),
+        route_ids,
+        rate_limit_id: rl.id,
+        proxy_api_ids: vec![posts.id],
+        request_logs: logs.len(),
+    })
+}
+
+fn generate_api_key() -> String {
+    let suffix: String = rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+    format!("demo_{}", suffix)
+}
+
+/// 确定性的合成结果：约 90% 成功，其余为 429 / 502 / 504
+fn synthetic_outcome(i: usize) -> (i32, i32, Option<&'static str>) {
+    let latency = 20 + ((i * 37) % 180) as i32;
+    match i % 20 {
+        7 => (429, 2, Some("rate limited")),
+        13 => (502, latency * 3, Some("upstream connection refused")),
+        19 => (504, 5000, Some("upstream timeout")),
+        _ => (200, latency, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::get_db;
+
+    #[test]
+    fn synthetic_outcomes_are_mostly_successful() {
+        let outcomes: Vec<_> = (0..200).map(synthetic_outcome).collect();
+        let ok = outcomes.iter().filter(|(s, _, _)| *s == 200).count();
+        assert_eq!(ok, 170);
+        assert!(outcomes.iter().all(|(s, _, e)| (*s == 200) == e.is_none()));
+        assert!(generate_api_key().starts_with("demo_"));
+    }
+
+    #[tokio::test]
+    async fn seed_creates_demo_data_once() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+
+        let opts = SeedOptions { tenant_name: format!("seed_{}", Uuid::new_v4().simple()), request_logs: 10, ..SeedOptions::default() };
+        let report = seed_demo(&db, &opts).await?;
+        assert_eq!(report.users.len(), 2);
+        assert_eq!(report.route_ids.len(), DEMO_ROUTES.len());
+        assert_eq!(report.request_logs, 10);
+        assert!(seed_demo(&db, &opts).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+//! Self-service tenant signup: one transaction creates the tenant, its first admin user,
+//! a default rate limit and a starter API key, so a failed signup leaves nothing behind.
+//! `/auth/register` and proxy API creation still create `auto-tenant-{id}` tenants for unknown ids.
+
+use common::time::Clock;
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, DbErr, Set, SqlErr, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use models::{apikey, ratelimit, tenant, user, user_credentials};
+
+use crate::auth::service::{hash_password, validate_password};
+use crate::db::apikey_service::hash_api_key;
+use crate::errors::ServiceError;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignupInput {
+    pub tenant_name: String,
+    pub email: String,
+    pub name: String,
+    pub password: String,
+}
+
+/// Rate limit given to new tenants (`[signup]` in config.toml)
+#[derive(Debug, Clone, Copy)]
+pub struct SignupDefaults {
+    pub requests_per_minute: i32,
+    pub burst: i32,
+}
+
+/// Everything the new tenant needs to get started; `api_key` is only ever returned here.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignupReport {
+    pub tenant_id: Uuid,
+    pub tenant_name: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub rate_limit_id: Uuid,
+    pub requests_per_minute: i32,
+    pub burst: i32,
+    pub api_key_id: Uuid,
+    pub api_key: String,
+}
+
+/// Create a tenant with its first admin. Tenant names and emails are unique columns; a taken name
+/// or an email already registered (in any tenant) hits the constraint inside the transaction,
+/// which rolls back and fails with [`ServiceError::Conflict`].
+pub async fn signup(db: &DatabaseConnection, input: &SignupInput, defaults: SignupDefaults, clock: &dyn Clock) -> Result<SignupReport, ServiceError> {
+    let tenant_name = input.tenant_name.trim();
+    tenant::validate_name(tenant_name)?;
+    user::validate_email(&input.email)?;
+    user::validate_name(&input.name)?;
+    validate_password(&input.password).map_err(|e| ServiceError::Validation(e.to_string()))?;
+
+    let dbe = |e: DbErr| ServiceError::Db(e.to_string());
+    // argon2 先于事务计算，避免持有事务期间做 CPU 密集的哈希
+    let password_hash = hash_password(&input.password).map_err(|e| ServiceError::Validation(e.to_string()))?;
+    let api_key = generate_api_key();
+    let key_hash = hash_api_key(&api_key)?;
+
+    let now = clock.now_fixed();
+    let txn = db.begin().await.map_err(dbe)?;
+    let t = tenant::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        name: Set(tenant_name.to_string()),
+        created_at: Set(now),
+        suspended: Set(false),
+        suspended_reason: Set(None),
+        email_verification: Set(tenant::EMAIL_VERIFICATION_OFF.into()),
+        invite_only: Set(false),
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| unique_conflict(e, format!("tenant '{}' already exists", tenant_name)))?;
+    let u = user::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        tenant_id: Set(t.id),
+        email: Set(input.email.clone()),
+        name: Set(input.name.clone()),
+        status: Set(user::STATUS_ACTIVE.into()),
+        role: Set(user::ROLE_ADMIN.into()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        deleted_at: Set(None),
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| unique_conflict(e, "email already registered".into()))?;
+    user_credentials::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(u.id),
+        password_hash: Set(password_hash),
+        password_algorithm: Set("argon2".into()),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(&txn)
+    .await
+    .map_err(dbe)?;
+    let rl = ratelimit::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        tenant_id: Set(Some(t.id)),
+        requests_per_minute: Set(defaults.requests_per_minute),
+        burst: Set(defaults.burst),
+        created_at: Set(now),
+        spike_arrest_interval_ms: Set(None),
+        algorithm: Set(common::rate_limit::Algorithm::TokenBucket.as_str().into()),
+    }
+    .insert(&txn)
+    .await
+    .map_err(dbe)?;
+    let key = apikey::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(u.id),
+        key_hash: Set(key_hash),
+        status: Set("active".into()),
+        created_at: Set(now),
+        last_used_at: Set(None),
+        expires_at: Set(None),
+    }
+    .insert(&txn)
+    .await
+    .map_err(dbe)?;
+    txn.commit().await.map_err(dbe)?;
+
+    info!(tenant_id = %t.id, user_id = %u.id, "tenant_signed_up");
+    Ok(SignupReport {
+        tenant_id: t.id,
+        tenant_name: t.name,
+        user_id: u.id,
+        email: u.email,
+        role: u.role,
+        rate_limit_id: rl.id,
+        requests_per_minute: rl.requests_per_minute,
+        burst: rl.burst,
+        api_key_id: key.id,
+        api_key,
+    })
+}
+
+/// Unique violations become conflicts; the dropped transaction rolls back.
+fn unique_conflict(e: DbErr, what: String) -> ServiceError {
+    match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => ServiceError::Conflict(what),
+        _ => ServiceError::Db(e.to_string()),
+    }
+}
+
+fn generate_api_key() -> String {
+    let suffix: String = rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+    format!("ak_{}", suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::get_db;
+    use common::time::{MockClock, SystemClock};
+    use chrono::Timelike;
+    use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+
+    fn input(tenant_name: &str, email: &str) -> SignupInput {
+        SignupInput { tenant_name: tenant_name.into(), email: email.into(), name: "Owner".into(), password: "Passw0rd".into() }
+    }
+
+    #[tokio::test]
+    async fn signup_bootstraps_tenant_in_one_transaction() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let defaults = SignupDefaults { requests_per_minute: 120, burst: 20 };
+        let name = format!("signup-{}", Uuid::new_v4().simple());
+        let email = format!("{}@example.com", Uuid::new_v4().simple());
+
+        let now = chrono::Utc::now().with_nanosecond(0).unwrap();
+        let report = signup(&db, &input(&name, &email), defaults, &MockClock::new(now)).await?;
+        assert_eq!(report.role, user::ROLE_ADMIN);
+        let t = tenant::Entity::find_by_id(report.tenant_id).one(&db).await?.expect("tenant");
+        assert_eq!(t.created_at, now.fixed_offset());
+        assert!(report.api_key.starts_with("ak_"));
+        let key = apikey::Entity::find_by_id(report.api_key_id).one(&db).await?.expect("api key");
+        assert_eq!(key.user_id, report.user_id);
+        assert_ne!(key.key_hash, report.api_key);
+        let rl = ratelimit::Entity::find_by_id(report.rate_limit_id).one(&db).await?.expect("rate limit");
+        assert_eq!((rl.tenant_id, rl.requests_per_minute), (Some(report.tenant_id), 120));
+
+        // 租户名或邮箱已被占用时不写入任何数据
+        assert!(matches!(signup(&db, &input(&name, "other@example.com"), defaults, &SystemClock).await, Err(ServiceError::Conflict(_))));
+        let taken = format!("signup-{}", Uuid::new_v4().simple());
+        assert!(matches!(signup(&db, &input(&taken, &email), defaults, &SystemClock).await, Err(ServiceError::Conflict(_))));
+        assert!(tenant::Entity::find().filter(tenant::Column::Name.eq(taken)).one(&db).await?.is_none());
+        Ok(())
+    }
+}
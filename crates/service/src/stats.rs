@@ -0,0 +1,233 @@
+//! Aggregate gateway statistics for the admin dashboard (`GET /admin/stats`).
+//! - Computed from `request_log` (PostgreSQL aggregates, percentiles via `percentile_cont`).
+//! - Results are cached for a short TTL per `top` value so dashboards polling every few seconds
+//!   do not rescan a day of logs on each refresh.
+//! - Circuit breakers are inferred: a route counts as open when its failures since the last
+//!   success within the breaker window reach `route.circuit_breaker_threshold`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, DbBackend, FromQueryResult, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use common::time::{system_clock, SharedClock};
+
+use crate::errors::ServiceError;
+
+/// Window used to decide whether a breaker is currently open.
+const BREAKER_WINDOW: Duration = Duration::from_secs(300);
+pub const MAX_TOP_ROUTES: u32 = 100;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub requests: i64,
+    pub errors: i64,
+    pub error_rate: f64,
+    /// 429 responses
+    pub rate_limited: i64,
+}
+
+impl WindowStats {
+    pub(crate) fn new(requests: i64, errors: i64, rate_limited: i64) -> Self {
+        let error_rate = if requests > 0 { errors as f64 / requests as f64 } else { 0.0 };
+        Self { requests, errors, error_rate, rate_limited }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct RouteStats {
+    pub route_id: Uuid,
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub p95_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, FromQueryResult)]
+pub struct OpenBreaker {
+    pub route_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub consecutive_failures: i64,
+    pub threshold: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DashboardStats {
+    pub generated_at: DateTime<Utc>,
+    pub last_hour: WindowStats,
+    pub last_day: WindowStats,
+    /// Last hour, by request count
+    pub top_routes: Vec<RouteStats>,
+    /// Tenants with traffic in the last hour
+    pub active_tenants: i64,
+    pub open_circuit_breakers: Vec<OpenBreaker>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct WindowRow {
+    requests: i64,
+    errors: i64,
+    rate_limited: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct CountRow {
+    count: i64,
+}
+
+fn dbe(e: sea_orm::DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+fn stmt(sql: &str, values: Vec<sea_orm::Value>) -> Statement {
+    Statement::from_sql_and_values(DbBackend::Postgres, sql, values)
+}
+
+async fn window(db: &DatabaseConnection, since: DateTime<Utc>) -> Result<WindowStats, ServiceError> {
+    let row = WindowRow::find_by_statement(stmt(
+        "SELECT count(*)::bigint AS requests, \
+                count(*) FILTER (WHERE NOT success)::bigint AS errors, \
+                count(*) FILTER (WHERE status_code = 429)::bigint AS rate_limited \
+         FROM request_log WHERE timestamp >= $1",
+        vec![since.fixed_offset().into()],
+    ))
+    .one(db)
+    .await
+    .map_err(dbe)?;
+    Ok(row.map(|r| WindowStats::new(r.requests, r.errors, r.rate_limited)).unwrap_or_default())
+}
+
+/// Compute all dashboard statistics at `now`.
+pub async fn compute(db: &DatabaseConnection, now: DateTime<Utc>, top: u32) -> Result<DashboardStats, ServiceError> {
+    let hour_ago = now - chrono::Duration::hours(1);
+    let last_hour = window(db, hour_ago).await?;
+    let last_day = window(db, now - chrono::Duration::days(1)).await?;
+
+    let top_routes = RouteStats::find_by_statement(stmt(
+        "SELECT r.id AS route_id, r.tenant_id, r.method, r.path, \
+                count(*)::bigint AS requests, \
+                count(*) FILTER (WHERE NOT l.success)::bigint AS errors, \
+                percentile_cont(0.95) WITHIN GROUP (ORDER BY l.latency_ms) AS p95_ms, \
+                percentile_cont(0.99) WITHIN GROUP (ORDER BY l.latency_ms) AS p99_ms \
+         FROM request_log l JOIN route r ON r.id = l.route_id \
+         WHERE l.timestamp >= $1 \
+         GROUP BY r.id, r.tenant_id, r.method, r.path \
+         ORDER BY requests DESC LIMIT $2",
+        vec![hour_ago.fixed_offset().into(), (top.clamp(1, MAX_TOP_ROUTES) as i64).into()],
+    ))
+    .all(db)
+    .await
+    .map_err(dbe)?;
+
+    let active_tenants = CountRow::find_by_statement(stmt(
+        "SELECT count(DISTINCT r.tenant_id)::bigint AS count \
+         FROM request_log l JOIN route r ON r.id = l.route_id WHERE l.timestamp >= $1",
+        vec![hour_ago.fixed_offset().into()],
+    ))
+    .one(db)
+    .await
+    .map_err(dbe)?
+    .map(|r| r.count)
+    .unwrap_or(0);
+
+    let breaker_since = now - chrono::Duration::from_std(BREAKER_WINDOW).unwrap_or_default();
+    let open_circuit_breakers = OpenBreaker::find_by_statement(stmt(
+        "SELECT r.id AS route_id, r.method, r.path, count(*)::bigint AS consecutive_failures, \
+                r.circuit_breaker_threshold AS threshold \
+         FROM request_log l JOIN route r ON r.id = l.route_id \
+         WHERE l.timestamp >= $1 AND NOT l.success \
+           AND l.timestamp > COALESCE( \
+               (SELECT max(s.timestamp) FROM request_log s WHERE s.route_id = l.route_id AND s.success AND s.timestamp >= $1), \
+               '-infinity'::timestamptz) \
+         GROUP BY r.id, r.method, r.path, r.circuit_breaker_threshold \
+         HAVING r.circuit_breaker_threshold > 0 AND count(*) >= r.circuit_breaker_threshold \
+         ORDER BY consecutive_failures DESC",
+        vec![breaker_since.fixed_offset().into()],
+    ))
+    .all(db)
+    .await
+    .map_err(dbe)?;
+
+    Ok(DashboardStats { generated_at: now, last_hour, last_day, top_routes, active_tenants, open_circuit_breakers })
+}
+
+/// TTL cache in front of [`compute`].
+pub struct StatsService {
+    db: DatabaseConnection,
+    ttl: Duration,
+    cache: Mutex<HashMap<u32, (Instant, DashboardStats)>>,
+    clock: SharedClock,
+}
+
+impl StatsService {
+    pub fn new(db: DatabaseConnection, ttl: Duration) -> Self {
+        Self { db, ttl, cache: Mutex::new(HashMap::new()), clock: system_clock() }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn cached(&self, top: u32, now: Instant) -> Option<DashboardStats> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(&top).filter(|(at, _)| now.duration_since(*at) < self.ttl).map(|(_, s)| s.clone())
+    }
+
+    fn store(&self, top: u32, at: Instant, stats: DashboardStats) {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|_, (t, _)| at.duration_since(*t) < self.ttl);
+        cache.insert(top, (at, stats));
+    }
+
+    pub async fn get(&self, top: u32) -> Result<DashboardStats, ServiceError> {
+        let top = top.clamp(1, MAX_TOP_ROUTES);
+        if let Some(s) = self.cached(top, Instant::now()) {
+            return Ok(s);
+        }
+        let started = Instant::now();
+        let stats = compute(&self.db, self.clock.now(), top).await?;
+        self.store(top, started, stats.clone());
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_error_rate() {
+        assert_eq!(WindowStats::new(0, 0, 0).error_rate, 0.0);
+        let w = WindowStats::new(200, 10, 4);
+        assert_eq!(w.error_rate, 0.05);
+        assert_eq!(w.rate_limited, 4);
+    }
+
+    #[test]
+    fn cache_entries_expire_after_ttl() {
+        let db = sea_orm::DatabaseConnection::Disconnected;
+        let svc = StatsService::new(db, Duration::from_secs(30));
+        let stats = DashboardStats {
+            generated_at: Utc::now(),
+            last_hour: WindowStats::default(),
+            last_day: WindowStats::default(),
+            top_routes: Vec::new(),
+            active_tenants: 3,
+            open_circuit_breakers: Vec::new(),
+        };
+        let t0 = Instant::now();
+        svc.store(10, t0, stats.clone());
+        assert_eq!(svc.cached(10, t0 + Duration::from_secs(5)), Some(stats));
+        assert!(svc.cached(20, t0).is_none());
+        assert!(svc.cached(10, t0 + Duration::from_secs(31)).is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Store-level health reporting and metrics for file- and database-backed stores.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec};
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::errors::ServiceError;
+
+/// Env var declaring how many replicas share this deployment.
+pub const REPLICAS_ENV: &str = "API_PROXY_REPLICAS";
+
+pub(crate) static STORE_ENTRIES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!("api_proxy_store_entries", "Entries held by a file-backed store", &["store"])
+        .expect("register store_entries")
+});
+
+pub(crate) static STORE_LAST_SAVE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "api_proxy_store_last_save_timestamp_seconds",
+        "Unix time of the last successful save of a file-backed store",
+        &["store"]
+    )
+    .expect("register store_last_save")
+});
+
+pub(crate) static STORE_SAVE_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!("api_proxy_store_save_errors_total", "Failed saves of a file-backed store", &["store"])
+        .expect("register store_save_errors")
+});
+
+pub(crate) static STORE_WRITABLE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "api_proxy_store_writable",
+        "Whether the backing file of a store is writable (1) or not (0)",
+        &["store"]
+    )
+    .expect("register store_writable")
+});
+
+/// Point-in-time health of one store.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StoreHealth {
+    pub name: String,
+    pub path: String,
+    pub writable: bool,
+    pub entries: usize,
+    pub last_save_at: Option<DateTime<Utc>>,
+    pub save_errors: u64,
+    pub last_error: Option<String>,
+}
+
+impl StoreHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.writable
+    }
+}
+
+/// Write bookkeeping for database-backed stores, reported through the same [`StoreHealth`]
+/// fields and metrics as the JSON stores.
+#[derive(Debug, Default)]
+pub(crate) struct WriteTracker {
+    state: Mutex<(Option<DateTime<Utc>>, u64, Option<String>)>,
+}
+
+impl WriteTracker {
+    pub(crate) fn record<T>(&self, store: &str, res: Result<T, sea_orm::DbErr>) -> Result<T, ServiceError> {
+        let mut state = self.state.lock().expect("write tracker lock");
+        match res {
+            Ok(v) => {
+                let now = Utc::now();
+                state.0 = Some(now);
+                state.2 = None;
+                STORE_LAST_SAVE.with_label_values(&[store]).set(now.timestamp());
+                Ok(v)
+            }
+            Err(e) => {
+                error!(store, error = %e, "database store write failed");
+                state.1 += 1;
+                state.2 = Some(e.to_string());
+                STORE_SAVE_ERRORS.with_label_values(&[store]).inc();
+                Err(ServiceError::Db(e.to_string()))
+            }
+        }
+    }
+
+    /// `entries` is `None` when the database could not be queried
+    pub(crate) fn health(&self, store: &str, entries: Option<usize>) -> StoreHealth {
+        let writable = entries.is_some();
+        STORE_WRITABLE.with_label_values(&[store]).set(writable as i64);
+        if let Some(n) = entries {
+            STORE_ENTRIES.with_label_values(&[store]).set(n as i64);
+        }
+        let state = self.state.lock().expect("write tracker lock");
+        StoreHealth {
+            name: store.to_string(),
+            path: format!("database:{}", store),
+            writable,
+            entries: entries.unwrap_or_default(),
+            last_save_at: state.0,
+            save_errors: state.1,
+            last_error: state.2.clone(),
+        }
+    }
+}
+
+/// Replica count declared via `API_PROXY_REPLICAS` (defaults to 1).
+pub fn declared_replicas() -> u32 {
+    std::env::var(REPLICAS_ENV).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(1)
+}
+
+/// JSON stores are local to one process: with several replicas each one sees a different copy.
+/// Returns whether a warning was emitted.
+pub fn warn_if_multi_replica(stores: &[&str]) -> bool {
+    let replicas = declared_replicas();
+    if replicas > 1 {
+        warn!(
+            replicas,
+            stores = ?stores,
+            "file-backed JSON stores are not shared between replicas; writes on one replica are invisible to others"
+        );
+        return true;
+    }
+    false
+}
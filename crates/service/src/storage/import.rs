@@ -0,0 +1,78 @@
+//! 切换到数据库后端时，一次性导入原 JSON 文件存储中的数据。
+//! 仅在目标表为空且文件存在时执行，重复启动不会重复导入。
+
+use std::path::Path;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::admin::{api_mgmt_store::ApiManagementStore, kv_store::AdminKvStore};
+use crate::db::{admin_kv_store::DbApiKeysStore, api_management_store::DbApiStore};
+use crate::errors::ServiceError;
+use crate::file::{admin_kv_store::ApiKeysStore, api_management::ApiStore};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub api_keys: usize,
+    pub apis: usize,
+}
+
+pub async fn import_json_stores(
+    api_keys_path: &str,
+    apis_path: &str,
+    keys: &DbApiKeysStore,
+    apis: &DbApiStore,
+) -> Result<ImportReport, ServiceError> {
+    let mut report = ImportReport::default();
+    if Path::new(api_keys_path).exists() && AdminKvStore::list(keys).await.is_empty() {
+        for (user, key) in ApiKeysStore::new(api_keys_path).await?.list().await {
+            keys.set(user, key).await?;
+            report.api_keys += 1;
+        }
+    }
+    if Path::new(apis_path).exists() && ApiManagementStore::list(apis).await.is_empty() {
+        for rec in ApiStore::new(apis_path).await?.list().await {
+            apis.insert_existing(rec).await?;
+            report.apis += 1;
+        }
+    }
+    if report != ImportReport::default() {
+        info!(api_keys = report.api_keys, apis = report.apis, api_keys_path, apis_path, "imported json stores into database");
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::api_management::{ApiRecordInput, AuthInfo};
+    use crate::test_support::get_db;
+
+    #[tokio::test]
+    async fn imports_only_into_empty_tables() -> Result<(), anyhow::Error> {
+        if std::env::var("SKIP_DB_TESTS").is_ok() { return Ok(()); }
+        let (db, _schema) = get_db().await?;
+        let (keys, apis) = (DbApiKeysStore::new(db.clone()), DbApiStore::new(db));
+        if !AdminKvStore::list(keys.as_ref()).await.is_empty() || !ApiManagementStore::list(apis.as_ref()).await.is_empty() {
+            // 共享数据库中已有数据时无法验证“空表”语义
+            return Ok(());
+        }
+        let dir = std::env::temp_dir().join(format!("svc_import_{}", uuid::Uuid::new_v4()));
+        let (keys_path, apis_path) = (dir.join("api_keys.json"), dir.join("apis.json"));
+        ApiKeysStore::new(&keys_path).await?.set("alice".into(), "k1".into()).await?;
+        let rec = ApiStore::new(&apis_path)
+            .await?
+            .create(ApiRecordInput { endpoint_url: "/imp".into(), method: "GET".into(), forward_target: "http://127.0.0.1:1".into(), auth: AuthInfo { require_api_key: false } })
+            .await?;
+
+        let (k, a) = (keys_path.to_str().unwrap(), apis_path.to_str().unwrap());
+        assert_eq!(import_json_stores(k, a, &keys, &apis).await?, ImportReport { api_keys: 1, apis: 1 });
+        assert_eq!(import_json_stores(k, a, &keys, &apis).await?, ImportReport::default());
+        assert_eq!(apis.get(rec.id).await.map(|r| r.endpoint_url), Some("/imp".to_string()));
+
+        keys.delete("alice").await?;
+        apis.delete(rec.id).await?;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
-use std::{collections::HashMap, hash::Hash, path::PathBuf, sync::Arc};
-use tokio::{fs, sync::RwLock};
+use std::{collections::HashMap, hash::Hash, path::PathBuf, sync::{Arc, Mutex}};
+use chrono::{DateTime, Utc};
+use tokio::{fs, io::AsyncWriteExt, sync::RwLock};
+use tracing::error;
 
 use crate::errors::ServiceError;
+use crate::storage::health::{StoreHealth, STORE_ENTRIES, STORE_LAST_SAVE, STORE_SAVE_ERRORS, STORE_WRITABLE};
+
+#[derive(Debug, Default)]
+struct SaveState {
+    last_save_at: Option<DateTime<Utc>>,
+    save_errors: u64,
+    last_error: Option<String>,
+}
 
 /// Generic JSON file-backed key-value map store.
 ///
@@ -11,6 +21,9 @@ use crate::errors::ServiceError;
 pub struct JsonMapStore<K, V> {
     inner: Arc<RwLock<HashMap<K, V>>>,
     file_path: PathBuf,
+    /// Metrics label; defaults to the file stem
+    name: String,
+    save_state: Arc<Mutex<SaveState>>,
 }
 
 impl<K, V> JsonMapStore<K, V>
@@ -36,14 +49,74 @@ where
             }
         };
 
-        Ok(Arc::new(Self { inner: Arc::new(RwLock::new(map)), file_path }))
+        let name = file_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "store".into());
+        STORE_ENTRIES.with_label_values(&[&name]).set(map.len() as i64);
+        Ok(Arc::new(Self { inner: Arc::new(RwLock::new(map)), file_path, name, save_state: Arc::default() }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     async fn save(&self) -> Result<(), ServiceError> {
         let map = self.inner.read().await;
+        let entries = map.len();
         let data = serde_json::to_vec(&*map).map_err(|e| ServiceError::Db(e.to_string()))?;
-        fs::write(&self.file_path, data).await.map_err(|e| ServiceError::Db(e.to_string()))?;
-        Ok(())
+        let res = fs::write(&self.file_path, data).await;
+        drop(map);
+
+        let mut state = self.save_state.lock().expect("save state lock");
+        match res {
+            Ok(()) => {
+                let now = Utc::now();
+                state.last_save_at = Some(now);
+                state.last_error = None;
+                STORE_LAST_SAVE.with_label_values(&[&self.name]).set(now.timestamp());
+                STORE_ENTRIES.with_label_values(&[&self.name]).set(entries as i64);
+                Ok(())
+            }
+            Err(e) => {
+                // 保存失败不再静默：记录日志与指标，/readyz 可见
+                error!(store = %self.name, path = %self.file_path.display(), error = %e, "json store save failed");
+                state.save_errors += 1;
+                state.last_error = Some(e.to_string());
+                STORE_SAVE_ERRORS.with_label_values(&[&self.name]).inc();
+                Err(ServiceError::Db(format!("save {} failed: {}", self.name, e)))
+            }
+        }
+    }
+
+    /// Probe that the backing directory accepts writes.
+    async fn probe_writable(&self) -> bool {
+        let mut probe = self.file_path.clone().into_os_string();
+        probe.push(".probe");
+        let probe = PathBuf::from(probe);
+        let ok = async {
+            let mut f = fs::File::create(&probe).await?;
+            f.write_all(b"ok").await?;
+            f.sync_all().await
+        }
+        .await
+        .is_ok();
+        let _ = fs::remove_file(&probe).await;
+        ok
+    }
+
+    /// Writability, entry count and save history of this store.
+    pub async fn health(&self) -> StoreHealth {
+        let writable = self.probe_writable().await;
+        STORE_WRITABLE.with_label_values(&[&self.name]).set(writable as i64);
+        let entries = self.inner.read().await.len();
+        let state = self.save_state.lock().expect("save state lock");
+        StoreHealth {
+            name: self.name.clone(),
+            path: self.file_path.display().to_string(),
+            writable,
+            entries,
+            last_save_at: state.last_save_at,
+            save_errors: state.save_errors,
+            last_error: state.last_error.clone(),
+        }
     }
 
     /// List all entries as `(key, value)` pairs.
@@ -132,4 +205,27 @@ mod tests {
         let _ = tokio::fs::remove_file(&tmp).await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn health_reports_entries_and_save_failures() -> Result<(), anyhow::Error> {
+        let dir = std::env::temp_dir().join(format!("json_map_store_health_{}", uuid::Uuid::new_v4()));
+        let store = JsonMapStore::<String, String>::new(dir.join("keys.json")).await?;
+        store.insert("a".into(), "1".into()).await?;
+
+        let h = store.health().await;
+        assert_eq!(h.name, "keys");
+        assert!(h.writable);
+        assert_eq!(h.entries, 1);
+        assert!(h.last_save_at.is_some());
+        assert_eq!(h.save_errors, 0);
+
+        // 目录被删除后保存失败应可见
+        tokio::fs::remove_dir_all(&dir).await?;
+        assert!(store.insert("b".into(), "2".into()).await.is_err());
+        let h = store.health().await;
+        assert!(!h.writable);
+        assert_eq!(h.save_errors, 1);
+        assert!(h.last_error.is_some());
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -3,4 +3,6 @@
 //! Contains reusable file-backed stores and helpers to avoid duplication
 //! across services that persist small maps as JSON.
 
-pub mod json_map_store;
\ No newline at end of file
+pub mod json_map_store;
+pub mod health;
+pub mod import;
\ No newline at end of file
@@ -1,30 +1,12 @@
 #![cfg(test)]
-use tokio::sync::OnceCell;
 use sea_orm::DatabaseConnection;
-use migration::MigratorTrait;
-use models::db::{connect_with_config, DatabaseConfig};
+use models::testing::TestDb;
 
-// Ensure migrations run only once across the entire test process
-static MIGRATED: OnceCell<()> = OnceCell::const_new();
-
-pub async fn get_db() -> Result<DatabaseConnection, anyhow::Error> {
-    // Run migrations exactly once, with a throwaway connection
-    MIGRATED
-        .get_or_init(|| async {
-            let mut cfg = DatabaseConfig::from_file().unwrap_or_else(DatabaseConfig::from_env);
-            cfg.max_connections = cfg.max_connections.max(10);
-            cfg.min_connections = cfg.min_connections.min(1);
-            let db = connect_with_config(&cfg).await.expect("connect db for migration");
-            migration::Migrator::up(&db, None).await.expect("migrate up");
-            drop(db);
-        })
-        .await;
-
-    // Return a fresh connection for the current test's runtime
-    let mut cfg = DatabaseConfig::from_file().unwrap_or_else(DatabaseConfig::from_env);
-    cfg.max_connections = cfg.max_connections.max(20);
-    cfg.min_connections = cfg.min_connections.min(1);
-    cfg.acquire_timeout = std::time::Duration::from_secs(10);
-    let db = connect_with_config(&cfg).await?;
-    Ok(db)
-}
\ No newline at end of file
+/// 每个测试独立的 schema（迁移在其中执行），测试之间不共享表数据。
+/// 返回的 `TestDb` 需在测试结束前保持存活，释放时删除 schema。
+pub async fn get_db() -> Result<(DatabaseConnection, TestDb), anyhow::Error> {
+    // 测试上游（echo）在环回地址上，默认的出站策略会拒绝
+    common::egress::install(common::egress::EgressPolicy::allow_all());
+    let test_db = TestDb::create().await?;
+    Ok((test_db.db.clone(), test_db))
+}
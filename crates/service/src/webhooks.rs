@@ -0,0 +1,594 @@
+//! Tenant webhooks for gateway events.
+//! - Tenants subscribe a URL to event names; `emit` writes one `webhook_delivery` row per matching subscription.
+//! - A background worker posts due deliveries, signing `"{timestamp}.{body}"` with HMAC-SHA256 of the
+//!   subscription secret (`X-Webhook-Signature: sha256=<hex>`), and retries failures with exponential backoff.
+//! - Control-plane changes from the event bus are emitted as `config.changed` for the owning tenant.
+//! - Subscription URLs must pass the process egress policy, checked with DNS resolution on create/update and
+//!   again before every delivery; the delivery client re-checks resolved addresses and redirects.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use common::egress::EgressPolicy;
+use common::http_client::{HttpClientConfig, HttpClientFactory};
+use common::pagination::{Page, Pagination};
+use common::time::{system_clock, SharedClock};
+use models::encrypted::EncryptedString;
+use models::{apikey, proxy_api, ratelimit, route, user, webhook, webhook_delivery};
+
+use crate::errors::ServiceError;
+use crate::events::{ConfigEvent, EventBus};
+
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+pub static WEBHOOK_DELIVERIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "api_proxy_webhook_deliveries_total",
+        "Webhook delivery attempts by event and result (delivered/retry/failed)",
+        &["event", "result"]
+    )
+    .expect("register webhook_deliveries_total")
+});
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Events tenants can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "circuit_breaker.opened")]
+    CircuitBreakerOpened,
+    #[serde(rename = "quota.exceeded")]
+    QuotaExceeded,
+    #[serde(rename = "upstream.unhealthy")]
+    UpstreamUnhealthy,
+    #[serde(rename = "config.changed")]
+    ConfigChanged,
+    #[serde(rename = "api_key.expiring")]
+    KeyExpiring,
+    #[serde(rename = "certificate.expiring")]
+    CertificateExpiring,
+    #[serde(rename = "signing_key.expiring")]
+    SigningKeyExpiring,
+    #[serde(rename = "report.generated")]
+    ReportGenerated,
+    #[serde(rename = "user.verification_requested")]
+    UserVerificationRequested,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 9] = [
+        WebhookEvent::CircuitBreakerOpened,
+        WebhookEvent::QuotaExceeded,
+        WebhookEvent::UpstreamUnhealthy,
+        WebhookEvent::ConfigChanged,
+        WebhookEvent::KeyExpiring,
+        WebhookEvent::CertificateExpiring,
+        WebhookEvent::SigningKeyExpiring,
+        WebhookEvent::ReportGenerated,
+        WebhookEvent::UserVerificationRequested,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::CircuitBreakerOpened => "circuit_breaker.opened",
+            WebhookEvent::QuotaExceeded => "quota.exceeded",
+            WebhookEvent::UpstreamUnhealthy => "upstream.unhealthy",
+            WebhookEvent::ConfigChanged => "config.changed",
+            WebhookEvent::KeyExpiring => "api_key.expiring",
+            WebhookEvent::CertificateExpiring => "certificate.expiring",
+            WebhookEvent::SigningKeyExpiring => "signing_key.expiring",
+            WebhookEvent::ReportGenerated => "report.generated",
+            WebhookEvent::UserVerificationRequested => "user.verification_requested",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s)
+    }
+}
+
+/// Delivery worker settings.
+#[derive(Debug, Clone)]
+pub struct DeliveryOptions {
+    pub poll_interval: Duration,
+    pub batch_size: u64,
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for DeliveryOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            batch_size: 100,
+            max_attempts: 8,
+            initial_backoff: Duration::from_secs(10),
+            max_backoff: Duration::from_secs(3600),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl DeliveryOptions {
+    /// Delay before the next attempt after `attempts` failures (doubling, capped).
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Hex HMAC-SHA256 over `"{timestamp}.{body}"`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateWebhookInput {
+    pub tenant_id: Uuid,
+    pub url: String,
+    pub events: Vec<String>,
+    /// Generated when omitted
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateWebhookInput {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+    /// Rotate the signing secret
+    pub secret: Option<String>,
+}
+
+/// Creation result; the secret is only returned here.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: webhook::Model,
+    pub secret: String,
+}
+
+fn validate_url(url: &str) -> Result<(), ServiceError> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation("url must start with http:// or https://".into()))
+    }
+}
+
+/// Scheme check plus the egress allowlist / private-range check on the resolved addresses.
+async fn check_url(egress: &EgressPolicy, url: &str) -> Result<(), ServiceError> {
+    validate_url(url)?;
+    egress.check_url_resolved(url).await.map_err(|e| ServiceError::Validation(format!("url: {}", e)))
+}
+
+/// Normalized comma separated event list.
+fn validate_events(events: &[String]) -> Result<String, ServiceError> {
+    if events.is_empty() {
+        return Err(ServiceError::Validation("at least one event is required".into()));
+    }
+    let mut seen = Vec::new();
+    for e in events {
+        let parsed = WebhookEvent::parse(e.trim()).ok_or_else(|| {
+            let known: Vec<&str> = WebhookEvent::ALL.iter().map(|e| e.as_str()).collect();
+            ServiceError::Validation(format!("unknown event {}; expected one of {}", e, known.join(", ")))
+        })?;
+        if !seen.contains(&parsed.as_str()) {
+            seen.push(parsed.as_str());
+        }
+    }
+    Ok(seen.join(","))
+}
+
+fn validate_secret(secret: &str) -> Result<(), ServiceError> {
+    if (16..=128).contains(&secret.len()) {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation("secret must be 16-128 characters".into()))
+    }
+}
+
+fn generate_secret() -> String {
+    let s: String = rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+    format!("whsec_{}", s)
+}
+
+fn dbe(e: DbErr) -> ServiceError {
+    ServiceError::Db(e.to_string())
+}
+
+pub struct WebhookService {
+    db: DatabaseConnection,
+    opts: DeliveryOptions,
+    client: reqwest::Client,
+    egress: Arc<EgressPolicy>,
+    clock: SharedClock,
+}
+
+impl WebhookService {
+    /// Uses the process egress policy ([`common::egress::global`]).
+    pub fn new(db: DatabaseConnection, opts: DeliveryOptions) -> Result<Self, ServiceError> {
+        let egress = common::egress::global();
+        let client = HttpClientFactory::new(HttpClientConfig { timeout_ms: opts.timeout.as_millis() as u64, ..Default::default() })
+            .with_egress(Arc::clone(&egress))
+            .build()
+            .map_err(|e| ServiceError::Validation(format!("webhook client: {}", e)))?;
+        Ok(Self { db, opts, client, egress, clock: system_clock() })
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub async fn create(&self, input: CreateWebhookInput) -> Result<CreatedWebhook, ServiceError> {
+        check_url(&self.egress, &input.url).await?;
+        let events = validate_events(&input.events)?;
+        let secret = match input.secret {
+            Some(s) => {
+                validate_secret(&s)?;
+                s
+            }
+            None => generate_secret(),
+        };
+        let now = self.clock.now_fixed();
+        let am = webhook::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            tenant_id: Set(input.tenant_id),
+            url: Set(input.url),
+            secret: Set(EncryptedString::new(secret.clone())?),
+            events: Set(events),
+            enabled: Set(true),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+        let saved = am.insert(&self.db).await.map_err(dbe)?;
+        info!(webhook_id = %saved.id, tenant_id = %saved.tenant_id, events = %saved.events, "webhook_created");
+        Ok(CreatedWebhook { webhook: saved, secret })
+    }
+
+    /// Newest first; optionally restricted to one tenant.
+    pub async fn list(&self, tenant_id: Option<Uuid>, opts: Pagination) -> Result<Page<webhook::Model>, ServiceError> {
+        let mut select = webhook::Entity::find();
+        if let Some(t) = tenant_id {
+            select = select.filter(webhook::Column::TenantId.eq(t));
+        }
+        crate::db::fetch_keyset_page(&self.db, select, webhook::Column::CreatedAt, webhook::Column::Id, opts, |m| {
+            crate::db::anchor_of(&m.created_at, m.id)
+        })
+        .await
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<webhook::Model, ServiceError> {
+        webhook::Entity::find_by_id(id).one(&self.db).await.map_err(dbe)?.ok_or_else(|| ServiceError::not_found("webhook"))
+    }
+
+    pub async fn update(&self, id: Uuid, input: UpdateWebhookInput) -> Result<webhook::Model, ServiceError> {
+        let mut am = self.get(id).await?.into_active_model();
+        if let Some(url) = input.url {
+            check_url(&self.egress, &url).await?;
+            am.url = Set(url);
+        }
+        if let Some(events) = input.events {
+            am.events = Set(validate_events(&events)?);
+        }
+        if let Some(enabled) = input.enabled {
+            am.enabled = Set(enabled);
+        }
+        if let Some(secret) = input.secret {
+            validate_secret(&secret)?;
+            am.secret = Set(EncryptedString::new(secret)?);
+        }
+        am.updated_at = Set(self.clock.now_fixed());
+        am.update(&self.db).await.map_err(dbe)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), ServiceError> {
+        let res = webhook::Entity::delete_by_id(id).exec(&self.db).await.map_err(dbe)?;
+        if res.rows_affected == 0 {
+            return Err(ServiceError::not_found("webhook"));
+        }
+        Ok(())
+    }
+
+    /// Delivery log of one webhook, newest first.
+    pub async fn deliveries(&self, webhook_id: Uuid, opts: Pagination) -> Result<Page<webhook_delivery::Model>, ServiceError> {
+        let select = webhook_delivery::Entity::find().filter(webhook_delivery::Column::WebhookId.eq(webhook_id));
+        let (created_at, id) = (webhook_delivery::Column::CreatedAt, webhook_delivery::Column::Id);
+        crate::db::fetch_keyset_page(&self.db, select, created_at, id, opts, |m| crate::db::anchor_of(&m.created_at, m.id)).await
+    }
+
+    /// Queue `event` for every enabled subscription of the tenant; returns the number of deliveries queued.
+    pub async fn emit(&self, tenant_id: Uuid, event: WebhookEvent, data: Value) -> Result<usize, ServiceError> {
+        let hooks: Vec<webhook::Model> = webhook::Entity::find()
+            .filter(webhook::Column::TenantId.eq(tenant_id))
+            .filter(webhook::Column::Enabled.eq(true))
+            .all(&self.db)
+            .await
+            .map_err(dbe)?
+            .into_iter()
+            .filter(|h| h.subscribes_to(event.as_str()))
+            .collect();
+        if hooks.is_empty() {
+            return Ok(0);
+        }
+        let now = self.clock.now_fixed();
+        let rows: Vec<webhook_delivery::ActiveModel> = hooks
+            .iter()
+            .map(|h| {
+                let id = Uuid::new_v4();
+                let payload = json!({
+                    "id": id,
+                    "event": event.as_str(),
+                    "tenant_id": tenant_id,
+                    "created_at": now,
+                    "data": data,
+                });
+                webhook_delivery::ActiveModel {
+                    id: Set(id),
+                    webhook_id: Set(h.id),
+                    event: Set(event.as_str().to_string()),
+                    payload: Set(payload.to_string()),
+                    status: Set(webhook_delivery::STATUS_PENDING.to_string()),
+                    attempts: Set(0),
+                    next_attempt_at: Set(now),
+                    response_status: Set(None),
+                    last_error: Set(None),
+                    created_at: Set(now),
+                    delivered_at: Set(None),
+                }
+            })
+            .collect();
+        let queued = rows.len();
+        webhook_delivery::Entity::insert_many(rows).exec(&self.db).await.map_err(dbe)?;
+        info!(tenant_id = %tenant_id, event = event.as_str(), queued, "webhook_event_queued");
+        Ok(queued)
+    }
+
+    /// Tenants with at least one enabled subscription to `event`.
+    pub async fn subscribed_tenants(&self, event: WebhookEvent) -> Result<Vec<Uuid>, ServiceError> {
+        let hooks = webhook::Entity::find().filter(webhook::Column::Enabled.eq(true)).all(&self.db).await.map_err(dbe)?;
+        let mut tenants: Vec<Uuid> = hooks.into_iter().filter(|h| h.subscribes_to(event.as_str())).map(|h| h.tenant_id).collect();
+        tenants.sort();
+        tenants.dedup();
+        Ok(tenants)
+    }
+
+    /// Post one delivery; returns the HTTP status or an error message.
+    async fn post(&self, hook: &webhook::Model, d: &webhook_delivery::Model) -> Result<u16, (Option<u16>, String)> {
+        // 注册后 DNS 或策略可能已变化
+        check_url(&self.egress, &hook.url).await.map_err(|e| (None, e.to_string()))?;
+        let ts = self.clock.now().timestamp();
+        let resp = self
+            .client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &d.event)
+            .header(DELIVERY_HEADER, d.id.to_string())
+            .header(TIMESTAMP_HEADER, ts.to_string())
+            .header(SIGNATURE_HEADER, format!("sha256={}", sign(&hook.secret, ts, &d.payload)))
+            .body(d.payload.clone())
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+        let status = resp.status();
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err((Some(status.as_u16()), format!("subscriber responded {}", status)))
+        }
+    }
+
+    /// Attempt every due pending delivery once; returns the number delivered.
+    pub async fn deliver_due(&self) -> Result<usize, ServiceError> {
+        let now = self.clock.now_fixed();
+        let due = webhook_delivery::Entity::find()
+            .filter(webhook_delivery::Column::Status.eq(webhook_delivery::STATUS_PENDING))
+            .filter(webhook_delivery::Column::NextAttemptAt.lte(now))
+            .order_by_asc(webhook_delivery::Column::NextAttemptAt)
+            .limit(self.opts.batch_size.max(1))
+            .all(&self.db)
+            .await
+            .map_err(dbe)?;
+        if due.is_empty() {
+            return Ok(0);
+        }
+        let ids: HashSet<Uuid> = due.iter().map(|d| d.webhook_id).collect();
+        let hooks = webhook::Entity::find().filter(webhook::Column::Id.is_in(ids)).all(&self.db).await.map_err(dbe)?;
+
+        let mut delivered = 0;
+        for d in due {
+            let hook = hooks.iter().find(|h| h.id == d.webhook_id && h.enabled);
+            let attempts = d.attempts + 1;
+            let mut am = d.clone().into_active_model();
+            am.attempts = Set(attempts);
+            let result = match hook {
+                Some(h) => self.post(h, &d).await,
+                None => Err((None, "webhook disabled".to_string())),
+            };
+            let outcome = match result {
+                Ok(status) => {
+                    am.status = Set(webhook_delivery::STATUS_DELIVERED.to_string());
+                    am.response_status = Set(Some(status as i32));
+                    am.last_error = Set(None);
+                    am.delivered_at = Set(Some(self.clock.now_fixed()));
+                    delivered += 1;
+                    "delivered"
+                }
+                Err((status, error)) => {
+                    am.response_status = Set(status.map(i32::from));
+                    am.last_error = Set(Some(common::redaction::global().redact_text(&error)));
+                    if hook.is_none() || attempts as u32 >= self.opts.max_attempts {
+                        am.status = Set(webhook_delivery::STATUS_FAILED.to_string());
+                        warn!(delivery_id = %d.id, webhook_id = %d.webhook_id, attempts, error = %error, "webhook_delivery_failed");
+                        "failed"
+                    } else {
+                        let delay = chrono::Duration::from_std(self.opts.backoff(attempts as u32)).unwrap_or_default();
+                        am.next_attempt_at = Set(self.clock.now_fixed() + delay);
+                        "retry"
+                    }
+                }
+            };
+            WEBHOOK_DELIVERIES_TOTAL.with_label_values(&[&d.event, outcome]).inc();
+            am.update(&self.db).await.map_err(dbe)?;
+        }
+        Ok(delivered)
+    }
+
+    /// Poll due deliveries every `poll_interval`.
+    pub fn spawn_worker(self: &Arc<Self>) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.opts.poll_interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = this.deliver_due().await {
+                    warn!(error = %e, "webhook_delivery_poll_failed");
+                }
+            }
+        })
+    }
+
+    /// Tenants owning the entity a config event touched.
+    async fn tenants_of(&self, event: &ConfigEvent) -> Result<Vec<Uuid>, ServiceError> {
+        let ids = match event {
+            ConfigEvent::TenantChanged { id } => vec![*id],
+            ConfigEvent::ProxyApiChanged { id } => {
+                proxy_api::Entity::find_by_id(*id).one(&self.db).await.map_err(dbe)?.map(|p| p.tenant_id).into_iter().collect()
+            }
+            ConfigEvent::RouteCutover { id, .. } | ConfigEvent::RouteChanged { id } => {
+                route::Entity::find_by_id(*id).one(&self.db).await.map_err(dbe)?.map(|r| r.tenant_id).into_iter().collect()
+            }
+            ConfigEvent::ApiKeyChanged { id } => match apikey::Entity::find_by_id(*id).one(&self.db).await.map_err(dbe)? {
+                Some(k) => user::Entity::find_by_id(k.user_id).one(&self.db).await.map_err(dbe)?.map(|u| u.tenant_id).into_iter().collect(),
+                None => Vec::new(),
+            },
+            ConfigEvent::RateLimitChanged { id } => {
+                ratelimit::Entity::find_by_id(*id).one(&self.db).await.map_err(dbe)?.and_then(|r| r.tenant_id).into_iter().collect()
+            }
+            // 上游可被多个租户的路由引用
+            ConfigEvent::UpstreamChanged { id } => route::Entity::find()
+                .select_only()
+                .column(route::Column::TenantId)
+                .filter(route::Column::UpstreamId.eq(*id))
+                .distinct()
+                .into_tuple()
+                .all(&self.db)
+                .await
+                .map_err(dbe)?,
+            ConfigEvent::CacheFlushed => Vec::new(),
+        };
+        Ok(ids)
+    }
+
+    /// Emit `config.changed` for every published control-plane event.
+    pub fn spawn_config_listener(self: &Arc<Self>, bus: &EventBus) -> JoinHandle<()> {
+        let this = Arc::clone(self);
+        let mut rx = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(rev) => {
+                        let data = json!({ "revision": rev.revision, "kind": rev.event.kind(), "entity_id": rev.event.entity_id() });
+                        let tenants = match this.tenants_of(&rev.event).await {
+                            Ok(t) => t,
+                            Err(e) => {
+                                warn!(revision = rev.revision, error = %e, "webhook_config_event_lookup_failed");
+                                continue;
+                            }
+                        };
+                        for tenant_id in tenants {
+                            if let Err(e) = this.emit(tenant_id, WebhookEvent::ConfigChanged, data.clone()).await {
+                                warn!(revision = rev.revision, tenant_id = %tenant_id, error = %e, "webhook_emit_failed");
+                            }
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => warn!(skipped = n, "webhook_config_listener_lagged"),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+/// Subscriber-side verification helper (also used by tests).
+pub fn verify(secret: &str, timestamp: i64, body: &str, signature_header: &str, now: DateTime<Utc>, tolerance: Duration) -> bool {
+    let fresh = (now.timestamp() - timestamp).unsigned_abs() <= tolerance.as_secs();
+    let expected = format!("sha256={}", sign(secret, timestamp, body));
+    fresh && common::crypto::constant_time_eq(expected.as_bytes(), signature_header.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_stable_and_verifiable() {
+        let sig = sign("secret", 1_700_000_000, "{}");
+        assert_eq!(sig.len(), 64);
+        assert_eq!(sig, sign("secret", 1_700_000_000, "{}"));
+        assert_ne!(sig, sign("other", 1_700_000_000, "{}"));
+        assert_ne!(sig, sign("secret", 1_700_000_001, "{}"));
+
+        let now = DateTime::from_timestamp(1_700_000_100, 0).unwrap();
+        let header = format!("sha256={}", sig);
+        assert!(verify("secret", 1_700_000_000, "{}", &header, now, Duration::from_secs(300)));
+        assert!(!verify("secret", 1_700_000_000, "{\"x\":1}", &header, now, Duration::from_secs(300)));
+        assert!(!verify("secret", 1_700_000_000, "{}", &header, now, Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let opts = DeliveryOptions { initial_backoff: Duration::from_secs(10), max_backoff: Duration::from_secs(60), ..Default::default() };
+        let delays: Vec<u64> = (1..=5).map(|a| opts.backoff(a).as_secs()).collect();
+        assert_eq!(delays, vec![10, 20, 40, 60, 60]);
+        assert_eq!(opts.backoff(u32::MAX).as_secs(), 60);
+    }
+
+    #[test]
+    fn event_validation() {
+        let events = vec!["config.changed".to_string(), "circuit_breaker.opened".into(), "config.changed".into()];
+        assert_eq!(validate_events(&events).unwrap(), "config.changed,circuit_breaker.opened");
+        assert!(validate_events(&[]).is_err());
+        assert!(validate_events(&["route.deleted".to_string()]).unwrap_err().to_string().contains("unknown event"));
+        assert_eq!(serde_json::to_value(WebhookEvent::KeyExpiring).unwrap(), "api_key.expiring");
+        assert!(validate_url("ftp://x").is_err());
+        assert!(generate_secret().starts_with("whsec_"));
+    }
+
+    #[tokio::test]
+    async fn url_must_pass_egress_policy() {
+        let egress = EgressPolicy::deny_private();
+        for url in ["http://169.254.169.254/latest/meta-data", "http://127.0.0.1:9000/hook", "http://localhost:9000/hook"] {
+            assert!(check_url(&egress, url).await.is_err(), "{}", url);
+        }
+        assert!(check_url(&egress, "https://93.184.216.34/hook").await.is_ok());
+        assert!(check_url(&EgressPolicy::allow_all(), "http://127.0.0.1:9000/hook").await.is_ok());
+    }
+}